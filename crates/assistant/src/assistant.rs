@@ -18,10 +18,11 @@ use client::{proto, Client};
 use command_palette_hooks::CommandPaletteFilter;
 pub(crate) use completion_provider::*;
 pub(crate) use context_store::*;
+use fs::Fs;
 use gpui::{actions, AppContext, Global, SharedString, UpdateGlobal};
 pub(crate) use inline_assistant::*;
 pub(crate) use model_selector::*;
-use rustdoc::RustdocStore;
+use rustdoc::{DocProviderRegistry, RustdocSettings, RustdocStore};
 use semantic_index::{CloudEmbeddingProvider, SemanticIndex};
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
@@ -34,7 +35,7 @@ use std::{
     sync::Arc,
 };
 pub(crate) use streaming_diff::*;
-use util::paths::EMBEDDINGS_DIR;
+use util::paths::{EMBEDDINGS_DIR, RUSTDOC_INDEX_DIR};
 
 actions!(
     assistant,
@@ -262,7 +263,7 @@ impl Assistant {
     }
 }
 
-pub fn init(client: Arc<Client>, cx: &mut AppContext) {
+pub fn init(fs: Arc<dyn Fs>, client: Arc<Client>, cx: &mut AppContext) {
     cx.set_global(Assistant::default());
     AssistantSettings::register(cx);
 
@@ -287,7 +288,13 @@ pub fn init(client: Arc<Client>, cx: &mut AppContext) {
     register_slash_commands(cx);
     assistant_panel::init(cx);
     inline_assistant::init(client.telemetry().clone(), cx);
-    RustdocStore::init_global(cx);
+    RustdocSettings::register(cx);
+    RustdocStore::init_global(fs, RUSTDOC_INDEX_DIR.clone(), cx);
+    let rustdoc_store = RustdocStore::global(cx);
+    let rustdoc_settings = RustdocSettings::get_global(cx);
+    rustdoc_store.set_memory_budget(rustdoc_settings.index_memory_budget());
+    rustdoc_store.set_reindex_changed_local_docs(rustdoc_settings.reindex_changed_local_docs);
+    DocProviderRegistry::init_global(cx);
 
     CommandPaletteFilter::update_global(cx, |filter, _cx| {
         filter.hide_namespace(Assistant::NAMESPACE);
@@ -302,6 +309,10 @@ pub fn init(client: Arc<Client>, cx: &mut AppContext) {
             let settings = AssistantSettings::get_global(cx);
             assistant.set_enabled(settings.enabled, cx);
         });
+        let rustdoc_store = RustdocStore::global(cx);
+        let rustdoc_settings = RustdocSettings::get_global(cx);
+        rustdoc_store.set_memory_budget(rustdoc_settings.index_memory_budget());
+        rustdoc_store.set_reindex_changed_local_docs(rustdoc_settings.reindex_changed_local_docs);
     })
     .detach();
 }
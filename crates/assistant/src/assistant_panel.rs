@@ -25,6 +25,7 @@ use editor::{
 use editor::{display_map::FlapId, FoldPlaceholder};
 use file_icons::FileIcons;
 use fs::Fs;
+use futures::channel::mpsc;
 use futures::future::Shared;
 use futures::{FutureExt, StreamExt};
 use gpui::{
@@ -41,6 +42,7 @@ use language::{
 use multi_buffer::MultiBufferRow;
 use picker::{Picker, PickerDelegate};
 use project::{Project, ProjectLspAdapterDelegate, ProjectTransaction};
+use rustdoc::{RustdocSettings, RustdocStore};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use settings::Settings;
 use std::{
@@ -62,8 +64,9 @@ use uuid::Uuid;
 use workspace::NewFile;
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
+    notifications::NotificationId,
     searchable::Direction,
-    Save, ToggleZoom, Toolbar, Workspace,
+    Save, Toast, ToggleZoom, Toolbar, Workspace,
 };
 
 pub fn init(cx: &mut AppContext) {
@@ -573,6 +576,43 @@ impl AssistantPanel {
         Some(self.active_context_editor()?.read(cx).context.clone())
     }
 
+    /// Moves the cursor in the active context to the given position, scrolling
+    /// it into view.
+    pub(crate) fn scroll_to_position(
+        &self,
+        position: language::Anchor,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(context_editor) = self.active_context_editor() {
+            context_editor.update(cx, |context_editor, cx| {
+                context_editor.editor.update(cx, |editor, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let excerpt_id = *snapshot.as_singleton().unwrap().0;
+                    if let Some(position) = snapshot.anchor_in_excerpt(excerpt_id, position) {
+                        editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                            selections.select_anchor_ranges([position..position])
+                        });
+                    }
+                })
+            });
+        }
+    }
+
+    /// Inserts the slash command with the given name and argument into the
+    /// active context, and runs it.
+    pub(crate) fn insert_command_with_argument(
+        &self,
+        name: &str,
+        argument: &str,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(context_editor) = self.active_context_editor() {
+            context_editor.update(cx, |context_editor, cx| {
+                context_editor.insert_command_with_argument(name, Some(argument), cx)
+            });
+        }
+    }
+
     fn render_popover_button(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         let assistant = cx.view().clone();
         let zoomed = self.zoomed;
@@ -1009,6 +1049,7 @@ pub struct Context {
     buffer: Model<Buffer>,
     edit_suggestions: Vec<EditSuggestion>,
     pending_slash_commands: Vec<PendingSlashCommand>,
+    inserted_slash_commands: Vec<InsertedSlashCommand>,
     edits_since_last_slash_command_parse: language::Subscription,
     message_anchors: Vec<MessageAnchor>,
     messages_metadata: HashMap<MessageId, MessageMetadata>,
@@ -1051,6 +1092,7 @@ impl Context {
             next_message_id: Default::default(),
             edit_suggestions: Vec::new(),
             pending_slash_commands: Vec::new(),
+            inserted_slash_commands: Vec::new(),
             edits_since_last_slash_command_parse,
             summary: None,
             pending_summary: Task::ready(None),
@@ -1156,6 +1198,7 @@ impl Context {
                 next_message_id,
                 edit_suggestions: Vec::new(),
                 pending_slash_commands: Vec::new(),
+                inserted_slash_commands: Vec::new(),
                 edits_since_last_slash_command_parse,
                 summary: Some(Summary {
                     text: saved_context.summary,
@@ -1368,6 +1411,26 @@ impl Context {
         cx.notify();
     }
 
+    /// Returns the start of the output of the first slash command with the
+    /// given name and a matching argument that was inserted into the context,
+    /// if its output hasn't since been deleted.
+    pub(crate) fn inserted_slash_command_position(
+        &self,
+        name: &str,
+        argument_matches: impl Fn(Option<&str>) -> bool,
+        cx: &AppContext,
+    ) -> Option<language::Anchor> {
+        let buffer = self.buffer.read(cx);
+        self.inserted_slash_commands
+            .iter()
+            .find(|command| {
+                command.name == name
+                    && argument_matches(command.argument.as_deref())
+                    && command.output_range.to_offset(buffer).len() > 0
+            })
+            .map(|command| command.output_range.start)
+    }
+
     fn pending_command_for_position(
         &mut self,
         position: language::Anchor,
@@ -1427,6 +1490,7 @@ impl Context {
         &mut self,
         command_range: Range<language::Anchor>,
         output: Task<Result<SlashCommandOutput>>,
+        mut partial_outputs: mpsc::UnboundedReceiver<SlashCommandOutput>,
         insert_trailing_newline: bool,
         cx: &mut ModelContext<Self>,
     ) {
@@ -1435,16 +1499,46 @@ impl Context {
         let insert_output_task = cx.spawn(|this, mut cx| {
             let command_range = command_range.clone();
             async move {
-                let output = output.await;
+                // The output produced so far is shown below the command until
+                // the rest of it is, and is then replaced along with it.
+                let mut partial_output_range = None;
+                let mut output = output.fuse();
+                let output = loop {
+                    futures::select_biased! {
+                        output = output => break output,
+                        partial_output = partial_outputs.next() => {
+                            let Some(partial_output) = partial_output else {
+                                continue;
+                            };
+                            let previous_range = partial_output_range.take();
+                            partial_output_range = this
+                                .update(&mut cx, |this, cx| {
+                                    this.show_partial_command_output(
+                                        command_range.end,
+                                        previous_range,
+                                        &partial_output.text,
+                                        cx,
+                                    )
+                                })
+                                .ok();
+                        }
+                    }
+                };
                 this.update(&mut cx, |this, cx| match output {
                     Ok(mut output) => {
                         if insert_trailing_newline {
                             output.text.push('\n');
                         }
 
+                        let command = this
+                            .pending_command_for_position(command_range.start, cx)
+                            .map(|command| (command.name.clone(), command.argument.clone()));
                         let event = this.buffer.update(cx, |buffer, cx| {
                             let start = command_range.start.to_offset(buffer);
-                            let old_end = command_range.end.to_offset(buffer);
+                            let mut old_end = command_range.end.to_offset(buffer);
+                            if let Some(partial_output_range) = &partial_output_range {
+                                old_end = old_end.max(partial_output_range.end.to_offset(buffer));
+                            }
                             let new_end = start + output.text.len();
                             buffer.edit([(start..old_end, output.text)], None, cx);
 
@@ -1465,9 +1559,27 @@ impl Context {
                                 run_commands_in_output: output.run_commands_in_text,
                             }
                         });
+                        if let (
+                            Some((name, argument)),
+                            ContextEvent::SlashCommandFinished { output_range, .. },
+                        ) = (command, &event)
+                        {
+                            this.inserted_slash_commands.push(InsertedSlashCommand {
+                                name,
+                                argument,
+                                output_range: output_range.clone(),
+                            });
+                        }
                         cx.emit(event);
                     }
                     Err(error) => {
+                        if let Some(partial_output_range) = partial_output_range {
+                            this.buffer.update(cx, |buffer, cx| {
+                                let range = partial_output_range.start.to_offset(buffer)
+                                    ..partial_output_range.end.to_offset(buffer);
+                                buffer.edit([(range, "")], None, cx);
+                            });
+                        }
                         if let Some(pending_command) =
                             this.pending_command_for_position(command_range.start, cx)
                         {
@@ -1495,6 +1607,30 @@ impl Context {
         }
     }
 
+    /// Shows the output that a command has produced so far on the lines after
+    /// the command, in place of any it showed before, returning its range.
+    fn show_partial_command_output(
+        &mut self,
+        command_end: language::Anchor,
+        previous_range: Option<Range<language::Anchor>>,
+        text: &str,
+        cx: &mut ModelContext<Self>,
+    ) -> Range<language::Anchor> {
+        self.buffer.update(cx, |buffer, cx| {
+            let range = match previous_range {
+                Some(range) => range.start.to_offset(buffer)..range.end.to_offset(buffer),
+                None => {
+                    let command_end = command_end.to_offset(buffer);
+                    command_end..command_end
+                }
+            };
+            let start = range.start;
+            let text = format!("\n{text}");
+            buffer.edit([(range, text.as_str())], None, cx);
+            buffer.anchor_after(start)..buffer.anchor_before(start + text.len())
+        })
+    }
+
     fn remaining_tokens(&self, cx: &AppContext) -> Option<isize> {
         let model = CompletionProvider::global(cx).model();
         Some(model.max_token_count() as isize - self.token_count? as isize)
@@ -2119,6 +2255,14 @@ fn parse_next_edit_suggestion(lines: &mut rope::Lines) -> Option<ParsedEditSugge
     }
 }
 
+/// A slash command whose output was inserted into a context.
+#[derive(Clone)]
+struct InsertedSlashCommand {
+    name: String,
+    argument: Option<String>,
+    output_range: Range<language::Anchor>,
+}
+
 #[derive(Clone)]
 struct PendingSlashCommand {
     name: String,
@@ -2159,6 +2303,8 @@ pub struct ContextEditor {
     blocks: HashSet<BlockId>,
     scroll_position: Option<ScrollPosition>,
     pending_slash_command_flaps: HashMap<Range<language::Anchor>, FlapId>,
+    /// The crates that `/rustdoc` has been suggested for in this context.
+    rustdoc_suggested_crates: HashSet<String>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -2231,6 +2377,7 @@ impl ContextEditor {
             fs,
             workspace: workspace.downgrade(),
             pending_slash_command_flaps: HashMap::default(),
+            rustdoc_suggested_crates: HashSet::default(),
             _subscriptions,
         };
         this.update_message_headers(cx);
@@ -2265,6 +2412,7 @@ impl ContextEditor {
 
     fn assist(&mut self, _: &Assist, cx: &mut ViewContext<Self>) {
         let cursors = self.cursors(cx);
+        self.suggest_rustdoc_for_mentioned_crates(&cursors, cx);
 
         let user_messages = self.context.update(cx, |context, cx| {
             let selected_messages = context
@@ -2296,6 +2444,77 @@ impl ContextEditor {
         }
     }
 
+    /// Offers to insert the docs for the indexed crates that are mentioned in
+    /// the user messages at the given offsets, if enabled.
+    fn suggest_rustdoc_for_mentioned_crates(
+        &mut self,
+        offsets: &[usize],
+        cx: &mut ViewContext<Self>,
+    ) {
+        if !RustdocSettings::get_global(cx).suggest_for_mentioned_crates {
+            return;
+        }
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+
+        let context = self.context.read(cx);
+        let buffer = context.buffer.read(cx);
+        let text = context
+            .messages_for_offsets(offsets.iter().copied(), cx)
+            .into_iter()
+            .filter(|message| message.role == Role::User)
+            .map(|message| {
+                buffer
+                    .text_for_range(message.offset_range)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let crate_names = RustdocStore::global(cx)
+            .mentioned_crates(&text)
+            .into_iter()
+            .filter(|crate_name| self.rustdoc_suggested_crates.insert(crate_name.clone()))
+            .collect::<Vec<_>>();
+        if crate_names.is_empty() {
+            return;
+        }
+
+        let message = format!(
+            "Insert the docs for {}?",
+            crate_names
+                .iter()
+                .map(|crate_name| format!("`{crate_name}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let context_editor = cx.view().downgrade();
+        workspace.update(cx, |workspace, cx| {
+            struct RustdocSuggestion;
+
+            workspace.show_toast(
+                Toast::new(NotificationId::unique::<RustdocSuggestion>(), message).on_click(
+                    "Insert docs",
+                    move |cx| {
+                        context_editor
+                            .update(cx, |context_editor, cx| {
+                                for crate_name in &crate_names {
+                                    context_editor.insert_command_with_argument(
+                                        "rustdoc",
+                                        Some(crate_name.as_str()),
+                                        cx,
+                                    );
+                                }
+                            })
+                            .ok();
+                    },
+                ),
+                cx,
+            )
+        });
+    }
+
     fn cancel_last_assist(&mut self, _: &editor::actions::Cancel, cx: &mut ViewContext<Self>) {
         if !self
             .context
@@ -2326,6 +2545,17 @@ impl ContextEditor {
     }
 
     fn insert_command(&mut self, name: &str, cx: &mut ViewContext<Self>) {
+        self.insert_command_with_argument(name, None, cx)
+    }
+
+    /// Inserts the slash command with the given name on a new line, running it
+    /// right away if it doesn't need an argument or one was provided.
+    fn insert_command_with_argument(
+        &mut self,
+        name: &str,
+        argument: Option<&str>,
+        cx: &mut ViewContext<Self>,
+    ) {
         if let Some(command) = self.slash_command_registry.command(name) {
             self.editor.update(cx, |editor, cx| {
                 editor.transact(cx, |editor, cx| {
@@ -2348,13 +2578,15 @@ impl ContextEditor {
                     }
 
                     editor.insert(&format!("/{name}"), cx);
-                    if command.requires_argument() {
+                    if let Some(argument) = argument {
+                        editor.insert(&format!(" {argument}"), cx);
+                    } else if command.requires_argument() {
                         editor.insert(" ", cx);
                         editor.show_completions(&ShowCompletions, cx);
                     }
                 });
             });
-            if !command.requires_argument() {
+            if argument.is_some() || !command.requires_argument() {
                 self.confirm_command(&ConfirmCommand, cx);
             }
         }
@@ -2406,11 +2638,21 @@ impl ContextEditor {
         if let Some(command) = self.slash_command_registry.command(name) {
             if let Some(lsp_adapter_delegate) = self.lsp_adapter_delegate.clone() {
                 let argument = argument.map(ToString::to_string);
-                let output = command.run(argument.as_deref(), workspace, lsp_adapter_delegate, cx);
+                let (partial_output_tx, partial_outputs) = mpsc::unbounded();
+                let output = command.run_with_partial_output(
+                    argument.as_deref(),
+                    workspace,
+                    lsp_adapter_delegate,
+                    Arc::new(move |partial_output| {
+                        partial_output_tx.unbounded_send(partial_output).ok();
+                    }),
+                    cx,
+                );
                 self.context.update(cx, |context, cx| {
                     context.insert_command_output(
                         command_range,
                         output,
+                        partial_outputs,
                         insert_trailing_newline,
                         cx,
                     )
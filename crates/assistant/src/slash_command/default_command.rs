@@ -1,4 +1,6 @@
-use super::{prompt_command::PromptPlaceholder, SlashCommand, SlashCommandOutput};
+use super::{
+    prompt_command::PromptPlaceholder, ArgumentCompletion, SlashCommand, SlashCommandOutput,
+};
 use crate::prompt_library::PromptStore;
 use anyhow::{anyhow, Result};
 use assistant_slash_command::SlashCommandOutputSection;
@@ -36,7 +38,7 @@ impl SlashCommand for DefaultSlashCommand {
         _cancellation_flag: Arc<AtomicBool>,
         _workspace: Option<WeakView<Workspace>>,
         _cx: &mut AppContext,
-    ) -> Task<Result<Vec<String>>> {
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
         Task::ready(Err(anyhow!("this command does not require argument")))
     }
 
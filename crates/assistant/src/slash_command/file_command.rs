@@ -1,4 +1,4 @@
-use super::{SlashCommand, SlashCommandOutput};
+use super::{ArgumentCompletion, SlashCommand, SlashCommandOutput};
 use anyhow::{anyhow, Result};
 use assistant_slash_command::SlashCommandOutputSection;
 use fuzzy::PathMatch;
@@ -103,7 +103,7 @@ impl SlashCommand for FileSlashCommand {
         cancellation_flag: Arc<AtomicBool>,
         workspace: Option<WeakView<Workspace>>,
         cx: &mut AppContext,
-    ) -> Task<Result<Vec<String>>> {
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
         let Some(workspace) = workspace.and_then(|workspace| workspace.upgrade()) else {
             return Task::ready(Err(anyhow!("workspace was dropped")));
         };
@@ -120,6 +120,7 @@ impl SlashCommand for FileSlashCommand {
                         path_match.path.to_string_lossy()
                     )
                 })
+                .map(ArgumentCompletion::from)
                 .collect())
         })
     }
@@ -1,4 +1,4 @@
-use super::{SlashCommand, SlashCommandOutput};
+use super::{ArgumentCompletion, SlashCommand, SlashCommandOutput};
 use anyhow::{anyhow, Context, Result};
 use assistant_slash_command::SlashCommandOutputSection;
 use fs::Fs;
@@ -107,7 +107,7 @@ impl SlashCommand for ProjectSlashCommand {
         _cancel: Arc<AtomicBool>,
         _workspace: Option<WeakView<Workspace>>,
         _cx: &mut AppContext,
-    ) -> Task<Result<Vec<String>>> {
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
         Task::ready(Err(anyhow!("this command does not require argument")))
     }
 
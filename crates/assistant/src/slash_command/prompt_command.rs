@@ -1,4 +1,4 @@
-use super::{SlashCommand, SlashCommandOutput};
+use super::{ArgumentCompletion, SlashCommand, SlashCommandOutput};
 use crate::prompt_library::PromptStore;
 use anyhow::{anyhow, Context, Result};
 use assistant_slash_command::SlashCommandOutputSection;
@@ -33,13 +33,14 @@ impl SlashCommand for PromptSlashCommand {
         _cancellation_flag: Arc<AtomicBool>,
         _workspace: Option<WeakView<Workspace>>,
         cx: &mut AppContext,
-    ) -> Task<Result<Vec<String>>> {
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
         let store = PromptStore::global(cx);
         cx.background_executor().spawn(async move {
             let prompts = store.await?.search(query).await;
             Ok(prompts
                 .into_iter()
                 .filter_map(|prompt| Some(prompt.title?.to_string()))
+                .map(ArgumentCompletion::from)
                 .collect())
         })
     }
@@ -1,99 +1,2341 @@
-use std::path::Path;
-use std::sync::atomic::AtomicBool;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
-use anyhow::{anyhow, bail, Context, Result};
-use assistant_slash_command::{SlashCommand, SlashCommandOutput, SlashCommandOutputSection};
+use anyhow::{anyhow, Context as _, Result};
+use assistant_slash_command::{
+    ArgumentCompletion, PartialOutputCallback, SlashCommand, SlashCommandOutput,
+    SlashCommandOutputSection,
+};
+use collections::{HashMap, HashSet};
 use fs::Fs;
-use futures::AsyncReadExt;
-use gpui::{AppContext, Model, Task, WeakView};
-use http::{AsyncBody, HttpClient, HttpClientWithUrl};
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use gpui::{AnyElement, AppContext, AsyncWindowContext, Model, Task, WeakView};
+use http::HttpClientWithUrl;
 use language::LspAdapterDelegate;
-use project::{Project, ProjectPath};
-use rustdoc::crawler::LocalProvider;
-use rustdoc::{convert_rustdoc_to_markdown, RustdocStore};
-use ui::{prelude::*, ButtonLike, ElevationIndex};
+use parking_lot::Mutex;
+use project::{Project, ProjectPath, WorktreeId};
+use rustdoc::crawler::{
+    CargoDocFeatures, DocsDotRsProvider, JsonProvider, LocalProvider, RustdocProvider,
+    RustdocSource, TarballProvider,
+};
+use rustdoc::{
+    canonical_crate_name, convert_markdown_to_plain_text, crate_dependencies, crate_search_scope,
+    debounce_search, dedup_sections, docs_host_for_crate, fetch_docs, is_docs_not_found,
+    locked_crate_names, locked_dependencies, manifest_root, normalize_crate_name, one_line_summary,
+    parse_crate_version, parse_item_path, parse_std_channel, split_docs_sections,
+    split_item_list_sections, split_item_queries, summarize_docs, truncate_docs,
+    workspace_member_crates, DocProvider, DocProviderRegistry, DocQuery, DocQueryMode, DocsCache,
+    DocsResolver, DocsSection, DocsSectionKind, DuplicateDocsBehavior, NotFoundCache,
+    ResolveDocsOptions, RustdocItemKind, RustdocItemOrder, RustdocSettings, RustdocStore,
+    StaleIndexBehavior, STANDARD_LIBRARY_CRATES,
+};
+use settings::Settings;
+use ui::{prelude::*, ButtonLike, ElevationIndex, Tooltip};
+use util::paths::{RUSTDOC_CACHE_DIR, RUSTDOC_TARBALLS_DIR};
+use util::ResultExt;
 use workspace::Workspace;
 
-#[derive(Debug, Clone, Copy)]
-enum RustdocSource {
-    /// The docs were sourced from local `cargo doc` output.
-    Local,
-    /// The docs were sourced from `docs.rs`.
-    DocsDotRs,
-}
+use crate::AssistantPanel;
+
+/// The maximum number of queries from a `--from-file` batch, or from a list
+/// of items, that are run at once.
+const MAX_CONCURRENT_QUERIES: usize = 4;
+
+/// The maximum number of versions of an item that can be compared at once.
+const MAX_COMPARED_VERSIONS: usize = 3;
+
+/// The maximum number of referenced items that `--with-refs` inserts the docs for.
+const MAX_REFERENCED_ITEMS: usize = 8;
+
+/// The maximum number of implementors of a trait that `--implementors` lists,
+/// unless `--all-implementors` is given.
+const MAX_IMPLEMENTORS: usize = 20;
+
+/// The maximum number of items in each folded section of the list of a
+/// crate's items that `--all` inserts.
+const MAX_ITEMS_PER_SECTION: usize = 200;
+
+/// The maximum number of similar items and crates that are suggested when
+/// there are no docs for the queried item.
+const MAX_NOT_FOUND_SUGGESTIONS: usize = 5;
+
+/// The maximum number of indexed items that `--search` lists.
+const MAX_SEARCH_RESULTS: usize = 10;
+
+/// How long typing must pause before the completions for the argument are
+/// searched for.
+const COMPLETION_DEBOUNCE: Duration = Duration::from_millis(120);
 
 pub(crate) struct RustdocSlashCommand;
 
 impl RustdocSlashCommand {
-    async fn build_message(
+    /// Returns the text to show when there are no docs for the queried item,
+    /// suggesting the given similar items instead.
+    ///
+    /// When the path was typed with generic arguments, it is shown as typed,
+    /// along with the path without them that was looked up.
+    ///
+    /// Any context that was added to the error (e.g., that the item may be
+    /// platform-specific) is kept, as it explains why nothing was found.
+    fn not_found_message(
+        query_path: &str,
+        typed_path: Option<&str>,
+        err: &anyhow::Error,
+        suggestions: &[String],
+        missing_item_hint: Option<&str>,
+    ) -> String {
+        let mut text = match typed_path {
+            Some(typed_path) => format!(
+                "No docs found for `{typed_path}`. Generic arguments are ignored, so the \
+                docs for `{query_path}` were looked up instead."
+            ),
+            None => format!("No docs found for `{query_path}`."),
+        };
+        if !suggestions.is_empty() {
+            text.push_str(" Did you mean one of these?\n");
+            for suggestion in suggestions {
+                text.push_str(&format!("\n- `/rustdoc {suggestion}`"));
+            }
+        }
+        if err.chain().count() > 1 {
+            text.push_str(&format!("\n\n{err}"));
+        }
+        if let Some(missing_item_hint) = missing_item_hint {
+            text.push_str(&format!("\n\n{missing_item_hint}"));
+        }
+        text
+    }
+
+    /// Returns the features of the given crate, as a Markdown list of each
+    /// feature and the features it enables.
+    ///
+    /// The features are read from the crate's `Cargo.toml` when it is indexed
+    /// or is a member of the workspace, and otherwise from its features page
+    /// on `docs.rs`, as are those of a specific version.
+    async fn build_features_message(
+        rustdoc_store: Arc<RustdocStore>,
         fs: Arc<dyn Fs>,
-        http_client: Arc<HttpClientWithUrl>,
+        cargo_workspace_roots: Vec<PathBuf>,
+        docs_dot_rs_provider: DocsDotRsProvider,
         crate_name: String,
-        module_path: Vec<String>,
-        path_to_cargo_toml: Option<&Path>,
+        version: Option<String>,
     ) -> Result<(RustdocSource, String)> {
-        let cargo_workspace_root = path_to_cargo_toml.and_then(|path| path.parent());
-        if let Some(cargo_workspace_root) = cargo_workspace_root {
-            let mut local_cargo_doc_path = cargo_workspace_root.join("target/doc");
-            local_cargo_doc_path.push(&crate_name);
-            if !module_path.is_empty() {
-                local_cargo_doc_path.push(module_path.join("/"));
+        let lib_name = normalize_crate_name(&crate_name);
+        let mut local_features = None;
+        // The local manifests are for whichever version is checked out.
+        if version.is_none() {
+            local_features = rustdoc_store.crate_features(&lib_name);
+            for cargo_workspace_root in cargo_workspace_roots {
+                if local_features.is_some() {
+                    break;
+                }
+                local_features = LocalProvider::new(fs.clone(), cargo_workspace_root)
+                    .crate_features(&lib_name)
+                    .await
+                    .log_err()
+                    .flatten();
+            }
+        }
+
+        let (source, features) = match local_features {
+            Some(features) => (RustdocSource::Local, features),
+            None => {
+                let docs_version = version.as_deref().unwrap_or("latest");
+                let features = docs_dot_rs_provider
+                    .fetch_features(&crate_name, docs_version)
+                    .await
+                    .with_context(|| format!("failed to fetch the features of {crate_name}"))?;
+                (RustdocSource::DocsDotRs, features)
+            }
+        };
+
+        let crate_spec = match version {
+            Some(version) => format!("{crate_name}@{version}"),
+            None => crate_name,
+        };
+        if features.is_empty() {
+            return Ok((source, format!("`{crate_spec}` has no features.")));
+        }
+
+        let mut text = format!("Features of `{crate_spec}`:\n");
+        for feature in features {
+            text.push_str(&format!("\n- `{}`", feature.name));
+            if !feature.enables.is_empty() {
+                let enables = feature
+                    .enables
+                    .iter()
+                    .map(|enabled| format!("`{enabled}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                text.push_str(&format!(": enables {enables}"));
+            }
+        }
+        Ok((source, text))
+    }
+
+    /// Returns the summarized docs for each of the types and traits that the
+    /// queried item directly references, as a section to append to its docs.
+    ///
+    /// Items from the standard library are left out, as they are well known
+    /// and would crowd out the crate's own items.
+    async fn build_references_message(
+        providers: Vec<Arc<dyn DocProvider>>,
+        query: DocQuery,
+    ) -> Result<String> {
+        let query_path = std::iter::once(&query.crate_name)
+            .chain(&query.item_path)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("::");
+        let references_query = query.clone().with_mode(DocQueryMode::References);
+        let (_source, references) = fetch_docs(&providers, &references_query).await?;
+
+        let references = references
+            .lines()
+            .filter(|path| !path.is_empty() && *path != query_path)
+            .filter(|path| {
+                let crate_name = path.split("::").next().unwrap_or_default();
+                !STANDARD_LIBRARY_CRATES.contains(&crate_name)
+            })
+            .collect::<Vec<_>>();
+        if references.is_empty() {
+            return Ok(format!(
+                "No types or traits referenced by `{query_path}` were found."
+            ));
+        }
+
+        let docs = join_all(references.iter().take(MAX_REFERENCED_ITEMS).map(|path| {
+            let mut path_components = path.split("::");
+            let crate_name = path_components.next().unwrap_or_default().to_string();
+            // Other crates are fetched at whichever version docs.rs
+            // considers the latest.
+            let version = query
+                .version
+                .clone()
+                .filter(|_| crate_name == query.crate_name);
+            let reference_query = DocQuery {
+                omit_see_also: query.omit_see_also,
+                item_order: query.item_order,
+                expand_auto_impls: query.expand_auto_impls,
+                ..DocQuery::new(
+                    crate_name,
+                    path_components.map(ToString::to_string).collect(),
+                )
+                .with_version(version)
+                .with_target(query.target.clone())
+            };
+            let providers = &providers;
+            async move { fetch_docs(providers, &reference_query).await }
+        }))
+        .await;
+
+        let mut text = format!("## Items referenced by `{query_path}`");
+        for (path, docs) in references.iter().zip(docs) {
+            match docs {
+                Ok((_source, markdown)) => {
+                    let summary = summarize_docs(&markdown);
+                    // The summaries are nested below the heading for the
+                    // referenced items.
+                    let summary = match summary.strip_prefix("# ") {
+                        Some(summary) => format!("### {summary}"),
+                        None => summary,
+                    };
+                    text.push_str(&format!("\n\n{summary}"));
+                }
+                Err(err) => {
+                    text.push_str(&format!("\n\nFailed to fetch docs for `{path}`: {err}"));
+                }
+            }
+        }
+        if references.len() > MAX_REFERENCED_ITEMS {
+            text.push_str(&format!(
+                "\n\n{count} more referenced items were left out.",
+                count = references.len() - MAX_REFERENCED_ITEMS
+            ));
+        }
+
+        Ok(text)
+    }
+
+    /// Returns the URL to the docs for the query, preferring `docs.rs` (or
+    /// `doc.rust-lang.org`, for the standard library, or the server that the
+    /// crate's docs are configured to be fetched from).
+    ///
+    /// Falls back to the first of the given local `cargo doc` outputs that
+    /// exists, for crates that are not published to `docs.rs`.
+    async fn resolve_docs_url(
+        provider: DocsDotRsProvider,
+        fs: Arc<dyn Fs>,
+        query: DocQuery,
+        std_channel: String,
+        local_docs_paths: Vec<PathBuf>,
+    ) -> Result<(RustdocSource, String)> {
+        let docs_dot_rs_url = query.docs_url(&std_channel);
+        if provider
+            .page_exists(&query.crate_name, &docs_dot_rs_url)
+            .await
+        {
+            return Ok((
+                RustdocSource::DocsDotRs,
+                provider.host_url(&query.crate_name, &docs_dot_rs_url),
+            ));
+        }
+
+        for local_docs_path in local_docs_paths {
+            if fs.is_file(&local_docs_path).await {
+                return Ok((
+                    RustdocSource::Local,
+                    format!("file://{}", local_docs_path.display()),
+                ));
+            }
+        }
+
+        Err(anyhow!(
+            "no docs URL found for {}. If it is a local crate, build its docs with `cargo doc`",
+            query.crate_name
+        ))
+    }
+
+    /// Returns the paths to the local `cargo doc` pages for the given query in
+    /// each of the given Cargo workspaces.
+    async fn local_docs_paths(
+        fs: Arc<dyn Fs>,
+        cargo_workspace_roots: Vec<PathBuf>,
+        query: DocQuery,
+    ) -> Vec<PathBuf> {
+        let mut local_docs_paths = Vec::new();
+        for cargo_workspace_root in cargo_workspace_roots {
+            local_docs_paths.push(
+                LocalProvider::new(fs.clone(), cargo_workspace_root)
+                    .docs_path(&query)
+                    .await,
+            );
+        }
+        local_docs_paths
+    }
+
+    /// Returns the URL to the docs that were fetched from the given source,
+    /// for viewing them in the browser.
+    ///
+    /// Local docs are opened from the first of the given `cargo doc` outputs
+    /// that exists.
+    async fn fetched_docs_url(
+        fs: Arc<dyn Fs>,
+        source: RustdocSource,
+        docs_dot_rs_url: String,
+        local_docs_paths: Vec<PathBuf>,
+    ) -> Option<SharedString> {
+        match source {
+            RustdocSource::DocsDotRs | RustdocSource::CachedDocsDotRs => {
+                Some(docs_dot_rs_url.into())
+            }
+            RustdocSource::Local => {
+                for local_docs_path in local_docs_paths {
+                    if fs.is_file(&local_docs_path).await {
+                        return Some(format!("file://{}", local_docs_path.display()).into());
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    /// Returns the age of the indexed docs for the query, if they are older
+    /// than the given max age.
+    fn stale_index_age(
+        store: &RustdocStore,
+        query: &DocQuery,
+        max_age: Option<Duration>,
+    ) -> Option<Duration> {
+        let max_age = max_age?;
+        // Queries for a specific version are never served from the store.
+        if query.version.is_some() {
+            return None;
+        }
+        let indexed_at = store.indexed_at(&query.crate_name, &query.item_path.join("::"))?;
+        let age = indexed_at.elapsed().ok()?;
+        (age > max_age).then_some(age)
+    }
+
+    /// Resolves an item name that isn't qualified with a crate name to its
+    /// full path, using the workspace symbols from the language server.
+    ///
+    /// Returns `None` if the argument doesn't need resolving.
+    fn resolve_with_language_server(
+        argument: &str,
+        workspace: &WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Option<Task<Option<String>>> {
+        let mut item_name = None;
+        for arg in argument.split(' ').map(|word| word.trim()) {
+            match RustdocFlag::get(arg) {
+                Some(flag) if flag.resolves_item => {}
+                Some(_) => return None,
+                None if arg.is_empty() => {}
+                None if arg.starts_with("--") => return None,
+                None => item_name = Some(arg.to_string()),
             }
-            local_cargo_doc_path.push("index.html");
+        }
+        let item_name = item_name.filter(|item_name| !item_name.contains("::"))?;
 
-            if let Ok(contents) = fs.load(&local_cargo_doc_path).await {
-                let (markdown, _items) = convert_rustdoc_to_markdown(contents.as_bytes())?;
+        let project = workspace.upgrade()?.read(cx).project().clone();
+        let worktree_root_names = project
+            .read(cx)
+            .worktrees()
+            .map(|worktree| {
+                let worktree = worktree.read(cx);
+                (worktree.id(), worktree.root_name().to_string())
+            })
+            .collect::<HashMap<_, _>>();
+        let symbols = project.update(cx, |project, cx| project.symbols(&item_name, cx));
+
+        let argument = argument.to_string();
+        Some(cx.foreground_executor().spawn(async move {
+            let symbols = symbols.await.log_err()?;
+            let item_path = symbols
+                .iter()
+                .filter(|symbol| symbol.name == item_name)
+                .find_map(|symbol| {
+                    item_path_for_symbol(&symbol.name, &symbol.path, &worktree_root_names)
+                })?;
+
+            Some(argument.replacen(&item_name, &item_path, 1))
+        }))
+    }
 
-                return Ok((RustdocSource::Local, markdown));
+    /// Runs the command for the given argument, first resolving unqualified
+    /// item names if enabled.
+    fn run_argument(
+        argument: &str,
+        workspace: WeakView<Workspace>,
+        partial_output: Option<PartialOutputCallback>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        if RustdocSettings::get_global(cx).resolve_with_language_server {
+            if let Some(resolved_argument) =
+                Self::resolve_with_language_server(argument, &workspace, cx)
+            {
+                let argument = argument.to_string();
+                return cx.spawn(|mut cx| async move {
+                    let argument = resolved_argument.await.unwrap_or(argument);
+                    cx.update(|cx| Self::run_query(&argument, workspace, partial_output, cx))?
+                        .await
+                });
             }
         }
 
-        let version = "latest";
-        let path = format!(
-            "{crate_name}/{version}/{crate_name}/{module_path}",
-            module_path = module_path.join("/")
+        Self::run_query(argument, workspace, partial_output, cx)
+    }
+
+    /// Lists the indexed items that best match the given query, each with a
+    /// one-line summary of its docs in a section of its own, whose placeholder
+    /// inserts the item's full docs.
+    fn run_search(
+        query: &str,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        // The query may contain spaces, e.g., when matching a signature.
+        let query = query.trim().to_string();
+        if query.is_empty() {
+            return Task::ready(Err(anyhow!("no query provided to --search")));
+        }
+
+        let rustdoc_store = RustdocStore::global(cx);
+        let results = rustdoc_store.search(
+            query.clone(),
+            MAX_SEARCH_RESULTS,
+            true,
+            None,
+            Arc::new(AtomicBool::new(false)),
         );
+        cx.foreground_executor().spawn(async move {
+            let results = results.await;
+            if results.is_empty() {
+                return Err(anyhow!(
+                    "no indexed items match `{query}`. Index the crates to search with \
+                    `--index <crate>` or `--workspace`"
+                ));
+            }
+
+            let docs = join_all(results.iter().map(|result| {
+                rustdoc_store.load(result.crate_name.clone(), Some(result.item.display()))
+            }))
+            .await;
+
+            let mut text = format!("Indexed items matching `{query}`:\n");
+            let mut sections = Vec::new();
+            for (result, docs) in results.into_iter().zip(docs) {
+                let item_path = format!("{}::{}", result.crate_name, result.item.display());
+                let summary = docs.ok().as_deref().and_then(one_line_summary);
+
+                let section_start = text.len();
+                text.push_str(&format!("\n- `{item_path}`"));
+                if let Some(summary) = summary {
+                    text.push_str(&format!(": {summary}"));
+                }
+                let range = section_start..text.len();
+
+                let item_path = SharedString::from(item_path);
+                let workspace = workspace.clone();
+                sections.push(SlashCommandOutputSection {
+                    range,
+                    render_placeholder: Arc::new(move |id, unfold, _cx| {
+                        RustdocSearchResultPlaceholder {
+                            id,
+                            unfold,
+                            item_path: item_path.clone(),
+                            workspace: workspace.clone(),
+                        }
+                        .into_any_element()
+                    }),
+                });
+            }
+
+            Ok(SlashCommandOutput {
+                text,
+                sections,
+                run_commands_in_text: false,
+            })
+        })
+    }
+
+    /// Runs the command for each of the queries listed in the file at the
+    /// given path, inserting each one as its own section.
+    ///
+    /// The file lists one query per line. Blank lines and lines starting with
+    /// `#` are skipped. Queries that fail are reported inline, without
+    /// aborting the rest of the batch.
+    ///
+    /// With `--dedup`, the sections that are identical across the docs for
+    /// more than one query are only inserted once, at the end.
+    fn run_batch(
+        argument: &str,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let mut path = "";
+        let mut dedup = false;
+        for arg in argument.split(' ').map(|word| word.trim()) {
+            match arg {
+                "--dedup" => dedup = true,
+                "" => {}
+                _ => path = arg,
+            }
+        }
+
+        if path.is_empty() {
+            return Task::ready(Err(anyhow!("no file provided to --from-file")));
+        }
+        let Some(project) = workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        let fs = project.read(cx).fs().clone();
+        let path = Self::worktree_path(&project, Path::new(path), cx);
+
+        cx.spawn(|cx| async move {
+            let contents = fs
+                .load(&path)
+                .await
+                .with_context(|| format!("failed to read {path:?}"))?;
+            let queries = contents
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+
+            let mut outputs = stream::iter(queries.into_iter().map(|query| {
+                let mut cx = cx.clone();
+                let workspace = workspace.clone();
+                async move {
+                    let output =
+                        match cx.update(|cx| Self::run_argument(&query, workspace, None, cx)) {
+                            Ok(output) => output.await,
+                            Err(err) => Err(err),
+                        };
+                    (query, output)
+                }
+            }))
+            .buffered(MAX_CONCURRENT_QUERIES)
+            .collect::<Vec<_>>()
+            .await;
+
+            let shared_sections = if dedup {
+                let mut texts = outputs
+                    .iter_mut()
+                    .filter_map(|(_, output)| output.as_mut().ok())
+                    .map(|output| std::mem::take(&mut output.text))
+                    .collect::<Vec<_>>();
+                let shared_sections = dedup_sections(&mut texts);
+
+                let mut texts = texts.into_iter();
+                for output in outputs
+                    .iter_mut()
+                    .filter_map(|(_, output)| output.as_mut().ok())
+                {
+                    output.text = texts.next().unwrap_or_default();
+                    // The shared sections are moved out of the docs for each
+                    // query, so the parts of the docs no longer line up with
+                    // their sections, and the first section covers them all.
+                    output.sections.truncate(1);
+                    for section in output.sections.iter_mut() {
+                        section.range = 0..output.text.len();
+                    }
+                }
+
+                shared_sections
+            } else {
+                Vec::new()
+            };
+
+            let mut text = String::new();
+            let mut sections = Vec::new();
+            for (query, output) in outputs {
+                if !text.is_empty() {
+                    text.push_str("\n\n");
+                }
+
+                match output {
+                    Ok(output) => {
+                        let offset = text.len();
+                        text.push_str(&output.text);
+                        sections.extend(output.sections.into_iter().map(|section| {
+                            SlashCommandOutputSection {
+                                range: section.range.start + offset..section.range.end + offset,
+                                render_placeholder: section.render_placeholder,
+                            }
+                        }));
+                    }
+                    Err(err) => {
+                        text.push_str(&format!("Failed to insert docs for `{query}`: {err}"));
+                    }
+                }
+            }
+
+            if !shared_sections.is_empty() {
+                text.push_str("\n\n# Shared sections");
+                for shared_section in shared_sections {
+                    text.push_str(&format!("\n\n{shared_section}"));
+                }
+            }
+
+            Ok(SlashCommandOutput {
+                text,
+                sections,
+                run_commands_in_text: false,
+            })
+        })
+    }
+
+    /// Runs the command for each of the given queries for the items listed in
+    /// one argument, inserting the docs for each item as one section, so that
+    /// they can be folded independently.
+    ///
+    /// Queries that fail are reported inline, without aborting the rest.
+    fn run_items(
+        queries: Vec<String>,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        cx.spawn(|cx| async move {
+            let outputs = stream::iter(queries.into_iter().map(|query| {
+                let mut cx = cx.clone();
+                let workspace = workspace.clone();
+                async move {
+                    let output = match cx.update(|cx| Self::run_single(&query, workspace, None, cx))
+                    {
+                        Ok(output) => output.await,
+                        Err(err) => Err(err),
+                    };
+                    (query, output)
+                }
+            }))
+            .buffered(MAX_CONCURRENT_QUERIES)
+            .collect::<Vec<_>>()
+            .await;
+
+            let mut text = String::new();
+            let mut sections = Vec::new();
+            for (query, output) in outputs {
+                if !text.is_empty() {
+                    text.push_str("\n\n");
+                }
+
+                match output {
+                    Ok(output) => {
+                        let start = text.len();
+                        text.push_str(&output.text);
+                        // The first section is for the item's description,
+                        // which stands for all of its docs.
+                        if let Some(section) = output.sections.into_iter().next() {
+                            sections.push(SlashCommandOutputSection {
+                                range: start..text.len(),
+                                render_placeholder: section.render_placeholder,
+                            });
+                        }
+                    }
+                    Err(err) => {
+                        text.push_str(&format!("Failed to insert docs for `{query}`: {err}"));
+                    }
+                }
+            }
+
+            Ok(SlashCommandOutput {
+                text,
+                sections,
+                run_commands_in_text: false,
+            })
+        })
+    }
+
+    /// Runs the command for a single query, unless the docs that it inserts
+    /// are already in the conversation and the settings say not to insert
+    /// them again.
+    fn run_single(
+        argument: &str,
+        workspace: WeakView<Workspace>,
+        partial_output: Option<PartialOutputCallback>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let duplicate_docs_behavior = RustdocSettings::get_global(cx).duplicate_docs_behavior;
+        if duplicate_docs_behavior != DuplicateDocsBehavior::Insert {
+            if let Some(query_key) = docs_query_key(argument) {
+                return Self::run_unless_duplicate(
+                    argument,
+                    query_key,
+                    duplicate_docs_behavior,
+                    workspace,
+                    partial_output,
+                    cx,
+                );
+            }
+        }
+
+        Self::run_argument(argument, workspace, partial_output, cx)
+    }
+
+    /// Returns the versioned crates and the rest of the argument, if the
+    /// argument starts with more than one version of a crate, e.g.,
+    /// `serde@1.0.100 serde@1.0.200 de::Deserializer`.
+    fn parse_version_comparison(argument: &str) -> Option<(Vec<&str>, String)> {
+        let mut words = argument.split_whitespace().peekable();
+        let mut crate_specs = Vec::new();
+        while let Some(word) = words.next_if(|word| word.contains('@') && !word.contains("::")) {
+            crate_specs.push(word);
+        }
+        if crate_specs.len() < 2 {
+            return None;
+        }
+
+        Some((crate_specs, words.collect::<Vec<_>>().join(" ")))
+    }
+
+    /// Runs the query for the same item at each of the given versions of its
+    /// crate, inserting them in adjacent sections labeled with their version.
+    fn run_version_comparison(
+        crate_specs: Vec<&str>,
+        rest: &str,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        if crate_specs.len() > MAX_COMPARED_VERSIONS {
+            return Task::ready(Err(anyhow!(
+                "at most {MAX_COMPARED_VERSIONS} versions can be compared at once"
+            )));
+        }
+
+        let mut versions = Vec::new();
+        for crate_spec in &crate_specs {
+            match parse_crate_version(crate_spec) {
+                Ok((crate_name, Some(version))) => versions.push((crate_name.to_string(), version)),
+                Ok((_, None)) => unreachable!("crate specs contain a version"),
+                Err(err) => return Task::ready(Err(err)),
+            }
+        }
+        if versions
+            .iter()
+            .any(|(crate_name, _)| *crate_name != versions[0].0)
+        {
+            return Task::ready(Err(anyhow!(
+                "only versions of the same crate can be compared"
+            )));
+        }
+
+        let outputs = crate_specs
+            .iter()
+            .map(|crate_spec| {
+                // The item path is relative to the crate, unless the rest of
+                // the argument is only flags.
+                let argument = if rest.is_empty() || rest.starts_with("--") {
+                    format!("{crate_spec} {rest}")
+                } else {
+                    format!("{crate_spec}::{rest}")
+                };
+                Self::run_query(&argument, workspace.clone(), None, cx)
+            })
+            .collect::<Vec<_>>();
+
+        cx.foreground_executor().spawn(async move {
+            let outputs = join_all(outputs).await;
+
+            let mut text = String::new();
+            let mut sections = Vec::new();
+            for ((crate_name, version), output) in versions.into_iter().zip(outputs) {
+                if !text.is_empty() {
+                    text.push_str("\n\n");
+                }
+
+                let offset = text.len();
+                text.push_str(&format!("# {crate_name} {version}\n\n"));
+                match output {
+                    Ok(output) => {
+                        let docs_offset = text.len();
+                        text.push_str(&output.text);
+                        // The section for the docs also covers their label.
+                        sections.extend(output.sections.into_iter().map(|section| {
+                            let start = if section.range.start == 0 {
+                                offset
+                            } else {
+                                section.range.start + docs_offset
+                            };
+                            SlashCommandOutputSection {
+                                range: start..section.range.end + docs_offset,
+                                render_placeholder: section.render_placeholder,
+                            }
+                        }));
+                    }
+                    Err(err) => {
+                        text.push_str(&format!(
+                            "Failed to insert docs for `{crate_name}@{version}`: {err}"
+                        ));
+                    }
+                }
+            }
+
+            Ok(SlashCommandOutput {
+                text,
+                sections,
+                run_commands_in_text: false,
+            })
+        })
+    }
+
+    /// Runs the command for the given argument, unless the docs for the same
+    /// item are already in the active context, in which case they are either
+    /// skipped or scrolled to.
+    fn run_unless_duplicate(
+        argument: &str,
+        query_key: DocsQueryKey,
+        behavior: DuplicateDocsBehavior,
+        workspace: WeakView<Workspace>,
+        partial_output: Option<PartialOutputCallback>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let argument = argument.to_string();
+        // The context is being updated while the command runs, so it is only
+        // read once the command has yielded.
+        cx.spawn(|mut cx| async move {
+            let existing_docs = cx.update(|cx| {
+                let panel = workspace.upgrade()?.read(cx).panel::<AssistantPanel>(cx)?;
+                let position = panel
+                    .read(cx)
+                    .active_context(cx)?
+                    .read(cx)
+                    .inserted_slash_command_position(
+                        "rustdoc",
+                        |argument| argument.and_then(docs_query_key).as_ref() == Some(&query_key),
+                        cx,
+                    )?;
+                Some((panel, position))
+            })?;
+
+            let Some((panel, position)) = existing_docs else {
+                return cx
+                    .update(|cx| Self::run_argument(&argument, workspace, partial_output, cx))?
+                    .await;
+            };
+
+            let DocsQueryKey {
+                crate_name,
+                version,
+                item_path,
+            } = query_key;
+            let crate_spec = version.map_or(crate_name.clone(), |version| {
+                format!("{crate_name}@{version}")
+            });
+            let item = if item_path.is_empty() {
+                crate_spec
+            } else {
+                format!("{crate_spec}::{item_path}")
+            };
+            let text = match behavior {
+                DuplicateDocsBehavior::Scroll => {
+                    cx.update(|cx| {
+                        panel.update(cx, |panel, cx| panel.scroll_to_position(position, cx))
+                    })?;
+                    format!(
+                        "The docs for `{item}` are already in this conversation, so they were scrolled to instead of being inserted again."
+                    )
+                }
+                DuplicateDocsBehavior::Skip => format!(
+                    "Skipped inserting the docs for `{item}`, as they are already in this conversation."
+                ),
+                DuplicateDocsBehavior::Insert => {
+                    unreachable!("duplicate docs are inserted without checking for them")
+                }
+            };
+            let label = SharedString::from(format!("rustdoc: {item} (already inserted)"));
+
+            Ok(output_with_placeholder(text, move |id, unfold| {
+                RustdocLabelPlaceholder {
+                    id,
+                    unfold,
+                    label: label.clone(),
+                }
+                .into_any_element()
+            }))
+        })
+    }
+
+    fn run_query(
+        argument: &str,
+        workspace: WeakView<Workspace>,
+        partial_output: Option<PartialOutputCallback>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        let project = workspace.read(cx).project().clone();
+        let fs = project.read(cx).fs().clone();
+        // The client's HTTP client is built with the `proxy` setting, so docs.rs
+        // is reached through the user's proxy, if they configured one.
+        let http_client = workspace.read(cx).client().http_client();
+        let cargo_manifest_paths = Self::cargo_manifest_paths(workspace.read(cx), cx);
+        // Indexing and reading dependencies only use the preferred workspace.
+        let path_to_cargo_toml = cargo_manifest_paths.first().cloned();
+
+        let mut args = match RustdocArgs::parse(argument) {
+            Ok(args) => args,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        for path in [
+            &mut args.save_path,
+            &mut args.tarball_path,
+            &mut args.manifest_path,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *path = Self::worktree_path(&project, path, cx);
+        }
+
+        // Indexing and reading dependencies both require a Cargo workspace, so
+        // bail out early rather than failing after spawning the work. A crate
+        // is indexed from its tarball on its own, and from the given manifest
+        // regardless of the project.
+        let has_manifest = path_to_cargo_toml.is_some() || args.manifest_path.is_some();
+        if ((args.index && args.tarball_path.is_none()) || args.deps_tree) && !has_manifest {
+            let flag = match (args.index, args.reindex, args.index_lockfile) {
+                (true, _, true) => "--index-deps",
+                (true, true, false) => "--reindex",
+                (true, false, false) => "--index",
+                (false, _, _) => "--deps-tree",
+            };
+            let message = if project.read(cx).worktrees().next().is_none() {
+                format!("{flag} requires an open project with a Cargo.toml")
+            } else {
+                format!("{flag} requires a Cargo.toml at the root of a project folder")
+            };
+            return Task::ready(Err(anyhow!(message)));
+        }
+
+        if args.list {
+            return Self::run_list(cx);
+        }
+
+        if args.ping {
+            return Self::run_ping(http_client, cx);
+        }
+
+        if args.clear_cache {
+            let crate_name = (!args.item_path.is_empty()).then_some(args.item_path);
+            return Self::run_clear_cache(crate_name, fs, cx);
+        }
+
+        if args.clear_index {
+            let crate_name = (!args.item_path.is_empty()).then_some(args.item_path);
+            return Self::run_clear_index(crate_name, cx);
+        }
+
+        if args.deps_tree {
+            return Self::run_deps_tree(
+                args.item_path,
+                args.transitive,
+                fs,
+                path_to_cargo_toml,
+                cx,
+            );
+        }
 
-        let mut response = http_client
-            .get(
-                &format!("https://docs.rs/{path}"),
-                AsyncBody::default(),
-                true,
+        if args.index {
+            return Self::run_index(
+                args,
+                fs,
+                http_client,
+                path_to_cargo_toml,
+                partial_output,
+                cx,
+            );
+        }
+
+        let RustdocArgs {
+            item_path,
+            open,
+            features_list,
+            features,
+            target,
+            std_channel,
+            save_path,
+            mode,
+            include_since,
+            include_layout,
+            plain,
+            with_references,
+            item_kinds,
+            list_implementors,
+            all_implementors,
+            omit_see_also,
+            item_order,
+            expand_auto_impls,
+            ..
+        } = args;
+
+        // The generic arguments in the path are stripped for looking up the
+        // docs, but kept in the placeholder (e.g., `Vec<String>`). Macros may
+        // be typed as they are invoked (e.g., `tokio::select!`).
+        let parsed_item_path = match parse_item_path(item_path.trim_end_matches('!')) {
+            Ok(parsed_item_path) => parsed_item_path,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        // Commonly mistyped crate names are looked up under the name of the
+        // crate they're configured as an alias of.
+        let (crate_name, version) = match parse_crate_version(&parsed_item_path.segments[0]) {
+            Ok((crate_name, version)) => (
+                canonical_crate_name(&RustdocSettings::get_global(cx).crate_aliases, crate_name)
+                    .to_string(),
+                version,
+            ),
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let item_path = parsed_item_path.segments[1..].to_vec();
+        if std_channel.is_some() && !STANDARD_LIBRARY_CRATES.contains(&crate_name.as_str()) {
+            return Task::ready(Err(anyhow!(
+                "--channel only applies to the standard library ({crates})",
+                crates = STANDARD_LIBRARY_CRATES.join(", ")
+            )));
+        }
+        let all_items = mode == DocQueryMode::AllItems;
+        if all_items && !item_path.is_empty() {
+            return Task::ready(Err(anyhow!(
+                "--all lists every item in a crate, so it takes a crate name rather than an \
+                item path"
+            )));
+        }
+
+        // The docs are looked up by the name in the crate's item paths, while
+        // the placeholder shows the name as typed (e.g., `serde-json`).
+        let query = DocQuery {
+            include_since,
+            include_layout,
+            item_kinds,
+            list_implementors,
+            max_implementors: (!all_implementors).then_some(MAX_IMPLEMENTORS),
+            omit_see_also,
+            item_order,
+            expand_auto_impls,
+            ..DocQuery::new(normalize_crate_name(&crate_name), item_path.clone())
+                .with_version(version)
+                .with_target(target)
+                .with_mode(mode)
+        };
+        let cargo_workspace_roots = cargo_manifest_paths
+            .iter()
+            .filter_map(|path| path.parent().map(|path| path.to_path_buf()))
+            .collect::<Vec<_>>();
+        let lockfile_paths = cargo_workspace_roots
+            .iter()
+            .map(|root| root.join("Cargo.lock"))
+            .collect::<Vec<_>>();
+
+        let crate_name = SharedString::from(crate_name);
+        let version = query
+            .version
+            .as_ref()
+            .map(|version| SharedString::from(version.to_string()));
+        let module_path = if item_path.is_empty() {
+            None
+        } else {
+            Some(SharedString::from(
+                parsed_item_path.display_segments[1..].join("::"),
+            ))
+        };
+        let typed_path = parsed_item_path
+            .has_generic_args()
+            .then(|| parsed_item_path.display_segments.join("::"));
+
+        if open {
+            return Self::run_open(
+                query,
+                std_channel,
+                crate_name,
+                version,
+                module_path,
+                fs,
+                http_client,
+                cargo_workspace_roots,
+                cx,
+            );
+        }
+
+        if features_list {
+            return Self::run_features_list(
+                crate_name,
+                version,
+                fs,
+                http_client,
+                cargo_workspace_roots,
+                cx,
+            );
+        }
+
+        let mut settings = RustdocSettings::get_global(cx).clone();
+        if let Some(std_channel) = std_channel {
+            settings.std_channel = std_channel;
+        }
+        let rustdoc_store = RustdocStore::global(cx);
+
+        let mut stale_index_warning = None;
+        if let Some(age) = Self::stale_index_age(&rustdoc_store, &query, settings.index_max_age()) {
+            match (
+                settings.stale_index_behavior,
+                cargo_workspace_roots.first().cloned(),
+            ) {
+                (StaleIndexBehavior::Reindex, Some(cargo_workspace_root)) => {
+                    rustdoc_store
+                        .index(
+                            query.crate_name.clone(),
+                            Box::new(
+                                LocalProvider::new(fs.clone(), cargo_workspace_root)
+                                    .with_features(features.clone()),
+                            ),
+                        )
+                        .detach_and_log_err(cx);
+                }
+                _ => {
+                    stale_index_warning = Some(format!(
+                        "> **Warning:** The local docs for `{crate_name}` were indexed {age} ago \
+                        and may be out of date. Run `/rustdoc --reindex {crate_name}` to refresh them.",
+                        age = format_age(age)
+                    ));
+                }
+            }
+        }
+
+        let registered_providers = DocProviderRegistry::global(cx).providers();
+        let resolver = Arc::new(DocsResolver::new(
+            fs.clone(),
+            http_client,
+            Some(rustdoc_store.clone()),
+            &query,
+            ResolveDocsOptions {
+                settings: settings.clone(),
+                cargo_workspace_roots,
+                features: features.clone(),
+                extra_providers: registered_providers.clone(),
+                cache_dir: Some(RUSTDOC_CACHE_DIR.clone()),
+                lockfile_paths,
+            },
+        ));
+        let docs_dot_rs_provider = resolver.docs_dot_rs_provider().clone();
+        let local_cargo_workspace_roots = resolver.local_cargo_workspace_roots().to_vec();
+        let missing_item_hint = resolver.missing_item_hint().map(ToString::to_string);
+        let local_docs_paths = cx.background_executor().spawn(Self::local_docs_paths(
+            fs.clone(),
+            local_cargo_workspace_roots.clone(),
+            query.clone(),
+        ));
+
+        // The store doesn't know what an item references, and the docs for a
+        // parent module aren't a substitute for those of a referenced item.
+        let references_message = with_references.then(|| {
+            let mut local_reference_providers: Vec<Arc<dyn DocProvider>> = Vec::new();
+            for cargo_workspace_root in local_cargo_workspace_roots {
+                local_reference_providers.push(Arc::new(
+                    LocalProvider::new(fs.clone(), cargo_workspace_root)
+                        .with_features(features.clone()),
+                ));
+            }
+            local_reference_providers.extend(registered_providers);
+            let reference_providers = settings.source_order.arrange(
+                local_reference_providers,
+                vec![docs_dot_rs_provider.clone() as Arc<dyn DocProvider>],
+            );
+            cx.background_executor()
+                .spawn(Self::build_references_message(
+                    reference_providers,
+                    query.clone(),
+                ))
+        });
+
+        let docs_dot_rs_url = query.docs_url(&settings.std_channel);
+        let docs_link_site = settings.include_docs_rs_link.then(|| {
+            let docs_host = docs_host_for_crate(&settings.docs_hosts, &query.crate_name);
+            let site = if query.is_standard_library() {
+                "doc.rust-lang.org"
+            } else if let Some(docs_host) = docs_host {
+                docs_host
+                    .base_url
+                    .trim_start_matches("https://")
+                    .trim_start_matches("http://")
+                    .trim_end_matches('/')
+            } else {
+                "docs.rs"
+            };
+            site.to_string()
+        });
+        let query_path = std::iter::once(&query.crate_name)
+            .chain(&query.item_path)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("::");
+        let link_indexed_items = settings.link_indexed_items;
+        let max_docs_length = settings.max_docs_length;
+
+        let text = cx
+            .background_executor()
+            .spawn(async move { resolver.resolve(&query).await });
+
+        let argument = argument.to_string();
+        let weak_workspace = workspace.downgrade();
+        let fs = fs.clone();
+        cx.spawn(|mut cx| async move {
+            let (source, mut text) = match text.await {
+                Ok(docs) => docs,
+                // A typo in the item's path shouldn't fail the command, so the
+                // items with similar paths are suggested instead, along with
+                // the same path in the crates with similar names.
+                Err(err) if is_docs_not_found(&err) => {
+                    return Self::run_not_found(
+                        err,
+                        &argument,
+                        &query_path,
+                        typed_path.as_deref(),
+                        version.as_ref(),
+                        missing_item_hint.as_deref(),
+                        weak_workspace,
+                        fs,
+                        cargo_manifest_paths,
+                        &mut cx,
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    log::debug!("failed to fetch docs for {query_path}: {err:#}");
+                    return Err(match missing_item_hint {
+                        Some(missing_item_hint) => anyhow!("{err:#}. {missing_item_hint}"),
+                        None => err,
+                    });
+                }
+            };
+            // docs.rs redirects to the version and target that the docs were
+            // actually found at, e.g., from `latest` to the concrete version.
+            let resolved_url = docs_dot_rs_provider
+                .resolved_url(&docs_dot_rs_url)
+                .unwrap_or(docs_dot_rs_url.clone());
+            let version = docs_dot_rs_provider
+                .resolved_version(&docs_dot_rs_url)
+                .map(SharedString::from)
+                .or(version);
+            let resolved_url = docs_dot_rs_provider.host_url(&crate_name, &resolved_url);
+            // The link is to the version that the docs were fetched for, e.g.,
+            // the one locked in `Cargo.lock`.
+            let docs_link = docs_link_site.map(|site| format!("[View on {site}]({resolved_url})"));
+            let docs_url =
+                Self::fetched_docs_url(fs.clone(), source, resolved_url, local_docs_paths.await)
+                    .await;
+            log::debug!(
+                "fetched docs for {query_path} from {source:?} ({})",
+                docs_url.as_deref().unwrap_or("no URL")
+            );
+            // The saved docs aren't inserted, so they are kept in full.
+            if let Some(max_docs_length) = max_docs_length.filter(|_| save_path.is_none()) {
+                truncate_docs(&mut text, max_docs_length, docs_url.as_deref());
+            }
+            // Converting the docs to plain text or saving them replaces them,
+            // so they're no longer split into their parts.
+            let mut docs_sections = if plain || save_path.is_some() {
+                Vec::new()
+            } else if all_items {
+                split_item_list_sections(&text, MAX_ITEMS_PER_SECTION)
+            } else {
+                split_docs_sections(&text)
+            };
+            if let Some(references_message) = references_message {
+                // The referenced items may take a while to fetch, so the docs
+                // are shown without them in the meantime.
+                if let Some(partial_output) =
+                    partial_output.filter(|_| !plain && save_path.is_none())
+                {
+                    partial_output(SlashCommandOutput {
+                        text: format!("{text}\n\n_Loading the referenced items…_"),
+                        sections: Vec::new(),
+                        run_commands_in_text: false,
+                    });
+                }
+                match references_message.await {
+                    Ok(references_message) => text.push_str(&format!("\n\n{references_message}")),
+                    Err(err) => text.push_str(&format!(
+                        "\n\nFailed to fetch the items referenced by `{query_path}`: {err}"
+                    )),
+                }
+            }
+            if let Some(docs_link) = docs_link {
+                text.push_str(&format!("\n\n{docs_link}"));
+            }
+            if link_indexed_items {
+                let referenced_items = rustdoc_store
+                    .referenced_items(&text)
+                    .into_iter()
+                    .map(|(crate_name, item)| format!("{crate_name}::{}", item.display()))
+                    .filter(|item_path| item_path != &query_path)
+                    .collect::<Vec<_>>();
+                if !referenced_items.is_empty() {
+                    text.push_str("\n\nReferenced items that are indexed locally:\n");
+                    for item_path in referenced_items {
+                        text.push_str(&format!("\n- `/rustdoc {item_path}`"));
+                    }
+                }
+            }
+            if let Some(stale_index_warning) = stale_index_warning {
+                text.push_str(&format!("\n\n{stale_index_warning}"));
+            }
+            if plain {
+                text = convert_markdown_to_plain_text(&text);
+            }
+            if let Some(save_path) = save_path {
+                fs.atomic_write(save_path.clone(), text)
+                    .await
+                    .with_context(|| format!("failed to save docs to {save_path:?}"))?;
+                text = format!("Saved docs for `{query_path}` to `{}`", save_path.display());
+            }
+
+            // What was appended to the docs is part of their description.
+            match docs_sections.last_mut() {
+                Some(section) if section.kind == DocsSectionKind::Description => {
+                    section.range.end = text.len();
+                }
+                Some(section) if section.range.end < text.len() => {
+                    let start = section.range.end;
+                    docs_sections.push(DocsSection {
+                        kind: DocsSectionKind::Description,
+                        range: start..text.len(),
+                    });
+                }
+                Some(_) => {}
+                None => docs_sections.push(DocsSection {
+                    kind: DocsSectionKind::Description,
+                    range: 0..text.len(),
+                }),
+            }
+            let sections = docs_sections
+                .into_iter()
+                .map(|section| {
+                    let docs_url = docs_url.clone();
+                    let crate_name = crate_name.clone();
+                    let version = version.clone();
+                    let module_path = module_path.clone();
+                    SlashCommandOutputSection {
+                        range: section.range,
+                        render_placeholder: Arc::new(move |id, unfold, _cx| {
+                            RustdocPlaceholder {
+                                id,
+                                unfold,
+                                source,
+                                section_kind: section.kind,
+                                docs_url: docs_url.clone(),
+                                crate_name: crate_name.clone(),
+                                version: version.clone(),
+                                module_path: module_path.clone(),
+                            }
+                            .into_any_element()
+                        }),
+                    }
+                })
+                .collect();
+            Ok(SlashCommandOutput {
+                text,
+                sections,
+                run_commands_in_text: false,
+            })
+        })
+    }
+
+    /// Lists the indexed crates, with how long ago each was indexed.
+    fn run_list(cx: &mut WindowContext) -> Task<Result<SlashCommandOutput>> {
+        let max_age = RustdocSettings::get_global(cx).index_max_age();
+        let indexed_crates = RustdocStore::global(cx).indexed_crates();
+
+        let mut text = if indexed_crates.is_empty() {
+            "No crates have been indexed.".to_string()
+        } else {
+            format!("Indexed crates ({}):\n", indexed_crates.len())
+        };
+        for indexed_crate in indexed_crates {
+            let age = indexed_crate.indexed_at.elapsed().unwrap_or_default();
+            let is_stale = max_age.map_or(false, |max_age| age > max_age);
+            text.push_str(&format!(
+                "\n- `{name}`{version}: {item_count} items from {source}, indexed {age} \
+                ago{stale}",
+                name = indexed_crate.name,
+                version = indexed_crate
+                    .version
+                    .map(|version| format!(" {version}"))
+                    .unwrap_or_default(),
+                item_count = indexed_crate.item_count,
+                source = match indexed_crate.source {
+                    RustdocSource::Local => "the local docs",
+                    RustdocSource::DocsDotRs => "docs.rs",
+                    RustdocSource::CachedDocsDotRs => "cached docs.rs pages",
+                },
+                age = format_age(age),
+                stale = if is_stale { " (stale)" } else { "" }
+            ));
+        }
+
+        Task::ready(Ok(output_with_placeholder(text, move |id, unfold| {
+            RustdocLabelPlaceholder {
+                id,
+                unfold,
+                label: SharedString::from("rustdoc: indexed crates"),
+            }
+            .into_any_element()
+        })))
+    }
+
+    /// Checks whether docs.rs can be reached, and how quickly it responds.
+    fn run_ping(
+        http_client: Arc<HttpClientWithUrl>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let provider = DocsDotRsProvider::new(http_client);
+        let ping_task = cx
+            .background_executor()
+            .spawn(async move { provider.ping().await });
+
+        cx.foreground_executor().spawn(async move {
+            let text = match ping_task.await {
+                Ok(ping) if ping.is_success() => format!(
+                    "docs.rs is reachable: `{url}` responded with status {status} in {latency} ms.",
+                    url = ping.url,
+                    status = ping.status,
+                    latency = ping.latency.as_millis()
+                ),
+                Ok(ping) => format!(
+                    "docs.rs is reachable, but `{url}` responded with status {status} in \
+                    {latency} ms, so docs.rs or a proxy in between may be having problems.",
+                    url = ping.url,
+                    status = ping.status,
+                    latency = ping.latency.as_millis()
+                ),
+                Err(err) => format!(
+                    "docs.rs is not reachable: {err:#}. Check your network connection and \
+                    proxy settings."
+                ),
+            };
+            Ok(output_with_placeholder(text, move |id, unfold| {
+                RustdocLabelPlaceholder {
+                    id,
+                    unfold,
+                    label: SharedString::from("rustdoc: docs.rs connectivity"),
+                }
+                .into_any_element()
+            }))
+        })
+    }
+
+    /// Removes the cached docs.rs pages for the given crate, or for every crate.
+    fn run_clear_cache(
+        crate_name: Option<String>,
+        fs: Arc<dyn Fs>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let cache = DocsCache::new(fs, RUSTDOC_CACHE_DIR.clone());
+        // The pages that weren't found are looked up again, too.
+        match crate_name.as_deref() {
+            Some(crate_name) => NotFoundCache::global().remove_crate(crate_name),
+            None => NotFoundCache::global().clear(),
+        }
+        let clear_task = cx.background_executor().spawn({
+            let crate_name = crate_name.clone();
+            async move { cache.clear(crate_name.as_deref()).await }
+        });
+
+        cx.foreground_executor().spawn(async move {
+            let cleared = clear_task.await?;
+            let text = format!(
+                "Cleared {entry_count} cached {pages} ({size}) for {crate_name}",
+                entry_count = cleared.entry_count,
+                pages = if cleared.entry_count == 1 {
+                    "page"
+                } else {
+                    "pages"
+                },
+                size = format_size(cleared.byte_count),
+                crate_name = crate_name
+                    .as_ref()
+                    .map_or("all crates".to_string(), |crate_name| format!(
+                        "`{crate_name}`"
+                    ))
+            );
+            Ok(output_with_placeholder(text, move |id, unfold| {
+                RustdocLabelPlaceholder {
+                    id,
+                    unfold,
+                    label: SharedString::from("rustdoc: cleared cache"),
+                }
+                .into_any_element()
+            }))
+        })
+    }
+
+    /// Removes the given crate, or every crate, from the index.
+    fn run_clear_index(
+        crate_name: Option<String>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let rustdoc_store = RustdocStore::global(cx);
+        let clear_task = match crate_name.clone() {
+            Some(crate_name) => rustdoc_store.remove(crate_name),
+            None => rustdoc_store.clear(),
+        };
+
+        cx.foreground_executor().spawn(async move {
+            let removed_count = clear_task.await?;
+            let text = format!(
+                "Removed {removed_count} indexed {items} for {crate_name}",
+                items = if removed_count == 1 { "item" } else { "items" },
+                crate_name = crate_name
+                    .as_ref()
+                    .map_or("all crates".to_string(), |crate_name| format!(
+                        "`{crate_name}`"
+                    ))
+            );
+            Ok(output_with_placeholder(text, move |id, unfold| {
+                RustdocLabelPlaceholder {
+                    id,
+                    unfold,
+                    label: SharedString::from("rustdoc: cleared index"),
+                }
+                .into_any_element()
+            }))
+        })
+    }
+
+    /// Lists the dependencies of the given crate in the `Cargo.lock` of the
+    /// given workspace, either directly or transitively.
+    fn run_deps_tree(
+        crate_name: String,
+        transitive: bool,
+        fs: Arc<dyn Fs>,
+        path_to_cargo_toml: Option<Arc<Path>>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        if crate_name.is_empty() {
+            return Task::ready(Err(anyhow!("no crate name provided to --deps-tree")));
+        }
+
+        let crate_name = SharedString::from(crate_name);
+        let dependencies_task = cx.background_executor().spawn({
+            let crate_name = crate_name.clone();
+            async move {
+                let cargo_workspace_root = path_to_cargo_toml
+                    .and_then(|path| path.parent().map(|path| path.to_path_buf()))
+                    .ok_or_else(|| anyhow!("no Cargo workspace root found"))?;
+
+                let lockfile = fs
+                    .load(&cargo_workspace_root.join("Cargo.lock"))
+                    .await
+                    .context("failed to read Cargo.lock")?;
+                let dependencies = crate_dependencies(&lockfile, &crate_name, transitive)?;
+
+                let mut text = format!(
+                    "{kind} dependencies of `{crate_name}`:\n",
+                    kind = if transitive { "All" } else { "Direct" }
+                );
+                for dependency in dependencies {
+                    text.push_str(&format!(
+                        "\n- `{name}` {version}",
+                        name = dependency.name,
+                        version = dependency.version
+                    ));
+                }
+
+                anyhow::Ok(text)
+            }
+        });
+
+        cx.foreground_executor().spawn(async move {
+            let text = dependencies_task.await?;
+            Ok(output_with_placeholder(text, move |id, unfold| {
+                RustdocPlaceholder {
+                    id,
+                    unfold,
+                    source: RustdocSource::Local,
+                    docs_url: None,
+                    crate_name: crate_name.clone(),
+                    section_kind: DocsSectionKind::Description,
+                    version: None,
+                    module_path: None,
+                }
+                .into_any_element()
+            }))
+        })
+    }
+
+    /// Indexes the docs for the crate, or the set of crates, that the given
+    /// arguments are for.
+    fn run_index(
+        args: RustdocArgs,
+        fs: Arc<dyn Fs>,
+        http_client: Arc<HttpClientWithUrl>,
+        path_to_cargo_toml: Option<Arc<Path>>,
+        partial_output: Option<PartialOutputCallback>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let RustdocArgs {
+            item_path,
+            reindex,
+            include_deps,
+            index_workspace,
+            index_lockfile,
+            from_docs_rs,
+            from_json,
+            build,
+            features,
+            tarball_path,
+            manifest_path,
+            item_order,
+            ..
+        } = args;
+
+        let crate_name_to_index = match (item_path.is_empty(), include_deps, index_workspace) {
+            _ if index_lockfile => SharedString::from("dependencies"),
+            (false, _, _) => SharedString::from(item_path),
+            (true, _, true) => SharedString::from("workspace crates"),
+            (true, true, false) => SharedString::from("all crates"),
+            (true, false, false) => {
+                let flag = if reindex { "--reindex" } else { "--index" };
+                return Task::ready(Err(anyhow!("no crate name provided to {flag}")));
+            }
+        };
+
+        let settings = RustdocSettings::get_global(cx).clone();
+        let index_task = cx.background_executor().spawn({
+            let rustdoc_store = RustdocStore::global(cx);
+            let build_progress = cargo_doc_progress(partial_output, &crate_name_to_index);
+            let fs = fs.clone();
+            let http_client = http_client.clone();
+            let crate_name_to_index = crate_name_to_index.clone();
+            let features = features.clone();
+            async move {
+                if let Some(tarball_path) = tarball_path {
+                    let provider = TarballProvider::new(
+                        fs.clone(),
+                        tarball_path.clone(),
+                        RUSTDOC_TARBALLS_DIR.clone(),
+                    )
+                    .with_features(features);
+                    let with_features = describe_features(provider.features());
+                    provider.build_docs(&build_progress).await?;
+
+                    let crate_name = normalize_crate_name(&crate_name_to_index);
+                    match provider.crate_features().await {
+                        Ok(features) => rustdoc_store.set_crate_features(&crate_name, features),
+                        Err(err) => {
+                            log::error!("failed to read the features of {crate_name}: {err:?}")
+                        }
+                    }
+                    let replaced_count = if reindex {
+                        rustdoc_store.remove(crate_name.clone()).await?
+                    } else {
+                        0
+                    };
+                    rustdoc_store
+                        .index(crate_name.clone(), Box::new(provider))
+                        .await?;
+
+                    let tarball_name = tarball_path.file_name().map_or_else(
+                        || tarball_path.display().to_string(),
+                        |file_name| file_name.to_string_lossy().to_string(),
+                    );
+                    if replaced_count > 0 {
+                        return anyhow::Ok((
+                            format!(
+                                "Re-indexed {crate_name}{with_features} from \
+                                `{tarball_name}`, replacing the {replaced_count} \
+                                previously indexed items"
+                            ),
+                            true,
+                        ));
+                    }
+                    return anyhow::Ok((
+                        format!("Indexed {crate_name}{with_features} from `{tarball_name}`"),
+                        false,
+                    ));
+                }
+
+                // The manifest is validated before indexing, so that a
+                // mistyped path doesn't fall back to the project's crates.
+                let cargo_workspace_root = match manifest_path {
+                    Some(manifest_path) => manifest_root(fs.as_ref(), &manifest_path).await?,
+                    None => path_to_cargo_toml
+                        .and_then(|path| path.parent().map(|path| path.to_path_buf()))
+                        .ok_or_else(|| anyhow!("no Cargo workspace root found"))?,
+                };
+
+                let provider = LocalProvider::new(fs.clone(), cargo_workspace_root.clone())
+                    .with_features(features);
+                let with_features = describe_features(provider.features());
+
+                if index_workspace {
+                    let crate_names =
+                        workspace_member_crates(fs.as_ref(), &cargo_workspace_root).await?;
+
+                    // The crates are indexed concurrently, and keep going
+                    // past the crates that fail to index, so that one broken
+                    // crate doesn't hold up the rest.
+                    let results = stream::iter(crate_names)
+                        .map(|crate_name| {
+                            let provider = &provider;
+                            let rustdoc_store = &rustdoc_store;
+                            async move {
+                                let mut replaced = false;
+                                if reindex {
+                                    match rustdoc_store.remove(crate_name.clone()).await {
+                                        Ok(removed_count) => replaced = removed_count > 0,
+                                        Err(err) => log::error!(
+                                            "failed to remove {crate_name} from the index: \
+                                            {err:?}"
+                                        ),
+                                    }
+                                }
+                                let provider =
+                                    provider.clone().with_docs_rs_metadata(&crate_name).await;
+                                let indexed = match provider.resolve_crate_name(&crate_name).await {
+                                    Ok(crate_name) => {
+                                        rustdoc_store.index(crate_name, Box::new(provider)).await
+                                    }
+                                    Err(err) => Err(err),
+                                };
+                                (crate_name, replaced, indexed)
+                            }
+                        })
+                        .buffered(rustdoc_store.max_concurrent_indexing())
+                        .collect::<Vec<_>>()
+                        .await;
+
+                    let mut indexed_crates = Vec::new();
+                    let mut failed_crates = Vec::new();
+                    let mut replaced_existing_index = false;
+                    for (crate_name, replaced, indexed) in results {
+                        replaced_existing_index |= replaced;
+                        match indexed {
+                            Ok(()) => indexed_crates.push(crate_name),
+                            Err(err) => {
+                                log::error!("failed to index {crate_name}: {err:?}");
+                                failed_crates.push((crate_name, err));
+                            }
+                        }
+                    }
+
+                    let mut text = format!(
+                        "{verb} {count} workspace crates{with_features}",
+                        verb = if replaced_existing_index {
+                            "Re-indexed"
+                        } else {
+                            "Indexed"
+                        },
+                        count = indexed_crates.len()
+                    );
+                    if !indexed_crates.is_empty() {
+                        text.push_str(":\n");
+                        for crate_name in indexed_crates {
+                            text.push_str(&format!("\n- `{crate_name}`"));
+                        }
+                    }
+                    if !failed_crates.is_empty() {
+                        text.push_str(&format!(
+                            "\n\nFailed to index {count} workspace crates:\n",
+                            count = failed_crates.len()
+                        ));
+                        for (crate_name, err) in failed_crates {
+                            text.push_str(&format!("\n- `{crate_name}`: {err:#}"));
+                        }
+                    }
+
+                    return anyhow::Ok((text, replaced_existing_index));
+                }
+
+                if index_lockfile {
+                    let lockfile = fs
+                        .load(&cargo_workspace_root.join("Cargo.lock"))
+                        .await
+                        .context("failed to read Cargo.lock")?;
+                    let dependencies = locked_dependencies(&lockfile)?;
+
+                    // `target/doc` only has the docs for one version of each
+                    // crate, and the index doesn't tell them apart.
+                    let mut seen_crate_names = HashSet::default();
+                    let dependencies = dependencies
+                        .into_iter()
+                        .filter(|dependency| {
+                            seen_crate_names.insert(dependency.name.replace('-', "_"))
+                        })
+                        .collect::<Vec<_>>();
+
+                    // The dependencies are indexed concurrently, and yield
+                    // `None` when they are skipped.
+                    let results = stream::iter(dependencies)
+                        .map(|dependency| {
+                            let provider = &provider;
+                            let rustdoc_store = &rustdoc_store;
+                            let http_client = &http_client;
+                            let fs = &fs;
+                            let settings = &settings;
+                            async move {
+                                let crate_name = dependency.name.replace('-', "_");
+                                let crate_spec =
+                                    format!("{}@{}", dependency.name, dependency.version);
+
+                                let (crate_name, crate_provider): (
+                                    String,
+                                    Box<dyn RustdocProvider + Send + Sync>,
+                                ) = match provider.resolve_crate_name(&crate_name).await {
+                                    Ok(crate_name) => (crate_name, Box::new(provider.clone())),
+                                    Err(_) if from_docs_rs => {
+                                        let provider = DocsDotRsProvider::new(http_client.clone())
+                                            .with_cache(
+                                                DocsCache::new(
+                                                    fs.clone(),
+                                                    RUSTDOC_CACHE_DIR.clone(),
+                                                )
+                                                .with_policy(settings.cache_policy()),
+                                            )
+                                            .with_max_body_size(settings.max_response_size())
+                                            .with_timeout(settings.request_timeout())
+                                            .with_crawl_version(dependency.version.clone())
+                                            .with_hosts(settings.docs_hosts.clone());
+                                        (crate_name, Box::new(provider))
+                                    }
+                                    Err(_) => return (crate_spec, false, None),
+                                };
+
+                                let mut replaced = false;
+                                if reindex {
+                                    match rustdoc_store.remove(crate_name.clone()).await {
+                                        Ok(removed_count) => replaced = removed_count > 0,
+                                        Err(err) => log::error!(
+                                            "failed to remove {crate_name} from the index: \
+                                            {err:?}"
+                                        ),
+                                    }
+                                }
+                                let indexed = rustdoc_store.index(crate_name, crate_provider).await;
+                                (crate_spec, replaced, Some(indexed))
+                            }
+                        })
+                        .buffered(rustdoc_store.max_concurrent_indexing())
+                        .collect::<Vec<_>>()
+                        .await;
+
+                    let mut indexed_crates = Vec::new();
+                    let mut skipped_crates = Vec::new();
+                    let mut failed_crates = Vec::new();
+                    let mut replaced_existing_index = false;
+                    for (crate_spec, replaced, indexed) in results {
+                        replaced_existing_index |= replaced;
+                        match indexed {
+                            Some(Ok(())) => indexed_crates.push(crate_spec),
+                            Some(Err(err)) => {
+                                log::error!("failed to index {crate_spec}: {err:?}");
+                                failed_crates.push((crate_spec, err));
+                            }
+                            None => skipped_crates.push(crate_spec),
+                        }
+                    }
+
+                    let mut text = format!(
+                        "{verb} {indexed} dependencies from Cargo.lock{with_features}, \
+                        skipped {skipped} without local docs, and failed to index {failed}",
+                        verb = if replaced_existing_index {
+                            "Re-indexed"
+                        } else {
+                            "Indexed"
+                        },
+                        indexed = indexed_crates.len(),
+                        skipped = skipped_crates.len(),
+                        failed = failed_crates.len()
+                    );
+                    if !indexed_crates.is_empty() {
+                        text.push_str("\n\nIndexed:\n");
+                        for crate_spec in indexed_crates {
+                            text.push_str(&format!("\n- `{crate_spec}`"));
+                        }
+                    }
+                    if !skipped_crates.is_empty() {
+                        text.push_str(
+                            "\n\nSkipped, as there are no local docs for them (run \
+                            `cargo doc`, or add `--docs-rs` to fetch them from docs.rs):\n",
+                        );
+                        for crate_spec in skipped_crates {
+                            text.push_str(&format!("\n- `{crate_spec}`"));
+                        }
+                    }
+                    if !failed_crates.is_empty() {
+                        text.push_str("\n\nFailed:\n");
+                        for (crate_spec, err) in failed_crates {
+                            text.push_str(&format!("\n- `{crate_spec}`: {err:#}"));
+                        }
+                    }
+
+                    return anyhow::Ok((text, replaced_existing_index));
+                }
+
+                // The rustdoc JSON is read in place of the HTML docs whenever
+                // it has been built, as its items don't need scraping.
+                let json_provider = JsonProvider::new(fs.clone(), cargo_workspace_root.clone())
+                    .with_item_order(item_order);
+                if !include_deps
+                    && (from_json || json_provider.has_json(&crate_name_to_index).await)
+                {
+                    let crate_name = normalize_crate_name(&crate_name_to_index);
+                    if !json_provider.has_json(&crate_name).await {
+                        return Err(anyhow!(
+                            "no rustdoc JSON found for `{crate_name}` at {path:?}. Build it \
+                            with `cargo +nightly rustdoc -p {crate_name_to_index} -- \
+                            -Z unstable-options --output-format json`",
+                            path = json_provider.json_path(&crate_name).await
+                        ));
+                    }
+                    match provider.crate_features(&crate_name).await {
+                        Ok(Some(features)) => {
+                            rustdoc_store.set_crate_features(&crate_name, features)
+                        }
+                        Ok(None) => {}
+                        Err(err) => {
+                            log::error!("failed to read the features of {crate_name}: {err:?}")
+                        }
+                    }
+                    let replaced_count = if reindex {
+                        rustdoc_store.remove(crate_name.clone()).await?
+                    } else {
+                        0
+                    };
+                    rustdoc_store
+                        .index(crate_name.clone(), Box::new(json_provider))
+                        .await?;
+
+                    if replaced_count > 0 {
+                        return anyhow::Ok((
+                            format!(
+                                "Re-indexed {crate_name} from rustdoc JSON, replacing \
+                                the {replaced_count} previously indexed items"
+                            ),
+                            true,
+                        ));
+                    }
+                    return anyhow::Ok((format!("Indexed {crate_name} from rustdoc JSON"), false));
+                }
+
+                if !include_deps {
+                    let provider = provider.with_docs_rs_metadata(&crate_name_to_index).await;
+                    // The docs are only built when they're missing, as
+                    // building them is slow even when they're up to date.
+                    let mut build_output = None;
+                    if build
+                        && provider
+                            .resolve_crate_name(&crate_name_to_index)
+                            .await
+                            .is_err()
+                    {
+                        build_output = Some(
+                            provider
+                                .build_docs(&crate_name_to_index, &build_progress)
+                                .await?,
+                        );
+                    }
+                    let crate_name = provider.resolve_crate_name(&crate_name_to_index).await?;
+                    match provider.crate_features(&crate_name).await {
+                        Ok(Some(features)) => {
+                            rustdoc_store.set_crate_features(&crate_name, features)
+                        }
+                        Ok(None) => {}
+                        Err(err) => {
+                            log::error!("failed to read the features of {crate_name}: {err:?}")
+                        }
+                    }
+                    let replaced_count = if reindex {
+                        rustdoc_store.remove(crate_name.clone()).await?
+                    } else {
+                        0
+                    };
+                    rustdoc_store
+                        .index(crate_name.clone(), Box::new(provider))
+                        .await?;
+
+                    let mut text = if replaced_count > 0 {
+                        format!(
+                            "Re-indexed {crate_name}{with_features}, replacing the \
+                            {replaced_count} previously indexed items"
+                        )
+                    } else {
+                        format!("Indexed {crate_name}{with_features}")
+                    };
+                    if let Some(build_output) = build_output.filter(|output| !output.is_empty()) {
+                        text.push_str(&format!(
+                            ", after building its docs with `cargo doc`:\n\n```\n\
+                            {build_output}\n```"
+                        ));
+                    }
+                    return anyhow::Ok((text, replaced_count > 0));
+                }
+
+                let mut indexed_crate_count = 0;
+                let mut replaced_crate_count = 0;
+                for crate_name in provider.crate_names().await? {
+                    if reindex {
+                        match rustdoc_store.remove(crate_name.clone()).await {
+                            Ok(0) => {}
+                            Ok(_) => replaced_crate_count += 1,
+                            Err(err) => {
+                                log::error!("failed to remove {crate_name} from the index: {err:?}")
+                            }
+                        }
+                    }
+                    match rustdoc_store
+                        .index(crate_name.clone(), Box::new(provider.clone()))
+                        .await
+                    {
+                        Ok(()) => indexed_crate_count += 1,
+                        Err(err) => log::error!("failed to index {crate_name}: {err:?}"),
+                    }
+                }
+
+                if replaced_crate_count > 0 {
+                    return anyhow::Ok((
+                        format!(
+                            "Re-indexed {indexed_crate_count} crates{with_features}, \
+                            replacing the existing index of {replaced_crate_count} of them"
+                        ),
+                        true,
+                    ));
+                }
+                anyhow::Ok((
+                    format!("Indexed {indexed_crate_count} crates{with_features}"),
+                    false,
+                ))
+            }
+        });
+
+        cx.foreground_executor().spawn(async move {
+            let (text, replaced_existing_index) = index_task.await?;
+            let crate_name_to_index = if replaced_existing_index {
+                SharedString::from(format!("{crate_name_to_index} (re-indexed)"))
+            } else {
+                crate_name_to_index
+            };
+            Ok(output_with_placeholder(text, move |id, unfold| {
+                RustdocIndexPlaceholder {
+                    id,
+                    unfold,
+                    source: RustdocSource::Local,
+                    crate_name: crate_name_to_index.clone(),
+                }
+                .into_any_element()
+            }))
+        })
+    }
+
+    /// Opens the docs for the given query in the browser, preferring the local
+    /// docs when there are any.
+    #[allow(clippy::too_many_arguments)]
+    fn run_open(
+        query: DocQuery,
+        std_channel: Option<String>,
+        crate_name: SharedString,
+        version: Option<SharedString>,
+        module_path: Option<SharedString>,
+        fs: Arc<dyn Fs>,
+        http_client: Arc<HttpClientWithUrl>,
+        cargo_workspace_roots: Vec<PathBuf>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let settings = RustdocSettings::get_global(cx);
+        let std_channel = std_channel.unwrap_or_else(|| settings.std_channel.clone());
+        let provider = DocsDotRsProvider::new(http_client).with_hosts(settings.docs_hosts.clone());
+        let url = cx.background_executor().spawn(async move {
+            let local_docs_paths =
+                Self::local_docs_paths(fs.clone(), cargo_workspace_roots, query.clone()).await;
+            Self::resolve_docs_url(provider, fs, query, std_channel, local_docs_paths).await
+        });
+
+        cx.spawn(|mut cx| async move {
+            let (source, url) = url.await?;
+            cx.update(|cx| cx.open_url(&url))?;
+
+            let text = format!("Opened {url}");
+            let docs_url = SharedString::from(url);
+            Ok(output_with_placeholder(text, move |id, unfold| {
+                RustdocPlaceholder {
+                    id,
+                    unfold,
+                    source,
+                    docs_url: Some(docs_url.clone()),
+                    crate_name: crate_name.clone(),
+                    section_kind: DocsSectionKind::Description,
+                    version: version.clone(),
+                    module_path: module_path.clone(),
+                }
+                .into_any_element()
+            }))
+        })
+    }
+
+    /// Lists the features of the given crate, and which of them its docs were
+    /// built with.
+    fn run_features_list(
+        crate_name: SharedString,
+        version: Option<SharedString>,
+        fs: Arc<dyn Fs>,
+        http_client: Arc<HttpClientWithUrl>,
+        cargo_workspace_roots: Vec<PathBuf>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let settings = RustdocSettings::get_global(cx);
+        let docs_dot_rs_provider = DocsDotRsProvider::new(http_client)
+            .with_cache(
+                DocsCache::new(fs.clone(), RUSTDOC_CACHE_DIR.clone())
+                    .with_policy(settings.cache_policy()),
             )
-            .await?;
+            .with_max_body_size(settings.max_response_size())
+            .with_timeout(settings.request_timeout())
+            .with_hosts(settings.docs_hosts.clone());
+        let text = cx.background_executor().spawn(Self::build_features_message(
+            RustdocStore::global(cx),
+            fs,
+            cargo_workspace_roots,
+            docs_dot_rs_provider,
+            crate_name.to_string(),
+            version.as_ref().map(ToString::to_string),
+        ));
+
+        cx.foreground_executor().spawn(async move {
+            let (source, text) = text.await?;
+            Ok(output_with_placeholder(text, move |id, unfold| {
+                RustdocPlaceholder {
+                    id,
+                    unfold,
+                    source,
+                    docs_url: None,
+                    crate_name: crate_name.clone(),
+                    section_kind: DocsSectionKind::Description,
+                    version: version.clone(),
+                    module_path: None,
+                }
+                .into_any_element()
+            }))
+        })
+    }
+
+    /// Returns the output for a query whose docs weren't found, which is the docs
+    /// for the crate with the most similar name when there's only one, or else
+    /// the similar items and crates to try instead.
+    ///
+    /// The docs for the corrected crate are queried with the argument as typed,
+    /// with only its crate replaced, so the same flags apply to them.
+    #[allow(clippy::too_many_arguments)]
+    async fn run_not_found(
+        err: anyhow::Error,
+        argument: &str,
+        query_path: &str,
+        typed_path: Option<&str>,
+        version: Option<&SharedString>,
+        missing_item_hint: Option<&str>,
+        workspace: WeakView<Workspace>,
+        fs: Arc<dyn Fs>,
+        cargo_manifest_paths: Vec<Arc<Path>>,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<SlashCommandOutput> {
+        let (queried_crate, queried_item) = query_path.split_once("::").unwrap_or((query_path, ""));
+        // A crate that is neither indexed nor locked may be a
+        // misspelling of one that is, whose docs are inserted
+        // instead when it's the only one with a similar name.
+        let known_crate_names = Self::project_crate_names(fs, cargo_manifest_paths)
+            .await
+            .into_iter()
+            .chain(STANDARD_LIBRARY_CRATES.map(ToString::to_string))
+            .collect();
+        let rustdoc_store = cx.update(|cx| RustdocStore::global(cx))?;
+        let corrections = rustdoc_store
+            .crate_name_corrections(queried_crate.to_string(), known_crate_names)
+            .await;
+        if let [corrected_crate] = corrections.as_slice() {
+            let note = format!(
+                "> **Note:** There's no crate named `{queried_crate}`, so these are \
+                the docs for `{corrected_crate}`, the crate with the most similar \
+                name.\n\n"
+            );
+            let argument = with_crate_name(argument, corrected_crate);
+            let output = cx
+                .update(|cx| Self::run_query(&argument, workspace, None, cx))?
+                .await?;
+            return Ok(with_note(output, &note));
+        }
+
+        let mut suggestions = corrections;
+        suggestions.extend(
+            rustdoc_store
+                .similar_crate_names(queried_crate.to_string(), MAX_NOT_FOUND_SUGGESTIONS)
+                .await
+                .into_iter()
+                .filter(|crate_name| !suggestions.contains(crate_name))
+                .collect::<Vec<_>>(),
+        );
+        let mut suggestions = suggestions
+            .into_iter()
+            .map(|crate_name| match queried_item {
+                "" => crate_name,
+                _ => format!("{crate_name}::{queried_item}"),
+            })
+            .collect::<Vec<_>>();
+        let similar_items = rustdoc_store
+            .search(
+                query_path.to_string(),
+                MAX_NOT_FOUND_SUGGESTIONS,
+                false,
+                None,
+                Arc::new(AtomicBool::new(false)),
+            )
+            .await
+            .into_iter()
+            .map(|result| format!("{}::{}", result.crate_name, result.item.display()))
+            .filter(|item_path| item_path != query_path)
+            .collect::<Vec<_>>();
+        // The similar items may repeat the paths in the suggested crates,
+        // so each suggestion is kept once, where it was first found.
+        let mut seen_suggestions = HashSet::default();
+        suggestions.extend(similar_items);
+        suggestions.retain(|suggestion| seen_suggestions.insert(suggestion.clone()));
+        suggestions.truncate(MAX_NOT_FOUND_SUGGESTIONS);
+        let text = Self::not_found_message(
+            query_path,
+            typed_path,
+            &err,
+            &suggestions,
+            missing_item_hint,
+        );
+        // Every source was tried, so the placeholder doesn't name one.
+        let label = SharedString::from(format!(
+            "rustdoc: {query_path}{version} (not found)",
+            version = version
+                .map(|version| format!(" {version}"))
+                .unwrap_or_default()
+        ));
+        Ok(output_with_placeholder(text, move |id, unfold| {
+            RustdocLabelPlaceholder {
+                id,
+                unfold,
+                label: label.clone(),
+            }
+            .into_any_element()
+        }))
+    }
 
-        let mut body = Vec::new();
-        response
-            .body_mut()
-            .read_to_end(&mut body)
-            .await
-            .context("error reading docs.rs response body")?;
+    /// Resolves the given path relative to the first worktree in the project.
+    fn worktree_path(project: &Model<Project>, path: &Path, cx: &AppContext) -> PathBuf {
+        match project.read(cx).worktrees().next() {
+            Some(worktree) => worktree.read(cx).abs_path().join(path),
+            None => path.to_path_buf(),
+        }
+    }
 
-        if response.status().is_client_error() {
-            let text = String::from_utf8_lossy(body.as_slice());
-            bail!(
-                "status error {}, response: {text:?}",
-                response.status().as_u16()
-            );
+    /// Returns the names of the crates in the `Cargo.lock`s next to the given
+    /// manifests, skipping the manifests without one.
+    async fn project_crate_names(
+        fs: Arc<dyn Fs>,
+        manifest_paths: Vec<Arc<Path>>,
+    ) -> HashSet<String> {
+        let mut crate_names = HashSet::default();
+        for manifest_path in manifest_paths {
+            let Some(lockfile_path) = manifest_path.parent().map(|dir| dir.join("Cargo.lock"))
+            else {
+                continue;
+            };
+            let Ok(lockfile) = fs.load(&lockfile_path).await else {
+                continue;
+            };
+            if let Some(names) = locked_crate_names(&lockfile).log_err() {
+                crate_names.extend(names);
+            }
         }
+        crate_names
+    }
 
-        let (markdown, _items) = convert_rustdoc_to_markdown(&body[..])?;
+    /// Returns the paths to the `Cargo.toml`s at the roots of the project's
+    /// worktrees, starting with the one containing the active item, so that
+    /// the Cargo workspace being worked on is preferred.
+    fn cargo_manifest_paths(workspace: &Workspace, cx: &AppContext) -> Vec<Arc<Path>> {
+        let project = workspace.project().read(cx);
+        let active_worktree_id = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+            .map(|path| path.worktree_id);
 
-        Ok((RustdocSource::DocsDotRs, markdown))
-    }
+        let mut manifest_paths = project
+            .worktrees()
+            .filter_map(|worktree| {
+                let worktree = worktree.read(cx);
+                let root_entry = worktree.root_entry()?;
+                // A worktree for a single file can only be a manifest itself.
+                let entry = if root_entry.is_file() {
+                    Some(root_entry).filter(|_| worktree.root_name() == "Cargo.toml")?
+                } else {
+                    worktree.entry_for_path("Cargo.toml")?
+                };
+                let path = ProjectPath {
+                    worktree_id: worktree.id(),
+                    path: entry.path.clone(),
+                };
+                let path = project.absolute_path(&path, cx)?;
+                Some((worktree.id(), Arc::from(path.as_path())))
+            })
+            .collect::<Vec<_>>();
+        manifest_paths.sort_by_key(|(worktree_id, _)| Some(*worktree_id) != active_worktree_id);
 
-    fn path_to_cargo_toml(project: Model<Project>, cx: &mut AppContext) -> Option<Arc<Path>> {
-        let worktree = project.read(cx).worktrees().next()?;
-        let worktree = worktree.read(cx);
-        let entry = worktree.entry_for_path("Cargo.toml")?;
-        let path = ProjectPath {
-            worktree_id: worktree.id(),
-            path: entry.path.clone(),
-        };
-        Some(Arc::from(
-            project.read(cx).absolute_path(&path, cx)?.as_path(),
-        ))
+        manifest_paths
+            .into_iter()
+            .map(|(_, manifest_path)| manifest_path)
+            .collect()
     }
 }
 
@@ -117,158 +2359,625 @@ impl SlashCommand for RustdocSlashCommand {
     fn complete_argument(
         &self,
         query: String,
-        _cancel: Arc<AtomicBool>,
-        _workspace: Option<WeakView<Workspace>>,
+        cancel: Arc<AtomicBool>,
+        workspace: Option<WeakView<Workspace>>,
         cx: &mut AppContext,
-    ) -> Task<Result<Vec<String>>> {
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
         let store = RustdocStore::global(cx);
+        let max_completions = RustdocSettings::get_global(cx).max_completions;
+
+        // `--project` limits the completions to the crates in the project's
+        // `Cargo.lock`, leaving out the ones indexed for other projects.
+        let (query, project_crates) = match query.strip_prefix("--project") {
+            Some(rest) if rest.is_empty() || rest.starts_with(' ') => {
+                let project_crates =
+                    workspace
+                        .and_then(|workspace| workspace.upgrade())
+                        .map(|workspace| {
+                            let workspace = workspace.read(cx);
+                            let fs = workspace.project().read(cx).fs().clone();
+                            let manifest_paths = Self::cargo_manifest_paths(workspace, cx);
+                            Self::project_crate_names(fs, manifest_paths)
+                        });
+                (rest.trim_start().to_string(), project_crates)
+            }
+            _ => (query, None),
+        };
+        // Macros are matched by their name, without the `!` they are invoked
+        // with.
+        let query = query.trim_end_matches('!').to_string();
+
+        let executor = cx.background_executor().clone();
         cx.background_executor().spawn(async move {
-            let items = store.search(query).await;
-            Ok(items
+            if !debounce_search(executor.timer(COMPLETION_DEBOUNCE), &cancel).await {
+                return Ok(Vec::new());
+            }
+            let scope = match project_crates {
+                Some(project_crates) => Some(project_crates.await),
+                None => None,
+            };
+            // A query qualified with a crate name (e.g., `serde::de`) only
+            // matches the items of that crate.
+            let scope = crate_search_scope(&query, scope);
+            let results = store
+                .search(query, max_completions, true, scope, cancel.clone())
+                .await;
+            // The results are stale once the query has changed.
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(Vec::new());
+            }
+            Ok(results
                 .into_iter()
-                .map(|(crate_name, item)| format!("{crate_name}::{}", item.display()))
+                .map(|result| {
+                    let path = format!("{}::{}", result.crate_name, result.item.display());
+                    match result.short_signature() {
+                        Some(signature) => ArgumentCompletion {
+                            label: format!("{path}{signature}"),
+                            new_text: path,
+                        },
+                        None if result.item.kind == RustdocItemKind::Macro => ArgumentCompletion {
+                            label: format!("{path}!"),
+                            new_text: path,
+                        },
+                        None => ArgumentCompletion::from(path),
+                    }
+                })
                 .collect())
         })
     }
 
     fn run(
+        self: Arc<Self>,
+        argument: Option<&str>,
+        workspace: WeakView<Workspace>,
+        delegate: Arc<dyn LspAdapterDelegate>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        self.run_with_partial_output(argument, workspace, delegate, Arc::new(|_| {}), cx)
+    }
+
+    fn run_with_partial_output(
         self: Arc<Self>,
         argument: Option<&str>,
         workspace: WeakView<Workspace>,
         _delegate: Arc<dyn LspAdapterDelegate>,
+        partial_output: PartialOutputCallback,
         cx: &mut WindowContext,
     ) -> Task<Result<SlashCommandOutput>> {
         let Some(argument) = argument else {
             return Task::ready(Err(anyhow!("missing crate name")));
         };
-        let Some(workspace) = workspace.upgrade() else {
-            return Task::ready(Err(anyhow!("workspace was dropped")));
-        };
 
-        let project = workspace.read(cx).project().clone();
-        let fs = project.read(cx).fs().clone();
-        let http_client = workspace.read(cx).client().http_client();
-        let path_to_cargo_toml = Self::path_to_cargo_toml(project, cx);
+        if let Some(argument) = strip_flag(argument.trim(), "--from-file") {
+            return Self::run_batch(argument, workspace, cx);
+        }
+        if let Some(query) = strip_flag(argument.trim(), "--search") {
+            return Self::run_search(query, workspace, cx);
+        }
+        if let Some((crate_specs, rest)) = Self::parse_version_comparison(argument) {
+            return Self::run_version_comparison(crate_specs, &rest, workspace, cx);
+        }
+        let queries = split_item_queries(argument, &RustdocFlag::value_flags());
+        if queries.len() > 1 {
+            let queries = queries.into_iter().map(ToString::to_string).collect();
+            return Self::run_items(queries, workspace, cx);
+        }
+
+        Self::run_single(argument, workspace, Some(partial_output), cx)
+    }
+}
 
-        let mut item_path = String::new();
-        let mut crate_name_to_index = None;
+/// Returns the rest of the given argument, if it starts with the given flag as
+/// a whole word, e.g., not for `--searches` when stripping `--search`.
+fn strip_flag<'a>(argument: &'a str, flag: &str) -> Option<&'a str> {
+    let rest = argument.strip_prefix(flag)?;
+    (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some(rest)
+}
 
-        let mut args = argument.split(' ').map(|word| word.trim());
-        while let Some(arg) = args.next() {
-            if arg == "--index" {
-                let Some(crate_name) = args.next() else {
-                    return Task::ready(Err(anyhow!("no crate name provided to --index")));
-                };
-                crate_name_to_index = Some(crate_name.to_string());
+/// A flag of the `/rustdoc` command.
+struct RustdocFlag {
+    name: &'static str,
+    /// Whether the flag is followed by a value, which may contain commas.
+    takes_value: bool,
+    /// Whether the docs for the queried item are still inserted with the flag,
+    /// as opposed to, e.g., with `--index` or `--methods`.
+    inserts_docs: bool,
+    /// Whether the flag is for a single item, which may be typed without its
+    /// crate for the language server to resolve.
+    resolves_item: bool,
+}
+
+impl RustdocFlag {
+    /// Every flag of the command.
+    ///
+    /// Every flag that takes a value must say so, or the value is mistaken for
+    /// the queried item when the argument is split or rewritten.
+    const ALL: &'static [RustdocFlag] = &[
+        Self::value("--target", true),
+        Self::value("--channel", true),
+        Self::value("--save", true),
+        Self::value("--tarball", false),
+        Self::value("--manifest-path", true),
+        Self::value("--features", true),
+        Self::value("--bounds", false),
+        Self::value("--order", true),
+        Self::value("--kind", true),
+        Self::switch("--all-features", true),
+        Self::item_switch("--defs-only", true),
+        Self::item_switch("--since", true),
+        Self::item_switch("--layout", true),
+        Self::item_switch("--dyn", false),
+        Self::item_switch("--methods", false),
+        Self::switch("--all", false),
+        Self::item_switch("--implementors", true),
+        Self::item_switch("--all-implementors", true),
+        Self::item_switch("--plain", true),
+        Self::item_switch("--with-refs", true),
+        Self::item_switch("--no-see-also", true),
+        Self::item_switch("--all-impls", true),
+        Self::switch("--index", false),
+        Self::switch("--reindex", false),
+        Self::switch("--deps", true),
+        Self::switch("--workspace", true),
+        Self::switch("--index-deps", false),
+        Self::switch("--docs-rs", true),
+        Self::switch("--json", false),
+        Self::switch("--build", true),
+        Self::item_switch("--open", false),
+        Self::switch("--list", false),
+        Self::switch("--deps-tree", false),
+        Self::switch("--transitive", true),
+        Self::switch("--clear-cache", false),
+        Self::switch("--clear", false),
+        Self::switch("--ping", false),
+        Self::switch("--features-list", false),
+        Self::switch("--from-file", false),
+        Self::switch("--search", false),
+    ];
+
+    const fn value(name: &'static str, inserts_docs: bool) -> Self {
+        Self {
+            name,
+            takes_value: true,
+            inserts_docs,
+            resolves_item: false,
+        }
+    }
+
+    const fn switch(name: &'static str, inserts_docs: bool) -> Self {
+        Self {
+            name,
+            takes_value: false,
+            inserts_docs,
+            resolves_item: false,
+        }
+    }
+
+    const fn item_switch(name: &'static str, inserts_docs: bool) -> Self {
+        Self {
+            resolves_item: true,
+            ..Self::switch(name, inserts_docs)
+        }
+    }
+
+    /// Returns the flag with the given name, if there is one.
+    fn get(name: &str) -> Option<&'static RustdocFlag> {
+        Self::ALL.iter().find(|flag| flag.name == name)
+    }
+
+    /// Returns the names of the flags that are followed by a value.
+    fn value_flags() -> Vec<&'static str> {
+        Self::ALL
+            .iter()
+            .filter(|flag| flag.takes_value)
+            .map(|flag| flag.name)
+            .collect()
+    }
+}
+
+/// The flags and item path of a single `/rustdoc` query.
+#[derive(Default)]
+struct RustdocArgs {
+    /// The path to the queried item, or the crate that a flag applies to.
+    item_path: String,
+    index: bool,
+    reindex: bool,
+    include_deps: bool,
+    index_workspace: bool,
+    index_lockfile: bool,
+    from_docs_rs: bool,
+    from_json: bool,
+    build: bool,
+    open: bool,
+    list: bool,
+    deps_tree: bool,
+    transitive: bool,
+    clear_cache: bool,
+    clear_index: bool,
+    ping: bool,
+    features_list: bool,
+    features: CargoDocFeatures,
+    target: Option<String>,
+    std_channel: Option<String>,
+    /// The paths are relative to the project until they're resolved.
+    save_path: Option<PathBuf>,
+    tarball_path: Option<PathBuf>,
+    manifest_path: Option<PathBuf>,
+    mode: DocQueryMode,
+    include_since: bool,
+    include_layout: bool,
+    plain: bool,
+    with_references: bool,
+    item_kinds: Vec<RustdocItemKind>,
+    list_implementors: bool,
+    all_implementors: bool,
+    omit_see_also: bool,
+    item_order: RustdocItemOrder,
+    expand_auto_impls: bool,
+}
+
+impl RustdocArgs {
+    /// Parses the given argument, failing if a flag is missing its value or is
+    /// combined with one it can't be used with.
+    fn parse(argument: &str) -> Result<Self> {
+        let mut args = Self::default();
+        let mut words = argument.split(' ').map(|word| word.trim());
+        while let Some(word) = words.next() {
+            let Some(flag) = RustdocFlag::get(word) else {
+                args.item_path.push_str(word);
                 continue;
-            }
+            };
+            let value = if flag.takes_value {
+                let Some(value) = words.next() else {
+                    let expected = match flag.name {
+                        "--target" => "target triple",
+                        "--channel" => "channel",
+                        "--save" => "file",
+                        "--tarball" => ".crate file",
+                        "--manifest-path" => "Cargo.toml",
+                        "--features" => "features",
+                        "--bounds" => "method name",
+                        "--order" => "item order",
+                        "--kind" => "item kinds",
+                        _ => "value",
+                    };
+                    return Err(anyhow!("no {expected} provided to {}", flag.name));
+                };
+                value
+            } else {
+                ""
+            };
 
-            item_path.push_str(arg);
+            match flag.name {
+                "--target" => args.target = Some(value.to_string()),
+                "--channel" => args.std_channel = Some(parse_std_channel(value)?),
+                "--save" => args.save_path = Some(PathBuf::from(value)),
+                "--tarball" => args.tarball_path = Some(PathBuf::from(value)),
+                "--manifest-path" => args.manifest_path = Some(PathBuf::from(value)),
+                "--features" => args.features = CargoDocFeatures::parse(value),
+                "--all-features" => args.features = CargoDocFeatures::All,
+                "--bounds" => args.mode = DocQueryMode::Bounds(value.to_string()),
+                "--order" => args.item_order = value.parse()?,
+                "--kind" => {
+                    for kind in value.split(',').filter(|kind| !kind.is_empty()) {
+                        args.item_kinds.push(kind.parse()?);
+                    }
+                }
+                "--defs-only" => args.mode = DocQueryMode::Definitions,
+                "--since" => args.include_since = true,
+                "--layout" => args.include_layout = true,
+                "--dyn" => args.mode = DocQueryMode::DynCompatibility,
+                "--methods" => args.mode = DocQueryMode::Methods,
+                "--all" => args.mode = DocQueryMode::AllItems,
+                "--implementors" => args.list_implementors = true,
+                "--all-implementors" => {
+                    args.list_implementors = true;
+                    args.all_implementors = true;
+                }
+                "--plain" => args.plain = true,
+                "--with-refs" => args.with_references = true,
+                "--no-see-also" => args.omit_see_also = true,
+                "--all-impls" => args.expand_auto_impls = true,
+                "--index" => args.index = true,
+                "--reindex" => {
+                    args.index = true;
+                    args.reindex = true;
+                }
+                "--deps" => args.include_deps = true,
+                "--workspace" => args.index_workspace = true,
+                "--index-deps" => {
+                    args.index = true;
+                    args.index_lockfile = true;
+                }
+                "--docs-rs" => args.from_docs_rs = true,
+                "--json" => args.from_json = true,
+                "--build" => args.build = true,
+                "--open" => args.open = true,
+                "--list" => args.list = true,
+                "--deps-tree" => args.deps_tree = true,
+                "--transitive" => args.transitive = true,
+                "--clear-cache" => args.clear_cache = true,
+                "--clear" => args.clear_index = true,
+                "--ping" => args.ping = true,
+                "--features-list" => args.features_list = true,
+                // `--from-file` and `--search` are run before the argument is
+                // parsed, so they can't be given along with the other flags.
+                _ => args.item_path.push_str(word),
+            }
         }
 
-        if let Some(crate_name_to_index) = crate_name_to_index {
-            let index_task = cx.background_executor().spawn({
-                let rustdoc_store = RustdocStore::global(cx);
-                let fs = fs.clone();
-                let crate_name_to_index = crate_name_to_index.clone();
-                async move {
-                    let cargo_workspace_root = path_to_cargo_toml
-                        .and_then(|path| path.parent().map(|path| path.to_path_buf()))
-                        .ok_or_else(|| anyhow!("no Cargo workspace root found"))?;
+        args.validate()?;
+        Ok(args)
+    }
 
-                    let provider = Box::new(LocalProvider::new(fs, cargo_workspace_root));
+    /// Fails if a flag is given without the flags it depends on.
+    fn validate(&self) -> Result<()> {
+        // The flags for where a crate is indexed from only apply when indexing
+        // a single crate.
+        let indexes_one_crate =
+            self.index && !self.include_deps && !self.index_workspace && !self.index_lockfile;
+        if self.tarball_path.is_some() && !indexes_one_crate {
+            return Err(anyhow!(
+                "--tarball can only be used with --index <crate> or --reindex <crate>"
+            ));
+        }
+        if self.manifest_path.is_some() && (!self.index || self.tarball_path.is_some()) {
+            return Err(anyhow!(
+                "--manifest-path can only be used when indexing, and not with --tarball"
+            ));
+        }
+        if self.from_json && (!indexes_one_crate || self.tarball_path.is_some()) {
+            return Err(anyhow!(
+                "--json can only be used with --index <crate> or --reindex <crate>"
+            ));
+        }
+        if self.build && (!indexes_one_crate || self.tarball_path.is_some() || self.from_json) {
+            return Err(anyhow!(
+                "--build can only be used with --index <crate> or --reindex <crate>"
+            ));
+        }
+        Ok(())
+    }
+}
 
-                    rustdoc_store
-                        .index(crate_name_to_index.clone(), provider)
-                        .await?;
+/// The crate, version, and item that a query inserts the docs for.
+#[derive(Debug, PartialEq, Eq)]
+struct DocsQueryKey {
+    crate_name: String,
+    version: Option<String>,
+    item_path: String,
+}
 
-                    anyhow::Ok(format!("Indexed {crate_name_to_index}"))
+/// Returns the item that the given argument inserts the docs for, if it is a
+/// query for an item's docs, as opposed to, e.g., `--index` or `--list`.
+fn docs_query_key(argument: &str) -> Option<DocsQueryKey> {
+    let mut path = String::new();
+    let mut channel = None;
+    let mut words = argument.split_whitespace();
+    while let Some(word) = words.next() {
+        match word {
+            "--channel" => channel = words.next(),
+            _ => match RustdocFlag::get(word) {
+                Some(flag) if !flag.inserts_docs => return None,
+                Some(flag) if flag.takes_value => {
+                    words.next();
                 }
-            });
+                Some(_) => {}
+                None if word.starts_with("--") => {}
+                None => path.push_str(word),
+            },
+        }
+    }
 
-            return cx.foreground_executor().spawn(async move {
-                let text = index_task.await?;
-                let range = 0..text.len();
-                Ok(SlashCommandOutput {
-                    text,
-                    sections: vec![SlashCommandOutputSection {
-                        range,
-                        render_placeholder: Arc::new(move |id, unfold, _cx| {
-                            RustdocIndexPlaceholder {
-                                id,
-                                unfold,
-                                source: RustdocSource::Local,
-                                crate_name: SharedString::from(crate_name_to_index.clone()),
-                            }
-                            .into_any_element()
-                        }),
-                    }],
-                    run_commands_in_text: false,
-                })
-            });
+    // Queries for the same item with and without generic arguments, or for a
+    // macro with and without its `!`, insert the same docs.
+    let item_path = parse_item_path(path.trim_end_matches('!')).ok()?;
+    let (crate_name, version) = parse_crate_version(&item_path.segments[0]).ok()?;
+    if crate_name.is_empty() {
+        return None;
+    }
+
+    // The docs for the standard library differ between release channels.
+    let version = version.map(|version| version.to_string()).or_else(|| {
+        channel
+            .filter(|_| STANDARD_LIBRARY_CRATES.contains(&crate_name))
+            .map(ToString::to_string)
+    });
+    Some(DocsQueryKey {
+        crate_name: normalize_crate_name(crate_name),
+        version,
+        item_path: item_path.segments[1..].join("::"),
+    })
+}
+
+/// Returns the given argument with the crate of the queried item replaced by
+/// the given crate, keeping the version and item path that were typed, e.g.,
+/// `tokio_util@0.7::codec` for `tokio_utl@0.7::codec`.
+fn with_crate_name(argument: &str, crate_name: &str) -> String {
+    let mut words = Vec::new();
+    let mut is_flag_value = false;
+    let mut is_replaced = false;
+    for word in argument.split(' ') {
+        if is_replaced || is_flag_value || word.is_empty() || word.starts_with("--") {
+            is_flag_value = RustdocFlag::get(word).map_or(false, |flag| flag.takes_value);
+            words.push(word.to_string());
+            continue;
         }
 
-        let mut path_components = item_path.split("::");
-        let crate_name = match path_components
-            .next()
-            .ok_or_else(|| anyhow!("missing crate name"))
-        {
-            Ok(crate_name) => crate_name.to_string(),
-            Err(err) => return Task::ready(Err(err)),
+        let (crate_spec, item_path) = match word.split_once("::") {
+            Some((crate_spec, item_path)) => (crate_spec, Some(item_path)),
+            None => (word, None),
         };
-        let item_path = path_components.map(ToString::to_string).collect::<Vec<_>>();
+        let mut word = crate_name.to_string();
+        if let Some((_crate_name, version)) = crate_spec.split_once('@') {
+            word.push_str(&format!("@{version}"));
+        }
+        if let Some(item_path) = item_path {
+            word.push_str(&format!("::{item_path}"));
+        }
+        words.push(word);
+        is_replaced = true;
+    }
+    words.join(" ")
+}
 
-        let text = cx.background_executor().spawn({
-            let rustdoc_store = RustdocStore::global(cx);
-            let crate_name = crate_name.clone();
-            let item_path = item_path.clone();
-            async move {
-                let item_docs = rustdoc_store
-                    .load(crate_name.clone(), Some(item_path.join("::")))
-                    .await;
+/// Returns the output with the given text in a single section, folded into
+/// the placeholder that the given function renders.
+fn output_with_placeholder(
+    text: String,
+    render_placeholder: impl Fn(ElementId, Arc<dyn Fn(&mut WindowContext)>) -> AnyElement
+        + Send
+        + Sync
+        + 'static,
+) -> SlashCommandOutput {
+    let range = 0..text.len();
+    SlashCommandOutput {
+        text,
+        sections: vec![SlashCommandOutputSection {
+            range,
+            render_placeholder: Arc::new(move |id, unfold, _cx| render_placeholder(id, unfold)),
+        }],
+        run_commands_in_text: false,
+    }
+}
 
-                if let Ok(item_docs) = item_docs {
-                    anyhow::Ok((RustdocSource::Local, item_docs))
-                } else {
-                    Self::build_message(
-                        fs,
-                        http_client,
-                        crate_name,
-                        item_path,
-                        path_to_cargo_toml.as_deref(),
-                    )
-                    .await
-                }
+/// Returns the given output with the given note before it, as part of its
+/// first section.
+fn with_note(output: SlashCommandOutput, note: &str) -> SlashCommandOutput {
+    let sections = output
+        .sections
+        .into_iter()
+        .map(|section| {
+            let start = match section.range.start {
+                0 => 0,
+                start => start + note.len(),
+            };
+            SlashCommandOutputSection {
+                range: start..section.range.end + note.len(),
+                render_placeholder: section.render_placeholder,
             }
-        });
+        })
+        .collect();
+    SlashCommandOutput {
+        text: format!("{note}{}", output.text),
+        sections,
+        run_commands_in_text: output.run_commands_in_text,
+    }
+}
 
-        let crate_name = SharedString::from(crate_name);
-        let module_path = if item_path.is_empty() {
-            None
+/// Returns the `crate::path` of the item defined by the symbol with the given
+/// name, derived from the path of the file that defines it.
+fn item_path_for_symbol(
+    symbol_name: &str,
+    symbol_path: &ProjectPath,
+    worktree_root_names: &HashMap<WorktreeId, String>,
+) -> Option<String> {
+    let path = &symbol_path.path;
+    if path.extension().and_then(|extension| extension.to_str()) != Some("rs") {
+        return None;
+    }
+
+    let components = path
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    let src_ix = components
+        .iter()
+        .rposition(|component| *component == "src")?;
+    let crate_dir = match src_ix.checked_sub(1) {
+        Some(crate_dir_ix) => components[crate_dir_ix],
+        None => worktree_root_names.get(&symbol_path.worktree_id)?.as_str(),
+    };
+
+    // Crates in the Cargo registry live in directories suffixed with their version.
+    let crate_dir = match crate_dir.rsplit_once('-') {
+        Some((crate_dir, version)) if version.starts_with(|char: char| char.is_ascii_digit()) => {
+            crate_dir
+        }
+        _ => crate_dir,
+    };
+    let crate_name = crate_dir.replace('-', "_");
+
+    let mut item_path = vec![crate_name.clone()];
+    let module_components = &components[src_ix + 1..];
+    for (ix, component) in module_components.iter().enumerate() {
+        let is_file = ix == module_components.len() - 1;
+        if is_file {
+            let module = Path::new(component).file_stem()?.to_str()?;
+            if !["lib", "main", "mod", crate_dir, crate_name.as_str()].contains(&module) {
+                item_path.push(module.to_string());
+            }
         } else {
-            Some(SharedString::from(item_path.join("::")))
+            item_path.push(component.to_string());
+        }
+    }
+    item_path.push(symbol_name.to_string());
+
+    Some(item_path.join("::"))
+}
+
+/// Returns a callback that shows the output of `cargo doc` while it builds the
+/// docs for the given crate, as the build may take minutes.
+fn cargo_doc_progress(
+    partial_output: Option<PartialOutputCallback>,
+    crate_name: &str,
+) -> impl Fn(&str) + Send + Sync {
+    let heading = format!("Building the docs for {crate_name} with `cargo doc`…");
+    let output = Mutex::new(String::new());
+    move |line| {
+        let Some(partial_output) = partial_output.as_ref() else {
+            return;
         };
-        cx.foreground_executor().spawn(async move {
-            let (source, text) = text.await?;
-            let range = 0..text.len();
-            Ok(SlashCommandOutput {
-                text,
-                sections: vec![SlashCommandOutputSection {
-                    range,
-                    render_placeholder: Arc::new(move |id, unfold, _cx| {
-                        RustdocPlaceholder {
-                            id,
-                            unfold,
-                            source,
-                            crate_name: crate_name.clone(),
-                            module_path: module_path.clone(),
-                        }
-                        .into_any_element()
-                    }),
-                }],
-                run_commands_in_text: false,
-            })
-        })
+        let mut output = output.lock();
+        output.push_str(line);
+        output.push('\n');
+        partial_output(SlashCommandOutput {
+            text: format!("{heading}\n\n```\n{output}```"),
+            sections: Vec::new(),
+            run_commands_in_text: false,
+        });
+    }
+}
+
+/// Describes the features that the docs were built with, to append to a
+/// message about them.
+fn describe_features(features: &CargoDocFeatures) -> String {
+    match features {
+        CargoDocFeatures::Default => String::new(),
+        CargoDocFeatures::Features(features) => format!(
+            " with features {}",
+            features
+                .iter()
+                .map(|feature| format!("`{feature}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        CargoDocFeatures::All => " with all features".to_string(),
+    }
+}
+
+/// Formats the given age in the largest whole unit, e.g., "3 hours".
+fn format_age(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    let (count, unit) = match minutes {
+        0..=59 => (minutes, "minute"),
+        60..=1439 => (minutes / 60, "hour"),
+        _ => (minutes / 1440, "day"),
+    };
+
+    format!("{count} {unit}{}", if count == 1 { "" } else { "s" })
+}
+
+fn format_size(byte_count: u64) -> String {
+    const KIB: u64 = 1024;
+    const MIB: u64 = 1024 * KIB;
+
+    if byte_count < KIB {
+        format!("{byte_count} B")
+    } else if byte_count < MIB {
+        format!("{:.1} KiB", byte_count as f64 / KIB as f64)
+    } else {
+        format!("{:.1} MiB", byte_count as f64 / MIB as f64)
     }
 }
 
@@ -277,7 +2986,13 @@ struct RustdocPlaceholder {
     pub id: ElementId,
     pub unfold: Arc<dyn Fn(&mut WindowContext)>,
     pub source: RustdocSource,
+    /// The part of the docs that the placeholder is for.
+    pub section_kind: DocsSectionKind,
+    /// The URL to the docs that were inserted, for viewing them in the browser.
+    pub docs_url: Option<SharedString>,
     pub crate_name: SharedString,
+    /// The version of the crate that the docs are for, when known.
+    pub version: Option<SharedString>,
     pub module_path: Option<SharedString>,
 }
 
@@ -285,23 +3000,92 @@ impl RenderOnce for RustdocPlaceholder {
     fn render(self, _cx: &mut WindowContext) -> impl IntoElement {
         let unfold = self.unfold;
 
+        let crate_name = match self.version {
+            Some(version) => format!("{crate_name}@{version}", crate_name = self.crate_name),
+            None => self.crate_name.to_string(),
+        };
         let crate_path = self
             .module_path
-            .map(|module_path| format!("{crate_name}::{module_path}", crate_name = self.crate_name))
-            .unwrap_or(self.crate_name.to_string());
+            .map(|module_path| format!("{crate_name}::{module_path}"))
+            .unwrap_or(crate_name);
+        let label = match self.section_kind {
+            DocsSectionKind::Description => "rustdoc",
+            DocsSectionKind::Examples => "rustdoc examples",
+            DocsSectionKind::Items => "rustdoc items",
+            DocsSectionKind::AutoImpls => "rustdoc auto & blanket impls",
+        };
 
-        ButtonLike::new(self.id)
-            .style(ButtonStyle::Filled)
-            .layer(ElevationIndex::ElevatedSurface)
-            .child(Icon::new(IconName::FileRust))
-            .child(Label::new(format!(
-                "rustdoc ({source}): {crate_path}",
-                source = match self.source {
-                    RustdocSource::Local => "local",
-                    RustdocSource::DocsDotRs => "docs.rs",
-                }
-            )))
-            .on_click(move |_, cx| unfold(cx))
+        h_flex()
+            .id(self.id)
+            .gap_1()
+            .child(
+                ButtonLike::new("unfold")
+                    .style(ButtonStyle::Filled)
+                    .layer(ElevationIndex::ElevatedSurface)
+                    .child(Icon::new(IconName::FileRust))
+                    .child(Label::new(format!(
+                        "{label} ({source}): {crate_path}",
+                        source = match self.source {
+                            RustdocSource::Local => "local",
+                            RustdocSource::DocsDotRs => "docs.rs",
+                            RustdocSource::CachedDocsDotRs => "docs.rs, cached",
+                        }
+                    )))
+                    .on_click(move |_, cx| unfold(cx)),
+            )
+            .children(self.docs_url.map(|docs_url| {
+                IconButton::new("open-docs", IconName::ExternalLink)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::text("Open Docs", cx))
+                    .on_click(move |_, cx| cx.open_url(&docs_url))
+            }))
+    }
+}
+
+#[derive(IntoElement)]
+struct RustdocSearchResultPlaceholder {
+    pub id: ElementId,
+    pub unfold: Arc<dyn Fn(&mut WindowContext)>,
+    /// The full path of the item, e.g., `tokio::sync::Mutex`.
+    pub item_path: SharedString,
+    /// The workspace whose active context the item's docs are inserted into.
+    pub workspace: WeakView<Workspace>,
+}
+
+impl RenderOnce for RustdocSearchResultPlaceholder {
+    fn render(self, _cx: &mut WindowContext) -> impl IntoElement {
+        let unfold = self.unfold;
+        let item_path = self.item_path;
+        let workspace = self.workspace;
+
+        h_flex()
+            .id(self.id)
+            .gap_1()
+            .child(
+                ButtonLike::new("unfold")
+                    .style(ButtonStyle::Filled)
+                    .layer(ElevationIndex::ElevatedSurface)
+                    .child(Icon::new(IconName::FileRust))
+                    .child(Label::new(format!("rustdoc search (local): {item_path}")))
+                    .on_click(move |_, cx| unfold(cx)),
+            )
+            .child(
+                IconButton::new("insert-docs", IconName::Plus)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::text("Insert Docs", cx))
+                    .on_click(move |_, cx| {
+                        workspace
+                            .update(cx, |workspace, cx| {
+                                if let Some(panel) = workspace.panel::<AssistantPanel>(cx) {
+                                    panel.update(cx, |panel, cx| {
+                                        panel
+                                            .insert_command_with_argument("rustdoc", &item_path, cx)
+                                    });
+                                }
+                            })
+                            .ok();
+                    }),
+            )
     }
 }
 
@@ -327,8 +3111,221 @@ impl RenderOnce for RustdocIndexPlaceholder {
                 source = match self.source {
                     RustdocSource::Local => "local",
                     RustdocSource::DocsDotRs => "docs.rs",
+                    RustdocSource::CachedDocsDotRs => "docs.rs, cached",
                 }
             )))
             .on_click(move |_, cx| unfold(cx))
     }
 }
+
+/// A placeholder for output that isn't the docs of a crate, like the list of indexed crates or
+/// the result of clearing the cache.
+#[derive(IntoElement)]
+struct RustdocLabelPlaceholder {
+    pub id: ElementId,
+    pub unfold: Arc<dyn Fn(&mut WindowContext)>,
+    pub label: SharedString,
+}
+
+impl RenderOnce for RustdocLabelPlaceholder {
+    fn render(self, _cx: &mut WindowContext) -> impl IntoElement {
+        let unfold = self.unfold;
+
+        ButtonLike::new(self.id)
+            .style(ButtonStyle::Filled)
+            .layer(ElevationIndex::ElevatedSurface)
+            .child(Icon::new(IconName::FileRust))
+            .child(Label::new(self.label))
+            .on_click(move |_, cx| unfold(cx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_crate_name_skips_flag_values() {
+        assert_eq!(
+            with_crate_name("tokio_utl@0.7::codec", "tokio_util"),
+            "tokio_util@0.7::codec"
+        );
+        for flag in RustdocFlag::value_flags() {
+            assert_eq!(
+                with_crate_name(
+                    &format!("{flag} value tokio_utl::codec --all"),
+                    "tokio_util"
+                ),
+                format!("{flag} value tokio_util::codec --all"),
+                "the value of {flag} is not the crate"
+            );
+        }
+    }
+
+    #[test]
+    fn test_strip_flag() {
+        assert_eq!(strip_flag("--search Mutex", "--search"), Some(" Mutex"));
+        assert_eq!(strip_flag("--search", "--search"), Some(""));
+        assert_eq!(strip_flag("--searches Mutex", "--search"), None);
+        assert_eq!(strip_flag("--from-file=queries.txt", "--from-file"), None);
+        assert_eq!(strip_flag("tokio --search", "--search"), None);
+    }
+
+    #[test]
+    fn test_rustdoc_args_parse() {
+        let args = RustdocArgs::parse("--reindex --features a,b tokio").unwrap();
+        assert!(args.index && args.reindex);
+        assert_eq!(args.features, CargoDocFeatures::parse("a,b"));
+        assert_eq!(args.item_path, "tokio");
+
+        let args = RustdocArgs::parse("--kind struct,fn --order alphabetical tokio::sync").unwrap();
+        assert_eq!(
+            args.item_kinds,
+            vec![RustdocItemKind::Struct, RustdocItemKind::Function]
+        );
+        assert_eq!(args.item_order, RustdocItemOrder::Alphabetical);
+        assert_eq!(args.item_path, "tokio::sync");
+
+        assert_eq!(
+            RustdocArgs::parse("tokio --kind")
+                .err()
+                .map(|err| err.to_string()),
+            Some("no item kinds provided to --kind".into())
+        );
+        assert!(RustdocArgs::parse("--tarball tokio.crate tokio").is_err());
+        assert!(RustdocArgs::parse("--index --workspace --json").is_err());
+    }
+
+    #[test]
+    fn test_docs_query_key() {
+        assert_eq!(
+            docs_query_key("serde-json@1.0.100::value::from_value"),
+            Some(DocsQueryKey {
+                crate_name: "serde_json".into(),
+                version: Some("1.0.100".into()),
+                item_path: "value::from_value".into(),
+            })
+        );
+
+        // Generic arguments and the `!` of a macro don't change the docs.
+        assert_eq!(
+            docs_query_key("std::vec::Vec<u8>"),
+            docs_query_key("std::vec::Vec")
+        );
+        assert_eq!(
+            docs_query_key("tokio::select!"),
+            docs_query_key("tokio::select")
+        );
+
+        // The docs for the standard library differ between release channels,
+        // while the channel is ignored for other crates.
+        assert_eq!(
+            docs_query_key("--channel nightly std::vec::Vec").map(|key| key.version),
+            Some(Some("nightly".into()))
+        );
+        assert_eq!(
+            docs_query_key("--channel nightly tokio").map(|key| key.version),
+            Some(None)
+        );
+
+        assert_eq!(docs_query_key("--list"), None);
+        assert_eq!(docs_query_key("--methods tokio::sync::Mutex"), None);
+        assert_eq!(docs_query_key(""), None);
+    }
+
+    #[test]
+    fn test_docs_query_key_skips_flag_values() {
+        let tokio_key = Some(DocsQueryKey {
+            crate_name: "tokio".into(),
+            version: None,
+            item_path: String::new(),
+        });
+        assert_eq!(docs_query_key("--order alphabetical tokio"), tokio_key);
+        assert_eq!(docs_query_key("tokio --kind struct,fn"), tokio_key);
+        assert_eq!(
+            docs_query_key("--target x86_64-pc-windows-msvc --features full tokio"),
+            tokio_key
+        );
+    }
+
+    #[test]
+    fn test_split_item_queries_skips_flag_values() {
+        assert_eq!(
+            split_item_queries(
+                "--order alphabetical serde::Serialize",
+                &RustdocFlag::value_flags()
+            ),
+            vec!["--order alphabetical serde::Serialize"]
+        );
+        assert_eq!(
+            split_item_queries(
+                "--manifest-path a,b/Cargo.toml --index serde",
+                &RustdocFlag::value_flags()
+            ),
+            vec!["--manifest-path a,b/Cargo.toml --index serde"]
+        );
+    }
+
+    #[test]
+    fn test_item_path_for_symbol() {
+        let worktree_id = WorktreeId::from_usize(1);
+        let worktree_root_names = HashMap::from_iter([(worktree_id, "my-app".to_string())]);
+        let item_path = |path: &str, name: &str| {
+            let path = ProjectPath {
+                worktree_id,
+                path: Path::new(path).into(),
+            };
+            item_path_for_symbol(name, &path, &worktree_root_names)
+        };
+
+        assert_eq!(item_path("src/lib.rs", "App"), Some("my_app::App".into()));
+        assert_eq!(
+            item_path("src/net/client.rs", "Client"),
+            Some("my_app::net::client::Client".into())
+        );
+        assert_eq!(
+            item_path("src/net/mod.rs", "connect"),
+            Some("my_app::net::connect".into())
+        );
+        // Crates in the Cargo registry are named after their directory,
+        // without its version.
+        assert_eq!(
+            item_path("registry/serde_json-1.0.100/src/value/mod.rs", "Value"),
+            Some("serde_json::value::Value".into())
+        );
+        assert_eq!(
+            item_path("crates/http-client/src/http_client.rs", "HttpClient"),
+            Some("http_client::HttpClient".into())
+        );
+        assert_eq!(item_path("src/build.toml", "App"), None);
+        assert_eq!(item_path("lib.rs", "App"), None);
+    }
+
+    #[test]
+    fn test_parse_version_comparison() {
+        assert_eq!(
+            RustdocSlashCommand::parse_version_comparison(
+                "serde@1.0.100  serde@1.0.200 de::Deserializer --defs-only"
+            ),
+            Some((
+                vec!["serde@1.0.100", "serde@1.0.200"],
+                "de::Deserializer --defs-only".to_string()
+            ))
+        );
+        assert_eq!(
+            RustdocSlashCommand::parse_version_comparison("serde@1.0.100 serde@1.0.200"),
+            Some((vec!["serde@1.0.100", "serde@1.0.200"], String::new()))
+        );
+
+        // A single version, or versions of items rather than crates, aren't
+        // compared.
+        assert_eq!(
+            RustdocSlashCommand::parse_version_comparison("serde@1.0.100 de::Deserializer"),
+            None
+        );
+        assert_eq!(
+            RustdocSlashCommand::parse_version_comparison("serde@1.0.100::de serde@1.0.200::de"),
+            None
+        );
+    }
+}
@@ -27,7 +27,7 @@ pub trait SlashCommand: 'static + Send + Sync {
         cancel: Arc<AtomicBool>,
         workspace: Option<WeakView<Workspace>>,
         cx: &mut AppContext,
-    ) -> Task<Result<Vec<String>>>;
+    ) -> Task<Result<Vec<ArgumentCompletion>>>;
     fn requires_argument(&self) -> bool;
     fn run(
         self: Arc<Self>,
@@ -41,6 +41,45 @@ pub trait SlashCommand: 'static + Send + Sync {
         delegate: Arc<dyn LspAdapterDelegate>,
         cx: &mut WindowContext,
     ) -> Task<Result<SlashCommandOutput>>;
+    /// Runs the command like [`SlashCommand::run`], passing the output that it
+    /// has produced so far to `partial_output` while the rest is produced,
+    /// e.g., to show the docs for an item before those it references.
+    ///
+    /// The returned output replaces the last partial output.
+    fn run_with_partial_output(
+        self: Arc<Self>,
+        argument: Option<&str>,
+        workspace: WeakView<Workspace>,
+        delegate: Arc<dyn LspAdapterDelegate>,
+        _partial_output: PartialOutputCallback,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        self.run(argument, workspace, delegate, cx)
+    }
+}
+
+/// Receives the output that a command has produced so far.
+///
+/// Only the text of a partial output is shown, without folding its sections.
+pub type PartialOutputCallback = Arc<dyn Send + Sync + Fn(SlashCommandOutput)>;
+
+/// A completion for the argument of a slash command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgumentCompletion {
+    /// The label to show in the completion menu.
+    pub label: String,
+    /// The argument to insert when the completion is accepted.
+    pub new_text: String,
+}
+
+impl From<String> for ArgumentCompletion {
+    /// Returns a completion that inserts the argument it is labeled with.
+    fn from(argument: String) -> Self {
+        Self {
+            label: argument.clone(),
+            new_text: argument,
+        }
+    }
 }
 
 pub type RenderFoldPlaceholder = Arc<
@@ -102,22 +102,71 @@ impl HandleTag for ListHandler {
 }
 
 pub struct TableHandler {
-    /// The number of columns in the current `<table>`.
-    current_table_columns: usize,
-    is_first_th: bool,
-    is_first_td: bool,
+    /// The tables currently being written, innermost last.
+    tables: Vec<Table>,
+}
+
+struct Table {
+    /// The offset in the Markdown output at which the `<table>` starts.
+    start: usize,
+    rows: Vec<TableRow>,
+    current_row: TableRow,
+    /// The offset in the Markdown output at which the current cell starts.
+    current_cell_start: Option<usize>,
+}
+
+#[derive(Default)]
+struct TableRow {
+    cells: Vec<String>,
+    is_header: bool,
 }
 
 impl TableHandler {
     pub fn new() -> Self {
-        Self {
-            current_table_columns: 0,
-            is_first_th: true,
-            is_first_td: true,
+        Self { tables: Vec::new() }
+    }
+
+    /// Renders the given rows as a GitHub-flavored Markdown table.
+    ///
+    /// Tables without a header row are given an empty one, as a header is
+    /// required for the table to be recognized.
+    fn render(rows: Vec<TableRow>) -> String {
+        let columns = rows.iter().map(|row| row.cells.len()).max().unwrap_or(0);
+        if columns == 0 {
+            return String::new();
+        }
+
+        let mut rows = rows.into_iter().peekable();
+        let header = match rows.peek() {
+            Some(row) if row.is_header => rows.next().map(|row| row.cells),
+            _ => None,
         }
+        .unwrap_or_default();
+
+        let render_row = |mut cells: Vec<String>| {
+            cells.resize(columns, String::new());
+            format!("| {} |", cells.join(" | "))
+        };
+
+        let mut lines = vec![
+            render_row(header),
+            render_row(vec!["---".to_string(); columns]),
+        ];
+        lines.extend(rows.map(|row| render_row(row.cells)));
+        lines.join("\n")
     }
 }
 
+/// Flattens the Markdown written for a table cell onto a single line,
+/// escaping any pipes so that they don't end the cell.
+fn table_cell(markdown: &str) -> String {
+    markdown
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
 impl HandleTag for TableHandler {
     fn should_handle(&self, tag: &str) -> bool {
         match tag {
@@ -131,25 +180,18 @@ impl HandleTag for TableHandler {
         tag: &HtmlElement,
         writer: &mut MarkdownWriter,
     ) -> StartTagOutcome {
+        let offset = writer.markdown.len();
         match tag.tag() {
-            "thead" => writer.push_blank_line(),
-            "tr" => writer.push_newline(),
-            "th" => {
-                self.current_table_columns += 1;
-                if self.is_first_th {
-                    self.is_first_th = false;
-                } else {
-                    writer.push_str(" ");
+            "table" => self.tables.push(Table {
+                start: offset,
+                rows: Vec::new(),
+                current_row: TableRow::default(),
+                current_cell_start: None,
+            }),
+            "th" | "td" => {
+                if let Some(table) = self.tables.last_mut() {
+                    table.current_cell_start = Some(offset);
                 }
-                writer.push_str("| ");
-            }
-            "td" => {
-                if self.is_first_td {
-                    self.is_first_td = false;
-                } else {
-                    writer.push_str(" ");
-                }
-                writer.push_str("| ");
             }
             _ => {}
         }
@@ -159,29 +201,85 @@ impl HandleTag for TableHandler {
 
     fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
         match tag.tag() {
-            "thead" => {
-                writer.push_newline();
-                for ix in 0..self.current_table_columns {
-                    if ix > 0 {
-                        writer.push_str(" ");
-                    }
-                    writer.push_str("| ---");
-                }
-                writer.push_str(" |");
-                self.is_first_th = true;
+            "th" | "td" => {
+                let Some(table) = self.tables.last_mut() else {
+                    return;
+                };
+                let Some(cell_start) = table.current_cell_start.take() else {
+                    return;
+                };
+
+                let cell = table_cell(writer.markdown_since(cell_start));
+                writer.markdown.truncate(cell_start);
+                // A row is a header if it is within a `<thead>`, or if it
+                // consists solely of `<th>`s.
+                let is_header = writer.is_inside("thead") || tag.tag() == "th";
+                let row = &mut table.current_row;
+                row.is_header = is_header && (row.is_header || row.cells.is_empty());
+                row.cells.push(cell);
             }
             "tr" => {
-                writer.push_str(" |");
-                self.is_first_td = true;
+                if let Some(table) = self.tables.last_mut() {
+                    let row = std::mem::take(&mut table.current_row);
+                    if !row.cells.is_empty() {
+                        table.rows.push(row);
+                    }
+                }
             }
             "table" => {
-                self.current_table_columns = 0;
+                let Some(table) = self.tables.pop() else {
+                    return;
+                };
+
+                writer.markdown.truncate(table.start);
+                let markdown = Self::render(table.rows);
+                if !markdown.is_empty() {
+                    writer.push_blank_line();
+                    writer.push_str(&markdown);
+                    writer.push_blank_line();
+                }
             }
             _ => {}
         }
     }
 }
 
+pub struct BlockquoteHandler {
+    /// The offsets in the Markdown output at which each of the enclosing
+    /// `<blockquote>`s start.
+    quote_starts: Vec<usize>,
+}
+
+impl BlockquoteHandler {
+    pub fn new() -> Self {
+        Self {
+            quote_starts: Vec::new(),
+        }
+    }
+}
+
+impl HandleTag for BlockquoteHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "blockquote"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        _tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        self.quote_starts.push(writer.markdown.len());
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, _tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        if let Some(offset) = self.quote_starts.pop() {
+            writer.quote_since(offset, None);
+        }
+    }
+}
+
 pub struct StyledTextHandler;
 
 impl HandleTag for StyledTextHandler {
@@ -1,22 +1,66 @@
 use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::rc::Rc;
-use std::sync::OnceLock;
 
 use anyhow::Result;
 use markup5ever_rcdom::{Handle, NodeData};
-use regex::Regex;
 
 use crate::html_element::HtmlElement;
 
-fn empty_line_regex() -> &'static Regex {
-    static REGEX: OnceLock<Regex> = OnceLock::new();
-    REGEX.get_or_init(|| Regex::new(r"^\s*$").unwrap())
+/// Normalizes the whitespace in the given Markdown, trimming the trailing
+/// whitespace of each line and collapsing each run of blank lines into a
+/// single one.
+///
+/// The lines outside of code blocks for which `is_boilerplate` returns `true`
+/// are removed too. The blank lines inside code blocks are kept as they are.
+pub fn normalize_markdown(markdown: &str, is_boilerplate: impl Fn(&str) -> bool) -> String {
+    let mut normalized = String::with_capacity(markdown.len());
+    // The character and length of the fence of the code block being visited.
+    let mut fence: Option<(char, usize)> = None;
+    let mut is_after_blank_line = false;
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        let trimmed_line = line.trim_start();
+        match fence {
+            Some((fence_char, fence_len)) => {
+                let is_closing_fence = trimmed_line.len() >= fence_len
+                    && trimmed_line.chars().all(|char| char == fence_char);
+                if is_closing_fence {
+                    fence = None;
+                }
+            }
+            None => {
+                if is_boilerplate(trimmed_line) {
+                    continue;
+                }
+                if line.is_empty() {
+                    if !is_after_blank_line {
+                        normalized.push('\n');
+                    }
+                    is_after_blank_line = true;
+                    continue;
+                }
+                fence = code_fence(trimmed_line);
+            }
+        }
+
+        is_after_blank_line = false;
+        normalized.push_str(line);
+        normalized.push('\n');
+    }
+
+    normalized.trim().to_string()
 }
 
-fn more_than_three_newlines_regex() -> &'static Regex {
-    static REGEX: OnceLock<Regex> = OnceLock::new();
-    REGEX.get_or_init(|| Regex::new(r"\n{3,}").unwrap())
+/// Returns the character and length of the fence that the given line opens a
+/// code block with, if it does.
+fn code_fence(line: &str) -> Option<(char, usize)> {
+    let fence_char = line
+        .chars()
+        .next()
+        .filter(|char| *char == '`' || *char == '~')?;
+    let fence_len = line.len() - line.trim_start_matches(fence_char).len();
+    (fence_len >= 3).then_some((fence_char, fence_len))
 }
 
 pub enum StartTagOutcome {
@@ -49,6 +93,20 @@ impl MarkdownWriter {
             .any(|parent_element| parent_element.tag() == tag)
     }
 
+    /// Returns the Markdown output written so far.
+    pub fn markdown(&self) -> &str {
+        &self.markdown
+    }
+
+    /// Returns the Markdown output written since the given offset.
+    ///
+    /// This is empty if the output has since been truncated to before the
+    /// offset, e.g., by a handler for an element that wasn't closed where it
+    /// was expected to be.
+    pub fn markdown_since(&self, offset: usize) -> &str {
+        self.markdown.get(offset..).unwrap_or_default()
+    }
+
     /// Appends the given string slice onto the end of the Markdown output.
     pub fn push_str(&mut self, str: &str) {
         self.markdown.push_str(str);
@@ -64,16 +122,73 @@ impl MarkdownWriter {
         self.push_str("\n\n");
     }
 
+    /// Removes the Markdown output written since the given offset.
+    pub fn truncate(&mut self, offset: usize) {
+        self.markdown.truncate(offset);
+    }
+
+    /// Indents each line of the Markdown output written since the given
+    /// offset with the given prefix, e.g., so that it continues the list item
+    /// before it.
+    pub fn indent_since(&mut self, offset: usize, indent: &str) {
+        let text = self.markdown_since(offset).trim().to_string();
+        let indented = text
+            .lines()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    String::new()
+                } else {
+                    format!("{indent}{line}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        self.markdown.truncate(offset);
+        if !indented.is_empty() {
+            self.push_blank_line();
+            self.push_str(&indented);
+            self.push_blank_line();
+        }
+    }
+
+    /// Turns the Markdown output written since the given offset into a
+    /// blockquote, by prefixing each of its lines with `> `.
+    ///
+    /// If a label is given (e.g., `**Warning:**`), it is prepended to the
+    /// first line of the blockquote.
+    pub fn quote_since(&mut self, offset: usize, label: Option<&str>) {
+        let mut text = self.markdown_since(offset).trim().to_string();
+        if let Some(label) = label {
+            text = format!("{label} {text}");
+        }
+        let quoted = text
+            .lines()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    ">".to_string()
+                } else {
+                    format!("> {line}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        self.markdown.truncate(offset);
+        if !quoted.is_empty() {
+            self.push_blank_line();
+            self.push_str(&quoted);
+            self.push_blank_line();
+        }
+    }
+
     pub fn run(mut self, root_node: &Handle, handlers: &mut Vec<TagHandler>) -> Result<String> {
         self.visit_node(&root_node, handlers)?;
         Ok(Self::prettify_markdown(self.markdown))
     }
 
     fn prettify_markdown(markdown: String) -> String {
-        let markdown = empty_line_regex().replace_all(&markdown, "");
-        let markdown = more_than_three_newlines_regex().replace_all(&markdown, "\n\n");
-
-        markdown.trim().to_string()
+        normalize_markdown(&markdown, |_| false)
     }
 
     fn visit_node(&mut self, node: &Handle, handlers: &mut [TagHandler]) -> Result<()> {
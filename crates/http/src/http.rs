@@ -5,6 +5,7 @@ use futures::future::BoxFuture;
 use futures_lite::FutureExt;
 use isahc::config::{Configurable, RedirectPolicy};
 pub use isahc::{
+    error::ErrorKind,
     http::{Method, StatusCode, Uri},
     AsyncBody, Error, HttpClient as IsahcHttpClient, Request, Response,
 };
@@ -227,6 +228,16 @@ pub struct FakeHttpClient {
 #[cfg(feature = "test-support")]
 impl FakeHttpClient {
     pub fn create<Fut, F>(handler: F) -> Arc<HttpClientWithUrl>
+    where
+        Fut: futures::Future<Output = Result<Response<AsyncBody>, Error>> + Send + 'static,
+        F: Fn(Request<AsyncBody>) -> Fut + Send + Sync + 'static,
+    {
+        Self::create_with_proxy(None, handler)
+    }
+
+    /// Returns a fake client that reports the given proxy, as if requests were
+    /// sent through it.
+    pub fn create_with_proxy<Fut, F>(proxy: Option<&str>, handler: F) -> Arc<HttpClientWithUrl>
     where
         Fut: futures::Future<Output = Result<Response<AsyncBody>, Error>> + Send + 'static,
         F: Fn(Request<AsyncBody>) -> Fut + Send + Sync + 'static,
@@ -236,7 +247,7 @@ impl FakeHttpClient {
             client: Arc::new(Self {
                 handler: Box::new(move |req| Box::pin(handler(req))),
             }),
-            proxy: None,
+            proxy: proxy.map(ToString::to_string),
         })
     }
 
@@ -0,0 +1,280 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use fs::{Fs, RemoveOptions};
+use futures::StreamExt;
+
+use crate::crawler::RustdocSource;
+
+/// How long the entries in a [`DocsCache`] are considered fresh, per source.
+///
+/// A TTL of `None` means the entries never expire.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CachePolicy {
+    /// The TTL for docs sourced locally, which change whenever they're rebuilt.
+    pub local_ttl: Option<Duration>,
+    /// The TTL for the `latest` docs on `docs.rs`, which change whenever a new
+    /// version of the crate is published, and for the docs for a release
+    /// channel of the standard library.
+    pub latest_ttl: Option<Duration>,
+}
+
+impl CachePolicy {
+    /// Returns the TTL for docs from the given source and crate version.
+    ///
+    /// The docs for a published version of a crate never change, so they are
+    /// cached indefinitely.
+    pub fn ttl(&self, source: RustdocSource, version: &str) -> Option<Duration> {
+        match (source, version) {
+            (RustdocSource::Local, _) => self.local_ttl,
+            (
+                RustdocSource::DocsDotRs | RustdocSource::CachedDocsDotRs,
+                "latest" | "stable" | "beta" | "nightly",
+            ) => self.latest_ttl,
+            (RustdocSource::DocsDotRs | RustdocSource::CachedDocsDotRs, _) => None,
+        }
+    }
+}
+
+/// Identifies a page in a [`DocsCache`].
+#[derive(Debug, Clone, Copy)]
+pub struct CacheKey<'a> {
+    pub source: RustdocSource,
+    pub crate_name: &'a str,
+    pub version: &'a str,
+    /// The location of the page within its source, e.g., its URL.
+    pub location: &'a str,
+}
+
+/// The number of entries and bytes that were removed from a [`DocsCache`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ClearedCache {
+    pub entry_count: usize,
+    pub byte_count: u64,
+}
+
+/// An on-disk cache of fetched docs pages.
+///
+/// Each crate has its own directory within the cache, with a subdirectory for
+/// each source and version that pages were fetched for.
+#[derive(Clone)]
+pub struct DocsCache {
+    fs: Arc<dyn Fs>,
+    cache_dir: PathBuf,
+    policy: CachePolicy,
+}
+
+impl DocsCache {
+    pub fn new(fs: Arc<dyn Fs>, cache_dir: PathBuf) -> Self {
+        Self {
+            fs,
+            cache_dir,
+            policy: CachePolicy::default(),
+        }
+    }
+
+    /// Expires the cached entries according to the given [`CachePolicy`].
+    pub fn with_policy(mut self, policy: CachePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    fn crate_dir(&self, crate_name: &str) -> PathBuf {
+        self.cache_dir.join(crate_name)
+    }
+
+    fn version_dir(&self, key: &CacheKey) -> PathBuf {
+        let source = match key.source {
+            RustdocSource::Local => "local",
+            RustdocSource::DocsDotRs | RustdocSource::CachedDocsDotRs => "docs-rs",
+        };
+        self.crate_dir(key.crate_name)
+            .join(format!("{source}@{version}", version = key.version))
+    }
+
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        let file_name = key
+            .location
+            .trim_start_matches("https://docs.rs/")
+            .trim_start_matches("https://doc.rust-lang.org/")
+            .replace('/', "%2F");
+        self.version_dir(key).join(format!("{file_name}.html"))
+    }
+
+    /// Returns the cached page for the given key, if there is one that hasn't
+    /// expired.
+    pub async fn load(&self, key: &CacheKey<'_>) -> Option<String> {
+        let entry_path = self.entry_path(key);
+        let (fetched_at, page) = split_fetched_at(self.fs.load(&entry_path).await.ok()?);
+        if let Some(ttl) = self.policy.ttl(key.source, key.version) {
+            // Entries cached before their fetch time was saved along with them
+            // are as old as the file.
+            let fetched_at = match fetched_at {
+                Some(fetched_at) => fetched_at,
+                None => self.fs.metadata(&entry_path).await.ok()??.mtime,
+            };
+            let age = fetched_at.elapsed().unwrap_or_default();
+            if age > ttl {
+                return None;
+            }
+        }
+
+        Some(page)
+    }
+
+    /// Returns the cached page for the given key, if there is one, even if it
+    /// has expired.
+    pub async fn load_expired(&self, key: &CacheKey<'_>) -> Option<String> {
+        let entry = self.fs.load(&self.entry_path(key)).await.ok()?;
+        Some(split_fetched_at(entry).1)
+    }
+
+    /// Caches the page for the given key, which was fetched just now.
+    pub async fn save(&self, key: &CacheKey<'_>, page: &str) -> Result<()> {
+        self.fs.create_dir(&self.version_dir(key)).await?;
+        self.fs
+            .atomic_write(
+                self.entry_path(key),
+                with_fetched_at(SystemTime::now(), page),
+            )
+            .await
+    }
+
+    /// Returns the paths to the cached entries for the given crate, or for all
+    /// crates when `None`.
+    pub async fn entries(&self, crate_name: Option<&str>) -> Result<Vec<PathBuf>> {
+        let crate_dirs = match crate_name {
+            Some(crate_name) => vec![self.crate_dir(crate_name)],
+            None => self.read_dir(self.cache_dir.clone()).await?,
+        };
+
+        let mut entries = Vec::new();
+        for crate_dir in crate_dirs {
+            for version_dir in self.read_dir(crate_dir).await? {
+                entries.extend(self.read_dir(version_dir).await?);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Removes the cached entries for the given crate, or for all crates when
+    /// `None`.
+    pub async fn clear(&self, crate_name: Option<&str>) -> Result<ClearedCache> {
+        let mut cleared = ClearedCache::default();
+        for entry in self.entries(crate_name).await? {
+            let byte_count = self.fs.load(&entry).await.map_or(0, |page| page.len());
+            self.fs
+                .remove_file(
+                    &entry,
+                    RemoveOptions {
+                        recursive: false,
+                        ignore_if_not_exists: true,
+                    },
+                )
+                .await
+                .with_context(|| format!("failed to remove cached docs at {entry:?}"))?;
+
+            cleared.entry_count += 1;
+            cleared.byte_count += byte_count as u64;
+        }
+
+        Ok(cleared)
+    }
+
+    async fn read_dir(&self, dir: PathBuf) -> Result<Vec<PathBuf>> {
+        if !self.fs.is_dir(&dir).await {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = self
+            .fs
+            .read_dir(&dir)
+            .await
+            .with_context(|| format!("reading cached docs from {dir:?}"))?;
+
+        let mut paths = Vec::new();
+        while let Some(entry) = entries.next().await {
+            paths.push(entry?);
+        }
+
+        Ok(paths)
+    }
+}
+
+/// Prefixes the given page with the time it was fetched at, which its age in
+/// the cache is measured from.
+pub(crate) fn with_fetched_at(fetched_at: SystemTime, page: &str) -> String {
+    let timestamp = fetched_at
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    format!("<!-- fetched at={timestamp} -->\n{page}")
+}
+
+/// Splits a cached entry into the time its page was fetched at, if it was
+/// saved with one, and the page.
+fn split_fetched_at(entry: String) -> (Option<SystemTime>, String) {
+    let Some(rest) = entry.strip_prefix("<!-- fetched at=") else {
+        return (None, entry);
+    };
+    let Some((timestamp, page)) = rest.split_once(" -->\n") else {
+        return (None, entry);
+    };
+    match timestamp.parse() {
+        Ok(timestamp) => (
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(timestamp)),
+            page.to_string(),
+        ),
+        Err(_) => (None, entry),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_policy_ttl() {
+        let policy = CachePolicy {
+            local_ttl: Some(Duration::from_secs(5 * 60)),
+            latest_ttl: Some(Duration::from_secs(24 * 60 * 60)),
+        };
+
+        assert_eq!(
+            policy.ttl(RustdocSource::Local, "latest"),
+            Some(Duration::from_secs(5 * 60))
+        );
+        assert_eq!(
+            policy.ttl(RustdocSource::DocsDotRs, "latest"),
+            Some(Duration::from_secs(24 * 60 * 60))
+        );
+        assert_eq!(
+            policy.ttl(RustdocSource::DocsDotRs, "nightly"),
+            Some(Duration::from_secs(24 * 60 * 60))
+        );
+        assert_eq!(policy.ttl(RustdocSource::DocsDotRs, "1.0.203"), None);
+    }
+
+    #[test]
+    fn test_fetched_at() {
+        let fetched_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_720_000_000);
+        let entry = with_fetched_at(fetched_at, "<html></html>");
+        assert_eq!(entry, "<!-- fetched at=1720000000 -->\n<html></html>");
+        assert_eq!(
+            split_fetched_at(entry),
+            (Some(fetched_at), "<html></html>".to_string())
+        );
+
+        // Entries cached before the fetch time was saved are loaded as is.
+        assert_eq!(
+            split_fetched_at("<!-- saved from url=https://docs.rs/foo/ -->\n".to_string()),
+            (
+                None,
+                "<!-- saved from url=https://docs.rs/foo/ -->\n".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+
+/// An in-memory cache of the Markdown converted from rustdoc pages, keyed by
+/// a hash of the page's HTML and of the options it was converted with.
+///
+/// Looking up the same item again converts the same HTML again, as the pages
+/// themselves are cached on disk by a [`crate::DocsCache`]. This cache serves
+/// those repeated conversions, evicting the least recently used entries once
+/// it holds more than its capacity.
+pub struct ConversionCache {
+    capacity: usize,
+    /// The cached Markdown, ordered from least to most recently used.
+    entries: Mutex<IndexMap<u64, String>>,
+}
+
+impl ConversionCache {
+    /// The number of conversions that the global cache holds.
+    pub const DEFAULT_CAPACITY: usize = 128;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(IndexMap::new()),
+        }
+    }
+
+    /// Returns the cache shared by every conversion in the process.
+    pub fn global() -> &'static Self {
+        static CACHE: OnceLock<ConversionCache> = OnceLock::new();
+        CACHE.get_or_init(|| Self::new(Self::DEFAULT_CAPACITY))
+    }
+
+    /// Returns the Markdown for the given HTML and options from the cache, or
+    /// converts it with the given function and caches the result.
+    ///
+    /// Failed conversions aren't cached.
+    pub fn get_or_convert(
+        &self,
+        html: &[u8],
+        options: &impl Hash,
+        convert: impl FnOnce() -> Result<String>,
+    ) -> Result<String> {
+        let key = Self::key(html, options);
+        if let Some(markdown) = self.get(key) {
+            return Ok(markdown);
+        }
+
+        let markdown = convert()?;
+        let mut entries = self.entries.lock();
+        entries.insert(key, markdown.clone());
+        while entries.len() > self.capacity {
+            entries.shift_remove_index(0);
+        }
+
+        Ok(markdown)
+    }
+
+    /// Returns the number of conversions in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, key: u64) -> Option<String> {
+        let mut entries = self.entries.lock();
+        // Move the entry to the back, as it is now the most recently used.
+        let markdown = entries.shift_remove(&key)?;
+        entries.insert(key, markdown.clone());
+        Some(markdown)
+    }
+
+    fn key(html: &[u8], options: &impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        html.hash(&mut hasher);
+        options.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_repeated_conversions_are_cached() {
+        let cache = ConversionCache::new(2);
+        let conversion_count = Cell::new(0);
+        let convert = |html: &str, options: &str| {
+            cache
+                .get_or_convert(html.as_bytes(), &options, || {
+                    conversion_count.set(conversion_count.get() + 1);
+                    Ok(format!("{html} ({options})"))
+                })
+                .unwrap()
+        };
+
+        assert_eq!(convert("<p>Mutex</p>", "full"), "<p>Mutex</p> (full)");
+        assert_eq!(convert("<p>Mutex</p>", "full"), "<p>Mutex</p> (full)");
+        assert_eq!(conversion_count.get(), 1);
+
+        // The same HTML converted with other options is another entry.
+        assert_eq!(
+            convert("<p>Mutex</p>", "definitions"),
+            "<p>Mutex</p> (definitions)"
+        );
+        assert_eq!(conversion_count.get(), 2);
+
+        // Using an entry keeps it from being the one that's evicted.
+        convert("<p>Mutex</p>", "full");
+        convert("<p>RwLock</p>", "full");
+        assert_eq!(cache.len(), 2);
+        assert_eq!(conversion_count.get(), 3);
+        convert("<p>Mutex</p>", "full");
+        assert_eq!(conversion_count.get(), 3);
+        convert("<p>Mutex</p>", "definitions");
+        assert_eq!(conversion_count.get(), 4);
+    }
+
+    #[test]
+    fn test_failed_conversions_are_not_cached() {
+        let cache = ConversionCache::new(2);
+        let result = cache.get_or_convert(b"<p>Mutex</p>", &(), || Err(anyhow::anyhow!("failed")));
+        assert!(result.is_err());
+        assert!(cache.is_empty());
+    }
+}
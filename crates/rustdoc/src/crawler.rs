@@ -1,22 +1,42 @@
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
 
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use async_compression::futures::bufread::GzipDecoder;
+use async_tar::Archive;
 use async_trait::async_trait;
-use collections::{HashSet, VecDeque};
+use collections::{HashMap, HashSet, VecDeque};
 use fs::Fs;
-use futures::AsyncReadExt;
-use http::{AsyncBody, HttpClient, HttpClientWithUrl};
+use futures::{AsyncBufReadExt, AsyncRead, AsyncReadExt, FutureExt, StreamExt};
+use http::{AsyncBody, ErrorKind, HttpClient, HttpClientWithUrl, Method, Request, StatusCode};
 use indexmap::IndexMap;
+use parking_lot::Mutex;
 
-use crate::{convert_rustdoc_to_markdown, RustdocItem, RustdocItemKind};
+use crate::to_markdown::{decode_html, is_docs_rs_build_failure_page, is_js_shell_page};
+use crate::workspace::{
+    cargo_target_dir, cargo_workspace, parse_crate_features, parse_docs_rs_metadata, CrateFeature,
+    DocsRsMetadata,
+};
+use crate::{
+    convert_docs_rs_features, convert_rustdoc_json_with_order,
+    convert_rustdoc_to_markdown_with_options, convert_rustdoc_to_reexports, docs_host_for_crate,
+    is_docs_not_found, normalize_crate_name, CacheKey, DocProvider, DocQuery, DocQueryMode,
+    DocsCache, DocsHost, DocsNotFound, LockedVersions, NotFoundCache, RustdocConversionOptions,
+    RustdocItem, RustdocItemKind, RustdocItemOrder, RustdocPageLocation, DEFAULT_STD_CHANNEL,
+};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RustdocSource {
     /// The docs were sourced from local `cargo doc` output.
     Local,
     /// The docs were sourced from `docs.rs`.
     DocsDotRs,
+    /// The docs were sourced from an expired copy of a `docs.rs` page in the
+    /// cache, as `docs.rs` couldn't be reached.
+    CachedDocsDotRs,
 }
 
 #[async_trait]
@@ -26,11 +46,114 @@ pub trait RustdocProvider {
         crate_name: &str,
         item: Option<&RustdocItem>,
     ) -> Result<Option<String>>;
+
+    /// Returns the docs for the whole crate at once, for the providers whose
+    /// docs aren't split into pages.
+    ///
+    /// When this returns `None`, the crate is crawled page by page instead.
+    async fn fetch_crate_docs(&self, _crate_name: &str) -> Result<Option<CrateDocs>> {
+        Ok(None)
+    }
+
+    /// Where the pages fetched by this provider come from.
+    fn source(&self) -> RustdocSource;
+
+    /// Returns when the docs for the given crate were last built, for the
+    /// providers whose docs can change after they were indexed.
+    async fn modified_at(&self, _crate_name: &str) -> Option<SystemTime> {
+        None
+    }
+
+    /// Returns the version of the given crate that this provider's docs are
+    /// for, if it is known.
+    async fn version(&self, _crate_name: &str) -> Option<String> {
+        None
+    }
+}
+
+#[async_trait]
+impl<T: RustdocProvider + Send + Sync + ?Sized> RustdocProvider for Arc<T> {
+    async fn fetch_page(
+        &self,
+        crate_name: &str,
+        item: Option<&RustdocItem>,
+    ) -> Result<Option<String>> {
+        self.as_ref().fetch_page(crate_name, item).await
+    }
+
+    async fn fetch_crate_docs(&self, crate_name: &str) -> Result<Option<CrateDocs>> {
+        self.as_ref().fetch_crate_docs(crate_name).await
+    }
+
+    fn source(&self) -> RustdocSource {
+        self.as_ref().source()
+    }
+
+    async fn modified_at(&self, crate_name: &str) -> Option<SystemTime> {
+        self.as_ref().modified_at(crate_name).await
+    }
+
+    async fn version(&self, crate_name: &str) -> Option<String> {
+        self.as_ref().version(crate_name).await
+    }
+}
+
+/// The features that the local `cargo doc` output was built with.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum CargoDocFeatures {
+    /// The crate's default features, as with a plain `cargo doc`.
+    #[default]
+    Default,
+    /// The given features, as with `cargo doc --features <features>`.
+    Features(Vec<String>),
+    /// All of the crate's features, as with `cargo doc --all-features`.
+    All,
 }
 
+impl CargoDocFeatures {
+    /// Parses the comma-separated list of features passed to `--features`.
+    pub fn parse(features: &str) -> Self {
+        Self::Features(
+            features
+                .split(',')
+                .map(|feature| feature.trim())
+                .filter(|feature| !feature.is_empty())
+                .map(ToString::to_string)
+                .collect(),
+        )
+    }
+
+    /// Returns the arguments to `cargo doc` that enable these features.
+    pub fn cargo_doc_args(&self) -> Vec<String> {
+        match self {
+            Self::Default => Vec::new(),
+            Self::Features(features) => vec!["--features".to_string(), features.join(",")],
+            Self::All => vec!["--all-features".to_string()],
+        }
+    }
+
+    /// Returns the arguments to `cargo doc` that enable these features, as
+    /// they're written after the command, e.g., ` --all-features`.
+    pub fn cargo_doc_flags(&self) -> String {
+        self.cargo_doc_args()
+            .iter()
+            .flat_map(|arg| [" ", arg.as_str()])
+            .collect()
+    }
+}
+
+#[derive(Clone)]
 pub struct LocalProvider {
     fs: Arc<dyn Fs>,
     cargo_workspace_root: PathBuf,
+    parent_module_fallback: bool,
+    features: CargoDocFeatures,
+    /// The target that the docs were built for with `cargo doc --target`, if
+    /// they were built for a specific one.
+    doc_target: Option<String>,
+    /// The directory that Cargo builds the workspace into, which is resolved
+    /// from the environment and the Cargo config when it is first needed.
+    target_dir: Arc<OnceLock<PathBuf>>,
 }
 
 impl LocalProvider {
@@ -38,8 +161,262 @@ impl LocalProvider {
         Self {
             fs,
             cargo_workspace_root,
+            parent_module_fallback: false,
+            features: CargoDocFeatures::Default,
+            doc_target: None,
+            target_dir: Arc::default(),
         }
     }
+
+    /// Reads the docs from the given target directory, rather than from the
+    /// one that Cargo is configured to use for the workspace.
+    pub fn with_target_dir(mut self, target_dir: PathBuf) -> Self {
+        self.target_dir = Arc::new(OnceLock::from(target_dir));
+        self
+    }
+
+    /// Applies the `[package.metadata.docs.rs]` of the given workspace crate,
+    /// as if its docs were built the way `docs.rs` builds them.
+    ///
+    /// The docs for the crate's default target are read when they have been
+    /// built (with `cargo doc --target <triple>`), and the features that
+    /// `docs.rs` enables are suggested when an item is missing, unless other
+    /// features were given.
+    pub async fn with_docs_rs_metadata(mut self, crate_name: &str) -> Self {
+        let metadata = match self.docs_rs_metadata(crate_name).await {
+            Ok(Some(metadata)) => metadata,
+            Ok(None) => return self,
+            Err(err) => {
+                log::error!("failed to read the docs.rs metadata of {crate_name}: {err:?}");
+                return self;
+            }
+        };
+
+        if let Some(default_target) = metadata.default_target() {
+            let doc_path = self.doc_dir(Some(default_target)).await;
+            if self.fs.is_dir(&doc_path).await {
+                self.doc_target = Some(default_target.to_string());
+            } else {
+                log::info!(
+                    "no docs built for the default target of {crate_name} ({default_target}), \
+                    using the docs for the host target"
+                );
+            }
+        }
+        if self.features == CargoDocFeatures::Default {
+            self.features = metadata.features();
+        }
+
+        self
+    }
+
+    async fn docs_rs_metadata(&self, crate_name: &str) -> Result<Option<DocsRsMetadata>> {
+        match self.member_manifest(crate_name).await? {
+            Some(manifest) => parse_docs_rs_metadata(&manifest),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the features declared in the `Cargo.toml` of the given crate,
+    /// or `None` if it isn't a member of the workspace.
+    ///
+    /// The manifests of dependencies aren't part of the workspace, so their
+    /// features are only known from `docs.rs`.
+    pub async fn crate_features(&self, crate_name: &str) -> Result<Option<Vec<CrateFeature>>> {
+        match self.member_manifest(crate_name).await? {
+            Some(manifest) => parse_crate_features(&manifest).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the contents of the `Cargo.toml` of the given workspace member.
+    async fn member_manifest(&self, crate_name: &str) -> Result<Option<String>> {
+        let workspace = cargo_workspace(self.fs.as_ref(), &self.cargo_workspace_root).await?;
+        let Some(member) = workspace.member(crate_name) else {
+            return Ok(None);
+        };
+
+        let manifest_path = member.path.join("Cargo.toml");
+        let manifest = self
+            .fs
+            .load(&manifest_path)
+            .await
+            .with_context(|| format!("failed to read {manifest_path:?}"))?;
+        Ok(Some(manifest))
+    }
+
+    /// Returns the `cargo doc` output directory for the given target.
+    async fn doc_dir(&self, target: Option<&str>) -> PathBuf {
+        let mut doc_dir = match self.target_dir.get() {
+            Some(target_dir) => target_dir.clone(),
+            None => {
+                let target_dir =
+                    cargo_target_dir(self.fs.as_ref(), &self.cargo_workspace_root).await;
+                self.target_dir.get_or_init(|| target_dir).clone()
+            }
+        };
+        // Docs built with `cargo doc --target <triple>` are output per target.
+        if let Some(target) = target {
+            doc_dir.push(target);
+        }
+        doc_dir.push("doc");
+        doc_dir
+    }
+
+    /// Records the features that the local docs were built with.
+    pub fn with_features(mut self, features: CargoDocFeatures) -> Self {
+        self.features = features;
+        self
+    }
+
+    pub fn features(&self) -> &CargoDocFeatures {
+        &self.features
+    }
+
+    /// Returns a hint for when an item is missing from the local docs, as it
+    /// may be behind a feature that they weren't built with.
+    ///
+    /// Returns `None` if the docs were built with all features.
+    pub fn missing_item_hint(&self) -> Option<String> {
+        if self.features == CargoDocFeatures::All {
+            return None;
+        }
+
+        Some(format!(
+            "The item may be behind a feature that wasn't enabled when the local docs were \
+            built with `cargo doc{args}`. Try building them with `cargo doc --all-features` \
+            and passing `--all-features`",
+            args = self.features.cargo_doc_flags()
+        ))
+    }
+
+    /// Serves the docs for the parent module of an item that has no local
+    /// docs, if the parent module has them.
+    pub fn with_parent_module_fallback(mut self, parent_module_fallback: bool) -> Self {
+        self.parent_module_fallback = parent_module_fallback;
+        self
+    }
+
+    /// Returns the path to the local `cargo doc` page for the given query.
+    pub async fn docs_path(&self, query: &DocQuery) -> PathBuf {
+        let target = query.target.as_ref().or(self.doc_target.as_ref());
+        let mut local_cargo_doc_path = self.doc_dir(target.map(String::as_str)).await;
+        local_cargo_doc_path.push(normalize_crate_name(&query.crate_name));
+        if query.mode == DocQueryMode::AllItems {
+            local_cargo_doc_path.push("all.html");
+            return local_cargo_doc_path;
+        }
+        if !query.item_path.is_empty() {
+            local_cargo_doc_path.push(query.item_path.join("/"));
+        }
+        local_cargo_doc_path.push("index.html");
+        local_cargo_doc_path
+    }
+
+    /// Resolves the given crate name to the name of its directory in
+    /// `target/doc`, returning a descriptive error if there are no local docs
+    /// for it.
+    ///
+    /// Crates that are members of the workspace are located through the root
+    /// `Cargo.toml`, which may be a virtual manifest without a `[package]`.
+    /// Their docs are still in the `target/doc` at the workspace root, as
+    /// Cargo shares one target directory between all of the members.
+    pub async fn resolve_crate_name(&self, crate_name: &str) -> Result<String> {
+        let crate_name = crate_name.replace('-', "_");
+        let docs_path = self
+            .doc_dir(self.doc_target.as_deref())
+            .await
+            .join(&crate_name)
+            .join("index.html");
+        if self.fs.is_file(&docs_path).await {
+            return Ok(crate_name);
+        }
+
+        let workspace = cargo_workspace(self.fs.as_ref(), &self.cargo_workspace_root).await?;
+        if let Some(member) = workspace.member(&crate_name) {
+            bail!(
+                "no local docs found for workspace member `{crate_name}` (in {path:?}). \
+                Build them with `cargo doc -p {package_name}{args}`",
+                path = member.path,
+                package_name = member.package_name,
+                args = self.features.cargo_doc_flags()
+            );
+        }
+
+        let kind = if workspace.is_virtual {
+            "virtual workspace"
+        } else {
+            "workspace"
+        };
+        bail!(
+            "`{crate_name}` is not a member of the Cargo {kind} at {root:?} and has no local docs. \
+            The members of the workspace are: {members}",
+            root = self.cargo_workspace_root,
+            members = workspace.member_names()
+        )
+    }
+
+    /// Builds the local docs for the given crate with `cargo doc`, with the
+    /// features that they are read with.
+    ///
+    /// Each line of the output of Cargo, which reports its progress on
+    /// stderr, is passed to `on_output` as it's written. Returns all of the
+    /// output, or an error with it if the build failed.
+    pub async fn build_docs(
+        &self,
+        crate_name: &str,
+        on_output: &(dyn Fn(&str) + Send + Sync),
+    ) -> Result<String> {
+        // `cargo doc -p` takes the name of the package, which may differ from
+        // that of the crate (e.g., `serde-json` for `serde_json`).
+        let workspace = cargo_workspace(self.fs.as_ref(), &self.cargo_workspace_root).await?;
+        let package_name = workspace
+            .member(crate_name)
+            .map_or(crate_name, |member| member.package_name.as_str());
+        let mut args = vec![
+            "-p".to_string(),
+            package_name.to_string(),
+            "--no-deps".to_string(),
+        ];
+        args.extend(self.features.cargo_doc_args());
+        if let Some(doc_target) = &self.doc_target {
+            args.extend(["--target".to_string(), doc_target.clone()]);
+        }
+
+        run_cargo_doc(&self.cargo_workspace_root, &args, on_output).await
+    }
+
+    /// Returns the names of all of the crates with docs in `target/doc`.
+    ///
+    /// This includes the dependencies of the workspace crates, unless the docs
+    /// were built with `cargo doc --no-deps`.
+    pub async fn crate_names(&self) -> Result<Vec<String>> {
+        let local_cargo_doc_path = self.doc_dir(self.doc_target.as_deref()).await;
+
+        let mut entries = self
+            .fs
+            .read_dir(&local_cargo_doc_path)
+            .await
+            .with_context(|| format!("reading docs from {local_cargo_doc_path:?}"))?;
+
+        let mut crate_names = Vec::new();
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+
+            // Each documented crate has its own directory with an `index.html`,
+            // which distinguishes it from directories like `src` and `static.files`.
+            if !self.fs.is_file(&entry.join("index.html")).await {
+                continue;
+            }
+
+            if let Some(crate_name) = entry.file_name().and_then(|name| name.to_str()) {
+                crate_names.push(crate_name.to_string());
+            }
+        }
+        crate_names.sort();
+
+        Ok(crate_names)
+    }
 }
 
 #[async_trait]
@@ -49,7 +426,7 @@ impl RustdocProvider for LocalProvider {
         crate_name: &str,
         item: Option<&RustdocItem>,
     ) -> Result<Option<String>> {
-        let mut local_cargo_doc_path = self.cargo_workspace_root.join("target/doc");
+        let mut local_cargo_doc_path = self.doc_dir(self.doc_target.as_deref()).await;
         local_cargo_doc_path.push(&crate_name);
         if let Some(item) = item {
             local_cargo_doc_path.push(item.url_path());
@@ -57,23 +434,1082 @@ impl RustdocProvider for LocalProvider {
             local_cargo_doc_path.push("index.html");
         }
 
-        println!("Fetching {}", local_cargo_doc_path.display());
+        log::debug!(
+            "fetching local docs from {}",
+            local_cargo_doc_path.display()
+        );
+
+        let Ok(contents) = self.fs.load(&local_cargo_doc_path).await else {
+            return Ok(None);
+        };
+
+        Ok(Some(contents))
+    }
+
+    fn source(&self) -> RustdocSource {
+        RustdocSource::Local
+    }
+
+    async fn modified_at(&self, crate_name: &str) -> Option<SystemTime> {
+        let mut index_path = self.doc_dir(self.doc_target.as_deref()).await;
+        index_path.push(crate_name);
+        index_path.push("index.html");
+        let metadata = self.fs.metadata(&index_path).await.ok()??;
+        Some(metadata.mtime)
+    }
+
+    /// Returns the version of the given crate in the workspace's `Cargo.lock`,
+    /// which `cargo doc` built the docs for.
+    ///
+    /// The crates of the workspace itself have no known version.
+    async fn version(&self, crate_name: &str) -> Option<String> {
+        let lockfile_path = self.cargo_workspace_root.join("Cargo.lock");
+        let versions = LockedVersions::load(self.fs.as_ref(), &lockfile_path)
+            .await
+            .ok()?;
+        versions.get(crate_name).map(ToString::to_string)
+    }
+}
+
+#[async_trait]
+impl DocProvider for LocalProvider {
+    fn name(&self) -> String {
+        "the local docs".to_string()
+    }
+
+    async fn fetch(&self, query: &DocQuery) -> Result<Option<(RustdocSource, String)>> {
+        // The pages are read as bytes, as they're transcoded from the charset
+        // that they declare when they're converted.
+        let docs_path = self.docs_path(query).await;
+        log::debug!("fetching local docs from {}", docs_path.display());
+        if let Ok(contents) = self.fs.load_bytes(&docs_path).await {
+            let markdown = query.convert(&contents)?;
+            return Ok(Some((RustdocSource::Local, markdown)));
+        }
+
+        let Some(parent_query) = query.parent_query() else {
+            return Ok(None);
+        };
+        let parent_docs_path = self.docs_path(&parent_query).await;
+        let Ok(contents) = self.fs.load_bytes(&parent_docs_path).await else {
+            return Ok(None);
+        };
+
+        // Items that are re-exported from elsewhere don't have a page at the
+        // path they are re-exported at.
+        if let Some(reexport) = query.find_reexport(&contents)? {
+            let is_local = !reexport.href.contains("://");
+            if let Some(parent_dir) = parent_docs_path.parent().filter(|_| is_local) {
+                if let Ok(item_contents) =
+                    self.fs.load_bytes(&parent_dir.join(&reexport.href)).await
+                {
+                    let markdown = query.convert_reexport(&item_contents, &reexport)?;
+                    return Ok(Some((RustdocSource::Local, markdown)));
+                }
+            }
+        }
+
+        // Items other than modules, e.g., macros, have a page named after
+        // their kind in the directory of their parent module.
+        if let Some(item) = query.find_item_page(&contents)? {
+            if let Some(parent_dir) = parent_docs_path.parent() {
+                if let Ok(item_contents) =
+                    self.fs.load_bytes(&parent_dir.join(item.url_path())).await
+                {
+                    let markdown = query.convert_item_page(&item_contents)?;
+                    return Ok(Some((RustdocSource::Local, markdown)));
+                }
+            }
+        }
+
+        if !self.parent_module_fallback {
+            return Ok(None);
+        }
+
+        let parent_path = std::iter::once(&parent_query.crate_name)
+            .chain(&parent_query.item_path)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("::");
+        let markdown = format!(
+            "> **Note:** No local docs were found for `{parent_path}::{item_name}`, so these \
+            are the docs for its parent module, `{parent_path}`.\n\n{docs}",
+            item_name = query.item_path.last().map_or("", String::as_str),
+            docs = parent_query.convert(&contents)?
+        );
+
+        Ok(Some((RustdocSource::Local, markdown)))
+    }
+}
+
+/// A provider of the docs for a crate from its `.crate` tarball (e.g., as
+/// downloaded from crates.io), for versions that aren't checked out locally
+/// and that `docs.rs` failed to build the docs for.
+///
+/// The tarball is extracted and the crate's docs are built there with
+/// `cargo doc`, after which they are read like those of a [`LocalProvider`].
+#[derive(Clone)]
+pub struct TarballProvider {
+    fs: Arc<dyn Fs>,
+    tarball_path: PathBuf,
+    /// The directory that the tarball is extracted to.
+    extract_dir: PathBuf,
+    features: CargoDocFeatures,
+}
+
+impl TarballProvider {
+    pub fn new(fs: Arc<dyn Fs>, tarball_path: PathBuf, extract_dir: PathBuf) -> Self {
+        Self {
+            fs,
+            tarball_path,
+            extract_dir,
+            features: CargoDocFeatures::Default,
+        }
+    }
+
+    /// Sets the features that the crate's docs are built with.
+    pub fn with_features(mut self, features: CargoDocFeatures) -> Self {
+        self.features = features;
+        self
+    }
+
+    pub fn features(&self) -> &CargoDocFeatures {
+        &self.features
+    }
+
+    /// Returns the directory of the extracted crate.
+    pub fn crate_dir(&self) -> Result<PathBuf> {
+        tarball_crate_dir(&self.tarball_path, &self.extract_dir)
+    }
+
+    /// Returns the features declared in the `Cargo.toml` of the extracted
+    /// crate.
+    pub async fn crate_features(&self) -> Result<Vec<CrateFeature>> {
+        let manifest_path = self.crate_dir()?.join("Cargo.toml");
+        let manifest = self
+            .fs
+            .load(&manifest_path)
+            .await
+            .with_context(|| format!("failed to read {manifest_path:?}"))?;
+        parse_crate_features(&manifest)
+    }
+
+    /// Extracts the tarball and builds the crate's docs with `cargo doc`,
+    /// passing each line of its output to `on_output` as it's written.
+    ///
+    /// Docs that were already built, e.g., for an earlier extraction of the
+    /// same tarball, are reused.
+    pub async fn build_docs(&self, on_output: &(dyn Fn(&str) + Send + Sync)) -> Result<()> {
+        let crate_dir = self.crate_dir()?;
+        if self.fs.is_dir(&crate_dir.join("target").join("doc")).await {
+            return Ok(());
+        }
+
+        let tarball = self
+            .fs
+            .load_bytes(&self.tarball_path)
+            .await
+            .with_context(|| format!("failed to read {:?}", self.tarball_path))?;
+        let tarball = GzipDecoder::new(futures::io::Cursor::new(tarball));
+        futures::pin_mut!(tarball);
+        let tarball: Pin<&mut (dyn AsyncRead + Send)> = tarball;
+        self.fs
+            .extract_tar_file(&self.extract_dir, Archive::new(tarball))
+            .await
+            .with_context(|| format!("failed to extract {:?}", self.tarball_path))?;
+        if !self.fs.is_file(&crate_dir.join("Cargo.toml")).await {
+            bail!("{:?} has no Cargo.toml at {crate_dir:?}", self.tarball_path);
+        }
+
+        // The target directory is given explicitly, so that the docs aren't
+        // built elsewhere when `CARGO_TARGET_DIR` is set.
+        let mut args = vec![
+            "--no-deps".to_string(),
+            "--target-dir".to_string(),
+            "target".to_string(),
+        ];
+        args.extend(self.features.cargo_doc_args());
+        run_cargo_doc(&crate_dir, &args, on_output)
+            .await
+            .with_context(|| format!("failed to build the docs in {crate_dir:?}"))?;
+
+        Ok(())
+    }
+}
+
+/// Runs `cargo doc` with the given arguments in the given directory, passing
+/// each line of the progress that Cargo reports on stderr to `on_output` as
+/// it's written.
+///
+/// Returns all of the output, or an error with it if the build failed.
+async fn run_cargo_doc(
+    dir: &Path,
+    args: &[String],
+    on_output: &(dyn Fn(&str) + Send + Sync),
+) -> Result<String> {
+    let mut child = smol::process::Command::new("cargo")
+        .current_dir(dir)
+        .arg("doc")
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run cargo doc")?;
+
+    let mut output = String::new();
+    if let Some(stderr) = child.stderr.take() {
+        let mut lines = futures::io::BufReader::new(stderr).lines();
+        while let Some(line) = lines.next().await {
+            let line = line.context("failed to read the output of cargo doc")?;
+            on_output(&line);
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+    let status = child.status().await.context("failed to run cargo doc")?;
+    let output = output.trim_end().to_string();
+    if !status.success() {
+        bail!("cargo doc {} failed: {output}", args.join(" "));
+    }
+
+    Ok(output)
+}
+
+/// Returns the directory that the crate in the given `.crate` tarball is
+/// extracted to, which is named after the tarball (e.g., `serde-1.0.200` for
+/// `serde-1.0.200.crate`).
+fn tarball_crate_dir(tarball_path: &Path, extract_dir: &Path) -> Result<PathBuf> {
+    let crate_dir_name = tarball_path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .and_then(|file_name| file_name.strip_suffix(".crate"))
+        .filter(|crate_dir_name| !crate_dir_name.is_empty())
+        .ok_or_else(|| anyhow!("{tarball_path:?} is not a .crate tarball"))?;
+    Ok(extract_dir.join(crate_dir_name))
+}
+
+#[async_trait]
+impl RustdocProvider for TarballProvider {
+    async fn fetch_page(
+        &self,
+        crate_name: &str,
+        item: Option<&RustdocItem>,
+    ) -> Result<Option<String>> {
+        // The docs are built into the crate's own `target`, whatever the
+        // target directory is configured to be.
+        let crate_dir = self.crate_dir()?;
+        LocalProvider::new(self.fs.clone(), crate_dir.clone())
+            .with_target_dir(crate_dir.join("target"))
+            .fetch_page(crate_name, item)
+            .await
+    }
+
+    fn source(&self) -> RustdocSource {
+        RustdocSource::Local
+    }
+}
+
+/// Reads the docs of a local crate from the JSON output of rustdoc, as built
+/// with `cargo +nightly rustdoc -p <crate> -- -Z unstable-options
+/// --output-format json`.
+///
+/// The JSON describes the crate's items directly, so the whole crate is
+/// converted at once, without depending on the HTML that the version of
+/// rustdoc generates.
+#[derive(Clone)]
+pub struct JsonProvider {
+    fs: Arc<dyn Fs>,
+    cargo_workspace_root: PathBuf,
+    /// The order to list the items of each module in.
+    item_order: RustdocItemOrder,
+}
+
+impl JsonProvider {
+    pub fn new(fs: Arc<dyn Fs>, cargo_workspace_root: PathBuf) -> Self {
+        Self {
+            fs,
+            cargo_workspace_root,
+            item_order: RustdocItemOrder::Page,
+        }
+    }
+
+    /// Lists the items of each module in the given order, e.g., in the order
+    /// that the module declares them in.
+    pub fn with_item_order(mut self, item_order: RustdocItemOrder) -> Self {
+        self.item_order = item_order;
+        self
+    }
+
+    /// Returns the path to the rustdoc JSON for the given crate, which is
+    /// output next to the HTML docs (e.g., `target/doc/tokio.json`).
+    pub async fn json_path(&self, crate_name: &str) -> PathBuf {
+        let mut json_path = cargo_target_dir(self.fs.as_ref(), &self.cargo_workspace_root).await;
+        json_path.push("doc");
+        json_path.push(format!("{}.json", normalize_crate_name(crate_name)));
+        json_path
+    }
+
+    /// Returns whether the rustdoc JSON for the given crate has been built.
+    pub async fn has_json(&self, crate_name: &str) -> bool {
+        self.fs.is_file(&self.json_path(crate_name).await).await
+    }
+}
+
+#[async_trait]
+impl RustdocProvider for JsonProvider {
+    async fn fetch_page(
+        &self,
+        _crate_name: &str,
+        _item: Option<&RustdocItem>,
+    ) -> Result<Option<String>> {
+        // There are no pages, as the whole crate is read by `fetch_crate_docs`.
+        Ok(None)
+    }
+
+    async fn fetch_crate_docs(&self, crate_name: &str) -> Result<Option<CrateDocs>> {
+        let json_path = self.json_path(crate_name).await;
+        let json = self
+            .fs
+            .load(&json_path)
+            .await
+            .with_context(|| format!("failed to read {json_path:?}"))?;
+        let crate_docs = convert_rustdoc_json_with_order(&json, crate_name, self.item_order)
+            .with_context(|| format!("failed to convert {json_path:?}"))?;
+        Ok(Some(crate_docs))
+    }
+
+    fn source(&self) -> RustdocSource {
+        RustdocSource::Local
+    }
+}
+
+/// The result of checking whether `docs.rs` can be reached.
+#[derive(Debug, Clone)]
+pub struct DocsDotRsPing {
+    pub url: &'static str,
+    /// The HTTP status code of the response.
+    pub status: u16,
+    /// How long it took to receive the response.
+    pub latency: Duration,
+}
+
+impl DocsDotRsPing {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// An error for a request that docs.rs rejected, e.g., because the page
+/// doesn't exist.
+#[derive(Debug)]
+pub(crate) struct ClientError {
+    pub(crate) status: u16,
+    pub(crate) response: String,
+}
+
+impl std::fmt::Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "status error {}, response: {:?}",
+            self.status, self.response
+        )
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// An error for a request that docs.rs rejected because too many requests
+/// were sent to it recently.
+#[derive(Debug)]
+pub(crate) struct RateLimited {
+    /// How long docs.rs asked to wait before sending another request, if it
+    /// said.
+    pub(crate) retry_after: Option<Duration>,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.retry_after {
+            Some(retry_after) => write!(
+                f,
+                "docs.rs is rate limiting requests; try again in {} seconds",
+                retry_after.as_secs().max(1)
+            ),
+            None => write!(f, "docs.rs is rate limiting requests; try again later"),
+        }
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// An error for a crate version that docs.rs failed to build the docs for, so
+/// that it serves a page saying so instead.
+#[derive(Debug)]
+pub(crate) struct BuildFailed {
+    pub(crate) crate_name: String,
+    pub(crate) version: String,
+}
+
+impl std::fmt::Display for BuildFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "docs.rs has no successful build for {crate_name}@{version}; try \
+            `/rustdoc --index {crate_name} --build` for local docs",
+            crate_name = self.crate_name,
+            version = self.version
+        )
+    }
+}
+
+impl std::error::Error for BuildFailed {}
+
+/// A response from `docs.rs`, after following its redirects.
+#[derive(Debug)]
+struct DocsDotRsResponse {
+    /// The URL that the response is for, which differs from the requested one
+    /// when `docs.rs` redirected the request.
+    url: String,
+    status: StatusCode,
+    /// How long `docs.rs` asked to wait before retrying, from the
+    /// `Retry-After` header of the response.
+    retry_after: Option<Duration>,
+    body: Vec<u8>,
+}
+
+/// A page fetched from `docs.rs`, or from the cache.
+#[derive(Debug)]
+struct FetchedPage {
+    source: RustdocSource,
+    /// The URL that the page was found at, after following redirects.
+    url: String,
+    body: String,
+}
+
+pub struct DocsDotRsProvider {
+    http_client: Arc<HttpClientWithUrl>,
+    cache: Option<DocsCache>,
+    /// The maximum size of a response body, in bytes.
+    max_body_size: Option<u64>,
+    /// The release channel to fetch the standard library docs for.
+    std_channel: String,
+    /// The number of times to try each request before giving up.
+    max_attempts: u32,
+    /// The delay before the first retry, which doubles with each attempt.
+    retry_base_delay: Duration,
+    /// How long to wait for each attempt, including reading the body.
+    timeout: Duration,
+    /// The URL that each page was found at, keyed by the URL it was requested
+    /// at.
+    resolved_urls: Mutex<HashMap<String, String>>,
+    /// The version of the crates to crawl when indexing them.
+    crawl_version: String,
+    /// The servers to fetch the docs for some crates from instead of
+    /// `docs.rs`.
+    hosts: Vec<DocsHost>,
+    /// The `Cargo.lock`s to look up the version of the crates to fetch the
+    /// docs for in, in order.
+    lockfiles: Option<(Arc<dyn Fs>, Vec<PathBuf>)>,
+    /// The pages that were recently not found, which aren't requested again
+    /// until their entries expire.
+    not_found_cache: Option<Arc<NotFoundCache>>,
+}
+
+impl DocsDotRsProvider {
+    const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+    const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+    /// The maximum number of redirects to follow for a single request.
+    ///
+    /// `docs.rs` chains a few of them, e.g., from `latest` to the concrete
+    /// version and then to the default target.
+    const MAX_REDIRECTS: usize = 10;
+
+    pub fn new(http_client: Arc<HttpClientWithUrl>) -> Self {
+        Self {
+            http_client,
+            cache: None,
+            max_body_size: None,
+            std_channel: DEFAULT_STD_CHANNEL.to_string(),
+            max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+            retry_base_delay: Self::DEFAULT_RETRY_BASE_DELAY,
+            timeout: Self::DEFAULT_TIMEOUT,
+            resolved_urls: Mutex::new(HashMap::default()),
+            crawl_version: "latest".to_string(),
+            hosts: Vec::new(),
+            lockfiles: None,
+            not_found_cache: None,
+        }
+    }
+
+    /// Caches the fetched pages in the given [`DocsCache`], and serves them
+    /// from it on subsequent fetches.
+    pub fn with_cache(mut self, cache: DocsCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Rejects responses with a body larger than the given number of bytes.
+    pub fn with_max_body_size(mut self, max_body_size: u64) -> Self {
+        self.max_body_size = Some(max_body_size);
+        self
+    }
+
+    /// Fetches the docs for the standard library from the given release
+    /// channel (e.g., `nightly`) on `doc.rust-lang.org`, rather than from
+    /// `stable`.
+    pub fn with_std_channel(mut self, std_channel: String) -> Self {
+        self.std_channel = std_channel;
+        self
+    }
+
+    /// Tries each request up to the given number of times when `docs.rs`
+    /// can't be reached or responds with a server error, waiting `base_delay`
+    /// before the first retry and twice as long before each subsequent one.
+    pub fn with_retries(mut self, max_attempts: u32, base_delay: Duration) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self.retry_base_delay = base_delay;
+        self
+    }
+
+    /// Gives up on a request when `docs.rs` hasn't responded with the full
+    /// body within the given duration, which counts as a failed attempt.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Crawls the docs for the given version of the crates when indexing
+    /// them (e.g., the version in a `Cargo.lock`), rather than for `latest`.
+    pub fn with_crawl_version(mut self, version: String) -> Self {
+        self.crawl_version = version;
+        self
+    }
+
+    /// Fetches the docs for the crates that the given servers serve from them
+    /// rather than from `docs.rs`, sending their headers with each request.
+    ///
+    /// The pages are still keyed by their URL on `docs.rs`, both in the cache
+    /// and in [`Self::resolved_url`].
+    pub fn with_hosts(mut self, hosts: Vec<DocsHost>) -> Self {
+        self.hosts = hosts;
+        self
+    }
+
+    /// Fetches the docs for the version of each crate that the first of the
+    /// given `Cargo.lock`s that has it is locked to, rather than for `latest`,
+    /// unless a query is for a specific version.
+    pub fn with_lockfiles(mut self, fs: Arc<dyn Fs>, lockfile_paths: Vec<PathBuf>) -> Self {
+        self.lockfiles = Some((fs, lockfile_paths));
+        self
+    }
+
+    /// Records the pages that `docs.rs` responds to with a 404 in the given
+    /// [`NotFoundCache`], and fails fast for the pages recorded there rather
+    /// than requesting them again.
+    pub fn with_not_found_cache(mut self, not_found_cache: Arc<NotFoundCache>) -> Self {
+        self.not_found_cache = Some(not_found_cache);
+        self
+    }
+
+    /// Returns the given query for the locked version of its crate, if it
+    /// isn't for a specific version already.
+    pub async fn locked_query(&self, query: &DocQuery) -> DocQuery {
+        let mut query = query.clone();
+        let Some((fs, lockfile_paths)) = self.lockfiles.as_ref() else {
+            return query;
+        };
+        if query.version.is_some() || query.is_standard_library() {
+            return query;
+        }
+
+        for lockfile_path in lockfile_paths {
+            let versions = match LockedVersions::load(fs.as_ref(), lockfile_path).await {
+                Ok(versions) => versions,
+                Err(err) => {
+                    log::error!("failed to read the locked versions: {err:#}");
+                    continue;
+                }
+            };
+            if let Some(version) = versions.get(&query.crate_name) {
+                query.version = Some(version.clone());
+                break;
+            }
+        }
+        query
+    }
+
+    /// Returns the URL that the given crate's page at the given `docs.rs` URL
+    /// is served at, i.e., on the server for the crate if there is one.
+    pub fn host_url(&self, crate_name: &str, url: &str) -> String {
+        docs_host_for_crate(&self.hosts, crate_name)
+            .and_then(|host| host.docs_url(url))
+            .unwrap_or_else(|| url.to_string())
+    }
+
+    /// Returns whether the given crate's page at the given URL exists, after
+    /// following any redirects, bypassing the cache.
+    pub async fn page_exists(&self, crate_name: &str, url: &str) -> bool {
+        let url = self.host_url(crate_name, url);
+        matches!(self.get(&url).await, Ok(Ok(response)) if response.status.is_success())
+    }
+
+    /// Returns the URL that the page requested at the given URL was found at,
+    /// after following the redirects of `docs.rs`, if this provider fetched it.
+    pub fn resolved_url(&self, url: &str) -> Option<String> {
+        self.resolved_urls.lock().get(url).cloned()
+    }
+
+    /// Returns the version of the crate that the page requested at the given
+    /// URL was found at, e.g., `1.38.0` for a page requested at `latest`.
+    pub fn resolved_version(&self, url: &str) -> Option<String> {
+        docs_dot_rs_url_version(&self.resolved_url(url)?).map(ToString::to_string)
+    }
+
+    /// Fetches the features of the given version of a crate, as listed on its
+    /// features page on `docs.rs`.
+    pub async fn fetch_features(
+        &self,
+        crate_name: &str,
+        version: &str,
+    ) -> Result<Vec<CrateFeature>> {
+        let url = format!("https://docs.rs/crate/{crate_name}/{version}/features");
+        let page = self.fetch_url(crate_name, version, &url).await?;
+        convert_docs_rs_features(page.body.as_bytes())
+    }
+}
+
+/// Returns the version of the crate in the given `docs.rs` URL, e.g., `1.38.0`
+/// for `https://docs.rs/tokio/1.38.0/tokio/`.
+///
+/// Returns `None` for `latest`, which isn't a version of its own.
+fn docs_dot_rs_url_version(url: &str) -> Option<&str> {
+    let mut segments = url.strip_prefix("https://docs.rs/")?.split('/');
+    let version = match segments.next()? {
+        // The overview page that `docs.rs` redirects to for crates without
+        // docs, e.g., `/crate/tokio/1.38.0`.
+        "crate" => segments.nth(1)?,
+        _ => segments.next()?,
+    };
+    Some(version).filter(|version| !version.is_empty() && *version != "latest")
+}
+
+/// Returns why the page that `docs.rs` responded with for the given crate is
+/// an error page rather than its docs, if it is.
+///
+/// `docs.rs` serves these with a `200 OK` status, e.g., after redirecting the
+/// request for a crate whose docs failed to build to the crate's overview page.
+///
+/// `version` is the version that was requested, for when the URL doesn't say.
+fn docs_dot_rs_error_page(
+    crate_name: &str,
+    version: &str,
+    url: &str,
+    body: &str,
+) -> Option<anyhow::Error> {
+    let path = url.strip_prefix("https://docs.rs/")?;
+    if path.starts_with("releases/search") || body.contains("The requested crate does not exist") {
+        // This is as good as there being no docs for the crate, so that
+        // similar crate names are suggested instead.
+        return Some(
+            anyhow::Error::new(DocsNotFound {
+                crate_name: crate_name.to_string(),
+            })
+            .context(format!("docs.rs has no crate named {crate_name}")),
+        );
+    }
+    // The features page is one of the crate's pages rather than its docs, so
+    // it is only an error page when a request for the docs redirected to it.
+    if path.ends_with("/features") {
+        return None;
+    }
+
+    let version = docs_dot_rs_url_version(url).unwrap_or(version);
+    if is_docs_rs_build_failure_page(body) {
+        return Some(anyhow!(BuildFailed {
+            crate_name: crate_name.to_string(),
+            version: version.to_string(),
+        }));
+    }
+    if !path.starts_with("crate/") {
+        return None;
+    }
+
+    Some(anyhow!(
+        "docs.rs has no docs for version {version} of {crate_name}, and redirected to {url} \
+        instead"
+    ))
+}
+
+/// Prefixes the given page with the URL it was found at, so that the page is
+/// resolved against that URL when it is loaded from the cache.
+fn with_page_url(url: &str, body: &str) -> String {
+    format!("<!-- saved from url={url} -->\n{body}")
+}
+
+/// Splits a cached page into the URL it was found at, if it was saved with
+/// one, and its body.
+fn split_page_url(page: String) -> (Option<String>, String) {
+    let Some(rest) = page.strip_prefix("<!-- saved from url=") else {
+        return (None, page);
+    };
+    match rest.split_once(" -->\n") {
+        Some((url, body)) => (Some(url.to_string()), body.to_string()),
+        None => (None, page),
+    }
+}
+
+/// Parses the value of a `Retry-After` header.
+///
+/// Only the number of seconds to wait is supported, not the date to wait
+/// until, which `docs.rs` doesn't send.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+impl DocsDotRsProvider {
+    /// A small page that `docs.rs` always serves, regardless of which crates
+    /// it hosts.
+    const PING_URL: &'static str = "https://docs.rs/robots.txt";
+
+    /// Checks whether `docs.rs` can be reached, bypassing the cache.
+    ///
+    /// Returns an error if no response was received at all, e.g., because of
+    /// the network or a proxy.
+    pub async fn ping(&self) -> Result<DocsDotRsPing> {
+        let started_at = Instant::now();
+        let response = self
+            .http_client
+            .get(Self::PING_URL, AsyncBody::default(), true)
+            .await
+            .map_err(|err| self.request_error(Self::PING_URL, err))
+            .with_context(|| format!("failed to reach {}", Self::PING_URL))?;
+
+        Ok(DocsDotRsPing {
+            url: Self::PING_URL,
+            status: response.status().as_u16(),
+            latency: started_at.elapsed(),
+        })
+    }
+
+    /// Fetches the page at the given URL, returning whether it came from
+    /// `docs.rs` or from an expired copy in the cache.
+    async fn fetch_url(&self, crate_name: &str, version: &str, url: &str) -> Result<FetchedPage> {
+        let cache_key = CacheKey {
+            source: RustdocSource::DocsDotRs,
+            crate_name,
+            version,
+            location: url,
+        };
+        if let Some(cache) = self.cache.as_ref() {
+            if let Some(page) = cache.load(&cache_key).await {
+                log::trace!("cache hit for {url}");
+                return Ok(self.cached_page(RustdocSource::DocsDotRs, url, page));
+            }
+            log::trace!("cache miss for {url}");
+        }
+        if let Some(response) = self
+            .not_found_cache
+            .as_ref()
+            .and_then(|not_found_cache| not_found_cache.get(url))
+        {
+            log::debug!("{url} was recently not found, so it isn't requested again");
+            bail!(ClientError {
+                status: StatusCode::NOT_FOUND.as_u16(),
+                response,
+            });
+        }
+
+        let host = docs_host_for_crate(&self.hosts, crate_name);
+        let request_url = host
+            .and_then(|host| host.docs_url(url))
+            .unwrap_or_else(|| url.to_string());
+        log::debug!("fetching {request_url}");
+
+        let response = self.get_with_retries(&request_url).await?;
+        // When docs.rs can't be reached, an expired copy of the page is better
+        // than no docs at all.
+        let is_unavailable = response.as_ref().map_or(true, |response| {
+            response.status.is_server_error() || response.status == StatusCode::TOO_MANY_REQUESTS
+        });
+        if is_unavailable {
+            if let Some(cache) = self.cache.as_ref() {
+                if let Some(page) = cache.load_expired(&cache_key).await {
+                    log::warn!("docs.rs is unavailable, using an expired cached copy of {url}");
+                    return Ok(self.cached_page(RustdocSource::CachedDocsDotRs, url, page));
+                }
+            }
+        }
+        let mut response = response?;
+        // The page is known by its URL on docs.rs, regardless of where it was
+        // fetched from.
+        if let Some(docs_dot_rs_url) = host.and_then(|host| host.docs_dot_rs_url(&response.url)) {
+            response.url = docs_dot_rs_url;
+        }
+
+        if response.status == StatusCode::TOO_MANY_REQUESTS {
+            bail!(RateLimited {
+                retry_after: response.retry_after,
+            });
+        }
+        if response.status.is_server_error() {
+            bail!(
+                "docs.rs responded with {status} for {url}",
+                status = response.status
+            );
+        }
+        if response.status.is_client_error() {
+            let body = String::from_utf8_lossy(response.body.as_slice()).to_string();
+            if let Some(not_found_cache) = self.not_found_cache.as_ref() {
+                if response.status == StatusCode::NOT_FOUND {
+                    not_found_cache.insert(crate_name, url, body.clone());
+                }
+            }
+            bail!(ClientError {
+                status: response.status.as_u16(),
+                response: body,
+            });
+        }
+
+        if response.url != url {
+            log::debug!("{url} resolved to {}", response.url);
+        }
+
+        let body = decode_html(&response.body).into_owned();
+        if let Some(error) = docs_dot_rs_error_page(crate_name, version, &response.url, &body) {
+            return Err(error);
+        }
+        if is_js_shell_page(&body) {
+            bail!(
+                "docs.rs returned a page that requires JavaScript to render ({url}). \
+                Try building the docs with `cargo doc` and indexing them with `/rustdoc --index <crate>`"
+            );
+        }
+
+        if let Some(cache) = self.cache.as_ref() {
+            if let Err(err) = cache
+                .save(&cache_key, &with_page_url(&response.url, &body))
+                .await
+            {
+                log::error!("failed to cache docs for {url}: {err:?}");
+            }
+        }
+
+        if let Some(not_found_cache) = self.not_found_cache.as_ref() {
+            not_found_cache.remove(url);
+        }
+        self.resolved_urls
+            .lock()
+            .insert(url.to_string(), response.url.clone());
+        Ok(FetchedPage {
+            source: RustdocSource::DocsDotRs,
+            url: response.url,
+            body,
+        })
+    }
+
+    /// Returns the given page that was requested at the given URL and loaded
+    /// from the cache.
+    fn cached_page(&self, source: RustdocSource, url: &str, page: String) -> FetchedPage {
+        // Pages cached before their URL was saved along with them are
+        // resolved against the URL they were requested at.
+        let (resolved_url, body) = split_page_url(page);
+        let resolved_url = resolved_url.unwrap_or_else(|| url.to_string());
+        self.resolved_urls
+            .lock()
+            .insert(url.to_string(), resolved_url.clone());
+        FetchedPage {
+            source,
+            url: resolved_url,
+            body,
+        }
+    }
+
+    /// Sends a GET request for the given URL, retrying with exponential
+    /// backoff when the connection fails or `docs.rs` responds with a server
+    /// error.
+    ///
+    /// When `docs.rs` is rate limiting requests, the request is retried once
+    /// after the delay it asks for, unless that's longer than the timeout.
+    ///
+    /// The outer error is for a response that should not be used at all
+    /// (e.g., because it is too large), and the inner one for when `docs.rs`
+    /// couldn't be reached, even after retrying.
+    async fn get_with_retries(&self, url: &str) -> Result<Result<DocsDotRsResponse>> {
+        let mut attempt = 1;
+        let mut was_rate_limited = false;
+        loop {
+            let response = self.get_with_timeout(url).await?;
+            let error = match &response {
+                Ok(rate_limited) if rate_limited.status == StatusCode::TOO_MANY_REQUESTS => {
+                    let delay = rate_limited.retry_after.unwrap_or(self.retry_base_delay);
+                    if was_rate_limited || delay > self.timeout {
+                        return Ok(response);
+                    }
+                    log::warn!("docs.rs is rate limiting requests to {url}, retrying in {delay:?}");
+                    smol::Timer::after(delay).await;
+                    was_rate_limited = true;
+                    continue;
+                }
+                Ok(response) if response.status.is_server_error() => {
+                    format!("status {status}", status = response.status)
+                }
+                Ok(_) => return Ok(response),
+                Err(err) => format!("{err:#}"),
+            };
+            if attempt >= self.max_attempts {
+                return Ok(response);
+            }
+
+            let delay = self.retry_base_delay * 2u32.saturating_pow(attempt - 1);
+            log::warn!(
+                "request to {url} failed ({error}), retrying in {delay:?} \
+                (attempt {attempt} of {max_attempts})",
+                max_attempts = self.max_attempts
+            );
+            smol::Timer::after(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Sends a single GET request for the given URL and reads its body, giving
+    /// up when that takes longer than the timeout.
+    ///
+    /// See [`Self::get_with_retries`] for the meaning of the errors.
+    async fn get_with_timeout(&self, url: &str) -> Result<Result<DocsDotRsResponse>> {
+        let mut timeout = smol::Timer::after(self.timeout).fuse();
+        let response = self.get(url).fuse();
+        futures::pin_mut!(response);
+
+        futures::select_biased! {
+            response = response => response,
+            _ = timeout => Ok(Err(anyhow!(
+                "docs.rs timed out after {timeout:?} fetching {url}",
+                timeout = self.timeout
+            ))),
+        }
+    }
+
+    /// Describes an error sending a request for the given URL.
+    ///
+    /// When the requests go through a proxy, failing to connect to the proxy
+    /// itself is told apart from the server being unreachable through it, so
+    /// that a misconfigured proxy doesn't look like `docs.rs` being down.
+    fn request_error(&self, url: &str, err: http::Error) -> anyhow::Error {
+        let Some(proxy) = self.http_client.proxy() else {
+            return err.into();
+        };
+        // All connections are made to the proxy, which resolves the server's
+        // name itself, so these can only be about the proxy.
+        let message = match err.kind() {
+            ErrorKind::ConnectionFailed | ErrorKind::NameResolution => format!(
+                "couldn't connect to the proxy at {proxy} to fetch {url}. \
+                Check the `proxy` setting, or the `HTTPS_PROXY` environment variable"
+            ),
+            _ => format!("couldn't reach {url} through the proxy at {proxy}"),
+        };
+        anyhow::Error::new(err).context(message)
+    }
+
+    /// Sends a single GET request for the given URL, following any redirects,
+    /// and reads its body.
+    ///
+    /// The redirects are followed here, rather than by the HTTP client, so
+    /// that the URL the page was eventually found at is known.
+    ///
+    /// See [`Self::get_with_retries`] for the meaning of the errors.
+    async fn get(&self, url: &str) -> Result<Result<DocsDotRsResponse>> {
+        let requested_url = url;
+        let mut url = url.to_string();
+        let mut redirect_count = 0;
+        let mut response = loop {
+            // The headers may contain credentials, so they're only sent to the
+            // server they're for, not to wherever it redirects to.
+            let headers = self
+                .hosts
+                .iter()
+                .find(|host| host.serves_url(&url))
+                .map(DocsHost::request_headers)
+                .unwrap_or_default();
+            // Unlike `HttpClient::get`, this doesn't set a redirect policy, but
+            // the client doesn't follow redirects by default.
+            let mut request = Request::builder().method(Method::GET).uri(&url);
+            for (name, value) in &headers {
+                request = request.header(name.as_str(), value.as_str());
+            }
+            let request = request
+                .body(AsyncBody::default())
+                .with_context(|| format!("invalid request for {url}"))?;
+            let response = match self.http_client.send(request).await {
+                Ok(response) => response,
+                Err(err) => return Ok(Err(self.request_error(&url, err))),
+            };
+            let location = response
+                .headers()
+                .get("location")
+                .and_then(|location| location.to_str().ok())
+                .filter(|_| response.status().is_redirection());
+            let Some(location) = location else {
+                break response;
+            };
 
-        let Ok(contents) = self.fs.load(&local_cargo_doc_path).await else {
-            return Ok(None);
+            if redirect_count == Self::MAX_REDIRECTS {
+                bail!(
+                    "docs.rs redirected {requested_url} more than {max_redirects} times",
+                    max_redirects = Self::MAX_REDIRECTS
+                );
+            }
+            url = url::Url::parse(&url)
+                .and_then(|url| url.join(location))
+                .with_context(|| format!("docs.rs redirected {url} to invalid URL {location:?}"))?
+                .to_string();
+            redirect_count += 1;
         };
 
-        Ok(Some(contents))
-    }
-}
+        let mut body = Vec::new();
+        let read = if let Some(max_body_size) = self.max_body_size {
+            let content_length = response
+                .headers()
+                .get("content-length")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok());
+            if content_length.map_or(false, |length| length > max_body_size) {
+                bail!(
+                    "docs.rs response for {url} exceeds the maximum size of {max_body_size} bytes"
+                );
+            }
 
-pub struct DocsDotRsProvider {
-    http_client: Arc<HttpClientWithUrl>,
-}
+            // The `Content-Length` may be missing or wrong, so cap the read too.
+            let read = response
+                .body_mut()
+                .take(max_body_size + 1)
+                .read_to_end(&mut body)
+                .await;
+            if body.len() as u64 > max_body_size {
+                bail!(
+                    "docs.rs response for {url} exceeds the maximum size of {max_body_size} bytes"
+                );
+            }
+            read
+        } else {
+            response.body_mut().read_to_end(&mut body).await
+        };
 
-impl DocsDotRsProvider {
-    pub fn new(http_client: Arc<HttpClientWithUrl>) -> Self {
-        Self { http_client }
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get("retry-after")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        Ok(read
+            .context("error reading docs.rs response body")
+            .map(|_| DocsDotRsResponse {
+                url,
+                status,
+                retry_after,
+                body,
+            }))
     }
 }
 
@@ -84,7 +1520,7 @@ impl RustdocProvider for DocsDotRsProvider {
         crate_name: &str,
         item: Option<&RustdocItem>,
     ) -> Result<Option<String>> {
-        let version = "latest";
+        let version = &self.crawl_version;
         let path = format!(
             "{crate_name}/{version}/{crate_name}{item_path}",
             item_path = item
@@ -92,33 +1528,185 @@ impl RustdocProvider for DocsDotRsProvider {
                 .unwrap_or_default()
         );
 
-        println!("Fetching {}", &format!("https://docs.rs/{path}"));
+        let page = self
+            .fetch_url(crate_name, version, &format!("https://docs.rs/{path}"))
+            .await?;
+        Ok(Some(page.body))
+    }
+
+    fn source(&self) -> RustdocSource {
+        RustdocSource::DocsDotRs
+    }
 
-        let mut response = self
-            .http_client
-            .get(
-                &format!("https://docs.rs/{path}"),
-                AsyncBody::default(),
-                true,
+    /// Returns the version that the crate's page was found at, e.g., the
+    /// latest one, once the crate has been crawled.
+    async fn version(&self, crate_name: &str) -> Option<String> {
+        let version = &self.crawl_version;
+        let crate_url = format!("https://docs.rs/{crate_name}/{version}/{crate_name}");
+        self.resolved_version(&crate_url)
+            .or_else(|| (version != "latest").then(|| version.clone()))
+    }
+}
+
+impl DocsDotRsProvider {
+    /// Fetches the docs for the given query, with those for the standard
+    /// library from the given release channel.
+    async fn fetch_from_channel(
+        &self,
+        query: &DocQuery,
+        std_channel: &str,
+    ) -> Result<Option<(RustdocSource, String)>> {
+        let requested_url = query.docs_url(std_channel);
+        let query = &self.locked_query(query).await;
+        let page = self
+            .fetch_url(
+                &query.crate_name,
+                &query.docs_version(std_channel),
+                &query.docs_url(std_channel),
             )
-            .await?;
+            .await;
+        let page = match (page, query.version.as_ref(), query.target.as_ref()) {
+            (Err(err), _, Some(target))
+                if err.is::<ClientError>() && !query.is_standard_library() =>
+            {
+                return Err(err.context(format!(
+                    "docs.rs has no docs for {crate_name} on the {target} target. Check that \
+                    the target is one of the targets that docs.rs builds the crate for (see \
+                    `[package.metadata.docs.rs]` in its `Cargo.toml`)",
+                    crate_name = query.crate_name
+                )));
+            }
+            (Err(err), Some(version), None)
+                if err.is::<ClientError>() && !query.is_standard_library() =>
+            {
+                return Err(err.context(format!(
+                    "docs.rs has no docs for version {version} of {crate_name}. Check that \
+                    the version has been published and that its docs were built",
+                    crate_name = query.crate_name
+                )));
+            }
+            (Err(err), _, _) if err.is::<ClientError>() && !query.item_path.is_empty() => {
+                // Items that are re-exported from elsewhere, and items that
+                // aren't modules, don't have a page at the queried path.
+                return match self.fetch_from_parent_module(query, std_channel).await? {
+                    Some(docs) => Ok(Some(docs)),
+                    None => Err(err),
+                };
+            }
+            (page, _, _) => page?,
+        };
+        // The page is also known by the URL it would have been requested at
+        // without a locked version, e.g., for linking to it.
+        self.resolved_urls
+            .lock()
+            .insert(requested_url, page.url.clone());
+        let markdown = query.convert_fetched(page.body.as_bytes(), &page.url)?;
 
-        let mut body = Vec::new();
-        response
-            .body_mut()
-            .read_to_end(&mut body)
-            .await
-            .context("error reading docs.rs response body")?;
+        Ok(Some((page.source, markdown)))
+    }
 
-        if response.status().is_client_error() {
-            let text = String::from_utf8_lossy(body.as_slice());
-            bail!(
-                "status error {}, response: {text:?}",
-                response.status().as_u16()
-            );
+    /// Fetches the docs for the item of the given query by following its
+    /// re-export or its link on the page of its parent module, for items that
+    /// don't have a page at the path they are queried at.
+    async fn fetch_from_parent_module(
+        &self,
+        query: &DocQuery,
+        std_channel: &str,
+    ) -> Result<Option<(RustdocSource, String)>> {
+        let Some(parent_query) = query.parent_query() else {
+            return Ok(None);
+        };
+        let version = query.docs_version(std_channel);
+        let Ok(parent_page) = self
+            .fetch_url(
+                &query.crate_name,
+                &version,
+                &parent_query.docs_url(std_channel),
+            )
+            .await
+        else {
+            return Ok(None);
+        };
+        let base_url = module_page_base_url(&parent_page.url)?;
+        if let Some(reexport) = query.find_reexport(parent_page.body.as_bytes())? {
+            let url = base_url.join(&reexport.href)?;
+            let page = self
+                .fetch_url(&query.crate_name, &version, url.as_str())
+                .await?;
+            let markdown = query.convert_reexport(page.body.as_bytes(), &reexport)?;
+            return Ok(Some((page.source, markdown)));
         }
 
-        Ok(Some(String::from_utf8(body)?))
+        // Items other than modules, e.g., macros, have a page named after
+        // their kind in the directory of their parent module.
+        let Some(item) = query.find_item_page(parent_page.body.as_bytes())? else {
+            return Ok(None);
+        };
+        let url = base_url.join(&item.url_path())?;
+        let page = self
+            .fetch_url(&query.crate_name, &version, url.as_str())
+            .await?;
+        let markdown = query.convert_item_page(page.body.as_bytes())?;
+
+        Ok(Some((page.source, markdown)))
+    }
+}
+
+/// Returns the URL that the relative links on the module page at the given
+/// URL are relative to, which is the module's directory.
+fn module_page_base_url(url: &str) -> Result<url::Url> {
+    let mut url = url::Url::parse(url)?;
+    let is_directory = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .map_or(true, |segment| {
+            segment.is_empty() || segment.ends_with(".html")
+        });
+    if !is_directory {
+        url.set_path(&format!("{}/", url.path()));
+    }
+    Ok(url)
+}
+
+#[async_trait]
+impl DocProvider for DocsDotRsProvider {
+    fn name(&self) -> String {
+        "docs.rs".to_string()
+    }
+
+    async fn fetch(&self, query: &DocQuery) -> Result<Option<(RustdocSource, String)>> {
+        let std_channel = self.std_channel.as_str();
+        match self.fetch_from_channel(query, std_channel).await {
+            // An unreachable channel, e.g., one whose docs are briefly
+            // unavailable, is better served by the stable docs than by none.
+            Err(err)
+                if query.is_standard_library()
+                    && query.version.is_none()
+                    && std_channel != DEFAULT_STD_CHANNEL
+                    && !is_docs_not_found(&err) =>
+            {
+                log::warn!(
+                    "failed to fetch the {std_channel} docs for {crate_name}, falling back to \
+                    {DEFAULT_STD_CHANNEL}: {err:#}",
+                    crate_name = query.crate_name
+                );
+                let Some((source, markdown)) = self
+                    .fetch_from_channel(query, DEFAULT_STD_CHANNEL)
+                    .await
+                    .map_err(|_| err)?
+                else {
+                    return Ok(None);
+                };
+                Ok(Some((
+                    source,
+                    format!(
+                        "> **Note:** The docs for the `{std_channel}` channel couldn't be \
+                        fetched, so these are the docs for `{DEFAULT_STD_CHANNEL}`.\n\n{markdown}"
+                    ),
+                )))
+            }
+            result => result,
+        }
     }
 }
 
@@ -132,6 +1720,9 @@ struct RustdocItemWithHistory {
 pub struct CrateDocs {
     pub crate_root_markdown: String,
     pub items: IndexMap<RustdocItem, String>,
+    /// The items that the crate's modules re-export, keyed by the path they
+    /// are re-exported at, along with the items at their canonical paths.
+    pub reexports: IndexMap<RustdocItem, RustdocItem>,
 }
 
 pub struct RustdocCrawler {
@@ -144,12 +1735,33 @@ impl RustdocCrawler {
     }
 
     pub async fn crawl(&self, crate_name: String) -> Result<Option<CrateDocs>> {
+        if let Some(crate_docs) = self.provider.fetch_crate_docs(&crate_name).await? {
+            return Ok(Some(crate_docs));
+        }
+
         let Some(crate_root_content) = self.provider.fetch_page(&crate_name, None).await? else {
             return Ok(None);
         };
 
-        let (crate_root_markdown, items) =
-            convert_rustdoc_to_markdown(crate_root_content.as_bytes())?;
+        let (crate_root_markdown, items) = convert_rustdoc_to_markdown_with_options(
+            crate_root_content.as_bytes(),
+            &conversion_options(&crate_name, None),
+        )?;
+
+        let mut items = items
+            .into_iter()
+            .map(|page_item| page_item.item)
+            .collect::<Vec<_>>();
+
+        let mut reexports = IndexMap::new();
+        for (reexport, canonical_item) in
+            crate_reexports(&crate_name, &[], crate_root_content.as_bytes())?
+        {
+            if !items.contains(&canonical_item) {
+                items.push(canonical_item.clone());
+            }
+            reexports.insert(reexport, canonical_item);
+        }
 
         let mut docs_by_item = IndexMap::new();
         let mut seen_items = HashSet::from_iter(items.clone());
@@ -163,7 +1775,7 @@ impl RustdocCrawler {
         while let Some(item_with_history) = items_to_visit.pop_front() {
             let item = &item_with_history.item;
 
-            println!("Visiting {:?} {:?} {}", &item.kind, &item.path, &item.name);
+            log::trace!("visiting {:?} {:?} {}", &item.kind, &item.path, &item.name);
 
             let Some(result) = self
                 .provider
@@ -187,12 +1799,38 @@ impl RustdocCrawler {
                 continue;
             };
 
-            let (markdown, referenced_items) = convert_rustdoc_to_markdown(result.as_bytes())?;
+            let (markdown, referenced_items) = convert_rustdoc_to_markdown_with_options(
+                result.as_bytes(),
+                &conversion_options(&crate_name, Some(item)),
+            )?;
 
             docs_by_item.insert(item.clone(), markdown);
 
+            if item.kind == RustdocItemKind::Mod {
+                let mut module_path = item.path.clone();
+                module_path.push(item.name.clone());
+                for (reexport, canonical_item) in
+                    crate_reexports(&crate_name, &module_path, result.as_bytes())?
+                {
+                    // The canonical item may be in a private module, which
+                    // isn't linked to from anywhere else.
+                    if seen_items.insert(canonical_item.clone()) {
+                        items_to_visit.push_back(RustdocItemWithHistory {
+                            #[cfg(debug_assertions)]
+                            history: {
+                                let mut history = item_with_history.history.clone();
+                                history.push(canonical_item.url_path());
+                                history
+                            },
+                            item: canonical_item.clone(),
+                        });
+                    }
+                    reexports.insert(reexport, canonical_item);
+                }
+            }
+
             let parent_item = item;
-            for mut item in referenced_items {
+            for mut item in referenced_items.into_iter().map(|page_item| page_item.item) {
                 if seen_items.contains(&item) {
                     continue;
                 }
@@ -222,6 +1860,1095 @@ impl RustdocCrawler {
         Ok(Some(CrateDocs {
             crate_root_markdown,
             items: docs_by_item,
+            reexports,
         }))
     }
 }
+
+/// Returns the options for converting the page for the given item, so that
+/// its links are resolved relative to the module it is in.
+fn conversion_options(crate_name: &str, item: Option<&RustdocItem>) -> RustdocConversionOptions {
+    let module_path = item
+        .map(|item| {
+            let mut module_path = item
+                .path
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            if item.kind == RustdocItemKind::Mod {
+                module_path.push(item.name.to_string());
+            }
+            module_path
+        })
+        .unwrap_or_default();
+
+    RustdocConversionOptions {
+        page_location: Some(RustdocPageLocation {
+            crate_name: crate_name.to_string(),
+            module_path,
+        }),
+        ..Default::default()
+    }
+}
+
+/// Returns the items that the module at the given path re-exports from
+/// within the crate, keyed by the path they are re-exported at, along with
+/// the items at their canonical paths.
+fn crate_reexports(
+    crate_name: &str,
+    module_path: &[Arc<str>],
+    module_page: &[u8],
+) -> Result<Vec<(RustdocItem, RustdocItem)>> {
+    Ok(convert_rustdoc_to_reexports(module_page)?
+        .into_iter()
+        .filter_map(|reexport| {
+            let canonical_item = reexport.canonical_item(crate_name)?;
+            let reexport = RustdocItem {
+                kind: reexport.kind,
+                path: module_path.to_vec(),
+                name: reexport.name.into(),
+            };
+            (reexport != canonical_item).then_some((reexport, canonical_item))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use std::sync::atomic::AtomicBool;
+    use std::time::SystemTime;
+
+    use fs::FakeFs;
+    use futures::executor::block_on;
+    use gpui::TestAppContext;
+    use http::{FakeHttpClient, Response};
+    use indoc::indoc;
+
+    use super::*;
+    use crate::cache::with_fetched_at;
+    use crate::{is_docs_not_found, CachePolicy};
+
+    #[test]
+    fn test_tarball_crate_dir() {
+        let extract_dir = Path::new("/tmp/rustdoc-tarballs");
+        assert_eq!(
+            tarball_crate_dir(
+                Path::new("/downloads/serde_json-1.0.117.crate"),
+                extract_dir
+            )
+            .unwrap(),
+            extract_dir.join("serde_json-1.0.117")
+        );
+        assert!(tarball_crate_dir(Path::new("/downloads/serde_json.tar.gz"), extract_dir).is_err());
+        assert!(tarball_crate_dir(Path::new("/downloads/.crate"), extract_dir).is_err());
+    }
+
+    #[test]
+    fn test_oversized_response() {
+        let body = "a".repeat(2048);
+
+        // With a `Content-Length`, the response is rejected before it is read.
+        let http_client = FakeHttpClient::create({
+            let body = body.clone();
+            move |_| {
+                let body = body.clone();
+                async move {
+                    Ok(Response::builder()
+                        .status(200)
+                        .header("content-length", body.len())
+                        .body(body.into())
+                        .unwrap())
+                }
+            }
+        });
+        let provider = DocsDotRsProvider::new(http_client).with_max_body_size(1024);
+        let error =
+            block_on(provider.fetch_url("foo", "latest", "https://docs.rs/foo/latest/foo/"))
+                .unwrap_err()
+                .to_string();
+        assert!(error.contains("exceeds the maximum size of 1024 bytes"));
+
+        // Without one, the read is capped.
+        let http_client = FakeHttpClient::create(move |_| {
+            let body = body.clone();
+            async move { Ok(Response::builder().status(200).body(body.into()).unwrap()) }
+        });
+        let provider = DocsDotRsProvider::new(http_client).with_max_body_size(1024);
+        let error =
+            block_on(provider.fetch_url("foo", "latest", "https://docs.rs/foo/latest/foo/"))
+                .unwrap_err()
+                .to_string();
+        assert!(error.contains("exceeds the maximum size of 1024 bytes"));
+    }
+
+    #[test]
+    fn test_retries() {
+        fn flaky_provider(responses: Vec<Option<u16>>) -> (DocsDotRsProvider, Arc<AtomicUsize>) {
+            let attempts = Arc::new(AtomicUsize::new(0));
+            let http_client = FakeHttpClient::create({
+                let attempts = attempts.clone();
+                move |_| {
+                    let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                    let response = responses[attempt.min(responses.len() - 1)];
+                    async move {
+                        match response {
+                            Some(status) => Ok(Response::builder()
+                                .status(status)
+                                .body(format!("attempt {attempt}").into())
+                                .unwrap()),
+                            None => {
+                                Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset)
+                                    .into())
+                            }
+                        }
+                    }
+                }
+            });
+            let provider = DocsDotRsProvider::new(http_client).with_retries(3, Duration::ZERO);
+            (provider, attempts)
+        }
+
+        // Connection errors and server errors are retried, and the body of
+        // the successful attempt is used.
+        let (provider, attempts) = flaky_provider(vec![None, Some(503), Some(200)]);
+        let response = block_on(provider.get_with_retries("https://docs.rs/foo/latest/foo/"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"attempt 2");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // Client errors are not.
+        let (provider, attempts) = flaky_provider(vec![Some(404), Some(200)]);
+        let response = block_on(provider.get_with_retries("https://docs.rs/foo/latest/foo/"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.status, 404);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        // Once the attempts run out, the last failure is returned.
+        let (provider, attempts) = flaky_provider(vec![Some(502), None]);
+        assert!(
+            block_on(provider.get_with_retries("https://docs.rs/foo/latest/foo/"))
+                .unwrap()
+                .is_err()
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_proxy_errors() {
+        fn proxied_provider(
+            proxy: Option<&str>,
+            error: std::io::ErrorKind,
+        ) -> (DocsDotRsProvider, Arc<AtomicUsize>) {
+            let attempts = Arc::new(AtomicUsize::new(0));
+            let http_client = FakeHttpClient::create_with_proxy(proxy, {
+                let attempts = attempts.clone();
+                move |_| {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    async move { Err(std::io::Error::from(error).into()) }
+                }
+            });
+            let provider = DocsDotRsProvider::new(http_client).with_retries(2, Duration::ZERO);
+            (provider, attempts)
+        }
+
+        let url = "https://docs.rs/foo/latest/foo/";
+
+        // The requests are sent through the configured proxy, and failing to
+        // connect to it is reported as such, after retrying.
+        let (provider, attempts) = proxied_provider(
+            Some("http://127.0.0.1:10809/"),
+            std::io::ErrorKind::ConnectionRefused,
+        );
+        assert_eq!(
+            provider.http_client.proxy(),
+            Some("http://127.0.0.1:10809/")
+        );
+        let error = block_on(provider.get_with_retries(url))
+            .unwrap()
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("couldn't connect to the proxy at http://127.0.0.1:10809/"));
+        assert!(error.contains("`proxy` setting"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        // Other failures mean the proxy was reached, but docs.rs wasn't.
+        let (provider, _) = proxied_provider(
+            Some("http://127.0.0.1:10809/"),
+            std::io::ErrorKind::ConnectionReset,
+        );
+        let error = block_on(provider.get_with_retries(url))
+            .unwrap()
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            error,
+            format!("couldn't reach {url} through the proxy at http://127.0.0.1:10809/")
+        );
+
+        // Without a proxy, the error is reported as is.
+        let (provider, _) = proxied_provider(None, std::io::ErrorKind::ConnectionRefused);
+        let error = block_on(provider.get_with_retries(url))
+            .unwrap()
+            .unwrap_err()
+            .to_string();
+        assert!(!error.contains("proxy"));
+    }
+
+    #[test]
+    fn test_rate_limiting() {
+        fn rate_limited_provider(
+            retry_after: &'static str,
+            timeout: Duration,
+        ) -> (DocsDotRsProvider, Arc<AtomicUsize>) {
+            let attempts = Arc::new(AtomicUsize::new(0));
+            let http_client = FakeHttpClient::create({
+                let attempts = attempts.clone();
+                move |_| {
+                    let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        let response = if attempt == 0 {
+                            Response::builder()
+                                .status(429)
+                                .header("retry-after", retry_after)
+                                .body("slow down".into())
+                        } else {
+                            Response::builder().status(200).body("docs".into())
+                        };
+                        Ok(response.unwrap())
+                    }
+                }
+            });
+            let provider = DocsDotRsProvider::new(http_client).with_timeout(timeout);
+            (provider, attempts)
+        }
+
+        // The request is retried once docs.rs says it can be.
+        let (provider, attempts) = rate_limited_provider("0", Duration::from_secs(1));
+        let response = block_on(provider.get_with_retries("https://docs.rs/foo/latest/foo/"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        // Waiting for longer than the timeout isn't worth it.
+        let (provider, attempts) = rate_limited_provider("120", Duration::from_secs(1));
+        let error =
+            block_on(provider.fetch_url("foo", "latest", "https://docs.rs/foo/latest/foo/"))
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "docs.rs is rate limiting requests; try again in 120 seconds"
+        );
+        assert!(!error.is::<ClientError>());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        assert_eq!(parse_retry_after(" 30 "), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn test_timeout() {
+        let http_client = FakeHttpClient::create(|_| futures::future::pending());
+        let provider = DocsDotRsProvider::new(http_client)
+            .with_retries(1, Duration::ZERO)
+            .with_timeout(Duration::from_millis(10));
+
+        let error = block_on(provider.get_with_retries("https://docs.rs/foo/latest/foo/"))
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "docs.rs timed out after 10ms fetching https://docs.rs/foo/latest/foo/"
+        );
+    }
+
+    #[test]
+    fn test_redirects() {
+        let http_client = FakeHttpClient::create(|request| async move {
+            let response = match request.uri().to_string().as_str() {
+                "https://docs.rs/foo-bar/latest/foo_bar/" => Response::builder()
+                    .status(302)
+                    .header("location", "/foo-bar/1.2.3/foo_bar/"),
+                "https://docs.rs/foo-bar/1.2.3/foo_bar/" => Response::builder()
+                    .status(302)
+                    .header("location", "x86_64-unknown-linux-gnu/foo_bar/"),
+                "https://docs.rs/foo-bar/1.2.3/foo_bar/x86_64-unknown-linux-gnu/foo_bar/" => {
+                    Response::builder().status(200)
+                }
+                "https://docs.rs/loop/latest/loop/" => Response::builder()
+                    .status(301)
+                    .header("location", "https://docs.rs/loop/latest/loop/"),
+                _ => Response::builder().status(404),
+            };
+            Ok(response.body("docs".into()).unwrap())
+        });
+        let provider = DocsDotRsProvider::new(http_client);
+
+        let requested_url = "https://docs.rs/foo-bar/latest/foo_bar/";
+        let page = block_on(provider.fetch_url("foo-bar", "latest", requested_url)).unwrap();
+        assert_eq!(
+            page.url,
+            "https://docs.rs/foo-bar/1.2.3/foo_bar/x86_64-unknown-linux-gnu/foo_bar/"
+        );
+        assert_eq!(page.body, "docs");
+        assert_eq!(provider.resolved_url(requested_url), Some(page.url));
+        assert_eq!(
+            provider.resolved_version(requested_url),
+            Some("1.2.3".to_string())
+        );
+
+        let error =
+            block_on(provider.fetch_url("loop", "latest", "https://docs.rs/loop/latest/loop/"))
+                .unwrap_err()
+                .to_string();
+        assert_eq!(
+            error,
+            "docs.rs redirected https://docs.rs/loop/latest/loop/ more than 10 times"
+        );
+    }
+
+    #[test]
+    fn test_docs_hosts() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let url = request.uri().to_string();
+                let authorization = request
+                    .headers()
+                    .get("authorization")
+                    .map(|value| value.to_str().unwrap().to_string());
+                requests.lock().push((url.clone(), authorization));
+                async move {
+                    let response = match url.as_str() {
+                        "https://docs.example.com/acme-db/latest/acme_db/" => Response::builder()
+                            .status(302)
+                            .header("location", "/acme-db/1.2.3/acme_db/"),
+                        "https://docs.example.com/acme-db/1.2.3/acme_db/" => {
+                            Response::builder().status(200)
+                        }
+                        "https://docs.example.com/acme-cdn/latest/acme_cdn/" => Response::builder()
+                            .status(302)
+                            .header("location", "https://cdn.example.com/acme_cdn/"),
+                        "https://cdn.example.com/acme_cdn/" => Response::builder().status(200),
+                        "https://docs.rs/serde/latest/serde/" => Response::builder().status(200),
+                        _ => Response::builder().status(404),
+                    };
+                    Ok(response.body("docs".into()).unwrap())
+                }
+            }
+        });
+        std::env::set_var("RUSTDOC_TEST_DOCS_HOSTS_TOKEN", "Bearer secret");
+        let provider = DocsDotRsProvider::new(http_client).with_hosts(vec![DocsHost {
+            base_url: "https://docs.example.com/".to_string(),
+            crate_prefixes: vec!["acme_".to_string()],
+            env_headers: [(
+                "Authorization".to_string(),
+                "RUSTDOC_TEST_DOCS_HOSTS_TOKEN".to_string(),
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        }]);
+
+        // The page is known by its URL on docs.rs, but served from the host.
+        let requested_url = "https://docs.rs/acme-db/latest/acme_db/";
+        let page = block_on(provider.fetch_url("acme-db", "latest", requested_url)).unwrap();
+        assert_eq!(page.url, "https://docs.rs/acme-db/1.2.3/acme_db/");
+        assert_eq!(
+            provider.resolved_version(requested_url),
+            Some("1.2.3".to_string())
+        );
+        assert_eq!(
+            provider.host_url("acme-db", &page.url),
+            "https://docs.example.com/acme-db/1.2.3/acme_db/"
+        );
+
+        block_on(provider.fetch_url(
+            "acme-cdn",
+            "latest",
+            "https://docs.rs/acme-cdn/latest/acme_cdn/",
+        ))
+        .unwrap();
+        block_on(provider.fetch_url("serde", "latest", "https://docs.rs/serde/latest/serde/"))
+            .unwrap();
+        assert_eq!(
+            provider.host_url("serde", "https://docs.rs/serde/latest/serde/"),
+            "https://docs.rs/serde/latest/serde/"
+        );
+
+        let bearer = Some("Bearer secret".to_string());
+        assert_eq!(
+            *requests.lock(),
+            vec![
+                (
+                    "https://docs.example.com/acme-db/latest/acme_db/".to_string(),
+                    bearer.clone()
+                ),
+                (
+                    "https://docs.example.com/acme-db/1.2.3/acme_db/".to_string(),
+                    bearer.clone()
+                ),
+                (
+                    "https://docs.example.com/acme-cdn/latest/acme_cdn/".to_string(),
+                    bearer
+                ),
+                // The credentials aren't sent to other servers.
+                ("https://cdn.example.com/acme_cdn/".to_string(), None),
+                ("https://docs.rs/serde/latest/serde/".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fetch_features() {
+        let http_client = FakeHttpClient::create(|request| async move {
+            assert_eq!(
+                request.uri().to_string(),
+                "https://docs.rs/crate/tokio/1.38.0/features"
+            );
+            let body = indoc! {r##"
+                <ul class="pure-menu-list">
+                    <li class="pure-menu-heading">Feature flags</li>
+                    <li class="pure-menu-item"><a href="#default">default</a></li>
+                    <li class="pure-menu-item"><a href="#full">full</a></li>
+                </ul>
+                <div class="package-details" id="main">
+                    <h1>tokio</h1>
+                    <h3 id="default"><a href="#default">default</a></h3>
+                    <p>This feature flag does not enable additional features.</p>
+                    <h3 id="full"><a href="#full">full</a></h3>
+                    <ul class="pure-menu-list">
+                        <li class="pure-menu-item"><span>fs</span></li>
+                        <li class="pure-menu-item"><span>io-util</span></li>
+                    </ul>
+                </div>
+            "##};
+            Ok(Response::builder().status(200).body(body.into()).unwrap())
+        });
+        let provider = DocsDotRsProvider::new(http_client);
+
+        assert_eq!(
+            block_on(provider.fetch_features("tokio", "1.38.0")).unwrap(),
+            vec![
+                CrateFeature {
+                    name: "default".to_string(),
+                    enables: Vec::new(),
+                },
+                CrateFeature {
+                    name: "full".to_string(),
+                    enables: vec!["fs".to_string(), "io-util".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_std_channel_fallback() {
+        let http_client = FakeHttpClient::create(|request| async move {
+            let status = match request.uri().to_string().as_str() {
+                "https://doc.rust-lang.org/stable/std/vec/index.html" => 200,
+                "https://doc.rust-lang.org/1.75.0/std/vec/index.html" => 404,
+                _ => 503,
+            };
+            Ok(Response::builder()
+                .status(status)
+                .body("<p>A contiguous growable array type.</p>".into())
+                .unwrap())
+        });
+        let provider = |std_channel: &str| {
+            DocsDotRsProvider::new(http_client.clone())
+                .with_std_channel(std_channel.to_string())
+                .with_retries(1, Duration::ZERO)
+        };
+        let query = DocQuery::new("std", vec!["vec".into()]);
+
+        // The stable docs are fetched when those for the channel are
+        // unavailable, with a note saying so.
+        let (_source, markdown) = block_on(provider("nightly").fetch(&query))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            markdown,
+            "> **Note:** The docs for the `nightly` channel couldn't be fetched, so these are \
+            the docs for `stable`.\n\n\
+            A contiguous growable array type."
+        );
+
+        // Docs that don't exist for a version aren't replaced with others.
+        let err = block_on(provider("1.75.0").fetch(&query)).unwrap_err();
+        assert!(is_docs_not_found(&err));
+
+        // Nor are those for crates other than the standard library.
+        assert!(block_on(provider("nightly").fetch(&DocQuery::new("tokio", Vec::new()))).is_err());
+    }
+
+    #[test]
+    fn test_not_found_pages_are_not_requested_again() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                requests.fetch_add(1, Ordering::SeqCst);
+                let status = match request.uri().to_string().as_str() {
+                    "https://docs.rs/tokio/latest/tokio/sync/index.html" => 200,
+                    _ => 404,
+                };
+                async move {
+                    Ok(Response::builder()
+                        .status(status)
+                        .body("<p>Mutex</p>".into())
+                        .unwrap())
+                }
+            }
+        });
+        let not_found_cache = Arc::new(NotFoundCache::new(Duration::from_secs(60)));
+        let provider = DocsDotRsProvider::new(http_client)
+            .with_not_found_cache(not_found_cache.clone())
+            .with_retries(1, Duration::ZERO);
+        let missing_query = DocQuery::new("tokio", vec!["sync".into(), "Missing".into()]);
+
+        // Both the item's page and its parent module's are requested once.
+        let err = block_on(provider.fetch(&missing_query)).unwrap_err();
+        assert!(is_docs_not_found(&err));
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        let err = block_on(provider.fetch(&missing_query)).unwrap_err();
+        assert!(is_docs_not_found(&err));
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // The crate's pages are requested again once they're forgotten, e.g.,
+        // because the crate was indexed.
+        let url = "https://docs.rs/tokio/latest/tokio/sync/index.html";
+        not_found_cache.insert("tokio", url, String::new());
+        assert!(block_on(provider.fetch_url("tokio", "latest", url)).is_err());
+        not_found_cache.remove_crate("tokio");
+        assert!(block_on(provider.fetch_url("tokio", "latest", url)).is_ok());
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_error_pages() {
+        let http_client = FakeHttpClient::create(|request| async move {
+            let (response, body) = match request.uri().to_string().as_str() {
+                "https://docs.rs/broken/1.0.0/broken/" => (
+                    Response::builder()
+                        .status(302)
+                        .header("location", "/crate/broken/1.0.0"),
+                    "",
+                ),
+                "https://docs.rs/crate/broken/1.0.0" => (
+                    Response::builder().status(200),
+                    "<h1>broken 1.0.0</h1><p>docs.rs failed to build broken-1.0.0</p>",
+                ),
+                "https://docs.rs/missing/latest/missing/" => (
+                    Response::builder()
+                        .status(302)
+                        .header("location", "/releases/search?query=missing"),
+                    "",
+                ),
+                _ => (
+                    Response::builder().status(200),
+                    "<p>The requested crate does not exist</p>",
+                ),
+            };
+            Ok(response.body(body.into()).unwrap())
+        });
+        let provider = DocsDotRsProvider::new(http_client);
+        let fetch = |crate_name: &str, version: &str| {
+            let url = format!("https://docs.rs/{crate_name}/{version}/{crate_name}/");
+            block_on(provider.fetch_url(crate_name, version, &url))
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            fetch("broken", "1.0.0"),
+            "docs.rs has no successful build for broken@1.0.0; try \
+            `/rustdoc --index broken --build` for local docs"
+        );
+        assert_eq!(
+            fetch("missing", "latest"),
+            "docs.rs has no crate named missing"
+        );
+        let err = block_on(provider.fetch_url(
+            "missing",
+            "latest",
+            "https://docs.rs/missing/latest/missing/",
+        ))
+        .unwrap_err();
+        assert!(is_docs_not_found(&err));
+    }
+
+    /// The page that `docs.rs` served for the docs of a version of a crate that
+    /// it failed to build, trimmed down to its content.
+    const BUILD_FAILURE_PAGE: &str = indoc! {r##"
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+            <meta charset="UTF-8">
+            <title>wasmer-sys 0.3.1 - Docs.rs</title>
+            <link rel="stylesheet" href="/-/static/vendored.css" type="text/css" media="all" />
+        </head>
+        <body class="crate-details-body">
+            <div class="nav-container">
+                <a href="/" class="pure-menu-heading pure-menu-link docsrs-logo" aria-label="Docs.rs">Docs.rs</a>
+            </div>
+            <div class="docsrs-package-container">
+                <div class="container">
+                    <h1 id="crate-title">wasmer-sys 0.3.1</h1>
+                    <div class="description">Low-level bindings to the Wasmer C API</div>
+                </div>
+            </div>
+            <div class="container package-page-container">
+                <div class="pure-g">
+                    <div class="pure-u-1 pure-u-sm-7-24 pure-u-md-5-24">
+                        <div class="pure-menu package-menu">
+                            <ul class="pure-menu-list">
+                                <li class="pure-menu-heading">Links</li>
+                                <li class="pure-menu-item"><a href="https://github.com/wasmerio/wasmer" class="pure-menu-link">Repository</a></li>
+                                <li class="pure-menu-item"><a href="https://crates.io/crates/wasmer-sys" class="pure-menu-link">crates.io</a></li>
+                            </ul>
+                        </div>
+                    </div>
+                    <div class="pure-u-1 pure-u-sm-17-24 pure-u-md-19-24 package-details" id="main">
+                        <div class="warning">
+                            docs.rs failed to build wasmer-sys-0.3.1<br>
+                            Please check the <a href="/crate/wasmer-sys/0.3.1/builds">build logs</a> for more information.<br>
+                            See <a href="/about/builds">Builds</a> for ideas on how to fix a failed build, or
+                            <a href="/about/metadata">Metadata</a> for how to configure docs.rs builds.<br>
+                            If you believe this is docs.rs' fault, <a href="https://github.com/rust-lang/docs.rs/issues/new/choose">open an issue</a>.
+                        </div>
+                        <div class="warning">
+                            Visit the last successful build:
+                            <a href="/crate/wasmer-sys/0.3.0">wasmer-sys-0.3.0</a>
+                        </div>
+                    </div>
+                </div>
+            </div>
+        </body>
+        </html>
+    "##};
+
+    #[test]
+    fn test_build_failure_page() {
+        let http_client = FakeHttpClient::create(|request| async move {
+            let body = match request.uri().to_string().as_str() {
+                "https://docs.rs/wasmer-sys/0.3.1/wasmer_sys/" => BUILD_FAILURE_PAGE,
+                _ => {
+                    "<main><div id=\"main-content\">docs.rs failed to build this once</div></main>"
+                }
+            };
+            Ok(Response::builder().status(200).body(body.into()).unwrap())
+        });
+        let provider = DocsDotRsProvider::new(http_client);
+
+        let err = block_on(provider.fetch_url(
+            "wasmer-sys",
+            "0.3.1",
+            "https://docs.rs/wasmer-sys/0.3.1/wasmer_sys/",
+        ))
+        .unwrap_err();
+        assert!(err.is::<BuildFailed>());
+        assert_eq!(
+            err.to_string(),
+            "docs.rs has no successful build for wasmer-sys@0.3.1; try \
+            `/rustdoc --index wasmer-sys --build` for local docs"
+        );
+
+        // Docs that mention a failed build are still docs.
+        let page = block_on(provider.fetch_url(
+            "wasmer-sys",
+            "0.3.0",
+            "https://docs.rs/wasmer-sys/0.3.0/wasmer_sys/",
+        ))
+        .unwrap();
+        assert!(page.body.contains("docs.rs failed to build this once"));
+    }
+
+    #[test]
+    fn test_page_url() {
+        let page = with_page_url("https://docs.rs/foo/1.2.3/foo/", "<html></html>");
+        assert_eq!(
+            split_page_url(page),
+            (
+                Some("https://docs.rs/foo/1.2.3/foo/".to_string()),
+                "<html></html>".to_string()
+            )
+        );
+        assert_eq!(
+            split_page_url("<html></html>".to_string()),
+            (None, "<html></html>".to_string())
+        );
+    }
+
+    #[gpui::test]
+    async fn test_expired_cache_entries(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let is_online = Arc::new(AtomicBool::new(true));
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let http_client = FakeHttpClient::create({
+            let is_online = is_online.clone();
+            let request_count = request_count.clone();
+            move |_| {
+                let is_online = is_online.load(Ordering::SeqCst);
+                let request_count = request_count.fetch_add(1, Ordering::SeqCst) + 1;
+                async move {
+                    if !is_online {
+                        return Err(
+                            std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into()
+                        );
+                    }
+                    Ok(Response::builder()
+                        .status(200)
+                        .body(format!("fetched {request_count}").into())
+                        .unwrap())
+                }
+            }
+        });
+        let ttl = Duration::from_secs(24 * 60 * 60);
+        let cache = DocsCache::new(fs.clone(), PathBuf::from("/cache")).with_policy(CachePolicy {
+            local_ttl: None,
+            latest_ttl: Some(ttl),
+        });
+        let provider = DocsDotRsProvider::new(http_client)
+            .with_cache(cache.clone())
+            .with_retries(1, Duration::ZERO);
+        let url = "https://docs.rs/tokio/latest/tokio/";
+
+        // Fresh entries are served from the cache.
+        let page = provider.fetch_url("tokio", "latest", url).await.unwrap();
+        assert_eq!(page.body, "fetched 1");
+        let page = provider.fetch_url("tokio", "latest", url).await.unwrap();
+        assert_eq!(page.body, "fetched 1");
+        assert_eq!(request_count.load(Ordering::SeqCst), 1);
+
+        // Entries older than the TTL are fetched again.
+        let entries = cache.entries(Some("tokio")).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        let expire = || {
+            let entry = with_fetched_at(
+                SystemTime::now() - ttl - Duration::from_secs(60),
+                &with_page_url(url, "fetched yesterday"),
+            );
+            fs.insert_file(&entries[0], entry.into_bytes())
+        };
+        expire().await;
+        let page = provider.fetch_url("tokio", "latest", url).await.unwrap();
+        assert_eq!(page.source, RustdocSource::DocsDotRs);
+        assert_eq!(page.body, "fetched 2");
+        assert_eq!(request_count.load(Ordering::SeqCst), 2);
+
+        // Unless docs.rs can't be reached, in which case the expired entry is
+        // better than nothing.
+        expire().await;
+        is_online.store(false, Ordering::SeqCst);
+        let page = provider.fetch_url("tokio", "latest", url).await.unwrap();
+        assert_eq!(page.source, RustdocSource::CachedDocsDotRs);
+        assert_eq!(page.body, "fetched yesterday");
+    }
+
+    #[test]
+    fn test_reexports() {
+        let http_client = FakeHttpClient::create(|request| async move {
+            let response = match request.uri().to_string().as_str() {
+                "https://docs.rs/tokio/latest/tokio/fs" => Response::builder().status(200).body(
+                    r#"<section id="main-content"><h2 id="reexports">Re-exports</h2><ul class="item-table"><li><div class="item-name" id="reexport.File"><code>pub use self::file::<a class="struct" href="file/struct.File.html" title="struct tokio::fs::file::File">File</a>;</code></div></li></ul></section>"#.into(),
+                ),
+                "https://docs.rs/tokio/latest/tokio/fs/file/struct.File.html" => {
+                    Response::builder().status(200).body(
+                        r#"<section id="main-content"><details class="toggle top-doc" open><div class="docblock"><p>A reference to an open file on the filesystem.</p></div></details></section>"#.into(),
+                    )
+                }
+                _ => Response::builder().status(404).body("not found".into()),
+            };
+            Ok(response.unwrap())
+        });
+        let provider = DocsDotRsProvider::new(http_client);
+        let query = |item_path: &[&str]| {
+            DocQuery::new("tokio", item_path.iter().map(ToString::to_string).collect())
+        };
+
+        let (_source, markdown) = block_on(provider.fetch(&query(&["fs", "File"])))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            markdown,
+            "> **Note:** `tokio::fs::File` is a re-export of `tokio::fs::file::File`, so these \
+            are the docs for `tokio::fs::file::File`.\n\nA reference to an open file on the \
+            filesystem."
+        );
+
+        // Items that aren't re-exported are still reported as missing.
+        let error = format!(
+            "{:#}",
+            block_on(provider.fetch(&query(&["fs", "Missing"]))).unwrap_err()
+        );
+        assert_eq!(error, "status error 404, response: \"not found\"");
+    }
+
+    #[test]
+    fn test_item_pages() {
+        let http_client = FakeHttpClient::create(|request| async move {
+            let response = match request.uri().to_string().as_str() {
+                "https://docs.rs/tokio/latest/tokio/" => Response::builder().status(200).body(
+                    r#"<section id="main-content"><h2 id="macros">Macros</h2><ul class="item-table"><li><div class="item-name"><a class="macro" href="macro.select.html" title="macro tokio::select">select</a></div><div class="desc docblock-short">Waits on multiple concurrent branches.</div></li></ul></section>"#.into(),
+                ),
+                "https://docs.rs/tokio/latest/tokio/macro.select.html" => {
+                    Response::builder().status(200).body(
+                        r#"<section id="main-content"><pre class="rust item-decl"><span class="macro">macro_rules!</span> select {
+    {
+        $(
+            <span class="macro-nonterminal">$bind</span>:pat = <span class="macro-nonterminal">$fut</span>:expr =&gt; <span class="macro-nonterminal">$handler</span>:expr,
+        )*
+    } =&gt; { ... };
+}</pre><details class="toggle top-doc" open><div class="docblock"><p>Waits on multiple concurrent branches, like <a href="macro.join.html">join!</a>.</p></div></details></section>"#.into(),
+                    )
+                }
+                _ => Response::builder().status(404).body("not found".into()),
+            };
+            Ok(response.unwrap())
+        });
+        let provider = DocsDotRsProvider::new(http_client);
+        let query = |item_path: &[&str]| {
+            DocQuery::new("tokio", item_path.iter().map(ToString::to_string).collect())
+        };
+
+        let (_source, markdown) = block_on(provider.fetch(&query(&["select"])))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            markdown,
+            indoc! {"
+                ```rs
+                macro_rules! select {
+                    {
+                        $(
+                            $bind:pat = $fut:expr => $handler:expr,
+                        )*
+                    } => { ... };
+                }
+                ```
+
+                Waits on multiple concurrent branches, like [join!](tokio::join).
+
+                ## See also
+
+                - `tokio::join`"}
+        );
+
+        // Items that aren't listed on the parent module's page are still
+        // reported as missing.
+        let error = format!(
+            "{:#}",
+            block_on(provider.fetch(&query(&["join"]))).unwrap_err()
+        );
+        assert_eq!(error, "status error 404, response: \"not found\"");
+    }
+
+    #[test]
+    fn test_ping() {
+        let http_client = FakeHttpClient::create(|request| async move {
+            let status = if request.uri() == DocsDotRsProvider::PING_URL {
+                200
+            } else {
+                404
+            };
+            Ok(Response::builder().status(status).body("".into()).unwrap())
+        });
+        let ping = block_on(DocsDotRsProvider::new(http_client).ping()).unwrap();
+        assert_eq!(ping.status, 200);
+        assert!(ping.is_success());
+
+        let http_client = FakeHttpClient::create(|_| async move {
+            Ok(Response::builder().status(503).body("".into()).unwrap())
+        });
+        let ping = block_on(DocsDotRsProvider::new(http_client).ping()).unwrap();
+        assert_eq!(ping.status, 503);
+        assert!(!ping.is_success());
+
+        let http_client = FakeHttpClient::create(|_| async move {
+            Err(std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into())
+        });
+        let error = block_on(DocsDotRsProvider::new(http_client).ping())
+            .unwrap_err()
+            .to_string();
+        assert_eq!(error, "failed to reach https://docs.rs/robots.txt");
+    }
+
+    #[test]
+    fn test_missing_version() {
+        let http_client = FakeHttpClient::create(|_| async move {
+            Ok(Response::builder()
+                .status(404)
+                .body("not found".into())
+                .unwrap())
+        });
+        let provider = DocsDotRsProvider::new(http_client);
+        let (crate_name, version) = crate::parse_crate_version("serde@1.0.999").unwrap();
+        let query =
+            DocQuery::new(crate_name, vec!["Deserializer".to_string()]).with_version(version);
+
+        let error = format!("{:#}", block_on(provider.fetch(&query)).unwrap_err());
+        assert_eq!(
+            error,
+            "docs.rs has no docs for version 1.0.999 of serde. Check that the version has been \
+            published and that its docs were built: status error 404, response: \"not found\""
+        );
+    }
+
+    #[test]
+    fn test_missing_target() {
+        let http_client = FakeHttpClient::create(|request| async move {
+            assert_eq!(
+                request.uri().to_string(),
+                "https://docs.rs/windows/latest/aarch64-apple-darwin/windows/Win32"
+            );
+            Ok(Response::builder()
+                .status(404)
+                .body("not found".into())
+                .unwrap())
+        });
+        let provider = DocsDotRsProvider::new(http_client);
+        let query = DocQuery::new("windows", vec!["Win32".to_string()])
+            .with_target(Some("aarch64-apple-darwin".to_string()));
+
+        let error = format!("{:#}", block_on(provider.fetch(&query)).unwrap_err());
+        assert_eq!(
+            error,
+            "docs.rs has no docs for windows on the aarch64-apple-darwin target. Check that the \
+            target is one of the targets that docs.rs builds the crate for (see \
+            `[package.metadata.docs.rs]` in its `Cargo.toml`): status error 404, response: \
+            \"not found\""
+        );
+    }
+
+    #[test]
+    fn test_cargo_doc_features() {
+        let features = CargoDocFeatures::parse("sync, rt-multi-thread,");
+        assert_eq!(
+            features,
+            CargoDocFeatures::Features(vec!["sync".to_string(), "rt-multi-thread".to_string()])
+        );
+        assert_eq!(
+            features.cargo_doc_args(),
+            vec!["--features".to_string(), "sync,rt-multi-thread".to_string()]
+        );
+        assert_eq!(
+            features.cargo_doc_flags(),
+            " --features sync,rt-multi-thread"
+        );
+        assert_eq!(CargoDocFeatures::Default.cargo_doc_flags(), "");
+        assert_eq!(CargoDocFeatures::All.cargo_doc_flags(), " --all-features");
+    }
+
+    #[gpui::test]
+    async fn test_local_page_encoding(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let html = indoc! {r##"
+            <html>
+            <head><meta charset="windows-1252"><title>cafe - Rust</title></head>
+            <body class="rustdoc mod crate">
+                <main><section id="main-content" class="content">
+                    <div class="main-heading"><h1>Crate <a class="mod" href="#">cafe</a></h1></div>
+                    <details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary>
+                    <div class="docblock"><p>A café.</p></div></details>
+                </section></main>
+            </body>
+            </html>
+        "##};
+        let (encoded, _, _) = encoding_rs::WINDOWS_1252.encode(html);
+        fs.insert_file("/app/target/doc/cafe/index.html", encoded.into_owned())
+            .await;
+        let provider = LocalProvider::new(fs, PathBuf::from("/app"))
+            .with_target_dir(PathBuf::from("/app/target"));
+
+        let (source, markdown) = provider
+            .fetch(&DocQuery::new("cafe", Vec::new()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(source, RustdocSource::Local);
+        assert_eq!(markdown, "# Crate cafe\n\nA café.");
+    }
+
+    #[gpui::test]
+    async fn test_locked_versions(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let lockfile = |name: &str, version: &str| {
+            format!(
+                "version = 3\n\n[[package]]\nname = \"{name}\"\nversion = \"{version}\"\n\
+                source = \"registry+https://github.com/rust-lang/crates.io-index\"\n"
+            )
+        };
+        fs.insert_file("/app/Cargo.lock", lockfile("tokio", "1.38.0").into_bytes())
+            .await;
+        fs.insert_file("/lib/Cargo.lock", lockfile("serde", "1.0.200").into_bytes())
+            .await;
+        let http_client = FakeHttpClient::create(|request| {
+            let url = request.uri().to_string();
+            async move {
+                let status = match url.as_str() {
+                    "https://docs.rs/tokio/1.38.0/tokio/" => 200,
+                    _ => 404,
+                };
+                Ok(Response::builder()
+                    .status(status)
+                    .body("<main>docs</main>".into())
+                    .unwrap())
+            }
+        });
+        let provider = DocsDotRsProvider::new(http_client).with_lockfiles(
+            fs.clone(),
+            vec![
+                PathBuf::from("/app/Cargo.lock"),
+                PathBuf::from("/missing/Cargo.lock"),
+                PathBuf::from("/lib/Cargo.lock"),
+            ],
+        );
+        let locked_version = |crate_name: &str, version: Option<&str>| {
+            let mut query = DocQuery::new(crate_name, Vec::new());
+            query.version = version.map(|version| version.parse().unwrap());
+            let provider = &provider;
+            async move {
+                provider
+                    .locked_query(&query)
+                    .await
+                    .version
+                    .map(|version| version.to_string())
+            }
+        };
+        assert_eq!(
+            locked_version("tokio", None).await,
+            Some("1.38.0".to_string())
+        );
+        assert_eq!(
+            locked_version("serde", None).await,
+            Some("1.0.200".to_string())
+        );
+        // A crate that isn't locked is fetched at its latest version.
+        assert_eq!(locked_version("rand", None).await, None);
+        // A query for a specific version keeps it.
+        assert_eq!(
+            locked_version("tokio", Some("1.0.0")).await,
+            Some("1.0.0".to_string())
+        );
+
+        // The page is also known by the URL it would have been requested at.
+        let query = DocQuery::new("tokio", Vec::new());
+        provider.fetch(&query).await.unwrap().unwrap();
+        let requested_url = query.docs_url(&provider.std_channel);
+        assert_eq!(
+            provider.resolved_url(&requested_url),
+            Some("https://docs.rs/tokio/1.38.0/tokio/".to_string())
+        );
+        assert_eq!(
+            provider.resolved_version(&requested_url),
+            Some("1.38.0".to_string())
+        );
+    }
+}
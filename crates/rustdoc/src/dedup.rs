@@ -0,0 +1,193 @@
+use collections::{HashMap, HashSet};
+
+/// The line that replaces the body of a section that was moved to the shared
+/// sections by [`dedup_sections`].
+const SHARED_SECTION_REFERENCE: &str = "_This section is shared with other items; see below._";
+
+/// Collapses the sections that are identical across more than one of the
+/// given docs into a single shared section.
+///
+/// A section is a heading below the top level (e.g., `## Examples`) along
+/// with everything up to the next heading. The body of each shared section is
+/// replaced with a reference to it in each of the docs, and the shared
+/// sections are returned in the order they first appear.
+pub fn dedup_sections(docs: &mut [String]) -> Vec<String> {
+    let sections_by_doc = docs
+        .iter()
+        .map(|doc| split_sections(doc))
+        .collect::<Vec<_>>();
+
+    let mut doc_counts: HashMap<&str, usize> = HashMap::default();
+    for sections in &sections_by_doc {
+        let unique_sections = sections
+            .iter()
+            .filter(|section| section.is_heading && section.has_body())
+            .map(|section| section.text.trim())
+            .collect::<HashSet<_>>();
+        for section in unique_sections {
+            *doc_counts.entry(section).or_default() += 1;
+        }
+    }
+
+    let mut shared_sections = Vec::new();
+    let mut seen_shared_sections = HashSet::default();
+    let deduped_docs = sections_by_doc
+        .iter()
+        .map(|sections| {
+            let mut deduped_doc = Vec::new();
+            for section in sections {
+                let text = section.text.trim();
+                let is_shared = doc_counts.get(text).map_or(false, |count| *count > 1);
+                if !is_shared {
+                    deduped_doc.push(section.text.to_string());
+                    continue;
+                }
+
+                if seen_shared_sections.insert(text) {
+                    shared_sections.push(text.to_string());
+                }
+                let heading = text.lines().next().unwrap_or_default();
+                deduped_doc.push(format!("{heading}\n\n{SHARED_SECTION_REFERENCE}"));
+            }
+            deduped_doc.join("\n\n")
+        })
+        .collect::<Vec<_>>();
+
+    for (doc, deduped_doc) in docs.iter_mut().zip(deduped_docs) {
+        *doc = deduped_doc;
+    }
+
+    shared_sections
+}
+
+struct Section<'a> {
+    text: &'a str,
+    /// Whether the section starts with a heading, as opposed to being the
+    /// content before the first heading.
+    is_heading: bool,
+}
+
+impl Section<'_> {
+    /// Returns whether the section has any content besides its heading.
+    fn has_body(&self) -> bool {
+        self.text
+            .lines()
+            .skip(1)
+            .any(|line| !line.trim().is_empty())
+    }
+}
+
+/// Splits the given Markdown into sections at each heading below the top
+/// level, ignoring any lines within code blocks that look like headings.
+fn split_sections(markdown: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut section_start = 0;
+    let mut is_heading = false;
+    let mut is_inside_code_block = false;
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        if line.starts_with("```") {
+            is_inside_code_block = !is_inside_code_block;
+        } else if !is_inside_code_block && line.starts_with("##") {
+            if offset > section_start {
+                sections.push(Section {
+                    text: markdown[section_start..offset].trim_end(),
+                    is_heading,
+                });
+            }
+            section_start = offset;
+            is_heading = true;
+        }
+        offset += line.len();
+    }
+    if offset > section_start {
+        sections.push(Section {
+            text: markdown[section_start..].trim_end(),
+            is_heading,
+        });
+    }
+
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_dedup_sections() {
+        let mut docs = vec![
+            indoc! {"
+                # Struct tokio::sync::Mutex
+
+                An asynchronous mutex.
+
+                ## Trait Implementations
+
+                ### impl<T> Send for Mutex<T>
+
+                ```rs
+                ## Not a heading
+                ```
+
+                ## Examples
+
+                Locking the mutex.
+            "}
+            .trim()
+            .to_string(),
+            indoc! {"
+                # Struct tokio::sync::RwLock
+
+                An asynchronous reader-writer lock.
+
+                ## Trait Implementations
+
+                ### impl<T> Send for Mutex<T>
+
+                ```rs
+                ## Not a heading
+                ```
+
+                ## Examples
+
+                Reading from the lock.
+            "}
+            .trim()
+            .to_string(),
+        ];
+
+        let shared_sections = dedup_sections(&mut docs);
+
+        assert_eq!(
+            shared_sections,
+            vec![indoc! {"
+                ### impl<T> Send for Mutex<T>
+
+                ```rs
+                ## Not a heading
+                ```"}
+            .to_string()]
+        );
+        assert_eq!(
+            docs[0],
+            indoc! {"
+                # Struct tokio::sync::Mutex
+
+                An asynchronous mutex.
+
+                ## Trait Implementations
+
+                ### impl<T> Send for Mutex<T>
+
+                _This section is shared with other items; see below._
+
+                ## Examples
+
+                Locking the mutex."}
+        );
+    }
+}
@@ -0,0 +1,483 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Context, Result};
+use collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use fs::Fs;
+use parking_lot::Mutex;
+use serde::Deserialize;
+
+use crate::normalize_crate_name;
+
+/// A dependency of a crate, as resolved in a `Cargo.lock`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct CrateDependency {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    /// Where the package comes from, e.g., a registry or a Git repository.
+    ///
+    /// `None` for the crates of the workspace and its path dependencies.
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+impl Lockfile {
+    /// Returns the package referenced by an entry in a package's `dependencies`.
+    ///
+    /// Entries are of the form `name`, or `name version` when multiple versions
+    /// of the package are present in the lockfile.
+    fn resolve(&self, dependency: &str) -> Option<&LockedPackage> {
+        let mut parts = dependency.split_whitespace();
+        let name = parts.next()?;
+        let version = parts.next();
+
+        self.package.iter().find(|package| {
+            package.name == name
+                && version.map_or(true, |version| versions_match(&package.version, version))
+        })
+    }
+}
+
+/// Returns whether the given versions refer to the same release.
+///
+/// Build metadata (e.g., the `+build.5` in `1.0.0-alpha.1+build.5`) is ignored,
+/// as it doesn't distinguish releases, but pre-release identifiers are not.
+fn versions_match(a: &str, b: &str) -> bool {
+    match (semver::Version::parse(a), semver::Version::parse(b)) {
+        (Ok(a), Ok(b)) => a.cmp_precedence(&b).is_eq(),
+        _ => a == b,
+    }
+}
+
+/// Returns the dependencies of the given crate from the contents of a
+/// `Cargo.lock`, sorted by name.
+///
+/// The crate may be given as `name@version` to pick one of multiple versions
+/// of it in the lockfile.
+///
+/// Only the direct dependencies are returned, unless `transitive` is set.
+pub fn crate_dependencies(
+    lockfile: &str,
+    crate_spec: &str,
+    transitive: bool,
+) -> Result<Vec<CrateDependency>> {
+    let lockfile: Lockfile = toml::from_str(lockfile).context("failed to parse Cargo.lock")?;
+
+    let (crate_name, version) = crate_spec
+        .split_once('@')
+        .map_or((crate_spec, None), |(name, version)| (name, Some(version)));
+    let normalize = |name: &str| name.replace('-', "_");
+    let root = lockfile
+        .package
+        .iter()
+        .find(|package| {
+            normalize(&package.name) == normalize(crate_name)
+                && version.map_or(true, |version| versions_match(&package.version, version))
+        })
+        .ok_or_else(|| anyhow!("{crate_spec} not found in Cargo.lock"))?;
+
+    let mut dependencies = BTreeSet::new();
+    let mut seen = HashSet::default();
+    let mut packages_to_visit = VecDeque::from([root]);
+    while let Some(package) = packages_to_visit.pop_front() {
+        for dependency in &package.dependencies {
+            let Some(dependency) = lockfile.resolve(dependency) else {
+                continue;
+            };
+
+            if !seen.insert((&dependency.name, &dependency.version)) {
+                continue;
+            }
+
+            dependencies.insert(CrateDependency {
+                name: dependency.name.clone(),
+                version: dependency.version.clone(),
+            });
+
+            if transitive {
+                packages_to_visit.push_back(dependency);
+            }
+        }
+    }
+
+    Ok(dependencies.into_iter().collect())
+}
+
+/// Returns every dependency resolved in the contents of a `Cargo.lock`,
+/// sorted by name and version.
+///
+/// The crates of the workspace and its path dependencies are left out, as
+/// they aren't dependencies that were fetched from elsewhere.
+pub fn locked_dependencies(lockfile: &str) -> Result<Vec<CrateDependency>> {
+    let lockfile: Lockfile = toml::from_str(lockfile).context("failed to parse Cargo.lock")?;
+
+    let dependencies = lockfile
+        .package
+        .into_iter()
+        .filter(|package| package.source.is_some())
+        .map(|package| CrateDependency {
+            name: package.name,
+            version: package.version,
+        })
+        .collect::<BTreeSet<_>>();
+    Ok(dependencies.into_iter().collect())
+}
+
+/// Returns the normalized names of every crate in the contents of a
+/// `Cargo.lock`, sorted and without duplicates.
+///
+/// Unlike [`locked_dependencies`], the crates of the workspace are included,
+/// so that the names cover every crate the project could have docs for.
+pub fn locked_crate_names(lockfile: &str) -> Result<Vec<String>> {
+    let lockfile: Lockfile = toml::from_str(lockfile).context("failed to parse Cargo.lock")?;
+
+    let crate_names = lockfile
+        .package
+        .into_iter()
+        .map(|package| normalize_crate_name(&package.name))
+        .collect::<BTreeSet<_>>();
+    Ok(crate_names.into_iter().collect())
+}
+
+/// The versions of the dependencies resolved in a `Cargo.lock`, keyed by
+/// their normalized names.
+///
+/// Only the dependencies from a registry are included, as `docs.rs` only has
+/// the docs for the versions published to crates.io.
+#[derive(Debug, Default)]
+pub struct LockedVersions {
+    versions: HashMap<String, semver::Version>,
+}
+
+impl LockedVersions {
+    /// Parses the contents of a `Cargo.lock`.
+    ///
+    /// When several versions of a crate are locked, the one that a crate of
+    /// the workspace depends on directly is picked, or else the newest one.
+    pub fn parse(lockfile: &str) -> Result<Self> {
+        let lockfile: Lockfile = toml::from_str(lockfile).context("failed to parse Cargo.lock")?;
+
+        let direct_dependencies = lockfile
+            .package
+            .iter()
+            .filter(|package| package.source.is_none())
+            .flat_map(|package| &package.dependencies)
+            .filter_map(|dependency| lockfile.resolve(dependency))
+            .map(|package| (&package.name, &package.version))
+            .collect::<HashSet<_>>();
+
+        let mut versions = HashMap::<String, (bool, semver::Version)>::default();
+        for package in &lockfile.package {
+            let is_from_registry = package.source.as_deref().map_or(false, |source| {
+                source.starts_with("registry+") || source.starts_with("sparse+")
+            });
+            let Some(version) = semver::Version::parse(&package.version)
+                .ok()
+                .filter(|_| is_from_registry)
+            else {
+                continue;
+            };
+
+            let is_direct = direct_dependencies.contains(&(&package.name, &package.version));
+            let candidate = (is_direct, version);
+            let crate_name = normalize_crate_name(&package.name);
+            match versions.get(&crate_name) {
+                Some(locked) if *locked >= candidate => {}
+                _ => {
+                    versions.insert(crate_name, candidate);
+                }
+            }
+        }
+
+        Ok(Self {
+            versions: versions
+                .into_iter()
+                .map(|(crate_name, (_, version))| (crate_name, version))
+                .collect(),
+        })
+    }
+
+    /// Loads the versions from the `Cargo.lock` at the given path.
+    ///
+    /// The versions parsed from each `Cargo.lock` are kept for as long as it
+    /// isn't modified, so that they aren't parsed again for each query.
+    pub async fn load(fs: &dyn Fs, lockfile_path: &Path) -> Result<Arc<Self>> {
+        static CACHE: OnceLock<Mutex<HashMap<PathBuf, (SystemTime, Arc<LockedVersions>)>>> =
+            OnceLock::new();
+        let cache = CACHE.get_or_init(Default::default);
+
+        let mtime = fs
+            .metadata(lockfile_path)
+            .await?
+            .ok_or_else(|| anyhow!("{lockfile_path:?} does not exist"))?
+            .mtime;
+        if let Some((cached_mtime, versions)) = cache.lock().get(lockfile_path) {
+            if *cached_mtime == mtime {
+                return Ok(versions.clone());
+            }
+        }
+
+        let lockfile = fs
+            .load(lockfile_path)
+            .await
+            .with_context(|| format!("failed to read {lockfile_path:?}"))?;
+        let versions = Arc::new(Self::parse(&lockfile)?);
+        cache
+            .lock()
+            .insert(lockfile_path.to_path_buf(), (mtime, versions.clone()));
+        Ok(versions)
+    }
+
+    /// Returns the locked version of the given crate, if it is a dependency.
+    pub fn get(&self, crate_name: &str) -> Option<&semver::Version> {
+        self.versions.get(&normalize_crate_name(crate_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const LOCKFILE: &str = indoc! {r#"
+        version = 3
+
+        [[package]]
+        name = "my-crate"
+        version = "0.1.0"
+        dependencies = [
+         "anyhow",
+         "syn 2.0.48",
+        ]
+
+        [[package]]
+        name = "anyhow"
+        version = "1.0.86"
+
+        [[package]]
+        name = "my-macros"
+        version = "0.2.0-alpha.1+build.5"
+        dependencies = [
+         "syn 2.0.0-rc.1",
+        ]
+
+        [[package]]
+        name = "proc-macro2"
+        version = "1.0.81"
+
+        [[package]]
+        name = "syn"
+        version = "1.0.109"
+
+        [[package]]
+        name = "syn"
+        version = "2.0.48"
+        dependencies = [
+         "proc-macro2",
+        ]
+
+        [[package]]
+        name = "syn"
+        version = "2.0.0-rc.1"
+    "#};
+
+    fn dependency(name: &str, version: &str) -> CrateDependency {
+        CrateDependency {
+            name: name.into(),
+            version: version.into(),
+        }
+    }
+
+    #[test]
+    fn test_direct_dependencies() {
+        assert_eq!(
+            crate_dependencies(LOCKFILE, "my_crate", false).unwrap(),
+            vec![dependency("anyhow", "1.0.86"), dependency("syn", "2.0.48")]
+        );
+    }
+
+    #[test]
+    fn test_transitive_dependencies() {
+        assert_eq!(
+            crate_dependencies(LOCKFILE, "my_crate", true).unwrap(),
+            vec![
+                dependency("anyhow", "1.0.86"),
+                dependency("proc-macro2", "1.0.81"),
+                dependency("syn", "2.0.48"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pre_release_dependencies() {
+        assert_eq!(
+            crate_dependencies(LOCKFILE, "my-macros", false).unwrap(),
+            vec![dependency("syn", "2.0.0-rc.1")]
+        );
+        assert_eq!(
+            crate_dependencies(LOCKFILE, "my_macros@0.2.0-alpha.1", false).unwrap(),
+            vec![dependency("syn", "2.0.0-rc.1")]
+        );
+        assert!(crate_dependencies(LOCKFILE, "my_macros@0.2.0", false).is_err());
+        assert!(crate_dependencies(LOCKFILE, "syn@2.0.0", false).is_err());
+    }
+
+    #[test]
+    fn test_locked_dependencies() {
+        let lockfile = indoc! {r#"
+            version = 3
+
+            [[package]]
+            name = "my-crate"
+            version = "0.1.0"
+            dependencies = [
+             "my-utils",
+             "syn 1.0.109",
+             "syn 2.0.48",
+            ]
+
+            [[package]]
+            name = "my-utils"
+            version = "0.1.0"
+
+            [[package]]
+            name = "syn"
+            version = "2.0.48"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+
+            [[package]]
+            name = "syn"
+            version = "1.0.109"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+
+            [[package]]
+            name = "anyhow"
+            version = "1.0.86"
+            source = "git+https://github.com/dtolnay/anyhow#1b2c3d4"
+        "#};
+
+        assert_eq!(
+            locked_dependencies(lockfile).unwrap(),
+            vec![
+                dependency("anyhow", "1.0.86"),
+                dependency("syn", "1.0.109"),
+                dependency("syn", "2.0.48"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_locked_crate_names() {
+        assert_eq!(
+            locked_crate_names(LOCKFILE).unwrap(),
+            vec!["anyhow", "my_crate", "my_macros", "proc_macro2", "syn"]
+        );
+    }
+
+    const SOURCED_LOCKFILE: &str = indoc! {r#"
+        version = 3
+
+        [[package]]
+        name = "my-crate"
+        version = "0.1.0"
+        dependencies = [
+         "my-utils",
+         "serde-json",
+         "syn 1.0.109",
+        ]
+
+        [[package]]
+        name = "my-utils"
+        version = "0.1.0"
+
+        [[package]]
+        name = "serde-json"
+        version = "1.0.120"
+        source = "registry+https://github.com/rust-lang/crates.io-index"
+
+        [[package]]
+        name = "syn"
+        version = "1.0.109"
+        source = "registry+https://github.com/rust-lang/crates.io-index"
+
+        [[package]]
+        name = "syn"
+        version = "2.0.48"
+        source = "registry+https://github.com/rust-lang/crates.io-index"
+
+        [[package]]
+        name = "tokio"
+        version = "1.38.0"
+        source = "sparse+https://index.crates.io/"
+
+        [[package]]
+        name = "forked"
+        version = "0.3.0"
+        source = "git+https://github.com/example/forked#0123456789abcdef"
+    "#};
+
+    #[test]
+    fn test_locked_versions() {
+        let versions = LockedVersions::parse(SOURCED_LOCKFILE).unwrap();
+        let version = |crate_name| versions.get(crate_name).map(ToString::to_string);
+        assert_eq!(version("serde_json"), Some("1.0.120".to_string()));
+        assert_eq!(version("tokio"), Some("1.38.0".to_string()));
+        // The version that the workspace depends on wins over a newer one.
+        assert_eq!(version("syn"), Some("1.0.109".to_string()));
+        // Neither the workspace's crates nor Git dependencies are on docs.rs.
+        assert_eq!(version("my_utils"), None);
+        assert_eq!(version("forked"), None);
+        assert_eq!(version("rand"), None);
+    }
+
+    #[gpui::test]
+    async fn test_load_locked_versions(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let lockfile_path = Path::new("/test_load_locked_versions/Cargo.lock");
+        fs.insert_file(lockfile_path, SOURCED_LOCKFILE.as_bytes().to_vec())
+            .await;
+        let versions = LockedVersions::load(fs.as_ref(), lockfile_path)
+            .await
+            .unwrap();
+        assert_eq!(
+            versions.get("tokio").map(ToString::to_string),
+            Some("1.38.0".to_string())
+        );
+
+        // The versions are reparsed only once the lockfile changes.
+        let cached_versions = LockedVersions::load(fs.as_ref(), lockfile_path)
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(&versions, &cached_versions));
+
+        let updated_lockfile = SOURCED_LOCKFILE.replace("1.38.0", "1.39.2");
+        fs.insert_file(lockfile_path, updated_lockfile.into_bytes())
+            .await;
+        let versions = LockedVersions::load(fs.as_ref(), lockfile_path)
+            .await
+            .unwrap();
+        assert_eq!(
+            versions.get("tokio").map(ToString::to_string),
+            Some("1.39.2".to_string())
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use gpui::{AppContext, Global, ReadGlobal};
+use parking_lot::RwLock;
+
+use crate::crawler::{ClientError, RustdocSource};
+use crate::DocQuery;
+
+/// A source of Rust documentation that can be queried for Markdown docs.
+#[async_trait]
+pub trait DocProvider: Send + Sync {
+    /// Returns the Markdown docs for the given query.
+    ///
+    /// Returns `Ok(None)` if this provider does not have docs for the query,
+    /// so that the next provider may be consulted.
+    async fn fetch(&self, query: &DocQuery) -> Result<Option<(RustdocSource, String)>>;
+
+    /// Returns the name of this provider, as shown in the errors for the
+    /// queries that every provider failed on, e.g., "docs.rs".
+    fn name(&self) -> String {
+        "another provider".to_string()
+    }
+}
+
+/// An error for a query that none of the [`DocProvider`]s have docs for.
+#[derive(Debug)]
+pub struct DocsNotFound {
+    pub crate_name: String,
+}
+
+impl std::fmt::Display for DocsNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "no docs found for {}", self.crate_name)
+    }
+}
+
+impl std::error::Error for DocsNotFound {}
+
+/// Returns whether the given error is because there are no docs for the
+/// query, as opposed to fetching or converting the docs having failed.
+pub fn is_docs_not_found(err: &anyhow::Error) -> bool {
+    err.is::<DocsNotFound>()
+        || err
+            .downcast_ref::<ClientError>()
+            .map_or(false, |err| err.status == 404)
+}
+
+struct GlobalDocProviderRegistry(Arc<DocProviderRegistry>);
+
+impl Global for GlobalDocProviderRegistry {}
+
+/// A registry of additional [`DocProvider`]s.
+///
+/// Registered providers are consulted after the local providers and before
+/// falling back to `docs.rs`.
+#[derive(Default)]
+pub struct DocProviderRegistry {
+    providers: RwLock<Vec<Arc<dyn DocProvider>>>,
+}
+
+impl DocProviderRegistry {
+    /// Returns the global [`DocProviderRegistry`].
+    pub fn global(cx: &AppContext) -> Arc<Self> {
+        GlobalDocProviderRegistry::global(cx).0.clone()
+    }
+
+    pub fn init_global(cx: &mut AppContext) {
+        cx.set_global(GlobalDocProviderRegistry(Arc::new(Self::default())));
+    }
+
+    /// Registers the provided [`DocProvider`].
+    pub fn register_provider(&self, provider: Arc<dyn DocProvider>) {
+        self.providers.write().push(provider);
+    }
+
+    /// Returns the registered [`DocProvider`]s, in registration order.
+    pub fn providers(&self) -> Vec<Arc<dyn DocProvider>> {
+        self.providers.read().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[test]
+    fn test_docs_not_found() {
+        let not_found = anyhow::Error::new(DocsNotFound {
+            crate_name: "tokio".into(),
+        });
+        assert_eq!(not_found.to_string(), "no docs found for tokio");
+        assert!(is_docs_not_found(&not_found));
+        assert!(is_docs_not_found(
+            &not_found.context("failed to fetch docs for tokio::sync::Mutx")
+        ));
+
+        let missing_page = anyhow::Error::new(ClientError {
+            status: 404,
+            response: String::new(),
+        });
+        assert!(is_docs_not_found(&missing_page));
+
+        let rate_limited = anyhow::Error::new(ClientError {
+            status: 429,
+            response: String::new(),
+        });
+        assert!(!is_docs_not_found(&rate_limited));
+        assert!(!is_docs_not_found(&anyhow!("failed to parse the docs")));
+    }
+}
@@ -1,6 +1,8 @@
+use std::str::FromStr;
 use std::sync::Arc;
 
-use strum::EnumIter;
+use anyhow::{anyhow, Result};
+use strum::{EnumIter, IntoEnumIterator};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, EnumIter)]
 pub enum RustdocItemKind {
@@ -31,8 +33,49 @@ impl RustdocItemKind {
             Self::DeriveMacro => "derive",
         }
     }
+
+    /// Returns the kind with the given class, as returned by [`Self::class`].
+    pub(crate) fn from_class(class: &str) -> Option<Self> {
+        Self::iter().find(|kind| kind.class() == class)
+    }
+
+    /// Returns the ID of the section that lists the items of this kind on a
+    /// module page.
+    pub(crate) const fn section_id(&self) -> &'static str {
+        match self {
+            Self::Mod => "modules",
+            Self::Macro => "macros",
+            Self::Struct => "structs",
+            Self::Enum => "enums",
+            Self::Constant => "constants",
+            Self::Trait => "traits",
+            Self::Function => "functions",
+            Self::TypeAlias => "types",
+            Self::AttributeMacro => "attributes",
+            Self::DeriveMacro => "derives",
+        }
+    }
+}
+
+impl FromStr for RustdocItemKind {
+    type Err = anyhow::Error;
+
+    /// Parses an item kind from its class (e.g., `fn` or `struct`).
+    fn from_str(class: &str) -> Result<Self> {
+        Self::from_class(class).ok_or_else(|| {
+            let classes = Self::iter()
+                .map(|kind| format!("`{}`", kind.class()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            anyhow!("unknown item kind `{class}`, expected one of {classes}")
+        })
+    }
 }
 
+/// An item that rustdoc has a page for, identified by its kind and path.
+///
+/// The path of an item found on a page is relative to the page's module, as
+/// in rustdoc's links (e.g., `Router` for `axum::Router` on the crate root).
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct RustdocItem {
     pub kind: RustdocItemKind,
@@ -42,6 +85,35 @@ pub struct RustdocItem {
     pub name: Arc<str>,
 }
 
+/// An item linked to from a rustdoc page, along with what the page says
+/// about it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RustdocPageItem {
+    pub item: RustdocItem,
+    /// The one-line summary of the item, as shown in the list of items on a
+    /// module page.
+    ///
+    /// `None` for items that the page only links to, e.g., in a signature.
+    pub summary: Option<String>,
+    /// Whether the item is marked as deprecated in the list of items on a
+    /// module page.
+    pub deprecated: bool,
+    /// Whether the item is marked as experimental, i.e., as only being
+    /// available on nightly, in the list of items on a module page.
+    pub unstable: bool,
+}
+
+impl RustdocPageItem {
+    pub fn new(item: RustdocItem) -> Self {
+        Self {
+            item,
+            summary: None,
+            deprecated: false,
+            unstable: false,
+        }
+    }
+}
+
 impl RustdocItem {
     pub fn display(&self) -> String {
         let mut path_segments = self.path.clone();
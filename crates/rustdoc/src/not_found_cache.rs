@@ -0,0 +1,127 @@
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use collections::HashMap;
+use parking_lot::Mutex;
+
+use crate::normalize_crate_name;
+
+/// An in-memory record of the `docs.rs` pages that were recently not found,
+/// keyed by the URL they were requested at.
+///
+/// Looking up an item that doesn't exist again would otherwise wait for
+/// `docs.rs` to respond with the same 404. The entries expire after a few
+/// minutes, so that a page that was missing because of a transient issue on
+/// `docs.rs` isn't missing for long.
+pub struct NotFoundCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, NotFoundEntry>>,
+}
+
+struct NotFoundEntry {
+    crate_name: String,
+    /// The body of the response, which is part of the error for the page.
+    response: String,
+    not_found_at: Instant,
+}
+
+impl NotFoundCache {
+    /// How long the global cache remembers that a page was not found.
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(5 * 60);
+
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::default()),
+        }
+    }
+
+    /// Returns the cache shared by every lookup in the process.
+    pub fn global() -> Arc<Self> {
+        static CACHE: OnceLock<Arc<NotFoundCache>> = OnceLock::new();
+        CACHE
+            .get_or_init(|| Arc::new(Self::new(Self::DEFAULT_TTL)))
+            .clone()
+    }
+
+    /// Returns the body of the response for the page at the given URL, if it
+    /// was not found within the TTL.
+    pub fn get(&self, url: &str) -> Option<String> {
+        let mut entries = self.entries.lock();
+        let entry = entries.get(url)?;
+        if entry.not_found_at.elapsed() >= self.ttl {
+            entries.remove(url);
+            return None;
+        }
+        Some(entry.response.clone())
+    }
+
+    /// Records that the given crate's page at the given URL was not found,
+    /// with the body of the response.
+    pub fn insert(&self, crate_name: &str, url: &str, response: String) {
+        self.entries.lock().insert(
+            url.to_string(),
+            NotFoundEntry {
+                crate_name: normalize_crate_name(crate_name),
+                response,
+                not_found_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Forgets that the page at the given URL was not found, e.g., because
+    /// it has since been fetched.
+    pub fn remove(&self, url: &str) {
+        self.entries.lock().remove(url);
+    }
+
+    /// Forgets the pages of the given crate that were not found, e.g.,
+    /// because the crate has since been indexed.
+    pub fn remove_crate(&self, crate_name: &str) {
+        let crate_name = normalize_crate_name(crate_name);
+        self.entries
+            .lock()
+            .retain(|_url, entry| entry.crate_name != crate_name);
+    }
+
+    /// Forgets every page that was not found.
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_not_found_cache() {
+        let cache = NotFoundCache::new(Duration::from_secs(60));
+        let url = "https://docs.rs/serde-json/latest/serde_json/struct.Missing.html";
+        cache.insert("serde-json", url, "not found".to_string());
+        cache.insert(
+            "tokio",
+            "https://docs.rs/tokio/latest/tokio/missing",
+            String::new(),
+        );
+        assert_eq!(cache.get(url), Some("not found".to_string()));
+        assert_eq!(
+            cache.get("https://docs.rs/serde_json/latest/serde_json/"),
+            None
+        );
+
+        // The crate's pages are forgotten by either form of its name.
+        cache.remove_crate("serde_json");
+        assert_eq!(cache.get(url), None);
+        assert!(cache
+            .get("https://docs.rs/tokio/latest/tokio/missing")
+            .is_some());
+
+        // Expired entries aren't returned.
+        let cache = NotFoundCache::new(Duration::ZERO);
+        cache.insert("serde_json", url, String::new());
+        assert_eq!(cache.get(url), None);
+    }
+}
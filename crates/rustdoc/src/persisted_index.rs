@@ -0,0 +1,287 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::crawler::RustdocSource;
+use crate::{RustdocItem, RustdocItemKind};
+
+/// The version of the format that the [`RustdocStore`](crate::RustdocStore)
+/// index is persisted in.
+///
+/// Bump this whenever the format changes, and add a migration from the
+/// previous version to [`parse_persisted_index`].
+const INDEX_FORMAT_VERSION: u32 = 2;
+
+/// The docs for an item in a persisted index.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PersistedDocs {
+    pub crate_name: String,
+    pub item: RustdocItem,
+    pub markdown: String,
+    pub source: RustdocSource,
+    /// The version of the crate that the docs are for, if it is known.
+    pub version: Option<String>,
+    pub indexed_at: SystemTime,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedIndex<Entry> {
+    version: u32,
+    docs: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedItem {
+    kind: String,
+    path: Vec<String>,
+    name: String,
+}
+
+/// An entry in version 2 of the format.
+#[derive(Serialize, Deserialize)]
+struct PersistedEntry {
+    crate_name: String,
+    item: PersistedItem,
+    markdown: String,
+    /// Whether the docs were indexed from local `cargo doc` output.
+    ///
+    /// Indexes written before this was recorded don't have it, which is
+    /// compatible enough not to need a new version of the format, so their
+    /// docs are treated as being from `docs.rs`.
+    #[serde(default)]
+    local: bool,
+    /// The version of the crate that the docs are for, if it is known.
+    ///
+    /// Like `local`, this is missing from older indexes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    /// When the item was indexed, in seconds since the Unix epoch.
+    indexed_at: u64,
+}
+
+/// An entry in version 1 of the format, which predates the `version` field
+/// and didn't record when each item was indexed.
+#[derive(Deserialize)]
+struct LegacyEntry {
+    crate_name: String,
+    item: PersistedItem,
+    markdown: String,
+}
+
+#[derive(Deserialize)]
+struct LegacyIndex {
+    docs: Vec<LegacyEntry>,
+}
+
+impl PersistedItem {
+    fn new(item: &RustdocItem) -> Self {
+        Self {
+            kind: item.kind.class().to_string(),
+            path: item.path.iter().map(ToString::to_string).collect(),
+            name: item.name.to_string(),
+        }
+    }
+
+    fn to_item(&self) -> Option<RustdocItem> {
+        Some(RustdocItem {
+            kind: RustdocItemKind::from_class(&self.kind)?,
+            path: self
+                .path
+                .iter()
+                .map(|segment| segment.as_str().into())
+                .collect(),
+            name: self.name.as_str().into(),
+        })
+    }
+}
+
+/// Serializes the given docs in the current version of the format.
+pub(crate) fn serialize_persisted_index(docs: &[PersistedDocs]) -> serde_json::Result<String> {
+    let docs = docs
+        .iter()
+        .map(|docs| PersistedEntry {
+            crate_name: docs.crate_name.clone(),
+            item: PersistedItem::new(&docs.item),
+            markdown: docs.markdown.clone(),
+            local: docs.source == RustdocSource::Local,
+            version: docs.version.clone(),
+            indexed_at: docs
+                .indexed_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+        .collect();
+
+    serde_json::to_string(&PersistedIndex {
+        version: INDEX_FORMAT_VERSION,
+        docs,
+    })
+}
+
+/// Parses a persisted index, migrating it from an older version of the format
+/// if needed.
+///
+/// Indexes that can't be migrated, e.g., because they were written by a newer
+/// version of Zed, are discarded, so that the crates are reindexed instead.
+pub(crate) fn parse_persisted_index(contents: &str) -> Vec<PersistedDocs> {
+    #[derive(Deserialize)]
+    struct Version {
+        #[serde(default = "legacy_version")]
+        version: u32,
+    }
+
+    fn legacy_version() -> u32 {
+        1
+    }
+
+    let version = match serde_json::from_str::<Version>(contents) {
+        Ok(Version { version }) => version,
+        Err(err) => {
+            log::warn!("discarding unreadable rustdoc index: {err}");
+            return Vec::new();
+        }
+    };
+
+    let entries = match version {
+        1 => serde_json::from_str::<LegacyIndex>(contents).map(|index| {
+            log::info!("migrating rustdoc index from version 1 to version {INDEX_FORMAT_VERSION}");
+            // Without a timestamp, treat the docs as having been indexed
+            // long ago, so that they are considered stale.
+            index
+                .docs
+                .into_iter()
+                .map(|entry| PersistedEntry {
+                    crate_name: entry.crate_name,
+                    item: entry.item,
+                    markdown: entry.markdown,
+                    local: false,
+                    version: None,
+                    indexed_at: 0,
+                })
+                .collect::<Vec<_>>()
+        }),
+        INDEX_FORMAT_VERSION => {
+            serde_json::from_str::<PersistedIndex<PersistedEntry>>(contents).map(|index| index.docs)
+        }
+        _ => {
+            log::warn!(
+                "discarding rustdoc index with unsupported version {version} \
+                (expected at most {INDEX_FORMAT_VERSION})"
+            );
+            return Vec::new();
+        }
+    };
+
+    let entries = match entries {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::warn!("discarding invalid rustdoc index (version {version}): {err}");
+            return Vec::new();
+        }
+    };
+
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let Some(item) = entry.item.to_item() else {
+                log::warn!(
+                    "discarding {crate_name}::{name} from rustdoc index with unknown kind {kind:?}",
+                    crate_name = entry.crate_name,
+                    name = entry.item.name,
+                    kind = entry.item.kind
+                );
+                return None;
+            };
+
+            Some(PersistedDocs {
+                crate_name: entry.crate_name,
+                item,
+                markdown: entry.markdown,
+                source: if entry.local {
+                    RustdocSource::Local
+                } else {
+                    RustdocSource::DocsDotRs
+                },
+                version: entry.version,
+                indexed_at: UNIX_EPOCH + Duration::from_secs(entry.indexed_at),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn docs(indexed_at: SystemTime) -> PersistedDocs {
+        PersistedDocs {
+            crate_name: "tokio".to_string(),
+            item: RustdocItem {
+                kind: RustdocItemKind::Struct,
+                path: vec!["sync".into()],
+                name: "Mutex".into(),
+            },
+            markdown: "# Struct tokio::sync::Mutex".to_string(),
+            source: RustdocSource::DocsDotRs,
+            version: None,
+            indexed_at,
+        }
+    }
+
+    #[test]
+    fn test_persisted_index_round_trip() {
+        let indexed_at = UNIX_EPOCH + Duration::from_secs(1_718_000_000);
+        let contents = serialize_persisted_index(&[docs(indexed_at)]).unwrap();
+        assert_eq!(parse_persisted_index(&contents), vec![docs(indexed_at)]);
+
+        let local_docs = PersistedDocs {
+            source: RustdocSource::Local,
+            version: Some("1.38.0".to_string()),
+            ..docs(indexed_at)
+        };
+        let contents = serialize_persisted_index(&[local_docs.clone()]).unwrap();
+        assert_eq!(parse_persisted_index(&contents), vec![local_docs]);
+    }
+
+    #[test]
+    fn test_migrate_legacy_index() {
+        let contents = indoc! {r##"
+            {
+                "docs": [
+                    {
+                        "crate_name": "tokio",
+                        "item": { "kind": "struct", "path": ["sync"], "name": "Mutex" },
+                        "markdown": "# Struct tokio::sync::Mutex"
+                    },
+                    {
+                        "crate_name": "tokio",
+                        "item": { "kind": "keyword", "path": [], "name": "async" },
+                        "markdown": "# Keyword async"
+                    }
+                ]
+            }
+        "##};
+
+        // Items with unknown kinds are dropped, and the rest are treated as
+        // stale.
+        assert_eq!(parse_persisted_index(contents), vec![docs(UNIX_EPOCH)]);
+    }
+
+    #[test]
+    fn test_discard_incompatible_index() {
+        let newer_version = format!(
+            r#"{{ "version": {}, "docs": {{}} }}"#,
+            INDEX_FORMAT_VERSION + 1
+        );
+        assert_eq!(parse_persisted_index(&newer_version), Vec::new());
+        assert_eq!(
+            parse_persisted_index(r#"{ "version": 2, "docs": 3 }"#),
+            Vec::new()
+        );
+        assert_eq!(parse_persisted_index("not json"), Vec::new());
+    }
+}
@@ -0,0 +1,191 @@
+/// Flattens the Markdown produced from rustdoc into readable plain text.
+///
+/// Code blocks lose their fences and are indented instead, headings lose
+/// their `#`s, list items are bulleted with `•`, tables are separated with
+/// spaces, and inline formatting (code spans, emphasis, and links) is removed.
+pub fn convert_markdown_to_plain_text(markdown: &str) -> String {
+    let mut lines = Vec::new();
+    let mut is_inside_code_block = false;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            is_inside_code_block = !is_inside_code_block;
+            continue;
+        }
+        if is_inside_code_block {
+            lines.push(if line.is_empty() {
+                String::new()
+            } else {
+                format!("    {line}")
+            });
+            continue;
+        }
+
+        let trimmed_line = line.trim_start();
+        let indent = &line[..line.len() - trimmed_line.len()];
+        let line = if let Some(heading) = strip_heading(trimmed_line) {
+            strip_inline_formatting(heading)
+        } else if let Some(item) = trimmed_line.strip_prefix("- ") {
+            format!("{indent}• {}", strip_inline_formatting(item))
+        } else if let Some(quote) = trimmed_line.strip_prefix('>') {
+            strip_inline_formatting(quote.trim_start())
+        } else if trimmed_line.starts_with('|') {
+            if is_table_separator(trimmed_line) {
+                continue;
+            }
+            trimmed_line
+                .trim_matches('|')
+                .split(" | ")
+                .map(|cell| strip_inline_formatting(cell.trim()))
+                .collect::<Vec<_>>()
+                .join("    ")
+        } else {
+            format!("{indent}{}", strip_inline_formatting(trimmed_line))
+        };
+        lines.push(line.trim_end().to_string());
+    }
+
+    lines.join("\n")
+}
+
+/// Returns the text of the given line if it is a heading.
+fn strip_heading(line: &str) -> Option<&str> {
+    let text = line.trim_start_matches('#');
+    let level = line.len() - text.len();
+    if (1..=6).contains(&level) && text.starts_with(' ') {
+        Some(text.trim_start())
+    } else {
+        None
+    }
+}
+
+/// Returns whether the given line is a row of `---`s separating a table's
+/// header from its body.
+fn is_table_separator(line: &str) -> bool {
+    line.chars()
+        .all(|char| matches!(char, '|' | '-' | ':' | ' '))
+}
+
+/// Removes the code spans, emphasis, and links from the given text, keeping
+/// their contents.
+///
+/// Link targets are kept in parentheses after the link text, as they can't be
+/// followed otherwise.
+fn strip_inline_formatting(text: &str) -> String {
+    let text = strip_links(text);
+    let mut plain_text = String::with_capacity(text.len());
+    // Code spans alternate with the text around them.
+    for (ix, part) in text.split('`').enumerate() {
+        if ix % 2 == 1 {
+            plain_text.push_str(part);
+        } else {
+            plain_text.push_str(&strip_emphasis(part));
+        }
+    }
+    plain_text
+}
+
+/// Removes the `**` and `_` delimiters used for emphasis, leaving the
+/// underscores within identifiers (e.g., `snake_case`) alone.
+fn strip_emphasis(text: &str) -> String {
+    let text = text.replace("**", "");
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut stripped = String::with_capacity(text.len());
+    for (ix, char) in chars.iter().enumerate() {
+        if *char == '_' {
+            let is_word_char = |char: Option<&char>| {
+                char.map_or(false, |char| char.is_alphanumeric() || *char == '_')
+            };
+            let previous = ix.checked_sub(1).and_then(|ix| chars.get(ix));
+            let is_inside_word = is_word_char(previous) && is_word_char(chars.get(ix + 1));
+            if !is_inside_word {
+                continue;
+            }
+        }
+        stripped.push(*char);
+    }
+    stripped
+}
+
+/// Replaces each `[text](url)` link with `text (url)`.
+fn strip_links(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        let link = rest[start + 1..]
+            .split_once("](")
+            .and_then(|(link_text, after)| {
+                let (url, after) = after.split_once(')')?;
+                Some((link_text, url, after))
+            });
+        let Some((link_text, url, after)) = link else {
+            break;
+        };
+
+        stripped.push_str(&rest[..start]);
+        stripped.push_str(&format!("{link_text} ({url})"));
+        rest = after;
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_convert_markdown_to_plain_text() {
+        let markdown = indoc! {"
+            # Struct tokio::sync::Mutex
+
+            ```rs
+            pub struct Mutex<T: ?Sized> { /* private fields */ }
+            ```
+
+            An asynchronous `Mutex`-like type for **shared** access to _mutable_ state.
+
+            ## Examples
+
+            - Locking with `lock_owned`
+            - See [`std::sync::Mutex`](https://doc.rust-lang.org/std/sync/struct.Mutex.html)
+
+            | Method | Blocking |
+            | --- | --- |
+            | `lock` | No |
+
+            > **Warning:** The local docs for `tokio` may be out of date.
+        "};
+        let expected = indoc! {"
+            Struct tokio::sync::Mutex
+
+                pub struct Mutex<T: ?Sized> { /* private fields */ }
+
+            An asynchronous Mutex-like type for shared access to mutable state.
+
+            Examples
+
+            • Locking with lock_owned
+            • See std::sync::Mutex (https://doc.rust-lang.org/std/sync/struct.Mutex.html)
+
+            Method    Blocking
+            lock    No
+
+            Warning: The local docs for tokio may be out of date."};
+
+        let plain_text = convert_markdown_to_plain_text(markdown);
+        assert_eq!(plain_text, expected);
+
+        // The code is left as is, so only the prose is checked.
+        for line in plain_text.lines().filter(|line| !line.starts_with("    ")) {
+            for syntax in ["```", "#", "**", "`", "](", "|", "- ", "> "] {
+                assert!(
+                    !line.contains(syntax),
+                    "{line:?} contains Markdown syntax {syntax:?}"
+                );
+            }
+        }
+    }
+}
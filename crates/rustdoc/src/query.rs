@@ -0,0 +1,790 @@
+use anyhow::{anyhow, Result};
+
+use crate::{
+    convert_rustdoc_to_all_items, convert_rustdoc_to_definitions,
+    convert_rustdoc_to_dyn_compatibility, convert_rustdoc_to_markdown_with_options,
+    convert_rustdoc_to_method_bounds, convert_rustdoc_to_method_index,
+    convert_rustdoc_to_reexports, convert_rustdoc_to_references, ConversionCache,
+    RustdocConversionOptions, RustdocItem, RustdocItemKind, RustdocItemOrder, RustdocPageLocation,
+    RustdocReexport,
+};
+
+/// The crates of the standard library, which are documented on
+/// `doc.rust-lang.org` rather than `docs.rs`.
+pub const STANDARD_LIBRARY_CRATES: [&str; 4] = ["std", "core", "alloc", "proc_macro"];
+
+/// The release channel that the docs for the standard library are fetched
+/// from by default, and when those from another channel can't be fetched.
+pub const DEFAULT_STD_CHANNEL: &str = "stable";
+
+/// What a [`DocQuery`] fetches from the docs page of its item.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub enum DocQueryMode {
+    /// The full docs, converted to Markdown.
+    #[default]
+    Docs,
+    /// Only the definitions on the page, without any prose.
+    Definitions,
+    /// The trait bounds of the method with the given name on the page.
+    Bounds(String),
+    /// Whether the trait is dyn compatible (object safe).
+    DynCompatibility,
+    /// The signatures of the methods of the type.
+    Methods,
+    /// The full paths of the types and traits that the item directly
+    /// references, one per line.
+    References,
+    /// The paths of every public item in the crate, grouped by their kind,
+    /// from its "all items" page.
+    ///
+    /// The item path is ignored, as the page is only at the crate root.
+    AllItems,
+}
+
+/// A request for the docs of a crate, or of an item within it.
+#[derive(Debug, Default, Clone, Hash)]
+pub struct DocQuery {
+    /// The name of the crate, preferably as it appears in its item paths
+    /// (e.g., `serde_json`), rather than as it was published (`serde-json`).
+    pub crate_name: String,
+    /// The version of the crate to fetch the docs for (e.g., `1.0.0-alpha.1`).
+    ///
+    /// When `None`, the docs for the latest version are fetched.
+    pub version: Option<semver::Version>,
+    /// The path to the item within the crate, not including the crate name.
+    pub item_path: Vec<String>,
+    /// The target triple to fetch the docs for (e.g., `x86_64-pc-windows-msvc`).
+    ///
+    /// When `None`, the docs for the default target are fetched.
+    pub target: Option<String>,
+    /// What to fetch from the docs page, e.g., only the signatures of the
+    /// methods of a type.
+    pub mode: DocQueryMode,
+    /// Whether to annotate items with the version they were stabilized in.
+    pub include_since: bool,
+    /// Whether to summarize the documented layout of the type.
+    pub include_layout: bool,
+    /// The kinds of items to list on a module page.
+    ///
+    /// When empty, the items of every kind are listed.
+    pub item_kinds: Vec<RustdocItemKind>,
+    /// Whether to list the types that implement the trait, in place of the
+    /// impls in its "Implementors" sections.
+    pub list_implementors: bool,
+    /// The number of implementors to list before noting how many more there
+    /// are, when listing them.
+    ///
+    /// When `None`, every implementor is listed.
+    pub max_implementors: Option<usize>,
+    /// Whether to leave out the "See also" list of the other items that the
+    /// page links to.
+    pub omit_see_also: bool,
+    /// The order to list the items on a module page in.
+    pub item_order: RustdocItemOrder,
+    /// Whether to list the auto trait and blanket impls of a type in their
+    /// own groups, rather than together under one heading.
+    pub expand_auto_impls: bool,
+}
+
+impl DocQuery {
+    /// Returns the query for the full docs of the latest version of the given
+    /// item in the given crate, for the default target.
+    pub fn new(crate_name: impl Into<String>, item_path: Vec<String>) -> Self {
+        Self {
+            crate_name: crate_name.into(),
+            item_path,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the version of the crate to fetch the docs for.
+    pub fn with_version(mut self, version: Option<semver::Version>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the target triple to fetch the docs for.
+    pub fn with_target(mut self, target: Option<String>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Sets what to fetch from the docs page.
+    pub fn with_mode(mut self, mode: DocQueryMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Converts the rustdoc HTML for this query to Markdown.
+    pub fn convert(&self, html: &[u8]) -> Result<String> {
+        self.convert_at(
+            html,
+            RustdocPageLocation {
+                crate_name: normalize_crate_name(&self.crate_name),
+                module_path: self.item_path.clone(),
+            },
+        )
+    }
+
+    /// Converts the given rustdoc HTML for this query, which was found at the
+    /// given URL after following any redirects.
+    ///
+    /// The relative links on the page are resolved against that URL, which
+    /// may differ from the one the page was requested at, e.g., for crates
+    /// whose library has a different name than the package.
+    pub fn convert_fetched(&self, html: &[u8], url: &str) -> Result<String> {
+        match RustdocPageLocation::from_url(url) {
+            Some(location) => self.convert_at(html, location),
+            None => self.convert(html),
+        }
+    }
+
+    /// Returns the query for the parent module of this query's item, or
+    /// `None` for the crate root.
+    pub(crate) fn parent_query(&self) -> Option<DocQuery> {
+        let mut parent_query = self.clone();
+        parent_query.item_path.pop()?;
+        Some(parent_query)
+    }
+
+    /// Returns the re-export that this query's item refers to, as listed on
+    /// the given page of its parent module.
+    pub(crate) fn find_reexport(&self, parent_html: &[u8]) -> Result<Option<RustdocReexport>> {
+        let Some(item_name) = self.item_path.last() else {
+            return Ok(None);
+        };
+
+        Ok(convert_rustdoc_to_reexports(parent_html)?
+            .into_iter()
+            .find(|reexport| reexport.name == *item_name))
+    }
+
+    /// Converts the page of the item that this query's item re-exports,
+    /// noting which item the docs are for.
+    pub(crate) fn convert_reexport(
+        &self,
+        html: &[u8],
+        reexport: &RustdocReexport,
+    ) -> Result<String> {
+        let docs = match reexport.canonical_location() {
+            Some(location) => self.convert_at(html, location)?,
+            None => self.convert(html)?,
+        };
+        let reexport_path = std::iter::once(&self.crate_name)
+            .chain(&self.item_path)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("::");
+
+        Ok(format!(
+            "{note}{docs}",
+            note = reexport_note(&reexport_path, &reexport.canonical_path)
+        ))
+    }
+
+    /// Returns the item that this query's item refers to, as listed on the
+    /// given page of its parent module, for items whose page is named after
+    /// their kind rather than being a module's `index.html` (e.g.,
+    /// `macro.select.html` for `tokio::select`).
+    ///
+    /// Items that share a name with a module (e.g., `std::vec` and `vec!`)
+    /// are found at the module's page instead, so modules are left out.
+    pub(crate) fn find_item_page(&self, parent_html: &[u8]) -> Result<Option<RustdocItem>> {
+        let Some(item_name) = self.item_path.last() else {
+            return Ok(None);
+        };
+
+        let (_markdown, page_items) =
+            convert_rustdoc_to_markdown_with_options(parent_html, &Default::default())?;
+        Ok(page_items
+            .into_iter()
+            .filter(|page_item| page_item.summary.is_some())
+            .map(|page_item| page_item.item)
+            .find(|item| {
+                item.kind != RustdocItemKind::Mod
+                    && item.path.is_empty()
+                    && *item.name == **item_name
+            }))
+    }
+
+    /// Converts the page of this query's item that was found with
+    /// [`Self::find_item_page`], whose links are relative to its parent
+    /// module.
+    pub(crate) fn convert_item_page(&self, html: &[u8]) -> Result<String> {
+        let mut module_path = self.item_path.clone();
+        module_path.pop();
+        self.convert_at(
+            html,
+            RustdocPageLocation {
+                crate_name: normalize_crate_name(&self.crate_name),
+                module_path,
+            },
+        )
+    }
+
+    /// Converts the given rustdoc HTML for this query as found at the given
+    /// location, reusing the Markdown from an earlier conversion of the same
+    /// HTML for the same query.
+    fn convert_at(&self, html: &[u8], page_location: RustdocPageLocation) -> Result<String> {
+        ConversionCache::global().get_or_convert(html, &(self, &page_location), || {
+            self.convert_uncached(html, page_location.clone())
+        })
+    }
+
+    fn convert_uncached(&self, html: &[u8], page_location: RustdocPageLocation) -> Result<String> {
+        match &self.mode {
+            DocQueryMode::AllItems => {
+                let crate_name = normalize_crate_name(&self.crate_name);
+                let categories = convert_rustdoc_to_all_items(html)?;
+                let item_count = categories
+                    .iter()
+                    .map(|(_heading, items)| items.len())
+                    .sum::<usize>();
+                if item_count == 0 {
+                    return Ok(format!("No public items of `{crate_name}` were found."));
+                }
+
+                let mut text = format!(
+                    "# All items in `{crate_name}`\n\n{item_count} public {items}.",
+                    items = if item_count == 1 { "item" } else { "items" }
+                );
+                for (heading, items) in categories {
+                    text.push_str(&format!("\n\n## {heading}\n"));
+                    for item in items {
+                        text.push_str(&format!("\n- `{crate_name}::{item}`"));
+                    }
+                }
+                Ok(text)
+            }
+            DocQueryMode::Bounds(method_name) => {
+                let bounds = convert_rustdoc_to_method_bounds(html, method_name)?
+                    .ok_or_else(|| anyhow!("no method named `{method_name}` found"))?;
+                if bounds.is_empty() {
+                    return Ok(format!("No bounds are required to call `{method_name}`."));
+                }
+
+                let mut text = format!("Bounds required to call `{method_name}`:\n");
+                for bound in bounds {
+                    text.push_str(&format!("\n- `{bound}`"));
+                }
+                Ok(text)
+            }
+            DocQueryMode::DynCompatibility => {
+                let trait_name = self.item_path.last().unwrap_or(&self.crate_name);
+                let dyn_compatibility = convert_rustdoc_to_dyn_compatibility(html)?
+                    .ok_or_else(|| anyhow!("`{trait_name}` is not a trait"))?;
+                if dyn_compatibility.is_dyn_compatible {
+                    return Ok(format!(
+                        "`{trait_name}` is dyn compatible (object safe), so it can be used as \
+                        `dyn {trait_name}`."
+                    ));
+                }
+
+                let mut text = format!(
+                    "`{trait_name}` is not dyn compatible (object safe), so it can't be used as \
+                    `dyn {trait_name}`."
+                );
+                if !dyn_compatibility.reasons.is_empty() {
+                    text.push_str("\n\nLikely reasons:\n");
+                    for reason in dyn_compatibility.reasons {
+                        text.push_str(&format!("\n- {reason}"));
+                    }
+                }
+                Ok(text)
+            }
+            DocQueryMode::Methods => {
+                let type_name = self.item_path.last().unwrap_or(&self.crate_name);
+                let method_index = convert_rustdoc_to_method_index(html)?;
+                if method_index.is_empty() {
+                    return Ok(format!("No methods of `{type_name}` were found."));
+                }
+
+                let mut text = format!("# Methods of `{type_name}`");
+                if !method_index.associated_types.is_empty() {
+                    text.push_str("\n\n## Associated types\n");
+                    for associated_type in method_index.associated_types {
+                        text.push_str(&format!("\n- `{associated_type}`"));
+                    }
+                }
+                if !method_index.associated_consts.is_empty() {
+                    text.push_str("\n\n## Associated constants\n");
+                    for associated_const in method_index.associated_consts {
+                        text.push_str(&format!("\n- `{associated_const}`"));
+                    }
+                }
+                if !method_index.inherent_methods.is_empty() {
+                    text.push_str("\n\n## Inherent methods\n");
+                    for method in method_index.inherent_methods {
+                        text.push_str(&format!("\n- `{method}`"));
+                    }
+                }
+                if !method_index.trait_impls.is_empty() {
+                    text.push_str("\n\n## Trait methods");
+                    for (header, methods) in method_index.trait_impls {
+                        text.push_str(&format!("\n\n### `{header}`\n"));
+                        for method in methods {
+                            text.push_str(&format!("\n- `{method}`"));
+                        }
+                    }
+                }
+                Ok(text)
+            }
+            DocQueryMode::References => Ok(convert_rustdoc_to_references(html)?.join("\n")),
+            DocQueryMode::Definitions => convert_rustdoc_to_definitions(html),
+            DocQueryMode::Docs => {
+                let options = RustdocConversionOptions {
+                    include_since: self.include_since,
+                    include_layout: self.include_layout,
+                    page_location: Some(page_location),
+                    item_kinds: self.item_kinds.clone(),
+                    list_implementors: self.list_implementors,
+                    max_implementors: self.max_implementors,
+                    omit_see_also: self.omit_see_also,
+                    item_order: self.item_order,
+                    expand_auto_impls: self.expand_auto_impls,
+                };
+                let (markdown, _items) = convert_rustdoc_to_markdown_with_options(html, &options)?;
+                Ok(markdown)
+            }
+        }
+    }
+
+    /// Returns the path of the crate or item that the query is for, e.g.,
+    /// `tokio::sync::Mutex`.
+    pub fn display_path(&self) -> String {
+        std::iter::once(&self.crate_name)
+            .chain(&self.item_path)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    /// Returns whether the query is for one of the [`STANDARD_LIBRARY_CRATES`].
+    pub fn is_standard_library(&self) -> bool {
+        STANDARD_LIBRARY_CRATES.contains(&self.crate_name.as_str())
+    }
+
+    /// Returns the version of the crate to fetch the docs for.
+    ///
+    /// For the standard library, this is the release channel (e.g., `stable`)
+    /// or, when pinned, the Rust version.
+    pub fn docs_version(&self, std_channel: &str) -> String {
+        match (self.is_standard_library(), self.version.as_ref()) {
+            (true, Some(version)) => version.to_string(),
+            (true, None) => std_channel.to_string(),
+            (false, _) => self.docs_dot_rs_version(),
+        }
+    }
+
+    /// Returns the URL to the docs for this query, which are on
+    /// `doc.rust-lang.org` for the standard library and on `docs.rs`
+    /// otherwise.
+    pub fn docs_url(&self, std_channel: &str) -> String {
+        if !self.is_standard_library() {
+            return self.docs_dot_rs_url();
+        }
+
+        let mut path = vec![self.docs_version(std_channel), self.crate_name.clone()];
+        if self.mode == DocQueryMode::AllItems {
+            return format!("https://doc.rust-lang.org/{}/all.html", path.join("/"));
+        }
+        path.extend(self.item_path.iter().cloned());
+        format!("https://doc.rust-lang.org/{}/index.html", path.join("/"))
+    }
+
+    /// Returns the version of the crate to fetch from `docs.rs`.
+    pub fn docs_dot_rs_version(&self) -> String {
+        match self.version.as_ref() {
+            Some(version) => {
+                // Versions that only differ in their build metadata are the same
+                // release, so docs.rs doesn't include it in its URLs.
+                let mut version = version.clone();
+                version.build = semver::BuildMetadata::EMPTY;
+                version.to_string()
+            }
+            None => "latest".to_string(),
+        }
+    }
+
+    /// Returns the URL to the docs for this query on `docs.rs`.
+    ///
+    /// `docs.rs` finds the package by its name with either hyphens or
+    /// underscores, but the path to the docs within it always uses the
+    /// underscores, e.g., `https://docs.rs/serde-json/latest/serde_json/`.
+    pub fn docs_dot_rs_url(&self) -> String {
+        let crate_name = &self.crate_name;
+        let version = self.docs_dot_rs_version();
+        let target = self
+            .target
+            .as_ref()
+            .map(|target| format!("{target}/"))
+            .unwrap_or_default();
+        format!(
+            "https://docs.rs/{crate_name}/{version}/{target}{lib_name}/{module_path}",
+            lib_name = normalize_crate_name(crate_name),
+            module_path = if self.mode == DocQueryMode::AllItems {
+                "all.html".to_string()
+            } else {
+                self.item_path.join("/")
+            }
+        )
+    }
+}
+
+/// Returns the note to put before the docs of a re-exported item, which are
+/// the docs of the item at its canonical path.
+pub(crate) fn reexport_note(reexport_path: &str, canonical_path: &str) -> String {
+    format!(
+        "> **Note:** `{reexport_path}` is a re-export of `{canonical_path}`, so these are the \
+        docs for `{canonical_path}`.\n\n"
+    )
+}
+
+/// Returns the given crate name as it appears in item paths and in the paths
+/// of its docs, in which hyphens are replaced with underscores (e.g.,
+/// `serde_json` for `serde-json`).
+pub fn normalize_crate_name(crate_name: &str) -> String {
+    crate_name.replace('-', "_")
+}
+
+/// Splits a crate name with an optional version, e.g., `serde@1.0.0-alpha.1`,
+/// into its name and version.
+pub fn parse_crate_version(spec: &str) -> Result<(&str, Option<semver::Version>)> {
+    let Some((crate_name, version)) = spec.split_once('@') else {
+        return Ok((spec, None));
+    };
+
+    let version = semver::Version::parse(version)
+        .map_err(|err| anyhow!("invalid version {version:?} for {crate_name}: {err}"))?;
+    Ok((crate_name, Some(version)))
+}
+
+/// Parses the release channel to fetch the docs for the standard library from,
+/// which is `stable`, `beta`, `nightly`, or a Rust version like `1.75.0`.
+pub fn parse_std_channel(channel: &str) -> Result<String> {
+    match channel {
+        "stable" | "beta" | "nightly" => Ok(channel.to_string()),
+        _ => semver::Version::parse(channel)
+            .map(|version| version.to_string())
+            .map_err(|_| {
+                anyhow!(
+                    "invalid channel {channel:?}: expected stable, beta, nightly, or a Rust \
+                    version like 1.75.0"
+                )
+            }),
+    }
+}
+
+/// An item path as it was typed, split into the segments that its docs are
+/// looked up by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemPath {
+    /// The segments of the path without their generic arguments, e.g.,
+    /// `["std", "vec", "Vec", "new"]` for `std::vec::Vec::<T>::new`.
+    pub segments: Vec<String>,
+    /// The segments of the path as they were typed, with their generic
+    /// arguments, e.g., `["std", "vec", "Vec::<T>", "new"]`.
+    pub display_segments: Vec<String>,
+}
+
+impl ItemPath {
+    /// Returns whether any generic arguments were stripped from the path.
+    pub fn has_generic_args(&self) -> bool {
+        self.segments != self.display_segments
+    }
+}
+
+/// Splits the given item path into its segments, stripping the generic
+/// arguments that are often pasted along with it, e.g., `Vec<String>` or
+/// `HashMap::<K, V>::new`.
+///
+/// The `::` of a turbofish and the `::` within generic arguments (e.g., in
+/// `Vec<std::string::String>`) aren't separators between segments.
+pub fn parse_item_path(path: &str) -> Result<ItemPath> {
+    let mut segments = Vec::new();
+    let mut display_segments = Vec::new();
+    let mut segment = String::new();
+    let mut display_segment = String::new();
+    let mut depth = 0_usize;
+    let mut chars = path.chars().peekable();
+    let mut previous_char = None;
+    while let Some(c) = chars.next() {
+        match c {
+            '<' => depth += 1,
+            // The arrow of a function type (e.g., `Box<dyn Fn() -> u8>`)
+            // doesn't close the generic arguments.
+            '>' if previous_char == Some('-') && depth > 0 => {}
+            '>' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| anyhow!("unmatched `>` in the item path `{path}`"))?;
+            }
+            ':' if depth == 0 && chars.peek() == Some(&':') => {
+                chars.next();
+                previous_char = Some(':');
+                // A turbofish belongs to the segment before it.
+                if chars.peek() == Some(&'<') {
+                    display_segment.push_str("::");
+                    continue;
+                }
+                segments.push(std::mem::take(&mut segment));
+                display_segments.push(std::mem::take(&mut display_segment));
+                continue;
+            }
+            _ if depth == 0 => segment.push(c),
+            _ => {}
+        }
+        display_segment.push(c);
+        previous_char = Some(c);
+    }
+    if depth > 0 {
+        return Err(anyhow!("unclosed `<` in the item path `{path}`"));
+    }
+    segments.push(segment);
+    display_segments.push(display_segment);
+
+    Ok(ItemPath {
+        segments,
+        display_segments,
+    })
+}
+
+/// Splits the given argument into the queries for each of the items that it
+/// lists, separated by commas, e.g., `std::sync::Mutex, std::sync::RwLock`.
+///
+/// The commas within generic arguments (e.g., in `HashMap<K, V>`) and within
+/// the value of any of the given flags (e.g., `--features a,b`) don't separate
+/// queries.
+pub fn split_item_queries<'a>(argument: &'a str, value_flags: &[&str]) -> Vec<&'a str> {
+    let mut queries = Vec::new();
+    let mut query_start = 0;
+    let mut depth = 0_usize;
+    let mut word_start = None;
+    let mut previous_word = "";
+    let mut previous_char = None;
+    for (ix, c) in argument.char_indices() {
+        if c.is_whitespace() {
+            if let Some(word_start) = word_start.take() {
+                previous_word = &argument[word_start..ix];
+            }
+        } else {
+            word_start.get_or_insert(ix);
+            match c {
+                '<' | '(' | '[' => depth += 1,
+                '>' if previous_char == Some('-') => {}
+                '>' | ')' | ']' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 && !value_flags.contains(&previous_word) => {
+                    queries.push(&argument[query_start..ix]);
+                    query_start = ix + c.len_utf8();
+                    word_start = None;
+                    previous_word = "";
+                }
+                _ => {}
+            }
+        }
+        previous_char = Some(c);
+    }
+    queries.push(&argument[query_start..]);
+
+    queries
+        .into_iter()
+        .map(str::trim)
+        .filter(|query| !query.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(crate_spec: &str, item_path: &[&str]) -> DocQuery {
+        let (crate_name, version) = parse_crate_version(crate_spec).unwrap();
+        DocQuery::new(
+            crate_name,
+            item_path.iter().map(ToString::to_string).collect(),
+        )
+        .with_version(version)
+    }
+
+    #[test]
+    fn test_docs_dot_rs_url() {
+        assert_eq!(
+            query("tokio", &["sync"]).docs_dot_rs_url(),
+            "https://docs.rs/tokio/latest/tokio/sync"
+        );
+        assert_eq!(
+            query("tokio@1.38.0", &["sync"]).docs_dot_rs_url(),
+            "https://docs.rs/tokio/1.38.0/tokio/sync"
+        );
+        assert_eq!(
+            query("axum@0.8.0-alpha.1", &[]).docs_dot_rs_url(),
+            "https://docs.rs/axum/0.8.0-alpha.1/axum/"
+        );
+        assert_eq!(
+            query("my_crate@1.0.0-beta.2+build.5", &["de"]).docs_dot_rs_url(),
+            "https://docs.rs/my_crate/1.0.0-beta.2/my_crate/de"
+        );
+    }
+
+    #[test]
+    fn test_hyphenated_crate_names() {
+        assert_eq!(normalize_crate_name("serde-json"), "serde_json");
+        assert_eq!(normalize_crate_name("serde_json"), "serde_json");
+
+        assert_eq!(
+            query("serde-json@1.0.120", &["de"]).docs_dot_rs_url(),
+            "https://docs.rs/serde-json/1.0.120/serde_json/de"
+        );
+        assert_eq!(
+            query("serde_json@1.0.120", &["de"]).docs_dot_rs_url(),
+            "https://docs.rs/serde_json/1.0.120/serde_json/de"
+        );
+
+        // Both forms convert to the same docs, with links to the same items.
+        let html = br#"<section id="main-content"><details class="toggle top-doc" open><div class="docblock"><p>See <a href="struct.Deserializer.html">Deserializer</a>.</p></div></details></section>"#;
+        let docs = query("serde-json", &["de"]).convert(html).unwrap();
+        assert_eq!(
+            docs,
+            "See [Deserializer](serde_json::de::Deserializer).\n\n\
+            ## See also\n\n\
+            - `serde_json::de::Deserializer`"
+        );
+        assert_eq!(query("serde_json", &["de"]).convert(html).unwrap(), docs);
+    }
+
+    #[test]
+    fn test_standard_library_url() {
+        assert_eq!(
+            query("std", &["vec"]).docs_url("stable"),
+            "https://doc.rust-lang.org/stable/std/vec/index.html"
+        );
+        assert_eq!(
+            query("core", &[]).docs_url("nightly"),
+            "https://doc.rust-lang.org/nightly/core/index.html"
+        );
+        assert_eq!(
+            query("alloc@1.78.0", &["collections"]).docs_url("stable"),
+            "https://doc.rust-lang.org/1.78.0/alloc/collections/index.html"
+        );
+        assert_eq!(
+            query("tokio", &["sync"]).docs_url("stable"),
+            "https://docs.rs/tokio/latest/tokio/sync"
+        );
+    }
+
+    #[test]
+    fn test_parse_std_channel() {
+        assert_eq!(parse_std_channel("nightly").unwrap(), "nightly");
+        assert_eq!(parse_std_channel("1.75.0").unwrap(), "1.75.0");
+        assert!(parse_std_channel("1.75").is_err());
+        assert!(parse_std_channel("unstable").is_err());
+    }
+
+    #[test]
+    fn test_all_items() {
+        let mut std_query = query("std", &[]);
+        std_query.mode = DocQueryMode::AllItems;
+        assert_eq!(
+            std_query.docs_url("stable"),
+            "https://doc.rust-lang.org/stable/std/all.html"
+        );
+        let mut serde_json_query = query("serde-json@1.0.120", &[]);
+        serde_json_query.mode = DocQueryMode::AllItems;
+        assert_eq!(
+            serde_json_query.docs_url("stable"),
+            "https://docs.rs/serde-json/1.0.120/serde_json/all.html"
+        );
+
+        let html = br#"<section id="main-content"><h1>List of all items</h1><h3 id="structs">Structs</h3><ul class="all-items"><li><a href="de/struct.Deserializer.html">de::Deserializer</a></li><li><a href="struct.Map.html">Map</a></li></ul><h3 id="functions">Functions</h3><ul class="all-items"><li><a href="fn.from_str.html">from_str</a></li></ul></section>"#;
+        assert_eq!(
+            serde_json_query.convert(html).unwrap(),
+            "# All items in `serde_json`\n\n\
+            3 public items.\n\n\
+            ## Structs\n\n\
+            - `serde_json::de::Deserializer`\n\
+            - `serde_json::Map`\n\n\
+            ## Functions\n\n\
+            - `serde_json::from_str`"
+        );
+    }
+
+    #[test]
+    fn test_split_item_queries() {
+        let value_flags = ["--features", "--kind"];
+        assert_eq!(
+            split_item_queries("std::sync::Mutex, std::sync::RwLock", &value_flags),
+            vec!["std::sync::Mutex", "std::sync::RwLock"]
+        );
+        assert_eq!(
+            split_item_queries("tokio::spawn,tokio::select!,", &value_flags),
+            vec!["tokio::spawn", "tokio::select!"]
+        );
+        assert_eq!(
+            split_item_queries(
+                "std::collections::HashMap<K, V>::new, std::boxed::Box<dyn Fn(u8, u8) -> u8>",
+                &value_flags
+            ),
+            vec![
+                "std::collections::HashMap<K, V>::new",
+                "std::boxed::Box<dyn Fn(u8, u8) -> u8>"
+            ]
+        );
+        assert_eq!(
+            split_item_queries("tokio --kind fn,macro", &value_flags),
+            vec!["tokio --kind fn,macro"]
+        );
+        assert_eq!(
+            split_item_queries(
+                "--features rt,macros tokio::spawn, tokio::time",
+                &value_flags
+            ),
+            vec!["--features rt,macros tokio::spawn", "tokio::time"]
+        );
+        assert_eq!(
+            split_item_queries("serde_json", &value_flags),
+            vec!["serde_json"]
+        );
+    }
+
+    #[test]
+    fn test_parse_item_path() {
+        let path = parse_item_path("tokio::sync::Mutex").unwrap();
+        assert_eq!(path.segments, vec!["tokio", "sync", "Mutex"]);
+        assert_eq!(path.display_segments, path.segments);
+        assert!(!path.has_generic_args());
+
+        let path = parse_item_path("alloc::vec::Vec<String>").unwrap();
+        assert_eq!(path.segments, vec!["alloc", "vec", "Vec"]);
+        assert_eq!(path.display_segments, vec!["alloc", "vec", "Vec<String>"]);
+        assert!(path.has_generic_args());
+
+        // The `::` of a turbofish and within the arguments aren't separators.
+        let path = parse_item_path("std::collections::HashMap::<K,V>::new").unwrap();
+        assert_eq!(path.segments, vec!["std", "collections", "HashMap", "new"]);
+        assert_eq!(
+            path.display_segments,
+            vec!["std", "collections", "HashMap::<K,V>", "new"]
+        );
+        let path = parse_item_path("std::vec::Vec<std::string::String>::push").unwrap();
+        assert_eq!(path.segments, vec!["std", "vec", "Vec", "push"]);
+        assert_eq!(
+            parse_item_path("std::boxed::Box<dyn Fn() -> Option<u8>>")
+                .unwrap()
+                .segments,
+            vec!["std", "boxed", "Box"]
+        );
+        assert_eq!(
+            parse_item_path("serde@1.0.0").unwrap().segments,
+            vec!["serde@1.0.0"]
+        );
+
+        assert!(parse_item_path("std::vec::Vec<String").is_err());
+        assert!(parse_item_path("std::vec::Vec>").is_err());
+    }
+
+    #[test]
+    fn test_invalid_version() {
+        assert!(parse_crate_version("tokio@1.x").is_err());
+        assert!(parse_crate_version("tokio@").is_err());
+    }
+}
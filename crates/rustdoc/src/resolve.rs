@@ -0,0 +1,491 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use fs::Fs;
+use http::HttpClientWithUrl;
+
+use crate::crawler::{
+    BuildFailed, CargoDocFeatures, ClientError, DocsDotRsProvider, LocalProvider, RateLimited,
+    RustdocSource,
+};
+use crate::{
+    is_docs_not_found, DocProvider, DocQuery, DocQueryMode, DocsCache, DocsNotFound, NotFoundCache,
+    RustdocItemOrder, RustdocSettings, RustdocStore,
+};
+
+/// Where to look for the docs for a query, besides `docs.rs`.
+#[derive(Clone)]
+pub struct ResolveDocsOptions {
+    /// The settings that configure how the docs are looked up, e.g., in which
+    /// order the local docs and those on `docs.rs` are tried.
+    pub settings: RustdocSettings,
+    /// The roots of the Cargo workspaces whose `cargo doc` output to look up
+    /// the docs in, in order.
+    pub cargo_workspace_roots: Vec<PathBuf>,
+    /// The features that the `cargo doc` output was built with.
+    pub features: CargoDocFeatures,
+    /// The other providers to look up the docs in, after the `cargo doc`
+    /// output, e.g., those in the [`crate::DocProviderRegistry`].
+    pub extra_providers: Vec<Arc<dyn DocProvider>>,
+    /// The directory to cache the pages fetched from `docs.rs` in.
+    ///
+    /// When `None`, the pages aren't cached.
+    pub cache_dir: Option<PathBuf>,
+    /// The `Cargo.lock`s to look up the version of the crate to fetch from
+    /// `docs.rs` in, in order.
+    ///
+    /// A crate that none of them have is fetched at its latest version.
+    pub lockfile_paths: Vec<PathBuf>,
+}
+
+impl ResolveDocsOptions {
+    /// Returns the options for looking up the docs only in the store and on
+    /// `docs.rs`, as configured by the given settings.
+    pub fn new(settings: RustdocSettings) -> Self {
+        Self {
+            settings,
+            cargo_workspace_roots: Vec::new(),
+            features: CargoDocFeatures::default(),
+            extra_providers: Vec::new(),
+            cache_dir: None,
+            lockfile_paths: Vec::new(),
+        }
+    }
+}
+
+/// The providers that the docs for a query are looked up in, in the order
+/// they are tried.
+pub struct DocsResolver {
+    providers: Vec<Arc<dyn DocProvider>>,
+    docs_dot_rs_provider: Arc<DocsDotRsProvider>,
+    local_cargo_workspace_roots: Vec<PathBuf>,
+    missing_item_hint: Option<String>,
+}
+
+impl DocsResolver {
+    /// Returns the providers to look up the docs for the given query in.
+    ///
+    /// The store is only used for the queries that it can answer, i.e., for
+    /// the full docs of the latest version for the default target.
+    pub fn new(
+        fs: Arc<dyn Fs>,
+        http_client: Arc<HttpClientWithUrl>,
+        store: Option<Arc<RustdocStore>>,
+        query: &DocQuery,
+        options: ResolveDocsOptions,
+    ) -> Self {
+        let settings = options.settings;
+
+        let mut local_providers: Vec<Arc<dyn DocProvider>> = Vec::new();
+        if let Some(store) = store {
+            if Self::is_served_by_store(query) {
+                local_providers.push(store);
+            } else {
+                log::debug!(
+                    "skipping the store for {}: it only has the full docs of the latest version",
+                    query.display_path()
+                );
+            }
+        }
+        // The local docs are for whichever version is in the lockfile, so a
+        // specific version is only fetched from docs.rs.
+        let local_cargo_workspace_roots = if query.version.is_none() {
+            options.cargo_workspace_roots
+        } else {
+            if !options.cargo_workspace_roots.is_empty() {
+                log::debug!(
+                    "skipping the local docs for {}: a specific version was requested",
+                    query.display_path()
+                );
+            }
+            Vec::new()
+        };
+        let mut missing_item_hint = None;
+        for cargo_workspace_root in &local_cargo_workspace_roots {
+            let local_provider = LocalProvider::new(fs.clone(), cargo_workspace_root.clone())
+                .with_features(options.features.clone())
+                .with_parent_module_fallback(settings.local_parent_module_fallback);
+            if !query.item_path.is_empty() && missing_item_hint.is_none() {
+                missing_item_hint = local_provider.missing_item_hint();
+            }
+            local_providers.push(Arc::new(local_provider));
+        }
+        local_providers.extend(options.extra_providers);
+
+        let mut docs_dot_rs_provider = DocsDotRsProvider::new(http_client)
+            .with_max_body_size(settings.max_response_size())
+            .with_timeout(settings.request_timeout())
+            .with_std_channel(settings.std_channel.clone())
+            .with_hosts(settings.docs_hosts.clone())
+            .with_not_found_cache(NotFoundCache::global());
+        if !options.lockfile_paths.is_empty() {
+            docs_dot_rs_provider =
+                docs_dot_rs_provider.with_lockfiles(fs.clone(), options.lockfile_paths);
+        }
+        if let Some(cache_dir) = options.cache_dir {
+            docs_dot_rs_provider = docs_dot_rs_provider
+                .with_cache(DocsCache::new(fs, cache_dir).with_policy(settings.cache_policy()));
+        }
+        let docs_dot_rs_provider = Arc::new(docs_dot_rs_provider);
+        let providers = settings.source_order.arrange(
+            local_providers,
+            vec![docs_dot_rs_provider.clone() as Arc<dyn DocProvider>],
+        );
+
+        Self {
+            providers,
+            docs_dot_rs_provider,
+            local_cargo_workspace_roots,
+            missing_item_hint,
+        }
+    }
+
+    /// Returns whether the store may have the docs for the given query.
+    ///
+    /// The store only indexes the full docs for the default target, without
+    /// any annotations.
+    fn is_served_by_store(query: &DocQuery) -> bool {
+        // Every mode is listed, so that a new one has to say whether the
+        // store has the docs for it.
+        let is_full_docs = match query.mode {
+            DocQueryMode::Docs => true,
+            DocQueryMode::Definitions
+            | DocQueryMode::Bounds(_)
+            | DocQueryMode::DynCompatibility
+            | DocQueryMode::Methods
+            | DocQueryMode::References
+            | DocQueryMode::AllItems => false,
+        };
+        is_full_docs
+            && query.version.is_none()
+            && query.target.is_none()
+            && !query.include_since
+            && !query.include_layout
+            && query.item_kinds.is_empty()
+            && !query.list_implementors
+            && !query.omit_see_also
+            && query.item_order == RustdocItemOrder::Page
+            && !query.expand_auto_impls
+    }
+
+    /// Returns the docs for the given query from the first of the providers
+    /// that has them.
+    pub async fn resolve(&self, query: &DocQuery) -> Result<(RustdocSource, String)> {
+        fetch_docs(&self.providers, query).await
+    }
+
+    /// Returns the provider that fetches the docs from `docs.rs`, which knows
+    /// the URLs that the pages it fetched were found at.
+    pub fn docs_dot_rs_provider(&self) -> &Arc<DocsDotRsProvider> {
+        &self.docs_dot_rs_provider
+    }
+
+    /// Returns the roots of the Cargo workspaces whose `cargo doc` output the
+    /// docs are looked up in.
+    ///
+    /// These are empty for a query for a specific version.
+    pub fn local_cargo_workspace_roots(&self) -> &[PathBuf] {
+        &self.local_cargo_workspace_roots
+    }
+
+    /// Returns a hint for why an item may be missing from the `cargo doc`
+    /// output, e.g., because it is behind a feature that wasn't enabled.
+    pub fn missing_item_hint(&self) -> Option<&str> {
+        self.missing_item_hint.as_deref()
+    }
+}
+
+/// Returns the docs for the given query, looking them up in the store, in the
+/// local `cargo doc` output and in the other providers given in the options,
+/// and on `docs.rs`.
+///
+/// Returns a [`DocsNotFound`] error when none of them have docs for the query.
+pub async fn resolve_docs(
+    fs: Arc<dyn Fs>,
+    http_client: Arc<HttpClientWithUrl>,
+    store: Option<Arc<RustdocStore>>,
+    query: &DocQuery,
+    options: ResolveDocsOptions,
+) -> Result<(RustdocSource, String)> {
+    DocsResolver::new(fs, http_client, store, query, options)
+        .resolve(query)
+        .await
+}
+
+/// Returns the docs for the given query from the first of the given providers
+/// that has them.
+///
+/// A provider that fails, e.g., because `docs.rs` can't be reached, falls
+/// back to the next one. When none of them have the docs and some failed for
+/// another reason than not having them, the error summarizes how each of them
+/// failed.
+pub async fn fetch_docs(
+    providers: &[Arc<dyn DocProvider>],
+    query: &DocQuery,
+) -> Result<(RustdocSource, String)> {
+    let mut outcomes = Vec::new();
+    for provider in providers {
+        match provider.fetch(query).await {
+            Ok(Some(docs)) => {
+                log::debug!(
+                    "found docs for {} in {} ({:?})",
+                    query.display_path(),
+                    provider.name(),
+                    docs.0
+                );
+                for (name, err) in &outcomes {
+                    if let Some(err) = err {
+                        log::info!(
+                            "falling back from {name} for {}: {err:#}",
+                            query.display_path()
+                        );
+                    } else {
+                        log::debug!("{name} has no docs for {}", query.display_path());
+                    }
+                }
+                return Ok(docs);
+            }
+            Ok(None) => outcomes.push((provider.name(), None)),
+            Err(err) => outcomes.push((provider.name(), Some(err))),
+        }
+    }
+
+    for (name, err) in &outcomes {
+        log::debug!(
+            "no docs for crate {}, item {:?} in {name}: {}",
+            query.crate_name,
+            query.item_path.join("::"),
+            err.as_ref().map_or("not found", error_category)
+        );
+    }
+
+    let has_failures = outcomes
+        .iter()
+        .any(|(_, err)| err.as_ref().map_or(false, |err| !is_docs_not_found(err)));
+    if has_failures && outcomes.len() > 1 {
+        return Err(failures_summary(outcomes, query));
+    }
+
+    // The first of the errors for docs that weren't found says the most about
+    // why, e.g., that docs.rs has no docs for the requested version.
+    let err = outcomes
+        .into_iter()
+        .find_map(|(_, err)| err)
+        .unwrap_or_else(|| {
+            anyhow::Error::new(DocsNotFound {
+                crate_name: query.crate_name.clone(),
+            })
+        });
+    Err(with_platform_hint(err, query))
+}
+
+/// Returns the kind of failure that the given error is for, e.g., "rate
+/// limited", for logging why a lookup failed.
+fn error_category(err: &anyhow::Error) -> &'static str {
+    if is_docs_not_found(err) {
+        "not found"
+    } else if err.is::<RateLimited>() {
+        "rate limited"
+    } else if err.is::<BuildFailed>() {
+        "build failed"
+    } else if err.is::<ClientError>() {
+        "client error"
+    } else {
+        "other"
+    }
+}
+
+/// Returns an error that summarizes why each of the providers didn't return
+/// docs for the given query, e.g., "not found in the local docs; docs.rs
+/// failed: …".
+fn failures_summary(
+    outcomes: Vec<(String, Option<anyhow::Error>)>,
+    query: &DocQuery,
+) -> anyhow::Error {
+    let summary = outcomes
+        .into_iter()
+        .map(|(name, err)| match err {
+            Some(err) if !is_docs_not_found(&err) => format!("{name} failed: {err:#}"),
+            _ => format!("not found in {name}"),
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    anyhow!(
+        "failed to fetch docs for {}: {summary}",
+        query.display_path()
+    )
+}
+
+/// Adds a hint to the given error that the item may be platform-specific, if
+/// the docs for it weren't found.
+///
+/// Items behind a `#[cfg]` for another platform are missing from the docs
+/// built for the default target, which otherwise surfaces as a confusing
+/// "not found" error. Other errors, e.g., those of the transport, are returned
+/// as is.
+fn with_platform_hint(err: anyhow::Error, query: &DocQuery) -> anyhow::Error {
+    if query.item_path.is_empty() || query.target.is_some() || !is_docs_not_found(&err) {
+        return err;
+    }
+
+    err.context(format!(
+        "failed to fetch docs for {crate_name}::{item_path}. The item may only be \
+        available on some platforms (e.g., behind `#[cfg(unix)]`); try fetching the \
+        docs for a specific target with `--target <triple>`",
+        crate_name = query.crate_name,
+        item_path = query.item_path.join("::")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use futures::executor::block_on;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    struct FakeProvider(Option<&'static str>);
+
+    #[async_trait]
+    impl DocProvider for FakeProvider {
+        async fn fetch(&self, _query: &DocQuery) -> Result<Option<(RustdocSource, String)>> {
+            Ok(self
+                .0
+                .map(|markdown| (RustdocSource::Local, markdown.to_string())))
+        }
+    }
+
+    struct NotFoundProvider;
+
+    #[async_trait]
+    impl DocProvider for NotFoundProvider {
+        async fn fetch(&self, _query: &DocQuery) -> Result<Option<(RustdocSource, String)>> {
+            Err(anyhow::Error::new(ClientError {
+                status: 404,
+                response: String::new(),
+            }))
+        }
+    }
+
+    struct FailingProvider(&'static str);
+
+    #[async_trait]
+    impl DocProvider for FailingProvider {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+
+        async fn fetch(&self, _query: &DocQuery) -> Result<Option<(RustdocSource, String)>> {
+            Err(anyhow!("error sending request"))
+        }
+    }
+
+    #[test]
+    fn test_fetch_docs() {
+        let providers: Vec<Arc<dyn DocProvider>> = vec![
+            Arc::new(FakeProvider(None)),
+            Arc::new(FakeProvider(Some("# first"))),
+            Arc::new(FakeProvider(Some("# second"))),
+        ];
+        let query = DocQuery::new("foo", vec!["Bar".to_string()]);
+        let (source, markdown) = block_on(fetch_docs(&providers, &query)).unwrap();
+        assert_eq!(source, RustdocSource::Local);
+        assert_eq!(markdown, "# first");
+
+        let err = block_on(fetch_docs(&providers[..1], &query)).unwrap_err();
+        assert!(is_docs_not_found(&err));
+        assert!(err
+            .to_string()
+            .starts_with("failed to fetch docs for foo::Bar"));
+
+        // A missing crate root isn't platform-specific.
+        let query = DocQuery::new("foo", Vec::new());
+        let err = block_on(fetch_docs(&providers[..1], &query)).unwrap_err();
+        assert_eq!(err.chain().count(), 1);
+    }
+
+    #[test]
+    fn test_fetch_docs_fallback() {
+        let query = DocQuery::new("foo", vec!["Bar".to_string()]);
+
+        // A provider that fails falls back to the next one.
+        let providers: Vec<Arc<dyn DocProvider>> = vec![
+            Arc::new(FailingProvider("docs.rs")),
+            Arc::new(FakeProvider(Some("# local"))),
+        ];
+        let (_source, markdown) = block_on(fetch_docs(&providers, &query)).unwrap();
+        assert_eq!(markdown, "# local");
+
+        // When every provider fails, the error says how each of them did.
+        let providers: Vec<Arc<dyn DocProvider>> = vec![
+            Arc::new(FakeProvider(None)),
+            Arc::new(FailingProvider("docs.rs")),
+        ];
+        let err = block_on(fetch_docs(&providers, &query)).unwrap_err();
+        assert!(!is_docs_not_found(&err));
+        assert_eq!(
+            err.to_string(),
+            "failed to fetch docs for foo::Bar: not found in another provider; docs.rs \
+            failed: error sending request"
+        );
+
+        // A sole provider's error is returned as is, without suggesting
+        // another target, as the item may well exist.
+        let err = block_on(fetch_docs(&providers[1..], &query)).unwrap_err();
+        assert_eq!(err.chain().count(), 1);
+        assert_eq!(format!("{err:#}"), "error sending request");
+
+        // Docs that weren't found may be for another target.
+        let providers: Vec<Arc<dyn DocProvider>> = vec![Arc::new(NotFoundProvider)];
+        let err = block_on(fetch_docs(&providers, &query)).unwrap_err();
+        assert!(is_docs_not_found(&err));
+        assert_eq!(
+            err.to_string(),
+            "failed to fetch docs for foo::Bar. The item may only be available on some \
+            platforms (e.g., behind `#[cfg(unix)]`); try fetching the docs for a specific \
+            target with `--target <triple>`"
+        );
+
+        // Unless the docs were already for a specific target.
+        let query = query.with_target(Some("x86_64-pc-windows-msvc".to_string()));
+        let err = block_on(fetch_docs(&providers, &query)).unwrap_err();
+        assert_eq!(err.chain().count(), 1);
+    }
+
+    #[test]
+    fn test_is_served_by_store() {
+        let query = DocQuery::new("foo", vec!["Bar".to_string()]);
+        assert!(DocsResolver::is_served_by_store(&query));
+        assert!(!DocsResolver::is_served_by_store(
+            &query.clone().with_mode(DocQueryMode::References)
+        ));
+        assert!(!DocsResolver::is_served_by_store(
+            &query.with_target(Some("x86_64-pc-windows-msvc".to_string()))
+        ));
+    }
+
+    #[test]
+    fn test_error_category() {
+        let not_found = anyhow::Error::new(ClientError {
+            status: 404,
+            response: String::new(),
+        });
+        assert_eq!(error_category(&not_found), "not found");
+        let client_error = anyhow::Error::new(ClientError {
+            status: 403,
+            response: String::new(),
+        });
+        assert_eq!(error_category(&client_error), "client error");
+        let rate_limited =
+            anyhow::Error::new(RateLimited { retry_after: None }).context("failed to fetch foo");
+        assert_eq!(error_category(&rate_limited), "rate limited");
+        let build_failed = anyhow::Error::new(BuildFailed {
+            crate_name: "foo".to_string(),
+            version: "1.0.0".to_string(),
+        });
+        assert_eq!(error_category(&build_failed), "build failed");
+        assert_eq!(error_category(&anyhow!("error sending request")), "other");
+    }
+}
@@ -1,8 +1,45 @@
+mod cache;
+mod conversion_cache;
 pub mod crawler;
+mod dedup;
+mod dependencies;
+mod doc_provider;
 mod item;
+mod not_found_cache;
+mod persisted_index;
+mod plain_text;
+mod query;
+mod resolve;
+mod rustdoc_json;
+mod rustdoc_settings;
+mod sections;
 mod store;
+mod summary;
 mod to_markdown;
+mod workspace;
 
+pub use crate::cache::*;
+pub use crate::conversion_cache::*;
+pub use crate::dedup::*;
+pub use crate::dependencies::*;
+pub use crate::doc_provider::*;
 pub use crate::item::*;
+pub use crate::not_found_cache::*;
+pub use crate::plain_text::*;
+pub use crate::query::*;
+pub use crate::resolve::*;
+pub use crate::rustdoc_json::*;
+pub use crate::rustdoc_settings::*;
+pub use crate::sections::*;
 pub use crate::store::*;
-pub use crate::to_markdown::convert_rustdoc_to_markdown;
+pub use crate::summary::*;
+pub use crate::to_markdown::{
+    convert_docs_rs_features, convert_rustdoc_to_all_items, convert_rustdoc_to_definitions,
+    convert_rustdoc_to_dyn_compatibility, convert_rustdoc_to_markdown,
+    convert_rustdoc_to_markdown_with_options, convert_rustdoc_to_method_bounds,
+    convert_rustdoc_to_method_index, convert_rustdoc_to_reexports, convert_rustdoc_to_references,
+    convert_rustdoc_to_since, convert_rustdoc_to_version, DynCompatibility, MethodIndex,
+    RustdocConversionOptions, RustdocHtmlFormat, RustdocItemOrder, RustdocPageLocation,
+    RustdocReexport, AUTO_AND_BLANKET_IMPLS_HEADING,
+};
+pub use crate::workspace::*;
@@ -0,0 +1,2126 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use collections::{HashMap, HashSet, VecDeque};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
+use strum::IntoEnumIterator;
+
+use crate::crawler::CrateDocs;
+use crate::{RustdocItem, RustdocItemKind, RustdocItemOrder, AUTO_AND_BLANKET_IMPLS_HEADING};
+
+/// Converts the JSON output of rustdoc for the given crate (as built with
+/// `cargo +nightly rustdoc -- -Z unstable-options --output-format json`) into
+/// the Markdown docs for each of its items.
+///
+/// The Markdown is laid out like the Markdown converted from the crate's HTML
+/// docs, but it is built from the typed items, so the signatures, item kinds,
+/// and links don't depend on how the version of rustdoc renders its pages.
+///
+/// The JSON format is unstable, so the items are read loosely: the parts of
+/// an item that aren't recognized are left out of its docs, rather than
+/// failing the conversion of the whole crate.
+pub fn convert_rustdoc_json(json: &str, crate_name: &str) -> Result<CrateDocs> {
+    convert_rustdoc_json_with_order(json, crate_name, RustdocItemOrder::Page)
+}
+
+/// Converts the JSON output of rustdoc for the given crate, listing the items
+/// of each module in the given order.
+///
+/// Unlike the HTML docs, the JSON has the items in the order that each module
+/// declares them in.
+pub fn convert_rustdoc_json_with_order(
+    json: &str,
+    crate_name: &str,
+    item_order: RustdocItemOrder,
+) -> Result<CrateDocs> {
+    let krate: RustdocJson =
+        serde_json::from_str(json).context("failed to parse the rustdoc JSON")?;
+    JsonConverter {
+        krate: &krate,
+        crate_name,
+        item_order,
+        locations: IndexMap::new(),
+    }
+    .convert()
+}
+
+#[derive(Deserialize)]
+struct RustdocJson {
+    root: Value,
+    index: HashMap<String, JsonItem>,
+    /// The paths of the items that the crate refers to, including the items
+    /// in other crates, keyed by their IDs.
+    #[serde(default)]
+    paths: HashMap<String, JsonItemSummary>,
+}
+
+#[derive(Deserialize)]
+struct JsonItem {
+    /// The crate that the item is from, where the documented crate is `0`.
+    #[serde(default)]
+    crate_id: u32,
+    name: Option<String>,
+    #[serde(default)]
+    visibility: Value,
+    docs: Option<String>,
+    /// The items that the intra-doc links in the docs resolve to, keyed by
+    /// their destinations as written in the docs (e.g., "`Mutex`").
+    #[serde(default)]
+    links: HashMap<String, Value>,
+    deprecation: Option<JsonDeprecation>,
+    inner: Value,
+}
+
+#[derive(Deserialize)]
+struct JsonDeprecation {
+    since: Option<String>,
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JsonItemSummary {
+    /// The full path of the item, starting with its crate name.
+    path: Vec<String>,
+}
+
+static NULL: Value = Value::Null;
+
+impl JsonItem {
+    /// Returns the kind of the item (e.g., `struct`), along with the details
+    /// for that kind.
+    fn inner(&self) -> Option<(&str, &Value)> {
+        tagged(&self.inner)
+    }
+
+    fn kind(&self) -> Option<RustdocItemKind> {
+        match self.inner()? {
+            ("module", _) => Some(RustdocItemKind::Mod),
+            ("struct", _) => Some(RustdocItemKind::Struct),
+            ("enum", _) => Some(RustdocItemKind::Enum),
+            ("trait", _) => Some(RustdocItemKind::Trait),
+            ("function", _) => Some(RustdocItemKind::Function),
+            ("type_alias" | "typedef", _) => Some(RustdocItemKind::TypeAlias),
+            ("constant", _) => Some(RustdocItemKind::Constant),
+            ("macro", _) => Some(RustdocItemKind::Macro),
+            ("proc_macro", proc_macro) => match proc_macro["kind"].as_str() {
+                Some("derive") => Some(RustdocItemKind::DeriveMacro),
+                Some("attr") => Some(RustdocItemKind::AttributeMacro),
+                _ => Some(RustdocItemKind::Macro),
+            },
+            _ => None,
+        }
+    }
+
+    fn is_public(&self) -> bool {
+        self.visibility == "public"
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the keyword that the item's declaration starts with, e.g.,
+    /// `pub ` for a public item.
+    fn visibility(&self) -> String {
+        match tagged(&self.visibility) {
+            Some(("public", _)) => "pub ".to_string(),
+            Some(("crate", _)) => "pub(crate) ".to_string(),
+            Some(("restricted", restricted)) => match restricted["path"].as_str() {
+                Some(path) => format!("pub(in {path}) "),
+                None => String::new(),
+            },
+            _ => String::new(),
+        }
+    }
+
+    /// Returns the blockquote for the item if it is deprecated, in the same
+    /// form as the deprecation banners converted from the HTML docs.
+    fn deprecation_notice(&self) -> Option<String> {
+        let deprecation = self.deprecation.as_ref()?;
+        let note = deprecation.note.as_deref().map(str::trim);
+        let notice = match (deprecation.since.as_deref(), note) {
+            (Some(since), Some(note)) => format!("{note} (since {since})"),
+            (Some(since), None) => format!("Since {since}."),
+            (None, Some(note)) => note.to_string(),
+            (None, None) => "This item is deprecated.".to_string(),
+        };
+        Some(format!("> **Deprecated:** {notice}"))
+    }
+}
+
+/// An item listed on a module page.
+struct ModuleEntry<'a> {
+    id: String,
+    item: &'a JsonItem,
+    kind: RustdocItemKind,
+    /// The name that the item is listed under, which is the name it is
+    /// re-exported as for re-exports.
+    name: &'a str,
+    /// Whether the item is re-exported from another module, rather than
+    /// declared in this one.
+    is_reexport: bool,
+}
+
+struct JsonConverter<'a> {
+    krate: &'a RustdocJson,
+    crate_name: &'a str,
+    item_order: RustdocItemOrder,
+    /// The item at the path that each item is documented at, keyed by its ID.
+    locations: IndexMap<String, RustdocItem>,
+}
+
+impl<'a> JsonConverter<'a> {
+    fn convert(mut self) -> Result<CrateDocs> {
+        let root = id_key(&self.krate.root)
+            .and_then(|root_id| self.krate.index.get(&root_id))
+            .context("the crate root is missing from the rustdoc JSON")?;
+
+        let reexports = self.locate_items(root);
+        let items = self
+            .locations
+            .iter()
+            .filter_map(|(id, item)| {
+                let json_item = self.krate.index.get(id)?;
+                Some((item.clone(), self.item_markdown(json_item, item)))
+            })
+            .collect();
+
+        Ok(CrateDocs {
+            crate_root_markdown: self.page_markdown(root, "Crate", self.crate_name),
+            items,
+            reexports,
+        })
+    }
+
+    /// Finds the path that each item in the public modules of the crate is
+    /// documented at, returning the items that are re-exported elsewhere.
+    ///
+    /// The items in private modules are documented where they are
+    /// re-exported, as they are in the HTML docs. When there are several such
+    /// re-exports, the one closest to where the item is declared is preferred
+    /// (e.g., `tokio::sync::Mutex` for `tokio::sync::mutex::Mutex`).
+    fn locate_items(&mut self, root: &'a JsonItem) -> IndexMap<RustdocItem, RustdocItem> {
+        let mut reexports = IndexMap::new();
+        let mut modules_to_visit = VecDeque::from([(Vec::<Arc<str>>::new(), root)]);
+        let mut reexports_to_resolve = VecDeque::new();
+        loop {
+            if let Some((module_path, module)) = modules_to_visit.pop_front() {
+                for entry in self.module_entries(module) {
+                    let item = RustdocItem {
+                        kind: entry.kind,
+                        path: module_path.clone(),
+                        name: entry.name.into(),
+                    };
+                    if entry.is_reexport {
+                        reexports_to_resolve.push_back((item, entry));
+                    } else if !self.locations.contains_key(&entry.id) {
+                        if entry.kind == RustdocItemKind::Mod {
+                            modules_to_visit.push_back((submodule_path(&item), entry.item));
+                        }
+                        self.locations.insert(entry.id, item);
+                    }
+                }
+            } else if let Some((reexport, entry)) = reexports_to_resolve.pop_front() {
+                if !self.locations.contains_key(&entry.id) {
+                    let location = reexports_to_resolve
+                        .iter()
+                        .filter(|(_, other_entry)| other_entry.id == entry.id)
+                        .map(|(other_reexport, _)| other_reexport)
+                        .chain([&reexport])
+                        .max_by_key(|location| self.shared_path_len(&entry.id, location))
+                        .cloned()
+                        .unwrap_or_else(|| reexport.clone());
+                    if entry.kind == RustdocItemKind::Mod {
+                        modules_to_visit.push_back((submodule_path(&location), entry.item));
+                    }
+                    self.locations.insert(entry.id.clone(), location);
+                }
+                if self.locations[&entry.id] != reexport {
+                    reexports.insert(reexport, self.locations[&entry.id].clone());
+                }
+            } else {
+                break;
+            }
+        }
+        reexports
+    }
+
+    /// Returns the number of modules that the given path shares with the
+    /// path that the item with the given ID is declared at.
+    fn shared_path_len(&self, id: &str, item: &RustdocItem) -> usize {
+        let Some(summary) = self.krate.paths.get(id) else {
+            return 0;
+        };
+        let module_path = summary
+            .path
+            .get(1..summary.path.len().saturating_sub(1))
+            .unwrap_or_default();
+        item.path
+            .iter()
+            .zip(module_path)
+            .take_while(|(a, b)| a.as_ref() == b.as_str())
+            .count()
+    }
+
+    fn item(&self, id: &Value) -> Option<(String, &'a JsonItem)> {
+        let id = id_key(id)?;
+        let item = self.krate.index.get(&id)?;
+        Some((id, item))
+    }
+
+    /// Returns the items listed on the page of the given module, which are its
+    /// public items and the items that it re-exports from within the crate.
+    fn module_entries(&self, module: &'a JsonItem) -> Vec<ModuleEntry<'a>> {
+        let mut entries = Vec::new();
+        self.collect_module_entries(module, false, &mut HashSet::default(), &mut entries);
+        entries
+    }
+
+    fn collect_module_entries(
+        &self,
+        module: &'a JsonItem,
+        is_glob_import: bool,
+        imported_modules: &mut HashSet<String>,
+        entries: &mut Vec<ModuleEntry<'a>>,
+    ) {
+        let Some(("module", module)) = module.inner() else {
+            return;
+        };
+
+        for id in module["items"].as_array().into_iter().flatten() {
+            let Some((id, item)) = self.item(id) else {
+                continue;
+            };
+            if !item.is_public() {
+                continue;
+            }
+
+            let Some(("use" | "import", import)) = item.inner() else {
+                let Some(kind) = item.kind() else {
+                    continue;
+                };
+                entries.push(ModuleEntry {
+                    id,
+                    item,
+                    kind,
+                    name: item.name(),
+                    is_reexport: is_glob_import,
+                });
+                continue;
+            };
+
+            // The items re-exported from other crates aren't documented here.
+            let Some((target_id, target)) = self.item(&import["id"]) else {
+                continue;
+            };
+            if target.crate_id != 0 {
+                continue;
+            }
+            if import["is_glob"].as_bool() == Some(true) {
+                if imported_modules.insert(target_id) {
+                    self.collect_module_entries(target, true, imported_modules, entries);
+                }
+                continue;
+            }
+            let Some(kind) = target.kind() else {
+                continue;
+            };
+            entries.push(ModuleEntry {
+                id: target_id,
+                item: target,
+                kind,
+                name: import["name"].as_str().unwrap_or_else(|| target.name()),
+                is_reexport: true,
+            });
+        }
+    }
+
+    fn item_markdown(&self, item: &JsonItem, location: &RustdocItem) -> String {
+        let path = format!("{}::{}", self.crate_name, location.display());
+        self.page_markdown(item, page_title(location.kind), &path)
+    }
+
+    fn page_markdown(&self, item: &JsonItem, title: &str, path: &str) -> String {
+        let name = path.rsplit("::").next().unwrap_or(path);
+        let Some((kind, details)) = item.inner() else {
+            return format!("# {title} {path}");
+        };
+        // The title includes the item's generic parameters, as the one on its
+        // rustdoc page does.
+        let mut sections = vec![format!(
+            "# {title} {path}{params}",
+            params = format_generic_params(&details["generics"])
+        )];
+
+        if kind != "module" {
+            if let Some(declaration) = self.declaration(kind, details, name) {
+                sections.push(format!("```rs\n{declaration}\n```"));
+            }
+        }
+        sections.extend(item.deprecation_notice());
+        sections.push(self.docs(item, 1));
+
+        match kind {
+            "module" => sections.extend(self.module_sections(item)),
+            "struct" => {
+                sections.extend(self.struct_fields_section(details));
+                sections.extend(self.impl_sections(details));
+            }
+            "enum" => {
+                sections.extend(self.variants_section(details));
+                sections.extend(self.impl_sections(details));
+            }
+            "trait" => sections.extend(self.trait_sections(details)),
+            _ => {}
+        }
+
+        sections.retain(|section| !section.is_empty());
+        sections.join("\n\n")
+    }
+
+    fn declaration(&self, kind: &str, details: &Value, name: &str) -> Option<String> {
+        let params = format_generic_params(&details["generics"]);
+        let where_clause = format_where_clause(&details["generics"]);
+        let declaration = match kind {
+            "struct" => self.struct_declaration(details, name),
+            "enum" => self.enum_declaration(details, name),
+            "trait" => self.trait_declaration(details, name),
+            "function" => format!("pub {}", format_function(details, name, true)),
+            "type_alias" | "typedef" => format!(
+                "pub type {name}{params}{where_clause} = {ty};",
+                ty = format_type(&details["type"])
+            ),
+            "constant" => {
+                // Newer versions of the format nest the value of the constant.
+                let value = field(details, &["const"]);
+                let value = if value.is_null() { details } else { value };
+                let ty = format_type(&details["type"]);
+                match value["expr"].as_str() {
+                    Some(expr) => format!("pub const {name}: {ty} = {expr};"),
+                    None => format!("pub const {name}: {ty};"),
+                }
+            }
+            "macro" => details.as_str()?.to_string(),
+            "proc_macro" => {
+                let helpers = details["helpers"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>();
+                match details["kind"].as_str() {
+                    Some("derive") if helpers.is_empty() => format!("#[derive({name})]"),
+                    Some("derive") => {
+                        let mut declaration = format!(
+                            "#[derive({name})]\n{{\n    // Attributes available to this derive:\n"
+                        );
+                        for helper in helpers {
+                            declaration.push_str(&format!("    #[{helper}]\n"));
+                        }
+                        declaration.push('}');
+                        declaration
+                    }
+                    Some("attr") => format!("#[{name}]"),
+                    _ => format!("{name}!() {{ /* proc-macro */ }}"),
+                }
+            }
+            _ => return None,
+        };
+        Some(declaration)
+    }
+
+    fn struct_declaration(&self, details: &Value, name: &str) -> String {
+        let generics = &details["generics"];
+        let declaration = format!("pub struct {name}{}", format_generic_params(generics));
+        let where_clause = format_where_clause(generics);
+        match tagged(&details["kind"]) {
+            Some(("tuple", fields)) => {
+                let fields = fields.as_array().map(Vec::as_slice).unwrap_or_default();
+                let fields = if fields.iter().all(Value::is_null) {
+                    "/* private fields */".to_string()
+                } else {
+                    fields
+                        .iter()
+                        .map(|field| match self.item(field) {
+                            Some((_, field)) => {
+                                format!("{}{}", field.visibility(), format_field_type(field))
+                            }
+                            None => "/* private field */".to_string(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                format!("{declaration}({fields}){where_clause};")
+            }
+            Some(("plain", plain)) => {
+                let fields = self.fields(&plain["fields"]);
+                let has_stripped_fields = field(plain, &["has_stripped_fields", "fields_stripped"])
+                    .as_bool()
+                    .unwrap_or_default();
+                let open = if where_clause.is_empty() { " {" } else { "\n{" };
+                if fields.is_empty() && has_stripped_fields {
+                    return format!("{declaration}{where_clause}{open} /* private fields */ }}");
+                }
+
+                let mut declaration = format!("{declaration}{where_clause}{open}\n");
+                for field in fields {
+                    declaration.push_str(&format!(
+                        "    {}{}: {},\n",
+                        field.visibility(),
+                        field.name(),
+                        format_field_type(field)
+                    ));
+                }
+                if has_stripped_fields {
+                    declaration.push_str("    /* private fields */\n");
+                }
+                declaration.push('}');
+                declaration
+            }
+            _ => format!("{declaration}{where_clause};"),
+        }
+    }
+
+    fn enum_declaration(&self, details: &Value, name: &str) -> String {
+        let generics = &details["generics"];
+        let where_clause = format_where_clause(generics);
+        let open = if where_clause.is_empty() { " {" } else { "\n{" };
+        let mut declaration = format!(
+            "pub enum {name}{params}{where_clause}{open}",
+            params = format_generic_params(generics)
+        );
+        let variants = self.items(&details["variants"]);
+        let has_stripped_variants = field(details, &["has_stripped_variants", "variants_stripped"])
+            .as_bool()
+            .unwrap_or_default();
+        if variants.is_empty() && !has_stripped_variants {
+            declaration.push('}');
+            return declaration;
+        }
+
+        declaration.push('\n');
+        for variant in variants {
+            let mut line = self.variant_signature(variant, true);
+            if let Some(discriminant) = variant
+                .inner()
+                .and_then(|(_, details)| details["discriminant"]["expr"].as_str())
+            {
+                line.push_str(&format!(" = {discriminant}"));
+            }
+            declaration.push_str(&format!("    {line},\n"));
+        }
+        if has_stripped_variants {
+            declaration.push_str("    // some variants omitted\n");
+        }
+        declaration.push('}');
+        declaration
+    }
+
+    fn trait_declaration(&self, details: &Value, name: &str) -> String {
+        let generics = &details["generics"];
+        let mut declaration = "pub ".to_string();
+        if field(details, &["is_unsafe", "unsafe_"]).as_bool() == Some(true) {
+            declaration.push_str("unsafe ");
+        }
+        if field(details, &["is_auto", "auto"]).as_bool() == Some(true) {
+            declaration.push_str("auto ");
+        }
+        declaration.push_str(&format!(
+            "trait {name}{params}",
+            params = format_generic_params(generics)
+        ));
+        let bounds = format_bounds(&details["bounds"]);
+        if !bounds.is_empty() {
+            declaration.push_str(&format!(": {bounds}"));
+        }
+        let where_clause = format_where_clause(generics);
+        declaration.push_str(&where_clause);
+        declaration.push_str(if where_clause.is_empty() { " {" } else { "\n{" });
+
+        let trait_items = self.items(&details["items"]);
+        if trait_items.is_empty() {
+            declaration.push_str(" }");
+            return declaration;
+        }
+
+        let mut groups = Vec::new();
+        let associated_items = trait_items
+            .iter()
+            .filter(|item| !matches!(item.inner(), Some(("function", _))))
+            .map(|item| format!("    {};", self.associated_item_signature(item)))
+            .collect::<Vec<_>>();
+        if !associated_items.is_empty() {
+            groups.push(associated_items.join("\n"));
+        }
+        let (provided_methods, required_methods): (Vec<_>, Vec<_>) = trait_items
+            .iter()
+            .filter_map(|item| match item.inner() {
+                Some(("function", function)) => Some((item, function)),
+                _ => None,
+            })
+            .partition(|(_, function)| function["has_body"].as_bool() == Some(true));
+        for (methods, kind) in [
+            (required_methods, "Required"),
+            (provided_methods, "Provided"),
+        ] {
+            if methods.is_empty() {
+                continue;
+            }
+            let mut group = format!(
+                "    // {kind} method{s}",
+                s = if methods.len() == 1 { "" } else { "s" }
+            );
+            for (method, function) in methods {
+                let body = if kind == "Provided" { " { ... }" } else { ";" };
+                group.push_str(&format!(
+                    "\n    {}{body}",
+                    format_function(function, method.name(), false)
+                ));
+            }
+            groups.push(group);
+        }
+
+        declaration.push('\n');
+        declaration.push_str(&groups.join("\n\n"));
+        declaration.push_str("\n}");
+        declaration
+    }
+
+    /// Returns the signature of an associated type, constant, or function,
+    /// as written in the heading for it.
+    fn associated_item_signature(&self, item: &JsonItem) -> String {
+        let name = item.name();
+        match item.inner() {
+            Some(("function", function)) => format!(
+                "{}{}",
+                item.visibility(),
+                format_function(function, name, false)
+            ),
+            Some(("assoc_type", assoc_type)) => {
+                let generics = &assoc_type["generics"];
+                let mut signature = format!("type {name}{}", format_generic_params(generics));
+                let bounds = format_bounds(&assoc_type["bounds"]);
+                if !bounds.is_empty() {
+                    signature.push_str(&format!(": {bounds}"));
+                }
+                let ty = field(assoc_type, &["type", "default"]);
+                if !ty.is_null() {
+                    signature.push_str(&format!(" = {}", format_type(ty)));
+                }
+                signature.push_str(&format_inline_where_clause(generics));
+                signature
+            }
+            Some(("assoc_const", assoc_const)) => {
+                let mut signature = format!(
+                    "{}const {name}: {}",
+                    item.visibility(),
+                    format_type(&assoc_const["type"])
+                );
+                if let Some(value) = field(assoc_const, &["value", "default"]).as_str() {
+                    signature.push_str(&format!(" = {value}"));
+                }
+                signature
+            }
+            _ => name.to_string(),
+        }
+    }
+
+    fn module_sections(&self, module: &'a JsonItem) -> Vec<String> {
+        let mut entries = self.module_entries(module);
+        if self.item_order == RustdocItemOrder::Declaration {
+            // The items are listed in a single section, with their kinds.
+            let mut listed = HashSet::default();
+            entries.retain(|entry| listed.insert((entry.kind, entry.name)));
+            if entries.is_empty() {
+                return Vec::new();
+            }
+
+            let mut section = "## Items\n".to_string();
+            for entry in entries {
+                section.push_str(&format!("\n- `{}` ({})", entry.name, entry.kind.class()));
+                section.push_str(&self.entry_annotations(entry.item));
+            }
+            return vec![section];
+        }
+
+        RustdocItemKind::iter()
+            .filter_map(|kind| {
+                let mut entries = entries
+                    .iter()
+                    .filter(|entry| entry.kind == kind)
+                    .collect::<Vec<_>>();
+                if entries.is_empty() {
+                    return None;
+                }
+                entries.sort_by_key(|entry| entry.name);
+                entries.dedup_by_key(|entry| entry.name);
+
+                let mut section = format!("## {}\n", section_title(kind));
+                for entry in entries {
+                    section.push_str(&format!("\n- `{}`", entry.name));
+                    section.push_str(&self.entry_annotations(entry.item));
+                }
+                Some(section)
+            })
+            .collect()
+    }
+
+    /// Returns what follows the name of an item listed on its module's page,
+    /// i.e., whether it's deprecated and its summary.
+    fn entry_annotations(&self, item: &JsonItem) -> String {
+        let mut annotations = String::new();
+        if item.deprecation.is_some() {
+            annotations.push_str(" [Deprecated]");
+        }
+        if let Some(summary) = self.summary(item) {
+            annotations.push_str(&format!(": {summary}"));
+        }
+        annotations
+    }
+
+    fn struct_fields_section(&self, details: &Value) -> Option<String> {
+        let (title, fields) = match tagged(&details["kind"])? {
+            ("plain", plain) => ("Fields", self.fields(&plain["fields"])),
+            ("tuple", fields) => ("Tuple Fields", self.fields(fields)),
+            _ => return None,
+        };
+        let fields = fields
+            .into_iter()
+            .map(|field| self.field_entry(field))
+            .collect::<Vec<_>>();
+        (!fields.is_empty()).then(|| format!("## {title}\n\n{}", fields.join("\n\n")))
+    }
+
+    /// Returns the list entry for a field, with its docs indented under it.
+    fn field_entry(&self, field: &JsonItem) -> String {
+        let mut entry = format!("- `{}: {}`", field.name(), format_field_type(field));
+        let docs = self.docs(field, 1);
+        if !docs.is_empty() {
+            entry.push_str("\n\n");
+            entry.push_str(&indent(&docs, "  "));
+        }
+        entry
+    }
+
+    fn variants_section(&self, details: &Value) -> Option<String> {
+        let variants = self.items(&details["variants"]);
+        if variants.is_empty() {
+            return None;
+        }
+
+        let mut sections = vec!["## Variants".to_string()];
+        for variant in variants {
+            sections.push(format!("### {}", self.variant_signature(variant, false)));
+            sections.extend(variant.deprecation_notice());
+            sections.push(self.docs(variant, 3));
+            if let Some(("struct", fields)) = variant
+                .inner()
+                .and_then(|(_, variant)| tagged(&variant["kind"]))
+            {
+                let fields = self
+                    .fields(&fields["fields"])
+                    .into_iter()
+                    .map(|field| self.field_entry(field))
+                    .collect::<Vec<_>>();
+                if !fields.is_empty() {
+                    sections.push(format!("#### Fields\n\n{}", fields.join("\n\n")));
+                }
+            }
+        }
+        sections.retain(|section| !section.is_empty());
+        Some(sections.join("\n\n"))
+    }
+
+    /// Returns the signature of an enum variant, e.g., `Ok(T)`, with the
+    /// fields of struct variants in the declaration of the enum.
+    fn variant_signature(&self, variant: &JsonItem, is_declaration: bool) -> String {
+        let name = variant.name();
+        let kind = variant
+            .inner()
+            .and_then(|(_, variant)| tagged(&variant["kind"]));
+        match kind {
+            Some(("tuple", fields)) => {
+                let fields = fields
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|field| match self.item(field) {
+                        Some((_, field)) => format_field_type(field),
+                        None => "_".to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                format!("{name}({})", fields.join(", "))
+            }
+            Some(("struct", fields)) if is_declaration => {
+                let fields = self
+                    .fields(&fields["fields"])
+                    .into_iter()
+                    .map(|field| format!("{}: {}", field.name(), format_field_type(field)))
+                    .collect::<Vec<_>>();
+                format!("{name} {{ {} }}", fields.join(", "))
+            }
+            _ => name.to_string(),
+        }
+    }
+
+    /// Returns the sections for the implementations of a struct or an enum.
+    fn impl_sections(&self, details: &Value) -> Vec<String> {
+        let mut inherent_impls = Vec::new();
+        let mut trait_impls = Vec::new();
+        let mut auto_trait_impls = Vec::new();
+        let mut blanket_impls = Vec::new();
+        for implementation in self.items(&details["impls"]) {
+            let Some(("impl", details)) = implementation.inner() else {
+                continue;
+            };
+            if field(details, &["trait"]).is_null() {
+                inherent_impls.push(self.inherent_impl(details));
+                continue;
+            }
+
+            let entry = format!("- `{}`", format_impl_header(details));
+            if field(details, &["is_synthetic", "synthetic"]).as_bool() == Some(true) {
+                auto_trait_impls.push(entry);
+            } else if !field(details, &["blanket_impl"]).is_null() {
+                blanket_impls.push(entry);
+            } else {
+                trait_impls.push(entry);
+            }
+        }
+
+        let mut sections = Vec::new();
+        inherent_impls.retain(|implementation| !implementation.is_empty());
+        if !inherent_impls.is_empty() {
+            sections.push(format!(
+                "## Implementations\n\n{}",
+                inherent_impls.join("\n\n")
+            ));
+        }
+        if !trait_impls.is_empty() || !auto_trait_impls.is_empty() || !blanket_impls.is_empty() {
+            let mut section = "## Implements".to_string();
+            if !trait_impls.is_empty() {
+                section.push_str(&format!("\n\n{}", trait_impls.join("\n")));
+            }
+            // As on the HTML pages, the auto trait and blanket impls are
+            // listed together, after the others.
+            auto_trait_impls.append(&mut blanket_impls);
+            if !auto_trait_impls.is_empty() {
+                section.push_str(&format!(
+                    "\n\n### {AUTO_AND_BLANKET_IMPLS_HEADING} ({})\n\n{}",
+                    auto_trait_impls.len(),
+                    auto_trait_impls.join("\n")
+                ));
+            }
+            sections.push(section);
+        }
+        sections
+    }
+
+    /// Returns the heading for an inherent impl, followed by its public items.
+    fn inherent_impl(&self, details: &Value) -> String {
+        let impl_items = self
+            .items(&details["items"])
+            .into_iter()
+            .filter(|item| item.is_public())
+            .collect::<Vec<_>>();
+        if impl_items.is_empty() {
+            return String::new();
+        }
+
+        let mut sections = vec![format!("### {}", format_impl_header(details))];
+        for impl_item in impl_items {
+            sections.push(format!(
+                "#### {}",
+                self.associated_item_signature(impl_item)
+            ));
+            sections.extend(impl_item.deprecation_notice());
+            sections.push(self.docs(impl_item, 4));
+        }
+        sections.retain(|section| !section.is_empty());
+        sections.join("\n\n")
+    }
+
+    fn trait_sections(&self, details: &Value) -> Vec<String> {
+        let trait_items = self.items(&details["items"]);
+        let groups: [(&str, fn(&str, &Value) -> bool); 6] = [
+            ("Required Associated Types", |kind, item| {
+                kind == "assoc_type" && field(item, &["type", "default"]).is_null()
+            }),
+            ("Provided Associated Types", |kind, item| {
+                kind == "assoc_type" && !field(item, &["type", "default"]).is_null()
+            }),
+            ("Required Associated Constants", |kind, item| {
+                kind == "assoc_const" && field(item, &["value", "default"]).is_null()
+            }),
+            ("Provided Associated Constants", |kind, item| {
+                kind == "assoc_const" && !field(item, &["value", "default"]).is_null()
+            }),
+            ("Required Methods", |kind, item| {
+                kind == "function" && item["has_body"].as_bool() != Some(true)
+            }),
+            ("Provided Methods", |kind, item| {
+                kind == "function" && item["has_body"].as_bool() == Some(true)
+            }),
+        ];
+
+        let mut sections = Vec::new();
+        for (title, is_in_group) in groups {
+            let mut group = Vec::new();
+            for trait_item in &trait_items {
+                let Some((kind, details)) = trait_item.inner() else {
+                    continue;
+                };
+                if !is_in_group(kind, details) {
+                    continue;
+                }
+                group.push(format!(
+                    "#### {}",
+                    self.associated_item_signature(trait_item)
+                ));
+                group.extend(trait_item.deprecation_notice());
+                group.push(self.docs(trait_item, 4));
+            }
+            group.retain(|section| !section.is_empty());
+            if !group.is_empty() {
+                sections.push(format!("## {title}\n\n{}", group.join("\n\n")));
+            }
+        }
+
+        let implementors = self
+            .items(&details["implementations"])
+            .into_iter()
+            .filter_map(|implementation| match implementation.inner() {
+                Some(("impl", details)) => Some(format!("### {}", format_impl_header(details))),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !implementors.is_empty() {
+            sections.push(format!("## Implementors\n\n{}", implementors.join("\n\n")));
+        }
+        sections
+    }
+
+    /// Returns the items with the given IDs, skipping the ones that aren't in
+    /// the index (e.g., because they are private).
+    fn items(&self, ids: &Value) -> Vec<&'a JsonItem> {
+        ids.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| Some(self.item(id)?.1))
+            .collect()
+    }
+
+    fn fields(&self, ids: &Value) -> Vec<&'a JsonItem> {
+        self.items(ids)
+            .into_iter()
+            .filter(|field| matches!(field.inner(), Some(("struct_field", _))))
+            .collect()
+    }
+
+    /// Returns the docs of the given item as Markdown, with their headings
+    /// nested below the given level and their intra-doc links resolved to
+    /// the full paths of the items they link to (e.g., `tokio::sync::Mutex`).
+    fn docs(&self, item: &JsonItem, heading_level: usize) -> String {
+        let Some(docs) = item.docs.as_deref() else {
+            return String::new();
+        };
+        let link_definitions = link_definitions(docs);
+
+        let mut markdown = String::new();
+        let mut code_block = None::<CodeFence>;
+        for line in docs.lines() {
+            let trimmed_line = line.trim_start();
+            let indentation = &line[..line.len() - trimmed_line.len()];
+            if let Some(fence) = &code_block {
+                if fence.is_closed_by(trimmed_line) {
+                    code_block = None;
+                } else if fence.is_rust {
+                    // Lines starting with `# ` are hidden from the examples in
+                    // the docs, and `##` escapes a `#` at the start of a line.
+                    if trimmed_line == "#" || trimmed_line.starts_with("# ") {
+                        continue;
+                    }
+                    if trimmed_line.starts_with("##") {
+                        markdown.push_str(indentation);
+                        markdown.push_str(&trimmed_line[1..]);
+                        markdown.push('\n');
+                        continue;
+                    }
+                }
+                markdown.push_str(line);
+                markdown.push('\n');
+                continue;
+            }
+
+            if let Some(fence) = CodeFence::parse(trimmed_line) {
+                if fence.is_rust {
+                    markdown.push_str(&format!("{indentation}{}rust\n", fence.marker));
+                } else {
+                    markdown.push_str(line);
+                    markdown.push('\n');
+                }
+                code_block = Some(fence);
+                continue;
+            }
+            if parse_link_definition(line).is_some() {
+                continue;
+            }
+
+            let line = match heading_depth(line) {
+                Some(depth) => {
+                    let level = (depth + heading_level).min(6);
+                    format!("{} {}", "#".repeat(level), line[depth..].trim_start())
+                }
+                None => line.to_string(),
+            };
+            markdown.push_str(&self.resolve_links(&line, item, &link_definitions, true));
+            markdown.push('\n');
+        }
+
+        markdown.trim().to_string()
+    }
+
+    /// Returns the first paragraph of the docs of the given item, without its
+    /// links, as listed on module pages.
+    fn summary(&self, item: &JsonItem) -> Option<String> {
+        let docs = item.docs.as_deref()?;
+        let paragraph = docs
+            .trim_start()
+            .lines()
+            .take_while(|line| !line.trim().is_empty())
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        if paragraph.is_empty() || CodeFence::parse(paragraph[0]).is_some() {
+            return None;
+        }
+        let summary = paragraph.join(" ");
+        let summary = summary.trim_start_matches('#').trim_start();
+        Some(self.resolve_links(summary, item, &link_definitions(docs), false))
+    }
+
+    /// Rewrites the links in the given line of docs, replacing the intra-doc
+    /// links with links to the full paths of the items they link to. Links
+    /// to anchors and to other pages of the HTML docs are replaced with their
+    /// text, as they don't lead anywhere in the Markdown.
+    ///
+    /// When `keep_links` is false, every link is replaced with its text.
+    fn resolve_links(
+        &self,
+        line: &str,
+        item: &JsonItem,
+        link_definitions: &HashMap<String, String>,
+        keep_links: bool,
+    ) -> String {
+        let mut output = String::new();
+        let mut rest = line;
+        while let Some(ix) = rest.find(['[', '`']) {
+            output.push_str(&rest[..ix]);
+            rest = &rest[ix..];
+
+            // Code spans can't contain links.
+            if rest.starts_with('`') {
+                let ticks = &rest[..rest.len() - rest.trim_start_matches('`').len()];
+                let end = rest[ticks.len()..]
+                    .find(ticks)
+                    .map_or(ticks.len(), |end| end + 2 * ticks.len());
+                output.push_str(&rest[..end]);
+                rest = &rest[end..];
+                continue;
+            }
+
+            let Some(text_end) = closing_bracket(rest) else {
+                output.push('[');
+                rest = &rest[1..];
+                continue;
+            };
+            let text = &rest[1..text_end];
+            let after_text = &rest[text_end + 1..];
+            let (destination, is_explicit, len) =
+                if let Some(after_paren) = after_text.strip_prefix('(') {
+                    match after_paren.find(')') {
+                        Some(end) => {
+                            // The destination may be followed by a title.
+                            let destination = after_paren[..end].split_whitespace().next();
+                            (destination.unwrap_or_default(), true, end + 2)
+                        }
+                        None => (text, false, 0),
+                    }
+                } else if let Some(after_bracket) = after_text.strip_prefix('[') {
+                    match after_bracket.find(']') {
+                        Some(0) => (text, false, 2),
+                        Some(end) => (&after_bracket[..end], false, end + 2),
+                        None => (text, false, 0),
+                    }
+                } else {
+                    (text, false, 0)
+                };
+            let destination = link_definitions
+                .get(destination)
+                .map_or(destination, String::as_str);
+            let is_defined = is_explicit || link_definitions.contains_key(destination);
+
+            let target = self
+                .link_target(item, destination)
+                .or_else(|| is_url(destination).then(|| destination.to_string()));
+            match target {
+                Some(target) if keep_links => output.push_str(&format!("[{text}]({target})")),
+                Some(_) => output.push_str(text),
+                // A link that doesn't resolve to anything is left as is, as it
+                // may just be text in brackets.
+                None if !is_defined && !keep_links => output.push_str(text),
+                None if !is_defined => output.push_str(&rest[..text_end + 1 + len]),
+                None => output.push_str(text),
+            }
+            rest = &after_text[len..];
+        }
+        output.push_str(rest);
+        output
+    }
+
+    /// Returns the full path of the item that an intra-doc link in the docs
+    /// of the given item links to, which is the path it is documented at for
+    /// the items in the crate.
+    fn link_target(&self, item: &JsonItem, destination: &str) -> Option<String> {
+        let id = item
+            .links
+            .get(destination)
+            .or_else(|| item.links.get(destination.trim_matches('`')))?;
+        let id = id_key(id)?;
+        if let Some(location) = self.locations.get(&id) {
+            return Some(format!("{}::{}", self.crate_name, location.display()));
+        }
+        let summary = self.krate.paths.get(&id)?;
+        Some(summary.path.join("::"))
+    }
+}
+
+/// Returns the name and the value of an externally tagged enum in the JSON,
+/// such as `{"struct": {...}}`, or the name alone for unit variants, such as
+/// `"infer"`.
+fn tagged(value: &Value) -> Option<(&str, &Value)> {
+    match value {
+        Value::Object(object) if object.len() == 1 => object
+            .iter()
+            .next()
+            .map(|(name, value)| (name.as_str(), value)),
+        Value::String(name) => Some((name.as_str(), &NULL)),
+        _ => None,
+    }
+}
+
+/// Returns the first of the given fields of an object that is present, as
+/// fields have been renamed between versions of the format.
+fn field<'a>(value: &'a Value, names: &[&str]) -> &'a Value {
+    names
+        .iter()
+        .map(|name| &value[*name])
+        .find(|value| !value.is_null())
+        .unwrap_or(&NULL)
+}
+
+/// Returns the key of the given ID in the index, as the IDs are strings in
+/// older versions of the format and integers in newer ones.
+fn id_key(id: &Value) -> Option<String> {
+    match id {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+fn submodule_path(module: &RustdocItem) -> Vec<Arc<str>> {
+    let mut path = module.path.clone();
+    path.push(module.name.clone());
+    path
+}
+
+/// Returns the kind of item in the title of its page, e.g., `Struct` in
+/// `Struct tokio::sync::Mutex`.
+fn page_title(kind: RustdocItemKind) -> &'static str {
+    match kind {
+        RustdocItemKind::Mod => "Module",
+        RustdocItemKind::Macro => "Macro",
+        RustdocItemKind::Struct => "Struct",
+        RustdocItemKind::Enum => "Enum",
+        RustdocItemKind::Constant => "Constant",
+        RustdocItemKind::Trait => "Trait",
+        RustdocItemKind::Function => "Function",
+        RustdocItemKind::TypeAlias => "Type Alias",
+        RustdocItemKind::AttributeMacro => "Attribute Macro",
+        RustdocItemKind::DeriveMacro => "Derive Macro",
+    }
+}
+
+/// Returns the title of the section that lists the items of the given kind
+/// on a module page.
+fn section_title(kind: RustdocItemKind) -> &'static str {
+    match kind {
+        RustdocItemKind::Mod => "Modules",
+        RustdocItemKind::Macro => "Macros",
+        RustdocItemKind::Struct => "Structs",
+        RustdocItemKind::Enum => "Enums",
+        RustdocItemKind::Constant => "Constants",
+        RustdocItemKind::Trait => "Traits",
+        RustdocItemKind::Function => "Functions",
+        RustdocItemKind::TypeAlias => "Type Aliases",
+        RustdocItemKind::AttributeMacro => "Attribute Macros",
+        RustdocItemKind::DeriveMacro => "Derive Macros",
+    }
+}
+
+/// The opening fence of a code block in the docs.
+struct CodeFence {
+    /// The backticks or tildes of the fence, which the closing fence repeats.
+    marker: String,
+    /// Whether the code block is a Rust example, as code blocks without a
+    /// language or with only rustdoc's attributes (e.g., `no_run`) are.
+    is_rust: bool,
+}
+
+impl CodeFence {
+    fn parse(line: &str) -> Option<Self> {
+        let fence_char = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+        let marker_len = line.len() - line.trim_start_matches(fence_char).len();
+        if marker_len < 3 {
+            return None;
+        }
+
+        let info = line[marker_len..].trim();
+        let is_rust = info
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|attribute| !attribute.is_empty())
+            .all(|attribute| {
+                matches!(
+                    attribute,
+                    "rust"
+                        | "ignore"
+                        | "should_panic"
+                        | "no_run"
+                        | "compile_fail"
+                        | "test_harness"
+                        | "standalone_crate"
+                ) || attribute.starts_with("edition")
+                    || attribute.starts_with("ignore-")
+            });
+        Some(Self {
+            marker: line[..marker_len].to_string(),
+            is_rust,
+        })
+    }
+
+    fn is_closed_by(&self, line: &str) -> bool {
+        let line = line.trim_end();
+        line.starts_with(&self.marker) && line.chars().all(|c| self.marker.starts_with(c))
+    }
+}
+
+/// Returns the depth of the given line if it is an ATX heading, e.g., `2`
+/// for `## Examples`.
+fn heading_depth(line: &str) -> Option<usize> {
+    let depth = line.len() - line.trim_start_matches('#').len();
+    let is_heading = (1..=6).contains(&depth)
+        && line[depth..]
+            .chars()
+            .next()
+            .map_or(true, char::is_whitespace);
+    is_heading.then_some(depth)
+}
+
+/// Returns the link reference definitions in the given docs (e.g.,
+/// `` [`Mutex`]: crate::sync::Mutex ``), keyed by their labels.
+fn link_definitions(docs: &str) -> HashMap<String, String> {
+    docs.lines()
+        .filter_map(parse_link_definition)
+        .map(|(label, destination)| (label.to_string(), destination.to_string()))
+        .collect()
+}
+
+fn parse_link_definition(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_start().strip_prefix('[')?;
+    let (label, destination) = line.split_once("]:")?;
+    let destination = destination.split_whitespace().next()?;
+    (!label.is_empty()).then_some((label, destination))
+}
+
+/// Returns the index of the `]` that closes the `[` at the start of the given
+/// text, allowing for nested brackets.
+fn closing_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (ix, c) in text.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(ix);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_url(destination: &str) -> bool {
+    destination.starts_with("https://") || destination.starts_with("http://")
+}
+
+fn indent(text: &str, indentation: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{indentation}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_field_type(field: &JsonItem) -> String {
+    match field.inner() {
+        Some(("struct_field", ty)) => format_type(ty),
+        _ => "_".to_string(),
+    }
+}
+
+/// Formats a type as rustdoc shows it, with the paths shortened to the names
+/// of the items (e.g., `Option<String>`).
+fn format_type(ty: &Value) -> String {
+    let Some((kind, ty)) = tagged(ty) else {
+        return "_".to_string();
+    };
+    match kind {
+        "resolved_path" => format_path(ty),
+        "generic" | "primitive" => ty.as_str().unwrap_or("_").to_string(),
+        "tuple" => {
+            let types = ty
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(format_type)
+                .collect::<Vec<_>>();
+            match types.as_slice() {
+                [ty] => format!("({ty},)"),
+                types => format!("({})", types.join(", ")),
+            }
+        }
+        "slice" => format!("[{}]", format_type(ty)),
+        "array" => format!(
+            "[{}; {}]",
+            format_type(&ty["type"]),
+            ty["len"].as_str().unwrap_or("_")
+        ),
+        "borrowed_ref" => {
+            let mut reference = "&".to_string();
+            if let Some(lifetime) = ty["lifetime"].as_str() {
+                reference.push_str(&format!("{lifetime} "));
+            }
+            if field(ty, &["is_mutable", "mutable"]).as_bool() == Some(true) {
+                reference.push_str("mut ");
+            }
+            reference.push_str(&format_type(&ty["type"]));
+            reference
+        }
+        "raw_pointer" => {
+            let mutability = if field(ty, &["is_mutable", "mutable"]).as_bool() == Some(true) {
+                "mut"
+            } else {
+                "const"
+            };
+            format!("*{mutability} {}", format_type(&ty["type"]))
+        }
+        "dyn_trait" => {
+            let mut bounds = ty["traits"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|poly_trait| {
+                    format!(
+                        "{}{}",
+                        format_higher_ranked_params(&poly_trait["generic_params"]),
+                        format_path(&poly_trait["trait"])
+                    )
+                })
+                .collect::<Vec<_>>();
+            if let Some(lifetime) = ty["lifetime"].as_str() {
+                bounds.push(lifetime.to_string());
+            }
+            format!("dyn {}", bounds.join(" + "))
+        }
+        "impl_trait" => format!("impl {}", format_bounds(ty)),
+        "function_pointer" => {
+            let sig = field(ty, &["sig", "decl"]);
+            format!(
+                "{}{}fn({}){}",
+                format_higher_ranked_params(&ty["generic_params"]),
+                format_qualifiers(&ty["header"]),
+                format_inputs(sig, true),
+                format_output(sig)
+            )
+        }
+        "qualified_path" => {
+            let name = ty["name"].as_str().unwrap_or("_");
+            let self_type = &ty["self_type"];
+            let args = format_generic_args(&ty["args"]);
+            let trait_path = field(ty, &["trait"]);
+            if matches!(tagged(self_type), Some(("generic", _))) || trait_path.is_null() {
+                format!("{}::{name}{args}", format_type(self_type))
+            } else {
+                format!(
+                    "<{} as {}>::{name}{args}",
+                    format_type(self_type),
+                    format_path(trait_path)
+                )
+            }
+        }
+        "pat" => format_type(&ty["type"]),
+        _ => "_".to_string(),
+    }
+}
+
+fn format_path(path: &Value) -> String {
+    let name = field(path, &["path", "name"]).as_str().unwrap_or("_");
+    let name = name.rsplit("::").next().unwrap_or(name);
+    format!("{name}{}", format_generic_args(&path["args"]))
+}
+
+fn format_generic_args(args: &Value) -> String {
+    match tagged(args) {
+        Some(("angle_bracketed", args)) => {
+            let mut formatted_args = args["args"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|arg| match tagged(arg) {
+                    Some(("lifetime", lifetime)) => lifetime.as_str().unwrap_or("'_").to_string(),
+                    Some(("type", ty)) => format_type(ty),
+                    Some(("const", constant)) => {
+                        constant["expr"].as_str().unwrap_or("_").to_string()
+                    }
+                    _ => "_".to_string(),
+                })
+                .collect::<Vec<_>>();
+            let constraints = field(args, &["constraints", "bindings"]);
+            for constraint in constraints.as_array().into_iter().flatten() {
+                let mut formatted_constraint = format!(
+                    "{}{}",
+                    constraint["name"].as_str().unwrap_or("_"),
+                    format_generic_args(&constraint["args"])
+                );
+                match tagged(&constraint["binding"]) {
+                    Some(("equality", term)) => {
+                        formatted_constraint.push_str(&format!(" = {}", format_term(term)));
+                    }
+                    Some(("constraint", bounds)) => {
+                        formatted_constraint.push_str(&format!(": {}", format_bounds(bounds)));
+                    }
+                    _ => {}
+                }
+                formatted_args.push(formatted_constraint);
+            }
+
+            if formatted_args.is_empty() {
+                String::new()
+            } else {
+                format!("<{}>", formatted_args.join(", "))
+            }
+        }
+        Some(("parenthesized", args)) => {
+            let inputs = args["inputs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(format_type)
+                .collect::<Vec<_>>();
+            let output = &args["output"];
+            if output.is_null() {
+                format!("({})", inputs.join(", "))
+            } else {
+                format!("({}) -> {}", inputs.join(", "), format_type(output))
+            }
+        }
+        Some(("return_type_notation", _)) => "(..)".to_string(),
+        _ => String::new(),
+    }
+}
+
+fn format_term(term: &Value) -> String {
+    match tagged(term) {
+        Some(("type", ty)) => format_type(ty),
+        Some(("constant", constant)) => constant["expr"].as_str().unwrap_or("_").to_string(),
+        _ => "_".to_string(),
+    }
+}
+
+fn format_bounds(bounds: &Value) -> String {
+    bounds
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|bound| match tagged(bound)? {
+            ("trait_bound", bound) => {
+                let modifier = match bound["modifier"].as_str() {
+                    Some("maybe") => "?",
+                    Some("maybe_const") => "~const ",
+                    _ => "",
+                };
+                Some(format!(
+                    "{}{modifier}{}",
+                    format_higher_ranked_params(&bound["generic_params"]),
+                    format_path(&bound["trait"])
+                ))
+            }
+            ("outlives", lifetime) => lifetime.as_str().map(ToString::to_string),
+            ("use", params) => {
+                let params = params
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|param| match param {
+                        Value::String(param) => Some(param.as_str()),
+                        param => tagged(param)?.1.as_str(),
+                    })
+                    .collect::<Vec<_>>();
+                Some(format!("use<{}>", params.join(", ")))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Formats the generic parameters of an item, e.g., `<'a, T: Clone>`.
+fn format_generic_params(generics: &Value) -> String {
+    let params = generics["params"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(format_generic_param)
+        .collect::<Vec<_>>();
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    }
+}
+
+fn format_generic_param(param: &Value) -> Option<String> {
+    let name = param["name"].as_str()?;
+    match tagged(&param["kind"])? {
+        ("lifetime", lifetime) => {
+            let outlives = lifetime["outlives"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>();
+            if outlives.is_empty() {
+                Some(name.to_string())
+            } else {
+                Some(format!("{name}: {}", outlives.join(" + ")))
+            }
+        }
+        ("type", ty) => {
+            // The parameters for `impl Trait` arguments are written in the
+            // arguments themselves.
+            if field(ty, &["is_synthetic", "synthetic"]).as_bool() == Some(true) {
+                return None;
+            }
+            let mut param = name.to_string();
+            let bounds = format_bounds(&ty["bounds"]);
+            if !bounds.is_empty() {
+                param.push_str(&format!(": {bounds}"));
+            }
+            if !ty["default"].is_null() {
+                param.push_str(&format!(" = {}", format_type(&ty["default"])));
+            }
+            Some(param)
+        }
+        ("const", constant) => {
+            let mut param = format!("const {name}: {}", format_type(&constant["type"]));
+            if let Some(default) = constant["default"].as_str() {
+                param.push_str(&format!(" = {default}"));
+            }
+            Some(param)
+        }
+        _ => None,
+    }
+}
+
+/// Formats the `for<'a>` of a higher-ranked bound, if it has one.
+fn format_higher_ranked_params(params: &Value) -> String {
+    let params = params
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(format_generic_param)
+        .collect::<Vec<_>>();
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("for<{}> ", params.join(", "))
+    }
+}
+
+fn where_predicates(generics: &Value) -> Vec<String> {
+    generics["where_predicates"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|predicate| match tagged(predicate)? {
+            ("bound_predicate", predicate) => {
+                let bounds = format_bounds(&predicate["bounds"]);
+                (!bounds.is_empty()).then(|| {
+                    format!(
+                        "{}{}: {bounds}",
+                        format_higher_ranked_params(&predicate["generic_params"]),
+                        format_type(&predicate["type"])
+                    )
+                })
+            }
+            ("lifetime_predicate" | "region_predicate", predicate) => {
+                let outlives = predicate["outlives"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>();
+                Some(format!(
+                    "{}: {}",
+                    predicate["lifetime"].as_str()?,
+                    outlives.join(" + ")
+                ))
+            }
+            ("eq_predicate", predicate) => Some(format!(
+                "{} = {}",
+                format_type(&predicate["lhs"]),
+                format_term(&predicate["rhs"])
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Formats the where clause of a declaration with a predicate on each line,
+/// as rustdoc shows it.
+fn format_where_clause(generics: &Value) -> String {
+    where_predicates(generics)
+        .into_iter()
+        .map(|predicate| format!("\n    {predicate},"))
+        .fold(String::new(), |where_clause, predicate| {
+            if where_clause.is_empty() {
+                format!("\nwhere{predicate}")
+            } else {
+                where_clause + &predicate
+            }
+        })
+}
+
+/// Formats the where clause of a heading, which is on the same line.
+fn format_inline_where_clause(generics: &Value) -> String {
+    let predicates = where_predicates(generics);
+    if predicates.is_empty() {
+        String::new()
+    } else {
+        format!(" where {}", predicates.join(", "))
+    }
+}
+
+/// Formats the signature of a function, without its visibility. The where
+/// clause is on its own lines in declarations, and inline in headings.
+fn format_function(function: &Value, name: &str, is_declaration: bool) -> String {
+    let generics = &function["generics"];
+    let sig = field(function, &["sig", "decl"]);
+    let where_clause = if is_declaration {
+        format_where_clause(generics)
+    } else {
+        format_inline_where_clause(generics)
+    };
+    format!(
+        "{qualifiers}fn {name}{params}({inputs}){output}{where_clause}",
+        qualifiers = format_qualifiers(&function["header"]),
+        params = format_generic_params(generics),
+        inputs = format_inputs(sig, false),
+        output = format_output(sig)
+    )
+}
+
+/// Formats the qualifiers of a function, e.g., `const unsafe `.
+fn format_qualifiers(header: &Value) -> String {
+    let mut qualifiers = String::new();
+    for (names, qualifier) in [
+        (["is_const", "const_"], "const "),
+        (["is_async", "async_"], "async "),
+        (["is_unsafe", "unsafe_"], "unsafe "),
+    ] {
+        if field(header, &names).as_bool() == Some(true) {
+            qualifiers.push_str(qualifier);
+        }
+    }
+    match tagged(&header["abi"]) {
+        Some(("Rust", _)) | None => {}
+        Some(("Other", abi)) => qualifiers.push_str(&format!("extern {abi} ")),
+        Some((abi, _)) => qualifiers.push_str(&format!("extern \"{abi}\" ")),
+    }
+    qualifiers
+}
+
+fn format_inputs(sig: &Value, is_function_pointer: bool) -> String {
+    let mut inputs = sig["inputs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|input| {
+            let name = input[0].as_str().unwrap_or("_");
+            let ty = &input[1];
+            if name == "self" {
+                if let Some(receiver) = format_receiver(ty) {
+                    return receiver;
+                }
+            }
+            if is_function_pointer && name == "_" {
+                format_type(ty)
+            } else {
+                format!("{name}: {}", format_type(ty))
+            }
+        })
+        .collect::<Vec<_>>();
+    if field(sig, &["is_c_variadic", "c_variadic"]).as_bool() == Some(true) {
+        inputs.push("...".to_string());
+    }
+    inputs.join(", ")
+}
+
+/// Formats the `self` argument of a method in its shorthand form, e.g.,
+/// `&mut self`, if it has one.
+fn format_receiver(ty: &Value) -> Option<String> {
+    let is_self = |ty: &Value| matches!(tagged(ty), Some(("generic", name)) if name == "Self");
+    if is_self(ty) {
+        return Some("self".to_string());
+    }
+    let ("borrowed_ref", reference) = tagged(ty)? else {
+        return None;
+    };
+    is_self(&reference["type"]).then(|| {
+        let mut receiver = "&".to_string();
+        if let Some(lifetime) = reference["lifetime"].as_str() {
+            receiver.push_str(&format!("{lifetime} "));
+        }
+        if field(reference, &["is_mutable", "mutable"]).as_bool() == Some(true) {
+            receiver.push_str("mut ");
+        }
+        receiver.push_str("self");
+        receiver
+    })
+}
+
+fn format_output(sig: &Value) -> String {
+    match &sig["output"] {
+        Value::Null => String::new(),
+        // The unit type is written as an empty tuple in some versions.
+        output if output["tuple"].as_array().is_some_and(Vec::is_empty) => String::new(),
+        output => format!(" -> {}", format_type(output)),
+    }
+}
+
+/// Formats the header of an impl block, e.g., `impl<T> Clone for Mutex<T>`,
+/// with its where clause.
+fn format_impl_header(details: &Value) -> String {
+    let generics = &details["generics"];
+    let mut header = String::new();
+    if field(details, &["is_unsafe", "unsafe_"]).as_bool() == Some(true) {
+        header.push_str("unsafe ");
+    }
+    header.push_str(&format!("impl{} ", format_generic_params(generics)));
+    let trait_path = field(details, &["trait"]);
+    if !trait_path.is_null() {
+        if field(details, &["is_negative", "negative"]).as_bool() == Some(true) {
+            header.push('!');
+        }
+        header.push_str(&format!("{} for ", format_path(trait_path)));
+    }
+    header.push_str(&format_type(&details["for"]));
+    header.push_str(&format_inline_where_clause(generics));
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn item(kind: RustdocItemKind, path: &[&str], name: &str) -> RustdocItem {
+        RustdocItem {
+            kind,
+            path: path.iter().map(|segment| Arc::from(*segment)).collect(),
+            name: name.into(),
+        }
+    }
+
+    /// A crate with a struct in a private module that is re-exported from
+    /// the root and from a public module, as `tokio::sync::Mutex` is.
+    const TOKIO_JSON: &str = indoc! {r##"
+        {
+            "root": 0,
+            "format_version": 39,
+            "index": {
+                "0": {
+                    "id": 0, "crate_id": 0, "name": "tokio", "visibility": "public",
+                    "docs": "A runtime for writing asynchronous applications.",
+                    "links": {}, "deprecation": null,
+                    "inner": {"module": {"is_crate": true, "items": [1, 8, 9, 10], "is_stripped": false}}
+                },
+                "1": {
+                    "id": 1, "crate_id": 0, "name": "sync", "visibility": "public",
+                    "docs": "Synchronization primitives.", "links": {}, "deprecation": null,
+                    "inner": {"module": {"is_crate": false, "items": [2, 7], "is_stripped": false}}
+                },
+                "2": {
+                    "id": 2, "crate_id": 0, "name": null, "visibility": "public",
+                    "docs": null, "links": {}, "deprecation": null,
+                    "inner": {"use": {"source": "self::mutex::Mutex", "name": "Mutex", "id": 3, "is_glob": false}}
+                },
+                "3": {
+                    "id": 3, "crate_id": 0, "name": "Mutex", "visibility": "public",
+                    "docs": "An asynchronous [`Mutex`]-like type, unlike [`std::sync::Mutex`].\n\nSee [spawning](crate::spawn) and [the guide](https://tokio.rs).\n\n# Examples\n\n```\n# use tokio::sync::Mutex;\nlet mutex = Mutex::new(1);\n```\n\n[`std::sync::Mutex`]: std::sync::Mutex",
+                    "links": {"`Mutex`": 3, "std::sync::Mutex": 20, "crate::spawn": 8},
+                    "deprecation": null,
+                    "inner": {"struct": {
+                        "kind": {"plain": {"fields": [], "has_stripped_fields": true}},
+                        "generics": {"params": [{"name": "T", "kind": {"type": {"bounds": [{"trait_bound": {"trait": {"path": "Sized", "id": 21, "args": null}, "generic_params": [], "modifier": "maybe"}}], "default": null, "is_synthetic": false}}}], "where_predicates": []},
+                        "impls": [4, 6]
+                    }}
+                },
+                "4": {
+                    "id": 4, "crate_id": 0, "name": null, "visibility": "default",
+                    "docs": null, "links": {}, "deprecation": null,
+                    "inner": {"impl": {
+                        "is_unsafe": false, "generics": {"params": [{"name": "T", "kind": {"type": {"bounds": [], "default": null, "is_synthetic": false}}}], "where_predicates": []},
+                        "provided_trait_methods": [], "trait": null,
+                        "for": {"resolved_path": {"path": "Mutex", "id": 3, "args": {"angle_bracketed": {"args": [{"type": {"generic": "T"}}], "constraints": []}}}},
+                        "items": [5], "is_negative": false, "is_synthetic": false, "blanket_impl": null
+                    }}
+                },
+                "5": {
+                    "id": 5, "crate_id": 0, "name": "lock", "visibility": "public",
+                    "docs": "Locks this mutex.\n\n# Cancel safety\n\nThis method is cancel safe.",
+                    "links": {}, "deprecation": null,
+                    "inner": {"function": {
+                        "sig": {"inputs": [["self", {"borrowed_ref": {"lifetime": null, "is_mutable": false, "type": {"generic": "Self"}}}]], "output": {"resolved_path": {"path": "MutexGuard", "id": 22, "args": {"angle_bracketed": {"args": [{"lifetime": "'_"}, {"type": {"generic": "T"}}], "constraints": []}}}}, "is_c_variadic": false},
+                        "generics": {"params": [], "where_predicates": []},
+                        "header": {"is_const": false, "is_unsafe": false, "is_async": true, "abi": "Rust"},
+                        "has_body": true
+                    }}
+                },
+                "6": {
+                    "id": 6, "crate_id": 0, "name": null, "visibility": "default",
+                    "docs": null, "links": {}, "deprecation": null,
+                    "inner": {"impl": {
+                        "is_unsafe": false,
+                        "generics": {"params": [{"name": "T", "kind": {"type": {"bounds": [], "default": null, "is_synthetic": false}}}], "where_predicates": [{"bound_predicate": {"type": {"generic": "T"}, "bounds": [{"trait_bound": {"trait": {"path": "Default", "id": 23, "args": null}, "generic_params": [], "modifier": "none"}}], "generic_params": []}}]},
+                        "provided_trait_methods": [],
+                        "trait": {"path": "Default", "id": 23, "args": null},
+                        "for": {"resolved_path": {"path": "Mutex", "id": 3, "args": {"angle_bracketed": {"args": [{"type": {"generic": "T"}}], "constraints": []}}}},
+                        "items": [], "is_negative": false, "is_synthetic": false, "blanket_impl": null
+                    }}
+                },
+                "7": {
+                    "id": 7, "crate_id": 0, "name": "oneshot", "visibility": "public",
+                    "docs": "Creates a new one-shot channel.", "links": {},
+                    "deprecation": {"since": "1.2.0", "note": "use `channel` instead"},
+                    "inner": {"function": {
+                        "sig": {"inputs": [], "output": {"tuple": []}, "is_c_variadic": false},
+                        "generics": {"params": [], "where_predicates": []},
+                        "header": {"is_const": false, "is_unsafe": false, "is_async": false, "abi": "Rust"},
+                        "has_body": true
+                    }}
+                },
+                "8": {
+                    "id": 8, "crate_id": 0, "name": "spawn", "visibility": "public",
+                    "docs": "Spawns a new asynchronous task.", "links": {}, "deprecation": null,
+                    "inner": {"function": {
+                        "sig": {"inputs": [["future", {"generic": "F"}]], "output": {"resolved_path": {"path": "task::JoinHandle", "id": 24, "args": {"angle_bracketed": {"args": [{"type": {"qualified_path": {"name": "Output", "args": null, "self_type": {"generic": "F"}, "trait": {"path": "Future", "id": 25, "args": null}}}}], "constraints": []}}}}, "is_c_variadic": false},
+                        "generics": {"params": [{"name": "F", "kind": {"type": {"bounds": [], "default": null, "is_synthetic": false}}}], "where_predicates": [{"bound_predicate": {"type": {"generic": "F"}, "bounds": [{"trait_bound": {"trait": {"path": "Future", "id": 25, "args": null}, "generic_params": [], "modifier": "none"}}, {"trait_bound": {"trait": {"path": "Send", "id": 26, "args": null}, "generic_params": [], "modifier": "none"}}, {"outlives": "'static"}], "generic_params": []}}]},
+                        "header": {"is_const": false, "is_unsafe": false, "is_async": false, "abi": "Rust"},
+                        "has_body": true
+                    }}
+                },
+                "9": {
+                    "id": 9, "crate_id": 0, "name": null, "visibility": "public",
+                    "docs": null, "links": {}, "deprecation": null,
+                    "inner": {"use": {"source": "crate::sync::Mutex", "name": "Mutex", "id": 3, "is_glob": false}}
+                },
+                "10": {
+                    "id": 10, "crate_id": 0, "name": null, "visibility": "public",
+                    "docs": null, "links": {}, "deprecation": null,
+                    "inner": {"use": {"source": "bytes::Bytes", "name": "Bytes", "id": 27, "is_glob": false}}
+                }
+            },
+            "paths": {
+                "3": {"crate_id": 0, "path": ["tokio", "sync", "mutex", "Mutex"], "kind": "struct"},
+                "8": {"crate_id": 0, "path": ["tokio", "spawn"], "kind": "function"},
+                "20": {"crate_id": 1, "path": ["std", "sync", "Mutex"], "kind": "struct"}
+            }
+        }
+    "##};
+
+    #[test]
+    fn test_convert_rustdoc_json() {
+        let crate_docs = convert_rustdoc_json(TOKIO_JSON, "tokio").unwrap();
+
+        // The struct is documented where it is re-exported from its private
+        // module, and the re-export from the root leads there.
+        assert_eq!(
+            crate_docs.items.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                item(RustdocItemKind::Mod, &[], "sync"),
+                item(RustdocItemKind::Function, &[], "spawn"),
+                item(RustdocItemKind::Function, &["sync"], "oneshot"),
+                item(RustdocItemKind::Struct, &["sync"], "Mutex"),
+            ]
+        );
+        assert_eq!(
+            crate_docs.reexports.into_iter().collect::<Vec<_>>(),
+            vec![(
+                item(RustdocItemKind::Struct, &[], "Mutex"),
+                item(RustdocItemKind::Struct, &["sync"], "Mutex"),
+            )]
+        );
+
+        assert_eq!(
+            crate_docs.crate_root_markdown,
+            indoc! {"
+                # Crate tokio
+
+                A runtime for writing asynchronous applications.
+
+                ## Modules
+
+                - `sync`: Synchronization primitives.
+
+                ## Structs
+
+                - `Mutex`: An asynchronous `Mutex`-like type, unlike `std::sync::Mutex`.
+
+                ## Functions
+
+                - `spawn`: Spawns a new asynchronous task."}
+        );
+        assert_eq!(
+            crate_docs.items[&item(RustdocItemKind::Mod, &[], "sync")],
+            indoc! {"
+                # Module tokio::sync
+
+                Synchronization primitives.
+
+                ## Structs
+
+                - `Mutex`: An asynchronous `Mutex`-like type, unlike `std::sync::Mutex`.
+
+                ## Functions
+
+                - `oneshot` [Deprecated]: Creates a new one-shot channel."}
+        );
+        assert_eq!(
+            crate_docs.items[&item(RustdocItemKind::Struct, &["sync"], "Mutex")],
+            indoc! {"
+                # Struct tokio::sync::Mutex<T: ?Sized>
+
+                ```rs
+                pub struct Mutex<T: ?Sized> { /* private fields */ }
+                ```
+
+                An asynchronous [`Mutex`](tokio::sync::Mutex)-like type, unlike [`std::sync::Mutex`](std::sync::Mutex).
+
+                See [spawning](tokio::spawn) and [the guide](https://tokio.rs).
+
+                ## Examples
+
+                ```rust
+                let mutex = Mutex::new(1);
+                ```
+
+                ## Implementations
+
+                ### impl<T> Mutex<T>
+
+                #### pub async fn lock(&self) -> MutexGuard<'_, T>
+
+                Locks this mutex.
+
+                ##### Cancel safety
+
+                This method is cancel safe.
+
+                ## Implements
+
+                - `impl<T> Default for Mutex<T> where T: Default`"}
+        );
+        assert_eq!(
+            crate_docs.items[&item(RustdocItemKind::Function, &[], "spawn")],
+            indoc! {"
+                # Function tokio::spawn<F>
+
+                ```rs
+                pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+                where
+                    F: Future + Send + 'static,
+                ```
+
+                Spawns a new asynchronous task."}
+        );
+        assert_eq!(
+            crate_docs.items[&item(RustdocItemKind::Function, &["sync"], "oneshot")],
+            indoc! {"
+                # Function tokio::sync::oneshot
+
+                ```rs
+                pub fn oneshot()
+                ```
+
+                > **Deprecated:** use `channel` instead (since 1.2.0)
+
+                Creates a new one-shot channel."}
+        );
+    }
+
+    #[test]
+    fn test_declaration_item_order() {
+        let crate_docs =
+            convert_rustdoc_json_with_order(TOKIO_JSON, "tokio", RustdocItemOrder::Declaration)
+                .unwrap();
+        assert_eq!(
+            crate_docs.crate_root_markdown,
+            indoc! {"
+                # Crate tokio
+
+                A runtime for writing asynchronous applications.
+
+                ## Items
+
+                - `sync` (mod): Synchronization primitives.
+                - `spawn` (fn): Spawns a new asynchronous task.
+                - `Mutex` (struct): An asynchronous `Mutex`-like type, unlike `std::sync::Mutex`."}
+        );
+    }
+
+    #[test]
+    fn test_convert_rustdoc_json_with_string_ids() {
+        let json = indoc! {r#"
+            {
+                "root": "0:0",
+                "index": {
+                    "0:0": {
+                        "crate_id": 0, "name": "geo", "visibility": "public", "docs": null,
+                        "inner": {"module": {"items": ["0:1", "0:4"]}}
+                    },
+                    "0:1": {
+                        "crate_id": 0, "name": "Shape", "visibility": "public",
+                        "docs": "A shape.",
+                        "inner": {"enum": {
+                            "generics": {"params": [], "where_predicates": []},
+                            "variants": ["0:2", "0:3"], "has_stripped_variants": false, "impls": []
+                        }}
+                    },
+                    "0:2": {
+                        "crate_id": 0, "name": "Circle", "visibility": "default",
+                        "docs": "A circle with the given radius.",
+                        "inner": {"variant": {"kind": {"tuple": ["0:5"]}, "discriminant": null}}
+                    },
+                    "0:3": {
+                        "crate_id": 0, "name": "Rect", "visibility": "default", "docs": null,
+                        "inner": {"variant": {"kind": {"struct": {"fields": ["0:6"], "has_stripped_fields": false}}, "discriminant": null}}
+                    },
+                    "0:4": {
+                        "crate_id": 0, "name": "Area", "visibility": "public", "docs": null,
+                        "inner": {"trait": {
+                            "is_auto": false, "is_unsafe": false, "items": ["0:7"],
+                            "generics": {"params": [], "where_predicates": []},
+                            "bounds": [], "implementations": []
+                        }}
+                    },
+                    "0:5": {
+                        "crate_id": 0, "name": "0", "visibility": "default", "docs": null,
+                        "inner": {"struct_field": {"primitive": "f64"}}
+                    },
+                    "0:6": {
+                        "crate_id": 0, "name": "width", "visibility": "default",
+                        "docs": "The width.",
+                        "inner": {"struct_field": {"primitive": "f64"}}
+                    },
+                    "0:7": {
+                        "crate_id": 0, "name": "area", "visibility": "default",
+                        "docs": "Returns the area.",
+                        "inner": {"function": {
+                            "decl": {"inputs": [["self", {"borrowed_ref": {"lifetime": null, "mutable": false, "type": {"generic": "Self"}}}]], "output": {"primitive": "f64"}, "c_variadic": false},
+                            "generics": {"params": [], "where_predicates": []},
+                            "header": {"const_": false, "unsafe_": false, "async_": false, "abi": "Rust"},
+                            "has_body": false
+                        }}
+                    }
+                },
+                "paths": {}
+            }
+        "#};
+        let crate_docs = convert_rustdoc_json(json, "geo").unwrap();
+
+        assert_eq!(
+            crate_docs.items[&item(RustdocItemKind::Enum, &[], "Shape")],
+            indoc! {"
+                # Enum geo::Shape
+
+                ```rs
+                pub enum Shape {
+                    Circle(f64),
+                    Rect { width: f64 },
+                }
+                ```
+
+                A shape.
+
+                ## Variants
+
+                ### Circle(f64)
+
+                A circle with the given radius.
+
+                ### Rect
+
+                #### Fields
+
+                - `width: f64`
+
+                  The width."}
+        );
+        assert_eq!(
+            crate_docs.items[&item(RustdocItemKind::Trait, &[], "Area")],
+            indoc! {"
+                # Trait geo::Area
+
+                ```rs
+                pub trait Area {
+                    // Required method
+                    fn area(&self) -> f64;
+                }
+                ```
+
+                ## Required Methods
+
+                #### fn area(&self) -> f64
+
+                Returns the area."}
+        );
+    }
+
+    #[test]
+    fn test_convert_invalid_rustdoc_json() {
+        assert!(convert_rustdoc_json("{}", "tokio").is_err());
+        assert!(convert_rustdoc_json(r#"{"root": 0, "index": {}}"#, "tokio").is_err());
+    }
+}
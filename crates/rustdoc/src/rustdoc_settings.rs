@@ -0,0 +1,457 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+use crate::{normalize_crate_name, CachePolicy, STANDARD_LIBRARY_CRATES};
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RustdocSettings {
+    pub include_docs_rs_link: bool,
+    pub index_max_age_hours: Option<u64>,
+    pub stale_index_behavior: StaleIndexBehavior,
+    pub resolve_with_language_server: bool,
+    pub link_indexed_items: bool,
+    pub max_response_size_mb: u64,
+    pub request_timeout_secs: u64,
+    pub local_cache_ttl_minutes: Option<u64>,
+    pub latest_cache_ttl_hours: Option<u64>,
+    pub suggest_for_mentioned_crates: bool,
+    pub local_parent_module_fallback: bool,
+    pub duplicate_docs_behavior: DuplicateDocsBehavior,
+    pub std_channel: String,
+    pub max_completions: usize,
+    pub max_docs_length: Option<usize>,
+    pub index_memory_budget_mb: Option<u64>,
+    pub reindex_changed_local_docs: bool,
+    pub source_order: DocsSourceOrder,
+    pub docs_hosts: Vec<DocsHost>,
+    pub crate_aliases: BTreeMap<String, String>,
+}
+
+impl RustdocSettings {
+    /// Returns the age after which locally indexed docs are considered stale.
+    pub fn index_max_age(&self) -> Option<Duration> {
+        self.index_max_age_hours
+            .map(|hours| Duration::from_secs(hours * 60 * 60))
+    }
+
+    /// Returns the maximum size of a docs.rs response, in bytes.
+    pub fn max_response_size(&self) -> u64 {
+        self.max_response_size_mb * 1024 * 1024
+    }
+
+    /// Returns how long to wait for a response from docs.rs.
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    /// Returns the maximum number of bytes of indexed docs to hold in memory.
+    pub fn index_memory_budget(&self) -> Option<usize> {
+        self.index_memory_budget_mb
+            .map(|megabytes| (megabytes * 1024 * 1024) as usize)
+    }
+
+    /// Returns the [`CachePolicy`] for the on-disk docs cache.
+    pub fn cache_policy(&self) -> CachePolicy {
+        CachePolicy {
+            local_ttl: self
+                .local_cache_ttl_minutes
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+            latest_ttl: self
+                .latest_cache_ttl_hours
+                .map(|hours| Duration::from_secs(hours * 60 * 60)),
+        }
+    }
+}
+
+/// What to do when docs are loaded from a local index that is older than
+/// the configured max age.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleIndexBehavior {
+    /// Insert the docs along with a warning that they may be out of date.
+    Warn,
+    /// Insert the docs and reindex the crate in the background.
+    Reindex,
+}
+
+/// What to do when inserting docs that are already in the conversation.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateDocsBehavior {
+    /// Insert the docs again.
+    Insert,
+    /// Skip inserting the docs, with a note that they are already inserted.
+    Skip,
+    /// Skip inserting the docs and scroll to the existing ones.
+    Scroll,
+}
+
+/// The order in which the sources of docs are looked up.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum DocsSourceOrder {
+    /// Look up the local docs first, and then docs.rs.
+    LocalFirst,
+    /// Look up docs.rs first, and then the local docs.
+    DocsRsFirst,
+    /// Only look up the local docs.
+    LocalOnly,
+    /// Only look up docs.rs.
+    DocsRsOnly,
+}
+
+impl DocsSourceOrder {
+    /// Returns the given sources of local docs and of docs.rs docs in the
+    /// order they are looked up in, leaving out those that aren't.
+    pub fn arrange<T>(self, local: Vec<T>, docs_dot_rs: Vec<T>) -> Vec<T> {
+        match self {
+            Self::LocalFirst => local.into_iter().chain(docs_dot_rs).collect(),
+            Self::DocsRsFirst => docs_dot_rs.into_iter().chain(local).collect(),
+            Self::LocalOnly => local,
+            Self::DocsRsOnly => docs_dot_rs,
+        }
+    }
+}
+
+/// A docs.rs-compatible server to fetch the docs for some crates from instead
+/// of docs.rs, e.g., an organization's internal one.
+///
+/// The server is assumed to serve the docs at the same paths as docs.rs, e.g.,
+/// `<base_url>/tokio/1.38.0/tokio/sync`.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct DocsHost {
+    /// The URL that the server serves the docs under, in place of
+    /// `https://docs.rs`.
+    pub base_url: String,
+    /// The prefixes of the names of the crates to fetch the docs for from the
+    /// server, e.g., `acme_`. When empty, the docs for every crate except
+    /// those of the standard library are fetched from it.
+    #[serde(default)]
+    pub crate_prefixes: Vec<String>,
+    /// The headers to send with each request to the server, e.g.,
+    /// `Authorization`.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// The headers to send with each request to the server, keyed by their
+    /// name, whose values are read from the given environment variables, so
+    /// that credentials can be kept out of the settings file.
+    #[serde(default)]
+    pub env_headers: BTreeMap<String, String>,
+}
+
+impl DocsHost {
+    /// Returns whether the docs for the given crate are fetched from this
+    /// server.
+    pub fn serves_crate(&self, crate_name: &str) -> bool {
+        if STANDARD_LIBRARY_CRATES.contains(&crate_name) {
+            return false;
+        }
+
+        let crate_name = normalize_crate_name(crate_name);
+        self.crate_prefixes.is_empty()
+            || self
+                .crate_prefixes
+                .iter()
+                .any(|prefix| crate_name.starts_with(&normalize_crate_name(prefix)))
+    }
+
+    /// Returns whether the given URL is on this server.
+    pub fn serves_url(&self, url: &str) -> bool {
+        url.strip_prefix(self.base_url())
+            .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'))
+    }
+
+    /// Returns the URL on this server for the given URL on docs.rs, or `None`
+    /// if it isn't on docs.rs.
+    pub fn docs_url(&self, docs_dot_rs_url: &str) -> Option<String> {
+        let path = docs_dot_rs_url.strip_prefix("https://docs.rs")?;
+        Some(format!("{}{path}", self.base_url()))
+    }
+
+    /// Returns the URL on docs.rs for the given URL on this server, or `None`
+    /// if it isn't on this server.
+    pub fn docs_dot_rs_url(&self, url: &str) -> Option<String> {
+        if !self.serves_url(url) {
+            return None;
+        }
+        Some(format!("https://docs.rs{}", &url[self.base_url().len()..]))
+    }
+
+    /// Returns the headers to send with each request to this server.
+    ///
+    /// The headers whose environment variable isn't set are left out.
+    pub fn request_headers(&self) -> Vec<(String, String)> {
+        let mut headers = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        for (name, env_var) in &self.env_headers {
+            match std::env::var(env_var) {
+                Ok(value) => headers.push((name.clone(), value)),
+                Err(_) => log::warn!(
+                    "not sending the {name} header to {base_url}, as ${env_var} isn't set",
+                    base_url = self.base_url
+                ),
+            }
+        }
+        headers
+    }
+
+    fn base_url(&self) -> &str {
+        self.base_url.trim_end_matches('/')
+    }
+}
+
+/// Returns the first of the given servers that serves the docs for the given
+/// crate, if any, or `None` if the crate's docs are fetched from docs.rs.
+pub fn docs_host_for_crate<'a>(hosts: &'a [DocsHost], crate_name: &str) -> Option<&'a DocsHost> {
+    hosts.iter().find(|host| host.serves_crate(crate_name))
+}
+
+/// Returns the name to look up the docs for the given crate under, which is
+/// the name of the crate it's an alias of in the given aliases, if it is one.
+///
+/// The names are compared as they appear in item paths, so that an alias
+/// matches however its hyphens are typed.
+pub fn canonical_crate_name<'a>(
+    aliases: &'a BTreeMap<String, String>,
+    crate_name: &'a str,
+) -> &'a str {
+    let normalized_name = normalize_crate_name(crate_name);
+    aliases
+        .iter()
+        .find(|(alias, _)| normalize_crate_name(alias) == normalized_name)
+        .map_or(crate_name, |(_, canonical_name)| canonical_name.as_str())
+}
+
+// The headers may contain credentials, so only their names are shown.
+impl std::fmt::Debug for DocsHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("DocsHost")
+            .field("base_url", &self.base_url)
+            .field("crate_prefixes", &self.crate_prefixes)
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .field("env_headers", &self.env_headers)
+            .finish()
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct RustdocSettingsContent {
+    /// Whether to append a link to the item's page on docs.rs, even when the
+    /// docs were sourced locally.
+    ///
+    /// Default: false
+    pub include_docs_rs_link: Option<bool>,
+    /// The number of hours after which locally indexed docs are considered
+    /// stale. When unset, indexed docs never go stale.
+    ///
+    /// Default: null
+    pub index_max_age_hours: Option<u64>,
+    /// What to do when loading docs from a stale index.
+    ///
+    /// Default: warn
+    pub stale_index_behavior: Option<StaleIndexBehavior>,
+    /// Whether to resolve item names that aren't qualified with a crate
+    /// name (e.g., `HashMap`) to their full path using the language server.
+    ///
+    /// Default: false
+    pub resolve_with_language_server: Option<bool>,
+    /// Whether to list the locally indexed items that are referenced in the
+    /// inserted docs, so that they can be inserted with `/rustdoc` too.
+    ///
+    /// Default: false
+    pub link_indexed_items: Option<bool>,
+    /// The maximum size of a docs.rs response, in megabytes. Larger responses
+    /// are rejected rather than read into memory.
+    ///
+    /// Default: 20
+    pub max_response_size_mb: Option<u64>,
+    /// The number of seconds to wait for a docs.rs response, including its
+    /// body, before retrying or giving up.
+    ///
+    /// Default: 15
+    pub request_timeout_secs: Option<u64>,
+    /// The number of minutes that cached local docs are kept for. When unset,
+    /// they never expire.
+    ///
+    /// Default: 5
+    pub local_cache_ttl_minutes: Option<u64>,
+    /// The number of hours that cached docs for the latest version of a crate
+    /// on docs.rs are kept for, after which they are fetched again. When
+    /// unset, they never expire.
+    ///
+    /// Expired docs are still used when docs.rs can't be reached. The docs for
+    /// a specific version of a crate never change, so they are always kept.
+    ///
+    /// Default: 24
+    pub latest_cache_ttl_hours: Option<u64>,
+    /// Whether to offer to insert the docs for the indexed crates that are
+    /// mentioned in a message sent to the assistant.
+    ///
+    /// Default: false
+    pub suggest_for_mentioned_crates: Option<bool>,
+    /// Whether to insert the local docs for an item's parent module when
+    /// there are no local docs for the item itself, rather than falling back
+    /// to docs.rs.
+    ///
+    /// Default: false
+    pub local_parent_module_fallback: Option<bool>,
+    /// What to do when inserting the docs for the same crate, version, and
+    /// item as docs that are already in the conversation.
+    ///
+    /// Default: insert
+    pub duplicate_docs_behavior: Option<DuplicateDocsBehavior>,
+    /// The release channel to fetch the docs for `std`, `core`, `alloc`, and
+    /// `proc_macro` from, e.g., "stable", "beta", "nightly", or a Rust version
+    /// like "1.78.0". Passing `--channel` to `/rustdoc` overrides it for that
+    /// lookup. When the docs for the channel can't be fetched, those for
+    /// "stable" are inserted instead.
+    ///
+    /// Default: stable
+    pub std_channel: Option<String>,
+    /// The maximum number of completions to offer for the crate or item
+    /// path passed to `/rustdoc`.
+    ///
+    /// Default: 100
+    pub max_completions: Option<usize>,
+    /// The maximum number of characters of docs to insert for an item. Longer
+    /// docs are cut at a heading where possible, with a note linking to the
+    /// rest. When unset, the docs are inserted in full.
+    ///
+    /// Default: 50000
+    pub max_docs_length: Option<usize>,
+    /// The maximum size of the indexed docs to keep in memory, in megabytes.
+    /// When exceeded, the docs for the least recently used crates are dropped
+    /// from memory, and reloaded from disk when they are next used. When
+    /// unset, all of the indexed docs are kept in memory.
+    ///
+    /// Default: null
+    pub index_memory_budget_mb: Option<u64>,
+    /// Whether to index the docs for a crate that were indexed from its local
+    /// docs again when they are loaded after `cargo doc` rebuilt them. Turning
+    /// this off skips checking when the local docs were last built.
+    ///
+    /// Default: true
+    pub reindex_changed_local_docs: Option<bool>,
+    /// The order to look up the docs for a crate in. The local docs are the
+    /// indexed docs and those built with `cargo doc`, which are for the
+    /// version in the `Cargo.lock`.
+    ///
+    /// Default: local-first
+    pub source_order: Option<DocsSourceOrder>,
+    /// The docs.rs-compatible servers to fetch the docs for some crates from,
+    /// instead of docs.rs. The docs for each crate are fetched from the first
+    /// server that serves it.
+    ///
+    /// Default: []
+    pub docs_hosts: Option<Vec<DocsHost>>,
+    /// The names to look up the docs for a crate under, keyed by the names
+    /// that may be typed for it instead, e.g., `{ "rand": "rand_core" }`.
+    ///
+    /// Default: {}
+    pub crate_aliases: Option<BTreeMap<String, String>>,
+}
+
+impl Settings for RustdocSettings {
+    const KEY: Option<&'static str> = Some("rustdoc");
+
+    type FileContent = RustdocSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_docs_hosts() {
+        let hosts = vec![
+            DocsHost {
+                base_url: "https://docs.example.com/".to_string(),
+                crate_prefixes: vec!["acme-".to_string()],
+                headers: [("Authorization".to_string(), "Bearer secret".to_string())]
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            },
+            DocsHost {
+                base_url: "https://mirror.example.com".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let host = docs_host_for_crate(&hosts, "acme_db").unwrap();
+        assert_eq!(host.base_url, "https://docs.example.com/");
+        assert_eq!(
+            host.docs_url("https://docs.rs/acme_db/latest/acme_db/"),
+            Some("https://docs.example.com/acme_db/latest/acme_db/".to_string())
+        );
+        assert_eq!(host.docs_url("https://doc.rust-lang.org/std/"), None);
+        assert_eq!(
+            host.docs_dot_rs_url("https://docs.example.com/acme_db/1.0.0/acme_db/"),
+            Some("https://docs.rs/acme_db/1.0.0/acme_db/".to_string())
+        );
+        assert_eq!(
+            host.docs_dot_rs_url("https://docs.example.community/acme_db/"),
+            None
+        );
+
+        let host = docs_host_for_crate(&hosts, "serde").unwrap();
+        assert_eq!(host.base_url, "https://mirror.example.com");
+        assert!(docs_host_for_crate(&hosts, "std").is_none());
+
+        let debug = format!("{:?}", hosts[0]);
+        assert!(debug.contains("Authorization"));
+        assert!(!debug.contains("secret"));
+    }
+
+    #[test]
+    fn test_crate_aliases() {
+        let aliases = [("tokio_ws", "tokio-tungstenite"), ("rand", "rand_core")]
+            .into_iter()
+            .map(|(alias, crate_name)| (alias.to_string(), crate_name.to_string()))
+            .collect();
+        assert_eq!(canonical_crate_name(&aliases, "rand"), "rand_core");
+        assert_eq!(
+            canonical_crate_name(&aliases, "tokio-ws"),
+            "tokio-tungstenite"
+        );
+        assert_eq!(canonical_crate_name(&aliases, "serde"), "serde");
+    }
+
+    #[test]
+    fn test_source_order() {
+        let arrange =
+            |order: DocsSourceOrder| order.arrange(vec!["store", "local"], vec!["docs.rs"]);
+        assert_eq!(
+            arrange(DocsSourceOrder::LocalFirst),
+            vec!["store", "local", "docs.rs"]
+        );
+        assert_eq!(
+            arrange(DocsSourceOrder::DocsRsFirst),
+            vec!["docs.rs", "store", "local"]
+        );
+        assert_eq!(arrange(DocsSourceOrder::LocalOnly), vec!["store", "local"]);
+        assert_eq!(arrange(DocsSourceOrder::DocsRsOnly), vec!["docs.rs"]);
+
+        assert_eq!(
+            serde_json::from_str::<DocsSourceOrder>("\"docs-rs-first\"").unwrap(),
+            DocsSourceOrder::DocsRsFirst
+        );
+    }
+}
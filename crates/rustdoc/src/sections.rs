@@ -0,0 +1,478 @@
+use std::ops::Range;
+
+use crate::AUTO_AND_BLANKET_IMPLS_HEADING;
+
+/// The kind of content in a [`DocsSection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocsSectionKind {
+    /// The description of the item, along with the rest of its docs that
+    /// aren't examples.
+    Description,
+    /// The examples of how to use the item.
+    Examples,
+    /// The items of one kind in a list of a crate's items, or a part of them
+    /// when there are many.
+    Items,
+    /// The auto trait and blanket impls of a type, which are the same for
+    /// most types.
+    AutoImpls,
+}
+
+/// A part of the Markdown docs for an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocsSection {
+    pub kind: DocsSectionKind,
+    /// The byte range of the section within the docs.
+    pub range: Range<usize>,
+}
+
+/// Splits the given Markdown docs into sections, separating the examples of
+/// the item (i.e., its `## Examples` section) and the group of its auto trait
+/// and blanket impls from the rest of its docs, so that each can be folded on
+/// its own.
+///
+/// The sections are in the order they appear in the docs. The examples end at
+/// the next heading of the same or a higher level, and the docs of the items
+/// on the page (e.g., the examples for each method) are left as they are.
+pub fn split_docs_sections(markdown: &str) -> Vec<DocsSection> {
+    let mut folded_sections = examples_range(markdown)
+        .map(|range| (DocsSectionKind::Examples, range))
+        .into_iter()
+        .chain(auto_impls_range(markdown).map(|range| (DocsSectionKind::AutoImpls, range)))
+        .collect::<Vec<_>>();
+    folded_sections.sort_by_key(|(_kind, range)| range.start);
+
+    let mut sections = Vec::new();
+    let mut offset = 0;
+    for (kind, range) in folded_sections {
+        if range.start < offset {
+            continue;
+        }
+        let description = markdown[offset..range.start].trim_end();
+        if !description.is_empty() {
+            sections.push(DocsSection {
+                kind: DocsSectionKind::Description,
+                range: offset..offset + description.len(),
+            });
+        }
+        let len = markdown[range.clone()].trim_end().len();
+        sections.push(DocsSection {
+            kind,
+            range: range.start..range.start + len,
+        });
+        offset = range.end;
+    }
+    if offset < markdown.len() || sections.is_empty() {
+        sections.push(DocsSection {
+            kind: DocsSectionKind::Description,
+            range: offset..markdown.len(),
+        });
+    }
+
+    sections
+}
+
+/// Splits the given Markdown list of a crate's items into sections, one for
+/// each kind of item (i.e., each `## ` heading) and one for the text before
+/// them, so that each can be folded on its own.
+///
+/// The kinds with more than `max_items` items are split further, into
+/// sections of at most `max_items` items each.
+pub fn split_item_list_sections(markdown: &str, max_items: usize) -> Vec<DocsSection> {
+    let mut sections = Vec::new();
+    let mut section_start = 0;
+    let mut item_count = 0;
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        let is_heading = line.starts_with("## ");
+        let is_item = line.starts_with("- ");
+        if is_heading || (is_item && item_count == max_items.max(1)) {
+            push_item_list_section(&mut sections, markdown, section_start..offset);
+            section_start = offset;
+            item_count = 0;
+        }
+        if is_item {
+            item_count += 1;
+        }
+        offset += line.len();
+    }
+    push_item_list_section(&mut sections, markdown, section_start..markdown.len());
+
+    sections
+}
+
+fn push_item_list_section(sections: &mut Vec<DocsSection>, markdown: &str, range: Range<usize>) {
+    let len = markdown[range.clone()].trim_end().len();
+    if len == 0 {
+        return;
+    }
+    sections.push(DocsSection {
+        kind: if sections.is_empty() && !markdown[range.clone()].starts_with("## ") {
+            DocsSectionKind::Description
+        } else {
+            DocsSectionKind::Items
+        },
+        range: range.start..range.start + len,
+    });
+}
+
+/// Returns the range of the `## Examples` section in the given Markdown, from
+/// its heading up to the next heading of the same or a higher level, ignoring
+/// any lines within code blocks that look like headings.
+fn examples_range(markdown: &str) -> Option<Range<usize>> {
+    let mut start = None;
+    let mut is_inside_code_block = false;
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        if line.starts_with("```") {
+            is_inside_code_block = !is_inside_code_block;
+        } else if !is_inside_code_block {
+            let heading = line
+                .strip_prefix("## ")
+                .or_else(|| line.strip_prefix("# "))
+                .map(str::trim);
+            match (start, heading) {
+                (None, Some("Examples" | "Example")) if line.starts_with("## ") => {
+                    start = Some(offset);
+                }
+                (Some(start), Some(_)) => return Some(start..offset),
+                _ => {}
+            }
+        }
+        offset += line.len();
+    }
+
+    start.map(|start| start..markdown.len())
+}
+
+/// Returns the range of the group of auto trait and blanket impls in the given
+/// Markdown, from its heading up to the next heading of the same or a higher
+/// level.
+fn auto_impls_range(markdown: &str) -> Option<Range<usize>> {
+    let heading = format!("### {AUTO_AND_BLANKET_IMPLS_HEADING} (");
+    let mut start = None;
+    let mut is_inside_code_block = false;
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        if line.starts_with("```") {
+            is_inside_code_block = !is_inside_code_block;
+        } else if !is_inside_code_block {
+            let is_heading = ["# ", "## ", "### "]
+                .iter()
+                .any(|prefix| line.starts_with(prefix));
+            match start {
+                None if line.starts_with(&heading) => start = Some(offset),
+                Some(start) if is_heading => return Some(start..offset),
+                _ => {}
+            }
+        }
+        offset += line.len();
+    }
+
+    start.map(|start| start..markdown.len())
+}
+
+/// Cuts the given Markdown docs to `max_len` characters if they are longer,
+/// appending a note with the number of characters that were omitted and
+/// where the full docs are, if known.
+///
+/// Returns whether the docs were truncated.
+pub fn truncate_docs(markdown: &mut String, max_len: usize, docs_url: Option<&str>) -> bool {
+    let Some(offset) = docs_truncation_offset(markdown, max_len) else {
+        return false;
+    };
+
+    let omitted_len = markdown[offset..].trim().chars().count();
+    markdown.truncate(offset);
+    markdown.push_str(&format!(
+        "\n\n\u{2026} (truncated, {omitted_len} characters omitted)"
+    ));
+    if let Some(docs_url) = docs_url {
+        markdown.push_str(&format!(". The full docs are at {docs_url}"));
+    }
+    true
+}
+
+/// Returns the byte offset to cut the given Markdown docs at, if they are
+/// longer than `max_len` characters.
+///
+/// The docs are cut before the last heading that keeps at least half of the
+/// allowed length, so that the summary at the top is kept and the lists that
+/// follow it (e.g., of the implementations) are dropped. Without such a
+/// heading, they are cut at the end of the last paragraph that fits, or else
+/// at `max_len` characters.
+fn docs_truncation_offset(markdown: &str, max_len: usize) -> Option<usize> {
+    let (max_offset, _) = markdown.char_indices().nth(max_len)?;
+
+    let mut heading_offset = None;
+    let mut paragraph_offset = None;
+    let mut is_inside_code_block = false;
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        if offset > max_offset {
+            break;
+        }
+        if line.starts_with("```") {
+            is_inside_code_block = !is_inside_code_block;
+        } else if !is_inside_code_block && offset > 0 {
+            if line.starts_with('#') {
+                heading_offset = Some(offset);
+            }
+            if line.trim().is_empty() {
+                paragraph_offset = Some(offset);
+            }
+        }
+        offset += line.len();
+    }
+
+    let offset = heading_offset
+        .filter(|offset| markdown[..*offset].chars().count() >= max_len / 2)
+        .or(paragraph_offset)
+        .unwrap_or(max_offset);
+    Some(markdown[..offset].trim_end().len())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn split(markdown: &str) -> Vec<(DocsSectionKind, &str)> {
+        split_docs_sections(markdown)
+            .into_iter()
+            .map(|section| (section.kind, &markdown[section.range]))
+            .collect()
+    }
+
+    #[test]
+    fn test_split_docs_sections() {
+        let markdown = indoc! {"
+            # Struct tokio::sync::Mutex
+
+            An asynchronous mutex.
+
+            ## Examples
+
+            ```rust
+            ## Not a heading
+            let mutex = Mutex::new(1);
+            ```
+
+            #### Examples
+
+            Nested headings are part of the examples.
+
+            ## Implementations
+
+            ### impl<T> Mutex<T>
+        "};
+
+        assert_eq!(
+            split(markdown),
+            vec![
+                (
+                    DocsSectionKind::Description,
+                    "# Struct tokio::sync::Mutex\n\nAn asynchronous mutex."
+                ),
+                (
+                    DocsSectionKind::Examples,
+                    indoc! {"
+                        ## Examples
+
+                        ```rust
+                        ## Not a heading
+                        let mutex = Mutex::new(1);
+                        ```
+
+                        #### Examples
+
+                        Nested headings are part of the examples."}
+                ),
+                (
+                    DocsSectionKind::Description,
+                    "## Implementations\n\n### impl<T> Mutex<T>\n"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_auto_impls() {
+        let markdown = indoc! {"
+            # Struct std::collections::HashMap
+
+            ## Implements
+
+            - `impl<K, V, S> Eq for HashMap<K, V, S>`
+
+            ### Auto & blanket implementations (2)
+
+            - `impl<K, V, S> Send for HashMap<K, V, S> where K: Send`
+            - `impl<T> Any for T where T: 'static + ?Sized`
+
+            ## See also
+        "};
+
+        assert_eq!(
+            split(markdown),
+            vec![
+                (
+                    DocsSectionKind::Description,
+                    indoc! {"
+                        # Struct std::collections::HashMap
+
+                        ## Implements
+
+                        - `impl<K, V, S> Eq for HashMap<K, V, S>`"}
+                ),
+                (
+                    DocsSectionKind::AutoImpls,
+                    indoc! {"
+                        ### Auto & blanket implementations (2)
+
+                        - `impl<K, V, S> Send for HashMap<K, V, S> where K: Send`
+                        - `impl<T> Any for T where T: 'static + ?Sized`"}
+                ),
+                (DocsSectionKind::Description, "## See also\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_docs_without_examples() {
+        let markdown = "# Function tokio::spawn\n\n#### Examples\n\nSpawning a task.";
+        assert_eq!(
+            split(markdown),
+            vec![(DocsSectionKind::Description, markdown)]
+        );
+
+        let markdown = "## Examples\n\nAt the end.";
+        assert_eq!(split(markdown), vec![(DocsSectionKind::Examples, markdown)]);
+    }
+
+    #[test]
+    fn test_split_item_list_sections() {
+        let markdown = indoc! {"
+            # All items in `serde_json`
+
+            4 public items.
+
+            ## Structs
+
+            - `serde_json::Deserializer`
+            - `serde_json::Map`
+            - `serde_json::Serializer`
+
+            ## Functions
+
+            - `serde_json::from_str`
+        "};
+        let split = |max_items| {
+            split_item_list_sections(markdown, max_items)
+                .into_iter()
+                .map(|section| (section.kind, &markdown[section.range]))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            split(10),
+            vec![
+                (
+                    DocsSectionKind::Description,
+                    "# All items in `serde_json`\n\n4 public items."
+                ),
+                (
+                    DocsSectionKind::Items,
+                    indoc! {"
+                        ## Structs
+
+                        - `serde_json::Deserializer`
+                        - `serde_json::Map`
+                        - `serde_json::Serializer`"}
+                ),
+                (
+                    DocsSectionKind::Items,
+                    "## Functions\n\n- `serde_json::from_str`"
+                ),
+            ]
+        );
+
+        // Long lists are split after every `max_items` items.
+        assert_eq!(
+            split(2)[1..],
+            [
+                (
+                    DocsSectionKind::Items,
+                    "## Structs\n\n- `serde_json::Deserializer`\n- `serde_json::Map`"
+                ),
+                (DocsSectionKind::Items, "- `serde_json::Serializer`"),
+                (
+                    DocsSectionKind::Items,
+                    "## Functions\n\n- `serde_json::from_str`"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_docs_truncation_offset() {
+        let markdown = indoc! {"
+            # Struct tokio::sync::Mutex
+
+            An asynchronous mutex.
+
+            ```rust
+            # fn main() {}
+            let mutex = Mutex::new(1);
+            ```
+
+            ## Implementations
+
+            ### impl<T> Mutex<T>
+
+            #### pub fn new(t: T) -> Mutex<T>
+        "};
+        assert_eq!(docs_truncation_offset(markdown, markdown.len()), None);
+
+        // The docs are cut before the last heading that fits, which isn't
+        // the commented-out line in the code block.
+        let truncate = |max_len| &markdown[..docs_truncation_offset(markdown, max_len).unwrap()];
+        let implementations = markdown.find("## Implementations").unwrap();
+        let method = markdown.find("#### pub fn").unwrap();
+        assert_eq!(
+            truncate(markdown.find("### impl").unwrap() - 1),
+            markdown[..implementations].trim_end()
+        );
+        assert_eq!(truncate(markdown.len() - 1), markdown[..method].trim_end());
+
+        // Without a heading that keeps enough of the docs, they are cut at the
+        // end of a paragraph, or else within one.
+        assert_eq!(truncate(40), "# Struct tokio::sync::Mutex");
+        let markdown = "a very long word ".repeat(10);
+        assert_eq!(
+            &markdown[..docs_truncation_offset(&markdown, 10).unwrap()],
+            "a very lon"
+        );
+    }
+
+    #[test]
+    fn test_truncate_docs() {
+        let mut markdown =
+            "# Module std::io\n\nTraits and helpers.\n\n## Structs\n\n- BufReader\n- BufWriter\n"
+                .to_string();
+        assert!(!truncate_docs(&mut markdown.clone(), markdown.len(), None));
+
+        assert!(truncate_docs(
+            &mut markdown,
+            40,
+            Some("https://doc.rust-lang.org/std/io/")
+        ));
+        assert_eq!(
+            markdown,
+            "# Module std::io\n\nTraits and helpers.\n\n\u{2026} (truncated, 35 characters omitted). \
+            The full docs are at https://doc.rust-lang.org/std/io/"
+        );
+    }
+}
@@ -1,22 +1,661 @@
-use std::sync::atomic::AtomicBool;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::SystemTime;
 
-use anyhow::{anyhow, Result};
-use collections::HashMap;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use fs::{Fs, RemoveOptions};
+use futures::future::Shared;
+use futures::{FutureExt, StreamExt};
 use fuzzy::StringMatchCandidate;
 use gpui::{AppContext, BackgroundExecutor, Global, ReadGlobal, Task, UpdateGlobal};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
-use crate::crawler::{RustdocCrawler, RustdocProvider};
-use crate::RustdocItem;
+use crate::crawler::{CrateDocs, RustdocCrawler, RustdocProvider, RustdocSource};
+use crate::persisted_index::{parse_persisted_index, serialize_persisted_index, PersistedDocs};
+use crate::query::reexport_note;
+use crate::{
+    normalize_crate_name, CrateFeature, DocProvider, DocQuery, NotFoundCache, RustdocItem,
+    RustdocItemKind,
+};
 
 struct GlobalRustdocStore(Arc<RustdocStore>);
 
 impl Global for GlobalRustdocStore {}
 
+#[derive(Debug, Clone)]
+struct IndexedDocs {
+    /// The docs, or `None` if they were evicted from memory to stay within
+    /// the memory budget, in which case they are in the persisted index.
+    markdown: Option<String>,
+    /// The declaration of the item, which is kept in memory for searching
+    /// even when the docs are evicted.
+    signature: Option<String>,
+    source: RustdocSource,
+    /// The version of the crate that the docs are for, if it is known.
+    version: Option<String>,
+    indexed_at: SystemTime,
+}
+
+impl IndexedDocs {
+    fn new(markdown: String, source: RustdocSource, indexed_at: SystemTime) -> Self {
+        Self {
+            signature: item_signature(&markdown),
+            markdown: Some(markdown),
+            source,
+            version: None,
+            indexed_at,
+        }
+    }
+
+    fn with_version(mut self, version: Option<String>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Returns the number of bytes of docs held in memory.
+    fn resident_size(&self) -> usize {
+        self.markdown.as_ref().map_or(0, String::len)
+    }
+}
+
+type IndexedDocsMap = HashMap<(String, RustdocItem), IndexedDocs>;
+
+/// The memory budget of a [`RustdocStore`], and when each crate's docs were
+/// last used, for evicting the least recently used ones.
+#[derive(Default)]
+struct MemoryBudget {
+    /// The maximum number of bytes of docs to hold in memory, if any.
+    max_bytes: Option<usize>,
+    tick: u64,
+    last_used: HashMap<String, u64>,
+}
+
+impl MemoryBudget {
+    /// Marks the docs for the given crate as the most recently used.
+    fn touch(&mut self, crate_name: &str) {
+        self.tick += 1;
+        self.last_used.insert(crate_name.to_string(), self.tick);
+    }
+
+    /// Returns the crates to evict for the docs of the given sizes to fit in
+    /// the budget, least recently used first.
+    ///
+    /// The most recently used crate is never evicted, even if it doesn't fit
+    /// on its own, as it is the one being worked with.
+    fn crates_to_evict(&self, crate_sizes: HashMap<String, usize>) -> Vec<String> {
+        let Some(max_bytes) = self.max_bytes else {
+            return Vec::new();
+        };
+        let mut usage = crate_sizes.values().sum::<usize>();
+        if usage <= max_bytes {
+            return Vec::new();
+        }
+
+        let mut crates = crate_sizes.into_iter().collect::<Vec<_>>();
+        crates.sort_by_key(|(crate_name, _size)| {
+            (
+                self.last_used.get(crate_name).copied().unwrap_or_default(),
+                crate_name.clone(),
+            )
+        });
+        crates.pop();
+
+        let mut evicted_crates = Vec::new();
+        for (crate_name, size) in crates {
+            if usage <= max_bytes {
+                break;
+            }
+            usage -= size;
+            evicted_crates.push(crate_name);
+        }
+        evicted_crates
+    }
+}
+
+/// Evicts the docs for the least recently used crates from memory until the
+/// rest fit in the memory budget.
+///
+/// The evicted docs are reloaded from the persisted index when they are next
+/// loaded, if there is one, and are otherwise removed from the store.
+fn enforce_memory_budget(
+    docs: &RwLock<IndexedDocsMap>,
+    memory_budget: &Mutex<MemoryBudget>,
+    can_reload: bool,
+) {
+    let mut memory_budget = memory_budget.lock();
+    let mut docs = docs.write();
+
+    let mut crate_sizes: HashMap<String, usize> = HashMap::default();
+    for ((crate_name, _item), item_docs) in docs.iter() {
+        if item_docs.markdown.is_some() {
+            *crate_sizes.entry(crate_name.clone()).or_default() += item_docs.resident_size();
+        }
+    }
+
+    let evicted_crates = memory_budget
+        .crates_to_evict(crate_sizes)
+        .into_iter()
+        .collect::<HashSet<_>>();
+    if evicted_crates.is_empty() {
+        return;
+    }
+
+    log::info!(
+        "evicting the docs for {} crates to stay within the rustdoc memory budget",
+        evicted_crates.len()
+    );
+    if can_reload {
+        for ((crate_name, _item), item_docs) in docs.iter_mut() {
+            if evicted_crates.contains(crate_name) {
+                item_docs.markdown = None;
+            }
+        }
+    } else {
+        docs.retain(|(crate_name, _item), _item_docs| !evicted_crates.contains(crate_name));
+        for crate_name in &evicted_crates {
+            memory_budget.last_used.remove(crate_name);
+        }
+    }
+}
+
+/// Inserts the docs for the items of the given crate into the store's docs.
+///
+/// The docs are inserted while holding the lock, so that the docs of crates
+/// indexed concurrently are never seen half-inserted.
+fn insert_crate_docs(
+    docs: &RwLock<IndexedDocsMap>,
+    crate_name: &str,
+    crate_docs: CrateDocs,
+    source: RustdocSource,
+    version: Option<&str>,
+    indexed_at: SystemTime,
+) {
+    let version = version.map(ToString::to_string);
+    let mut lock = docs.write();
+
+    // The re-exported items are stored at the paths they are re-exported at
+    // as well, so that they can be loaded by either.
+    for (reexport, item) in &crate_docs.reexports {
+        let Some(item_docs) = crate_docs.items.get(item) else {
+            continue;
+        };
+        let note = reexport_note(
+            &format!("{crate_name}::{}", reexport.display()),
+            &format!("{crate_name}::{}", item.display()),
+        );
+        lock.insert(
+            (crate_name.to_string(), reexport.clone()),
+            IndexedDocs::new(format!("{note}{item_docs}"), source, indexed_at)
+                .with_version(version.clone()),
+        );
+    }
+
+    for (item, item_docs) in crate_docs.items {
+        lock.insert(
+            (crate_name.to_string(), item),
+            IndexedDocs::new(item_docs, source, indexed_at).with_version(version.clone()),
+        );
+    }
+}
+
+/// Indexes the docs for the given crate from the given provider, persisting
+/// the index when the store has one.
+async fn index_crate(
+    docs: &RwLock<IndexedDocsMap>,
+    persisted_index: Option<&PersistedIndexDir>,
+    memory_budget: &Mutex<MemoryBudget>,
+    indexed_from: &RwLock<HashMap<String, IndexedFrom>>,
+    crate_name: &str,
+    provider: Arc<dyn RustdocProvider + Send + Sync>,
+) -> Result<()> {
+    let source = provider.source();
+    // The docs are checked for changes before crawling them, so that docs
+    // that are rebuilt while they are crawled are indexed again.
+    let modified_at = provider.modified_at(crate_name).await;
+    let crawler = RustdocCrawler::new(Box::new(provider.clone()));
+
+    log::debug!("indexing {crate_name}");
+
+    let Some(crate_docs) = crawler.crawl(crate_name.to_string()).await? else {
+        return Ok(());
+    };
+    // The version is only known once the crate was crawled, e.g., for the
+    // latest version on docs.rs.
+    let version = provider.version(crate_name).await;
+
+    insert_crate_docs(
+        docs,
+        crate_name,
+        crate_docs,
+        source,
+        version.as_deref(),
+        SystemTime::now(),
+    );
+    // The crate's pages that weren't found before it was indexed may exist
+    // now, e.g., in a newly published version.
+    NotFoundCache::global().remove_crate(crate_name);
+    let indexed_crate_name = normalize_crate_name(crate_name);
+    match modified_at {
+        Some(modified_at) => {
+            indexed_from.write().insert(
+                indexed_crate_name,
+                IndexedFrom {
+                    provider,
+                    modified_at,
+                },
+            );
+        }
+        None => {
+            indexed_from.write().remove(&indexed_crate_name);
+        }
+    }
+    memory_budget.lock().touch(crate_name);
+
+    // Write the crate's docs before evicting anything, so that they can be
+    // reloaded if they are evicted.
+    if let Some(persisted_index) = persisted_index {
+        persisted_index.write_crate(docs, crate_name).await?;
+    }
+    enforce_memory_budget(docs, memory_budget, persisted_index.is_some());
+
+    Ok(())
+}
+
+/// Indexes the docs for the given crate again if they changed since they were
+/// indexed, e.g., because `cargo doc` rebuilt them.
+///
+/// When they fail to be indexed again, the docs that were indexed before are
+/// kept.
+async fn reindex_if_changed(
+    docs: &RwLock<IndexedDocsMap>,
+    persisted_index: Option<&PersistedIndexDir>,
+    memory_budget: &Mutex<MemoryBudget>,
+    indexed_from: &RwLock<HashMap<String, IndexedFrom>>,
+    crate_name: &str,
+) {
+    let Some((provider, indexed_modified_at)) = indexed_from
+        .read()
+        .get(crate_name)
+        .map(|indexed_from| (indexed_from.provider.clone(), indexed_from.modified_at))
+    else {
+        return;
+    };
+    let Some(modified_at) = provider.modified_at(crate_name).await else {
+        return;
+    };
+    if modified_at <= indexed_modified_at {
+        return;
+    }
+
+    // The change is recorded up front, so that the docs loaded while they
+    // are indexed again don't index them yet again.
+    if let Some(indexed_from) = indexed_from.write().get_mut(crate_name) {
+        indexed_from.modified_at = modified_at;
+    }
+    log::debug!("the docs for {crate_name} changed since they were indexed, indexing them again");
+    if let Err(err) = index_crate(
+        docs,
+        persisted_index,
+        memory_budget,
+        indexed_from,
+        crate_name,
+        provider,
+    )
+    .await
+    {
+        log::error!("failed to index {crate_name} again: {err:?}");
+    }
+}
+
+/// Returns the docs for the only item in the given crate that has the same
+/// name as the item at the given path.
+///
+/// Returns an [`AmbiguousItemPath`] error listing the items when several of
+/// them have that name.
+fn find_item_by_name(
+    docs: &IndexedDocsMap,
+    crate_name: &str,
+    item_path: &str,
+) -> Result<((String, RustdocItem), Option<String>)> {
+    let name = item_path.rsplit("::").next().unwrap_or(item_path);
+    let mut candidates = docs
+        .iter()
+        .filter(|((item_crate_name, item), _)| item_crate_name == crate_name && &*item.name == name)
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|((_, item), _)| item.display());
+    // The same item may be indexed under several kinds, e.g., from its local
+    // docs and from docs.rs.
+    candidates.dedup_by_key(|((_, item), _)| item.display());
+
+    match candidates.as_slice() {
+        [] => Err(anyhow!("no docs found")),
+        [(key, item_docs)] => Ok(((*key).clone(), item_docs.markdown.clone())),
+        _ => Err(anyhow::Error::new(AmbiguousItemPath {
+            item_path: format!("{crate_name}::{item_path}"),
+            candidates: candidates
+                .iter()
+                .map(|((_, item), _)| format!("{crate_name}::{}", item.display()))
+                .collect(),
+        })),
+    }
+}
+
+/// Returns the note to put before the docs of an item that were found at
+/// another path than the one they were loaded for.
+fn resolved_path_note(item_path: &str, resolved_path: &str) -> String {
+    format!(
+        "> **Note:** `{item_path}` isn't documented at that path, so these are the docs \
+        for `{resolved_path}`, the only item with that name.\n\n"
+    )
+}
+
+/// An error for an item that isn't documented at the path it was loaded for,
+/// while several items in the crate have its name.
+#[derive(Debug)]
+struct AmbiguousItemPath {
+    item_path: String,
+    candidates: Vec<String>,
+}
+
+impl std::fmt::Display for AmbiguousItemPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let candidates = self
+            .candidates
+            .iter()
+            .map(|candidate| format!("`{candidate}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(
+            f,
+            "`{}` isn't documented at that path; did you mean one of {candidates}?",
+            self.item_path
+        )
+    }
+}
+
+impl std::error::Error for AmbiguousItemPath {}
+
+/// The provider that a crate's docs were indexed from, for indexing them again
+/// once they change.
+struct IndexedFrom {
+    provider: Arc<dyn RustdocProvider + Send + Sync>,
+    /// When the provider's docs were last modified, as of indexing them.
+    modified_at: SystemTime,
+}
+
+/// A crate whose docs have been indexed into the [`RustdocStore`].
+#[derive(Debug, Clone)]
+pub struct IndexedCrate {
+    pub name: String,
+    pub item_count: usize,
+    /// When the least recently indexed item in the crate was indexed.
+    pub indexed_at: SystemTime,
+    /// Where the most recently indexed docs for the crate came from.
+    pub source: RustdocSource,
+    /// The version of the crate that the most recently indexed docs are for,
+    /// if it is known.
+    pub version: Option<String>,
+}
+
+/// Where a [`RustdocStore`] persists its index: a directory with a file for
+/// each crate, so that indexing or removing a crate only rewrites that crate's
+/// file.
+#[derive(Clone)]
+struct PersistedIndexDir {
+    fs: Arc<dyn Fs>,
+    dir: PathBuf,
+    /// The lock held while each crate's file is written, so that the writes
+    /// for a crate indexed concurrently don't interleave, while the writes
+    /// for different crates don't wait for each other.
+    write_locks: Arc<Mutex<HashMap<String, Arc<futures::lock::Mutex<()>>>>>,
+}
+
+impl PersistedIndexDir {
+    fn new(fs: Arc<dyn Fs>, dir: PathBuf) -> Self {
+        Self {
+            fs,
+            dir,
+            write_locks: Arc::default(),
+        }
+    }
+
+    fn crate_path(&self, crate_name: &str) -> PathBuf {
+        self.dir.join(format!("{crate_name}.json"))
+    }
+
+    /// The file that the whole index was persisted to before it was split
+    /// into a file for each crate, next to the directory (e.g., `index.json`
+    /// for `index/`).
+    fn legacy_path(&self) -> PathBuf {
+        self.dir.with_extension("json")
+    }
+
+    fn write_lock(&self, crate_name: &str) -> Arc<futures::lock::Mutex<()>> {
+        self.write_locks
+            .lock()
+            .entry(crate_name.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Reads the docs for every crate in the persisted index.
+    ///
+    /// An index persisted to a single file is split into a file for each
+    /// crate first.
+    async fn read_all(&self) -> Result<Vec<PersistedDocs>> {
+        self.migrate_legacy_index().await?;
+
+        let Self { fs, dir, .. } = self;
+        let mut persisted_docs = Vec::new();
+        if !fs.is_dir(dir).await {
+            return Ok(persisted_docs);
+        }
+        let mut paths = fs
+            .read_dir(dir)
+            .await
+            .with_context(|| format!("failed to read rustdoc index from {dir:?}"))?;
+        while let Some(path) = paths.next().await {
+            let path = path?;
+            if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+                continue;
+            }
+            let contents = fs
+                .load(&path)
+                .await
+                .with_context(|| format!("failed to read rustdoc index from {path:?}"))?;
+            persisted_docs.extend(parse_persisted_index(&contents));
+        }
+        Ok(persisted_docs)
+    }
+
+    /// Reads the docs for the given crate in the persisted index, if any.
+    async fn read_crate(&self, crate_name: &str) -> Result<Vec<PersistedDocs>> {
+        let path = self.crate_path(crate_name);
+        if !self.fs.is_file(&path).await {
+            return Ok(Vec::new());
+        }
+
+        let contents = self
+            .fs
+            .load(&path)
+            .await
+            .with_context(|| format!("failed to read rustdoc index from {path:?}"))?;
+        Ok(parse_persisted_index(&contents))
+    }
+
+    /// Writes the given crate's docs to its file, replacing it, or removes the
+    /// file when the crate has no docs.
+    ///
+    /// The docs that were evicted from memory are carried over from the
+    /// crate's current file.
+    async fn write_crate(&self, docs: &RwLock<IndexedDocsMap>, crate_name: &str) -> Result<()> {
+        let write_lock = self.write_lock(crate_name);
+        let _write_guard = write_lock.lock().await;
+
+        // The docs are read in one go, as they may be evicted while the
+        // crate's current file is being read.
+        let snapshot = docs
+            .read()
+            .iter()
+            .filter(|((item_crate_name, _item), _item_docs)| item_crate_name == crate_name)
+            .map(|((_crate_name, item), item_docs)| {
+                (
+                    item.clone(),
+                    item_docs.markdown.clone(),
+                    item_docs.source,
+                    item_docs.version.clone(),
+                    item_docs.indexed_at,
+                )
+            })
+            .collect::<Vec<_>>();
+        if snapshot.is_empty() {
+            return self.remove_file(&self.crate_path(crate_name)).await;
+        }
+        let mut evicted_markdown = HashMap::default();
+        if snapshot
+            .iter()
+            .any(|(_, markdown, _, _, _)| markdown.is_none())
+        {
+            for persisted_docs in self.read_crate(crate_name).await? {
+                evicted_markdown.insert(persisted_docs.item, persisted_docs.markdown);
+            }
+        }
+
+        let persisted_docs = snapshot
+            .into_iter()
+            .filter_map(|(item, markdown, source, version, indexed_at)| {
+                let markdown = match markdown {
+                    Some(markdown) => markdown,
+                    None => evicted_markdown.remove(&item)?,
+                };
+                Some(PersistedDocs {
+                    crate_name: crate_name.to_string(),
+                    item,
+                    markdown,
+                    source,
+                    version,
+                    indexed_at,
+                })
+            })
+            .collect::<Vec<_>>();
+        let contents = serialize_persisted_index(&persisted_docs)?;
+
+        let path = self.crate_path(crate_name);
+        self.fs.create_dir(&self.dir).await?;
+        self.fs
+            .atomic_write(path.clone(), contents)
+            .await
+            .with_context(|| format!("failed to write rustdoc index to {path:?}"))
+    }
+
+    /// Removes the file for every crate, along with an index persisted to a
+    /// single file.
+    async fn clear(&self) -> Result<()> {
+        self.fs
+            .remove_dir(
+                &self.dir,
+                RemoveOptions {
+                    recursive: true,
+                    ignore_if_not_exists: true,
+                },
+            )
+            .await
+            .with_context(|| format!("failed to remove rustdoc index at {:?}", self.dir))?;
+        self.remove_file(&self.legacy_path()).await
+    }
+
+    async fn remove_file(&self, path: &Path) -> Result<()> {
+        self.fs
+            .remove_file(
+                path,
+                RemoveOptions {
+                    recursive: false,
+                    ignore_if_not_exists: true,
+                },
+            )
+            .await
+            .with_context(|| format!("failed to remove rustdoc index at {path:?}"))
+    }
+
+    /// Splits an index persisted to a single file into a file for each crate,
+    /// removing the single file once they are written.
+    async fn migrate_legacy_index(&self) -> Result<()> {
+        let legacy_path = self.legacy_path();
+        if !self.fs.is_file(&legacy_path).await {
+            return Ok(());
+        }
+
+        let contents = self
+            .fs
+            .load(&legacy_path)
+            .await
+            .with_context(|| format!("failed to read rustdoc index from {legacy_path:?}"))?;
+        let mut crates: BTreeMap<String, Vec<PersistedDocs>> = BTreeMap::default();
+        for persisted_docs in parse_persisted_index(&contents) {
+            crates
+                .entry(persisted_docs.crate_name.clone())
+                .or_default()
+                .push(persisted_docs);
+        }
+        log::info!(
+            "splitting the rustdoc index at {legacy_path:?} into a file for each of its {} crates",
+            crates.len()
+        );
+
+        self.fs.create_dir(&self.dir).await?;
+        for (crate_name, persisted_docs) in crates {
+            let path = self.crate_path(&crate_name);
+            self.fs
+                .atomic_write(path.clone(), serialize_persisted_index(&persisted_docs)?)
+                .await
+                .with_context(|| format!("failed to write rustdoc index to {path:?}"))?;
+        }
+        self.remove_file(&legacy_path).await
+    }
+
+    /// Reloads the evicted docs for the given crate from its file, without
+    /// reading those of the other crates.
+    async fn reload(&self, docs: &RwLock<IndexedDocsMap>, crate_name: &str) -> Result<()> {
+        let persisted_docs = self.read_crate(crate_name).await?;
+
+        let mut lock = docs.write();
+        for persisted_docs in persisted_docs {
+            if let Some(item_docs) = lock.get_mut(&(persisted_docs.crate_name, persisted_docs.item))
+            {
+                item_docs.markdown.get_or_insert(persisted_docs.markdown);
+            }
+        }
+
+        Ok(())
+    }
+}
+
 pub struct RustdocStore {
     executor: BackgroundExecutor,
-    docs: Arc<RwLock<HashMap<(String, RustdocItem), String>>>,
+    docs: Arc<RwLock<IndexedDocsMap>>,
+    persisted_index: Option<PersistedIndexDir>,
+    memory_budget: Arc<Mutex<MemoryBudget>>,
+    /// The features of the crates that were indexed from their local docs,
+    /// as read from their `Cargo.toml`.
+    ///
+    /// These aren't persisted, as the manifest is cheap to read again.
+    crate_features: Arc<RwLock<HashMap<String, Vec<CrateFeature>>>>,
+    /// The providers that the crates whose docs can change after they were
+    /// indexed, e.g., the local `cargo doc` output, were indexed from.
+    ///
+    /// These aren't persisted, so the docs restored from the persisted index
+    /// aren't indexed again when they change.
+    indexed_from: Arc<RwLock<HashMap<String, IndexedFrom>>>,
+    /// Whether to index the docs for a crate again when they are loaded after
+    /// they changed.
+    reindex_changed: Arc<AtomicBool>,
+    /// Resolves once the persisted index has been restored, if it is being
+    /// restored.
+    restored: RwLock<Shared<Task<()>>>,
 }
 
 impl RustdocStore {
@@ -24,34 +663,242 @@ impl RustdocStore {
         GlobalRustdocStore::global(cx).0.clone()
     }
 
-    pub fn init_global(cx: &mut AppContext) {
-        GlobalRustdocStore::set_global(
-            cx,
-            GlobalRustdocStore(Arc::new(Self::new(cx.background_executor().clone()))),
+    /// Initializes the global store, restoring the index persisted in the
+    /// given directory.
+    pub fn init_global(fs: Arc<dyn Fs>, index_dir: PathBuf, cx: &mut AppContext) {
+        let store = Arc::new(
+            Self::new(cx.background_executor().clone()).with_persisted_index(fs, index_dir),
         );
+        store.restore();
+        GlobalRustdocStore::set_global(cx, GlobalRustdocStore(store));
     }
 
     pub fn new(executor: BackgroundExecutor) -> Self {
         Self {
             executor,
             docs: Arc::new(RwLock::new(HashMap::default())),
+            persisted_index: None,
+            memory_budget: Arc::new(Mutex::new(MemoryBudget::default())),
+            crate_features: Arc::new(RwLock::new(HashMap::default())),
+            indexed_from: Arc::new(RwLock::new(HashMap::default())),
+            reindex_changed: Arc::new(AtomicBool::new(true)),
+            restored: RwLock::new(Task::ready(()).shared()),
         }
     }
 
+    /// Limits the docs held in memory to the given number of bytes, evicting
+    /// the docs for the least recently loaded crates when it is exceeded.
+    ///
+    /// With a persisted index, evicted docs are reloaded from it when they are
+    /// next loaded; without one, they are removed from the store. When `None`,
+    /// the docs in memory are unbounded.
+    pub fn set_memory_budget(&self, max_bytes: Option<usize>) {
+        self.memory_budget.lock().max_bytes = max_bytes;
+        enforce_memory_budget(
+            &self.docs,
+            &self.memory_budget,
+            self.persisted_index.is_some(),
+        );
+    }
+
+    /// Sets whether to index the docs for a crate again when they are loaded
+    /// after they changed, e.g., because `cargo doc` rebuilt them.
+    ///
+    /// Turning this off skips checking the docs for changes on every load.
+    pub fn set_reindex_changed_local_docs(&self, enabled: bool) {
+        self.reindex_changed.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns the number of bytes of docs held in memory.
+    pub fn memory_usage(&self) -> usize {
+        self.docs
+            .read()
+            .values()
+            .map(IndexedDocs::resident_size)
+            .sum()
+    }
+
+    /// Persists the index in the given directory, writing a crate's file
+    /// whenever the crate is indexed or removed.
+    pub fn with_persisted_index(mut self, fs: Arc<dyn Fs>, dir: PathBuf) -> Self {
+        self.persisted_index = Some(PersistedIndexDir::new(fs, dir));
+        self
+    }
+
+    /// Loads the persisted index into the store in the background.
+    ///
+    /// Loading, searching, and indexing docs wait for the index to be
+    /// restored, so that the persisted docs aren't missed or overwritten. Docs
+    /// that were indexed since the store was created are kept over the
+    /// persisted ones.
+    pub fn restore(&self) {
+        let Some(persisted_index) = self.persisted_index.clone() else {
+            return;
+        };
+
+        let docs = self.docs.clone();
+        let memory_budget = self.memory_budget.clone();
+        let restore = async move {
+            let persisted_docs = persisted_index.read_all().await?;
+
+            {
+                let mut lock = docs.write();
+                for persisted_docs in persisted_docs {
+                    lock.entry((persisted_docs.crate_name, persisted_docs.item))
+                        .or_insert_with(|| {
+                            IndexedDocs::new(
+                                persisted_docs.markdown,
+                                persisted_docs.source,
+                                persisted_docs.indexed_at,
+                            )
+                            .with_version(persisted_docs.version)
+                        });
+                }
+            }
+            enforce_memory_budget(&docs, &memory_budget, true);
+
+            anyhow::Ok(())
+        };
+        let restored = self
+            .executor
+            .spawn(async move {
+                if let Err(err) = restore.await {
+                    log::error!("failed to restore rustdoc index: {err:?}");
+                }
+            })
+            .shared();
+
+        *self.restored.write() = restored;
+    }
+
     pub fn load(&self, crate_name: String, item_path: Option<String>) -> Task<Result<String>> {
-        let item_docs = self
-            .docs
+        // Crates are indexed by the name in their item paths.
+        let crate_name = normalize_crate_name(&crate_name);
+        let restored = self.restored.read().clone();
+        let docs = self.docs.clone();
+        let persisted_index = self.persisted_index.clone();
+        let memory_budget = self.memory_budget.clone();
+        let indexed_from = self.indexed_from.clone();
+        let reindex_changed = self.reindex_changed.load(Ordering::Relaxed);
+        self.executor.spawn(async move {
+            restored.await;
+
+            if reindex_changed {
+                reindex_if_changed(
+                    &docs,
+                    persisted_index.as_ref(),
+                    &memory_budget,
+                    &indexed_from,
+                    &crate_name,
+                )
+                .await;
+            }
+
+            let item_docs = docs.read().iter().find_map(|(key, item_docs)| {
+                let (item_crate_name, item) = key;
+                if item_crate_name == &crate_name && item_path == Some(item.display()) {
+                    Some((key.clone(), item_docs.markdown.clone()))
+                } else {
+                    None
+                }
+            });
+            // Items that are re-exported with `#[doc(inline)]` or through a glob
+            // are documented at another path than the one they're used at, so
+            // they are looked up by their name instead.
+            let (key, markdown, resolved_path_note) = match item_docs {
+                Some((key, markdown)) => (key, markdown, None),
+                None => {
+                    let item_path = item_path
+                        .as_deref()
+                        .ok_or_else(|| anyhow!("no docs found"))?;
+                    let (key, markdown) = find_item_by_name(&docs.read(), &crate_name, item_path)?;
+                    let note = resolved_path_note(
+                        &format!("{crate_name}::{item_path}"),
+                        &format!("{crate_name}::{}", key.1.display()),
+                    );
+                    (key, markdown, Some(note))
+                }
+            };
+
+            let markdown = match (markdown, persisted_index.as_ref()) {
+                (Some(markdown), _) => markdown,
+                (None, Some(persisted_index)) => {
+                    persisted_index.reload(&docs, &crate_name).await?;
+                    docs.read()
+                        .get(&key)
+                        .and_then(|item_docs| item_docs.markdown.clone())
+                        .ok_or_else(|| anyhow!("no docs found"))?
+                }
+                (None, None) => return Err(anyhow!("no docs found")),
+            };
+
+            memory_budget.lock().touch(&crate_name);
+            enforce_memory_budget(&docs, &memory_budget, persisted_index.is_some());
+
+            Ok(match resolved_path_note {
+                Some(note) => format!("{note}{markdown}"),
+                None => markdown,
+            })
+        })
+    }
+
+    /// Returns when the docs for the given item were indexed, if they are in
+    /// the store.
+    pub fn indexed_at(&self, crate_name: &str, item_path: &str) -> Option<SystemTime> {
+        let crate_name = normalize_crate_name(crate_name);
+        self.docs
             .read()
             .iter()
             .find_map(|((item_crate_name, item), item_docs)| {
-                if item_crate_name == &crate_name && item_path == Some(item.display()) {
-                    Some(item_docs.clone())
+                if *item_crate_name == crate_name && item.display() == item_path {
+                    Some(item_docs.indexed_at)
                 } else {
                     None
                 }
-            });
+            })
+    }
+
+    /// Returns the crates that have been indexed, sorted by name.
+    pub fn indexed_crates(&self) -> Vec<IndexedCrate> {
+        // Each crate is paired with when its most recently indexed item was
+        // indexed, whose source and version the crate is listed with.
+        let mut indexed_crates: HashMap<String, (IndexedCrate, SystemTime)> = HashMap::default();
+        for ((crate_name, _item), item_docs) in self.docs.read().iter() {
+            let (indexed_crate, last_indexed_at) =
+                indexed_crates.entry(crate_name.clone()).or_insert_with(|| {
+                    let indexed_crate = IndexedCrate {
+                        name: crate_name.clone(),
+                        item_count: 0,
+                        indexed_at: item_docs.indexed_at,
+                        source: item_docs.source,
+                        version: item_docs.version.clone(),
+                    };
+                    (indexed_crate, item_docs.indexed_at)
+                });
+            indexed_crate.item_count += 1;
+            indexed_crate.indexed_at = indexed_crate.indexed_at.min(item_docs.indexed_at);
+            if item_docs.indexed_at > *last_indexed_at {
+                *last_indexed_at = item_docs.indexed_at;
+                indexed_crate.source = item_docs.source;
+                indexed_crate.version.clone_from(&item_docs.version);
+            }
+        }
 
-        Task::ready(item_docs.ok_or_else(|| anyhow!("no docs found")))
+        let mut indexed_crates = indexed_crates
+            .into_values()
+            .map(|(indexed_crate, _)| indexed_crate)
+            .collect::<Vec<_>>();
+        indexed_crates.sort_by(|a, b| a.name.cmp(&b.name));
+        indexed_crates
+    }
+
+    /// Returns how many crates to index at once when indexing several, e.g.,
+    /// all of the crates in a workspace.
+    ///
+    /// This is bounded by the number of CPUs, so as not to thrash the file
+    /// system.
+    pub fn max_concurrent_indexing(&self) -> usize {
+        self.executor.num_cpus().max(1)
     }
 
     pub fn index(
@@ -60,57 +907,1792 @@ impl RustdocStore {
         provider: Box<dyn RustdocProvider + Send + Sync + 'static>,
     ) -> Task<Result<()>> {
         let docs = self.docs.clone();
+        let persisted_index = self.persisted_index.clone();
+        let memory_budget = self.memory_budget.clone();
+        let indexed_from = self.indexed_from.clone();
+        let restored = self.restored.read().clone();
         self.executor.spawn(async move {
-            let crawler = RustdocCrawler::new(provider);
+            restored.await;
+
+            index_crate(
+                &docs,
+                persisted_index.as_ref(),
+                &memory_budget,
+                &indexed_from,
+                &crate_name,
+                Arc::from(provider),
+            )
+            .await
+        })
+    }
 
-            println!("Indexing {crate_name}");
+    /// Removes the docs for the given crate from the store, including from the
+    /// persisted index, so that they can be indexed afresh.
+    ///
+    /// Returns the number of items that were removed.
+    pub fn remove(&self, crate_name: String) -> Task<Result<usize>> {
+        self.crate_features.write().remove(&crate_name);
+        self.indexed_from
+            .write()
+            .remove(&normalize_crate_name(&crate_name));
+        let docs = self.docs.clone();
+        let persisted_index = self.persisted_index.clone();
+        let memory_budget = self.memory_budget.clone();
+        let restored = self.restored.read().clone();
+        self.executor.spawn(async move {
+            restored.await;
 
-            let Some(crate_docs) = crawler.crawl(crate_name.clone()).await? else {
-                return Ok(());
+            memory_budget.lock().last_used.remove(&crate_name);
+            let removed_count = {
+                let mut lock = docs.write();
+                let count_before = lock.len();
+                lock.retain(|(item_crate_name, _item), _item_docs| *item_crate_name != crate_name);
+                count_before - lock.len()
             };
 
-            let mut lock = docs.write();
+            if let Some(persisted_index) = persisted_index.filter(|_| removed_count > 0) {
+                persisted_index.write_crate(&docs, &crate_name).await?;
+            }
+
+            Ok(removed_count)
+        })
+    }
+
+    /// Removes the docs for every crate from the store, including from the
+    /// persisted index, so that a bad index can be recovered from.
+    ///
+    /// Returns the number of items that were removed.
+    pub fn clear(&self) -> Task<Result<usize>> {
+        self.crate_features.write().clear();
+        self.indexed_from.write().clear();
+        let docs = self.docs.clone();
+        let persisted_index = self.persisted_index.clone();
+        let memory_budget = self.memory_budget.clone();
+        let restored = self.restored.read().clone();
+        self.executor.spawn(async move {
+            restored.await;
+
+            memory_budget.lock().last_used.clear();
+            let removed_count = {
+                let mut lock = docs.write();
+                let count = lock.len();
+                lock.clear();
+                count
+            };
 
-            for (item, item_docs) in crate_docs.items {
-                lock.insert((crate_name.clone(), item), item_docs);
+            // The persisted index is removed even when the store was already
+            // empty, as it may hold docs that couldn't be restored.
+            if let Some(persisted_index) = persisted_index {
+                persisted_index.clear().await?;
             }
 
-            Ok(())
+            Ok(removed_count)
         })
     }
 
-    pub fn search(&self, query: String) -> Task<Vec<(String, RustdocItem)>> {
+    /// Records the features of the given crate, e.g., as read from its
+    /// `Cargo.toml` when indexing it.
+    pub fn set_crate_features(&self, crate_name: &str, features: Vec<CrateFeature>) {
+        self.crate_features
+            .write()
+            .insert(normalize_crate_name(crate_name), features);
+    }
+
+    /// Returns the features of the given crate, if they were recorded when
+    /// indexing it.
+    pub fn crate_features(&self, crate_name: &str) -> Option<Vec<CrateFeature>> {
+        self.crate_features
+            .read()
+            .get(&normalize_crate_name(crate_name))
+            .cloned()
+    }
+
+    /// Returns the indexed items that are referenced by name in the inline code
+    /// of the given Markdown, sorted by path.
+    pub fn referenced_items(&self, markdown: &str) -> Vec<(String, RustdocItem)> {
+        let identifiers = inline_code_identifiers(markdown);
+
+        let mut items = self
+            .docs
+            .read()
+            .keys()
+            .filter(|(_crate_name, item)| identifiers.contains(item.name.as_ref()))
+            .cloned()
+            .collect::<Vec<_>>();
+        items.sort();
+        items
+    }
+
+    /// Returns the names of the indexed crates that are mentioned in the given
+    /// text, sorted by name.
+    ///
+    /// Identifiers are matched against the crate names, so `tokio::spawn` is a
+    /// mention of `tokio`.
+    pub fn mentioned_crates(&self, text: &str) -> Vec<String> {
+        let identifiers = text
+            .split(|char: char| !(char.is_alphanumeric() || char == '_'))
+            .filter(|identifier| !identifier.is_empty())
+            .collect::<HashSet<_>>();
+
+        self.docs
+            .read()
+            .keys()
+            .map(|(crate_name, _item)| crate_name)
+            .filter(|crate_name| identifiers.contains(crate_name.as_str()))
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Returns up to `limit` of the names of the indexed crates that best match
+    /// the given crate name, e.g., to suggest when there are no docs for it.
+    ///
+    /// Returns nothing if the crate itself is indexed, as its name isn't why
+    /// its docs weren't found.
+    pub fn similar_crate_names(&self, crate_name: String, limit: usize) -> Task<Vec<String>> {
         let executor = self.executor.clone();
-        let docs = self.docs.read().clone();
+        let docs = self.docs.clone();
+        let restored = self.restored.read().clone();
         self.executor.spawn(async move {
-            if query.is_empty() {
+            restored.await;
+            let crate_name = normalize_crate_name(&crate_name);
+            let crate_names = docs
+                .read()
+                .keys()
+                .map(|(crate_name, _item)| crate_name.clone())
+                .collect::<BTreeSet<_>>();
+            if crate_name.is_empty() || limit == 0 || crate_names.contains(&crate_name) {
                 return Vec::new();
             }
 
-            let items = docs.keys().collect::<Vec<_>>();
-
-            let candidates = items
-                .iter()
+            let candidates = crate_names
+                .into_iter()
                 .enumerate()
-                .map(|(ix, (crate_name, item))| {
-                    StringMatchCandidate::new(ix, format!("{crate_name}::{}", item.display()))
-                })
+                .map(|(ix, crate_name)| StringMatchCandidate::new(ix, crate_name))
                 .collect::<Vec<_>>();
+            fuzzy::match_strings(
+                &candidates,
+                &crate_name,
+                false,
+                limit,
+                &AtomicBool::new(false),
+                executor,
+            )
+            .await
+            .into_iter()
+            .map(|mat| mat.string)
+            .collect()
+        })
+    }
+
+    /// Returns the names of the indexed crates and the given other crates
+    /// (e.g., the dependencies locked in the project's `Cargo.lock`s) that are
+    /// a few typos away from the given crate name, closest first, to correct
+    /// the name of a crate that there are no docs for.
+    ///
+    /// Returns nothing if the crate itself is one of them, as its name isn't
+    /// why its docs weren't found.
+    pub fn crate_name_corrections(
+        &self,
+        crate_name: String,
+        other_crate_names: Vec<String>,
+    ) -> Task<Vec<String>> {
+        let docs = self.docs.clone();
+        let restored = self.restored.read().clone();
+        self.executor.spawn(async move {
+            restored.await;
+            let mut crate_names = docs
+                .read()
+                .keys()
+                .map(|(crate_name, _item)| crate_name.clone())
+                .collect::<BTreeSet<_>>();
+            crate_names.extend(
+                other_crate_names
+                    .iter()
+                    .map(|crate_name| normalize_crate_name(crate_name)),
+            );
+            let crate_name = normalize_crate_name(&crate_name);
+            if crate_names.contains(&crate_name) {
+                return Vec::new();
+            }
+            near_crate_names(&crate_name, crate_names)
+        })
+    }
+
+    /// Searches the indexed items for the given query, returning up to `limit`
+    /// of the most relevant ones.
+    ///
+    /// Items are matched by name, unless `match_signatures` is set and the
+    /// query looks like part of a signature (e.g., `-> Result`), in which case
+    /// they are matched by their declaration instead.
+    ///
+    /// If a `scope` is given, only the items of the crates with those names
+    /// are searched, e.g., to leave out the crates indexed for other projects.
+    ///
+    /// The search stops early once the cancellation flag is set, returning an
+    /// empty or partial list of results.
+    pub fn search(
+        &self,
+        query: String,
+        limit: usize,
+        match_signatures: bool,
+        scope: Option<HashSet<String>>,
+        cancellation_flag: Arc<AtomicBool>,
+    ) -> Task<Vec<RustdocSearchResult>> {
+        let executor = self.executor.clone();
+        let docs = self.docs.clone();
+        let memory_budget = self.memory_budget.clone();
+        let restored = self.restored.read().clone();
+        self.executor.spawn(async move {
+            if query.is_empty() || limit == 0 || cancellation_flag.load(Ordering::Relaxed) {
+                return Vec::new();
+            }
+
+            restored.await;
+
+            // The docs are only read while the lock is held, rather than
+            // cloned, as this runs on every keystroke.
+            if match_signatures && is_signature_query(&query) {
+                let docs = docs.read();
+                let scoped_docs = scoped_docs(&docs, scope.as_ref());
+                let mut signatures = Vec::with_capacity(scoped_docs.len());
+                for (ix, (key, item_docs)) in scoped_docs.into_iter().enumerate() {
+                    if ix % 1024 == 0 && cancellation_flag.load(Ordering::Relaxed) {
+                        return Vec::new();
+                    }
+                    if let Some(signature) = item_docs.signature.as_deref() {
+                        signatures.push((key, signature));
+                    }
+                }
+
+                let results =
+                    rank_search_results(&query, signature_matches(&query, signatures), usize::MAX);
+                let mut results = dedup_search_results(&docs, results, limit);
+                add_signatures(&docs, &mut results);
+                touch_result_crates(&memory_budget, &results);
+                return results;
+            }
+
+            let (items, candidates) = {
+                let docs = docs.read();
+                let scoped_docs = scoped_docs(&docs, scope.as_ref());
+                let mut items = Vec::with_capacity(scoped_docs.len());
+                let mut candidates = Vec::with_capacity(scoped_docs.len());
+                for (ix, ((crate_name, item), _)) in scoped_docs.into_iter().enumerate() {
+                    // Building the candidates is slow for crates with many
+                    // items, so check in now and then.
+                    if ix % 1024 == 0 && cancellation_flag.load(Ordering::Relaxed) {
+                        return Vec::new();
+                    }
+                    candidates.push(StringMatchCandidate::new(
+                        ix,
+                        format!("{crate_name}::{}", item.display()),
+                    ));
+                    items.push((crate_name.clone(), item.clone()));
+                }
+                (items, candidates)
+            };
 
+            // Fetch more matches than are returned, as the best fuzzy matches
+            // aren't necessarily the most relevant ones, but stop well short of
+            // matching every item.
             let matches = fuzzy::match_strings(
                 &candidates,
                 &query,
                 false,
-                100,
-                &AtomicBool::default(),
+                limit.saturating_mul(SEARCH_CANDIDATES_PER_RESULT),
+                &cancellation_flag,
                 executor,
             )
             .await;
 
-            matches
-                .into_iter()
-                .map(|mat| items[mat.candidate_id].clone())
-                .collect()
+            // The same item may have been indexed more than once, e.g., from
+            // both its local docs and `docs.rs`, so all of the matches are
+            // ranked before the duplicates are dropped.
+            let docs = docs.read();
+            let results = rank_search_results(
+                &query,
+                matches.into_iter().map(|mat| {
+                    let (crate_name, item) = items[mat.candidate_id].clone();
+                    (crate_name, item, mat.score)
+                }),
+                usize::MAX,
+            );
+            let mut results = dedup_search_results(&docs, results, limit);
+            add_signatures(&docs, &mut results);
+            touch_result_crates(&memory_budget, &results);
+            results
         })
     }
 }
+
+/// Narrows the given scope of a search to the crate that the query starts
+/// with, if it's qualified with one, e.g., to `serde` for `serde::de`.
+///
+/// A crate that is outside of the given scope leaves nothing to search.
+pub fn crate_search_scope(query: &str, scope: Option<HashSet<String>>) -> Option<HashSet<String>> {
+    let Some(crate_name) = query
+        .split_once("::")
+        .map(|(crate_name, _)| crate_name.trim())
+        .filter(|crate_name| {
+            !crate_name.is_empty()
+                && crate_name
+                    .chars()
+                    .all(|char| char.is_alphanumeric() || char == '_' || char == '-')
+        })
+    else {
+        return scope;
+    };
+
+    let crate_name = normalize_crate_name(crate_name);
+    let is_in_scope = scope.as_ref().map_or(true, |scope| {
+        scope
+            .iter()
+            .any(|scoped_crate| normalize_crate_name(scoped_crate) == crate_name)
+    });
+    let mut crate_scope = HashSet::default();
+    if is_in_scope {
+        crate_scope.insert(crate_name);
+    }
+    Some(crate_scope)
+}
+
+/// Returns the indexed docs of the crates in the given scope, or all of them
+/// if there isn't one.
+fn scoped_docs<'a>(
+    docs: &'a IndexedDocsMap,
+    scope: Option<&HashSet<String>>,
+) -> Vec<(&'a (String, RustdocItem), &'a IndexedDocs)> {
+    let scope = scope.map(|scope| {
+        scope
+            .iter()
+            .map(|crate_name| normalize_crate_name(crate_name))
+            .collect::<HashSet<_>>()
+    });
+    docs.iter()
+        .filter(|((crate_name, _), _)| {
+            scope
+                .as_ref()
+                .map_or(true, |scope| scope.contains(crate_name.as_str()))
+        })
+        .collect()
+}
+
+/// Drops the search results for items that are already in the results under
+/// the same path, e.g., because they were indexed both from their local docs
+/// and from `docs.rs` and their kinds differ, and returns up to `limit` of
+/// them.
+///
+/// Each item keeps the position of its most relevant result, but the docs
+/// indexed locally are preferred over those from `docs.rs`.
+fn dedup_search_results(
+    docs: &IndexedDocsMap,
+    results: Vec<RustdocSearchResult>,
+    limit: usize,
+) -> Vec<RustdocSearchResult> {
+    let is_local = |result: &RustdocSearchResult| {
+        docs.get(&(result.crate_name.clone(), result.item.clone()))
+            .map_or(false, |item_docs| item_docs.source == RustdocSource::Local)
+    };
+
+    let mut deduped = Vec::new();
+    let mut positions = HashMap::default();
+    for result in results {
+        let path = (
+            normalize_crate_name(&result.crate_name),
+            result.item.display(),
+        );
+        match positions.get(&path) {
+            Some(&ix) => {
+                let kept = &mut deduped[ix];
+                if !is_local(kept) && is_local(&result) {
+                    *kept = RustdocSearchResult {
+                        score: kept.score,
+                        ..result
+                    };
+                }
+            }
+            None => {
+                // Once the results are full, a later duplicate can still
+                // replace one of them, but nothing else is added.
+                if deduped.len() < limit {
+                    positions.insert(path, deduped.len());
+                    deduped.push(result);
+                }
+            }
+        }
+    }
+    deduped
+}
+
+/// Fills in the signatures of the given search results.
+fn add_signatures(docs: &IndexedDocsMap, results: &mut [RustdocSearchResult]) {
+    for result in results {
+        result.signature = docs
+            .get(&(result.crate_name.clone(), result.item.clone()))
+            .and_then(|item_docs| item_docs.signature.clone());
+    }
+}
+
+/// Marks the crates of the given search results as used.
+///
+/// The crates of the best results are the most likely to be loaded next, so
+/// they are marked as used last.
+fn touch_result_crates(memory_budget: &Mutex<MemoryBudget>, results: &[RustdocSearchResult]) {
+    let mut memory_budget = memory_budget.lock();
+    for result in results.iter().rev() {
+        memory_budget.touch(&result.crate_name);
+    }
+}
+
+/// Returns the declaration of the item that the given docs are for, with its
+/// whitespace collapsed.
+///
+/// The declaration is the first `rs` code block, as examples are tagged as
+/// `rust`.
+fn item_signature(markdown: &str) -> Option<String> {
+    let mut lines = markdown.lines().skip_while(|line| *line != "```rs");
+    lines.next()?;
+    let signature = lines
+        .take_while(|line| !line.starts_with("```"))
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!signature.is_empty()).then_some(signature)
+}
+
+/// Returns whether the query looks like part of a signature, such as a return
+/// type (`-> Result`) or parameters (`(path: &Path`), rather than a name.
+fn is_signature_query(query: &str) -> bool {
+    query.contains("->") || query.contains('(')
+}
+
+/// Returns the items whose signature contains the query, ignoring case and
+/// whitespace.
+///
+/// Each match is scored by how much of the signature the query covers, so
+/// that shorter signatures, which match more closely, rank higher.
+fn signature_matches<'a>(
+    query: &str,
+    signatures: impl IntoIterator<Item = (&'a (String, RustdocItem), &'a str)>,
+) -> Vec<(String, RustdocItem, f64)> {
+    fn normalize(text: &str) -> String {
+        text.chars()
+            .filter(|char| !char.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect()
+    }
+
+    let query = normalize(query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    signatures
+        .into_iter()
+        .filter_map(|((crate_name, item), signature)| {
+            let signature = normalize(signature);
+            signature.contains(&query).then(|| {
+                (
+                    crate_name.clone(),
+                    item.clone(),
+                    query.len() as f64 / signature.len() as f64,
+                )
+            })
+        })
+        .collect()
+}
+
+/// The number of fuzzy matches that [`RustdocStore::search`] ranks for each
+/// result that it returns.
+const SEARCH_CANDIDATES_PER_RESULT: usize = 10;
+
+/// An item matching a [`RustdocStore::search`] query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RustdocSearchResult {
+    pub crate_name: String,
+    pub item: RustdocItem,
+    /// How relevant the item is to the query, where higher is better.
+    ///
+    /// The whole part ranks how the query matches (the crate name, then a
+    /// prefix of the crate name, then the exact name of the item, then a
+    /// substring of its path) and the fractional part is the quality of the
+    /// fuzzy match.
+    pub score: f64,
+    /// The declaration of the item, with its whitespace collapsed.
+    pub signature: Option<String>,
+}
+
+/// The maximum length of a [`RustdocSearchResult::short_signature`].
+const MAX_SHORT_SIGNATURE_LEN: usize = 80;
+
+/// The maximum length of the generics in a
+/// [`RustdocSearchResult::short_signature`], beyond which they are elided.
+const MAX_SHORT_GENERICS_LEN: usize = 24;
+
+impl RustdocSearchResult {
+    /// Returns the generics, parameters, and return type of the function that
+    /// the result is for, e.g., `(s: &str) -> Result<T>` for
+    /// `serde_json::from_str`.
+    ///
+    /// The `where` clause is left out, long generics are elided to `<…>`, and
+    /// the rest is truncated to [`MAX_SHORT_SIGNATURE_LEN`] characters.
+    pub fn short_signature(&self) -> Option<String> {
+        if self.item.kind != RustdocItemKind::Function {
+            return None;
+        }
+
+        let signature = self.signature.as_deref()?;
+        let (_, signature) = signature.split_once(&format!("fn {}", self.item.name))?;
+        let signature = signature
+            .split_once(" where ")
+            .map_or(signature, |(signature, _)| signature)
+            .trim_end();
+
+        let mut short_signature = String::new();
+        if signature.starts_with('<') {
+            let mut depth = 0;
+            let mut previous_char = None;
+            let generics_end = signature.char_indices().find_map(|(ix, char)| {
+                match char {
+                    '<' => depth += 1,
+                    // The `>` in `->` (e.g., in `F: Fn() -> T`) doesn't close
+                    // anything.
+                    '>' if previous_char != Some('-') => depth -= 1,
+                    _ => {}
+                }
+                previous_char = Some(char);
+                (depth == 0).then_some(ix + 1)
+            })?;
+            let generics = &signature[..generics_end];
+            if generics.chars().count() > MAX_SHORT_GENERICS_LEN {
+                short_signature.push_str("<…>");
+            } else {
+                short_signature.push_str(generics);
+            }
+            short_signature.push_str(&signature[generics_end..]);
+        } else {
+            short_signature.push_str(signature);
+        }
+
+        if short_signature.chars().count() > MAX_SHORT_SIGNATURE_LEN {
+            short_signature = short_signature
+                .chars()
+                .take(MAX_SHORT_SIGNATURE_LEN - 1)
+                .collect();
+            short_signature.push('…');
+        }
+        Some(short_signature)
+    }
+}
+
+/// The names among the candidates that are a few typos away from the given
+/// crate name, sorted from closest to furthest.
+///
+/// The number of typos allowed grows with the length of the name, and names
+/// shorter than three characters aren't matched, as nearly any other short
+/// name would be close to them. Letter case is ignored, as it is by
+/// `crates.io`.
+fn near_crate_names(crate_name: &str, candidates: impl IntoIterator<Item = String>) -> Vec<String> {
+    let crate_name = crate_name.to_lowercase().chars().collect::<Vec<_>>();
+    if crate_name.len() < 3 {
+        return Vec::new();
+    }
+    let max_distance = (crate_name.len() / 4).clamp(1, 3);
+
+    let mut matches = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(
+                &crate_name,
+                &candidate.to_lowercase().chars().collect::<Vec<_>>(),
+            );
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect::<Vec<_>>();
+    matches.sort();
+    matches.dedup();
+    matches
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// The number of characters that have to be inserted, removed, replaced, or
+/// swapped with their neighbor to turn one string into the other.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    // The distances from each prefix of `a` to the prefixes of `b` so far,
+    // keeping the two rows before the current one to count swaps.
+    let mut previous_previous_row = vec![0; b.len() + 1];
+    let mut previous_row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution_cost = usize::from(a_char != b_char);
+            row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(row[j] + 1);
+            if i > 0 && j > 0 && *a_char == b[j - 1] && a[i - 1] == *b_char {
+                row[j + 1] = row[j + 1].min(previous_previous_row[j - 1] + 1);
+            }
+        }
+        previous_previous_row = std::mem::replace(&mut previous_row, row);
+    }
+    previous_row[b.len()]
+}
+
+/// Scores the given fuzzy matches for the query, and returns up to `limit` of
+/// them, sorted from most to least relevant.
+///
+/// Matches that are equally relevant are sorted with the shortest paths first,
+/// so that top-level items come before deeply nested ones.
+fn rank_search_results(
+    query: &str,
+    matches: impl IntoIterator<Item = (String, RustdocItem, f64)>,
+    limit: usize,
+) -> Vec<RustdocSearchResult> {
+    let query = query.to_lowercase();
+    let (query_crate_name, query_item_name) = match query.split_once("::") {
+        Some((crate_name, item_path)) => (
+            normalize_crate_name(crate_name),
+            item_path.rsplit("::").next().unwrap_or(item_path),
+        ),
+        None => (normalize_crate_name(&query), query.as_str()),
+    };
+
+    let mut results = matches
+        .into_iter()
+        .map(|(crate_name, item, fuzzy_score)| {
+            let path = format!("{crate_name}::{}", item.display()).to_lowercase();
+            let crate_name_lowercase = crate_name.to_lowercase();
+            let tier = if crate_name_lowercase == query_crate_name {
+                4
+            } else if crate_name_lowercase.starts_with(&query_crate_name) {
+                3
+            } else if item.name.to_lowercase() == query_item_name {
+                2
+            } else if path.contains(&query) {
+                1
+            } else {
+                0
+            };
+
+            RustdocSearchResult {
+                crate_name,
+                item,
+                score: tier as f64 + fuzzy_score.clamp(0., 1.) * 0.99,
+                signature: None,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    results.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.item.path.len().cmp(&b.item.path.len()))
+            .then_with(|| a.crate_name.cmp(&b.crate_name))
+            .then_with(|| a.item.cmp(&b.item))
+    });
+    results.truncate(limit);
+    results
+}
+
+/// Waits for the given timer to fire, e.g., for typing to pause, and returns
+/// whether the search for a query should still run, i.e., whether it wasn't
+/// cancelled by a newer query in the meantime.
+///
+/// This keeps each keystroke from running a search over large indexes, as only
+/// the last of several queries in quick succession is searched for.
+pub async fn debounce_search(
+    timer: impl Future<Output = ()>,
+    cancellation_flag: &AtomicBool,
+) -> bool {
+    timer.await;
+    !cancellation_flag.load(Ordering::Relaxed)
+}
+
+/// Returns the identifiers that appear in inline code spans in the given Markdown.
+fn inline_code_identifiers(markdown: &str) -> HashSet<&str> {
+    let mut identifiers = HashSet::default();
+    let mut is_inside_code_block = false;
+    for line in markdown.lines() {
+        if line.starts_with("```") {
+            is_inside_code_block = !is_inside_code_block;
+            continue;
+        }
+        if is_inside_code_block {
+            continue;
+        }
+
+        for inline_code in line.split('`').skip(1).step_by(2) {
+            identifiers.extend(
+                inline_code
+                    .split(|char: char| !(char.is_alphanumeric() || char == '_'))
+                    .filter(|identifier| !identifier.is_empty()),
+            );
+        }
+    }
+    identifiers
+}
+
+#[async_trait]
+impl DocProvider for RustdocStore {
+    fn name(&self) -> String {
+        "the index".to_string()
+    }
+
+    async fn fetch(&self, query: &DocQuery) -> Result<Option<(RustdocSource, String)>> {
+        let item_docs = self
+            .load(query.crate_name.clone(), Some(query.item_path.join("::")))
+            .await;
+
+        match item_docs {
+            Ok(docs) => Ok(Some((RustdocSource::Local, docs))),
+            // The items that the path may have meant say more than that the
+            // docs weren't found.
+            Err(err) if err.is::<AmbiguousItemPath>() => Err(err),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use indexmap::IndexMap;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn item(kind: RustdocItemKind, path: &[&str], name: &str) -> RustdocItem {
+        RustdocItem {
+            kind,
+            path: path.iter().map(|segment| (*segment).into()).collect(),
+            name: name.into(),
+        }
+    }
+
+    fn ranked_paths(query: &str, matches: Vec<(&str, RustdocItem, f64)>) -> Vec<String> {
+        rank_search_results(
+            query,
+            matches
+                .into_iter()
+                .map(|(crate_name, item, score)| (crate_name.to_string(), item, score)),
+            usize::MAX,
+        )
+        .into_iter()
+        .map(|result| format!("{}::{}", result.crate_name, result.item.display()))
+        .collect()
+    }
+
+    #[test]
+    fn test_debounce_search() {
+        // Each query cancels the search for the one before it once typed.
+        let cancellation_flags = (0..3)
+            .map(|_| Arc::new(AtomicBool::new(false)))
+            .collect::<Vec<_>>();
+        let (timer_tx, timer_rx) = futures::channel::oneshot::channel::<()>();
+        let timer = timer_rx.map(|_| ()).shared();
+        let searches = cancellation_flags
+            .iter()
+            .enumerate()
+            .map(|(ix, cancellation_flag)| {
+                if let Some(previous_flag) = ix.checked_sub(1) {
+                    cancellation_flags[previous_flag].store(true, Ordering::Relaxed);
+                }
+                debounce_search(timer.clone(), cancellation_flag)
+            })
+            .collect::<Vec<_>>();
+
+        // The timers fire once typing pauses, after the last query.
+        timer_tx.send(()).unwrap();
+        assert_eq!(
+            futures::executor::block_on(futures::future::join_all(searches)),
+            vec![false, false, true]
+        );
+    }
+
+    #[test]
+    fn test_crates_to_evict() {
+        let crate_sizes = |sizes: &[(&str, usize)]| {
+            sizes
+                .iter()
+                .map(|(crate_name, size)| (crate_name.to_string(), *size))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let mut memory_budget = MemoryBudget::default();
+        memory_budget.touch("serde");
+        memory_budget.touch("tokio");
+        memory_budget.touch("axum");
+        memory_budget.touch("serde");
+        let sizes = crate_sizes(&[("serde", 40), ("tokio", 50), ("axum", 30)]);
+
+        // Without a budget, nothing is evicted.
+        assert_eq!(
+            memory_budget.crates_to_evict(sizes.clone()),
+            Vec::<String>::new()
+        );
+
+        // The least recently used crates are evicted until the rest fit.
+        memory_budget.max_bytes = Some(80);
+        assert_eq!(memory_budget.crates_to_evict(sizes.clone()), vec!["tokio"]);
+        memory_budget.max_bytes = Some(50);
+        assert_eq!(
+            memory_budget.crates_to_evict(sizes.clone()),
+            vec!["tokio", "axum"]
+        );
+
+        // The most recently used crate is kept, even if it doesn't fit.
+        memory_budget.max_bytes = Some(10);
+        assert_eq!(memory_budget.crates_to_evict(sizes), vec!["tokio", "axum"]);
+
+        // Crates that haven't been used since they were restored go first.
+        assert_eq!(
+            memory_budget.crates_to_evict(crate_sizes(&[("serde", 40), ("rand", 20)])),
+            vec!["rand"]
+        );
+    }
+
+    #[test]
+    fn test_concurrent_indexing() {
+        fn crate_docs(crate_name: &str) -> CrateDocs {
+            let mut items = IndexMap::default();
+            for name in ["Foo", "Bar", "Baz"] {
+                items.insert(
+                    item(RustdocItemKind::Struct, &["types"], name),
+                    format!("# Struct {crate_name}::types::{name}"),
+                );
+            }
+            let mut reexports = IndexMap::default();
+            reexports.insert(
+                item(RustdocItemKind::Struct, &[], "Foo"),
+                item(RustdocItemKind::Struct, &["types"], "Foo"),
+            );
+            CrateDocs {
+                crate_root_markdown: format!("# Crate {crate_name}"),
+                items,
+                reexports,
+            }
+        }
+
+        fn store_state(docs: &RwLock<IndexedDocsMap>) -> Vec<(String, String, Option<String>)> {
+            let mut state = docs
+                .read()
+                .iter()
+                .map(|((crate_name, item), item_docs)| {
+                    (
+                        crate_name.clone(),
+                        item.display(),
+                        item_docs.markdown.clone(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            state.sort();
+            state
+        }
+
+        let crate_names = (0..16).map(|ix| format!("crate_{ix}")).collect::<Vec<_>>();
+        let indexed_at = SystemTime::now();
+
+        let sequential_docs = RwLock::new(IndexedDocsMap::default());
+        for crate_name in &crate_names {
+            insert_crate_docs(
+                &sequential_docs,
+                crate_name,
+                crate_docs(crate_name),
+                RustdocSource::Local,
+                None,
+                indexed_at,
+            );
+        }
+
+        let concurrent_docs = RwLock::new(IndexedDocsMap::default());
+        std::thread::scope(|scope| {
+            for crate_name in &crate_names {
+                let concurrent_docs = &concurrent_docs;
+                scope.spawn(move || {
+                    insert_crate_docs(
+                        concurrent_docs,
+                        crate_name,
+                        crate_docs(crate_name),
+                        RustdocSource::Local,
+                        None,
+                        indexed_at,
+                    )
+                });
+            }
+        });
+
+        let sequential_state = store_state(&sequential_docs);
+        assert_eq!(sequential_state.len(), crate_names.len() * 4);
+        assert_eq!(store_state(&concurrent_docs), sequential_state);
+    }
+
+    #[test]
+    fn test_near_crate_names() {
+        let candidates = || {
+            [
+                "tokio",
+                "tokio_util",
+                "tokio_stream",
+                "serde",
+                "serde_json",
+                "std",
+            ]
+            .map(ToString::to_string)
+        };
+        // Swapped, missing, and extra characters are all typos.
+        assert_eq!(near_crate_names("tokoi", candidates()), vec!["tokio"]);
+        assert_eq!(
+            near_crate_names("tokio_utl", candidates()),
+            vec!["tokio_util"]
+        );
+        assert_eq!(near_crate_names("Serdes", candidates()), vec!["serde"]);
+        assert_eq!(
+            near_crate_names("serde_jsno", candidates()),
+            vec!["serde_json"]
+        );
+        // Longer names allow for more typos, and can be near several crates.
+        assert_eq!(
+            near_crate_names("tokio_utilz", candidates()),
+            vec!["tokio_util"]
+        );
+        assert_eq!(
+            near_crate_names(
+                "tokio_utl",
+                ["tokio_utils".to_string()].into_iter().chain(candidates())
+            ),
+            vec!["tokio_util", "tokio_utils"]
+        );
+        assert_eq!(near_crate_names("axum", candidates()), Vec::<String>::new());
+        // Names this short would be near almost any other.
+        assert_eq!(near_crate_names("st", candidates()), Vec::<String>::new());
+    }
+
+    #[gpui::test]
+    async fn test_crate_name_corrections(cx: &mut TestAppContext) {
+        let store = RustdocStore::new(cx.executor());
+        let mut items = IndexMap::default();
+        items.insert(
+            item(RustdocItemKind::Mod, &[], "codec"),
+            "# Module tokio_util::codec".to_string(),
+        );
+        let crate_docs = CrateDocs {
+            crate_root_markdown: "# Crate tokio_util".to_string(),
+            items,
+            reexports: IndexMap::default(),
+        };
+        insert_crate_docs(
+            &store.docs,
+            "tokio_util",
+            crate_docs,
+            RustdocSource::Local,
+            None,
+            SystemTime::now(),
+        );
+
+        assert_eq!(
+            store
+                .crate_name_corrections("tokio-utl".to_string(), vec!["serde-json".to_string()])
+                .await,
+            vec!["tokio_util"]
+        );
+        // The other crates are matched by their normalized names.
+        assert_eq!(
+            store
+                .crate_name_corrections("serde_jsn".to_string(), vec!["serde-json".to_string()])
+                .await,
+            vec!["serde_json"]
+        );
+        // The crate's docs weren't found for another reason than its name.
+        assert_eq!(
+            store
+                .crate_name_corrections("serde_json".to_string(), vec!["serde-json".to_string()])
+                .await,
+            Vec::<String>::new()
+        );
+    }
+
+    #[gpui::test]
+    async fn test_similar_crate_names(cx: &mut TestAppContext) {
+        let store = RustdocStore::new(cx.executor());
+        for crate_name in ["tokio", "tokio_tungstenite", "serde"] {
+            let mut items = IndexMap::default();
+            items.insert(
+                item(RustdocItemKind::Mod, &[], "prelude"),
+                format!("# Module {crate_name}::prelude"),
+            );
+            let crate_docs = CrateDocs {
+                crate_root_markdown: format!("# Crate {crate_name}"),
+                items,
+                reexports: IndexMap::default(),
+            };
+            insert_crate_docs(
+                &store.docs,
+                crate_name,
+                crate_docs,
+                RustdocSource::Local,
+                None,
+                SystemTime::now(),
+            );
+        }
+
+        assert_eq!(
+            store
+                .similar_crate_names("tokio-tungstenit".to_string(), 5)
+                .await,
+            vec!["tokio_tungstenite"]
+        );
+        assert_eq!(
+            store.similar_crate_names("serde_json".to_string(), 5).await,
+            Vec::<String>::new()
+        );
+        // The crate's docs weren't found for another reason than its name.
+        assert_eq!(
+            store.similar_crate_names("tokio".to_string(), 5).await,
+            Vec::<String>::new()
+        );
+    }
+
+    #[gpui::test]
+    async fn test_reindex_changed_local_docs(cx: &mut TestAppContext) {
+        struct ChangingProvider {
+            markdown: Mutex<&'static str>,
+            modified_at: Mutex<SystemTime>,
+        }
+
+        #[async_trait]
+        impl RustdocProvider for ChangingProvider {
+            async fn fetch_page(
+                &self,
+                _crate_name: &str,
+                _item: Option<&RustdocItem>,
+            ) -> Result<Option<String>> {
+                Ok(None)
+            }
+
+            async fn fetch_crate_docs(&self, crate_name: &str) -> Result<Option<CrateDocs>> {
+                let mut items = IndexMap::default();
+                items.insert(
+                    item(RustdocItemKind::Struct, &[], "Foo"),
+                    self.markdown.lock().to_string(),
+                );
+                Ok(Some(CrateDocs {
+                    crate_root_markdown: format!("# Crate {crate_name}"),
+                    items,
+                    reexports: IndexMap::default(),
+                }))
+            }
+
+            fn source(&self) -> RustdocSource {
+                RustdocSource::Local
+            }
+
+            async fn modified_at(&self, _crate_name: &str) -> Option<SystemTime> {
+                Some(*self.modified_at.lock())
+            }
+        }
+
+        let store = RustdocStore::new(cx.executor());
+        let provider = Arc::new(ChangingProvider {
+            markdown: Mutex::new("# Struct foo::Foo"),
+            modified_at: Mutex::new(SystemTime::UNIX_EPOCH),
+        });
+        store
+            .index("foo".to_string(), Box::new(provider.clone()))
+            .await
+            .unwrap();
+        let load = || store.load("foo".to_string(), Some("Foo".to_string()));
+        assert_eq!(load().await.unwrap(), "# Struct foo::Foo");
+
+        // The docs aren't indexed again until they are rebuilt.
+        *provider.markdown.lock() = "# Struct foo::Foo\n\nRebuilt.";
+        assert_eq!(load().await.unwrap(), "# Struct foo::Foo");
+
+        *provider.modified_at.lock() += Duration::from_secs(1);
+        assert_eq!(load().await.unwrap(), "# Struct foo::Foo\n\nRebuilt.");
+
+        // With the check turned off, the indexed docs are loaded as they are.
+        store.set_reindex_changed_local_docs(false);
+        *provider.markdown.lock() = "# Struct foo::Foo\n\nRebuilt again.";
+        *provider.modified_at.lock() += Duration::from_secs(1);
+        assert_eq!(load().await.unwrap(), "# Struct foo::Foo\n\nRebuilt.");
+    }
+
+    /// A provider of the same docs for every crate, with a single struct.
+    struct StructProvider;
+
+    #[async_trait]
+    impl RustdocProvider for StructProvider {
+        async fn fetch_page(
+            &self,
+            _crate_name: &str,
+            _item: Option<&RustdocItem>,
+        ) -> Result<Option<String>> {
+            Ok(None)
+        }
+
+        async fn fetch_crate_docs(&self, crate_name: &str) -> Result<Option<CrateDocs>> {
+            let mut items = IndexMap::default();
+            items.insert(
+                item(RustdocItemKind::Struct, &[], "Foo"),
+                format!("# Struct {crate_name}::Foo"),
+            );
+            Ok(Some(CrateDocs {
+                crate_root_markdown: format!("# Crate {crate_name}"),
+                items,
+                reexports: IndexMap::default(),
+            }))
+        }
+
+        fn source(&self) -> RustdocSource {
+            RustdocSource::Local
+        }
+    }
+
+    #[gpui::test]
+    async fn test_persisted_index_per_crate(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let store = RustdocStore::new(cx.executor())
+            .with_persisted_index(fs.clone(), PathBuf::from("/index"));
+        store
+            .index("serde".to_string(), Box::new(StructProvider))
+            .await
+            .unwrap();
+
+        // Indexing another crate leaves the file of the first one as it is.
+        let serde_path = Path::new("/index/serde.json");
+        let serde_contents = fs
+            .load(serde_path)
+            .await
+            .unwrap()
+            .replace("# Struct serde::Foo", "# Struct serde::Foo (unchanged)");
+        fs.insert_file(serde_path, serde_contents.clone().into_bytes())
+            .await;
+        store
+            .index("tokio".to_string(), Box::new(StructProvider))
+            .await
+            .unwrap();
+        assert_eq!(fs.load(serde_path).await.unwrap(), serde_contents);
+        assert!(fs.is_file(Path::new("/index/tokio.json")).await);
+
+        // As does removing another crate.
+        store.remove("tokio".to_string()).await.unwrap();
+        assert!(!fs.is_file(Path::new("/index/tokio.json")).await);
+        assert_eq!(fs.load(serde_path).await.unwrap(), serde_contents);
+
+        let restored_store = RustdocStore::new(cx.executor())
+            .with_persisted_index(fs.clone(), PathBuf::from("/index"));
+        restored_store.restore();
+        assert_eq!(
+            restored_store
+                .load("serde".to_string(), Some("Foo".to_string()))
+                .await
+                .unwrap(),
+            "# Struct serde::Foo (unchanged)"
+        );
+        assert!(restored_store
+            .load("tokio".to_string(), Some("Foo".to_string()))
+            .await
+            .is_err());
+
+        store.clear().await.unwrap();
+        assert!(!fs.is_file(serde_path).await);
+    }
+
+    #[gpui::test]
+    async fn test_reload_evicted_crate(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let store = RustdocStore::new(cx.executor())
+            .with_persisted_index(fs.clone(), PathBuf::from("/index"));
+        store.set_memory_budget(Some(1));
+        for crate_name in ["serde", "tokio"] {
+            store
+                .index(crate_name.to_string(), Box::new(StructProvider))
+                .await
+                .unwrap();
+        }
+        // Only the most recently indexed crate is kept in memory.
+        assert_eq!(store.memory_usage(), "# Struct tokio::Foo".len());
+
+        // Reloading the evicted crate doesn't read the other crate's file,
+        // which would fail to load.
+        fs.insert_file("/index/tokio.json", vec![0xff]).await;
+        assert_eq!(
+            store
+                .load("serde".to_string(), Some("Foo".to_string()))
+                .await
+                .unwrap(),
+            "# Struct serde::Foo"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_migrate_single_file_index(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let persisted_docs = ["serde", "tokio"].map(|crate_name| PersistedDocs {
+            crate_name: crate_name.to_string(),
+            item: item(RustdocItemKind::Struct, &[], "Foo"),
+            markdown: format!("# Struct {crate_name}::Foo"),
+            source: RustdocSource::DocsDotRs,
+            version: None,
+            indexed_at: SystemTime::UNIX_EPOCH,
+        });
+        fs.insert_file(
+            "/index.json",
+            serialize_persisted_index(&persisted_docs)
+                .unwrap()
+                .into_bytes(),
+        )
+        .await;
+
+        let store = RustdocStore::new(cx.executor())
+            .with_persisted_index(fs.clone(), PathBuf::from("/index"));
+        store.restore();
+        assert_eq!(
+            store
+                .load("tokio".to_string(), Some("Foo".to_string()))
+                .await
+                .unwrap(),
+            "# Struct tokio::Foo"
+        );
+        assert!(!fs.is_file(Path::new("/index.json")).await);
+        for crate_name in ["serde", "tokio"] {
+            let path = PathBuf::from(format!("/index/{crate_name}.json"));
+            assert_eq!(
+                parse_persisted_index(&fs.load(&path).await.unwrap()),
+                persisted_docs
+                    .iter()
+                    .filter(|docs| docs.crate_name == crate_name)
+                    .cloned()
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[gpui::test]
+    async fn test_load_by_item_name(cx: &mut TestAppContext) {
+        let store = RustdocStore::new(cx.executor());
+        let mut items = IndexMap::default();
+        for (path, name) in [(&["inner"][..], "Baz"), (&["a"], "Qux"), (&["b"], "Qux")] {
+            items.insert(
+                item(RustdocItemKind::Struct, path, name),
+                format!("# Struct foo::{}::{name}", path.join("::")),
+            );
+        }
+        let crate_docs = CrateDocs {
+            crate_root_markdown: "# Crate foo".to_string(),
+            items,
+            reexports: IndexMap::default(),
+        };
+        insert_crate_docs(
+            &store.docs,
+            "foo",
+            crate_docs,
+            RustdocSource::Local,
+            None,
+            SystemTime::now(),
+        );
+
+        let load = |item_path: &str| store.load("foo".to_string(), Some(item_path.to_string()));
+        assert_eq!(
+            load("inner::Baz").await.unwrap(),
+            "# Struct foo::inner::Baz"
+        );
+        assert_eq!(
+            load("bar::Baz").await.unwrap(),
+            "> **Note:** `foo::bar::Baz` isn't documented at that path, so these are the docs \
+            for `foo::inner::Baz`, the only item with that name.\n\n# Struct foo::inner::Baz"
+        );
+
+        let err = load("Qux").await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`foo::Qux` isn't documented at that path; did you mean one of `foo::a::Qux`, \
+            `foo::b::Qux`?"
+        );
+        let query = DocQuery::new("foo", vec!["Qux".to_string()]);
+        assert!(store.fetch(&query).await.is_err());
+
+        assert_eq!(load("Quux").await.unwrap_err().to_string(), "no docs found");
+        let query = DocQuery::new("foo", vec!["Quux".to_string()]);
+        assert_eq!(store.fetch(&query).await.unwrap(), None);
+    }
+
+    #[gpui::test]
+    async fn test_indexed_crates(cx: &mut TestAppContext) {
+        let store = RustdocStore::new(cx.executor());
+        let indexed_at = SystemTime::UNIX_EPOCH;
+        let crates = [
+            ("tokio", RustdocSource::Local, Some("1.38.0"), 0),
+            ("serde", RustdocSource::DocsDotRs, None, 0),
+            // Re-indexed from docs.rs after being indexed locally.
+            ("serde", RustdocSource::DocsDotRs, Some("1.0.200"), 2),
+        ];
+        for (crate_name, source, version, secs) in crates {
+            let mut items = IndexMap::default();
+            items.insert(
+                item(RustdocItemKind::Struct, &[], &format!("Item{secs}")),
+                format!("# Struct {crate_name}::Item{secs}"),
+            );
+            let crate_docs = CrateDocs {
+                crate_root_markdown: format!("# Crate {crate_name}"),
+                items,
+                reexports: IndexMap::default(),
+            };
+            insert_crate_docs(
+                &store.docs,
+                crate_name,
+                crate_docs,
+                source,
+                version,
+                indexed_at + Duration::from_secs(secs),
+            );
+        }
+
+        let indexed_crates = store
+            .indexed_crates()
+            .into_iter()
+            .map(|indexed_crate| {
+                (
+                    indexed_crate.name,
+                    indexed_crate.item_count,
+                    indexed_crate.indexed_at,
+                    indexed_crate.source,
+                    indexed_crate.version,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            indexed_crates,
+            vec![
+                (
+                    "serde".to_string(),
+                    2,
+                    indexed_at,
+                    RustdocSource::DocsDotRs,
+                    Some("1.0.200".to_string())
+                ),
+                (
+                    "tokio".to_string(),
+                    1,
+                    indexed_at,
+                    RustdocSource::Local,
+                    Some("1.38.0".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_crate_search_scope(cx: &mut TestAppContext) {
+        let store = RustdocStore::new(cx.executor());
+        let crates = [
+            (
+                "serde",
+                vec![(&["de"][..], "Deserializer"), (&["ser"], "Serializer")],
+            ),
+            ("serde_json", vec![(&["de"][..], "Deserializer")]),
+        ];
+        for (crate_name, items) in crates {
+            let items = items
+                .into_iter()
+                .map(|(path, name)| {
+                    (
+                        item(RustdocItemKind::Trait, path, name),
+                        format!("# Trait {crate_name}::{}::{name}", path.join("::")),
+                    )
+                })
+                .collect();
+            let crate_docs = CrateDocs {
+                crate_root_markdown: format!("# Crate {crate_name}"),
+                items,
+                reexports: IndexMap::default(),
+            };
+            insert_crate_docs(
+                &store.docs,
+                crate_name,
+                crate_docs,
+                RustdocSource::Local,
+                None,
+                SystemTime::now(),
+            );
+        }
+        let search = |query: &str, scope: Option<HashSet<String>>| {
+            store.search(
+                query.to_string(),
+                10,
+                false,
+                crate_search_scope(query, scope),
+                Arc::new(AtomicBool::new(false)),
+            )
+        };
+
+        let results = search("serde::de", None).await;
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| format!("{}::{}", result.crate_name, result.item.display()))
+                .collect::<Vec<_>>(),
+            vec!["serde::de::Deserializer"]
+        );
+        assert!(search("tokio::sync", None).await.is_empty());
+        // The crate must be in the scope that was given too.
+        let project_crates = ["serde-json".to_string()]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert!(search("serde::de", Some(project_crates.clone()))
+            .await
+            .is_empty());
+        assert_eq!(
+            crate_search_scope("Deserializer", Some(project_crates.clone())),
+            Some(project_crates)
+        );
+    }
+
+    #[test]
+    fn test_rank_search_results() {
+        // The crate's own items come first, even if the fuzzy match of another
+        // item is better.
+        assert_eq!(
+            ranked_paths(
+                "serde",
+                vec![
+                    (
+                        "serde_json",
+                        item(RustdocItemKind::Struct, &["de"], "something"),
+                        0.9,
+                    ),
+                    ("tokio", item(RustdocItemKind::Mod, &[], "serde"), 0.95),
+                    ("serde", item(RustdocItemKind::Trait, &[], "Serialize"), 0.5),
+                    (
+                        "my_app",
+                        item(RustdocItemKind::Mod, &["serde"], "json"),
+                        0.7
+                    ),
+                ]
+            ),
+            vec![
+                "serde::Serialize",
+                "serde_json::de::something",
+                "tokio::serde",
+                "my_app::serde::json",
+            ]
+        );
+
+        // Equally relevant items are sorted by the length of their path.
+        assert_eq!(
+            ranked_paths(
+                "tokio::Mutex",
+                vec![
+                    (
+                        "tokio",
+                        item(RustdocItemKind::Struct, &["sync", "mutex"], "Mutex"),
+                        0.5,
+                    ),
+                    (
+                        "tokio",
+                        item(RustdocItemKind::Struct, &["sync"], "Mutex"),
+                        0.5
+                    ),
+                ]
+            ),
+            vec!["tokio::sync::Mutex", "tokio::sync::mutex::Mutex"]
+        );
+
+        // Crate names may be typed with hyphens.
+        assert_eq!(
+            ranked_paths(
+                "serde-json::Value",
+                vec![
+                    ("serde", item(RustdocItemKind::Struct, &[], "Value"), 0.9),
+                    ("serde_json", item(RustdocItemKind::Enum, &[], "Value"), 0.5),
+                ]
+            ),
+            vec!["serde_json::Value", "serde::Value"]
+        );
+    }
+
+    #[test]
+    fn test_scoped_docs() {
+        let indexed_at = SystemTime::UNIX_EPOCH;
+        let docs = [
+            ("serde", item(RustdocItemKind::Trait, &[], "Serialize")),
+            ("tokio", item(RustdocItemKind::Function, &[], "spawn")),
+            (
+                "serde_json",
+                item(RustdocItemKind::Function, &[], "to_string"),
+            ),
+        ]
+        .into_iter()
+        .map(|(crate_name, item)| {
+            (
+                (crate_name.to_string(), item),
+                IndexedDocs::new(String::new(), RustdocSource::Local, indexed_at),
+            )
+        })
+        .collect::<IndexedDocsMap>();
+        let scoped_paths = |scope: Option<&[&str]>| {
+            let scope = scope.map(|scope| {
+                scope
+                    .iter()
+                    .map(|crate_name| crate_name.to_string())
+                    .collect::<HashSet<_>>()
+            });
+            let mut paths = scoped_docs(&docs, scope.as_ref())
+                .into_iter()
+                .map(|((crate_name, item), _)| format!("{crate_name}::{}", item.display()))
+                .collect::<Vec<_>>();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(
+            scoped_paths(None),
+            vec!["serde::Serialize", "serde_json::to_string", "tokio::spawn"]
+        );
+        assert_eq!(scoped_paths(Some(&["serde"])), vec!["serde::Serialize"]);
+        // The names in the scope are normalized like the indexed ones.
+        assert_eq!(
+            scoped_paths(Some(&["serde-json", "tokio"])),
+            vec!["serde_json::to_string", "tokio::spawn"]
+        );
+    }
+
+    #[test]
+    fn test_dedup_search_results() {
+        let indexed_at = SystemTime::UNIX_EPOCH;
+        // The crate was indexed from `docs.rs`, where `Value` is an enum, and
+        // then from its local docs, where it's a type alias.
+        let docs = [
+            (
+                item(RustdocItemKind::Enum, &[], "Value"),
+                RustdocSource::DocsDotRs,
+            ),
+            (
+                item(RustdocItemKind::TypeAlias, &[], "Value"),
+                RustdocSource::Local,
+            ),
+            (
+                item(RustdocItemKind::Function, &[], "to_value"),
+                RustdocSource::DocsDotRs,
+            ),
+        ]
+        .into_iter()
+        .map(|(item, source)| {
+            (
+                ("serde_json".to_string(), item),
+                IndexedDocs::new(String::new(), source, indexed_at),
+            )
+        })
+        .collect::<IndexedDocsMap>();
+        let results = rank_search_results(
+            "value",
+            docs.keys()
+                .map(|(crate_name, item)| (crate_name.clone(), item.clone(), 0.5)),
+            usize::MAX,
+        );
+        assert_eq!(results.len(), 3);
+
+        let deduped = dedup_search_results(&docs, results.clone(), usize::MAX);
+        assert_eq!(
+            deduped
+                .iter()
+                .map(|result| (result.item.display(), result.item.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Value".to_string(), RustdocItemKind::TypeAlias),
+                ("to_value".to_string(), RustdocItemKind::Function),
+            ]
+        );
+
+        let deduped = dedup_search_results(&docs, results, 1);
+        assert_eq!(deduped.len(), 1);
+        assert_eq!(deduped[0].item.kind, RustdocItemKind::TypeAlias);
+    }
+
+    #[test]
+    fn test_rank_search_results_limit() {
+        // Every item matches, but only the ones in `tokio` itself are exact
+        // matches for the crate name.
+        let matches = (0..500).map(|ix| {
+            let crate_name = if ix % 50 == 0 { "tokio" } else { "tokio_util" };
+            (
+                crate_name.to_string(),
+                item(RustdocItemKind::Struct, &[], &format!("Item{ix}")),
+                0.5,
+            )
+        });
+
+        let results = rank_search_results("tokio", matches, 10);
+        assert_eq!(results.len(), 10);
+        assert_eq!(
+            results
+                .iter()
+                .filter(|result| result.crate_name == "tokio")
+                .count(),
+            10
+        );
+        assert!(results
+            .windows(2)
+            .all(|pair| pair[0].score >= pair[1].score));
+    }
+
+    #[test]
+    fn test_item_signature() {
+        let markdown = indoc! {"
+            # Function tokio::fs::read
+
+            ```rs
+            pub async fn read(
+                path: impl AsRef<Path>,
+            ) -> Result<Vec<u8>>
+            ```
+
+            Reads the entire contents of a file into a bytes vector.
+
+            ```rs
+            let contents = read(\"foo.txt\").await?;
+            ```
+        "};
+        assert_eq!(
+            item_signature(markdown).as_deref(),
+            Some("pub async fn read( path: impl AsRef<Path>, ) -> Result<Vec<u8>>")
+        );
+
+        // Examples aren't declarations.
+        let markdown = indoc! {"
+            # Module tokio::fs
+
+            ```rust
+            tokio::fs::read(\"foo.txt\").await?;
+            ```
+        "};
+        assert_eq!(item_signature(markdown), None);
+    }
+
+    #[test]
+    fn test_signature_matches() {
+        let read = (
+            "tokio".to_string(),
+            item(RustdocItemKind::Function, &["fs"], "read"),
+        );
+        let create_dir = (
+            "tokio".to_string(),
+            item(RustdocItemKind::Function, &["fs"], "create_dir"),
+        );
+        let file = (
+            "tokio".to_string(),
+            item(RustdocItemKind::Struct, &["fs"], "File"),
+        );
+        let signatures = [
+            (
+                &read,
+                "pub async fn read(path: impl AsRef<Path>) -> Result<Vec<u8>>",
+            ),
+            (
+                &create_dir,
+                "pub async fn create_dir(path: impl AsRef<Path>) -> Result<()>",
+            ),
+            (&file, "pub struct File { /* private fields */ }"),
+        ];
+
+        // None of the names contain the query, but the signatures do.
+        assert!(is_signature_query("-> Result<Vec"));
+        assert!(!is_signature_query("Result"));
+        let matches = signature_matches("-> Result<Vec", signatures)
+            .into_iter()
+            .map(|(_crate_name, item, _score)| item.display())
+            .collect::<Vec<_>>();
+        assert_eq!(matches, vec!["fs::read"]);
+
+        // Matching ignores case and whitespace, and prefers shorter signatures.
+        let results = rank_search_results(
+            "(PATH: impl AsRef<Path>)",
+            signature_matches("(PATH: impl AsRef<Path>)", signatures),
+            10,
+        );
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.item.display())
+                .collect::<Vec<_>>(),
+            vec!["fs::read", "fs::create_dir"]
+        );
+    }
+
+    #[test]
+    fn test_short_signature() {
+        fn short_signature(kind: RustdocItemKind, name: &str, signature: &str) -> Option<String> {
+            RustdocSearchResult {
+                crate_name: "serde_json".to_string(),
+                item: item(kind, &[], name),
+                score: 1.,
+                signature: Some(signature.to_string()),
+            }
+            .short_signature()
+        }
+
+        assert_eq!(
+            short_signature(
+                RustdocItemKind::Function,
+                "from_str",
+                "pub fn from_str<'a, T>(s: &'a str) -> Result<T> where T: Deserialize<'a>"
+            )
+            .as_deref(),
+            Some("<'a, T>(s: &'a str) -> Result<T>")
+        );
+        assert_eq!(
+            short_signature(
+                RustdocItemKind::Function,
+                "serve",
+                "pub fn serve<M, S, F: Fn() -> S, Fut>(listener: TcpListener, make_service: M) -> Serve<M, S>"
+            )
+            .as_deref(),
+            Some("<…>(listener: TcpListener, make_service: M) -> Serve<M, S>")
+        );
+        assert_eq!(
+            short_signature(
+                RustdocItemKind::Function,
+                "to_writer",
+                "pub fn to_writer<W, T>(writer: W, value: &T, formatter: &mut PrettyFormatter<'static>, indent: usize) -> Result<()>"
+            )
+            .as_deref(),
+            Some(
+                "<W, T>(writer: W, value: &T, formatter: &mut PrettyFormatter<'static>, indent: …"
+            )
+        );
+
+        // Only functions have a signature worth showing.
+        assert_eq!(
+            short_signature(
+                RustdocItemKind::Struct,
+                "Value",
+                "pub enum Value { Null, Bool(bool) }"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_inline_code_identifiers() {
+        let markdown = indoc! {"
+            Returns an `Option<String>` from the `Deserializer`.
+
+            ```rs
+            let value: Vec<u8> = todo!();
+            ```
+        "};
+
+        assert_eq!(
+            inline_code_identifiers(markdown),
+            HashSet::from_iter(["Option", "String", "Deserializer"])
+        );
+    }
+}
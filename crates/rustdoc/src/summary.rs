@@ -0,0 +1,164 @@
+/// Shortens the Markdown docs for an item to its heading, its declaration, and
+/// the first paragraph of its description.
+pub fn summarize_docs(markdown: &str) -> String {
+    let mut summary = Vec::new();
+    for block in description_blocks(markdown) {
+        let is_prose = is_prose(&block);
+        summary.push(block);
+        if is_prose {
+            break;
+        }
+    }
+
+    summary.join("\n\n")
+}
+
+/// Returns the first paragraph of the description in the Markdown docs for an
+/// item, on one line, e.g., for listing the item along with others.
+///
+/// The notes quoted before the description, e.g., that the item is deprecated,
+/// are skipped.
+pub fn one_line_summary(markdown: &str) -> Option<String> {
+    let paragraph = description_blocks(markdown)
+        .into_iter()
+        .find(|block| is_prose(block) && !block.starts_with('>'))?;
+    Some(paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Returns whether the given block of Markdown is prose, as opposed to a
+/// heading or a code block.
+fn is_prose(block: &str) -> bool {
+    !block.starts_with('#') && !block.starts_with("```")
+}
+
+/// Returns the blocks of the given Markdown docs up until the sections after
+/// the description, e.g., `## Implementations`.
+fn description_blocks(markdown: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut block = Vec::new();
+    let mut is_inside_code_block = false;
+    for line in markdown.lines() {
+        if line.starts_with("```") {
+            is_inside_code_block = !is_inside_code_block;
+        }
+        if !is_inside_code_block && line.trim().is_empty() {
+            if !block.is_empty() {
+                blocks.push(std::mem::take(&mut block).join("\n"));
+            }
+            continue;
+        }
+        block.push(line);
+    }
+    if !block.is_empty() {
+        blocks.push(block.join("\n"));
+    }
+
+    // The sections after the description aren't part of it.
+    if let Some(sections_start) = blocks.iter().position(|block| block.starts_with("##")) {
+        blocks.truncate(sections_start);
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_summarize_docs() {
+        let markdown = indoc! {"
+            # Struct axum::routing::MethodRouter
+
+            ```rs
+            pub struct MethodRouter<S = ()> {
+
+                /* private fields */
+            }
+            ```
+
+            A `Service` that accepts requests based on a `MethodFilter`
+            and allows chaining additional handlers.
+
+            Handlers are tried in the order they were added.
+
+            ## Implementations
+        "};
+
+        assert_eq!(
+            summarize_docs(markdown),
+            indoc! {"
+                # Struct axum::routing::MethodRouter
+
+                ```rs
+                pub struct MethodRouter<S = ()> {
+
+                    /* private fields */
+                }
+                ```
+
+                A `Service` that accepts requests based on a `MethodFilter`
+                and allows chaining additional handlers."}
+        );
+
+        // Docs without a description are left with their declaration.
+        let markdown = indoc! {"
+            # Trait tower_service::Service
+
+            ```rs
+            pub trait Service<Request> { }
+            ```
+
+            ## Required Associated Types
+        "};
+        assert_eq!(
+            summarize_docs(markdown),
+            indoc! {"
+                # Trait tower_service::Service
+
+                ```rs
+                pub trait Service<Request> { }
+                ```"}
+        );
+    }
+
+    #[test]
+    fn test_one_line_summary() {
+        let markdown = indoc! {"
+            # Struct axum::routing::MethodRouter
+
+            ```rs
+            pub struct MethodRouter<S = ()> {
+
+                /* private fields */
+            }
+            ```
+
+            > **Deprecated:** use `Router` instead
+
+            A `Service` that accepts requests based on a `MethodFilter`
+            and allows chaining additional handlers.
+
+            Handlers are tried in the order they were added.
+        "};
+        assert_eq!(
+            one_line_summary(markdown).as_deref(),
+            Some(
+                "A `Service` that accepts requests based on a `MethodFilter` and allows \
+                chaining additional handlers."
+            )
+        );
+
+        // The sections after the description aren't summarized.
+        let markdown = indoc! {"
+            # Trait tower_service::Service
+
+            ## Required Associated Types
+
+            The type of the responses.
+        "};
+        assert_eq!(one_line_summary(markdown), None);
+    }
+}
@@ -1,303 +1,1275 @@
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::io::Read;
+use std::ops::Range;
 use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use collections::HashSet;
+use encoding_rs::{Encoding, UTF_8};
 use html_to_markdown::markdown::{
-    HeadingHandler, ListHandler, ParagraphHandler, StyledTextHandler, TableHandler,
+    BlockquoteHandler, HeadingHandler, ListHandler, ParagraphHandler, StyledTextHandler,
+    TableHandler,
 };
 use html_to_markdown::{
-    convert_html_to_markdown, HandleTag, HandlerOutcome, HtmlElement, MarkdownWriter,
-    StartTagOutcome, TagHandler,
+    convert_html_to_markdown, normalize_markdown, HandleTag, HandlerOutcome, HtmlElement,
+    MarkdownWriter, StartTagOutcome, TagHandler,
 };
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use strum::IntoEnumIterator;
 
-use crate::{RustdocItem, RustdocItemKind};
+use crate::{CrateFeature, RustdocItem, RustdocItemKind, RustdocPageItem, STANDARD_LIBRARY_CRATES};
+
+/// The text of rustdoc's UI controls, which is left out of the docs when it
+/// makes up a whole line, e.g., for controls in elements that aren't removed.
+const RUSTDOC_UI_TEXT: [&str; 9] = [
+    "Expand description",
+    "Summary",
+    "Copy item path",
+    "Source",
+    "Run",
+    "ⓘ",
+    "§",
+    "[−]",
+    "[+]",
+];
+
+/// Options for converting rustdoc HTML to Markdown.
+///
+/// The defaults are what [`convert_rustdoc_to_markdown`] converts with, and
+/// new options default to leaving the conversion as it was.
+#[derive(Debug, Default, Clone)]
+pub struct RustdocConversionOptions {
+    /// Whether to annotate items with the version they were stabilized in.
+    pub include_since: bool,
+    /// Whether to summarize the documented layout of the type, e.g., its
+    /// `#[repr]` and size.
+    pub include_layout: bool,
+    /// The location of the page being converted.
+    ///
+    /// When given, the links in the docs are kept, with the links to other
+    /// items resolved to their full paths (e.g., `axum::extract::Path`).
+    pub page_location: Option<RustdocPageLocation>,
+    /// The kinds of items to list on a module page.
+    ///
+    /// When empty, the items of every kind are listed.
+    pub item_kinds: Vec<RustdocItemKind>,
+    /// Whether to list the types that implement the trait, in place of the
+    /// impls in its "Implementors" sections.
+    pub list_implementors: bool,
+    /// The number of implementors to list before noting how many more there
+    /// are, when listing them.
+    ///
+    /// When `None`, every implementor is listed.
+    pub max_implementors: Option<usize>,
+    /// Whether to leave out the "See also" list of the other items that the
+    /// page links to, which is only added when the page's location is given.
+    pub omit_see_also: bool,
+    /// The order to list the items on a module page in.
+    pub item_order: RustdocItemOrder,
+    /// Whether to list the auto trait and blanket impls of a type in their
+    /// own groups, rather than together under the
+    /// [`AUTO_AND_BLANKET_IMPLS_HEADING`].
+    pub expand_auto_impls: bool,
+}
+
+/// The order to list the items of a module in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RustdocItemOrder {
+    /// The order that the docs list them in, i.e., grouped by kind and, for
+    /// most pages, sorted by name.
+    #[default]
+    Page,
+    /// The order that the module declares them in, in a single list.
+    ///
+    /// Only rustdoc JSON records the order of the items, so the items on an
+    /// HTML page are listed in the order on the page, which is the
+    /// declaration order for the docs built with
+    /// `--sort-modules-by-appearance`.
+    Declaration,
+    /// Alphabetically within each kind, which makes the items of a very large
+    /// module easier to find.
+    Alphabetical,
+}
+
+impl RustdocItemOrder {
+    const NAMES: [(&'static str, Self); 3] = [
+        ("page", Self::Page),
+        ("declaration", Self::Declaration),
+        ("alphabetical", Self::Alphabetical),
+    ];
+}
+
+impl FromStr for RustdocItemOrder {
+    type Err = anyhow::Error;
+
+    /// Parses an item order from its name (e.g., `declaration`).
+    fn from_str(name: &str) -> Result<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(order_name, _)| *order_name == name)
+            .map(|(_, order)| *order)
+            .ok_or_else(|| {
+                let names = Self::NAMES
+                    .iter()
+                    .map(|(name, _)| format!("`{name}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                anyhow!("unknown item order `{name}`, expected one of {names}")
+            })
+    }
+}
+
+/// The module that a rustdoc page is in, which its relative links are
+/// relative to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RustdocPageLocation {
+    pub crate_name: String,
+    /// The path to the module within the crate, not including the crate name.
+    pub module_path: Vec<String>,
+}
+
+impl RustdocPageLocation {
+    /// Returns the location of the rustdoc page at the given URL on `docs.rs`
+    /// or `doc.rust-lang.org`, e.g., the `sync` module of `tokio` for
+    /// `https://docs.rs/tokio/1.38.0/tokio/sync/struct.Mutex.html`.
+    pub fn from_url(url: &str) -> Option<Self> {
+        let url = url.split(['#', '?']).next().unwrap_or(url);
+        let mut segments = if let Some(path) = url.strip_prefix("https://docs.rs/") {
+            // The overview pages of crates aren't rustdoc pages.
+            if path.starts_with("crate/") {
+                return None;
+            }
+            // Skip the package name and the version.
+            let mut segments = path.split('/').skip(2).peekable();
+            // The version may be followed by a target triple, which, unlike
+            // the name of a crate, always contains a `-`.
+            if segments
+                .peek()
+                .map_or(false, |segment| segment.contains('-'))
+            {
+                segments.next();
+            }
+            segments.collect::<Vec<_>>()
+        } else if let Some(path) = url.strip_prefix("https://doc.rust-lang.org/") {
+            // Skip the release channel or Rust version (e.g., `nightly`).
+            let mut segments = path.split('/').collect::<Vec<_>>();
+            if !STANDARD_LIBRARY_CRATES.contains(segments.first()?) {
+                segments.remove(0);
+            }
+            segments
+        } else {
+            return None;
+        };
+
+        // Relative links are relative to the module that the page is in.
+        if segments
+            .last()
+            .map_or(false, |segment| segment.ends_with(".html"))
+        {
+            segments.pop();
+        }
+        let mut segments = segments
+            .into_iter()
+            .filter(|segment| !segment.is_empty())
+            .map(ToString::to_string);
+        Some(Self {
+            crate_name: segments.next()?,
+            module_path: segments.collect(),
+        })
+    }
+}
 
 /// Converts the provided rustdoc HTML to Markdown.
-pub fn convert_rustdoc_to_markdown(html: impl Read) -> Result<(String, Vec<RustdocItem>)> {
+///
+/// Also returns the items that the page links to, in the order they first
+/// appear, which for a module page are the items it contains.
+pub fn convert_rustdoc_to_markdown(html: impl Read) -> Result<(String, Vec<RustdocPageItem>)> {
+    convert_rustdoc_to_markdown_with_options(html, &RustdocConversionOptions::default())
+}
+
+/// Converts the provided rustdoc HTML to Markdown, using the given options.
+///
+/// HTML that isn't valid UTF-8 is transcoded from the charset it declares, and
+/// HTML that was cut off, e.g., by an interrupted download, is converted as
+/// far as it goes.
+pub fn convert_rustdoc_to_markdown_with_options(
+    mut html: impl Read,
+    options: &RustdocConversionOptions,
+) -> Result<(String, Vec<RustdocPageItem>)> {
+    let mut html_bytes = Vec::new();
+    html.read_to_end(&mut html_bytes).with_context(|| {
+        format!(
+            "failed to read rustdoc HTML after {} bytes",
+            html_bytes.len()
+        )
+    })?;
+
+    let html = decode_html(&html_bytes);
+    convert_utf8_rustdoc_to_markdown(html.as_bytes(), options).with_context(|| {
+        format!(
+            "failed to convert {} bytes of rustdoc HTML to Markdown",
+            html_bytes.len()
+        )
+    })
+}
+
+/// Returns the given HTML as UTF-8.
+///
+/// HTML that isn't valid UTF-8 is transcoded from the charset declared by its
+/// byte order mark or its `<meta>` tag, or else decoded as UTF-8 with its
+/// invalid bytes replaced, e.g., those of a character that was cut off.
+pub(crate) fn decode_html(html: &[u8]) -> Cow<'_, str> {
+    if let Ok(html) = std::str::from_utf8(html) {
+        return Cow::Borrowed(html);
+    }
+
+    let encoding = declared_charset(html)
+        .and_then(Encoding::for_label)
+        .unwrap_or(UTF_8);
+    // A byte order mark takes precedence over the declared charset.
+    let (html, encoding, _had_errors) = encoding.decode(html);
+    log::debug!(
+        "decoded rustdoc HTML that isn't valid UTF-8 as {}",
+        encoding.name()
+    );
+    html
+}
+
+/// Returns the charset declared by the `<meta>` tag at the start of the given
+/// HTML, e.g., `windows-1252` for `<meta charset="windows-1252">` or for
+/// `<meta http-equiv="Content-Type" content="text/html; charset=windows-1252">`.
+fn declared_charset(html: &[u8]) -> Option<&[u8]> {
+    // As in browsers, the charset is only looked for near the start of the
+    // document.
+    let prefix = &html[..html.len().min(1024)];
+    let ix = prefix
+        .windows(b"charset=".len())
+        .position(|window| window.eq_ignore_ascii_case(b"charset="))?;
+    let value = &prefix[ix + b"charset=".len()..];
+    let value = value
+        .strip_prefix(b"\"")
+        .or_else(|| value.strip_prefix(b"'"))
+        .unwrap_or(value);
+    let end = value
+        .iter()
+        .position(|byte| {
+            matches!(byte, b'"' | b'\'' | b';' | b'/' | b'>') || byte.is_ascii_whitespace()
+        })
+        .unwrap_or(value.len());
+    Some(&value[..end]).filter(|charset| !charset.is_empty())
+}
+
+/// Converts the provided rustdoc HTML, which is valid UTF-8, to Markdown.
+fn convert_utf8_rustdoc_to_markdown(
+    html_bytes: &[u8],
+    options: &RustdocConversionOptions,
+) -> Result<(String, Vec<RustdocPageItem>)> {
     let item_collector = Rc::new(RefCell::new(RustdocItemCollector::new()));
+    let item_kind_filter = Rc::new(RefCell::new(RustdocItemKindFilter::new(
+        options.item_kinds.clone(),
+    )));
 
     let mut handlers: Vec<TagHandler> = vec![
+        item_kind_filter.clone(),
         Rc::new(RefCell::new(ParagraphHandler)),
+        Rc::new(RefCell::new(RustdocSinceHandler::new(
+            options.include_since,
+        ))),
+        Rc::new(RefCell::new(RustdocSafetyHandler::new())),
         Rc::new(RefCell::new(HeadingHandler)),
         Rc::new(RefCell::new(ListHandler)),
         Rc::new(RefCell::new(TableHandler::new())),
         Rc::new(RefCell::new(StyledTextHandler)),
+        Rc::new(RefCell::new(BlockquoteHandler::new())),
+        Rc::new(RefCell::new(RustdocWarningHandler::new())),
+        Rc::new(RefCell::new(RustdocDeprecationHandler::new())),
+        Rc::new(RefCell::new(RustdocUnstableHandler::new())),
         Rc::new(RefCell::new(RustdocChromeRemover)),
         Rc::new(RefCell::new(RustdocHeadingHandler)),
-        Rc::new(RefCell::new(RustdocCodeHandler)),
+        Rc::new(RefCell::new(RustdocCodeHandler::new())),
         Rc::new(RefCell::new(RustdocItemHandler)),
+        Rc::new(RefCell::new(RustdocStructFieldHandler::new())),
+        Rc::new(RefCell::new(RustdocEnumVariantHandler)),
+        Rc::new(RefCell::new(RustdocTraitImplsHandler::new(
+            options.expand_auto_impls,
+        ))),
         item_collector.clone(),
     ];
+    if let Some(page_location) = options.page_location.clone() {
+        handlers.push(Rc::new(RefCell::new(RustdocLinkHandler::new(
+            page_location,
+        ))));
+    }
+    if options.item_order == RustdocItemOrder::Alphabetical {
+        // The items are sorted once they've all been written.
+        handlers.push(Rc::new(RefCell::new(RustdocItemSorter::new())));
+    }
+    if options.include_layout {
+        // The layout section is summarized at the end instead.
+        handlers.insert(0, Rc::new(RefCell::new(RustdocLayoutSectionRemover)));
+    }
+    if options.list_implementors {
+        // The implementors are listed at the end instead.
+        handlers.insert(
+            0,
+            Rc::new(RefCell::new(RustdocImplementorsSectionRemover::new())),
+        );
+    }
+
+    let mut markdown = convert_html_to_markdown(html_bytes, &mut handlers)?;
+
+    // rustdoc only shows the generic parameters of the page's item in its
+    // declaration, so they're added to the title from there.
+    if let Some(declaration) = convert_rustdoc_to_declaration(html_bytes)? {
+        let item_name = page_title(&markdown)
+            .and_then(|title| title.rsplit("::").next())
+            .map(str::trim);
+        if let Some(generics) =
+            item_name.and_then(|item_name| declared_generic_params(&declaration, item_name))
+        {
+            let generics = format!("<{}>", collapse_whitespace(generics).trim());
+            append_to_title(&mut markdown, &generics);
+        }
+    }
 
-    let markdown = convert_html_to_markdown(html, &mut handlers)?;
+    // The version that the page's item was stabilized in is shown next to its
+    // title, outside of the heading, so it's added to the title separately.
+    if options.include_since {
+        if let Some(since) = convert_rustdoc_to_since(html_bytes)? {
+            append_to_title(&mut markdown, &format!(" (since {since})"));
+        }
+    }
+
+    if !options.item_kinds.is_empty() && !item_kind_filter.borrow().has_matching_items {
+        let item_kinds = options
+            .item_kinds
+            .iter()
+            .map(|kind| format!("`{}`", kind.class()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        markdown.push_str(&format!(
+            "\n\nNo items of the requested kinds ({item_kinds}) are listed on this page."
+        ));
+    }
+
+    if options.include_layout {
+        let layout = convert_rustdoc_to_layout(html_bytes)?;
+        if !layout.is_empty() {
+            markdown.push_str("\n\n## Layout\n");
+            for entry in layout {
+                markdown.push_str(&format!("\n- {entry}"));
+            }
+        }
+    }
+
+    if options.list_implementors {
+        let implementors = convert_rustdoc_to_implementors(html_bytes)?;
+        if implementors.is_empty() {
+            markdown.push_str("\n\nNo implementors are listed on this page.");
+        } else {
+            let listed_count = options
+                .max_implementors
+                .map_or(implementors.len(), |max| max.min(implementors.len()));
+            markdown.push_str("\n\n## Implementors\n");
+            for implementor in &implementors[..listed_count] {
+                markdown.push_str(&format!("\n- `{implementor}`"));
+            }
+            if listed_count < implementors.len() {
+                markdown.push_str(&format!(
+                    "\n\n…and {} more.",
+                    implementors.len() - listed_count
+                ));
+            }
+        }
+    }
+
+    if let Some(page_location) = options.page_location.as_ref() {
+        if !options.omit_see_also {
+            let see_also = convert_rustdoc_to_see_also(html_bytes, page_location)?;
+            if !see_also.is_empty() {
+                markdown.push_str("\n\n## See also\n");
+                for path in see_also {
+                    markdown.push_str(&format!("\n- `{path}`"));
+                }
+            }
+        }
+    }
 
     let items = item_collector
         .borrow()
         .items
-        .iter()
+        .values()
         .cloned()
         .collect::<Vec<_>>();
 
+    // The handlers rely on rustdoc's markup, which changes between versions,
+    // so pages from versions that it hasn't been checked against may come out
+    // incomplete.
+    if let Some(version) = convert_rustdoc_to_version(html_bytes)? {
+        if RustdocHtmlFormat::for_version(&version).is_none() {
+            markdown.insert_str(0, &unsupported_version_note(&version));
+        }
+    }
+
+    let markdown = normalize_markdown(&markdown, |line| RUSTDOC_UI_TEXT.contains(&line));
     Ok((markdown, items))
 }
 
-pub struct RustdocHeadingHandler;
+/// The markup of the pages generated by a range of rustdoc versions.
+///
+/// The handlers tell the formats apart by the elements they find, e.g.,
+/// falling back to the older classes when the newer ones are missing, so a
+/// page in a format that isn't recognized is read as either of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RustdocHtmlFormat {
+    /// The items on a module page are listed in a `<ul class="item-table">`,
+    /// with an `.item-name` and a `.desc` for each item, and the bounds of
+    /// `where` clauses are broken with `fmt-newline` spans.
+    ItemTableList,
+    /// The items on a module page are listed in a `<dl class="item-table">`,
+    /// with a `<dt>` and a `<dd>` for each item, and `where` clauses are
+    /// blocks of their own.
+    ItemTableDefinitionList,
+}
 
-impl HandleTag for RustdocHeadingHandler {
-    fn should_handle(&self, _tag: &str) -> bool {
-        // We're only handling text, so we don't need to visit any tags.
-        false
+impl RustdocHtmlFormat {
+    /// The ranges of rustdoc versions that the conversion is known to handle,
+    /// as the first and last minor version of Rust 1.x in each, along with
+    /// the format that they generate.
+    const SUPPORTED_VERSIONS: [(u64, u64, Self); 2] = [
+        (54, 83, Self::ItemTableList),
+        (84, 92, Self::ItemTableDefinitionList),
+    ];
+
+    /// Returns the format of the pages generated by the given version of
+    /// rustdoc, as in its `data-rustdoc-version` (e.g., `1.80.0 (051478957
+    /// 2024-07-21)`), or `None` if it isn't one of the supported versions.
+    pub fn for_version(version: &str) -> Option<Self> {
+        let version = version.split_whitespace().next()?;
+        let version = semver::Version::parse(version).ok()?;
+        if version.major != 1 {
+            return None;
+        }
+
+        Self::SUPPORTED_VERSIONS
+            .iter()
+            .find(|(first, last, _)| (*first..=*last).contains(&version.minor))
+            .map(|(_, _, format)| *format)
     }
+}
 
-    fn handle_text(&mut self, text: &str, writer: &mut MarkdownWriter) -> HandlerOutcome {
-        if writer.is_inside("h1")
-            || writer.is_inside("h2")
-            || writer.is_inside("h3")
-            || writer.is_inside("h4")
-            || writer.is_inside("h5")
-            || writer.is_inside("h6")
-        {
-            let text = text
-                .trim_matches(|char| char == '\n' || char == '\r' || char == '§')
-                .replace('\n', " ");
-            writer.push_str(&text);
+/// Returns the note shown at the top of a page generated by a version of
+/// rustdoc that isn't one of the supported ones.
+fn unsupported_version_note(version: &str) -> String {
+    let supported_versions = RustdocHtmlFormat::SUPPORTED_VERSIONS;
+    let (first, _, _) = supported_versions[0];
+    let (_, last, _) = supported_versions[supported_versions.len() - 1];
+    format!(
+        "> **Note:** This page was generated by rustdoc {version}, whose HTML format isn't \
+        recognized (rustdoc 1.{first} to 1.{last} are supported), so parts of the docs may be \
+        incomplete or missing.\n\n"
+    )
+}
 
-            return HandlerOutcome::Handled;
+/// Returns the version of rustdoc that generated the provided page, e.g.,
+/// `1.80.0 (051478957 2024-07-21)`.
+///
+/// Returns `None` for pages that don't say, such as those generated by very
+/// old versions of rustdoc.
+pub fn convert_rustdoc_to_version(html: impl Read) -> Result<Option<String>> {
+    // The chrome isn't removed, as the version is in the `<head>` of newer
+    // pages.
+    let version_collector = Rc::new(RefCell::new(RustdocVersionCollector::new()));
+    let mut handlers: Vec<TagHandler> = vec![version_collector.clone()];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let version = version_collector.borrow_mut().version.take();
+    Ok(version)
+}
+
+/// Returns the version that the item documented on a rustdoc page was
+/// stabilized in, as shown next to its title, e.g., `1.0.0` for `std::vec::Vec`.
+///
+/// Returns `None` for the items without a stability attribute, which includes
+/// those of most crates outside of the standard library.
+pub fn convert_rustdoc_to_since(html: impl Read) -> Result<Option<String>> {
+    // The chrome isn't removed, as the version is in the `out-of-band` part
+    // of the title.
+    let since_collector = Rc::new(RefCell::new(RustdocPageSinceCollector::new()));
+    let mut handlers: Vec<TagHandler> = vec![since_collector.clone()];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let since = since_collector.borrow_mut().since.take();
+    Ok(since)
+}
+
+/// Returns the first top-level heading in the given Markdown, which is the
+/// title of the page, without its `# `.
+fn page_title(markdown: &str) -> Option<&str> {
+    markdown
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(str::trim_end)
+}
+
+/// Appends the given text to the line of the first top-level heading in the
+/// given Markdown, which is the title of the page.
+fn append_to_title(markdown: &mut String, text: &str) {
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        if line.starts_with("# ") {
+            markdown.insert_str(offset + line.trim_end().len(), text);
+            return;
         }
+        offset += line.len();
+    }
+}
 
-        HandlerOutcome::NoOp
+/// Returns the declaration of the item documented on a rustdoc page, e.g.,
+/// `pub struct Mutex<T: ?Sized> { /* private fields */ }`, or `None` for a
+/// page without one, such as a module's.
+fn convert_rustdoc_to_declaration(html: impl Read) -> Result<Option<String>> {
+    let declaration_collector = Rc::new(RefCell::new(RustdocDeclarationCollector::new()));
+
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(RustdocChromeRemover)),
+        declaration_collector.clone(),
+    ];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let declaration = declaration_collector.borrow().declaration.clone();
+    Ok(declaration)
+}
+
+/// Returns the generic parameters of the item with the given name in its
+/// declaration, with their bounds and defaults, e.g., `T: Clone, const N:
+/// usize` for `pub fn fill<T: Clone, const N: usize>(value: T) -> [T; N]`.
+fn declared_generic_params<'a>(declaration: &'a str, item_name: &str) -> Option<&'a str> {
+    let after_name = ["fn", "struct", "enum", "union", "trait", "type"]
+        .iter()
+        .find_map(|keyword| declaration.split_once(&format!("{keyword} {item_name}<")))?
+        .1;
+    until_closing_angle_bracket(after_name)
+}
+
+/// Returns the layout information documented on a rustdoc page, such as the
+/// type's `#[repr]` and the size reported in its "Layout" section.
+fn convert_rustdoc_to_layout(html: impl Read) -> Result<Vec<String>> {
+    let layout_collector = Rc::new(RefCell::new(RustdocLayoutCollector::new()));
+
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(RustdocChromeRemover)),
+        layout_collector.clone(),
+    ];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let layout = layout_collector.borrow().layout.clone();
+    Ok(layout)
+}
+
+/// Returns the types that implement the trait documented on a rustdoc page,
+/// as listed in its "Implementors", "Implementations on Foreign Types", and
+/// "Auto implementors" sections, e.g., `WindowsMut<'w, T>` for
+/// `impl<'w, T> LendingIterator for WindowsMut<'w, T>`.
+///
+/// The implementors in other crates are loaded by the page's scripts, so
+/// they aren't included.
+fn convert_rustdoc_to_implementors(html: impl Read) -> Result<Vec<String>> {
+    let implementors_collector = Rc::new(RefCell::new(RustdocImplementorsCollector::new()));
+
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(RustdocChromeRemover)),
+        implementors_collector.clone(),
+    ];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let implementors = implementors_collector.borrow().implementors.clone();
+    Ok(implementors)
+}
+
+/// Converts the provided rustdoc HTML to a Rust code block containing only
+/// the definitions on the page, without any of the prose.
+///
+/// This includes the item's declaration, the headers of its impls and their
+/// associated items, and the names of the items listed on a module page.
+pub fn convert_rustdoc_to_definitions(html: impl Read) -> Result<String> {
+    let definitions_collector = Rc::new(RefCell::new(RustdocDefinitionsCollector::new()));
+
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(RustdocChromeRemover)),
+        definitions_collector.clone(),
+    ];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let definitions = &definitions_collector.borrow().definitions;
+    if definitions.is_empty() {
+        return Ok(String::new());
     }
+
+    Ok(format!("```rs\n{}\n```", definitions.join("\n")))
 }
 
-pub struct RustdocCodeHandler;
+/// Returns the trait bounds required to call the method with the given name
+/// on the provided rustdoc page, from both its generic parameters and its
+/// `where` clause.
+///
+/// Returns `None` if the page doesn't document a method with that name.
+pub fn convert_rustdoc_to_method_bounds(
+    html: impl Read,
+    method_name: &str,
+) -> Result<Option<Vec<String>>> {
+    let signature_collector = Rc::new(RefCell::new(RustdocMethodSignatureCollector::new(
+        method_name,
+    )));
 
-impl HandleTag for RustdocCodeHandler {
-    fn should_handle(&self, tag: &str) -> bool {
-        match tag {
-            "pre" | "code" => true,
-            _ => false,
-        }
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(RustdocChromeRemover)),
+        signature_collector.clone(),
+    ];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let collector = signature_collector.borrow();
+    let Some(signature) = collector.signature.as_ref() else {
+        return Ok(None);
+    };
+
+    let mut bounds = Vec::new();
+    if let Some(generics) = generic_params(signature, method_name) {
+        bounds.extend(
+            split_top_level(generics)
+                .into_iter()
+                .filter(|param| param.contains(':')),
+        );
+    }
+    if let Some(where_clause) = collector.where_clause.as_ref() {
+        let where_clause = where_clause.trim().trim_start_matches("where");
+        bounds.extend(split_top_level(where_clause));
     }
 
-    fn handle_tag_start(
-        &mut self,
-        tag: &HtmlElement,
-        writer: &mut MarkdownWriter,
-    ) -> StartTagOutcome {
-        match tag.tag() {
-            "code" => {
-                if !writer.is_inside("pre") {
-                    writer.push_str("`");
-                }
-            }
-            "pre" => {
-                let classes = tag.classes();
-                let is_rust = classes.iter().any(|class| class == "rust");
-                let language = is_rust
-                    .then(|| "rs")
-                    .or_else(|| {
-                        classes.iter().find_map(|class| {
-                            if let Some((_, language)) = class.split_once("language-") {
-                                Some(language.trim())
-                            } else {
-                                None
-                            }
-                        })
-                    })
-                    .unwrap_or("");
-
-                writer.push_str(&format!("\n\n```{language}\n"));
-            }
+    Ok(Some(bounds))
+}
+
+/// Returns the generic parameters of the function with the given name in the
+/// given signature, e.g., `B, F: FnMut(Self::Item) -> B` for
+/// `fn map<B, F: FnMut(Self::Item) -> B>(self, f: F)`.
+fn generic_params<'a>(signature: &'a str, function_name: &str) -> Option<&'a str> {
+    let after_name = signature
+        .split_once(&format!("fn {function_name}"))?
+        .1
+        .strip_prefix('<')?;
+    until_closing_angle_bracket(after_name)
+}
+
+/// Returns the given text up to the `>` that closes the `<` before it, e.g.,
+/// `F: Fn() -> Vec<T>` for `F: Fn() -> Vec<T>>(f: F)`.
+fn until_closing_angle_bracket(text: &str) -> Option<&str> {
+    let mut depth = 0;
+    let mut previous_char = None;
+    for (ix, char) in text.char_indices() {
+        match char {
+            '<' => depth += 1,
+            // Don't mistake the `>` in a `->` for a closing bracket.
+            '>' if previous_char == Some('-') => {}
+            '>' if depth == 0 => return Some(&text[..ix]),
+            '>' => depth -= 1,
             _ => {}
         }
-
-        StartTagOutcome::Continue
+        previous_char = Some(char);
     }
 
-    fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
-        match tag.tag() {
-            "code" => {
-                if !writer.is_inside("pre") {
-                    writer.push_str("`");
-                }
+    None
+}
+
+/// Splits the given text on the commas that aren't nested within brackets,
+/// trimming each part and dropping the empty ones.
+fn split_top_level(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut part = String::new();
+    for char in text.chars() {
+        match char {
+            '<' | '(' | '[' => depth += 1,
+            // Don't mistake the `>` in a `->` for a closing bracket.
+            '>' if part.ends_with('-') => {}
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(collapse_whitespace(&part).trim().to_string());
+                part.clear();
+                continue;
             }
-            "pre" => writer.push_str("\n```\n"),
             _ => {}
         }
+        part.push(char);
     }
+    parts.push(collapse_whitespace(&part).trim().to_string());
 
-    fn handle_text(&mut self, text: &str, writer: &mut MarkdownWriter) -> HandlerOutcome {
-        if writer.is_inside("pre") {
-            writer.push_str(&text);
-            return HandlerOutcome::Handled;
-        }
+    parts.retain(|part| !part.is_empty());
+    parts
+}
 
-        HandlerOutcome::NoOp
-    }
+/// Whether a trait can be used as a trait object, i.e., as `dyn Trait`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynCompatibility {
+    pub is_dyn_compatible: bool,
+    /// The parts of the trait's declaration that likely make it dyn
+    /// incompatible, as rustdoc doesn't say why.
+    pub reasons: Vec<String>,
 }
 
-const RUSTDOC_ITEM_NAME_CLASS: &str = "item-name";
+/// Returns whether the trait documented on the provided rustdoc page is dyn
+/// compatible (formerly known as "object safe").
+///
+/// Returns `None` if the page doesn't document a trait.
+pub fn convert_rustdoc_to_dyn_compatibility(html: impl Read) -> Result<Option<DynCompatibility>> {
+    let dyn_compatibility_collector =
+        Rc::new(RefCell::new(RustdocDynCompatibilityCollector::new()));
 
-pub struct RustdocItemHandler;
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(RustdocChromeRemover)),
+        dyn_compatibility_collector.clone(),
+    ];
 
-impl RustdocItemHandler {
-    /// Returns whether we're currently inside of an `.item-name` element, which
-    /// rustdoc uses to display Rust items in a list.
-    fn is_inside_item_name(writer: &MarkdownWriter) -> bool {
-        writer
-            .current_element_stack()
-            .iter()
-            .any(|element| element.has_class(RUSTDOC_ITEM_NAME_CLASS))
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let collector = dyn_compatibility_collector.borrow();
+    let Some(declaration) = collector.declaration.as_ref() else {
+        return Ok(None);
+    };
+    let header = declaration.split('{').next().unwrap_or_default();
+    if !header.split_whitespace().any(|word| word == "trait") {
+        return Ok(None);
     }
+
+    // rustdoc only calls out the traits that aren't dyn compatible.
+    let is_dyn_compatible = !collector.is_marked_dyn_incompatible;
+    let reasons = if is_dyn_compatible {
+        Vec::new()
+    } else {
+        dyn_incompatibility_reasons(declaration)
+    };
+
+    Ok(Some(DynCompatibility {
+        is_dyn_compatible,
+        reasons,
+    }))
 }
 
-impl HandleTag for RustdocItemHandler {
-    fn should_handle(&self, tag: &str) -> bool {
-        match tag {
-            "div" | "span" => true,
-            _ => false,
-        }
+/// Returns the parts of the given trait declaration that make the trait dyn
+/// incompatible, per the rules in the Rust Reference.
+///
+/// Associated items that are bounded by `where Self: Sized` are exempt from
+/// the rules, so they are ignored.
+fn dyn_incompatibility_reasons(declaration: &str) -> Vec<String> {
+    let declaration = declaration
+        .lines()
+        .filter(|line| !line.trim().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let (header, body) = declaration.split_once('{').unwrap_or((&declaration, ""));
+    let body = body.trim_end().strip_suffix('}').unwrap_or(body);
+
+    let mut reasons = Vec::new();
+    let is_sized = |text: &str| {
+        text.split(|char: char| !(char.is_alphanumeric() || char == '_'))
+            .any(|word| word == "Sized")
+    };
+    if header
+        .split_once(':')
+        .map_or(false, |(_, bounds)| is_sized(bounds))
+    {
+        reasons.push("it requires `Self: Sized`".to_string());
     }
 
-    fn handle_tag_start(
-        &mut self,
-        tag: &HtmlElement,
-        writer: &mut MarkdownWriter,
-    ) -> StartTagOutcome {
-        match tag.tag() {
-            "div" | "span" => {
-                if Self::is_inside_item_name(writer) && tag.has_class("stab") {
-                    writer.push_str(" [");
-                }
+    for item in split_associated_items(body) {
+        let item = collapse_whitespace(&item).trim().to_string();
+        if item.contains("Self: Sized") {
+            continue;
+        }
+
+        if let Some(constant) = item.strip_prefix("const ") {
+            let name = constant.split(':').next().unwrap_or_default().trim();
+            reasons.push(format!("it has an associated constant `{name}`"));
+            continue;
+        }
+
+        if let Some(associated_type) = item.strip_prefix("type ") {
+            let name = associated_type
+                .split(|char: char| !(char.is_alphanumeric() || char == '_'))
+                .next()
+                .unwrap_or_default();
+            if associated_type[name.len()..].starts_with('<') {
+                reasons.push(format!("it has a generic associated type `{name}`"));
             }
-            _ => {}
+            continue;
         }
 
-        StartTagOutcome::Continue
+        let Some((qualifiers, function)) = item.split_once("fn ") else {
+            continue;
+        };
+        let name = function
+            .split(|char: char| !(char.is_alphanumeric() || char == '_'))
+            .next()
+            .unwrap_or_default();
+        let (params, return_type) = function[name.len()..]
+            .split_once("->")
+            .unwrap_or((&function[name.len()..], ""));
+
+        if qualifiers.split_whitespace().any(|word| word == "async") {
+            reasons.push(format!("`{name}` is an `async fn`"));
+        }
+        let has_generic_types = generic_params(&item, name).map_or(false, |generics| {
+            split_top_level(generics)
+                .iter()
+                .any(|param| !param.starts_with('\''))
+        });
+        if has_generic_types {
+            reasons.push(format!("`{name}` has generic type parameters"));
+        }
+        let first_param = params
+            .split_once('(')
+            .map_or("", |(_, args)| args)
+            .split([',', ')'])
+            .next()
+            .unwrap_or_default()
+            .trim();
+        let mut receiver = first_param.trim_start_matches('&');
+        if receiver.starts_with('\'') {
+            // Skip the lifetime of the reference, e.g., `&'a self`.
+            receiver = receiver
+                .split_once(' ')
+                .map_or("", |(_, receiver)| receiver);
+        }
+        let receiver = receiver.trim_start_matches("mut ");
+        let has_receiver = receiver == "self" || receiver.starts_with("self:");
+        if !has_receiver {
+            reasons.push(format!("`{name}` has no `self` receiver"));
+        }
+        if item.contains("impl ") {
+            reasons.push(format!("`{name}` uses `impl Trait`"));
+        }
+        let returns_self = return_type
+            .split(|char: char| !(char.is_alphanumeric() || char == '_' || char == ':'))
+            .any(|word| word == "Self");
+        if returns_self {
+            reasons.push(format!("`{name}` returns `Self`"));
+        }
     }
 
-    fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
-        match tag.tag() {
-            "div" | "span" => {
-                if tag.has_class(RUSTDOC_ITEM_NAME_CLASS) {
-                    writer.push_str(": ");
-                }
+    reasons
+}
 
-                if Self::is_inside_item_name(writer) && tag.has_class("stab") {
-                    writer.push_str("]");
+/// Splits the body of a trait declaration into its associated items, which
+/// end either with a `;` or with a (collapsed) default body.
+fn split_associated_items(body: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut depth = 0;
+    for char in body.chars() {
+        item.push(char);
+        match char {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    items.push(std::mem::take(&mut item));
                 }
             }
+            ';' if depth == 0 => items.push(std::mem::take(&mut item)),
             _ => {}
         }
     }
 
-    fn handle_text(&mut self, text: &str, writer: &mut MarkdownWriter) -> HandlerOutcome {
-        if Self::is_inside_item_name(writer)
-            && !writer.is_inside("span")
-            && !writer.is_inside("code")
-        {
-            writer.push_str(&format!("`{text}`"));
-            return HandlerOutcome::Handled;
-        }
-
-        HandlerOutcome::NoOp
-    }
+    items.retain(|item| !item.trim().is_empty());
+    items
 }
 
-pub struct RustdocChromeRemover;
+/// The methods and associated items of a type or trait, without any of their
+/// docs.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MethodIndex {
+    /// The definitions of the associated types of the trait, e.g., `type Item`.
+    pub associated_types: Vec<String>,
+    /// The definitions of the associated constants of the trait or of the
+    /// inherent impls of the type, e.g., `pub const MAX: u32 = 4_294_967_295u32`.
+    pub associated_consts: Vec<String>,
+    /// The signatures of the methods in the inherent impls of the type.
+    pub inherent_methods: Vec<String>,
+    /// The header of each trait impl of the type that has methods or
+    /// associated items, along with their signatures, e.g., `type Err =
+    /// Infallible` for `impl FromStr for String`.
+    pub trait_impls: Vec<(String, Vec<String>)>,
+}
 
-impl HandleTag for RustdocChromeRemover {
-    fn should_handle(&self, tag: &str) -> bool {
-        match tag {
-            "head" | "script" | "nav" | "summary" | "button" | "div" | "span" => true,
-            _ => false,
-        }
+impl MethodIndex {
+    pub fn is_empty(&self) -> bool {
+        self.associated_types.is_empty()
+            && self.associated_consts.is_empty()
+            && self.inherent_methods.is_empty()
+            && self.trait_impls.is_empty()
     }
+}
 
-    fn handle_tag_start(
+/// Returns the signatures of the methods and associated items of the type or
+/// trait documented on the provided rustdoc page, with those of its inherent
+/// impls separate from those of its trait impls.
+///
+/// The `where` clauses are left out to keep the signatures short, and the
+/// auto trait and blanket impls are left out, as they are the same for most
+/// types.
+pub fn convert_rustdoc_to_method_index(html: impl Read) -> Result<MethodIndex> {
+    let method_index_collector = Rc::new(RefCell::new(RustdocMethodIndexCollector::new()));
+
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(RustdocChromeRemover)),
+        method_index_collector.clone(),
+    ];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let mut method_index = method_index_collector.borrow().method_index.clone();
+    method_index
+        .trait_impls
+        .retain(|(_header, methods)| !methods.is_empty());
+    Ok(method_index)
+}
+
+/// Returns the full paths of the types and traits that the item documented on
+/// the provided rustdoc page directly references, in the order they first
+/// appear.
+///
+/// Only the links in the item's declaration and in its top-level docs are
+/// considered, as opposed to those in its impls and associated items.
+pub fn convert_rustdoc_to_references(html: impl Read) -> Result<Vec<String>> {
+    let reference_collector = Rc::new(RefCell::new(RustdocReferenceCollector::new()));
+
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(RustdocChromeRemover)),
+        reference_collector.clone(),
+    ];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let references = reference_collector.borrow().references.clone();
+    Ok(references.into_iter().collect())
+}
+
+/// Returns the full paths of the other items that the rustdoc page at the
+/// given location links to, from its docs or its sidebar, in the order they
+/// first appear.
+///
+/// The items that are documented on the page itself, i.e., its own item, the
+/// associated items of a type or trait, and the items listed on a module
+/// page, are left out.
+fn convert_rustdoc_to_see_also(
+    html: impl Read,
+    location: &RustdocPageLocation,
+) -> Result<Vec<String>> {
+    let see_also_collector = Rc::new(RefCell::new(RustdocSeeAlsoCollector::new(location.clone())));
+    let mut handlers: Vec<TagHandler> = vec![see_also_collector.clone()];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let see_also = see_also_collector.borrow().see_also();
+    Ok(see_also)
+}
+
+/// An item that a module re-exports with `pub use`, as listed under
+/// "Re-exports" on the module's rustdoc page.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RustdocReexport {
+    /// The name that the item is re-exported under.
+    pub name: String,
+    pub kind: RustdocItemKind,
+    /// The full path to the item where it is defined, e.g.,
+    /// `tokio::fs::file::File`.
+    pub canonical_path: String,
+    /// The link to the page of the item, relative to the module's page.
+    pub href: String,
+}
+
+impl RustdocReexport {
+    /// Returns the re-exported item, with its path within the given crate,
+    /// or `None` if the item is defined in another crate.
+    pub fn canonical_item(&self, crate_name: &str) -> Option<RustdocItem> {
+        let mut segments = self.canonical_path.split("::");
+        if segments.next() != Some(crate_name) {
+            return None;
+        }
+
+        let mut path = segments.map(Arc::from).collect::<Vec<Arc<str>>>();
+        let name = path.pop()?;
+        Some(RustdocItem {
+            kind: self.kind,
+            path,
+            name,
+        })
+    }
+
+    /// Returns the location of the page of the re-exported item, which its
+    /// relative links are relative to.
+    pub fn canonical_location(&self) -> Option<RustdocPageLocation> {
+        let mut segments = self.canonical_path.split("::").map(str::to_string);
+        let crate_name = segments.next()?;
+        let mut module_path = segments.collect::<Vec<_>>();
+        if self.kind != RustdocItemKind::Mod {
+            module_path.pop();
+        }
+
+        Some(RustdocPageLocation {
+            crate_name,
+            module_path,
+        })
+    }
+}
+
+/// Returns the items that the module documented on the provided rustdoc page
+/// re-exports, in the order they are listed.
+///
+/// Glob re-exports (e.g., `pub use self::inner::*;`) are left out, as rustdoc
+/// lists the items they bring in as if they were defined in the module.
+pub fn convert_rustdoc_to_reexports(html: impl Read) -> Result<Vec<RustdocReexport>> {
+    let reexport_collector = Rc::new(RefCell::new(RustdocReexportCollector::new()));
+
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(RustdocChromeRemover)),
+        reexport_collector.clone(),
+    ];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let reexports = reexport_collector.borrow().reexports.clone();
+    Ok(reexports)
+}
+
+/// Returns the public items listed on a crate's "all items" page
+/// (`all.html`), as the headings of their categories (e.g., `Structs`) with
+/// the paths of the items in each, relative to the crate root, in the order
+/// they are listed.
+pub fn convert_rustdoc_to_all_items(html: impl Read) -> Result<Vec<(String, Vec<String>)>> {
+    let all_items_collector = Rc::new(RefCell::new(RustdocAllItemsCollector::new()));
+
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(RustdocChromeRemover)),
+        all_items_collector.clone(),
+    ];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let mut categories = all_items_collector.borrow().categories.clone();
+    categories.retain(|(_heading, items)| !items.is_empty());
+    Ok(categories)
+}
+
+/// Returns the features of a crate listed on its features page on `docs.rs`
+/// (e.g., `https://docs.rs/crate/tokio/latest/features`), in the order they
+/// are listed.
+pub fn convert_docs_rs_features(html: impl Read) -> Result<Vec<CrateFeature>> {
+    let feature_collector = Rc::new(RefCell::new(DocsRsFeatureCollector::new()));
+
+    let mut handlers: Vec<TagHandler> = vec![feature_collector.clone()];
+
+    convert_html_to_markdown(html, &mut handlers)?;
+
+    let features = feature_collector.borrow().features.clone();
+    Ok(features)
+}
+
+/// Returns whether the provided HTML is an unrendered shell of a page that
+/// relies on client-side JavaScript to render its contents.
+///
+/// Converting such a page would produce no docs, as the doc body is absent
+/// from the HTML we receive.
+pub(crate) fn is_js_shell_page(html: &str) -> bool {
+    // Rendered rustdoc pages always contain the main content section.
+    if html.contains(r#"id="main-content""#) {
+        return false;
+    }
+
+    let html = html.to_lowercase();
+    let has_script = html.contains("<script");
+    let has_shell_marker = ["<noscript", r#"id="app""#, r#"id="root""#]
+        .iter()
+        .any(|marker| html.contains(marker));
+
+    has_script && has_shell_marker
+}
+
+/// Returns whether the provided HTML is the page that `docs.rs` serves in
+/// place of a crate's docs when it failed to build them.
+///
+/// `docs.rs` serves it with a `200 OK` status, either at the URL of the docs
+/// themselves or at the crate's overview page, which it redirects to.
+pub(crate) fn is_docs_rs_build_failure_page(html: &str) -> bool {
+    // The docs may well mention a failed build, e.g., of a crate building
+    // their own docs, but aren't the page saying so.
+    !html.contains(r#"id="main-content""#) && html.contains("docs.rs failed to build")
+}
+
+pub struct RustdocHeadingHandler;
+
+impl RustdocHeadingHandler {
+    fn is_inside_heading(writer: &MarkdownWriter) -> bool {
+        writer.is_inside("h1")
+            || writer.is_inside("h2")
+            || writer.is_inside("h3")
+            || writer.is_inside("h4")
+            || writer.is_inside("h5")
+            || writer.is_inside("h6")
+    }
+}
+
+impl HandleTag for RustdocHeadingHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "br" | "span" | "div" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
         &mut self,
         tag: &HtmlElement,
-        _writer: &mut MarkdownWriter,
+        writer: &mut MarkdownWriter,
     ) -> StartTagOutcome {
-        match tag.tag() {
-            "head" | "script" | "nav" => return StartTagOutcome::Skip,
-            "summary" => {
-                if tag.has_class("hideme") {
-                    return StartTagOutcome::Skip;
-                }
-            }
-            "button" => {
-                if tag.attr("id").as_deref() == Some("copy-path") {
-                    return StartTagOutcome::Skip;
-                }
-            }
-            "div" | "span" => {
-                let classes_to_skip = ["nav-container", "sidebar-elems", "out-of-band"];
-                if tag.has_any_classes(&classes_to_skip) {
-                    return StartTagOutcome::Skip;
-                }
-            }
-            _ => {}
+        // rustdoc renders `where` clauses in code headers on their own line, so
+        // we need to make sure they stay separated from the rest of the signature
+        // once the heading is flattened to a single line.
+        let is_line_break = tag.tag() == "br" || tag.has_class("where");
+        if is_line_break
+            && Self::is_inside_heading(writer)
+            && !writer.markdown().ends_with(char::is_whitespace)
+        {
+            writer.push_str(" ");
         }
 
         StartTagOutcome::Continue
     }
+
+    fn handle_text(&mut self, text: &str, writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if Self::is_inside_heading(writer) {
+            let text = text.trim_matches(|char| char == '\n' || char == '\r' || char == '§');
+            let mut text = collapse_whitespace(text);
+            // The space for a line break is already there, e.g., before the
+            // indented bounds of a `where` clause.
+            if writer.markdown().ends_with(' ') {
+                text = text.trim_start().to_string();
+            }
+            writer.push_str(&text);
+
+            return HandlerOutcome::Handled;
+        }
+
+        HandlerOutcome::NoOp
+    }
 }
 
-pub struct RustdocItemCollector {
-    pub items: IndexSet<RustdocItem>,
+/// Collapses each run of whitespace in the given text into a single space.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    for char in text.chars() {
+        if char.is_whitespace() {
+            if !collapsed.ends_with(' ') {
+                collapsed.push(' ');
+            }
+        } else {
+            collapsed.push(char);
+        }
+    }
+    collapsed
 }
 
-impl RustdocItemCollector {
+pub struct RustdocCodeHandler {
+    /// The `<pre>` block currently being written, if any.
+    code_block: Option<CodeBlock>,
+    /// The depth of `<pre>` elements nested within the current code block.
+    nested_pre_depth: usize,
+}
+
+struct CodeBlock {
+    language: String,
+    code: String,
+    /// Whether this block is the declaration of the item being documented.
+    is_item_decl: bool,
+}
+
+impl RustdocCodeHandler {
     pub fn new() -> Self {
         Self {
-            items: IndexSet::new(),
+            code_block: None,
+            nested_pre_depth: 0,
         }
     }
 
-    fn parse_item(tag: &HtmlElement) -> Option<RustdocItem> {
-        if tag.tag() != "a" {
-            return None;
-        }
-
-        let href = tag.attr("href")?;
-        if href.starts_with('#') || href.starts_with("https://") || href.starts_with("../") {
-            return None;
+    /// Returns the language that the given `<pre>` block should be fenced as.
+    ///
+    /// Doc examples are tagged as `rust`, while the other Rust code (e.g., item
+    /// declarations) is tagged as `rs`. Any other block keeps the language from
+    /// its `language-*` class, if it has one.
+    fn language(tag: &HtmlElement) -> String {
+        let classes = tag.classes();
+        let is_rust_example = classes
+            .iter()
+            .any(|class| class == "rust-example-rendered" || class == "language-rust");
+        let is_rust = classes.iter().any(|class| class == "rust");
+        if is_rust_example {
+            return "rust".to_string();
         }
 
-        for kind in RustdocItemKind::iter() {
-            if tag.has_class(kind.class()) {
-                let mut parts = href.trim_end_matches("/index.html").split('/');
-
-                if let Some(last_component) = parts.next_back() {
-                    let last_component = match last_component.split_once('#') {
-                        Some((component, _fragment)) => component,
-                        None => last_component,
-                    };
+        is_rust
+            .then(|| "rs")
+            .or_else(|| {
+                classes.iter().find_map(|class| {
+                    if let Some((_, language)) = class.split_once("language-") {
+                        Some(language.trim())
+                    } else {
+                        None
+                    }
+                })
+            })
+            .unwrap_or("")
+            .to_string()
+    }
 
-                    let name = last_component
-                        .trim_start_matches(&format!("{}.", kind.class()))
-                        .trim_end_matches(".html");
+    /// Returns the helper attributes listed in the declaration of a derive
+    /// macro, e.g., `#[serde]` for `#[derive(Serialize)]`.
+    fn derive_helper_attributes(code: &str) -> Vec<&str> {
+        code.lines()
+            .map(|line| line.trim())
+            .skip_while(|line| *line != "// Attributes available to this derive:")
+            .skip(1)
+            .take_while(|line| line.starts_with("#["))
+            .collect()
+    }
 
-                    return Some(RustdocItem {
-                        kind,
-                        name: name.into(),
-                        path: parts.map(Into::into).collect(),
-                    });
-                }
-            }
-        }
+    /// Returns a code fence that is longer than any run of backticks in the
+    /// given code, so that the code can't close the fence early.
+    fn fence_for(code: &str) -> String {
+        let longest_backtick_run = code
+            .split(|char| char != '`')
+            .map(|run| run.len())
+            .max()
+            .unwrap_or(0);
 
-        None
+        "`".repeat((longest_backtick_run + 1).max(3))
     }
 }
 
-impl HandleTag for RustdocItemCollector {
+impl HandleTag for RustdocCodeHandler {
     fn should_handle(&self, tag: &str) -> bool {
-        tag == "a"
+        match tag {
+            "pre" | "code" | "div" | "span" | "br" => true,
+            _ => false,
+        }
     }
 
     fn handle_tag_start(
@@ -305,223 +1277,4709 @@ impl HandleTag for RustdocItemCollector {
         tag: &HtmlElement,
         writer: &mut MarkdownWriter,
     ) -> StartTagOutcome {
+        if let Some(code_block) = self.code_block.as_mut() {
+            // The `where` clause of a declaration is a block of its own, or,
+            // in older versions of rustdoc, a `fmt-newline` span with a `<br>`
+            // before each bound, so its line breaks have to be kept for the
+            // bounds to stay readable.
+            let is_line_break = tag.tag() == "br"
+                || (tag.tag() == "div" && tag.has_class("where"))
+                || tag.has_class("fmt-newline");
+            if is_line_break && !code_block.code.ends_with('\n') {
+                let trimmed_len = code_block.code.trim_end_matches(' ').len();
+                code_block.code.truncate(trimmed_len);
+                code_block.code.push('\n');
+            }
+        }
+
         match tag.tag() {
-            "a" => {
-                let is_reexport = writer.current_element_stack().iter().any(|element| {
-                    if let Some(id) = element.attr("id") {
-                        id.starts_with("reexport.") || id.starts_with("method.")
-                    } else {
-                        false
+            "code" => {
+                if !writer.is_inside("pre") {
+                    writer.push_str("`");
+                }
+            }
+            "pre" => {
+                if self.code_block.is_some() {
+                    // Nested `<pre>`s are written as part of the outer code block.
+                    self.nested_pre_depth += 1;
+                } else {
+                    self.code_block = Some(CodeBlock {
+                        language: Self::language(tag),
+                        code: String::new(),
+                        is_item_decl: tag.has_class("item-decl"),
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        match tag.tag() {
+            "code" => {
+                if !writer.is_inside("pre") {
+                    writer.push_str("`");
+                }
+            }
+            "div" => {
+                // rustdoc puts each attribute in an item's declaration in its
+                // own block, without a line break after it.
+                if tag.has_class("code-attribute") {
+                    if let Some(code_block) = self.code_block.as_mut() {
+                        code_block.code.push('\n');
                     }
-                });
+                }
+            }
+            "pre" => {
+                if self.nested_pre_depth > 0 {
+                    self.nested_pre_depth -= 1;
+                    return;
+                }
 
-                if !is_reexport {
-                    if let Some(item) = Self::parse_item(tag) {
-                        self.items.insert(item);
+                if let Some(CodeBlock {
+                    language,
+                    code,
+                    is_item_decl,
+                }) = self.code_block.take()
+                {
+                    let fence = Self::fence_for(&code);
+                    writer.push_str(&format!("\n\n{fence}{language}\n{code}\n{fence}\n"));
+
+                    let helper_attributes = if is_item_decl {
+                        Self::derive_helper_attributes(&code)
+                    } else {
+                        Vec::new()
+                    };
+                    if !helper_attributes.is_empty() {
+                        writer.push_str("\n\nHelper attributes:\n");
+                        for attribute in helper_attributes {
+                            writer.push_str(&format!("\n- `{attribute}`"));
+                        }
+                        writer.push_blank_line();
                     }
                 }
             }
             _ => {}
         }
+    }
 
-        StartTagOutcome::Continue
+    fn handle_text(&mut self, text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if let Some(code_block) = self.code_block.as_mut() {
+            // Older versions of rustdoc indent the bounds of `where` clauses
+            // with non-breaking spaces.
+            code_block.code.push_str(&text.replace('\u{a0}', " "));
+            return HandlerOutcome::Handled;
+        }
+
+        HandlerOutcome::NoOp
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use html_to_markdown::{convert_html_to_markdown, TagHandler};
-    use indoc::indoc;
-    use pretty_assertions::assert_eq;
+const RUSTDOC_ITEM_NAME_CLASS: &str = "item-name";
 
-    use super::*;
+/// Renders the lists of items on a module page, with each item's name
+/// followed by its one-line summary.
+///
+/// Older versions of rustdoc list the items in a `<ul class="item-table">`,
+/// with an `.item-name` and a `.desc` for each item, while newer ones use a
+/// `<dl class="item-table">`, with a `<dt>` and a `<dd>`.
+pub struct RustdocItemHandler;
 
-    fn rustdoc_handlers() -> Vec<TagHandler> {
-        vec![
-            Rc::new(RefCell::new(ParagraphHandler)),
-            Rc::new(RefCell::new(HeadingHandler)),
-            Rc::new(RefCell::new(ListHandler)),
-            Rc::new(RefCell::new(TableHandler::new())),
-            Rc::new(RefCell::new(StyledTextHandler)),
-            Rc::new(RefCell::new(RustdocChromeRemover)),
-            Rc::new(RefCell::new(RustdocHeadingHandler)),
-            Rc::new(RefCell::new(RustdocCodeHandler)),
-            Rc::new(RefCell::new(RustdocItemHandler)),
-        ]
+impl RustdocItemHandler {
+    /// Returns whether we're currently inside of an `.item-name` element, or
+    /// the `<dt>` of an item table, which rustdoc uses to display Rust items
+    /// in a list.
+    fn is_inside_item_name(writer: &MarkdownWriter) -> bool {
+        let stack = writer.current_element_stack();
+        stack
+            .iter()
+            .any(|element| element.has_class(RUSTDOC_ITEM_NAME_CLASS))
+            || (stack.iter().any(|element| element.tag() == "dt")
+                && Self::is_inside_item_table(writer))
     }
 
-    #[test]
-    fn test_main_heading_buttons_get_removed() {
-        let html = indoc! {r##"
-            <div class="main-heading">
-                <h1>Crate <a class="mod" href="#">serde</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1>
-                <span class="out-of-band">
-                    <a class="src" href="../src/serde/lib.rs.html#1-340">source</a> · <button id="toggle-all-docs" title="collapse all docs">[<span>−</span>]</button>
-                </span>
-            </div>
-        "##};
-        let expected = indoc! {"
-            # Crate serde
-        "}
-        .trim();
+    /// Returns whether we're currently inside of a `<dl class="item-table">`.
+    fn is_inside_item_table(writer: &MarkdownWriter) -> bool {
+        writer
+            .current_element_stack()
+            .iter()
+            .any(|element| element.tag() == "dl" && element.has_class("item-table"))
+    }
 
+    /// Returns whether the given tag is the name of an item in a list of items,
+    /// given that its parents are on the element stack.
+    fn is_item_name(tag: &HtmlElement, writer: &MarkdownWriter) -> bool {
+        tag.has_class(RUSTDOC_ITEM_NAME_CLASS)
+            || (tag.tag() == "dt" && Self::is_inside_item_table(writer))
+    }
+
+    /// Ends the line of the last item in a `<dl class="item-table">` if it
+    /// had no summary to end it, dropping the separator before the summary.
+    fn end_item_without_summary(writer: &mut MarkdownWriter) {
+        let markdown = writer.markdown();
+        if let Some(separator_start) = markdown.strip_suffix(": ").map(str::len) {
+            writer.truncate(separator_start);
+        }
+        if !writer.markdown().ends_with('\n') {
+            writer.push_newline();
+        }
+    }
+
+    /// Returns whether the given tag is the summary of an item in a list of
+    /// items, given that its parents are on the element stack.
+    fn is_item_summary(tag: &HtmlElement, writer: &MarkdownWriter) -> bool {
+        tag.has_class("desc") || (tag.tag() == "dd" && Self::is_inside_item_table(writer))
+    }
+}
+
+impl HandleTag for RustdocItemHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "div" | "span" | "dl" | "dt" | "dd" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match tag.tag() {
+            "div" | "span" => {
+                if Self::is_inside_item_name(writer) && tag.has_class("stab") {
+                    writer.push_str(" [");
+                }
+            }
+            "dl" if tag.has_class("item-table") => writer.push_newline(),
+            "dt" if Self::is_inside_item_table(writer) => {
+                Self::end_item_without_summary(writer);
+                writer.push_str("- ");
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        match tag.tag() {
+            "div" | "span" | "dt" => {
+                if Self::is_item_name(tag, writer) {
+                    writer.push_str(": ");
+                }
+
+                if Self::is_inside_item_name(writer) && tag.has_class("stab") {
+                    writer.push_str("]");
+                }
+            }
+            "dd" if Self::is_inside_item_table(writer) => writer.push_newline(),
+            "dl" if tag.has_class("item-table") => {
+                Self::end_item_without_summary(writer);
+                writer.push_newline();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_text(&mut self, text: &str, writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if Self::is_inside_item_name(writer)
+            && !writer.is_inside("span")
+            && !writer.is_inside("code")
+        {
+            writer.push_str(&format!("`{text}`"));
+            return HandlerOutcome::Handled;
+        }
+
+        HandlerOutcome::NoOp
+    }
+}
+
+/// Sorts the entries of each list of items on a module page by name, once
+/// the [`RustdocItemHandler`] has written them.
+pub struct RustdocItemSorter {
+    /// For each of the enclosing elements that may be a list of items, the
+    /// offset in the Markdown output at which it starts, if it is one.
+    list_starts: Vec<Option<usize>>,
+}
+
+impl RustdocItemSorter {
+    pub fn new() -> Self {
+        Self {
+            list_starts: Vec::new(),
+        }
+    }
+
+    /// Returns the given list of items, with its entries sorted by name.
+    ///
+    /// An entry is a line starting with `- `, along with any lines up to the
+    /// next one.
+    fn sort_entries(list: &str) -> String {
+        let body_end = list.trim_end_matches('\n').len();
+        let (body, suffix) = list.split_at(body_end);
+
+        let mut prefix = String::new();
+        let mut entries: Vec<String> = Vec::new();
+        for line in body.split_inclusive('\n') {
+            let text = line.trim_end_matches('\n');
+            match entries.last_mut() {
+                // The last item of a `<ul class="item-table">` doesn't have
+                // the separator before its missing summary dropped yet.
+                _ if line.starts_with("- ") => {
+                    entries.push(text.strip_suffix(": ").unwrap_or(text).to_string())
+                }
+                Some(entry) => {
+                    entry.push('\n');
+                    entry.push_str(text);
+                }
+                None => prefix.push_str(line),
+            }
+        }
+        // The names are in backticks, e.g., `- `Mutex`: An asynchronous…`.
+        entries.sort_by_cached_key(|entry| {
+            let name = entry.split('`').nth(1).unwrap_or(entry).to_string();
+            (name.to_lowercase(), name)
+        });
+
+        format!("{prefix}{}{suffix}", entries.join("\n"))
+    }
+}
+
+impl HandleTag for RustdocItemSorter {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "div" | "ul" | "dl" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        self.list_starts
+            .push(tag.has_class("item-table").then(|| writer.markdown().len()));
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, _tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        let Some(Some(start)) = self.list_starts.pop() else {
+            return;
+        };
+
+        let sorted = Self::sort_entries(writer.markdown_since(start));
+        writer.truncate(start);
+        writer.push_str(&sorted);
+    }
+}
+
+const RUSTDOC_STRUCT_FIELD_CLASS: &str = "structfield";
+
+/// Renders the fields of a struct as a list, with each field's docs indented
+/// under its entry.
+///
+/// Tuple structs are numbered by rustdoc itself (e.g., `0: u32`), and the
+/// fields of struct variants are listed the same way.
+pub struct RustdocStructFieldHandler {
+    /// Whether the last field header has ended, so that the docs that follow
+    /// it (if any) are the field's.
+    is_after_field: bool,
+    /// For each of the enclosing `<div>`s, the offset in the Markdown output
+    /// at which it starts, if it is the docs of a field.
+    field_docs_starts: Vec<Option<usize>>,
+}
+
+impl RustdocStructFieldHandler {
+    pub fn new() -> Self {
+        Self {
+            is_after_field: false,
+            field_docs_starts: Vec::new(),
+        }
+    }
+
+    fn is_inside_struct_field(writer: &MarkdownWriter) -> bool {
+        writer
+            .current_element_stack()
+            .iter()
+            .any(|element| element.has_class(RUSTDOC_STRUCT_FIELD_CLASS))
+    }
+
+    /// Returns whether the given tag is the header of a field of a struct, or
+    /// of a struct variant (e.g., `variant.Rect.field.width`).
+    fn is_field_header(tag: &HtmlElement) -> bool {
+        tag.tag() == "span"
+            && (tag.has_class(RUSTDOC_STRUCT_FIELD_CLASS)
+                || tag.attr("id").map_or(false, |id| {
+                    id.starts_with("variant.") && id.contains(".field.")
+                }))
+    }
+}
+
+impl HandleTag for RustdocStructFieldHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "span" | "a" | "div" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match tag.tag() {
+            "span" => {
+                if Self::is_field_header(tag) {
+                    self.is_after_field = false;
+                    writer.push_blank_line();
+                    writer.push_str("- ");
+                }
+            }
+            "a" => {
+                // Skip the `§` anchor link preceding the field name.
+                if Self::is_inside_struct_field(writer) && tag.has_class("anchor") {
+                    return StartTagOutcome::Skip;
+                }
+            }
+            "div" => {
+                let is_field_docs = self.is_after_field && tag.has_class("docblock");
+                self.is_after_field = false;
+                self.field_docs_starts
+                    .push(is_field_docs.then(|| writer.markdown().len()));
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        match tag.tag() {
+            "span" => {
+                if Self::is_field_header(tag) {
+                    self.is_after_field = true;
+                    writer.push_blank_line();
+                }
+            }
+            "div" => {
+                if let Some(Some(offset)) = self.field_docs_starts.pop() {
+                    writer.indent_since(offset, "  ");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Renders the variants of an enum, each as a heading with its payload (e.g.,
+/// `Circle(f64)`) followed by its docs, leaving out the anchor links that
+/// rustdoc puts before each variant and field.
+pub struct RustdocEnumVariantHandler;
+
+impl RustdocEnumVariantHandler {
+    fn is_inside_variants(writer: &MarkdownWriter) -> bool {
+        writer
+            .current_element_stack()
+            .iter()
+            .any(|element| element.tag() == "div" && element.has_class("variants"))
+    }
+}
+
+impl HandleTag for RustdocEnumVariantHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "a"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if tag.has_class("anchor") && Self::is_inside_variants(writer) {
+            return StartTagOutcome::Skip;
+        }
+
+        StartTagOutcome::Continue
+    }
+}
+
+/// The sections that list the trait impls of a type, by the ID of their
+/// heading and of their list, along with the heading of their group in the
+/// "Implements" section.
+const TRAIT_IMPL_SECTIONS: [(&str, &str, Option<&str>); 3] = [
+    ("trait-implementations", "trait-implementations-list", None),
+    (
+        "synthetic-implementations",
+        "synthetic-implementations-list",
+        Some("Auto traits"),
+    ),
+    (
+        "blanket-implementations",
+        "blanket-implementations-list",
+        Some("Blanket implementations"),
+    ),
+];
+
+/// The heading of the group that the auto trait and blanket impls of a type
+/// are listed together under, followed by their count in parentheses.
+pub const AUTO_AND_BLANKET_IMPLS_HEADING: &str = "Auto & blanket implementations";
+
+/// Condenses the sections listing the trait impls of a type into a list of
+/// their headers, under an "Implements" heading.
+///
+/// The docs for the trait methods are left out, as they are the same as those
+/// of the traits. The auto trait and blanket impls are the same for most
+/// types, so they're listed after the others, together under the
+/// [`AUTO_AND_BLANKET_IMPLS_HEADING`] unless they are to be expanded into
+/// their own groups.
+pub struct RustdocTraitImplsHandler {
+    expand_auto_impls: bool,
+    /// The offset at which the heading of a trait impl section started.
+    heading_start: Option<usize>,
+    /// The group of the trait impl list being visited, and the offset at
+    /// which it started.
+    list_start: Option<(Option<&'static str>, usize)>,
+    /// The offset at which the impl header being visited started.
+    header_start: Option<usize>,
+    headers: Vec<String>,
+    has_implements_heading: bool,
+    /// The headers of the auto trait and blanket impls listed together so far.
+    auto_impl_headers: Vec<String>,
+    /// The range of the group that they're listed in, up to the end of its
+    /// last header.
+    auto_impls_range: Option<Range<usize>>,
+}
+
+impl RustdocTraitImplsHandler {
+    pub fn new(expand_auto_impls: bool) -> Self {
+        Self {
+            expand_auto_impls,
+            heading_start: None,
+            list_start: None,
+            header_start: None,
+            headers: Vec::new(),
+            has_implements_heading: false,
+            auto_impl_headers: Vec::new(),
+            auto_impls_range: None,
+        }
+    }
+
+    /// Lists the headers of the auto trait or blanket impls that were just
+    /// visited along with those listed before them, replacing the group they
+    /// were listed in if nothing else was written since.
+    fn list_auto_impls(&mut self, writer: &mut MarkdownWriter) {
+        match self.auto_impls_range.take() {
+            Some(range) if writer.markdown().trim_end().len() == range.end => {
+                writer.truncate(range.start);
+            }
+            _ => self.auto_impl_headers.clear(),
+        }
+        self.auto_impl_headers.append(&mut self.headers);
+
+        let start = writer.markdown().len();
+        writer.push_str(&format!(
+            "\n\n### {AUTO_AND_BLANKET_IMPLS_HEADING} ({})\n\n",
+            self.auto_impl_headers.len()
+        ));
+        for header in &self.auto_impl_headers {
+            writer.push_str(&format!("- `{header}`\n"));
+        }
+        self.auto_impls_range = Some(start..writer.markdown().trim_end().len());
+    }
+
+    fn section(
+        tag: &HtmlElement,
+    ) -> Option<&'static (&'static str, &'static str, Option<&'static str>)> {
+        let id = tag.attr("id")?;
+        TRAIT_IMPL_SECTIONS
+            .iter()
+            .find(|(heading_id, list_id, _group)| match tag.tag() {
+                "h2" => *heading_id == id,
+                "div" => *list_id == id,
+                _ => false,
+            })
+    }
+}
+
+impl HandleTag for RustdocTraitImplsHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "h2" | "div" | "h3" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match tag.tag() {
+            "h2" => {
+                if Self::section(tag).is_some() {
+                    // Include the `## ` that the `HeadingHandler` has already
+                    // written, so that the whole heading is removed.
+                    let markdown = writer.markdown();
+                    let heading_start = markdown.trim_end_matches(['#', ' ']).len();
+                    self.heading_start = Some(heading_start);
+                }
+            }
+            "div" => {
+                if let Some((_heading_id, _list_id, group)) = Self::section(tag) {
+                    self.list_start = Some((*group, writer.markdown().len()));
+                    self.headers.clear();
+                }
+            }
+            "h3" => {
+                if self.list_start.is_some() && tag.has_class("code-header") {
+                    self.header_start = Some(writer.markdown().len());
+                }
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        match tag.tag() {
+            "h2" => {
+                if let Some(heading_start) = self.heading_start.take() {
+                    writer.truncate(heading_start);
+                }
+            }
+            "h3" => {
+                if let Some(header_start) = self.header_start.take() {
+                    let header = collapse_whitespace(writer.markdown_since(header_start));
+                    let header = header.trim().trim_end_matches(',');
+                    if !header.is_empty() {
+                        self.headers.push(header.to_string());
+                    }
+                }
+            }
+            "div" => {
+                if Self::section(tag).is_none() {
+                    return;
+                }
+                let Some((group, list_start)) = self.list_start.take() else {
+                    return;
+                };
+
+                writer.truncate(list_start);
+                if self.headers.is_empty() {
+                    return;
+                }
+                if !self.has_implements_heading {
+                    writer.push_str("\n\n## Implements\n\n");
+                    self.has_implements_heading = true;
+                }
+                if let Some(group) = group {
+                    if !self.expand_auto_impls {
+                        self.list_auto_impls(writer);
+                        return;
+                    }
+                    writer.push_str(&format!("\n\n### {group}\n\n"));
+                }
+                for header in self.headers.drain(..) {
+                    writer.push_str(&format!("- `{header}`\n"));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DefinitionKind {
+    /// The declaration of the item the page is for.
+    Declaration,
+    /// The header of an `impl` block.
+    ImplHeader,
+    /// An associated item within an `impl` block.
+    AssociatedItem,
+    /// An item listed on a module page.
+    ModuleItem(RustdocItemKind),
+}
+
+struct PendingDefinition {
+    kind: DefinitionKind,
+    tag: String,
+    text: String,
+}
+
+/// Collects the definitions on a rustdoc page, discarding everything else.
+pub struct RustdocDefinitionsCollector {
+    pub definitions: Vec<String>,
+    pending: Option<PendingDefinition>,
+}
+
+impl RustdocDefinitionsCollector {
+    pub fn new() -> Self {
+        Self {
+            definitions: Vec::new(),
+            pending: None,
+        }
+    }
+
+    fn definition_kind(tag: &HtmlElement, writer: &MarkdownWriter) -> Option<DefinitionKind> {
+        match tag.tag() {
+            "pre" if tag.has_class("item-decl") => Some(DefinitionKind::Declaration),
+            "h3" if tag.has_class("code-header") => Some(DefinitionKind::ImplHeader),
+            "h4" if tag.has_class("code-header") => Some(DefinitionKind::AssociatedItem),
+            "a" if RustdocItemHandler::is_inside_item_name(writer) => RustdocItemKind::iter()
+                .find(|kind| tag.has_class(kind.class()))
+                .map(DefinitionKind::ModuleItem),
+            _ => None,
+        }
+    }
+}
+
+impl HandleTag for RustdocDefinitionsCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "pre" | "h3" | "h4" | "a" | "div" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if let Some(pending) = self.pending.as_mut() {
+            if tag.tag() == "a" && tag.has_class("anchor") {
+                return StartTagOutcome::Skip;
+            }
+            if tag.has_class("where") {
+                pending.text.push(' ');
+            }
+
+            return StartTagOutcome::Continue;
+        }
+
+        // The auto trait and blanket impls are the same for most types, so
+        // they aren't worth including.
+        let ids_to_skip = [
+            "synthetic-implementations-list",
+            "blanket-implementations-list",
+        ];
+        if let Some(id) = tag.attr("id") {
+            if ids_to_skip.contains(&id.as_str()) {
+                return StartTagOutcome::Skip;
+            }
+        }
+
+        if let Some(kind) = Self::definition_kind(tag, writer) {
+            self.pending = Some(PendingDefinition {
+                kind,
+                tag: tag.tag().to_string(),
+                text: String::new(),
+            });
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, _writer: &mut MarkdownWriter) {
+        if self
+            .pending
+            .as_ref()
+            .map_or(true, |pending| pending.tag != tag.tag())
+        {
+            return;
+        }
+        let Some(PendingDefinition { kind, text, .. }) = self.pending.take() else {
+            return;
+        };
+
+        let definition = match kind {
+            DefinitionKind::Declaration => text.trim().to_string(),
+            DefinitionKind::ImplHeader => {
+                // Separate each `impl` block from the definitions that precede it.
+                if !self.definitions.is_empty() {
+                    self.definitions.push(String::new());
+                }
+                collapse_whitespace(&text).trim().to_string()
+            }
+            DefinitionKind::AssociatedItem => {
+                format!("    {};", collapse_whitespace(&text).trim())
+            }
+            DefinitionKind::ModuleItem(item_kind) => {
+                format!("{} {}", item_kind.class(), text.trim())
+            }
+        };
+        self.definitions.push(definition);
+    }
+
+    fn handle_text(&mut self, text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if let Some(pending) = self.pending.as_mut() {
+            pending.text.push_str(text);
+        }
+
+        // Everything outside of a definition is prose, which gets discarded.
+        HandlerOutcome::Handled
+    }
+}
+
+/// The IDs of the sections on a module page that list items without a
+/// [`RustdocItemKind`].
+const OTHER_ITEM_SECTIONS: [&str; 7] = [
+    "reexports",
+    "primitives",
+    "statics",
+    "unions",
+    "keywords",
+    "foreign-types",
+    "trait-aliases",
+];
+
+/// Removes the sections of a module page that list items of kinds other than
+/// the given ones.
+pub struct RustdocItemKindFilter {
+    item_kinds: Vec<RustdocItemKind>,
+    /// Whether the next item table is in a section that's filtered out.
+    skip_item_table: bool,
+    /// Whether the page lists any items of the given kinds.
+    pub has_matching_items: bool,
+}
+
+impl RustdocItemKindFilter {
+    /// Returns a filter for the given item kinds, which keeps every section
+    /// when empty.
+    pub fn new(item_kinds: Vec<RustdocItemKind>) -> Self {
+        Self {
+            item_kinds,
+            skip_item_table: false,
+            has_matching_items: false,
+        }
+    }
+}
+
+impl HandleTag for RustdocItemKindFilter {
+    fn should_handle(&self, tag: &str) -> bool {
+        if self.item_kinds.is_empty() {
+            return false;
+        }
+
+        match tag {
+            "h2" | "ul" | "dl" | "div" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        _writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if tag.tag() == "h2" {
+            self.skip_item_table = false;
+            let Some(id) = tag.attr("id") else {
+                return StartTagOutcome::Continue;
+            };
+
+            if self.item_kinds.iter().any(|kind| kind.section_id() == id) {
+                self.has_matching_items = true;
+            } else if RustdocItemKind::iter().any(|kind| kind.section_id() == id)
+                || OTHER_ITEM_SECTIONS.contains(&id.as_str())
+            {
+                self.skip_item_table = true;
+                return StartTagOutcome::Skip;
+            }
+        } else if self.skip_item_table && tag.has_class("item-table") {
+            self.skip_item_table = false;
+            return StartTagOutcome::Skip;
+        }
+
+        StartTagOutcome::Continue
+    }
+}
+
+/// Removes the "Layout" section that rustdoc emits for types when run with
+/// `--show-type-layout`.
+pub struct RustdocLayoutSectionRemover;
+
+impl HandleTag for RustdocLayoutSectionRemover {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "h2" | "div" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        _writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        let is_layout_heading = tag.tag() == "h2" && tag.attr("id").as_deref() == Some("layout");
+        if is_layout_heading || tag.has_class("type-layout") {
+            return StartTagOutcome::Skip;
+        }
+
+        StartTagOutcome::Continue
+    }
+}
+
+/// Collects the layout information on a rustdoc page, discarding everything
+/// else.
+pub struct RustdocLayoutCollector {
+    pub layout: Vec<String>,
+    /// The tag and text of the element currently being collected, if any.
+    pending: Option<(String, String)>,
+}
+
+impl RustdocLayoutCollector {
+    pub fn new() -> Self {
+        Self {
+            layout: Vec::new(),
+            pending: None,
+        }
+    }
+
+    fn is_inside_type_layout(writer: &MarkdownWriter) -> bool {
+        writer
+            .current_element_stack()
+            .iter()
+            .any(|element| element.has_class("type-layout"))
+    }
+}
+
+impl HandleTag for RustdocLayoutCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "pre" | "p" | "li" | "code" | "div" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match tag.tag() {
+            "pre" if tag.has_class("item-decl") => {
+                self.pending = Some((tag.tag().to_string(), String::new()));
+            }
+            "p" | "li" if Self::is_inside_type_layout(writer) => {
+                self.pending = Some((tag.tag().to_string(), String::new()));
+            }
+            "code" => {
+                if let Some((pending_tag, text)) = self.pending.as_mut() {
+                    if pending_tag != "pre" {
+                        text.push('`');
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, _writer: &mut MarkdownWriter) {
+        match tag.tag() {
+            "code" => {
+                if let Some((pending_tag, text)) = self.pending.as_mut() {
+                    if pending_tag != "pre" {
+                        text.push('`');
+                    }
+                }
+                return;
+            }
+            "div" => {
+                if tag.has_class("code-attribute") {
+                    if let Some((_, text)) = self.pending.as_mut() {
+                        text.push('\n');
+                    }
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        if self
+            .pending
+            .as_ref()
+            .map_or(true, |(pending_tag, _)| pending_tag != tag.tag())
+        {
+            return;
+        }
+        let Some((pending_tag, text)) = self.pending.take() else {
+            return;
+        };
+
+        if pending_tag == "pre" {
+            self.layout.extend(
+                text.lines()
+                    .map(|line| line.trim())
+                    .filter(|line| line.starts_with("#[repr("))
+                    .map(|line| format!("`{line}`")),
+            );
+        } else {
+            // Skip the note about layout being unstable, as it's the same for
+            // every type.
+            let text = collapse_whitespace(&text).trim().to_string();
+            if !text.is_empty() && !text.starts_with("Note:") {
+                self.layout.push(text);
+            }
+        }
+    }
+
+    fn handle_text(&mut self, text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if let Some((_, pending_text)) = self.pending.as_mut() {
+            pending_text.push_str(text);
+        }
+
+        HandlerOutcome::Handled
+    }
+}
+
+/// Collects the signature of a method on a rustdoc page, discarding
+/// everything else.
+pub struct RustdocMethodSignatureCollector {
+    section_ids: [String; 2],
+    /// The signature of the method, without its `where` clause.
+    pub signature: Option<String>,
+    pub where_clause: Option<String>,
+    is_collecting: bool,
+    is_inside_where_clause: bool,
+}
+
+impl RustdocMethodSignatureCollector {
+    pub fn new(method_name: &str) -> Self {
+        Self {
+            // Required trait methods have a different ID from provided ones.
+            section_ids: [
+                format!("method.{method_name}"),
+                format!("tymethod.{method_name}"),
+            ],
+            signature: None,
+            where_clause: None,
+            is_collecting: false,
+            is_inside_where_clause: false,
+        }
+    }
+
+    fn is_inside_method_section(&self, writer: &MarkdownWriter) -> bool {
+        writer.current_element_stack().iter().any(|element| {
+            element
+                .attr("id")
+                .map_or(false, |id| self.section_ids.contains(&id))
+        })
+    }
+}
+
+impl HandleTag for RustdocMethodSignatureCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "h4" | "div" | "span" | "a" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if self.is_collecting {
+            if tag.tag() == "a" && tag.has_class("anchor") {
+                return StartTagOutcome::Skip;
+            }
+            if tag.has_class("where") {
+                self.is_inside_where_clause = true;
+                self.where_clause.get_or_insert_with(String::new);
+            }
+
+            return StartTagOutcome::Continue;
+        }
+
+        // Only the first method with the name is used, as the rest are
+        // usually the same method in other impls.
+        if tag.tag() == "h4"
+            && tag.has_class("code-header")
+            && self.signature.is_none()
+            && self.is_inside_method_section(writer)
+        {
+            self.is_collecting = true;
+            self.signature = Some(String::new());
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, _writer: &mut MarkdownWriter) {
+        if !self.is_collecting {
+            return;
+        }
+
+        match tag.tag() {
+            "h4" => {
+                self.is_collecting = false;
+                self.is_inside_where_clause = false;
+            }
+            _ if tag.has_class("where") => self.is_inside_where_clause = false,
+            _ => {}
+        }
+    }
+
+    fn handle_text(&mut self, text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if self.is_collecting {
+            let collected_text = if self.is_inside_where_clause {
+                self.where_clause.as_mut()
+            } else {
+                self.signature.as_mut()
+            };
+            if let Some(collected_text) = collected_text {
+                collected_text.push_str(text);
+            }
+        }
+
+        HandlerOutcome::Handled
+    }
+}
+
+/// Returns whether the given heading starts one of the sections of a trait's
+/// page that list the impls of the trait.
+fn is_implementors_heading(tag: &HtmlElement) -> bool {
+    tag.tag() == "h2"
+        && matches!(
+            tag.attr("id").as_deref(),
+            Some("implementors" | "foreign-impls" | "synthetic-implementors")
+        )
+}
+
+/// Returns the type that the impl with the given header is for, e.g.,
+/// `WindowsMut<'w, T>` for `impl<'w, T> LendingIterator for WindowsMut<'w, T>`.
+fn implementor_type(impl_header: &str) -> Option<String> {
+    let mut depth = 0;
+    let mut previous_char = None;
+    for (ix, char) in impl_header.char_indices() {
+        match char {
+            '<' | '(' | '[' => depth += 1,
+            // Don't mistake the `>` in a `->` for a closing bracket.
+            '>' if previous_char == Some('-') => {}
+            '>' | ')' | ']' => depth -= 1,
+            _ if depth == 0 && impl_header[ix..].starts_with(" for ") => {
+                let implementor = impl_header[ix + " for ".len()..].trim();
+                return (!implementor.is_empty()).then(|| implementor.to_string());
+            }
+            _ => {}
+        }
+        previous_char = Some(char);
+    }
+
+    None
+}
+
+/// Removes the sections of a trait's page that list the impls of the trait,
+/// which run from their heading up to the next section's heading.
+pub struct RustdocImplementorsSectionRemover {
+    is_inside_implementors: bool,
+}
+
+impl RustdocImplementorsSectionRemover {
+    pub fn new() -> Self {
+        Self {
+            is_inside_implementors: false,
+        }
+    }
+}
+
+impl HandleTag for RustdocImplementorsSectionRemover {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "h2" | "div" | "details" | "section" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        _writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if tag.tag() == "h2" {
+            self.is_inside_implementors = is_implementors_heading(tag);
+        }
+        if self.is_inside_implementors {
+            return StartTagOutcome::Skip;
+        }
+
+        StartTagOutcome::Continue
+    }
+}
+
+/// Collects the types that the impls in the sections of a trait's page that
+/// list the impls of the trait are for, discarding everything else.
+pub struct RustdocImplementorsCollector {
+    pub implementors: Vec<String>,
+    is_inside_implementors: bool,
+    /// The text of the impl header being collected.
+    code_header: Option<String>,
+}
+
+impl RustdocImplementorsCollector {
+    pub fn new() -> Self {
+        Self {
+            implementors: Vec::new(),
+            is_inside_implementors: false,
+            code_header: None,
+        }
+    }
+}
+
+impl HandleTag for RustdocImplementorsCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "h2" | "h3" | "div" | "span" | "a" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        _writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if self.code_header.is_some() {
+            // Leave out the anchor links, the "notable traits" tooltips, and
+            // the `where` clauses.
+            let is_link_to_skip = tag.tag() == "a" && tag.has_any_classes(&["anchor", "tooltip"]);
+            if is_link_to_skip || tag.has_class("where") {
+                return StartTagOutcome::Skip;
+            }
+
+            return StartTagOutcome::Continue;
+        }
+
+        match tag.tag() {
+            "h2" => self.is_inside_implementors = is_implementors_heading(tag),
+            "h3" if self.is_inside_implementors && tag.has_class("code-header") => {
+                self.code_header = Some(String::new());
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, _writer: &mut MarkdownWriter) {
+        if tag.tag() != "h3" {
+            return;
+        }
+        let Some(code_header) = self.code_header.take() else {
+            return;
+        };
+        if let Some(implementor) = implementor_type(&compact_signature(&code_header)) {
+            self.implementors.push(implementor);
+        }
+    }
+
+    fn handle_text(&mut self, text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if let Some(code_header) = self.code_header.as_mut() {
+            code_header.push_str(text);
+        }
+
+        HandlerOutcome::Handled
+    }
+}
+
+/// The list of impls on a rustdoc page that a [`RustdocMethodIndexCollector`]
+/// is visiting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImplList {
+    Inherent,
+    Trait,
+}
+
+/// The kind of an associated item on a rustdoc page, as told by the ID of
+/// its section, e.g., `associatedtype.Item`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssociatedItemKind {
+    Method,
+    Type,
+    Constant,
+}
+
+/// Collects the headers of the inherent and trait impls on a rustdoc page,
+/// along with the signatures of their methods and associated items, as well
+/// as the associated items of a trait, discarding everything else.
+pub struct RustdocMethodIndexCollector {
+    pub method_index: MethodIndex,
+    impl_list: Option<ImplList>,
+    /// The text of the impl header or method signature being collected.
+    code_header: Option<String>,
+    /// The kind of the associated item whose signature is being collected.
+    code_header_kind: Option<AssociatedItemKind>,
+}
+
+impl RustdocMethodIndexCollector {
+    pub fn new() -> Self {
+        Self {
+            method_index: MethodIndex::default(),
+            impl_list: None,
+            code_header: None,
+            code_header_kind: None,
+        }
+    }
+
+    /// Returns the kind of the associated item whose section is being
+    /// visited, if any.
+    fn associated_item_kind(writer: &MarkdownWriter) -> Option<AssociatedItemKind> {
+        let section = writer
+            .current_element_stack()
+            .iter()
+            .rev()
+            .find(|element| element.tag() == "section")?;
+        let id = section.attr("id").unwrap_or_default();
+        if id.starts_with("associatedtype.") {
+            Some(AssociatedItemKind::Type)
+        } else if id.starts_with("associatedconstant.") {
+            Some(AssociatedItemKind::Constant)
+        } else if section.has_class("method") {
+            Some(AssociatedItemKind::Method)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether an impl's items are being visited, e.g., one of the
+    /// implementors listed on a trait's page.
+    fn is_inside_impl_items(writer: &MarkdownWriter) -> bool {
+        writer
+            .current_element_stack()
+            .iter()
+            .any(|element| element.has_class("impl-items"))
+    }
+
+    fn impl_list(tag: &HtmlElement) -> Option<ImplList> {
+        match tag.attr("id").as_deref() {
+            Some("implementations-list") => Some(ImplList::Inherent),
+            Some("trait-implementations-list") => Some(ImplList::Trait),
+            _ => None,
+        }
+    }
+}
+
+impl HandleTag for RustdocMethodIndexCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "div" | "h3" | "h4" | "span" | "a" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if self.code_header.is_some() {
+            // Leave out the anchor links, the "notable traits" tooltips, and
+            // the `where` clauses.
+            let is_link_to_skip = tag.tag() == "a" && tag.has_any_classes(&["anchor", "tooltip"]);
+            if is_link_to_skip || tag.has_class("where") {
+                return StartTagOutcome::Skip;
+            }
+
+            return StartTagOutcome::Continue;
+        }
+
+        match tag.tag() {
+            "div" => {
+                if let Some(impl_list) = Self::impl_list(tag) {
+                    self.impl_list = Some(impl_list);
+                }
+            }
+            "h3" if self.impl_list.is_some() && tag.has_class("code-header") => {
+                self.code_header = Some(String::new());
+            }
+            "h4" if tag.has_class("code-header") => {
+                let is_collected = match Self::associated_item_kind(writer) {
+                    Some(AssociatedItemKind::Method) => self.impl_list.is_some(),
+                    // The associated items of the trait itself aren't in an
+                    // impl, unlike those of its implementors.
+                    Some(_) => self.impl_list.is_some() || !Self::is_inside_impl_items(writer),
+                    None => false,
+                };
+                if is_collected {
+                    self.code_header = Some(String::new());
+                    self.code_header_kind = Self::associated_item_kind(writer);
+                }
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, _writer: &mut MarkdownWriter) {
+        match tag.tag() {
+            "div" if Self::impl_list(tag).is_some() => self.impl_list = None,
+            "h3" | "h4" => {
+                let Some(code_header) = self.code_header.take() else {
+                    return;
+                };
+                let code_header = compact_signature(&code_header);
+                let kind = self.code_header_kind.take();
+                let method_index = &mut self.method_index;
+                let list = match (tag.tag(), self.impl_list, kind) {
+                    ("h3", Some(ImplList::Trait), _) => {
+                        method_index.trait_impls.push((code_header, Vec::new()));
+                        return;
+                    }
+                    ("h4", Some(ImplList::Trait), _) => match method_index.trait_impls.last_mut() {
+                        Some((_header, items)) => items,
+                        None => return,
+                    },
+                    ("h4", _, Some(AssociatedItemKind::Method)) => {
+                        &mut method_index.inherent_methods
+                    }
+                    ("h4", _, Some(AssociatedItemKind::Type)) => &mut method_index.associated_types,
+                    ("h4", _, Some(AssociatedItemKind::Constant)) => {
+                        &mut method_index.associated_consts
+                    }
+                    _ => return,
+                };
+                if !list.contains(&code_header) {
+                    list.push(code_header);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_text(&mut self, text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if let Some(code_header) = self.code_header.as_mut() {
+            code_header.push_str(text);
+        }
+
+        HandlerOutcome::Handled
+    }
+}
+
+/// Flattens the given signature onto a single line, without the trailing
+/// commas that rustdoc adds when it wraps the parameters onto their own lines.
+fn compact_signature(signature: &str) -> String {
+    collapse_whitespace(signature)
+        .trim()
+        .trim_end_matches(',')
+        .replace("( ", "(")
+        .replace(", )", ")")
+        .replace(" )", ")")
+}
+
+/// Collects the declaration of the item on a rustdoc page and whether it is
+/// marked as not being dyn compatible, discarding everything else.
+pub struct RustdocDynCompatibilityCollector {
+    pub declaration: Option<String>,
+    pub is_marked_dyn_incompatible: bool,
+    is_collecting: bool,
+}
+
+impl RustdocDynCompatibilityCollector {
+    pub fn new() -> Self {
+        Self {
+            declaration: None,
+            is_marked_dyn_incompatible: false,
+            is_collecting: false,
+        }
+    }
+}
+
+impl HandleTag for RustdocDynCompatibilityCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "pre" | "h2" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        _writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match tag.tag() {
+            "pre" if tag.has_class("item-decl") && self.declaration.is_none() => {
+                self.is_collecting = true;
+                self.declaration = Some(String::new());
+            }
+            "h2" => {
+                // The section was called "Object Safety" before it was renamed
+                // to "Dyn Compatibility" in Rust 1.83.
+                let id = tag.attr("id");
+                if matches!(id.as_deref(), Some("dyn-compatibility" | "object-safety")) {
+                    self.is_marked_dyn_incompatible = true;
+                }
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, _writer: &mut MarkdownWriter) {
+        if tag.tag() == "pre" {
+            self.is_collecting = false;
+        }
+    }
+
+    fn handle_text(&mut self, text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if self.is_collecting {
+            if let Some(declaration) = self.declaration.as_mut() {
+                declaration.push_str(text);
+            }
+        }
+
+        HandlerOutcome::Handled
+    }
+}
+
+pub struct RustdocDeclarationCollector {
+    pub declaration: Option<String>,
+    is_collecting: bool,
+}
+
+impl RustdocDeclarationCollector {
+    pub fn new() -> Self {
+        Self {
+            declaration: None,
+            is_collecting: false,
+        }
+    }
+}
+
+impl HandleTag for RustdocDeclarationCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "pre"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        _writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if tag.has_class("item-decl") && self.declaration.is_none() {
+            self.is_collecting = true;
+            self.declaration = Some(String::new());
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, _tag: &HtmlElement, _writer: &mut MarkdownWriter) {
+        self.is_collecting = false;
+    }
+
+    fn handle_text(&mut self, text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if self.is_collecting {
+            if let Some(declaration) = self.declaration.as_mut() {
+                declaration.push_str(text);
+            }
+        }
+
+        HandlerOutcome::Handled
+    }
+}
+
+/// Collects the types and traits linked to from the declaration and the
+/// top-level docs of the item on a rustdoc page.
+pub struct RustdocReferenceCollector {
+    pub references: IndexSet<String>,
+}
+
+impl RustdocReferenceCollector {
+    /// The kinds of items that are collected, as they appear in the `title`
+    /// of rustdoc's links, e.g., `struct tokio::sync::Mutex`.
+    const REFERENCE_KINDS: [&'static str; 5] = ["struct", "enum", "union", "trait", "type"];
+
+    pub fn new() -> Self {
+        Self {
+            references: IndexSet::new(),
+        }
+    }
+
+    fn is_inside_declaration_or_top_doc(writer: &MarkdownWriter) -> bool {
+        writer.current_element_stack().iter().any(|element| {
+            (element.tag() == "pre" && element.has_class("item-decl"))
+                || (element.tag() == "details" && element.has_class("top-doc"))
+        })
+    }
+}
+
+impl HandleTag for RustdocReferenceCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "a"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if !Self::is_inside_declaration_or_top_doc(writer) {
+            return StartTagOutcome::Continue;
+        }
+
+        // rustdoc titles the links to items with their kind and full path.
+        if let Some(title) = tag.attr("title") {
+            if let Some((kind, path)) = title.split_once(' ') {
+                if Self::REFERENCE_KINDS.contains(&kind) && path.contains("::") {
+                    self.references.insert(path.to_string());
+                }
+            }
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_text(&mut self, _text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        HandlerOutcome::Handled
+    }
+}
+
+/// Collects the items that a rustdoc page links to from its docs and its
+/// sidebar, along with the items that are documented on the page, for the
+/// "See also" list.
+///
+/// The sidebar is visited too, so this runs without the chrome being removed.
+pub struct RustdocSeeAlsoCollector {
+    location: RustdocPageLocation,
+    /// The text of the page's title, e.g., `Struct tokio::sync::Mutex`.
+    title: String,
+    links: IndexSet<String>,
+    /// The items listed on the page, e.g., in the item tables of a module.
+    listed_items: HashSet<String>,
+}
+
+impl RustdocSeeAlsoCollector {
+    pub fn new(location: RustdocPageLocation) -> Self {
+        Self {
+            location,
+            title: String::new(),
+            links: IndexSet::new(),
+            listed_items: HashSet::new(),
+        }
+    }
+
+    fn is_inside_sidebar(writer: &MarkdownWriter) -> bool {
+        writer
+            .current_element_stack()
+            .iter()
+            .any(|element| element.has_class("sidebar-elems"))
+    }
+
+    /// Returns the linked items that aren't documented on the page.
+    pub fn see_also(&self) -> Vec<String> {
+        // The title is the kind of the item followed by its full path, e.g.,
+        // `Struct tokio::sync::Mutex` or `Crate tokio`.
+        let title = collapse_whitespace(&self.title);
+        let (kind, page_path) = title.rsplit_once(' ').unwrap_or(("", title.as_str()));
+        // Unlike the items in a module, the associated items of a type or
+        // trait are documented on its page.
+        let documents_children = !matches!(kind, "Module" | "Crate");
+        let child_prefix = format!("{page_path}::");
+
+        self.links
+            .iter()
+            .filter(|path| {
+                path.as_str() != page_path
+                    && !(documents_children && path.starts_with(&child_prefix))
+                    && !self.listed_items.contains(*path)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+impl HandleTag for RustdocSeeAlsoCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "head" | "script" | "button" | "span" | "a" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match tag.tag() {
+            "head" | "script" | "button" => return StartTagOutcome::Skip,
+            // Older versions of rustdoc have the version and the link to the
+            // source inside of the title.
+            "span" => {
+                if tag.has_class("out-of-band") {
+                    return StartTagOutcome::Skip;
+                }
+            }
+            "a" => {
+                let target = tag
+                    .attr("href")
+                    .and_then(|href| RustdocLinkTarget::resolve(&href, &self.location));
+                if let Some(RustdocLinkTarget::Item(path)) = target {
+                    if RustdocItemHandler::is_inside_item_name(writer) {
+                        self.listed_items.insert(path);
+                    } else if RustdocLinkHandler::is_inside_prose(writer)
+                        || Self::is_inside_sidebar(writer)
+                    {
+                        self.links.insert(path);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_text(&mut self, text: &str, writer: &mut MarkdownWriter) -> HandlerOutcome {
+        // The headings in the docs start at `<h2>`, so the only `<h1>` is the
+        // page's title.
+        if writer.is_inside("h1") {
+            self.title.push_str(text);
+        }
+        HandlerOutcome::Handled
+    }
+}
+
+/// A re-export that a [`RustdocReexportCollector`] is visiting.
+struct PendingReexport {
+    name: String,
+    /// The kind, full path, and link of the last item linked to so far.
+    target: Option<(RustdocItemKind, String, String)>,
+    is_glob: bool,
+}
+
+/// Collects the re-exports listed on a rustdoc module page, discarding
+/// everything else.
+pub struct RustdocReexportCollector {
+    pub reexports: Vec<RustdocReexport>,
+    current_reexport: Option<PendingReexport>,
+}
+
+impl RustdocReexportCollector {
+    pub fn new() -> Self {
+        Self {
+            reexports: Vec::new(),
+            current_reexport: None,
+        }
+    }
+
+    fn reexport_name(tag: &HtmlElement) -> Option<String> {
+        let id = tag.attr("id")?;
+        id.strip_prefix("reexport.").map(str::to_string)
+    }
+}
+
+impl HandleTag for RustdocReexportCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "div" | "dt" | "code" | "a" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        _writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match self.current_reexport.as_mut() {
+            None => {
+                if let Some(name) = Self::reexport_name(tag) {
+                    self.current_reexport = Some(PendingReexport {
+                        name,
+                        target: None,
+                        is_glob: false,
+                    });
+                }
+            }
+            Some(reexport) if tag.tag() == "a" => {
+                // rustdoc titles the links to items with their kind and full
+                // path, e.g., `struct tokio::fs::File`.
+                let title = tag.attr("title");
+                let target = title.as_deref().and_then(|title| title.split_once(' '));
+                if let (Some((kind, path)), Some(href)) = (target, tag.attr("href")) {
+                    if let Some(kind) = RustdocItemKind::from_class(kind) {
+                        reexport.target = Some((kind, path.to_string(), href));
+                    }
+                }
+            }
+            Some(_) => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, _writer: &mut MarkdownWriter) {
+        if Self::reexport_name(tag).is_none() {
+            return;
+        }
+
+        let Some(reexport) = self.current_reexport.take() else {
+            return;
+        };
+        if reexport.is_glob {
+            return;
+        }
+        if let Some((kind, canonical_path, href)) = reexport.target {
+            self.reexports.push(RustdocReexport {
+                name: reexport.name,
+                kind,
+                canonical_path,
+                href,
+            });
+        }
+    }
+
+    fn handle_text(&mut self, text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        if let Some(reexport) = self.current_reexport.as_mut() {
+            if text.contains('*') {
+                reexport.is_glob = true;
+            }
+        }
+
+        HandlerOutcome::Handled
+    }
+}
+
+/// Converts the warnings that crates call out with `<div class="warning">`,
+/// which rustdoc renders with a distinct style, to blockquotes.
+pub struct RustdocWarningHandler {
+    /// For each of the enclosing `<div>`s, the offset in the Markdown output
+    /// at which it starts, if it is a warning.
+    warning_starts: Vec<Option<usize>>,
+}
+
+impl RustdocWarningHandler {
+    pub fn new() -> Self {
+        Self {
+            warning_starts: Vec::new(),
+        }
+    }
+
+    /// Returns whether the given text already starts with a label, like
+    /// `**Note:**`, so that it doesn't need the default one.
+    fn starts_with_label(text: &str) -> bool {
+        let text = text.trim_start().trim_start_matches("**");
+        ["Note:", "Warning:", "Caution:", "Important:"]
+            .iter()
+            .any(|label| text.starts_with(label))
+    }
+}
+
+impl HandleTag for RustdocWarningHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "div"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        let is_warning = tag.has_class("warning");
+        self.warning_starts
+            .push(is_warning.then(|| writer.markdown().len()));
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, _tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        if let Some(Some(offset)) = self.warning_starts.pop() {
+            let label = if Self::starts_with_label(writer.markdown_since(offset)) {
+                None
+            } else {
+                Some("**Warning:**")
+            };
+            writer.quote_since(offset, label);
+        }
+    }
+}
+
+/// Converts the "Safety" sections of the docs, which spell out what the caller
+/// of an `unsafe` function must uphold, to blockquotes, e.g., `> **Safety:**
+/// The pointer must be aligned.`.
+///
+/// A section ends at the next heading of the same or a higher level, or at the
+/// end of the docs that it is in.
+pub struct RustdocSafetyHandler {
+    /// For each of the enclosing `<div>`s, whether it is a docblock.
+    docblocks: Vec<bool>,
+    /// The Safety section currently being written, if any.
+    section: Option<SafetySection>,
+}
+
+struct SafetySection {
+    /// The offset in the Markdown output at which the section starts.
+    start: usize,
+    /// The level of the section's heading, e.g., 2 for `<h2>`.
+    level: usize,
+    /// The number of docblocks that the section is in.
+    docblock_depth: usize,
+}
+
+impl RustdocSafetyHandler {
+    pub fn new() -> Self {
+        Self {
+            docblocks: Vec::new(),
+            section: None,
+        }
+    }
+
+    fn docblock_depth(&self) -> usize {
+        self.docblocks
+            .iter()
+            .filter(|is_docblock| **is_docblock)
+            .count()
+    }
+
+    /// Returns whether the given heading is for a Safety section, whose ID
+    /// rustdoc suffixes with a number when there's more than one on a page,
+    /// e.g., `safety-1`.
+    fn is_safety_heading(tag: &HtmlElement) -> bool {
+        let Some(id) = tag.attr("id") else {
+            return false;
+        };
+        id == "safety"
+            || id
+                .strip_prefix("safety-")
+                .map_or(false, |suffix| suffix.parse::<usize>().is_ok())
+    }
+
+    fn end_section(&mut self, writer: &mut MarkdownWriter) {
+        let Some(section) = self.section.take() else {
+            return;
+        };
+
+        // The blank lines between the blocks in the section would otherwise
+        // add up once it is quoted.
+        let text = normalize_markdown(writer.markdown_since(section.start).trim(), |_| false);
+        // A label in front of a list or a code block would break it, so it
+        // goes on its own line instead.
+        let starts_with_block = text.starts_with(['-', '*', '#', '>', '|'])
+            || text.starts_with("```")
+            || text
+                .split_once(". ")
+                .map_or(false, |(number, _)| number.parse::<usize>().is_ok());
+        writer.truncate(section.start);
+        if starts_with_block {
+            writer.push_str(&format!("**Safety:**\n\n{text}"));
+            writer.quote_since(section.start, None);
+        } else {
+            writer.push_str(&text);
+            writer.quote_since(section.start, Some("**Safety:**"));
+        }
+    }
+}
+
+impl HandleTag for RustdocSafetyHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if tag.tag() == "div" {
+            self.docblocks.push(tag.has_class("docblock"));
+            return StartTagOutcome::Continue;
+        }
+
+        let level = tag.tag()[1..].parse::<usize>().unwrap_or(1);
+        if self
+            .section
+            .as_ref()
+            .map_or(false, |section| level <= section.level)
+        {
+            self.end_section(writer);
+        }
+
+        let docblock_depth = self.docblock_depth();
+        if docblock_depth > 0 && self.section.is_none() && Self::is_safety_heading(tag) {
+            writer.push_blank_line();
+            self.section = Some(SafetySection {
+                start: writer.markdown().len(),
+                level,
+                docblock_depth,
+            });
+            // The label takes the place of the heading.
+            return StartTagOutcome::Skip;
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        if tag.tag() != "div" {
+            return;
+        }
+
+        let docblock_depth = self.docblock_depth();
+        if self.docblocks.pop() == Some(true)
+            && self
+                .section
+                .as_ref()
+                .map_or(false, |section| section.docblock_depth == docblock_depth)
+        {
+            self.end_section(writer);
+        }
+    }
+}
+
+/// Turns the banners that rustdoc shows for deprecated items into blockquotes,
+/// e.g., `> **Deprecated:** superseded by `trim_start` (since 1.33.0)`.
+pub struct RustdocDeprecationHandler {
+    /// For each of the enclosing `<div>`s and `<span>`s, the offset in the
+    /// Markdown output at which it starts, if it is a deprecation banner.
+    deprecation_starts: Vec<Option<usize>>,
+}
+
+impl RustdocDeprecationHandler {
+    pub fn new() -> Self {
+        Self {
+            deprecation_starts: Vec::new(),
+        }
+    }
+
+    fn is_inside_deprecation(&self) -> bool {
+        self.deprecation_starts.iter().any(Option::is_some)
+    }
+
+    /// Rewrites the text of a banner, e.g., "Deprecated since 1.33.0:
+    /// superseded by `trim_start`", with the version at the end.
+    fn notice(text: &str) -> String {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let Some(rest) = text.strip_prefix("Deprecated") else {
+            // E.g., "Deprecation planned".
+            return text;
+        };
+
+        let (since, message) = match rest.trim_start().strip_prefix("since ") {
+            Some(rest) => match rest.split_once(": ") {
+                Some((since, message)) => (Some(since), message),
+                None => (Some(rest), ""),
+            },
+            None => (None, rest.trim_start().trim_start_matches(':').trim_start()),
+        };
+
+        match (since, message.is_empty()) {
+            (Some(since), false) => format!("{message} (since {since})"),
+            (Some(since), true) => format!("Since {since}."),
+            (None, false) => message.to_string(),
+            (None, true) => "This item is deprecated.".to_string(),
+        }
+    }
+}
+
+impl HandleTag for RustdocDeprecationHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "div" | "span" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        // Skip the 👎 preceding the notice.
+        if self.is_inside_deprecation() && tag.has_class("emoji") {
+            return StartTagOutcome::Skip;
+        }
+
+        // The deprecated items listed on a module page are marked as such by
+        // the `RustdocItemHandler` instead.
+        let is_deprecation = tag.has_class("stab")
+            && tag.has_class("deprecated")
+            && !RustdocItemHandler::is_inside_item_name(writer);
+        self.deprecation_starts
+            .push(is_deprecation.then(|| writer.markdown().len()));
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, _tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        if let Some(Some(offset)) = self.deprecation_starts.pop() {
+            let notice = Self::notice(writer.markdown_since(offset));
+            writer.truncate(offset);
+            writer.push_str(&notice);
+            writer.quote_since(offset, Some("**Deprecated:**"));
+        }
+    }
+}
+
+/// Handles the `since` markers that rustdoc shows for items with a stability
+/// attribute, e.g., `#[stable(since = "1.0.0")]`.
+///
+/// The markers precede the header of the item they belong to, so they are
+/// held onto and appended to the next heading, if enabled.
+pub struct RustdocSinceHandler {
+    include_since: bool,
+    pending_since: Option<String>,
+}
+
+impl RustdocSinceHandler {
+    pub fn new(include_since: bool) -> Self {
+        Self {
+            include_since,
+            pending_since: None,
+        }
+    }
+
+    /// Returns the versions spelled out in the title of a `since` marker,
+    /// e.g., "1.0.0, const since 1.39.0" for "Stable since Rust version
+    /// 1.0.0, const since 1.39.0".
+    fn since(title: &str) -> String {
+        title
+            .trim_start_matches("Stable since Rust version ")
+            .to_string()
+    }
+}
+
+/// Collects the version that the item documented on a rustdoc page was
+/// stabilized in, from the `since` marker next to the page's title.
+pub struct RustdocPageSinceCollector {
+    pub since: Option<String>,
+}
+
+impl RustdocPageSinceCollector {
+    pub fn new() -> Self {
+        Self { since: None }
+    }
+}
+
+impl HandleTag for RustdocPageSinceCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "span"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        // Older versions of rustdoc have the marker inside of the title.
+        let is_inside_title = writer
+            .current_element_stack()
+            .iter()
+            .any(|element| element.has_class("main-heading") || element.tag() == "h1");
+        if self.since.is_none() && tag.has_class("since") && is_inside_title {
+            self.since = tag
+                .attr("title")
+                .map(|title| RustdocSinceHandler::since(&title));
+        }
+
+        StartTagOutcome::Continue
+    }
+}
+
+/// Collects the version of rustdoc that generated a page, which is in the
+/// `data-rustdoc-version` of the `rustdoc-vars` element, a `<meta>` in newer
+/// versions of rustdoc and a `<div>` in older ones.
+pub struct RustdocVersionCollector {
+    pub version: Option<String>,
+}
+
+impl RustdocVersionCollector {
+    pub fn new() -> Self {
+        Self { version: None }
+    }
+}
+
+impl HandleTag for RustdocVersionCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "meta" | "div" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        _writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if self.version.is_none() {
+            self.version = tag
+                .attr("data-rustdoc-version")
+                .map(|version| version.trim().to_string())
+                .filter(|version| !version.is_empty());
+        }
+
+        StartTagOutcome::Continue
+    }
+}
+
+/// Turns the banners that rustdoc shows for unstable items into blockquotes,
+/// e.g., `> **Unstable:** This is a nightly-only experimental API.
+/// (`allocator_api` #32838)`.
+pub struct RustdocUnstableHandler {
+    /// For each of the enclosing `<div>`s and `<span>`s, the offset in the
+    /// Markdown output at which it starts, if it is an unstable banner.
+    unstable_starts: Vec<Option<usize>>,
+}
+
+impl RustdocUnstableHandler {
+    pub fn new() -> Self {
+        Self {
+            unstable_starts: Vec::new(),
+        }
+    }
+}
+
+impl HandleTag for RustdocUnstableHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "div" | "span" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        // Skip the 🔬 preceding the notice.
+        if self.unstable_starts.iter().any(Option::is_some) && tag.has_class("emoji") {
+            return StartTagOutcome::Skip;
+        }
+
+        // The unstable items listed on a module page are marked as such by
+        // the `RustdocItemHandler` instead.
+        let is_unstable = tag.has_class("stab")
+            && tag.has_class("unstable")
+            && !RustdocItemHandler::is_inside_item_name(writer);
+        self.unstable_starts
+            .push(is_unstable.then(|| writer.markdown().len()));
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, _tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        if let Some(Some(offset)) = self.unstable_starts.pop() {
+            let notice = writer
+                .markdown_since(offset)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            writer.truncate(offset);
+            writer.push_str(&notice);
+            writer.quote_since(offset, Some("**Unstable:**"));
+        }
+    }
+}
+
+impl HandleTag for RustdocSinceHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "span" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        _writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        if tag.tag() == "span" && tag.has_class("since") {
+            self.pending_since = tag.attr("title").map(|title| Self::since(&title));
+
+            return StartTagOutcome::Skip;
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_text(&mut self, text: &str, writer: &mut MarkdownWriter) -> HandlerOutcome {
+        // Drop the separator between the marker and the link to the source,
+        // which are both left out.
+        let is_inside_rightside = writer
+            .current_element_stack()
+            .iter()
+            .any(|element| element.has_class("rightside"));
+        if is_inside_rightside && text.trim() == "·" {
+            return HandlerOutcome::Handled;
+        }
+
+        HandlerOutcome::NoOp
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        match tag.tag() {
+            // Older versions of rustdoc have the page's marker inside of its
+            // title, which the version is added to separately.
+            "h1" => {
+                self.pending_since.take();
+            }
+            "h2" | "h3" | "h4" | "h5" | "h6" => {
+                if let Some(since) = self.pending_since.take() {
+                    if self.include_since {
+                        writer.push_str(&format!(" (since {since})"));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A link in rustdoc prose, once resolved.
+#[derive(Debug, PartialEq, Eq)]
+enum RustdocLinkTarget {
+    /// A link to an item, by its full path (e.g., `std::sync::Mutex`).
+    Item(String),
+    /// A link to a page outside of the docs.
+    External(String),
+}
+
+impl RustdocLinkTarget {
+    /// The kinds of items that rustdoc has pages for, as they appear in the
+    /// page names (e.g., `struct.Mutex.html`).
+    const PAGE_KINDS: [&'static str; 14] = [
+        "struct",
+        "enum",
+        "union",
+        "trait",
+        "traitalias",
+        "type",
+        "fn",
+        "macro",
+        "attr",
+        "derive",
+        "constant",
+        "static",
+        "primitive",
+        "keyword",
+    ];
+
+    /// The kinds of associated items that rustdoc links to by their anchor
+    /// on the page of their parent (e.g., `#method.lock`).
+    const ANCHOR_KINDS: [&'static str; 6] = [
+        "method",
+        "tymethod",
+        "variant",
+        "structfield",
+        "associatedtype",
+        "associatedconstant",
+    ];
+
+    /// Resolves the given `href` from a page at the given location.
+    ///
+    /// Returns `None` for links that can't be resolved to an item, such as
+    /// links to sections on the same page or to the source code.
+    fn resolve(href: &str, location: &RustdocPageLocation) -> Option<Self> {
+        let (path, fragment) = match href.split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment)),
+            None => (href, None),
+        };
+
+        let segments = if let Some(path) = path.strip_prefix("https://doc.rust-lang.org/") {
+            // Skip the release channel or Rust version (e.g., `nightly`).
+            let mut segments = path.split('/').collect::<Vec<_>>();
+            if !STANDARD_LIBRARY_CRATES.contains(segments.first()?) {
+                segments.remove(0);
+            }
+            segments
+        } else if let Some(path) = path.strip_prefix("https://docs.rs") {
+            Self::docs_dot_rs_segments(path)?
+        } else if path.starts_with("http://") || path.starts_with("https://") {
+            return Some(Self::External(href.to_string()));
+        } else if path.starts_with('/') {
+            Self::docs_dot_rs_segments(path)?
+        } else if path.is_empty() || path.contains(':') {
+            return None;
+        } else {
+            let mut segments = vec![location.crate_name.as_str()];
+            segments.extend(location.module_path.iter().map(String::as_str));
+            for segment in path.split('/') {
+                match segment {
+                    "" | "." => {}
+                    ".." => {
+                        segments.pop();
+                    }
+                    segment => segments.push(segment),
+                }
+            }
+            segments
+        };
+
+        let (page, modules) = segments.split_last()?;
+        if modules.is_empty() {
+            return None;
+        }
+
+        let mut item_path = modules.to_vec();
+        if *page != "index.html" {
+            let (kind, name) = page.strip_suffix(".html")?.split_once('.')?;
+            if !Self::PAGE_KINDS.contains(&kind) || name.contains('.') {
+                return None;
+            }
+            if kind == "primitive" || kind == "keyword" {
+                item_path.truncate(1);
+            }
+            item_path.push(name);
+        }
+
+        if let Some((kind, name)) = fragment.and_then(|fragment| fragment.split_once('.')) {
+            if Self::ANCHOR_KINDS.contains(&kind) {
+                item_path.push(name);
+            }
+        }
+
+        Some(Self::Item(item_path.join("::")))
+    }
+
+    /// Returns the path segments of a `docs.rs` URL, starting from the crate
+    /// name, e.g., `tokio/sync/struct.Mutex.html` for
+    /// `/tokio/1.0.0/tokio/sync/struct.Mutex.html`.
+    fn docs_dot_rs_segments(path: &str) -> Option<Vec<&str>> {
+        let segments = path.trim_start_matches('/').split('/').collect::<Vec<_>>();
+        let crate_name = segments.first()?;
+        // The version may be followed by a target triple.
+        let crate_root = segments
+            .iter()
+            .skip(2)
+            .position(|segment| segment == crate_name)?;
+        Some(segments[crate_root + 2..].to_vec())
+    }
+
+    fn to_markdown(&self, text: &str) -> String {
+        match self {
+            Self::Item(path) | Self::External(path) => format!("[{text}]({path})"),
+        }
+    }
+}
+
+/// Keeps the links in rustdoc prose, resolving the relative links to items
+/// into their full paths.
+pub struct RustdocLinkHandler {
+    location: RustdocPageLocation,
+    /// The offset at which the text of each of the enclosing links starts,
+    /// along with its target, or `None` for links that aren't kept.
+    links: Vec<Option<(usize, RustdocLinkTarget)>>,
+}
+
+impl RustdocLinkHandler {
+    pub fn new(location: RustdocPageLocation) -> Self {
+        Self {
+            location,
+            links: Vec::new(),
+        }
+    }
+
+    fn is_inside_prose(writer: &MarkdownWriter) -> bool {
+        let stack = writer.current_element_stack();
+        stack.iter().any(|element| element.has_class("docblock"))
+            && !stack
+                .iter()
+                .any(|element| matches!(element.tag(), "pre" | "a"))
+    }
+}
+
+impl HandleTag for RustdocLinkHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "a"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        let target = Self::is_inside_prose(writer)
+            .then(|| tag.attr("href"))
+            .flatten()
+            .and_then(|href| RustdocLinkTarget::resolve(&href, &self.location));
+        self.links
+            .push(target.map(|target| (writer.markdown().len(), target)));
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, _tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        let Some(Some((offset, target))) = self.links.pop() else {
+            return;
+        };
+
+        let text = writer.markdown_since(offset).trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+
+        writer.truncate(offset);
+        writer.push_str(&target.to_markdown(&text));
+    }
+}
+
+pub struct RustdocChromeRemover;
+
+impl HandleTag for RustdocChromeRemover {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "head" | "script" | "nav" | "summary" | "button" | "div" | "span" | "a" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        _writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match tag.tag() {
+            "head" | "script" | "nav" => return StartTagOutcome::Skip,
+            "summary" => {
+                if tag.has_class("hideme") {
+                    return StartTagOutcome::Skip;
+                }
+            }
+            "button" => {
+                if tag.attr("id").as_deref() == Some("copy-path") {
+                    return StartTagOutcome::Skip;
+                }
+            }
+            "div" | "span" => {
+                let classes_to_skip = ["nav-container", "sidebar-elems", "out-of-band"];
+                if tag.has_any_classes(&classes_to_skip) {
+                    return StartTagOutcome::Skip;
+                }
+            }
+            // The links to the item's source and the buttons that run the
+            // examples on the playground.
+            "a" => {
+                if tag.has_any_classes(&["src", "test-arrow"]) {
+                    return StartTagOutcome::Skip;
+                }
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+}
+
+pub struct RustdocItemCollector {
+    pub items: IndexMap<RustdocItem, RustdocPageItem>,
+    /// The item in the list of items on a module page that is being visited.
+    listed_item: Option<RustdocItem>,
+    /// The offset in the Markdown output at which the summary of the listed
+    /// item started.
+    summary_start: Option<usize>,
+}
+
+impl RustdocItemCollector {
+    pub fn new() -> Self {
+        Self {
+            items: IndexMap::new(),
+            listed_item: None,
+            summary_start: None,
+        }
+    }
+
+    fn listed_item_mut(&mut self) -> Option<&mut RustdocPageItem> {
+        self.items.get_mut(self.listed_item.as_ref()?)
+    }
+
+    fn parse_item(tag: &HtmlElement) -> Option<RustdocItem> {
+        if tag.tag() != "a" {
+            return None;
+        }
+
+        let href = tag.attr("href")?;
+        if href.starts_with('#') || href.starts_with("https://") || href.starts_with("../") {
+            return None;
+        }
+
+        for kind in RustdocItemKind::iter() {
+            if tag.has_class(kind.class()) {
+                let mut parts = href.trim_end_matches("/index.html").split('/');
+
+                if let Some(last_component) = parts.next_back() {
+                    let last_component = match last_component.split_once('#') {
+                        Some((component, _fragment)) => component,
+                        None => last_component,
+                    };
+
+                    let name = last_component
+                        .trim_start_matches(&format!("{}.", kind.class()))
+                        .trim_end_matches(".html");
+
+                    return Some(RustdocItem {
+                        kind,
+                        name: name.into(),
+                        path: parts.map(Into::into).collect(),
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl HandleTag for RustdocItemCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "a" | "div" | "span" | "dt" | "dd" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match tag.tag() {
+            "a" => {
+                let is_reexport = writer.current_element_stack().iter().any(|element| {
+                    if let Some(id) = element.attr("id") {
+                        id.starts_with("reexport.") || id.starts_with("method.")
+                    } else {
+                        false
+                    }
+                });
+
+                if !is_reexport {
+                    if let Some(item) = Self::parse_item(tag) {
+                        self.items
+                            .entry(item.clone())
+                            .or_insert_with(|| RustdocPageItem::new(item.clone()));
+                        if RustdocItemHandler::is_inside_item_name(writer) {
+                            self.listed_item = Some(item);
+                        }
+                    }
+                }
+            }
+            "div" | "span" | "dt" | "dd" => {
+                if RustdocItemHandler::is_item_name(tag, writer) {
+                    // Not every listed item has a summary, so don't attribute
+                    // the next one's to it.
+                    self.listed_item = None;
+                } else if tag.has_class("stab")
+                    && tag.has_class("deprecated")
+                    && RustdocItemHandler::is_inside_item_name(writer)
+                {
+                    if let Some(listed_item) = self.listed_item_mut() {
+                        listed_item.deprecated = true;
+                    }
+                } else if tag.has_class("stab")
+                    && tag.has_class("unstable")
+                    && RustdocItemHandler::is_inside_item_name(writer)
+                {
+                    if let Some(listed_item) = self.listed_item_mut() {
+                        listed_item.unstable = true;
+                    }
+                } else if RustdocItemHandler::is_item_summary(tag, writer)
+                    && self.listed_item.is_some()
+                {
+                    self.summary_start = Some(writer.markdown().len());
+                }
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        if !RustdocItemHandler::is_item_summary(tag, writer) {
+            return;
+        }
+
+        if let Some(summary_start) = self.summary_start.take() {
+            let summary = writer.markdown_since(summary_start).trim().to_string();
+            if let Some(listed_item) = self.listed_item_mut() {
+                listed_item.summary = (!summary.is_empty()).then_some(summary);
+            }
+            self.listed_item = None;
+        }
+    }
+}
+
+/// Collects the features from a crate's features page on `docs.rs`, on which
+/// each feature is a heading followed by a list of the features it enables.
+pub struct DocsRsFeatureCollector {
+    pub features: Vec<CrateFeature>,
+    is_inside_heading: bool,
+    is_inside_enabled_feature: bool,
+}
+
+impl DocsRsFeatureCollector {
+    pub fn new() -> Self {
+        Self {
+            features: Vec::new(),
+            is_inside_heading: false,
+            is_inside_enabled_feature: false,
+        }
+    }
+}
+
+impl HandleTag for DocsRsFeatureCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "h3" | "span" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match tag.tag() {
+            "h3" if tag.attr("id").is_some() => {
+                self.is_inside_heading = true;
+                self.features.push(CrateFeature {
+                    name: String::new(),
+                    enables: Vec::new(),
+                });
+            }
+            // The sidebar also lists the features, but before the first
+            // heading, so only the lists under a heading are what it enables.
+            "span" => {
+                let is_list_item = writer
+                    .current_element_stack()
+                    .iter()
+                    .any(|element| element.tag() == "li");
+                if let Some(feature) = self.features.last_mut().filter(|_| is_list_item) {
+                    self.is_inside_enabled_feature = true;
+                    feature.enables.push(String::new());
+                }
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, _writer: &mut MarkdownWriter) {
+        match tag.tag() {
+            "h3" => self.is_inside_heading = false,
+            "span" => self.is_inside_enabled_feature = false,
+            _ => {}
+        }
+    }
+
+    fn handle_text(&mut self, text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        let Some(feature) = self.features.last_mut() else {
+            return HandlerOutcome::Handled;
+        };
+        if self.is_inside_heading {
+            feature.name.push_str(text.trim());
+        } else if self.is_inside_enabled_feature {
+            if let Some(enabled_feature) = feature.enables.last_mut() {
+                enabled_feature.push_str(text.trim());
+            }
+        }
+
+        HandlerOutcome::Handled
+    }
+}
+
+pub struct RustdocAllItemsCollector {
+    pub categories: Vec<(String, Vec<String>)>,
+    is_inside_heading: bool,
+    is_inside_item: bool,
+}
+
+impl RustdocAllItemsCollector {
+    pub fn new() -> Self {
+        Self {
+            categories: Vec::new(),
+            is_inside_heading: false,
+            is_inside_item: false,
+        }
+    }
+}
+
+impl HandleTag for RustdocAllItemsCollector {
+    fn should_handle(&self, tag: &str) -> bool {
+        match tag {
+            "h3" | "a" => true,
+            _ => false,
+        }
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        match tag.tag() {
+            "h3" if tag.attr("id").is_some() => {
+                self.is_inside_heading = true;
+                self.categories.push((String::new(), Vec::new()));
+            }
+            "a" => {
+                let is_list_item = writer
+                    .current_element_stack()
+                    .iter()
+                    .any(|element| element.tag() == "li");
+                if let Some((_heading, items)) = self.categories.last_mut().filter(|_| is_list_item)
+                {
+                    self.is_inside_item = true;
+                    items.push(String::new());
+                }
+            }
+            _ => {}
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, tag: &HtmlElement, _writer: &mut MarkdownWriter) {
+        match tag.tag() {
+            "h3" => self.is_inside_heading = false,
+            "a" => self.is_inside_item = false,
+            _ => {}
+        }
+    }
+
+    fn handle_text(&mut self, text: &str, _writer: &mut MarkdownWriter) -> HandlerOutcome {
+        let Some((heading, items)) = self.categories.last_mut() else {
+            return HandlerOutcome::Handled;
+        };
+        if self.is_inside_heading {
+            heading.push_str(text.trim());
+        } else if self.is_inside_item {
+            if let Some(item) = items.last_mut() {
+                item.push_str(text.trim());
+            }
+        }
+
+        HandlerOutcome::Handled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use html_to_markdown::{convert_html_to_markdown, TagHandler};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn rustdoc_handlers() -> Vec<TagHandler> {
+        vec![
+            Rc::new(RefCell::new(ParagraphHandler)),
+            Rc::new(RefCell::new(RustdocSinceHandler::new(false))),
+            Rc::new(RefCell::new(RustdocSafetyHandler::new())),
+            Rc::new(RefCell::new(HeadingHandler)),
+            Rc::new(RefCell::new(ListHandler)),
+            Rc::new(RefCell::new(TableHandler::new())),
+            Rc::new(RefCell::new(StyledTextHandler)),
+            Rc::new(RefCell::new(BlockquoteHandler::new())),
+            Rc::new(RefCell::new(RustdocWarningHandler::new())),
+            Rc::new(RefCell::new(RustdocDeprecationHandler::new())),
+            Rc::new(RefCell::new(RustdocUnstableHandler::new())),
+            Rc::new(RefCell::new(RustdocChromeRemover)),
+            Rc::new(RefCell::new(RustdocHeadingHandler)),
+            Rc::new(RefCell::new(RustdocCodeHandler::new())),
+            Rc::new(RefCell::new(RustdocItemHandler)),
+            Rc::new(RefCell::new(RustdocStructFieldHandler::new())),
+            Rc::new(RefCell::new(RustdocEnumVariantHandler)),
+            Rc::new(RefCell::new(RustdocTraitImplsHandler::new(false))),
+        ]
+    }
+
+    #[test]
+    fn test_main_heading_buttons_get_removed() {
+        let html = indoc! {r##"
+            <div class="main-heading">
+                <h1>Crate <a class="mod" href="#">serde</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1>
+                <span class="out-of-band">
+                    <a class="src" href="../src/serde/lib.rs.html#1-340">source</a> · <button id="toggle-all-docs" title="collapse all docs">[<span>−</span>]</button>
+                </span>
+            </div>
+        "##};
+        let expected = indoc! {"
+            # Crate serde
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_single_paragraph() {
+        let html = indoc! {r#"
+            <p>In particular, the last point is what sets <code>axum</code> apart from other frameworks.
+            <code>axum</code> doesn’t have its own middleware system but instead uses
+            <a href="https://docs.rs/tower-service/0.3.2/x86_64-unknown-linux-gnu/tower_service/trait.Service.html" title="trait tower_service::Service"><code>tower::Service</code></a>. This means <code>axum</code> gets timeouts, tracing, compression,
+            authorization, and more, for free. It also enables you to share middleware with
+            applications written using <a href="http://crates.io/crates/hyper"><code>hyper</code></a> or <a href="http://crates.io/crates/tonic"><code>tonic</code></a>.</p>
+        "#};
+        let expected = indoc! {"
+            In particular, the last point is what sets `axum` apart from other frameworks. `axum` doesn’t have its own middleware system but instead uses `tower::Service`. This means `axum` gets timeouts, tracing, compression, authorization, and more, for free. It also enables you to share middleware with applications written using `hyper` or `tonic`.
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_multiple_paragraphs() {
+        let html = indoc! {r##"
+            <h2 id="serde"><a class="doc-anchor" href="#serde">§</a>Serde</h2>
+            <p>Serde is a framework for <em><strong>ser</strong></em>ializing and <em><strong>de</strong></em>serializing Rust data
+            structures efficiently and generically.</p>
+            <p>The Serde ecosystem consists of data structures that know how to serialize
+            and deserialize themselves along with data formats that know how to
+            serialize and deserialize other things. Serde provides the layer by which
+            these two groups interact with each other, allowing any supported data
+            structure to be serialized and deserialized using any supported data format.</p>
+            <p>See the Serde website <a href="https://serde.rs/">https://serde.rs/</a> for additional documentation and
+            usage examples.</p>
+            <h3 id="design"><a class="doc-anchor" href="#design">§</a>Design</h3>
+            <p>Where many other languages rely on runtime reflection for serializing data,
+            Serde is instead built on Rust’s powerful trait system. A data structure
+            that knows how to serialize and deserialize itself is one that implements
+            Serde’s <code>Serialize</code> and <code>Deserialize</code> traits (or uses Serde’s derive
+            attribute to automatically generate implementations at compile time). This
+            avoids any overhead of reflection or runtime type information. In fact in
+            many situations the interaction between data structure and data format can
+            be completely optimized away by the Rust compiler, leaving Serde
+            serialization to perform the same speed as a handwritten serializer for the
+            specific selection of data structure and data format.</p>
+        "##};
+        let expected = indoc! {"
+            ## Serde
+
+            Serde is a framework for _**ser**_ializing and _**de**_serializing Rust data structures efficiently and generically.
+
+            The Serde ecosystem consists of data structures that know how to serialize and deserialize themselves along with data formats that know how to serialize and deserialize other things. Serde provides the layer by which these two groups interact with each other, allowing any supported data structure to be serialized and deserialized using any supported data format.
+
+            See the Serde website https://serde.rs/ for additional documentation and usage examples.
+
+            ### Design
+
+            Where many other languages rely on runtime reflection for serializing data, Serde is instead built on Rust’s powerful trait system. A data structure that knows how to serialize and deserialize itself is one that implements Serde’s `Serialize` and `Deserialize` traits (or uses Serde’s derive attribute to automatically generate implementations at compile time). This avoids any overhead of reflection or runtime type information. In fact in many situations the interaction between data structure and data format can be completely optimized away by the Rust compiler, leaving Serde serialization to perform the same speed as a handwritten serializer for the specific selection of data structure and data format.
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_styled_text() {
+        let html = indoc! {r#"
+            <p>This text is <strong>bolded</strong>.</p>
+            <p>This text is <em>italicized</em>.</p>
+        "#};
+        let expected = indoc! {"
+            This text is **bolded**.
+
+            This text is _italicized_.
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_rust_code_block() {
+        let html = indoc! {r#"
+            <pre class="rust rust-example-rendered"><code><span class="kw">use </span>axum::extract::{Path, Query, Json};
+            <span class="kw">use </span>std::collections::HashMap;
+
+            <span class="comment">// `Path` gives you the path parameters and deserializes them.
+            </span><span class="kw">async fn </span>path(Path(user_id): Path&lt;u32&gt;) {}
+
+            <span class="comment">// `Query` gives you the query parameters and deserializes them.
+            </span><span class="kw">async fn </span>query(Query(params): Query&lt;HashMap&lt;String, String&gt;&gt;) {}
+
+            <span class="comment">// Buffer the request body and deserialize it as JSON into a
+            // `serde_json::Value`. `Json` supports any type that implements
+            // `serde::Deserialize`.
+            </span><span class="kw">async fn </span>json(Json(payload): Json&lt;serde_json::Value&gt;) {}</code></pre>
+        "#};
+        let expected = indoc! {"
+            ```rust
+            use axum::extract::{Path, Query, Json};
+            use std::collections::HashMap;
+
+            // `Path` gives you the path parameters and deserializes them.
+            async fn path(Path(user_id): Path<u32>) {}
+
+            // `Query` gives you the query parameters and deserializes them.
+            async fn query(Query(params): Query<HashMap<String, String>>) {}
+
+            // Buffer the request body and deserialize it as JSON into a
+            // `serde_json::Value`. `Json` supports any type that implements
+            // `serde::Deserialize`.
+            async fn json(Json(payload): Json<serde_json::Value>) {}
+            ```
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_toml_code_block() {
+        let html = indoc! {r##"
+            <h2 id="required-dependencies"><a class="doc-anchor" href="#required-dependencies">§</a>Required dependencies</h2>
+            <p>To use axum there are a few dependencies you have to pull in as well:</p>
+            <div class="example-wrap"><pre class="language-toml"><code>[dependencies]
+            axum = &quot;&lt;latest-version&gt;&quot;
+            tokio = { version = &quot;&lt;latest-version&gt;&quot;, features = [&quot;full&quot;] }
+            tower = &quot;&lt;latest-version&gt;&quot;
+            </code></pre></div>
+        "##};
+        let expected = indoc! {r#"
+            ## Required dependencies
+
+            To use axum there are a few dependencies you have to pull in as well:
+
+            ```toml
+            [dependencies]
+            axum = "<latest-version>"
+            tokio = { version = "<latest-version>", features = ["full"] }
+            tower = "<latest-version>"
+
+            ```
+        "#}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_code_block_languages() {
+        let html = indoc! {r##"
+            <pre class="rust item-decl"><code>pub fn spawn()</code></pre>
+            <div class="example-wrap"><pre class="language-rust"><code>tokio::spawn(async {
+                work().await;
+            });</code></pre></div>
+            <div class="example-wrap"><pre class="language-bash"><code>cargo add tokio</code></pre></div>
+            <div class="example-wrap"><pre><code>  plain
+                indented</code></pre></div>
+        "##};
+        let expected = indoc! {r#"
+            ```rs
+            pub fn spawn()
+            ```
+
+            ```rust
+            tokio::spawn(async {
+                work().await;
+            });
+            ```
+
+            ```bash
+            cargo add tokio
+            ```
+
+            ```
+              plain
+                indented
+            ```
+        "#}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_table_without_header() {
+        let html = indoc! {r##"
+            <p>The operators are:</p>
+            <table><tbody>
+            <tr><td><code>a | b</code></td><td><p>Bitwise or, see <a href="trait.BitOr.html" title="trait core::ops::BitOr"><code>BitOr</code></a></p></td></tr>
+            <tr><td><code>a || b</code></td><td>Lazy <strong>boolean</strong> or</td></tr>
+            <tr><td><code>!a</code></td></tr>
+            </tbody></table>
+            <p>Each operator can be overloaded.</p>
+        "##};
+        let expected = indoc! {r#"
+            The operators are:
+
+            |  |  |
+            | --- | --- |
+            | `a \| b` | Bitwise or, see `BitOr` |
+            | `a \|\| b` | Lazy **boolean** or |
+            | `!a` |  |
+
+            Each operator can be overloaded.
+        "#}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_item_table() {
+        let html = indoc! {r##"
+            <h2 id="structs" class="section-header">Structs<a href="#structs" class="anchor">§</a></h2>
+            <ul class="item-table">
+            <li><div class="item-name"><a class="struct" href="struct.Error.html" title="struct axum::Error">Error</a></div><div class="desc docblock-short">Errors that can happen when using axum.</div></li>
+            <li><div class="item-name"><a class="struct" href="struct.Extension.html" title="struct axum::Extension">Extension</a></div><div class="desc docblock-short">Extractor and response for extensions.</div></li>
+            <li><div class="item-name"><a class="struct" href="struct.Form.html" title="struct axum::Form">Form</a><span class="stab portability" title="Available on crate feature `form` only"><code>form</code></span></div><div class="desc docblock-short">URL encoded extractor and response.</div></li>
+            <li><div class="item-name"><a class="struct" href="struct.Json.html" title="struct axum::Json">Json</a><span class="stab portability" title="Available on crate feature `json` only"><code>json</code></span></div><div class="desc docblock-short">JSON Extractor / Response.</div></li>
+            <li><div class="item-name"><a class="struct" href="struct.Router.html" title="struct axum::Router">Router</a></div><div class="desc docblock-short">The router type for composing handlers and services.</div></li></ul>
+            <h2 id="functions" class="section-header">Functions<a href="#functions" class="anchor">§</a></h2>
+            <ul class="item-table">
+            <li><div class="item-name"><a class="fn" href="fn.serve.html" title="fn axum::serve">serve</a><span class="stab portability" title="Available on crate feature `tokio` and (crate features `http1` or `http2`) only"><code>tokio</code> and (<code>http1</code> or <code>http2</code>)</span></div><div class="desc docblock-short">Serve the service with the supplied listener.</div></li>
+            </ul>
+        "##};
+        let expected = indoc! {r#"
+            ## Structs
+
+            - `Error`: Errors that can happen when using axum.
+            - `Extension`: Extractor and response for extensions.
+            - `Form` [`form`]: URL encoded extractor and response.
+            - `Json` [`json`]: JSON Extractor / Response.
+            - `Router`: The router type for composing handlers and services.
+
+            ## Functions
+
+            - `serve` [`tokio` and (`http1` or `http2`)]: Serve the service with the supplied listener.
+        "#}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_module_overview() {
+        let html = indoc! {r##"
+            <section id="main-content" class="content"><div class="main-heading"><h1>Module <a href="../index.html">tokio</a>::<wbr><a class="mod" href="#">sync</a></h1></div><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>Synchronization primitives for use in asynchronous contexts.</p></div></details><h2 id="modules" class="section-header">Modules<a href="#modules" class="anchor">§</a></h2><dl class="item-table"><dt><a class="mod" href="broadcast/index.html" title="mod tokio::sync::broadcast">broadcast</a></dt><dd>A multi-producer, multi-consumer broadcast queue.</dd><dt><a class="mod" href="futures/index.html" title="mod tokio::sync::futures">futures</a></dt></dl><h2 id="structs" class="section-header">Structs<a href="#structs" class="anchor">§</a></h2><dl class="item-table"><dt><a class="struct" href="struct.Mutex.html" title="struct tokio::sync::Mutex">Mutex</a></dt><dd>An asynchronous <code>Mutex</code>-like type.</dd><dt><a class="struct" href="struct.OnceCell.html" title="struct tokio::sync::OnceCell">OnceCell</a><span class="stab portability" title="Available on crate feature `sync` only"><code>sync</code></span></dt><dd>A thread-safe cell that can be written to only once.</dd></dl><h2 id="functions" class="section-header">Functions<a href="#functions" class="anchor">§</a></h2><dl class="item-table"><dt><a class="fn" href="fn.oneshot.html" title="fn tokio::sync::oneshot">oneshot</a><span class="stab deprecated" title="">Deprecated</span></dt><dd>Creates a new one-shot channel.</dd></dl></section>
+        "##};
+        let expected = indoc! {r#"
+            # Module tokio::sync
+
+            Synchronization primitives for use in asynchronous contexts.
+
+            ## Modules
+
+            - `broadcast`: A multi-producer, multi-consumer broadcast queue.
+            - `futures`
+
+            ## Structs
+
+            - `Mutex`: An asynchronous `Mutex`-like type.
+            - `OnceCell` [`sync`]: A thread-safe cell that can be written to only once.
+
+            ## Functions
+
+            - `oneshot` [Deprecated]: Creates a new one-shot channel.
+        "#}
+        .trim();
+
+        let (markdown, items) = convert_rustdoc_to_markdown(html.as_bytes()).unwrap();
+        assert_eq!(markdown, expected);
+
+        let item = |kind, name: &str| RustdocItem {
+            kind,
+            path: Vec::new(),
+            name: name.into(),
+        };
+        assert_eq!(
+            items,
+            vec![
+                RustdocPageItem {
+                    item: item(RustdocItemKind::Mod, "broadcast"),
+                    summary: Some("A multi-producer, multi-consumer broadcast queue.".to_string()),
+                    deprecated: false,
+                    unstable: false,
+                },
+                RustdocPageItem::new(item(RustdocItemKind::Mod, "futures")),
+                RustdocPageItem {
+                    item: item(RustdocItemKind::Struct, "Mutex"),
+                    summary: Some("An asynchronous `Mutex`-like type.".to_string()),
+                    deprecated: false,
+                    unstable: false,
+                },
+                RustdocPageItem {
+                    item: item(RustdocItemKind::Struct, "OnceCell"),
+                    summary: Some(
+                        "A thread-safe cell that can be written to only once.".to_string()
+                    ),
+                    deprecated: false,
+                    unstable: false,
+                },
+                RustdocPageItem {
+                    item: item(RustdocItemKind::Function, "oneshot"),
+                    summary: Some("Creates a new one-shot channel.".to_string()),
+                    deprecated: true,
+                    unstable: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alphabetical_item_order() {
+        let html = indoc! {r##"
+            <h2 id="structs" class="section-header">Structs<a href="#structs" class="anchor">§</a></h2><dl class="item-table"><dt><a class="struct" href="struct.Semaphore.html" title="struct tokio::sync::Semaphore">Semaphore</a></dt><dd>Counting semaphore performing asynchronous permit acquisition.</dd><dt><a class="struct" href="struct.Barrier.html" title="struct tokio::sync::Barrier">Barrier</a></dt><dt><a class="struct" href="struct.Mutex.html" title="struct tokio::sync::Mutex">Mutex</a></dt><dd>An asynchronous <code>Mutex</code>-like type.</dd></dl>
+            <h2 id="functions" class="section-header">Functions<a href="#functions" class="anchor">§</a></h2><ul class="item-table"><li><div class="item-name"><a class="fn" href="fn.oneshot.html" title="fn tokio::sync::oneshot">oneshot</a></div><div class="desc docblock-short">Creates a new one-shot channel.</div></li><li><div class="item-name"><a class="fn" href="fn.Broadcast.html" title="fn tokio::sync::Broadcast">Broadcast</a></div></li></ul>
+        "##};
+        let options = RustdocConversionOptions {
+            item_order: RustdocItemOrder::Alphabetical,
+            ..Default::default()
+        };
+        let (markdown, items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(
+            markdown,
+            indoc! {"
+                ## Structs
+
+                - `Barrier`
+                - `Mutex`: An asynchronous `Mutex`-like type.
+                - `Semaphore`: Counting semaphore performing asynchronous permit acquisition.
+
+                ## Functions
+
+                - `Broadcast`
+                - `oneshot`: Creates a new one-shot channel.
+            "}
+            .trim()
+        );
+        // The items are still reported in the order on the page.
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| item.item.name.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["Semaphore", "Barrier", "Mutex", "oneshot", "Broadcast"]
+        );
+
+        // By default, the items are listed in the order on the page.
+        let (markdown, _items) = convert_rustdoc_to_markdown(html.as_bytes()).unwrap();
+        assert!(markdown.contains("- `Semaphore`: Counting semaphore"));
+        assert!(markdown.find("`Semaphore`") < markdown.find("`Barrier`"));
+
+        assert_eq!(
+            "declaration".parse::<RustdocItemOrder>().unwrap(),
+            RustdocItemOrder::Declaration
+        );
+        assert!("source"
+            .parse::<RustdocItemOrder>()
+            .unwrap_err()
+            .to_string()
+            .contains("expected one of `page`, `declaration`, `alphabetical`"));
+    }
+
+    #[test]
+    fn test_combined_options() {
+        let html = indoc! {r##"
+            <h1>Module <a href="#">sync</a></h1>
+            <h2 id="structs" class="section-header">Structs<a href="#structs" class="anchor">§</a></h2><dl class="item-table"><dt><a class="struct" href="struct.Semaphore.html" title="struct tokio::sync::Semaphore">Semaphore</a></dt><dd>Counting semaphore performing asynchronous permit acquisition.</dd><dt><a class="struct" href="struct.Barrier.html" title="struct tokio::sync::Barrier">Barrier</a></dt><dt><a class="struct" href="struct.Mutex.html" title="struct tokio::sync::Mutex">Mutex</a></dt><dd>An asynchronous <code>Mutex</code>-like type.</dd></dl>
+            <h2 id="functions" class="section-header">Functions<a href="#functions" class="anchor">§</a></h2><ul class="item-table"><li><div class="item-name"><a class="fn" href="fn.oneshot.html" title="fn tokio::sync::oneshot">oneshot</a></div><div class="desc docblock-short">Creates a new one-shot channel.</div></li></ul>
+        "##};
+        // The items of the requested kinds are filtered before they're sorted.
+        let options = RustdocConversionOptions {
+            item_kinds: vec![RustdocItemKind::Struct],
+            item_order: RustdocItemOrder::Alphabetical,
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(
+            markdown,
+            indoc! {"
+                # Module sync
+
+                ## Structs
+
+                - `Barrier`
+                - `Mutex`: An asynchronous `Mutex`-like type.
+                - `Semaphore`: Counting semaphore performing asynchronous permit acquisition.
+            "}
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_page_items() {
+        let html = indoc! {r##"
+            <h2 id="structs" class="section-header">Structs</h2>
+            <ul class="item-table">
+            <li><div class="item-name"><a class="struct" href="struct.Router.html" title="struct axum::Router">Router</a></div><div class="desc docblock-short">The router type for composing <code>handlers</code>.</div></li>
+            <li><div class="item-name"><a class="fn" href="fn.trim_left.html" title="fn axum::trim_left">trim_left</a><span class="stab deprecated" title="">Deprecated</span></div></li>
+            <li><div class="item-name"><a class="mod" href="routing/index.html" title="mod axum::routing">routing</a></div><div class="desc docblock-short">Routing between services.</div></li>
+            </ul>
+            <div class="docblock"><p>See <a class="struct" href="body/struct.Body.html">Body</a>.</p></div>
+        "##};
+        let item = |kind, path: &[&str], name: &str| RustdocItem {
+            kind,
+            path: path.iter().map(|segment| (*segment).into()).collect(),
+            name: name.into(),
+        };
+
+        let (_markdown, items) = convert_rustdoc_to_markdown(html.as_bytes()).unwrap();
+        assert_eq!(
+            items,
+            vec![
+                RustdocPageItem {
+                    item: item(RustdocItemKind::Struct, &[], "Router"),
+                    summary: Some("The router type for composing `handlers`.".to_string()),
+                    deprecated: false,
+                    unstable: false,
+                },
+                RustdocPageItem {
+                    item: item(RustdocItemKind::Function, &[], "trim_left"),
+                    summary: None,
+                    deprecated: true,
+                    unstable: false,
+                },
+                RustdocPageItem {
+                    item: item(RustdocItemKind::Mod, &[], "routing"),
+                    summary: Some("Routing between services.".to_string()),
+                    deprecated: false,
+                    unstable: false,
+                },
+                RustdocPageItem::new(item(RustdocItemKind::Struct, &["body"], "Body")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trait_implementations() {
+        let html = indoc! {r##"
+            <h2 id="trait-implementations" class="section-header">Trait Implementations<a href="#trait-implementations" class="anchor">§</a></h2><div id="trait-implementations-list"><details class="toggle implementors-toggle" open><summary><section id="impl-Clone-for-HashMap%3CK,+V,+S%3E" class="impl"><a class="src rightside" href="../../src/std/collections/hash/map.rs.html#1284-1302">source</a><a href="#impl-Clone-for-HashMap%3CK,+V,+S%3E" class="anchor">§</a><h3 class="code-header">impl&lt;K, V, S&gt; <a class="trait" href="../clone/trait.Clone.html" title="trait std::clone::Clone">Clone</a> for <a class="struct" href="struct.HashMap.html" title="struct std::collections::HashMap">HashMap</a>&lt;K, V, S&gt;<div class="where">where
+                K: <a class="trait" href="../clone/trait.Clone.html" title="trait std::clone::Clone">Clone</a>,</div></h3></section></summary><div class="impl-items"><details class="toggle method-toggle" open><summary><section id="method.clone" class="method trait-impl"><a href="#method.clone" class="anchor">§</a><h4 class="code-header">fn <a href="../clone/trait.Clone.html#tymethod.clone" class="fn">clone</a>(&amp;self) -&gt; Self</h4></section></summary><div class="docblock"><p>Returns a copy of the value.</p></div></details></div></details><section id="impl-Eq-for-HashMap%3CK,+V,+S%3E" class="impl"><a href="#impl-Eq-for-HashMap%3CK,+V,+S%3E" class="anchor">§</a><h3 class="code-header">impl&lt;K, V, S&gt; <a class="trait" href="../cmp/trait.Eq.html" title="trait std::cmp::Eq">Eq</a> for <a class="struct" href="struct.HashMap.html" title="struct std::collections::HashMap">HashMap</a>&lt;K, V, S&gt;</h3></section></div><h2 id="synthetic-implementations" class="section-header">Auto Trait Implementations<a href="#synthetic-implementations" class="anchor">§</a></h2><div id="synthetic-implementations-list"><section id="impl-Send-for-HashMap%3CK,+V,+S%3E" class="impl"><a href="#impl-Send-for-HashMap%3CK,+V,+S%3E" class="anchor">§</a><h3 class="code-header">impl&lt;K, V, S&gt; <a class="trait" href="../marker/trait.Send.html" title="trait std::marker::Send">Send</a> for <a class="struct" href="struct.HashMap.html" title="struct std::collections::HashMap">HashMap</a>&lt;K, V, S&gt;<div class="where">where K: <a class="trait" href="../marker/trait.Send.html" title="trait std::marker::Send">Send</a>,</div></h3></section></div><h2 id="blanket-implementations" class="section-header">Blanket Implementations<a href="#blanket-implementations" class="anchor">§</a></h2><div id="blanket-implementations-list"><section id="impl-Any-for-T" class="impl"><a href="#impl-Any-for-T" class="anchor">§</a><h3 class="code-header">impl&lt;T&gt; <a class="trait" href="../any/trait.Any.html" title="trait std::any::Any">Any</a> for T<div class="where">where T: 'static + ?<a class="trait" href="../marker/trait.Sized.html" title="trait std::marker::Sized">Sized</a>,</div></h3></section></div>
+        "##};
+        let expected = indoc! {"
+            ## Implements
+
+            - `impl<K, V, S> Clone for HashMap<K, V, S> where K: Clone`
+            - `impl<K, V, S> Eq for HashMap<K, V, S>`
+
+            ### Auto & blanket implementations (2)
+
+            - `impl<K, V, S> Send for HashMap<K, V, S> where K: Send`
+            - `impl<T> Any for T where T: 'static + ?Sized`
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        );
+
+        // When expanded, the auto trait and blanket impls get their own groups.
+        let mut handlers = rustdoc_handlers();
+        handlers.pop();
+        handlers.push(Rc::new(RefCell::new(RustdocTraitImplsHandler::new(true))));
+        let expected = indoc! {"
+            ## Implements
+
+            - `impl<K, V, S> Clone for HashMap<K, V, S> where K: Clone`
+            - `impl<K, V, S> Eq for HashMap<K, V, S>`
+
+            ### Auto traits
+
+            - `impl<K, V, S> Send for HashMap<K, V, S> where K: Send`
+
+            ### Blanket implementations
+
+            - `impl<T> Any for T where T: 'static + ?Sized`
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut handlers).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_table() {
+        let html = indoc! {r##"
+            <h2 id="feature-flags"><a class="doc-anchor" href="#feature-flags">§</a>Feature flags</h2>
+            <p>axum uses a set of <a href="https://doc.rust-lang.org/cargo/reference/features.html#the-features-section">feature flags</a> to reduce the amount of compiled and
+            optional dependencies.</p>
+            <p>The following optional features are available:</p>
+            <div><table><thead><tr><th>Name</th><th>Description</th><th>Default?</th></tr></thead><tbody>
+            <tr><td><code>http1</code></td><td>Enables hyper’s <code>http1</code> feature</td><td>Yes</td></tr>
+            <tr><td><code>http2</code></td><td>Enables hyper’s <code>http2</code> feature</td><td>No</td></tr>
+            <tr><td><code>json</code></td><td>Enables the <a href="struct.Json.html" title="struct axum::Json"><code>Json</code></a> type and some similar convenience functionality</td><td>Yes</td></tr>
+            <tr><td><code>macros</code></td><td>Enables optional utility macros</td><td>No</td></tr>
+            <tr><td><code>matched-path</code></td><td>Enables capturing of every request’s router path and the <a href="extract/struct.MatchedPath.html" title="struct axum::extract::MatchedPath"><code>MatchedPath</code></a> extractor</td><td>Yes</td></tr>
+            <tr><td><code>multipart</code></td><td>Enables parsing <code>multipart/form-data</code> requests with <a href="extract/struct.Multipart.html" title="struct axum::extract::Multipart"><code>Multipart</code></a></td><td>No</td></tr>
+            <tr><td><code>original-uri</code></td><td>Enables capturing of every request’s original URI and the <a href="extract/struct.OriginalUri.html" title="struct axum::extract::OriginalUri"><code>OriginalUri</code></a> extractor</td><td>Yes</td></tr>
+            <tr><td><code>tokio</code></td><td>Enables <code>tokio</code> as a dependency and <code>axum::serve</code>, <code>SSE</code> and <code>extract::connect_info</code> types.</td><td>Yes</td></tr>
+            <tr><td><code>tower-log</code></td><td>Enables <code>tower</code>’s <code>log</code> feature</td><td>Yes</td></tr>
+            <tr><td><code>tracing</code></td><td>Log rejections from built-in extractors</td><td>Yes</td></tr>
+            <tr><td><code>ws</code></td><td>Enables WebSockets support via <a href="extract/ws/index.html" title="mod axum::extract::ws"><code>extract::ws</code></a></td><td>No</td></tr>
+            <tr><td><code>form</code></td><td>Enables the <code>Form</code> extractor</td><td>Yes</td></tr>
+            <tr><td><code>query</code></td><td>Enables the <code>Query</code> extractor</td><td>Yes</td></tr>
+            </tbody></table>
+        "##};
+        let expected = indoc! {r#"
+            ## Feature flags
+
+            axum uses a set of feature flags to reduce the amount of compiled and optional dependencies.
+
+            The following optional features are available:
+
+            | Name | Description | Default? |
+            | --- | --- | --- |
+            | `http1` | Enables hyper’s `http1` feature | Yes |
+            | `http2` | Enables hyper’s `http2` feature | No |
+            | `json` | Enables the `Json` type and some similar convenience functionality | Yes |
+            | `macros` | Enables optional utility macros | No |
+            | `matched-path` | Enables capturing of every request’s router path and the `MatchedPath` extractor | Yes |
+            | `multipart` | Enables parsing `multipart/form-data` requests with `Multipart` | No |
+            | `original-uri` | Enables capturing of every request’s original URI and the `OriginalUri` extractor | Yes |
+            | `tokio` | Enables `tokio` as a dependency and `axum::serve`, `SSE` and `extract::connect_info` types. | Yes |
+            | `tower-log` | Enables `tower`’s `log` feature | Yes |
+            | `tracing` | Log rejections from built-in extractors | Yes |
+            | `ws` | Enables WebSockets support via `extract::ws` | No |
+            | `form` | Enables the `Form` extractor | Yes |
+            | `query` | Enables the `Query` extractor | Yes |
+        "#}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_generic_associated_types() {
+        let html = indoc! {r##"
+            <pre class="rust item-decl"><code>pub trait LendingIterator {
+                type <a href="#associatedtype.Item" class="associatedtype">Item</a>&lt;'a&gt;
+                   <span class="where">where Self: 'a</span>;
+
+                // Required method
+                fn <a href="#tymethod.next" class="fn">next</a>&lt;'a&gt;(&amp;'a mut self) -&gt; Option&lt;Self::<a class="associatedtype" href="trait.LendingIterator.html#associatedtype.Item" title="type lending::LendingIterator::Item">Item</a>&lt;'a&gt;&gt;;
+            }</code></pre>
+            <h2 id="required-associated-types" class="section-header">Required Associated Types<a href="#required-associated-types" class="anchor">§</a></h2>
+            <div class="methods"><section id="associatedtype.Item" class="method"><h4 class="code-header">type <a href="#associatedtype.Item" class="associatedtype">Item</a>&lt;'a&gt;<br><span class="where">where
+                Self: 'a</span></h4></section></div>
+            <h2 id="implementors" class="section-header">Implementors<a href="#implementors" class="anchor">§</a></h2>
+            <section id="impl-LendingIterator-for-WindowsMut%3C'w,+T%3E" class="impl"><h3 class="code-header">impl&lt;'w, T&gt; <a class="trait" href="trait.LendingIterator.html" title="trait lending::LendingIterator">LendingIterator</a> for <a class="struct" href="struct.WindowsMut.html" title="struct lending::WindowsMut">WindowsMut</a>&lt;'w, T&gt;</h3></section>
+            <section id="associatedtype.Item-1" class="associatedtype trait-impl"><h4 class="code-header">type <a href="#associatedtype.Item" class="associatedtype">Item</a>&lt;'a&gt; = &amp;'a mut [T] <span class="where">where Self: 'a</span></h4></section>
+        "##};
+        let expected = indoc! {r#"
+            ```rs
+            pub trait LendingIterator {
+                type Item<'a>
+                   where Self: 'a;
+
+                // Required method
+                fn next<'a>(&'a mut self) -> Option<Self::Item<'a>>;
+            }
+            ```
+
+            ## Required Associated Types
+
+            #### type Item<'a> where Self: 'a
+
+            ## Implementors
+
+            ### impl<'w, T> LendingIterator for WindowsMut<'w, T>
+
+            #### type Item<'a> = &'a mut [T] where Self: 'a
+        "#}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_where_clauses() {
+        let html = indoc! {r##"
+            <pre class="rust item-decl"><code>pub fn spawn&lt;F&gt;(future: F) -&gt; <a class="struct" href="task/struct.JoinHandle.html" title="struct tokio::task::JoinHandle">JoinHandle</a>&lt;F::<a class="associatedtype" href="https://doc.rust-lang.org/nightly/core/future/future/trait.Future.html#associatedtype.Output" title="type core::future::future::Future::Output">Output</a>&gt; <div class="where">where
+                F: <a class="trait" href="https://doc.rust-lang.org/nightly/core/future/future/trait.Future.html" title="trait core::future::future::Future">Future</a> + <a class="trait" href="https://doc.rust-lang.org/nightly/core/marker/trait.Send.html" title="trait core::marker::Send">Send</a> + 'static,
+                F::<a class="associatedtype" href="https://doc.rust-lang.org/nightly/core/future/future/trait.Future.html#associatedtype.Output" title="type core::future::future::Future::Output">Output</a>: <a class="trait" href="https://doc.rust-lang.org/nightly/core/marker/trait.Send.html" title="trait core::marker::Send">Send</a> + 'static,</div></code></pre>
+        "##};
+        let expected = indoc! {r#"
+            ```rs
+            pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+            where
+                F: Future + Send + 'static,
+                F::Output: Send + 'static,
+            ```
+        "#}
+        .trim();
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        );
+
+        // Older versions of rustdoc break the lines of `where` clauses with
+        // `<br>`s, and indent the bounds with non-breaking spaces.
+        let html = indoc! {r##"
+            <pre class="rust item-decl"><code>pub trait Borrow&lt;Borrowed&gt;<span class="where fmt-newline">where<br>&nbsp;&nbsp;&nbsp;&nbsp;Borrowed: ?<a class="trait" href="../marker/trait.Sized.html" title="trait core::marker::Sized">Sized</a>,</span>{
+                // Required method
+                fn <a href="#tymethod.borrow" class="fn">borrow</a>(&amp;self) -&gt; &amp;Borrowed;
+            }</code></pre>
+            <section id="impl-Borrow%3CT%3E-for-T" class="impl"><h3 class="code-header">impl&lt;T&gt; <a class="trait" href="trait.Borrow.html" title="trait core::borrow::Borrow">Borrow</a>&lt;T&gt; for T<span class="where fmt-newline">where<br>&nbsp;&nbsp;&nbsp;&nbsp;T: ?<a class="trait" href="../marker/trait.Sized.html" title="trait core::marker::Sized">Sized</a>,</span></h3></section>
+        "##};
+        let expected = indoc! {r#"
+            ```rs
+            pub trait Borrow<Borrowed>
+            where
+                Borrowed: ?Sized,{
+                // Required method
+                fn borrow(&self) -> &Borrowed;
+            }
+            ```
+
+            ### impl<T> Borrow<T> for T where T: ?Sized,
+        "#}
+        .trim();
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        );
+
+        // The supertraits of a trait are part of its declaration.
+        let html = indoc! {r##"
+            <pre class="rust item-decl"><code>pub trait Ord: <a class="trait" href="trait.Eq.html" title="trait core::cmp::Eq">Eq</a> + <a class="trait" href="trait.PartialOrd.html" title="trait core::cmp::PartialOrd">PartialOrd</a> {
+                // Provided method
+                fn <a href="#method.max" class="fn">max</a>(self, other: Self) -&gt; Self
+                   <span class="where">where Self: <a class="trait" href="../marker/trait.Sized.html" title="trait core::marker::Sized">Sized</a></span> { ... }
+            }</code></pre>
+        "##};
+        let expected = indoc! {r#"
+            ```rs
+            pub trait Ord: Eq + PartialOrd {
+                // Provided method
+                fn max(self, other: Self) -> Self
+                   where Self: Sized { ... }
+            }
+            ```
+        "#}
+        .trim();
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_malformed_html() {
+        let html = indoc! {r##"
+            <!DOCTYPE html>
+            <html lang="en">
+            <head><meta charset="windows-1252"><title>Cafe in cafe - Rust</title></head>
+            <body class="rustdoc struct">
+                <main><section id="main-content" class="content">
+                    <div class="main-heading"><h1>Struct <a href="index.html">cafe</a>::<a class="struct" href="#">Cafe</a></h1></div>
+                    <details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary>
+                    <div class="docblock"><p>A café that serves <code>Coffee</code> and <em>pastries</em>.</p>
+                    <p>It opens at <code>7</code></p>
+                    </div></details>
+                </section></main>
+            </body>
+            </html>
+        "##};
+        let (encoded, _, _) = encoding_rs::WINDOWS_1252.encode(html);
+        assert!(std::str::from_utf8(&encoded).is_err());
+
+        // The page is transcoded from the charset it declares.
+        let (markdown, _) = convert_rustdoc_to_markdown(encoded.as_ref()).unwrap();
+        assert_eq!(
+            markdown,
+            indoc! {"
+                # Struct cafe::Cafe
+
+                A café that serves `Coffee` and _pastries_.
+
+                It opens at `7`
+            "}
+            .trim()
+        );
+
+        // A page that was cut off is converted up to where it ends, with the
+        // character that it ends in the middle of replaced.
+        let utf8 = html.replace("windows-1252", "utf-8");
+        let cut_off = &utf8.as_bytes()[..utf8.find('é').unwrap() + 1];
+        let (markdown, _) = convert_rustdoc_to_markdown(cut_off).unwrap();
+        assert_eq!(markdown, "# Struct cafe::Cafe\n\nA caf\u{fffd}");
+
+        let cut_off = &utf8[..utf8.find("<em>pastries").unwrap() + "<em>past".len()];
+        let (markdown, _) = convert_rustdoc_to_markdown(cut_off.as_bytes()).unwrap();
+        assert_eq!(
+            markdown,
+            "# Struct cafe::Cafe\n\nA café that serves `Coffee` and _past_"
+        );
+    }
+
+    #[test]
+    fn test_js_shell_page_detection() {
+        let html = indoc! {r##"
+            <!DOCTYPE html>
+            <html lang="en">
+            <head>
+                <meta charset="UTF-8">
+                <title>axum - Rust</title>
+                <script type="module" src="/-/static/app.js"></script>
+            </head>
+            <body>
+                <div id="app"></div>
+                <noscript>You need to enable JavaScript to view this page.</noscript>
+            </body>
+            </html>
+        "##};
+
+        assert!(is_js_shell_page(html));
+    }
+
+    #[test]
+    fn test_rendered_page_is_not_a_js_shell() {
+        let html = indoc! {r##"
+            <!DOCTYPE html>
+            <html lang="en">
+            <head>
+                <script defer src="../static.files/main-20240508-2b4b1b0b.js"></script>
+                <noscript><link rel="stylesheet" href="../static.files/noscript-20240508-b4b5dbcb.css"></noscript>
+            </head>
+            <body class="rustdoc mod crate">
+                <main><section id="main-content" class="content"><h1>Crate <a class="mod" href="#">axum</a></h1></section></main>
+            </body>
+            </html>
+        "##};
+
+        assert!(!is_js_shell_page(html));
+    }
+
+    #[test]
+    fn test_struct_fields() {
+        let html = indoc! {r##"
+            <h2 id="fields" class="fields section-header">Fields<a href="#fields" class="anchor">§</a></h2><span id="structfield.name" class="structfield section-header"><a href="#structfield.name" class="anchor field">§</a><code>name: <a class="struct" href="https://doc.rust-lang.org/nightly/alloc/string/struct.String.html" title="struct alloc::string::String">String</a></code></span><div class="docblock"><p>The name of the person.</p>
+            </div><span id="structfield.age" class="structfield section-header"><a href="#structfield.age" class="anchor field">§</a><code>age: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.u32.html">u32</a></code></span><div class="docblock"><p>The age of the person, in years.</p>
+            <p>This is always rounded down.</p>
+            </div><span id="structfield.email" class="structfield section-header"><a href="#structfield.email" class="anchor field">§</a><code>email: <a class="enum" href="https://doc.rust-lang.org/nightly/core/option/enum.Option.html" title="enum core::option::Option">Option</a>&lt;<a class="struct" href="https://doc.rust-lang.org/nightly/alloc/string/struct.String.html" title="struct alloc::string::String">String</a>&gt;</code></span><h2 id="implementations" class="section-header">Implementations<a href="#implementations" class="anchor">§</a></h2>
+        "##};
+        let expected = indoc! {"
+            ## Fields
+
+            - `name: String`
+
+              The name of the person.
+
+            - `age: u32`
+
+              The age of the person, in years.
+
+              This is always rounded down.
+
+            - `email: Option<String>`
+
+            ## Implementations
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        );
+
+        // rustdoc numbers the fields of tuple structs.
+        let html = indoc! {r##"
+            <h2 id="fields" class="fields section-header">Tuple Fields<a href="#fields" class="anchor">§</a></h2><span id="structfield.0" class="structfield section-header"><a href="#structfield.0" class="anchor field">§</a><code>0: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.u8.html">u8</a></code></span><div class="docblock"><p>The red channel.</p>
+            </div><span id="structfield.1" class="structfield section-header"><a href="#structfield.1" class="anchor field">§</a><code>1: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.u8.html">u8</a></code></span><div class="docblock"><p>The green channel, e.g.:</p>
+            <div class="example-wrap"><pre class="rust rust-example-rendered"><code><span class="kw">let </span>green = Rgb(<span class="number">0</span>, <span class="number">255</span>, <span class="number">0</span>);</code></pre></div></div><h2 id="implementations" class="section-header">Implementations<a href="#implementations" class="anchor">§</a></h2>
+        "##};
+        let expected = indoc! {"
+            ## Tuple Fields
+
+            - `0: u8`
+
+              The red channel.
+
+            - `1: u8`
+
+              The green channel, e.g.:
+
+              ```rust
+              let green = Rgb(0, 255, 0);
+              ```
+
+            ## Implementations
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_enum_variants() {
+        let html = indoc! {r##"
+            <h2 id="variants" class="variants section-header">Variants<a href="#variants" class="anchor">§</a></h2><div class="variants"><section id="variant.Ok" class="variant"><a href="#variant.Ok" class="anchor">§</a><span class="since rightside" title="Stable since Rust version 1.0.0">1.0.0</span><h3 class="code-header">Ok(T)</h3></section><div class="docblock"><p>Contains the success value</p>
+            </div><section id="variant.Err" class="variant"><a href="#variant.Err" class="anchor">§</a><span class="since rightside" title="Stable since Rust version 1.0.0">1.0.0</span><h3 class="code-header">Err(E)</h3></section><div class="docblock"><p>Contains the error value</p>
+            </div></div><h2 id="implementations" class="section-header">Implementations<a href="#implementations" class="anchor">§</a></h2>
+        "##};
+        let expected = indoc! {"
+            ## Variants
+
+            ### Ok(T)
+
+            Contains the success value
+
+            ### Err(E)
+
+            Contains the error value
+
+            ## Implementations
+        "}
+        .trim();
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        );
+
+        let html = indoc! {r##"
+            <h2 id="variants" class="variants section-header">Variants<a href="#variants" class="anchor">§</a></h2><div class="variants"><section id="variant.Empty" class="variant"><a href="#variant.Empty" class="anchor">§</a><h3 class="code-header">Empty</h3></section><div class="docblock"><p>No shape at all.</p></div><section id="variant.Circle" class="variant"><a href="#variant.Circle" class="anchor">§</a><h3 class="code-header">Circle(<a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.f64.html">f64</a>)</h3></section><div class="docblock"><p>A circle with the given radius.</p></div><section id="variant.Rect" class="variant"><a href="#variant.Rect" class="anchor">§</a><h3 class="code-header">Rect</h3></section><div class="docblock"><p>A rectangle.</p></div><div class="sub-variant" id="variant.Rect.fields"><h4>Fields</h4><div class="sub-variant-field"><span id="variant.Rect.field.width" class="section-header"><a href="#variant.Rect.field.width" class="anchor field">§</a><code>width: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.f64.html">f64</a></code></span><div class="docblock"><p>The width.</p></div></div><div class="sub-variant-field"><span id="variant.Rect.field.height" class="section-header"><a href="#variant.Rect.field.height" class="anchor field">§</a><code>height: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.f64.html">f64</a></code></span></div></div></div>
+        "##};
+        let expected = indoc! {"
+            ## Variants
+
+            ### Empty
+
+            No shape at all.
+
+            ### Circle(f64)
+
+            A circle with the given radius.
+
+            ### Rect
+
+            A rectangle.
+
+            #### Fields
+
+            - `width: f64`
+
+              The width.
+
+            - `height: f64`
+        "}
+        .trim();
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_nested_and_indented_code_blocks() {
+        let html = indoc! {r##"
+            <div class="docblock"><p>Write the docs as Markdown:</p>
+            <div class="example-wrap"><pre class="language-markdown"><code>Some docs:
+
+            ```rust
+            let x = 1;
+            ```
+            </code></pre></div>
+            <div class="example-wrap"><pre class="rust rust-example-rendered"><code><span class="kw">fn </span>main() {
+                <span class="kw">if </span><span class="bool-val">true </span>{
+                    <span class="macro">println!</span>(<span class="string">"deep"</span>);
+                }
+            }</code></pre></div>
+            <pre class="language-text"><code>outer:
+            <pre>    inner
+                    deeper</pre>
+            done</code></pre></div>
+        "##};
+        let expected = indoc! {r#"
+            Write the docs as Markdown:
+
+            ````markdown
+            Some docs:
+
+            ```rust
+            let x = 1;
+            ```
+
+            ````
+
+            ```rust
+            fn main() {
+                if true {
+                    println!("deep");
+                }
+            }
+            ```
+
+            ```text
+            outer:
+                inner
+                    deeper
+            done
+            ```
+        "#}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_definitions_only() {
+        let html = indoc! {r##"
+            <main><div class="width-limiter"><section id="main-content" class="content"><div class="main-heading"><h1>Struct <a href="index.html">axum</a>::<wbr><a class="struct" href="#">Router</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1><span class="out-of-band"><a class="src" href="../src/axum/routing/mod.rs.html#59-63">source</a> · <button id="toggle-all-docs" title="collapse all docs">[<span>&#x2212;</span>]</button></span></div><pre class="rust item-decl"><code>pub struct Router&lt;S = ()&gt; { <span class="comment">/* private fields */</span> }</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>The router type for composing handlers and services.</p>
+            </div></details><h2 id="implementations" class="section-header">Implementations<a href="#implementations" class="anchor">§</a></h2><div id="implementations-list"><details class="toggle implementors-toggle" open><summary><section id="impl-Router%3CS%3E" class="impl"><a class="src rightside" href="../src/axum/routing/mod.rs.html#98-563">source</a><a href="#impl-Router%3CS%3E" class="anchor">§</a><h3 class="code-header">impl&lt;S&gt; <a class="struct" href="struct.Router.html" title="struct axum::Router">Router</a>&lt;S&gt;<div class="where">where
+                S: <a class="trait" href="https://doc.rust-lang.org/nightly/core/clone/trait.Clone.html" title="trait core::clone::Clone">Clone</a> + <a class="trait" href="https://doc.rust-lang.org/nightly/core/marker/trait.Send.html" title="trait core::marker::Send">Send</a> + 'static,</div></h3></section></summary><div class="impl-items"><details class="toggle method-toggle" open><summary><section id="method.new" class="method"><a class="src rightside" href="../src/axum/routing/mod.rs.html#106-111">source</a><h4 class="code-header">pub fn <a href="#method.new" class="fn">new</a>() -&gt; Self</h4></section></summary><div class="docblock"><p>Create a new <code>Router</code>.</p>
+            </div></details><details class="toggle method-toggle" open><summary><section id="method.route" class="method"><a class="src rightside" href="../src/axum/routing/mod.rs.html#164-195">source</a><h4 class="code-header">pub fn <a href="#method.route" class="fn">route</a>(self, path: &amp;<a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.str.html">str</a>, method_router: <a class="struct" href="routing/struct.MethodRouter.html" title="struct axum::routing::MethodRouter">MethodRouter</a>&lt;S&gt;) -&gt; Self</h4></section></summary><div class="docblock"><p>Add another route to the router.</p>
+            </div></details></div></details></div><h2 id="synthetic-implementations" class="section-header">Auto Trait Implementations<a href="#synthetic-implementations" class="anchor">§</a></h2><div id="synthetic-implementations-list"><section id="impl-Freeze-for-Router%3CS%3E" class="impl"><a href="#impl-Freeze-for-Router%3CS%3E" class="anchor">§</a><h3 class="code-header">impl&lt;S&gt; Freeze for <a class="struct" href="struct.Router.html" title="struct axum::Router">Router</a>&lt;S&gt;</h3></section></div></section></div></main>
+        "##};
+        let expected = indoc! {"
+            ```rs
+            pub struct Router<S = ()> { /* private fields */ }
+
+            impl<S> Router<S> where S: Clone + Send + 'static,
+                pub fn new() -> Self;
+                pub fn route(self, path: &str, method_router: MethodRouter<S>) -> Self;
+            ```
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_rustdoc_to_definitions(html.as_bytes()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_since_annotations() {
+        let html = indoc! {r##"
+            <details class="toggle method-toggle" open><summary><section id="method.new" class="method"><span class="rightside"><span class="since" title="Stable since Rust version 1.0.0, const since 1.39.0">1.0.0 (const: 1.39.0)</span></span><h4 class="code-header">pub const fn <a href="#method.new" class="fn">new</a>() -&gt; <a class="struct" href="struct.Vec.html">Vec</a>&lt;T&gt;</h4></section></summary><div class="docblock"><p>Constructs a new, empty <code>Vec&lt;T&gt;</code>.</p></div></details>
+            <details class="toggle method-toggle" open><summary><section id="method.push_within_capacity" class="method"><h4 class="code-header">pub fn <a href="#method.push_within_capacity" class="fn">push_within_capacity</a>(&amp;mut self, value: T) -&gt; <a class="enum" href="../result/enum.Result.html">Result</a>&lt;(), T&gt;</h4></section></summary><div class="docblock"><p>Appends an element if there is sufficient spare capacity.</p></div></details>
+        "##};
+
+        let expected = indoc! {"
+            #### pub const fn new() -> Vec<T> (since 1.0.0, const since 1.39.0)
+
+            Constructs a new, empty `Vec<T>`.
+
+            #### pub fn push_within_capacity(&mut self, value: T) -> Result<(), T>
+
+            Appends an element if there is sufficient spare capacity.
+        "}
+        .trim();
+        let options = RustdocConversionOptions {
+            include_since: true,
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(markdown, expected);
+
+        let expected = indoc! {"
+            #### pub const fn new() -> Vec<T>
+
+            Constructs a new, empty `Vec<T>`.
+
+            #### pub fn push_within_capacity(&mut self, value: T) -> Result<(), T>
+
+            Appends an element if there is sufficient spare capacity.
+        "}
+        .trim();
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        );
+
+        // The version is added to the title of the page once, even where the
+        // marker is inside of it.
+        let html = indoc! {r##"
+            <h1>Module <a href="#">sync</a><span class="since" title="Stable since Rust version 1.0.0">1.0.0</span></h1>
+        "##};
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(markdown, "# Module sync (since 1.0.0)");
+    }
+
+    #[test]
+    fn test_stability_annotations() {
+        let html = indoc! {r##"
+            <section id="main-content" class="content"><div class="main-heading"><h1>Struct <a href="index.html">std</a>::<wbr><a href="index.html">vec</a>::<wbr><a class="struct" href="#">Vec</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1><span class="out-of-band"><span class="since" title="Stable since Rust version 1.0.0">1.0.0</span> · <a class="src" href="../../src/alloc/vec/mod.rs.html#397-400">source</a> · <button id="toggle-all-docs" title="collapse all docs">[<span>&#x2212;</span>]</button></span></div><pre class="rust item-decl"><code>pub struct Vec&lt;T&gt; { /* private fields */ }</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>A contiguous growable array type.</p></div></details>
+            <details class="toggle method-toggle" open><summary><section id="method.with_capacity" class="method"><span class="rightside"><span class="since" title="Stable since Rust version 1.0.0">1.0.0</span> · <a class="src" href="../../src/alloc/vec/mod.rs.html#479">source</a></span><h4 class="code-header">pub fn <a href="#method.with_capacity" class="fn">with_capacity</a>(capacity: <a class="primitive" href="../primitive.usize.html">usize</a>) -&gt; <a class="struct" href="struct.Vec.html">Vec</a>&lt;T&gt;</h4></section></summary><div class="docblock"><p>Constructs a new, empty <code>Vec&lt;T&gt;</code> with at least the specified capacity.</p></div></details>
+            <details class="toggle method-toggle" open><summary><section id="method.new_in" class="method"><a class="src rightside" href="../../src/alloc/vec/mod.rs.html#560">source</a><h4 class="code-header">pub const fn <a href="#method.new_in" class="fn">new_in</a>(alloc: A) -&gt; Self</h4></section><span class="item-info"><div class="stab unstable"><span class="emoji">🔬</span><span>This is a nightly-only experimental API. (<code>allocator_api</code>&nbsp;<a href="https://github.com/rust-lang/rust/issues/32838">#32838</a>)</span></div></span></summary><div class="docblock"><p>Constructs a new, empty <code>Vec&lt;T, A&gt;</code>.</p></div></details>
+        "##};
+
+        let expected = indoc! {"
+            # Struct std::vec::Vec<T> (since 1.0.0)
+
+            ```rs
+            pub struct Vec<T> { /* private fields */ }
+            ```
+
+            A contiguous growable array type.
+
+            #### pub fn with_capacity(capacity: usize) -> Vec<T> (since 1.0.0)
+
+            Constructs a new, empty `Vec<T>` with at least the specified capacity.
+
+            #### pub const fn new_in(alloc: A) -> Self
+
+            > **Unstable:** This is a nightly-only experimental API. (`allocator_api` #32838)
+
+            Constructs a new, empty `Vec<T, A>`.
+        "}
+        .trim();
+        let options = RustdocConversionOptions {
+            include_since: true,
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(markdown, expected);
+        assert_eq!(
+            convert_rustdoc_to_since(html.as_bytes()).unwrap(),
+            Some("1.0.0".to_string())
+        );
+
+        // The unstable items listed on a module page are marked as such.
+        let html = indoc! {r##"
+            <h2 id="structs" class="section-header">Structs<a href="#structs" class="anchor">§</a></h2><dl class="item-table"><dt><a class="struct" href="struct.Global.html" title="struct std::alloc::Global">Global</a><span class="stab unstable" title="">Experimental</span></dt><dd>The global memory allocator.</dd><dt><a class="struct" href="struct.System.html" title="struct std::alloc::System">System</a></dt><dd>The default memory allocator provided by the operating system.</dd></dl>
+        "##};
+        let (markdown, items) = convert_rustdoc_to_markdown(html.as_bytes()).unwrap();
+        assert_eq!(
+            markdown,
+            indoc! {"
+                ## Structs
+
+                - `Global` [Experimental]: The global memory allocator.
+                - `System`: The default memory allocator provided by the operating system.
+            "}
+            .trim()
+        );
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| (item.item.name.as_ref(), item.unstable))
+                .collect::<Vec<_>>(),
+            vec![("Global", true), ("System", false)]
+        );
+        assert_eq!(convert_rustdoc_to_since(html.as_bytes()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_rustdoc_versions() {
+        assert_eq!(
+            RustdocHtmlFormat::for_version("1.80.0 (051478957 2024-07-21)"),
+            Some(RustdocHtmlFormat::ItemTableList)
+        );
+        assert_eq!(
+            RustdocHtmlFormat::for_version("1.86.0-nightly (9a1d156f3 2025-01-19)"),
+            Some(RustdocHtmlFormat::ItemTableDefinitionList)
+        );
+        assert_eq!(
+            RustdocHtmlFormat::for_version("1.40.0 (73528e339 2019-12-16)"),
+            None
+        );
+        assert_eq!(
+            RustdocHtmlFormat::for_version("1.99.0 (4d91de4e4 2027-02-18)"),
+            None
+        );
+        assert_eq!(RustdocHtmlFormat::for_version("unknown"), None);
+
+        let page = |vars: &str| {
+            format!(
+                r##"<html><head>{vars}</head><body><section id="main-content" class="content"><details class="toggle top-doc" open><div class="docblock"><p>A contiguous growable array type.</p></div></details></section></body></html>"##
+            )
+        };
+
+        // Newer versions of rustdoc have the version in a `<meta>`, and older
+        // ones in a `<div>`.
+        let html = page(
+            r#"<meta name="rustdoc-vars" data-root-path="../../" data-rustdoc-version="1.84.0 (9fc6b4312 2025-01-07)">"#,
+        );
+        assert_eq!(
+            convert_rustdoc_to_version(html.as_bytes()).unwrap(),
+            Some("1.84.0 (9fc6b4312 2025-01-07)".to_string())
+        );
+        let (markdown, _items) = convert_rustdoc_to_markdown(html.as_bytes()).unwrap();
+        assert_eq!(markdown, "A contiguous growable array type.");
+
+        let html = page(
+            r#"<div id="rustdoc-vars" data-root-path="../../" data-rustdoc-version="1.99.0 (4d91de4e4 2027-02-18)"></div>"#,
+        );
+        let (markdown, _items) = convert_rustdoc_to_markdown(html.as_bytes()).unwrap();
+        assert_eq!(
+            markdown,
+            indoc! {"
+                > **Note:** This page was generated by rustdoc 1.99.0 (4d91de4e4 2027-02-18), whose HTML format isn't recognized (rustdoc 1.54 to 1.92 are supported), so parts of the docs may be incomplete or missing.
+
+                A contiguous growable array type.
+            "}
+            .trim()
+        );
+
+        // Pages that don't say which version generated them are converted as
+        // is.
+        let html = page("");
+        assert_eq!(convert_rustdoc_to_version(html.as_bytes()).unwrap(), None);
+        let (markdown, _items) = convert_rustdoc_to_markdown(html.as_bytes()).unwrap();
+        assert_eq!(markdown, "A contiguous growable array type.");
+    }
+
+    #[test]
+    fn test_derive_macro_helper_attributes() {
+        let html = indoc! {r##"
+            <section id="main-content" class="content"><div class="main-heading"><h1>Derive Macro <a href="index.html">serde_derive</a>::<wbr><a class="derive" href="#">Serialize</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1><span class="out-of-band"><a class="src" href="../src/serde_derive/lib.rs.html#97-102">source</a></span></div><pre class="rust item-decl"><code>#[derive(Serialize)]
+            {
+                // Attributes available to this derive:
+                #[serde]
+            }
+            </code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>Derives <code>serde::Serialize</code> for the annotated type.</p></div></details></section>
+        "##};
+        let expected = indoc! {"
+            # Derive Macro serde_derive::Serialize
+
+            ```rs
+            #[derive(Serialize)]
+            {
+                // Attributes available to this derive:
+                #[serde]
+            }
+
+            ```
+
+            Helper attributes:
+
+            - `#[serde]`
+
+            Derives `serde::Serialize` for the annotated type.
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        )
+    }
+
+    #[test]
+    fn test_layout() {
+        let html = indoc! {r##"
+            <section id="main-content" class="content"><div class="main-heading"><h1>Struct <a href="index.html">geo</a>::<wbr><a class="struct" href="#">Point</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1><span class="out-of-band"><a class="src" href="../src/geo/lib.rs.html#4-7">source</a></span></div><pre class="rust item-decl"><code><div class="code-attribute">#[repr(C)]</div>pub struct Point {
+                pub x: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.f64.html">f64</a>,
+                pub y: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.f64.html">f64</a>,
+            }</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>A point in 2D space.</p></div></details><h2 id="layout" class="section-header">Layout<a href="#layout" class="anchor">§</a></h2><div class="docblock type-layout"><p><strong>Note:</strong> Most layout information is <strong>completely unstable</strong> and may even differ between compilations. The only exception is types with certain <code>repr(...)</code> attributes. Please see the Rust Reference's <a href="https://doc.rust-lang.org/reference/type-layout.html">“Type Layout”</a> chapter for details on type layout guarantees.</p><p><strong>Size:</strong> 16 bytes</p></div></section>
+        "##};
+        let expected = indoc! {"
+            # Struct geo::Point
+
+            ```rs
+            #[repr(C)]
+            pub struct Point {
+                pub x: f64,
+                pub y: f64,
+            }
+            ```
+
+            A point in 2D space.
+
+            ## Layout
+
+            - `#[repr(C)]`
+            - Size: 16 bytes
+        "}
+        .trim();
+        let options = RustdocConversionOptions {
+            include_layout: true,
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(markdown, expected);
+
+        // Types without any layout information are left as is.
+        let html = indoc! {r##"
+            <pre class="rust item-decl"><code>pub struct Empty;</code></pre><div class="docblock"><p>An empty struct.</p></div>
+        "##};
+        let expected = indoc! {"
+            ```rs
+            pub struct Empty;
+            ```
+
+            An empty struct.
+        "}
+        .trim();
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn test_implementors() {
+        let html = indoc! {r##"
+            <section id="main-content" class="content"><div class="main-heading"><h1>Trait <a href="../index.html">core</a>::<wbr><a href="index.html">fmt</a>::<wbr><a class="trait" href="#">Display</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1></div><pre class="rust item-decl"><code>pub trait Display {
+                // Required method
+                fn <a href="#tymethod.fmt" class="fn">fmt</a>(&amp;self, f: &amp;mut <a class="struct" href="struct.Formatter.html" title="struct core::fmt::Formatter">Formatter</a>&lt;'_&gt;) -&gt; <a class="type" href="type.Result.html" title="type core::fmt::Result">Result</a>;
+            }</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>Format trait for an empty format, <code>{}</code>.</p></div></details>
+            <h2 id="foreign-impls" class="section-header">Implementations on Foreign Types<a href="#foreign-impls" class="anchor">§</a></h2><details class="toggle implementors-toggle"><summary><section id="impl-Display-for-bool" class="impl"><a class="src rightside" href="../../src/core/fmt/mod.rs.html#2368">source</a><a href="#impl-Display-for-bool" class="anchor">§</a><h3 class="code-header">impl <a class="trait" href="trait.Display.html" title="trait core::fmt::Display">Display</a> for <a class="primitive" href="../primitive.bool.html">bool</a></h3></section></summary><div class="impl-items"><section id="method.fmt-1" class="method trait-impl"><h4 class="code-header">fn <a href="#tymethod.fmt" class="fn">fmt</a>(&amp;self, f: &amp;mut <a class="struct" href="struct.Formatter.html">Formatter</a>&lt;'_&gt;) -&gt; <a class="type" href="type.Result.html">Result</a></h4></section></div></details><details class="toggle implementors-toggle"><summary><section id="impl-Display-for-%26T" class="impl"><h3 class="code-header">impl&lt;T: <a class="trait" href="trait.Display.html">Display</a> + ?<a class="trait" href="../marker/trait.Sized.html">Sized</a>&gt; <a class="trait" href="trait.Display.html">Display</a> for &amp;T</h3></section></summary></details>
+            <h2 id="implementors" class="section-header">Implementors<a href="#implementors" class="anchor">§</a></h2><div id="implementors-list"><section id="impl-Display-for-Arguments%3C'_%3E" class="impl"><a href="#impl-Display-for-Arguments%3C'_%3E" class="anchor">§</a><h3 class="code-header">impl <a class="trait" href="trait.Display.html">Display</a> for <a class="struct" href="struct.Arguments.html">Arguments</a>&lt;'_&gt;</h3></section><section id="impl-Display-for-Error" class="impl"><h3 class="code-header">impl <a class="trait" href="trait.Display.html">Display</a> for <a class="struct" href="struct.Error.html">Error</a></h3></section><section id="impl-Display-for-Wrapping%3CT%3E" class="impl"><h3 class="code-header">impl&lt;T&gt; <a class="trait" href="trait.Display.html">Display</a> for <a class="struct" href="../num/struct.Wrapping.html">Wrapping</a>&lt;T&gt;<div class="where">where
+                T: <a class="trait" href="trait.Display.html">Display</a>,</div></h3></section></div></section>
+        "##};
+        let declaration = indoc! {"
+            # Trait core::fmt::Display
+
+            ```rs
+            pub trait Display {
+                // Required method
+                fn fmt(&self, f: &mut Formatter<'_>) -> Result;
+            }
+            ```
+
+            Format trait for an empty format, `{}`.
+        "}
+        .trim();
+        let options = RustdocConversionOptions {
+            list_implementors: true,
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(
+            markdown,
+            format!(
+                "{declaration}\n\n## Implementors\n\n- `bool`\n- `&T`\n- `Arguments<'_>`\n- \
+                `Error`\n- `Wrapping<T>`"
+            )
+        );
+
+        // Long lists of implementors are cut short.
+        let options = RustdocConversionOptions {
+            list_implementors: true,
+            max_implementors: Some(2),
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(
+            markdown,
+            format!("{declaration}\n\n## Implementors\n\n- `bool`\n- `&T`\n\n…and 3 more.")
+        );
+
+        let html = indoc! {r##"
+            <pre class="rust item-decl"><code>pub struct Empty;</code></pre><div class="docblock"><p>An empty struct.</p></div>
+        "##};
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(
+            markdown,
+            "```rs\npub struct Empty;\n```\n\nAn empty struct.\n\nNo implementors are listed on \
+            this page."
+        );
+    }
+
+    #[test]
+    fn test_item_kind_filter() {
+        let html = indoc! {r##"
+            <div class="docblock"><p>Asynchronous file utilities.</p></div>
+            <h2 id="reexports" class="section-header">Re-exports<a href="#reexports" class="anchor">§</a></h2>
+            <ul class="item-table"><li><div class="item-name" id="reexport.File"><code>pub use self::file::<a class="struct" href="struct.File.html" title="struct tokio::fs::File">File</a>;</code></div></li></ul>
+            <h2 id="structs" class="section-header">Structs<a href="#structs" class="anchor">§</a></h2>
+            <ul class="item-table"><li><div class="item-name"><a class="struct" href="struct.DirEntry.html" title="struct tokio::fs::DirEntry">DirEntry</a></div><div class="desc docblock-short">Entries returned by the <code>ReadDir</code> stream.</div></li></ul>
+            <h2 id="functions" class="section-header">Functions<a href="#functions" class="anchor">§</a></h2>
+            <ul class="item-table"><li><div class="item-name"><a class="fn" href="fn.copy.html" title="fn tokio::fs::copy">copy</a></div><div class="desc docblock-short">Copies the contents of one file to another.</div></li><li><div class="item-name"><a class="fn" href="fn.read.html" title="fn tokio::fs::read">read</a></div><div class="desc docblock-short">Reads the entire contents of a file into a bytes vector.</div></li></ul>
+        "##};
+        let expected = indoc! {"
+            Asynchronous file utilities.
+
+            ## Functions
+
+            - `copy`: Copies the contents of one file to another.
+            - `read`: Reads the entire contents of a file into a bytes vector.
+        "}
+        .trim();
+        let options = RustdocConversionOptions {
+            item_kinds: vec![RustdocItemKind::Function],
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(markdown, expected);
+
+        let expected = indoc! {"
+            Asynchronous file utilities.
+
+            No items of the requested kinds (`trait`, `macro`) are listed on this page.
+        "}
+        .trim();
+        let options = RustdocConversionOptions {
+            item_kinds: vec![RustdocItemKind::Trait, RustdocItemKind::Macro],
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn test_deprecation_notices() {
+        let html = indoc! {r##"
+            <pre class="rust item-decl"><code>pub fn trim_left(&amp;self) -&gt; &amp;str</code></pre><span class="item-info"><div class="stab deprecated"><span class="emoji">👎</span><span>Deprecated since 1.33.0: superseded by <code>trim_start</code></span></div></span>
+            <details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>Returns a string slice with leading whitespace removed.</p></div></details>
+            <details class="toggle method-toggle" open><summary><section id="method.description" class="method"><h4 class="code-header">fn <a href="#method.description" class="fn">description</a>(&amp;self) -&gt; &amp;str</h4></section><span class="item-info"><div class="stab deprecated"><span class="emoji">👎</span><span>Deprecated</span></div></span></summary><div class="docblock"><p>Returns a short description of the error.</p></div></details>
+            <ul class="item-table"><li><div class="item-name"><a class="fn" href="fn.trim_left.html" title="fn trim_left">trim_left</a><span class="stab deprecated" title="">Deprecated</span></div></li></ul>
+        "##};
+        let expected = indoc! {"
+            ```rs
+            pub fn trim_left(&self) -> &str
+            ```
+
+            > **Deprecated:** superseded by `trim_start` (since 1.33.0)
+
+            Returns a string slice with leading whitespace removed.
+
+            #### fn description(&self) -> &str
+
+            > **Deprecated:** This item is deprecated.
+
+            Returns a short description of the error.
+            - `trim_left` [Deprecated]:
+        "}
+        .trim();
+
+        assert_eq!(
+            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
+            expected
+        );
+        assert_eq!(
+            RustdocDeprecationHandler::notice("Deprecated since 0.2.0"),
+            "Since 0.2.0."
+        );
+        assert_eq!(
+            RustdocDeprecationHandler::notice("Deprecation planned"),
+            "Deprecation planned"
+        );
+    }
+
+    #[test]
+    fn test_resolve_links() {
+        let location = RustdocPageLocation {
+            crate_name: "axum".to_string(),
+            module_path: vec!["extract".to_string()],
+        };
+        let resolve = |href: &str| RustdocLinkTarget::resolve(href, &location);
+        let item = |path: &str| Some(RustdocLinkTarget::Item(path.to_string()));
+
+        assert_eq!(resolve("struct.Path.html"), item("axum::extract::Path"));
+        assert_eq!(resolve("../struct.Json.html"), item("axum::Json"));
+        assert_eq!(resolve("ws/index.html"), item("axum::extract::ws"));
+        assert_eq!(
+            resolve("../../http/request/struct.Parts.html#structfield.uri"),
+            item("http::request::Parts::uri")
+        );
+        assert_eq!(
+            resolve("trait.FromRequest.html#tymethod.from_request"),
+            item("axum::extract::FromRequest::from_request")
+        );
+        assert_eq!(
+            resolve("https://doc.rust-lang.org/nightly/std/sync/struct.Mutex.html#method.lock"),
+            item("std::sync::Mutex::lock")
+        );
+        assert_eq!(
+            resolve("https://doc.rust-lang.org/nightly/core/primitive.u8.html"),
+            item("core::u8")
+        );
+        assert_eq!(
+            resolve("https://docs.rs/tokio/1.37.0/x86_64-unknown-linux-gnu/tokio/fn.spawn.html"),
+            item("tokio::spawn")
+        );
+        assert_eq!(
+            resolve("/serde/1.0.200/serde/trait.Serialize.html"),
+            item("serde::Serialize")
+        );
+        assert_eq!(
+            resolve("https://github.com/tokio-rs/axum"),
+            Some(RustdocLinkTarget::External(
+                "https://github.com/tokio-rs/axum".to_string()
+            ))
+        );
+
+        // Links within the page, to the source code, or to other files in the
+        // docs aren't items.
+        assert_eq!(resolve("#examples"), None);
+        assert_eq!(resolve("../../src/axum/extract/mod.rs.html#1"), None);
+        assert_eq!(resolve("../all.html"), None);
+        assert_eq!(resolve("mailto:security@tokio.rs"), None);
+    }
+
+    #[test]
+    fn test_page_location_from_url() {
+        let location = |crate_name: &str, module_path: &[&str]| {
+            Some(RustdocPageLocation {
+                crate_name: crate_name.to_string(),
+                module_path: module_path.iter().map(ToString::to_string).collect(),
+            })
+        };
+
+        assert_eq!(
+            RustdocPageLocation::from_url("https://docs.rs/tokio/1.38.0/tokio/sync/index.html"),
+            location("tokio", &["sync"])
+        );
+        assert_eq!(
+            RustdocPageLocation::from_url(
+                "https://docs.rs/tokio/1.38.0/x86_64-unknown-linux-gnu/tokio/sync/struct.Mutex.html#method.lock"
+            ),
+            location("tokio", &["sync"])
+        );
+        // The library of a package may have a different name.
+        assert_eq!(
+            RustdocPageLocation::from_url("https://docs.rs/tree-sitter/0.22.6/tree_sitter/"),
+            location("tree_sitter", &[])
+        );
+        assert_eq!(
+            RustdocPageLocation::from_url(
+                "https://doc.rust-lang.org/nightly/std/collections/index.html"
+            ),
+            location("std", &["collections"])
+        );
+        assert_eq!(
+            RustdocPageLocation::from_url("https://doc.rust-lang.org/core/"),
+            location("core", &[])
+        );
+        assert_eq!(
+            RustdocPageLocation::from_url("https://docs.rs/crate/tokio/1.38.0"),
+            None
+        );
+        assert_eq!(
+            RustdocPageLocation::from_url("https://github.com/tokio-rs/tokio"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        let html = indoc! {r##"
+            <pre class="rust item-decl"><code>pub struct Path&lt;T&gt;(pub <a class="type" href="struct.Json.html">T</a>);</code></pre>
+            <details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>Extractor that will get captures from the URL and parse them using <a href="https://docs.rs/serde/1.0.200/serde/trait.Deserialize.html"><code>serde</code></a>.</p>
+            <p>See the <a href="../index.html#extractors">extractors</a> docs, <a href="../struct.Json.html" title="struct axum::Json"><code>Json</code></a>, and the <a href="https://github.com/tokio-rs/axum/tree/main/examples">examples</a>. Or read more <a href="#examples">below</a>.</p></div></details>
+        "##};
+        let expected = indoc! {"
+            ```rs
+            pub struct Path<T>(pub T);
+            ```
+
+            Extractor that will get captures from the URL and parse them using [`serde`](serde::Deserialize).
+
+            See the [extractors](axum) docs, [`Json`](axum::Json), and the [examples](https://github.com/tokio-rs/axum/tree/main/examples). Or read more below.
+
+            ## See also
+
+            - `serde::Deserialize`
+            - `axum`
+            - `axum::Json`
+        "}
+        .trim();
+        let options = RustdocConversionOptions {
+            page_location: Some(RustdocPageLocation {
+                crate_name: "axum".to_string(),
+                module_path: vec!["extract".to_string()],
+            }),
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn test_see_also() {
+        let html = indoc! {r##"
+            <nav class="sidebar"><div class="sidebar-elems"><section><h3><a href="#implementations">Methods</a></h3><ul class="block method"><li><a href="#method.lock">lock</a></li></ul></section><h2><a href="index.html">In tokio::sync</a></h2></div></nav>
+            <section id="main-content" class="content"><div class="main-heading"><h1>Struct <a href="../index.html">tokio</a>::<wbr><a href="index.html">sync</a>::<wbr><a class="struct" href="#">Mutex</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1></div><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>An asynchronous <code>Mutex</code>-like type, unlike <a href="https://doc.rust-lang.org/nightly/std/sync/struct.Mutex.html" title="struct std::sync::Mutex"><code>std::sync::Mutex</code></a>. Use <a href="struct.Mutex.html#method.lock"><code>lock</code></a> to acquire it.</p>
+            <p>See also <a href="struct.RwLock.html"><code>RwLock</code></a>, which is like a <code>Mutex</code> but allows many readers (see <a href="struct.RwLock.html#method.read"><code>RwLock::read</code></a>, not the <a href="#examples">examples</a>) and <a href="https://github.com/tokio-rs/tokio">the repository</a>.</p></div></details></section>
+        "##};
+        let location = RustdocPageLocation {
+            crate_name: "tokio".to_string(),
+            module_path: vec!["sync".to_string()],
+        };
+        let options = RustdocConversionOptions {
+            page_location: Some(location.clone()),
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        // The links are listed by the full paths of the items they resolve to,
+        // leaving out the struct's own method.
+        assert_eq!(
+            markdown.split_once("## See also").unwrap().1.trim(),
+            indoc! {"
+                - `tokio::sync`
+                - `std::sync::Mutex`
+                - `tokio::sync::RwLock`
+                - `tokio::sync::RwLock::read`
+            "}
+            .trim()
+        );
+
+        let options = RustdocConversionOptions {
+            page_location: Some(location.clone()),
+            omit_see_also: true,
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert!(!markdown.contains("## See also"));
+
+        // On a module page, the items listed in the module are left out, but
+        // the other items in the module aren't.
+        let html = indoc! {r##"
+            <section id="main-content" class="content"><div class="main-heading"><h1>Module <a href="../index.html">tokio</a>::<wbr><a class="mod" href="#">sync</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1></div><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>Synchronization primitives, such as <a href="struct.Mutex.html"><code>Mutex</code></a> and <a href="mpsc/fn.channel.html"><code>mpsc::channel</code></a>.</p></div></details>
+            <h2 id="structs" class="section-header">Structs<a href="#structs" class="anchor">§</a></h2><dl class="item-table"><dt><a class="struct" href="struct.Mutex.html" title="struct tokio::sync::Mutex">Mutex</a></dt><dd>An asynchronous <code>Mutex</code>-like type.</dd></dl></section>
+        "##};
+        let options = RustdocConversionOptions {
+            page_location: Some(location),
+            ..Default::default()
+        };
+        let (markdown, _items) =
+            convert_rustdoc_to_markdown_with_options(html.as_bytes(), &options).unwrap();
+        assert_eq!(
+            markdown.split_once("## See also").unwrap().1.trim(),
+            "- `tokio::sync::mpsc::channel`"
+        );
+    }
+
+    #[test]
+    fn test_method_bounds() {
+        let html = indoc! {r##"
+            <details class="toggle method-toggle" open><summary><section id="tymethod.next" class="method"><a class="src rightside" href="../../src/core/iter/traits/iterator.rs.html#104">source</a><h4 class="code-header">fn <a href="#tymethod.next" class="fn">next</a>(&amp;mut self) -&gt; <a class="enum" href="../option/enum.Option.html">Option</a>&lt;Self::<a class="associatedtype" href="trait.Iterator.html#associatedtype.Item">Item</a>&gt;</h4></section></summary></details>
+            <details class="toggle method-toggle" open><summary><section id="method.map" class="method"><a class="src rightside" href="../../src/core/iter/traits/iterator.rs.html#743-746">source</a><h4 class="code-header">fn <a href="#method.map" class="fn">map</a>&lt;B, F&gt;(self, f: F) -&gt; <a class="struct" href="struct.Map.html">Map</a>&lt;Self, F&gt; <a href="#" class="tooltip" data-notable-ty="Map&lt;Self, F&gt;">ⓘ</a><div class="where">where
+                Self: <a class="trait" href="../marker/trait.Sized.html">Sized</a>,
+                F: <a class="trait" href="../ops/trait.FnMut.html">FnMut</a>(Self::<a class="associatedtype" href="trait.Iterator.html#associatedtype.Item">Item</a>) -&gt; B,</div></h4></section></summary></details>
+            <details class="toggle method-toggle" open><summary><section id="method.sum" class="method"><h4 class="code-header">fn <a href="#method.sum" class="fn">sum</a>&lt;S: <a class="trait" href="trait.Sum.html">Sum</a>&lt;Self::<a class="associatedtype" href="trait.Iterator.html#associatedtype.Item">Item</a>&gt;&gt;(self) -&gt; S <div class="where">where Self: <a class="trait" href="../marker/trait.Sized.html">Sized</a></div></h4></section></summary></details>
+        "##};
+
+        assert_eq!(
+            convert_rustdoc_to_method_bounds(html.as_bytes(), "map").unwrap(),
+            Some(vec![
+                "Self: Sized".to_string(),
+                "F: FnMut(Self::Item) -> B".to_string()
+            ])
+        );
         assert_eq!(
-            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
-            expected
-        )
+            convert_rustdoc_to_method_bounds(html.as_bytes(), "sum").unwrap(),
+            Some(vec![
+                "S: Sum<Self::Item>".to_string(),
+                "Self: Sized".to_string()
+            ])
+        );
+        assert_eq!(
+            convert_rustdoc_to_method_bounds(html.as_bytes(), "next").unwrap(),
+            Some(Vec::new())
+        );
+        assert_eq!(
+            convert_rustdoc_to_method_bounds(html.as_bytes(), "filter").unwrap(),
+            None
+        );
     }
 
     #[test]
-    fn test_single_paragraph() {
-        let html = indoc! {r#"
-            <p>In particular, the last point is what sets <code>axum</code> apart from other frameworks.
-            <code>axum</code> doesn’t have its own middleware system but instead uses
-            <a href="https://docs.rs/tower-service/0.3.2/x86_64-unknown-linux-gnu/tower_service/trait.Service.html" title="trait tower_service::Service"><code>tower::Service</code></a>. This means <code>axum</code> gets timeouts, tracing, compression,
-            authorization, and more, for free. It also enables you to share middleware with
-            applications written using <a href="http://crates.io/crates/hyper"><code>hyper</code></a> or <a href="http://crates.io/crates/tonic"><code>tonic</code></a>.</p>
-        "#};
-        let expected = indoc! {"
-            In particular, the last point is what sets `axum` apart from other frameworks. `axum` doesn’t have its own middleware system but instead uses `tower::Service`. This means `axum` gets timeouts, tracing, compression, authorization, and more, for free. It also enables you to share middleware with applications written using `hyper` or `tonic`.
-        "}
-        .trim();
+    fn test_method_index() {
+        let html = indoc! {r##"
+            <h2 id="implementations" class="section-header">Implementations<a href="#implementations" class="anchor">§</a></h2><div id="implementations-list"><details class="toggle implementors-toggle" open><summary><section id="impl-String" class="impl"><a class="src rightside" href="../../src/alloc/string.rs.html#365">source</a><a href="#impl-String" class="anchor">§</a><h3 class="code-header">impl <a class="struct" href="struct.String.html">String</a></h3></section></summary><div class="impl-items"><details class="toggle method-toggle" open><summary><section id="method.new" class="method"><span class="rightside"><span class="since" title="Stable since Rust version 1.0.0">1.0.0</span> · <a class="src" href="../../src/alloc/string.rs.html#440">source</a></span><h4 class="code-header">pub const fn <a href="#method.new" class="fn">new</a>() -&gt; <a class="struct" href="struct.String.html">String</a></h4></section></summary><div class="docblock"><p>Creates a new empty <code>String</code>.</p></div></details><details class="toggle method-toggle" open><summary><section id="method.extend_from_within" class="method"><h4 class="code-header">pub fn <a href="#method.extend_from_within" class="fn">extend_from_within</a>&lt;R&gt;(
+                &amp;mut self,
+                src: R,
+            ) <div class="where">where
+                R: <a class="trait" href="../ops/trait.RangeBounds.html">RangeBounds</a>&lt;<a class="primitive" href="../primitive.usize.html">usize</a>&gt;,</div></h4></section></summary><div class="docblock"><p>Copies elements from <code>src</code> range to the end of the string.</p></div></details></div></details></div>
+            <h2 id="trait-implementations" class="section-header">Trait Implementations<a href="#trait-implementations" class="anchor">§</a></h2><div id="trait-implementations-list"><details class="toggle implementors-toggle" open><summary><section id="impl-Clone-for-String" class="impl"><h3 class="code-header">impl <a class="trait" href="../clone/trait.Clone.html">Clone</a> for <a class="struct" href="struct.String.html">String</a></h3></section></summary><div class="impl-items"><details class="toggle method-toggle" open><summary><section id="method.clone" class="method trait-impl"><h4 class="code-header">fn <a href="../clone/trait.Clone.html#tymethod.clone" class="fn">clone</a>(&amp;self) -&gt; Self</h4></section></summary><div class="docblock"><p>Returns a copy of the value.</p></div></details></div></details><section id="impl-Eq-for-String" class="impl"><h3 class="code-header">impl <a class="trait" href="../cmp/trait.Eq.html">Eq</a> for <a class="struct" href="struct.String.html">String</a></h3></section><details class="toggle implementors-toggle" open><summary><section id="impl-FromStr-for-String" class="impl"><h3 class="code-header">impl <a class="trait" href="../str/trait.FromStr.html">FromStr</a> for <a class="struct" href="struct.String.html">String</a></h3></section></summary><div class="impl-items"><section id="associatedtype.Err" class="associatedtype trait-impl"><h4 class="code-header">type <a href="../str/trait.FromStr.html#associatedtype.Err" class="associatedtype">Err</a> = <a class="enum" href="../convert/enum.Infallible.html">Infallible</a></h4></section><section id="method.from_str" class="method trait-impl"><h4 class="code-header">fn <a href="../str/trait.FromStr.html#tymethod.from_str" class="fn">from_str</a>(s: &amp;<a class="primitive" href="../primitive.str.html">str</a>) -&gt; <a class="enum" href="../result/enum.Result.html">Result</a>&lt;<a class="struct" href="struct.String.html">String</a>, Self::<a class="associatedtype" href="../str/trait.FromStr.html#associatedtype.Err">Err</a>&gt;</h4></section></div></details></div>
+            <h2 id="blanket-implementations" class="section-header">Blanket Implementations<a href="#blanket-implementations" class="anchor">§</a></h2><div id="blanket-implementations-list"><details class="toggle implementors-toggle"><summary><section id="impl-From%3CT%3E-for-T" class="impl"><h3 class="code-header">impl&lt;T&gt; <a class="trait" href="../convert/trait.From.html">From</a>&lt;T&gt; for T</h3></section></summary><div class="impl-items"><section id="method.from" class="method trait-impl"><h4 class="code-header">fn <a href="../convert/trait.From.html#tymethod.from" class="fn">from</a>(t: T) -&gt; T</h4></section></div></details></div>
+        "##};
 
         assert_eq!(
-            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
-            expected
-        )
+            convert_rustdoc_to_method_index(html.as_bytes()).unwrap(),
+            MethodIndex {
+                inherent_methods: vec![
+                    "pub const fn new() -> String".to_string(),
+                    "pub fn extend_from_within<R>(&mut self, src: R)".to_string(),
+                ],
+                trait_impls: vec![
+                    (
+                        "impl Clone for String".to_string(),
+                        vec!["fn clone(&self) -> Self".to_string()]
+                    ),
+                    (
+                        "impl FromStr for String".to_string(),
+                        vec![
+                            "type Err = Infallible".to_string(),
+                            "fn from_str(s: &str) -> Result<String, Self::Err>".to_string()
+                        ]
+                    ),
+                ],
+                ..Default::default()
+            }
+        );
     }
 
     #[test]
-    fn test_multiple_paragraphs() {
+    fn test_associated_items() {
         let html = indoc! {r##"
-            <h2 id="serde"><a class="doc-anchor" href="#serde">§</a>Serde</h2>
-            <p>Serde is a framework for <em><strong>ser</strong></em>ializing and <em><strong>de</strong></em>serializing Rust data
-            structures efficiently and generically.</p>
-            <p>The Serde ecosystem consists of data structures that know how to serialize
-            and deserialize themselves along with data formats that know how to
-            serialize and deserialize other things. Serde provides the layer by which
-            these two groups interact with each other, allowing any supported data
-            structure to be serialized and deserialized using any supported data format.</p>
-            <p>See the Serde website <a href="https://serde.rs/">https://serde.rs/</a> for additional documentation and
-            usage examples.</p>
-            <h3 id="design"><a class="doc-anchor" href="#design">§</a>Design</h3>
-            <p>Where many other languages rely on runtime reflection for serializing data,
-            Serde is instead built on Rust’s powerful trait system. A data structure
-            that knows how to serialize and deserialize itself is one that implements
-            Serde’s <code>Serialize</code> and <code>Deserialize</code> traits (or uses Serde’s derive
-            attribute to automatically generate implementations at compile time). This
-            avoids any overhead of reflection or runtime type information. In fact in
-            many situations the interaction between data structure and data format can
-            be completely optimized away by the Rust compiler, leaving Serde
-            serialization to perform the same speed as a handwritten serializer for the
-            specific selection of data structure and data format.</p>
+            <main><section id="main-content" class="content"><div class="main-heading"><h1>Trait <a href="index.html">demo</a>::<wbr><a class="trait" href="#">Shape</a></h1></div><pre class="rust item-decl"><code>pub trait Shape {
+                type <a href="#associatedtype.Unit" class="associatedtype">Unit</a>;
+
+                const <a href="#associatedconstant.SIDES" class="constant">SIDES</a>: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.usize.html">usize</a>;
+                const <a href="#associatedconstant.NAME" class="constant">NAME</a>: &amp;'static <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.str.html">str</a> = "shape";
+
+                // Required method
+                fn <a href="#tymethod.area" class="fn">area</a>(&amp;self) -&gt; Self::<a class="associatedtype" href="trait.Shape.html#associatedtype.Unit" title="type demo::Shape::Unit">Unit</a>;
+            }</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>A shape.</p></div></details><h2 id="required-associated-types" class="section-header">Required Associated Types<a href="#required-associated-types" class="anchor">§</a></h2><div class="methods"><details class="toggle" open><summary><section id="associatedtype.Unit" class="method"><a class="src rightside" href="../src/demo/lib.rs.html#3">Source</a><h4 class="code-header">type <a href="#associatedtype.Unit" class="associatedtype">Unit</a></h4></section></summary><div class="docblock"><p>The unit that the area is measured in.</p></div></details></div><h2 id="required-associated-consts" class="section-header">Required Associated Constants<a href="#required-associated-consts" class="anchor">§</a></h2><div class="methods"><details class="toggle" open><summary><section id="associatedconstant.SIDES" class="method"><h4 class="code-header">const <a href="#associatedconstant.SIDES" class="constant">SIDES</a>: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.usize.html">usize</a></h4></section></summary><div class="docblock"><p>The number of sides.</p></div></details></div><h2 id="provided-associated-consts" class="section-header">Provided Associated Constants<a href="#provided-associated-consts" class="anchor">§</a></h2><div class="methods"><section id="associatedconstant.NAME" class="method"><h4 class="code-header">const <a href="#associatedconstant.NAME" class="constant">NAME</a>: &amp;'static <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.str.html">str</a> = "shape"</h4></section></div><h2 id="required-methods" class="section-header">Required Methods<a href="#required-methods" class="anchor">§</a></h2><div class="methods"><details class="toggle method-toggle" open><summary><section id="tymethod.area" class="method"><h4 class="code-header">fn <a href="#tymethod.area" class="fn">area</a>(&amp;self) -&gt; Self::<a class="associatedtype" href="trait.Shape.html#associatedtype.Unit" title="type demo::Shape::Unit">Unit</a></h4></section></summary><div class="docblock"><p>Returns the area.</p></div></details></div><h2 id="implementors" class="section-header">Implementors<a href="#implementors" class="anchor">§</a></h2><div id="implementors-list"><details class="toggle implementors-toggle" open><summary><section id="impl-Shape-for-Square" class="impl"><a href="#impl-Shape-for-Square" class="anchor">§</a><h3 class="code-header">impl <a class="trait" href="trait.Shape.html" title="trait demo::Shape">Shape</a> for <a class="struct" href="struct.Square.html" title="struct demo::Square">Square</a></h3></section></summary><div class="impl-items"><section id="associatedtype.Unit-1" class="associatedtype trait-impl"><a href="#associatedtype.Unit-1" class="anchor">§</a><h4 class="code-header">type <a href="#associatedtype.Unit" class="associatedtype">Unit</a> = <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.f64.html">f64</a></h4></section><section id="associatedconstant.SIDES-1" class="associatedconstant trait-impl"><a href="#associatedconstant.SIDES-1" class="anchor">§</a><h4 class="code-header">const <a href="#associatedconstant.SIDES" class="constant">SIDES</a>: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.usize.html">usize</a> = 4usize</h4></section></div></details></div></section></main>
         "##};
-        let expected = indoc! {"
-            ## Serde
 
-            Serde is a framework for _**ser**_ializing and _**de**_serializing Rust data structures efficiently and generically.
+        // The associated items are kept along with their docs, under the
+        // headings for their kinds.
+        let (markdown, _items) = convert_rustdoc_to_markdown(html.as_bytes()).unwrap();
+        let expected_sections = indoc! {r#"
+            ## Required Associated Types
 
-            The Serde ecosystem consists of data structures that know how to serialize and deserialize themselves along with data formats that know how to serialize and deserialize other things. Serde provides the layer by which these two groups interact with each other, allowing any supported data structure to be serialized and deserialized using any supported data format.
+            #### type Unit
 
-            See the Serde website https://serde.rs/ for additional documentation and usage examples.
+            The unit that the area is measured in.
 
-            ### Design
+            ## Required Associated Constants
 
-            Where many other languages rely on runtime reflection for serializing data, Serde is instead built on Rust’s powerful trait system. A data structure that knows how to serialize and deserialize itself is one that implements Serde’s `Serialize` and `Deserialize` traits (or uses Serde’s derive attribute to automatically generate implementations at compile time). This avoids any overhead of reflection or runtime type information. In fact in many situations the interaction between data structure and data format can be completely optimized away by the Rust compiler, leaving Serde serialization to perform the same speed as a handwritten serializer for the specific selection of data structure and data format.
-        "}
-        .trim();
+            #### const SIDES: usize
 
+            The number of sides.
+
+            ## Provided Associated Constants
+
+            #### const NAME: &'static str = "shape"
+        "#};
+        assert!(
+            markdown.contains(expected_sections.trim()),
+            "unexpected markdown:\n{markdown}"
+        );
+
+        // The associated items of the implementors aren't the trait's own.
         assert_eq!(
-            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
-            expected
-        )
+            convert_rustdoc_to_method_index(html.as_bytes()).unwrap(),
+            MethodIndex {
+                associated_types: vec!["type Unit".to_string()],
+                associated_consts: vec![
+                    "const SIDES: usize".to_string(),
+                    "const NAME: &'static str = \"shape\"".to_string(),
+                ],
+                ..Default::default()
+            }
+        );
     }
 
     #[test]
-    fn test_styled_text() {
-        let html = indoc! {r#"
-            <p>This text is <strong>bolded</strong>.</p>
-            <p>This text is <em>italicized</em>.</p>
-        "#};
-        let expected = indoc! {"
-            This text is **bolded**.
+    fn test_dyn_compatibility() {
+        let html = indoc! {r##"
+            <section id="main-content" class="content"><div class="main-heading"><h1>Trait <a href="../index.html">std</a>::<wbr><a href="index.html">io</a>::<wbr><a class="trait" href="#">Write</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1></div><pre class="rust item-decl"><code>pub trait Write {
+                <span class="comment">// Required methods</span>
+                fn <a href="#tymethod.write" class="fn">write</a>(&amp;mut self, buf: &amp;[<a class="primitive" href="../primitive.u8.html">u8</a>]) -&gt; <a class="type" href="type.Result.html">Result</a>&lt;<a class="primitive" href="../primitive.usize.html">usize</a>&gt;;
+                fn <a href="#tymethod.flush" class="fn">flush</a>(&amp;mut self) -&gt; <a class="type" href="type.Result.html">Result</a>&lt;<a class="primitive" href="../primitive.unit.html">()</a>&gt;;
 
-            This text is _italicized_.
-        "}
-        .trim();
+                <span class="comment">// Provided method</span>
+                fn <a href="#method.by_ref" class="fn">by_ref</a>(&amp;mut self) -&gt; &amp;mut Self
+                   <span class="where">where Self: <a class="trait" href="../marker/trait.Sized.html">Sized</a></span> { ... }
+            }</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>A trait for objects which are byte-oriented sinks.</p></div></details></section>
+        "##};
+        assert_eq!(
+            convert_rustdoc_to_dyn_compatibility(html.as_bytes()).unwrap(),
+            Some(DynCompatibility {
+                is_dyn_compatible: true,
+                reasons: Vec::new(),
+            })
+        );
+
+        let html = indoc! {r##"
+            <section id="main-content" class="content"><div class="main-heading"><h1>Trait <a href="index.html">shapes</a>::<wbr><a class="trait" href="#">Shape</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1></div><pre class="rust item-decl"><code>pub trait Shape {
+                const <a href="#associatedconstant.SIDES" class="constant">SIDES</a>: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.usize.html">usize</a>;
+
+                <span class="comment">// Required methods</span>
+                fn <a href="#tymethod.new" class="fn">new</a>(size: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.f64.html">f64</a>) -&gt; Self;
+                fn <a href="#tymethod.scale" class="fn">scale</a>&lt;T: <a class="trait" href="https://doc.rust-lang.org/nightly/core/convert/trait.Into.html">Into</a>&lt;<a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.f64.html">f64</a>&gt;&gt;(&amp;self, factor: T);
+                fn <a href="#tymethod.area" class="fn">area</a>(&amp;self) -&gt; <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.f64.html">f64</a>;
+            }</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>A two-dimensional shape.</p></div></details><h2 id="dyn-compatibility" class="section-header">Dyn Compatibility<a href="#dyn-compatibility" class="anchor">§</a></h2><div class="dyn-compatibility-info"><p>This trait is <b>not</b> <a href="https://doc.rust-lang.org/1.83.0/reference/items/traits.html#dyn-compatibility">dyn compatible</a>.</p><p><i>In older versions of Rust, dyn compatibility was called "object safety", so this trait is not object safe.</i></p></div></section>
+        "##};
+        assert_eq!(
+            convert_rustdoc_to_dyn_compatibility(html.as_bytes()).unwrap(),
+            Some(DynCompatibility {
+                is_dyn_compatible: false,
+                reasons: vec![
+                    "it has an associated constant `SIDES`".to_string(),
+                    "`new` has no `self` receiver".to_string(),
+                    "`new` returns `Self`".to_string(),
+                    "`scale` has generic type parameters".to_string(),
+                ],
+            })
+        );
 
+        // Only traits have a dyn compatibility.
+        let html = indoc! {r##"
+            <pre class="rust item-decl"><code>pub struct Empty;</code></pre><div class="docblock"><p>An empty struct.</p></div>
+        "##};
         assert_eq!(
-            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
-            expected
-        )
+            convert_rustdoc_to_dyn_compatibility(html.as_bytes()).unwrap(),
+            None
+        );
     }
 
     #[test]
-    fn test_rust_code_block() {
-        let html = indoc! {r#"
-            <pre class="rust rust-example-rendered"><code><span class="kw">use </span>axum::extract::{Path, Query, Json};
-            <span class="kw">use </span>std::collections::HashMap;
-
-            <span class="comment">// `Path` gives you the path parameters and deserializes them.
-            </span><span class="kw">async fn </span>path(Path(user_id): Path&lt;u32&gt;) {}
+    fn test_references() {
+        let html = indoc! {r##"
+            <main><div class="main-heading"><h1>Struct <a href="index.html">axum</a>::<wbr><a class="struct" href="#">Router</a></h1></div><pre class="rust item-decl"><code>pub struct Router&lt;S = <a class="struct" href="body/struct.Body.html" title="struct axum::body::Body">Body</a>&gt; { <span class="comment">/* private fields */</span> }</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>The router type for composing handlers and <a href="https://docs.rs/tower-service/0.3.2/tower_service/trait.Service.html" title="trait tower_service::Service"><code>Service</code></a>s, built on a <a href="routing/struct.MethodRouter.html" title="struct axum::routing::MethodRouter"><code>MethodRouter</code></a> for each <a href="body/struct.Body.html" title="struct axum::body::Body"><code>Body</code></a>.</p><p>See <a href="routing/fn.get.html" title="fn axum::routing::get"><code>get</code></a> and <a href="https://doc.rust-lang.org/nightly/std/primitive.str.html" title="primitive str"><code>str</code></a>.</p></div></details><h2 id="implementations" class="section-header">Implementations</h2><div id="implementations-list"><section id="impl-Router%3CS%3E" class="impl"><h3 class="code-header">impl&lt;S&gt; <a class="struct" href="struct.Router.html" title="struct axum::Router">Router</a>&lt;S&gt;<div class="where">where S: <a class="trait" href="https://doc.rust-lang.org/nightly/core/clone/trait.Clone.html" title="trait core::clone::Clone">Clone</a></div></h3></section></div></main>
+        "##};
 
-            <span class="comment">// `Query` gives you the query parameters and deserializes them.
-            </span><span class="kw">async fn </span>query(Query(params): Query&lt;HashMap&lt;String, String&gt;&gt;) {}
+        // Functions, primitives, and the links in the impls aren't included.
+        assert_eq!(
+            convert_rustdoc_to_references(html.as_bytes()).unwrap(),
+            vec![
+                "axum::body::Body".to_string(),
+                "tower_service::Service".to_string(),
+                "axum::routing::MethodRouter".to_string(),
+            ]
+        );
+    }
 
-            <span class="comment">// Buffer the request body and deserialize it as JSON into a
-            // `serde_json::Value`. `Json` supports any type that implements
-            // `serde::Deserialize`.
-            </span><span class="kw">async fn </span>json(Json(payload): Json&lt;serde_json::Value&gt;) {}</code></pre>
-        "#};
-        let expected = indoc! {"
-            ```rs
-            use axum::extract::{Path, Query, Json};
-            use std::collections::HashMap;
+    #[test]
+    fn test_reexports() {
+        let html = indoc! {r##"
+            <main><div class="main-heading"><h1>Module <a href="../index.html">tokio</a>::<wbr><a class="mod" href="#">fs</a></h1></div><h2 id="reexports" class="section-header">Re-exports<a href="#reexports" class="anchor">§</a></h2><ul class="item-table"><li><div class="item-name" id="reexport.File"><code>pub use self::file::<a class="struct" href="file/struct.File.html" title="struct tokio::fs::file::File">File</a>;</code></div></li><li><div class="item-name" id="reexport.Bytes"><code>pub use <a class="mod" href="https://docs.rs/bytes/1.6.0/bytes/index.html" title="mod bytes">bytes</a>::<a class="struct" href="https://docs.rs/bytes/1.6.0/bytes/struct.Bytes.html" title="struct bytes::Bytes">Bytes</a>;</code></div></li><li><div class="item-name" id="reexport.os"><code>pub use self::<a class="mod" href="os/index.html" title="mod tokio::fs::os">os</a>::*;</code></div></li></ul><h2 id="functions" class="section-header">Functions</h2><ul class="item-table"><li><div class="item-name"><a class="fn" href="fn.read.html" title="fn tokio::fs::read">read</a></div></li></ul></main>
+        "##};
 
-            // `Path` gives you the path parameters and deserializes them.
-            async fn path(Path(user_id): Path<u32>) {}
+        // Glob re-exports and the rest of the items aren't included.
+        let reexports = convert_rustdoc_to_reexports(html.as_bytes()).unwrap();
+        assert_eq!(
+            reexports,
+            vec![
+                RustdocReexport {
+                    name: "File".to_string(),
+                    kind: RustdocItemKind::Struct,
+                    canonical_path: "tokio::fs::file::File".to_string(),
+                    href: "file/struct.File.html".to_string(),
+                },
+                RustdocReexport {
+                    name: "Bytes".to_string(),
+                    kind: RustdocItemKind::Struct,
+                    canonical_path: "bytes::Bytes".to_string(),
+                    href: "https://docs.rs/bytes/1.6.0/bytes/struct.Bytes.html".to_string(),
+                },
+            ]
+        );
 
-            // `Query` gives you the query parameters and deserializes them.
-            async fn query(Query(params): Query<HashMap<String, String>>) {}
+        assert_eq!(
+            reexports[0].canonical_item("tokio"),
+            Some(RustdocItem {
+                kind: RustdocItemKind::Struct,
+                path: vec!["fs".into(), "file".into()],
+                name: "File".into(),
+            })
+        );
+        assert_eq!(
+            reexports[0].canonical_location(),
+            Some(RustdocPageLocation {
+                crate_name: "tokio".to_string(),
+                module_path: vec!["fs".to_string(), "file".to_string()],
+            })
+        );
+        assert_eq!(reexports[1].canonical_item("tokio"), None);
+    }
 
-            // Buffer the request body and deserialize it as JSON into a
-            // `serde_json::Value`. `Json` supports any type that implements
-            // `serde::Deserialize`.
-            async fn json(Json(payload): Json<serde_json::Value>) {}
-            ```
-        "}
-        .trim();
+    #[test]
+    fn test_all_items() {
+        let html = indoc! {r##"
+            <html><body><nav class="sidebar"><h3 id="sidebar-structs">Structs</h3><ul><li><a href="#structs">Structs</a></li></ul></nav><main><section id="main-content" class="content"><h1>List of all items</h1><h3 id="structs">Structs</h3><ul class="all-items"><li><a href="de/struct.Deserializer.html">de::<wbr>Deserializer</a></li><li><a href="struct.Map.html">Map</a></li></ul><h3 id="traits">Traits</h3><ul class="all-items"><li><a href="de/trait.Read.html">de::Read</a></li></ul><h3 id="macros">Macros</h3><ul class="all-items"></ul><h3 id="functions">Functions</h3><ul class="all-items"><li><a href="fn.from_str.html">from_str</a></li></ul></section></main></body></html>
+        "##};
 
+        // The sidebar and empty categories aren't included.
         assert_eq!(
-            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
-            expected
-        )
+            convert_rustdoc_to_all_items(html.as_bytes()).unwrap(),
+            vec![
+                (
+                    "Structs".to_string(),
+                    vec!["de::Deserializer".to_string(), "Map".to_string()]
+                ),
+                ("Traits".to_string(), vec!["de::Read".to_string()]),
+                ("Functions".to_string(), vec!["from_str".to_string()]),
+            ]
+        );
     }
 
     #[test]
-    fn test_toml_code_block() {
+    fn test_blockquotes_and_warnings() {
         let html = indoc! {r##"
-            <h2 id="required-dependencies"><a class="doc-anchor" href="#required-dependencies">§</a>Required dependencies</h2>
-            <p>To use axum there are a few dependencies you have to pull in as well:</p>
-            <div class="example-wrap"><pre class="language-toml"><code>[dependencies]
-            axum = &quot;&lt;latest-version&gt;&quot;
-            tokio = { version = &quot;&lt;latest-version&gt;&quot;, features = [&quot;full&quot;] }
-            tower = &quot;&lt;latest-version&gt;&quot;
-            </code></pre></div>
+            <div class="docblock">
+            <p>A guard that releases the lock when dropped.</p>
+            <div class="warning"><p>Holding the guard across an <code>.await</code> can deadlock.</p></div>
+            <div class="warning"><p><strong>Note:</strong> The guard is not <code>Send</code>.</p>
+            <p>Use an owned guard instead.</p></div>
+            <blockquote><p>Locks are <em>fair</em>.</p><blockquote><p>Mostly.</p></blockquote></blockquote>
+            <p>Acquire the lock with <code>lock</code>.</p>
+            </div>
         "##};
-        let expected = indoc! {r#"
-            ## Required dependencies
+        let expected = indoc! {"
+            A guard that releases the lock when dropped.
 
-            To use axum there are a few dependencies you have to pull in as well:
+            > **Warning:** Holding the guard across an `.await` can deadlock.
 
-            ```toml
-            [dependencies]
-            axum = "<latest-version>"
-            tokio = { version = "<latest-version>", features = ["full"] }
-            tower = "<latest-version>"
+            > **Note:** The guard is not `Send`.
+            >
+            > Use an owned guard instead.
 
-            ```
-        "#}
+            > Locks are _fair_.
+            >
+            > > Mostly.
+
+            Acquire the lock with `lock`.
+        "}
         .trim();
 
         assert_eq!(
@@ -531,92 +5989,137 @@ mod tests {
     }
 
     #[test]
-    fn test_item_table() {
+    fn test_safety_sections() {
         let html = indoc! {r##"
-            <h2 id="structs" class="section-header">Structs<a href="#structs" class="anchor">§</a></h2>
-            <ul class="item-table">
-            <li><div class="item-name"><a class="struct" href="struct.Error.html" title="struct axum::Error">Error</a></div><div class="desc docblock-short">Errors that can happen when using axum.</div></li>
-            <li><div class="item-name"><a class="struct" href="struct.Extension.html" title="struct axum::Extension">Extension</a></div><div class="desc docblock-short">Extractor and response for extensions.</div></li>
-            <li><div class="item-name"><a class="struct" href="struct.Form.html" title="struct axum::Form">Form</a><span class="stab portability" title="Available on crate feature `form` only"><code>form</code></span></div><div class="desc docblock-short">URL encoded extractor and response.</div></li>
-            <li><div class="item-name"><a class="struct" href="struct.Json.html" title="struct axum::Json">Json</a><span class="stab portability" title="Available on crate feature `json` only"><code>json</code></span></div><div class="desc docblock-short">JSON Extractor / Response.</div></li>
-            <li><div class="item-name"><a class="struct" href="struct.Router.html" title="struct axum::Router">Router</a></div><div class="desc docblock-short">The router type for composing handlers and services.</div></li></ul>
-            <h2 id="functions" class="section-header">Functions<a href="#functions" class="anchor">§</a></h2>
-            <ul class="item-table">
-            <li><div class="item-name"><a class="fn" href="fn.serve.html" title="fn axum::serve">serve</a><span class="stab portability" title="Available on crate feature `tokio` and (crate features `http1` or `http2`) only"><code>tokio</code> and (<code>http1</code> or <code>http2</code>)</span></div><div class="desc docblock-short">Serve the service with the supplied listener.</div></li>
+            <main><section id="main-content" class="content"><div class="main-heading"><h1>Function <a href="index.html">core</a>::<wbr><a class="fn" href="#">from_raw_parts</a></h1></div><pre class="rust item-decl"><code>pub const unsafe fn from_raw_parts&lt;'a, T&gt;(data: *const T, len: usize) -&gt; &amp;'a [T]</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>Forms a slice from a pointer and a length.</p>
+            <h2 id="safety"><a class="doc-anchor" href="#safety">§</a>Safety</h2>
+            <p>Behavior is undefined if any of the following conditions are violated:</p>
+            <ul>
+            <li><code>data</code> must be non-null and aligned.</li>
+            <li>The total size must be no larger than <code>isize::MAX</code>.</li>
             </ul>
+            <h3 id="caveat"><a class="doc-anchor" href="#caveat">§</a>Caveat</h3>
+            <p>The lifetime is inferred.</p>
+            <h2 id="examples"><a class="doc-anchor" href="#examples">§</a>Examples</h2>
+            <div class="example-wrap"><pre class="rust rust-example-rendered"><code><span class="kw">let </span>x = <span class="number">42</span>;</code></pre></div>
+            </div></details><details class="toggle method-toggle" open><summary><section id="method.read" class="method"><h4 class="code-header">pub unsafe fn <a href="#method.read" class="fn">read</a>(self) -&gt; T</h4></section></summary><div class="docblock"><p>Reads the value.</p>
+            <h5 id="safety-1"><a class="doc-anchor" href="#safety-1">§</a>Safety</h5>
+            <ul>
+            <li>The pointer must be valid for reads.</li>
+            </ul>
+            </div></details></section></main>
         "##};
-        let expected = indoc! {r#"
-            ## Structs
+        let expected = indoc! {"
+            # Function core::from_raw_parts<'a, T>
 
-            - `Error`: Errors that can happen when using axum.
-            - `Extension`: Extractor and response for extensions.
-            - `Form` [`form`]: URL encoded extractor and response.
-            - `Json` [`json`]: JSON Extractor / Response.
-            - `Router`: The router type for composing handlers and services.
+            ```rs
+            pub const unsafe fn from_raw_parts<'a, T>(data: *const T, len: usize) -> &'a [T]
+            ```
 
-            ## Functions
+            Forms a slice from a pointer and a length.
 
-            - `serve` [`tokio` and (`http1` or `http2`)]: Serve the service with the supplied listener.
-        "#}
+            > **Safety:** Behavior is undefined if any of the following conditions are violated:
+            > - `data` must be non-null and aligned.
+            > - The total size must be no larger than `isize::MAX`.
+            >
+            > ### Caveat
+            >
+            > The lifetime is inferred.
+
+            ## Examples
+
+            ```rust
+            let x = 42;
+            ```
+
+            #### pub unsafe fn read(self) -> T
+
+            Reads the value.
+
+            > **Safety:**
+            >
+            > - The pointer must be valid for reads.
+        "}
         .trim();
 
+        let (markdown, _items) = convert_rustdoc_to_markdown(html.as_bytes()).unwrap();
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn test_generic_params_in_title() {
+        let html = indoc! {r##"
+            <main><section id="main-content" class="content"><div class="main-heading"><h1>Function <a href="index.html">arrayvec</a>::<wbr><a class="fn" href="#">fill</a></h1></div><pre class="rust item-decl"><code>pub fn fill&lt;'a, T: <a class="trait" href="https://doc.rust-lang.org/nightly/core/clone/trait.Clone.html" title="trait core::clone::Clone">Clone</a> + 'a, F: <a class="trait" href="https://doc.rust-lang.org/nightly/core/ops/function/trait.Fn.html" title="trait core::ops::function::Fn">Fn</a>() -&gt; T, const N: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.usize.html">usize</a>&gt;(f: F) -&gt; [T; N]</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>Fills an array.</p></div></details></section></main>
+        "##};
+        let (markdown, _items) = convert_rustdoc_to_markdown(html.as_bytes()).unwrap();
         assert_eq!(
-            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
-            expected
-        )
+            markdown,
+            indoc! {"
+                # Function arrayvec::fill<'a, T: Clone + 'a, F: Fn() -> T, const N: usize>
+
+                ```rs
+                pub fn fill<'a, T: Clone + 'a, F: Fn() -> T, const N: usize>(f: F) -> [T; N]
+                ```
+
+                Fills an array."}
+        );
     }
 
     #[test]
-    fn test_table() {
+    fn test_normalizes_whitespace_and_ui_text() {
         let html = indoc! {r##"
-            <h2 id="feature-flags"><a class="doc-anchor" href="#feature-flags">§</a>Feature flags</h2>
-            <p>axum uses a set of <a href="https://doc.rust-lang.org/cargo/reference/features.html#the-features-section">feature flags</a> to reduce the amount of compiled and
-            optional dependencies.</p>
-            <p>The following optional features are available:</p>
-            <div><table><thead><tr><th>Name</th><th>Description</th><th>Default?</th></tr></thead><tbody>
-            <tr><td><code>http1</code></td><td>Enables hyper’s <code>http1</code> feature</td><td>Yes</td></tr>
-            <tr><td><code>http2</code></td><td>Enables hyper’s <code>http2</code> feature</td><td>No</td></tr>
-            <tr><td><code>json</code></td><td>Enables the <a href="struct.Json.html" title="struct axum::Json"><code>Json</code></a> type and some similar convenience functionality</td><td>Yes</td></tr>
-            <tr><td><code>macros</code></td><td>Enables optional utility macros</td><td>No</td></tr>
-            <tr><td><code>matched-path</code></td><td>Enables capturing of every request’s router path and the <a href="extract/struct.MatchedPath.html" title="struct axum::extract::MatchedPath"><code>MatchedPath</code></a> extractor</td><td>Yes</td></tr>
-            <tr><td><code>multipart</code></td><td>Enables parsing <code>multipart/form-data</code> requests with <a href="extract/struct.Multipart.html" title="struct axum::extract::Multipart"><code>Multipart</code></a></td><td>No</td></tr>
-            <tr><td><code>original-uri</code></td><td>Enables capturing of every request’s original URI and the <a href="extract/struct.OriginalUri.html" title="struct axum::extract::OriginalUri"><code>OriginalUri</code></a> extractor</td><td>Yes</td></tr>
-            <tr><td><code>tokio</code></td><td>Enables <code>tokio</code> as a dependency and <code>axum::serve</code>, <code>SSE</code> and <code>extract::connect_info</code> types.</td><td>Yes</td></tr>
-            <tr><td><code>tower-log</code></td><td>Enables <code>tower</code>’s <code>log</code> feature</td><td>Yes</td></tr>
-            <tr><td><code>tracing</code></td><td>Log rejections from built-in extractors</td><td>Yes</td></tr>
-            <tr><td><code>ws</code></td><td>Enables WebSockets support via <a href="extract/ws/index.html" title="mod axum::extract::ws"><code>extract::ws</code></a></td><td>No</td></tr>
-            <tr><td><code>form</code></td><td>Enables the <code>Form</code> extractor</td><td>Yes</td></tr>
-            <tr><td><code>query</code></td><td>Enables the <code>Query</code> extractor</td><td>Yes</td></tr>
-            </tbody></table>
+            <main><div class="width-limiter"><section id="main-content" class="content"><div class="main-heading"><h1>Struct <a href="index.html">tokio</a>::<wbr><a class="struct" href="#">Mutex</a><button id="copy-path" title="Copy item path to clipboard">Copy item path</button></h1><span class="sub-heading"><a class="src" href="../../src/tokio/sync/mutex.rs.html#129-133">Source</a> </span><button id="toggle-all-docs" title="Collapse sections" tabindex="-1"><span>Summary</span></button></div><pre class="rust item-decl"><code>pub struct Mutex&lt;T: ?<a class="trait" href="https://doc.rust-lang.org/nightly/core/marker/trait.Sized.html" title="trait core::marker::Sized">Sized</a>&gt; { <span class="comment">/* private fields */</span> }</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>An asynchronous <code>Mutex</code>-like type.</p>
+            <p>   </p>
+            <p>   </p>
+            <div class="example-wrap"><pre class="rust rust-example-rendered"><code><span class="kw">use </span>tokio::sync::Mutex;
+
+
+            <span class="kw">let </span>data = Mutex::new(<span class="number">0</span>);   </code></pre><a class="test-arrow" target="_blank" href="https://play.rust-lang.org/">Run</a></div>
+            <p>Locking it yields a guard.   </p></div></details><h2 id="implementations" class="section-header">Implementations<a href="#implementations" class="anchor">§</a></h2><div id="implementations-list"><details class="toggle implementors-toggle" open><summary><section id="impl-Mutex%3CT%3E" class="impl"><a class="src rightside" href="../../src/tokio/sync/mutex.rs.html#336-860">Source</a><a href="#impl-Mutex%3CT%3E" class="anchor">§</a><h3 class="code-header">impl&lt;T: ?<a class="trait" href="https://doc.rust-lang.org/nightly/core/marker/trait.Sized.html" title="trait core::marker::Sized">Sized</a>&gt; <a class="struct" href="struct.Mutex.html" title="struct tokio::sync::Mutex">Mutex</a>&lt;T&gt;</h3></section></summary><div class="impl-items"><details class="toggle method-toggle" open><summary><section id="method.new" class="method"><a class="src rightside" href="../../src/tokio/sync/mutex.rs.html#355-363">Source</a><h4 class="code-header">pub fn <a href="#method.new" class="fn">new</a>(t: T) -&gt; Self</h4></section></summary><div class="docblock"><p>Creates a new lock in an unlocked state ready for use.</p></div></details></div></details></div></section></div></main>
         "##};
-        let expected = indoc! {r#"
-            ## Feature flags
+        // The links to the source, the buttons, and the blank and trailing
+        // whitespace are left out, but not the blank lines in the example.
+        let expected_markdown = indoc! {"
+            # Struct tokio::Mutex<T: ?Sized>
 
-            axum uses a set of feature flags to reduce the amount of compiled and optional dependencies.
+            ```rs
+            pub struct Mutex<T: ?Sized> { /* private fields */ }
+            ```
 
-            The following optional features are available:
+            An asynchronous `Mutex`-like type.
 
-            | Name | Description | Default? |
-            | --- | --- | --- |
-            | `http1` | Enables hyper’s `http1` feature | Yes |
-            | `http2` | Enables hyper’s `http2` feature | No |
-            | `json` | Enables the `Json` type and some similar convenience functionality | Yes |
-            | `macros` | Enables optional utility macros | No |
-            | `matched-path` | Enables capturing of every request’s router path and the `MatchedPath` extractor | Yes |
-            | `multipart` | Enables parsing `multipart/form-data` requests with `Multipart` | No |
-            | `original-uri` | Enables capturing of every request’s original URI and the `OriginalUri` extractor | Yes |
-            | `tokio` | Enables `tokio` as a dependency and `axum::serve`, `SSE` and `extract::connect_info` types. | Yes |
-            | `tower-log` | Enables `tower`’s `log` feature | Yes |
-            | `tracing` | Log rejections from built-in extractors | Yes |
-            | `ws` | Enables WebSockets support via `extract::ws` | No |
-            | `form` | Enables the `Form` extractor | Yes |
-            | `query` | Enables the `Query` extractor | Yes |
-        "#}
+            ```rust
+            use tokio::sync::Mutex;
+
+
+            let data = Mutex::new(0);
+            ```
+
+            Locking it yields a guard.
+
+            ## Implementations
+
+            ### impl<T: ?Sized> Mutex<T>
+
+            #### pub fn new(t: T) -> Self
+
+            Creates a new lock in an unlocked state ready for use.
+        "}
         .trim();
 
+        let (markdown, _items) = convert_rustdoc_to_markdown(html.as_bytes()).unwrap();
+        assert_eq!(markdown, expected_markdown);
+        assert!(markdown
+            .lines()
+            .all(|line| line == line.trim_end() && !RUSTDOC_UI_TEXT.contains(&line)));
+    }
+
+    #[test]
+    fn test_ui_text_in_code_blocks_is_kept() {
+        let markdown = "Summary\n\n```text\nSummary\n\n\n\nSource\n```\n\n\n\nSource";
         assert_eq!(
-            convert_html_to_markdown(html.as_bytes(), &mut rustdoc_handlers()).unwrap(),
-            expected
-        )
+            normalize_markdown(markdown, |line| RUSTDOC_UI_TEXT.contains(&line)),
+            "```text\nSummary\n\n\n\nSource\n```"
+        );
     }
 }
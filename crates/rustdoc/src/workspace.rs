@@ -0,0 +1,675 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use collections::BTreeMap;
+use fs::Fs;
+use futures::StreamExt;
+use serde::Deserialize;
+
+use crate::crawler::CargoDocFeatures;
+
+#[derive(Deserialize)]
+struct Manifest {
+    package: Option<ManifestPackage>,
+    workspace: Option<ManifestWorkspace>,
+    features: Option<BTreeMap<String, Vec<String>>>,
+}
+
+#[derive(Deserialize)]
+struct ManifestPackage {
+    name: String,
+    metadata: Option<ManifestMetadata>,
+}
+
+/// The `[package.metadata]` table, in which `docs.rs` is a `docs` table with
+/// an `rs` table inside.
+#[derive(Deserialize)]
+struct ManifestMetadata {
+    docs: Option<ManifestMetadataDocs>,
+}
+
+#[derive(Deserialize)]
+struct ManifestMetadataDocs {
+    rs: Option<DocsRsMetadata>,
+}
+
+/// How `docs.rs` builds the docs for a crate, as configured in the
+/// `[package.metadata.docs.rs]` of its `Cargo.toml`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DocsRsMetadata {
+    /// The features to enable, in addition to the default ones.
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Whether to enable all of the crate's features.
+    #[serde(default)]
+    pub all_features: bool,
+    /// The target to show the docs for by default.
+    pub default_target: Option<String>,
+    /// The targets to build the docs for.
+    #[serde(default)]
+    pub targets: Vec<String>,
+}
+
+impl DocsRsMetadata {
+    /// Returns the target that `docs.rs` shows the docs for by default, if
+    /// the crate configures one.
+    ///
+    /// Without a `default-target`, the first of the `targets` is the default.
+    pub fn default_target(&self) -> Option<&str> {
+        self.default_target
+            .as_deref()
+            .or_else(|| self.targets.first().map(String::as_str))
+    }
+
+    /// Returns the features that `docs.rs` builds the docs with.
+    pub fn features(&self) -> CargoDocFeatures {
+        if self.all_features {
+            CargoDocFeatures::All
+        } else if !self.features.is_empty() {
+            CargoDocFeatures::Features(self.features.clone())
+        } else {
+            CargoDocFeatures::Default
+        }
+    }
+}
+
+/// Parses the `[package.metadata.docs.rs]` from the contents of a crate's
+/// `Cargo.toml`, if it has one.
+pub fn parse_docs_rs_metadata(manifest: &str) -> Result<Option<DocsRsMetadata>> {
+    let manifest: Manifest = toml::from_str(manifest).context("failed to parse Cargo.toml")?;
+    Ok(manifest
+        .package
+        .and_then(|package| package.metadata)
+        .and_then(|metadata| metadata.docs)
+        .and_then(|docs| docs.rs))
+}
+
+/// A feature of a crate, as declared in the `[features]` of its `Cargo.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateFeature {
+    pub name: String,
+    /// The features and optional dependencies that the feature enables, e.g.,
+    /// `std` or `dep:serde`.
+    pub enables: Vec<String>,
+}
+
+/// Parses the `[features]` from the contents of a crate's `Cargo.toml`, with
+/// the `default` feature first and the rest sorted by name.
+pub fn parse_crate_features(manifest: &str) -> Result<Vec<CrateFeature>> {
+    let manifest: Manifest = toml::from_str(manifest).context("failed to parse Cargo.toml")?;
+    let mut features = manifest
+        .features
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, enables)| CrateFeature { name, enables })
+        .collect::<Vec<_>>();
+    features.sort_by_key(|feature| feature.name != "default");
+    Ok(features)
+}
+
+#[derive(Deserialize)]
+struct ManifestWorkspace {
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+/// The members listed in the `[workspace]` of a `Cargo.toml`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WorkspaceMembers {
+    /// The name of the package at the root of the workspace, if any.
+    pub root_package: Option<String>,
+    /// The paths to the members, relative to the workspace root, which may
+    /// contain globs (e.g., `crates/*`).
+    pub members: Vec<String>,
+    /// The paths that are excluded from the members.
+    pub exclude: Vec<String>,
+}
+
+/// Parses the workspace members from the contents of a workspace's root
+/// `Cargo.toml`.
+pub fn parse_workspace_members(manifest: &str) -> Result<WorkspaceMembers> {
+    let manifest: Manifest = toml::from_str(manifest).context("failed to parse Cargo.toml")?;
+    let workspace = manifest.workspace.unwrap_or(ManifestWorkspace {
+        members: Vec::new(),
+        exclude: Vec::new(),
+    });
+
+    Ok(WorkspaceMembers {
+        root_package: manifest.package.map(|package| package.name),
+        members: workspace.members,
+        exclude: workspace.exclude,
+    })
+}
+
+/// A crate that is a member of a Cargo workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceMember {
+    /// The name of the package, as given in its `Cargo.toml`.
+    pub package_name: String,
+    /// The name of the crate, as it appears in `target/doc` (i.e., with `-`s
+    /// replaced by `_`s).
+    pub crate_name: String,
+    /// The directory containing the crate's `Cargo.toml`.
+    pub path: PathBuf,
+}
+
+/// The crates in a Cargo workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CargoWorkspace {
+    /// Whether the root `Cargo.toml` is a virtual manifest, i.e., one with a
+    /// `[workspace]` but no `[package]`.
+    pub is_virtual: bool,
+    /// The members of the workspace, including the root package, if any,
+    /// sorted by crate name.
+    pub members: Vec<WorkspaceMember>,
+}
+
+impl CargoWorkspace {
+    /// Returns the member that defines the given crate, which may be named
+    /// with either `-`s or `_`s.
+    pub fn member(&self, crate_name: &str) -> Option<&WorkspaceMember> {
+        let crate_name = crate_name.replace('-', "_");
+        self.members
+            .iter()
+            .find(|member| member.crate_name == crate_name)
+    }
+
+    /// Returns the names of the member crates, for listing them in messages.
+    pub fn member_names(&self) -> String {
+        self.members
+            .iter()
+            .map(|member| format!("`{}`", member.crate_name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Checks that the file at the given path is a Cargo manifest, i.e., a
+/// `Cargo.toml` with a `[package]` or a `[workspace]`, and returns the
+/// directory that it's in, which its crates are built and documented from.
+pub async fn manifest_root(fs: &dyn Fs, manifest_path: &Path) -> Result<PathBuf> {
+    if manifest_path.file_name() != Some("Cargo.toml".as_ref()) {
+        bail!("{manifest_path:?} is not a Cargo.toml");
+    }
+    if !fs.is_file(manifest_path).await {
+        bail!("no Cargo.toml found at {manifest_path:?}");
+    }
+
+    let manifest = fs
+        .load(manifest_path)
+        .await
+        .with_context(|| format!("failed to read {manifest_path:?}"))?;
+    let manifest: Manifest =
+        toml::from_str(&manifest).with_context(|| format!("failed to parse {manifest_path:?}"))?;
+    if manifest.package.is_none() && manifest.workspace.is_none() {
+        bail!("{manifest_path:?} has neither a [package] nor a [workspace]");
+    }
+
+    manifest_path
+        .parent()
+        .map(Path::to_path_buf)
+        .with_context(|| format!("{manifest_path:?} has no parent directory"))
+}
+
+/// Reads the Cargo workspace at the given root.
+pub async fn cargo_workspace(fs: &dyn Fs, workspace_root: &Path) -> Result<CargoWorkspace> {
+    let manifest = fs
+        .load(&workspace_root.join("Cargo.toml"))
+        .await
+        .context("failed to read Cargo.toml")?;
+    let workspace_members = parse_workspace_members(&manifest)?;
+
+    let mut members = BTreeMap::new();
+    if let Some(root_package) = workspace_members.root_package.as_ref() {
+        members.insert(
+            root_package.replace('-', "_"),
+            (root_package.clone(), workspace_root.to_path_buf()),
+        );
+    }
+
+    let excluded_paths = workspace_members
+        .exclude
+        .iter()
+        .map(|path| workspace_root.join(path))
+        .collect::<Vec<_>>();
+    for member in &workspace_members.members {
+        for member_path in expand_glob(fs, workspace_root, member).await? {
+            if excluded_paths.contains(&member_path) {
+                continue;
+            }
+
+            let manifest_path = member_path.join("Cargo.toml");
+            if !fs.is_file(&manifest_path).await {
+                continue;
+            }
+
+            let manifest = fs
+                .load(&manifest_path)
+                .await
+                .with_context(|| format!("failed to read {manifest_path:?}"))?;
+            let manifest: Manifest = toml::from_str(&manifest)
+                .with_context(|| format!("failed to parse {manifest_path:?}"))?;
+            if let Some(package) = manifest.package {
+                members
+                    .entry(package.name.replace('-', "_"))
+                    .or_insert((package.name, member_path));
+            }
+        }
+    }
+
+    Ok(CargoWorkspace {
+        is_virtual: workspace_members.root_package.is_none(),
+        members: members
+            .into_iter()
+            .map(|(crate_name, (package_name, path))| WorkspaceMember {
+                package_name,
+                crate_name,
+                path,
+            })
+            .collect(),
+    })
+}
+
+/// Returns the names of the crates in the Cargo workspace at the given root,
+/// as they appear in `target/doc` (i.e., with `-`s replaced by `_`s), sorted
+/// by name.
+pub async fn workspace_member_crates(fs: &dyn Fs, workspace_root: &Path) -> Result<Vec<String>> {
+    let workspace = cargo_workspace(fs, workspace_root).await?;
+    Ok(workspace
+        .members
+        .into_iter()
+        .map(|member| member.crate_name)
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct CargoConfig {
+    build: Option<CargoConfigBuild>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CargoConfigBuild {
+    target_dir: Option<PathBuf>,
+}
+
+/// Returns the directory that Cargo builds the workspace at the given root
+/// into, which is where `cargo doc` outputs the docs.
+///
+/// Like Cargo, this is the `CARGO_TARGET_DIR` environment variable if it is
+/// set, then the `build.target-dir` of the nearest `.cargo/config.toml`, and
+/// otherwise the `target` directory at the root.
+pub async fn cargo_target_dir(fs: &dyn Fs, workspace_root: &Path) -> PathBuf {
+    let env_target_dir = ["CARGO_TARGET_DIR", "CARGO_BUILD_TARGET_DIR"]
+        .into_iter()
+        .find_map(std::env::var_os)
+        .map(PathBuf::from);
+    let config_target_dir = match env_target_dir {
+        Some(_) => None,
+        None => config_target_dir(fs, workspace_root).await,
+    };
+    resolve_target_dir(workspace_root, env_target_dir, config_target_dir)
+}
+
+/// Returns the `build.target-dir` from the nearest Cargo config in the given
+/// directory or its ancestors, relative to the directory containing the
+/// config's `.cargo` directory.
+async fn config_target_dir(fs: &dyn Fs, workspace_root: &Path) -> Option<PathBuf> {
+    for dir in workspace_root.ancestors() {
+        // Cargo reads `config` over `config.toml` when both exist.
+        for file_name in ["config", "config.toml"] {
+            let config_path = dir.join(".cargo").join(file_name);
+            if !fs.is_file(&config_path).await {
+                continue;
+            }
+
+            let target_dir = fs
+                .load(&config_path)
+                .await
+                .and_then(|config| parse_config_target_dir(&config))
+                .with_context(|| format!("failed to read {config_path:?}"));
+            match target_dir {
+                Ok(Some(target_dir)) => return Some(dir.join(target_dir)),
+                Ok(None) => break,
+                Err(err) => {
+                    log::warn!("{err:#}");
+                    break;
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Parses the `build.target-dir` of a Cargo config.
+fn parse_config_target_dir(config: &str) -> Result<Option<PathBuf>> {
+    let config: CargoConfig = toml::from_str(config).context("failed to parse Cargo config")?;
+    Ok(config.build.and_then(|build| build.target_dir))
+}
+
+/// Returns the target directory for the workspace at the given root, from
+/// the one set in the environment or else the one set in a Cargo config.
+///
+/// A relative target directory in the environment is relative to the root,
+/// as that's where Cargo is usually run from.
+fn resolve_target_dir(
+    workspace_root: &Path,
+    env_target_dir: Option<PathBuf>,
+    config_target_dir: Option<PathBuf>,
+) -> PathBuf {
+    match env_target_dir.filter(|target_dir| !target_dir.as_os_str().is_empty()) {
+        Some(target_dir) => workspace_root.join(target_dir),
+        None => config_target_dir.unwrap_or_else(|| workspace_root.join("target")),
+    }
+}
+
+/// Returns the directories matching the given path relative to the root, in
+/// which each component may contain `*` and `?` wildcards.
+async fn expand_glob(fs: &dyn Fs, root: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![root.to_path_buf()];
+    for component in pattern.split('/').filter(|component| !component.is_empty()) {
+        if !component.contains(['*', '?']) {
+            for path in &mut paths {
+                path.push(component);
+            }
+            continue;
+        }
+
+        let mut matching_paths = Vec::new();
+        for path in paths {
+            if !fs.is_dir(&path).await {
+                continue;
+            }
+
+            let mut entries = fs.read_dir(&path).await?;
+            while let Some(entry) = entries.next().await {
+                let entry = entry?;
+                let is_match = entry
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| matches_wildcards(component, name));
+                if is_match && fs.is_dir(&entry).await {
+                    matching_paths.push(entry);
+                }
+            }
+        }
+        matching_paths.sort();
+        paths = matching_paths;
+    }
+
+    Ok(paths)
+}
+
+/// Returns whether the given name matches the pattern, where `*` matches any
+/// sequence of characters and `?` matches any single character.
+fn matches_wildcards(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    // `matches[j]` is whether the pattern so far matches the first `j`
+    // characters of the name.
+    let mut matches = vec![false; name.len() + 1];
+    matches[0] = true;
+    for pattern_char in pattern {
+        let mut next_matches = vec![false; name.len() + 1];
+        for j in 0..=name.len() {
+            next_matches[j] = match pattern_char {
+                '*' => matches[j] || (j > 0 && next_matches[j - 1]),
+                '?' => j > 0 && matches[j - 1],
+                char => j > 0 && matches[j - 1] && name[j - 1] == char,
+            };
+        }
+        matches = next_matches;
+    }
+
+    matches[name.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_workspace_members() {
+        let manifest = indoc! {r#"
+            [package]
+            name = "my-app"
+            version = "0.1.0"
+
+            [workspace]
+            members = ["crates/*", "tools/xtask"]
+            exclude = ["crates/scratch"]
+        "#};
+
+        assert_eq!(
+            parse_workspace_members(manifest).unwrap(),
+            WorkspaceMembers {
+                root_package: Some("my-app".to_string()),
+                members: vec!["crates/*".to_string(), "tools/xtask".to_string()],
+                exclude: vec!["crates/scratch".to_string()],
+            }
+        );
+        assert_eq!(
+            parse_workspace_members("[package]\nname = \"solo\"").unwrap(),
+            WorkspaceMembers {
+                root_package: Some("solo".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_docs_rs_metadata() {
+        let manifest = indoc! {r#"
+            [package]
+            name = "my-app"
+            version = "0.1.0"
+
+            [package.metadata.docs.rs]
+            features = ["serde", "tokio"]
+            targets = ["x86_64-pc-windows-msvc", "x86_64-unknown-linux-gnu"]
+            rustdoc-args = ["--cfg", "docsrs"]
+        "#};
+        let metadata = parse_docs_rs_metadata(manifest).unwrap().unwrap();
+        assert_eq!(metadata.default_target(), Some("x86_64-pc-windows-msvc"));
+        assert_eq!(
+            metadata.features(),
+            CargoDocFeatures::Features(vec!["serde".to_string(), "tokio".to_string()])
+        );
+
+        let manifest = indoc! {r#"
+            [package]
+            name = "my-app"
+
+            [package.metadata.docs.rs]
+            all-features = true
+            default-target = "aarch64-apple-darwin"
+            targets = ["x86_64-unknown-linux-gnu"]
+        "#};
+        let metadata = parse_docs_rs_metadata(manifest).unwrap().unwrap();
+        assert_eq!(metadata.default_target(), Some("aarch64-apple-darwin"));
+        assert_eq!(metadata.features(), CargoDocFeatures::All);
+
+        assert_eq!(
+            parse_docs_rs_metadata("[package]\nname = \"solo\"").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_crate_features() {
+        let manifest = indoc! {r#"
+            [package]
+            name = "my-app"
+
+            [features]
+            tracing = ["dep:tracing", "tokio/tracing"]
+            default = ["std"]
+            std = []
+        "#};
+        assert_eq!(
+            parse_crate_features(manifest).unwrap(),
+            vec![
+                CrateFeature {
+                    name: "default".to_string(),
+                    enables: vec!["std".to_string()],
+                },
+                CrateFeature {
+                    name: "std".to_string(),
+                    enables: Vec::new(),
+                },
+                CrateFeature {
+                    name: "tracing".to_string(),
+                    enables: vec!["dep:tracing".to_string(), "tokio/tracing".to_string()],
+                },
+            ]
+        );
+
+        assert_eq!(
+            parse_crate_features("[package]\nname = \"solo\"").unwrap(),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn test_workspace_member() {
+        let workspace = CargoWorkspace {
+            is_virtual: true,
+            members: vec![
+                WorkspaceMember {
+                    package_name: "my-app".to_string(),
+                    crate_name: "my_app".to_string(),
+                    path: PathBuf::from("/code/app/crates/my-app"),
+                },
+                WorkspaceMember {
+                    package_name: "xtask".to_string(),
+                    crate_name: "xtask".to_string(),
+                    path: PathBuf::from("/code/app/tools/xtask"),
+                },
+            ],
+        };
+
+        assert_eq!(
+            workspace
+                .member("my-app")
+                .map(|member| member.path.as_path()),
+            Some(Path::new("/code/app/crates/my-app"))
+        );
+        assert_eq!(
+            workspace
+                .member("my_app")
+                .map(|member| member.path.as_path()),
+            Some(Path::new("/code/app/crates/my-app"))
+        );
+        assert_eq!(workspace.member("serde"), None);
+        assert_eq!(workspace.member_names(), "`my_app`, `xtask`");
+    }
+
+    #[gpui::test]
+    async fn test_manifest_root(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(
+            "/vendor/serde/Cargo.toml",
+            b"[package]\nname = \"serde\"\nversion = \"1.0.200\"\n".to_vec(),
+        )
+        .await;
+        fs.insert_file("/vendor/empty/Cargo.toml", b"[features]\n".to_vec())
+            .await;
+        fs.insert_file("/vendor/serde/README.md", b"# Serde\n".to_vec())
+            .await;
+
+        assert_eq!(
+            manifest_root(fs.as_ref(), Path::new("/vendor/serde/Cargo.toml"))
+                .await
+                .unwrap(),
+            PathBuf::from("/vendor/serde")
+        );
+
+        let error = |path: &'static str| {
+            let fs = fs.clone();
+            async move {
+                manifest_root(fs.as_ref(), Path::new(path))
+                    .await
+                    .unwrap_err()
+                    .to_string()
+            }
+        };
+        assert!(error("/vendor/serde/README.md")
+            .await
+            .contains("is not a Cargo.toml"));
+        assert!(error("/vendor/tokio/Cargo.toml")
+            .await
+            .contains("no Cargo.toml found"));
+        assert!(error("/vendor/empty/Cargo.toml")
+            .await
+            .contains("neither a [package] nor a [workspace]"));
+    }
+
+    #[test]
+    fn test_matches_wildcards() {
+        assert!(matches_wildcards("*", "rustdoc"));
+        assert!(matches_wildcards("rust*", "rustdoc"));
+        assert!(matches_wildcards("*doc", "rustdoc"));
+        assert!(matches_wildcards("r?stdoc", "rustdoc"));
+        assert!(!matches_wildcards("rust*", "assistant"));
+        assert!(!matches_wildcards("r?doc", "rustdoc"));
+    }
+
+    #[test]
+    fn test_target_dir_from_env() {
+        let workspace_root = Path::new("/projects/zed");
+        assert_eq!(
+            resolve_target_dir(
+                workspace_root,
+                Some(PathBuf::from("/shared/target")),
+                Some(PathBuf::from("/projects/target"))
+            ),
+            Path::new("/shared/target")
+        );
+        assert_eq!(
+            resolve_target_dir(workspace_root, Some(PathBuf::from("build")), None),
+            Path::new("/projects/zed/build")
+        );
+        assert_eq!(
+            resolve_target_dir(workspace_root, Some(PathBuf::new()), None),
+            Path::new("/projects/zed/target")
+        );
+    }
+
+    #[test]
+    fn test_target_dir_from_config() {
+        let config = indoc! {r#"
+            [build]
+            target-dir = "../shared-target"
+            jobs = 4
+        "#};
+        assert_eq!(
+            parse_config_target_dir(config).unwrap(),
+            Some(PathBuf::from("../shared-target"))
+        );
+        assert_eq!(parse_config_target_dir("[build]\njobs = 4").unwrap(), None);
+        assert!(parse_config_target_dir("[build").is_err());
+
+        let workspace_root = Path::new("/projects/zed");
+        assert_eq!(
+            resolve_target_dir(
+                workspace_root,
+                None,
+                Some(PathBuf::from("/projects/shared-target"))
+            ),
+            Path::new("/projects/shared-target")
+        );
+        assert_eq!(
+            resolve_target_dir(workspace_root, None, None),
+            Path::new("/projects/zed/target")
+        );
+    }
+}
@@ -92,6 +92,9 @@ lazy_static::lazy_static! {
     } else {
         HOME.join(".cache").join("zed")
     };
+    pub static ref RUSTDOC_CACHE_DIR: PathBuf = TEMP_DIR.join("rustdoc");
+    pub static ref RUSTDOC_TARBALLS_DIR: PathBuf = TEMP_DIR.join("rustdoc-tarballs");
+    pub static ref RUSTDOC_INDEX_DIR: PathBuf = SUPPORT_DIR.join("rustdoc").join("index");
 }
 
 pub trait PathExt {